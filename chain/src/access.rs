@@ -1,3 +1,5 @@
+use stage::PipelineStageFlags;
+
 bitflags! {
     /// Bitmask specifying memory access types that will participate in a memory dependency.
     /// See Vulkan docs for detailed info:
@@ -54,20 +56,31 @@ bitflags! {
 
         /// Access type performed to write data via non-specific entities.
         const MEMORY_WRITE = 0x00010000;
+
+        /// Access type performed by the device to read acceleration structure in trace or build commands.
+        const ACCELERATION_STRUCTURE_READ_NV = 0x00200000;
+
+        /// Access type performed by the device to write acceleration structure in build commands.
+        const ACCELERATION_STRUCTURE_WRITE_NV = 0x00400000;
     }
 }
 
 impl AccessFlags {
     /// Check if flags contains at least on write flag.
     pub fn is_write(&self) -> bool {
-        self.intersects(
-            AccessFlags::SHADER_WRITE
+        !self.writes().is_empty()
+    }
+
+    /// Get only write flags.
+    pub fn writes(&self) -> AccessFlags {
+        *self
+            & (AccessFlags::SHADER_WRITE
                 | AccessFlags::COLOR_ATTACHMENT_WRITE
                 | AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE
                 | AccessFlags::TRANSFER_WRITE
                 | AccessFlags::HOST_WRITE
-                | AccessFlags::MEMORY_WRITE,
-        )
+                | AccessFlags::MEMORY_WRITE
+                | AccessFlags::ACCELERATION_STRUCTURE_WRITE_NV)
     }
 
     /// Check if flags contains at least on read flag.
@@ -83,7 +96,43 @@ impl AccessFlags {
                 | AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
                 | AccessFlags::TRANSFER_READ
                 | AccessFlags::HOST_READ
-                | AccessFlags::MEMORY_READ,
+                | AccessFlags::MEMORY_READ
+                | AccessFlags::ACCELERATION_STRUCTURE_READ_NV,
         )
     }
+
+    /// Get pipeline stages at which access types from flags are performed.
+    /// Only access types bound to specific stages are considered.
+    /// Shader accesses may happen at any shader stage so user must specify them explicitly.
+    ///
+    /// `INDIRECT_COMMAND_READ` is performed at `DRAW_INDIRECT` stage
+    /// which is commonly missed when indirect arguments are written by compute shader.
+    pub fn required_stages(&self) -> PipelineStageFlags {
+        let mut stages = PipelineStageFlags::empty();
+        if self.contains(AccessFlags::INDIRECT_COMMAND_READ) {
+            stages |= PipelineStageFlags::DRAW_INDIRECT;
+        }
+        if self.intersects(AccessFlags::INDEX_READ | AccessFlags::VERTEX_ATTRIBUTE_READ) {
+            stages |= PipelineStageFlags::VERTEX_INPUT;
+        }
+        if self.intersects(
+            AccessFlags::COLOR_ATTACHMENT_READ | AccessFlags::COLOR_ATTACHMENT_WRITE,
+        ) {
+            stages |= PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT;
+        }
+        if self.intersects(
+            AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                | AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+        ) {
+            stages |=
+                PipelineStageFlags::EARLY_FRAGMENT_TESTS | PipelineStageFlags::LATE_FRAGMENT_TESTS;
+        }
+        if self.intersects(AccessFlags::TRANSFER_READ | AccessFlags::TRANSFER_WRITE) {
+            stages |= PipelineStageFlags::TRANSFER;
+        }
+        if self.intersects(AccessFlags::HOST_READ | AccessFlags::HOST_WRITE) {
+            stages |= PipelineStageFlags::HOST;
+        }
+        stages
+    }
 }
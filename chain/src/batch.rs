@@ -0,0 +1,319 @@
+//! This module batches barriers of all resources by the point where they are recorded.
+//! Each batch is recorded with single pipeline barrier command.
+//!
+
+use std::ops::Range;
+
+use fnv::{FnvHashMap, FnvHashSet};
+
+use access::AccessFlags;
+use resource::{AccelerationStructure, Buffer, Image, Resource};
+use schedule::{Schedule, Submission, SubmissionId};
+use stage::PipelineStageFlags;
+use sync::{Barrier, Barriers, DependencyFlags, Guard, Placement, SyncData, SyncMethod};
+use Id;
+
+/// Point where barriers are recorded.
+/// Acquire side of a submission lies between it and previous submission of the queue.
+/// Release side lies between it and next submission of the queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SyncPoint {
+    /// Submission into which barriers are recorded.
+    pub submission: SubmissionId,
+
+    /// Side of the submission.
+    pub placement: Placement,
+}
+
+/// Global memory barrier recorded instead of buffer barriers with identical transitions.
+/// Single global barrier is cheaper than many buffer barriers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlobalBarrier {
+    /// Access and stages transition shared by replaced barriers.
+    pub states: Range<(AccessFlags, PipelineStageFlags)>,
+
+    /// Buffers which barriers are replaced, sorted by id.
+    pub buffers: Vec<Id>,
+}
+
+/// Barriers of all resources recorded at the same point.
+#[derive(Clone, Debug)]
+pub struct BarrierBatch {
+    /// Union of source stages and union of destination stages of all barriers.
+    pub stages: Range<PipelineStageFlags>,
+
+    /// Dependency flags shared by all barriers.
+    pub dependencies: DependencyFlags,
+
+    /// Global memory barriers sorted by the first buffer they replace.
+    pub global: Vec<GlobalBarrier>,
+
+    /// Buffer barriers not replaced by global ones, sorted by id.
+    pub buffers: Vec<(Id, Barrier<Buffer>)>,
+
+    /// Image barriers sorted by id.
+    /// Those are never replaced by global ones as they carry layouts.
+    pub images: Vec<(Id, Barrier<Image>)>,
+
+    /// Acceleration structure barriers sorted by id.
+    pub acceleration_structures: Vec<(Id, Barrier<AccelerationStructure>)>,
+}
+
+impl BarrierBatch {
+    fn new() -> Self {
+        BarrierBatch {
+            stages: PipelineStageFlags::empty()..PipelineStageFlags::empty(),
+            dependencies: DependencyFlags::all(),
+            global: Vec::new(),
+            buffers: Vec::new(),
+            images: Vec::new(),
+            acceleration_structures: Vec::new(),
+        }
+    }
+
+    /// Get number of barriers in the batch.
+    pub fn len(&self) -> usize {
+        self.global.len()
+            + self.buffers.len()
+            + self.images.len()
+            + self.acceleration_structures.len()
+    }
+
+    /// Check if batch has no barriers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn add<R: Resource>(&mut self, barriers: &Barriers<R>) -> Vec<(Id, Barrier<R>)> {
+        let mut batched: Vec<_> = barriers
+            .iter()
+            .filter(|&(_, barrier)| barrier.method == SyncMethod::Barrier)
+            .map(|(&id, barrier)| (id, barrier.clone()))
+            .collect();
+        batched.sort_by_key(|&(id, _)| id);
+        for &(_, ref barrier) in &batched {
+            self.stages.start |= barrier.states.start.2;
+            self.stages.end |= barrier.states.end.2;
+            self.dependencies &= barrier.dependencies;
+        }
+        batched
+    }
+}
+
+/// Batches of barriers by the point where they are recorded.
+/// This is what renderer records with pipeline barrier commands.
+#[derive(Clone, Debug)]
+pub struct BarrierSet {
+    batches: FnvHashMap<SyncPoint, BarrierBatch>,
+    internal: FnvHashMap<SubmissionId, Vec<BarrierBatch>>,
+}
+
+impl BarrierSet {
+    /// Get batch recorded at the point.
+    pub fn get(&self, point: SyncPoint) -> Option<&BarrierBatch> {
+        self.batches.get(&point)
+    }
+
+    /// Get batches the submission records between its own accesses of resources.
+    /// `internal(submission)[i]` is recorded between `i`-th and next access,
+    /// it is empty if no barriers are needed there.
+    pub fn internal(&self, submission: SubmissionId) -> &[BarrierBatch] {
+        self.internal
+            .get(&submission)
+            .map_or(&[][..], |batches| &batches[..])
+    }
+
+    /// Iterate over all points and their batches.
+    pub fn iter(&self) -> impl Iterator<Item = (&SyncPoint, &BarrierBatch)> {
+        self.batches.iter()
+    }
+
+    /// Get number of points with barriers.
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Check if there are no barriers at all.
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty() && self.internal.is_empty()
+    }
+}
+
+/// Batch barriers of synchronized schedule by the points where they are recorded.
+/// Barriers synchronized with events are not included
+/// as they are recorded by event commands instead of pipeline barriers.
+pub fn barrier_set<S, W>(schedule: &Schedule<SyncData<S, W>>) -> BarrierSet {
+    barrier_set_filtered(schedule, |_, _, _| true)
+}
+
+/// Barriers of synchronized schedule split by how often they must be recorded.
+#[derive(Clone, Debug)]
+pub struct StaticSplit {
+    /// Barriers recorded once at load.
+    /// Those are barriers of upload submissions
+    /// and barriers that synchronize with upload submissions only.
+    pub prologue: BarrierSet,
+
+    /// Barriers recorded every frame.
+    pub frame: BarrierSet,
+}
+
+impl<S, W> Schedule<SyncData<S, W>> {
+    /// Split barriers into static prologue and per-frame portion.
+    ///
+    /// Upload submissions run once at load, so do barriers synchronizing with them.
+    /// Static prologue can be recorded into long-lived command buffer submitted once
+    /// while the rest is recorded every frame, e.g. by `compile_frame`.
+    pub fn split_static(&self) -> StaticSplit {
+        let uploads = Uploads::new(self);
+        StaticSplit {
+            prologue: barrier_set_filtered(self, |submission, placement, id| {
+                uploads.is_static(submission, placement, id)
+            }),
+            frame: barrier_set_filtered(self, |submission, placement, id| {
+                !uploads.is_static(submission, placement, id)
+            }),
+        }
+    }
+}
+
+fn barrier_set_filtered<S, W, F>(schedule: &Schedule<SyncData<S, W>>, keep: F) -> BarrierSet
+where
+    F: Fn(&Submission<SyncData<S, W>>, Placement, Id) -> bool,
+{
+    let mut batches = FnvHashMap::default();
+    let mut internal = FnvHashMap::default();
+    for submission in schedule.ordered() {
+        let sync = submission.sync();
+        let sides = [
+            (Placement::Acquire, &sync.acquire),
+            (Placement::Release, &sync.release),
+        ];
+        for &(placement, guard) in &sides {
+            let batch = batch(&filter(guard, |id| keep(submission, placement, id)));
+            if !batch.is_empty() {
+                let point = SyncPoint {
+                    submission: submission.id(),
+                    placement,
+                };
+                batches.insert(point, batch);
+            }
+        }
+
+        // Internal barriers are recorded by the submission after some of its commands,
+        // so they are kept or dropped the same way as release ones.
+        let steps: Vec<_> = sync
+            .internal
+            .iter()
+            .map(|guard| {
+                batch(&filter(guard, |id| {
+                    keep(submission, Placement::Release, id)
+                }))
+            })
+            .collect();
+        if steps.iter().any(|batch| !batch.is_empty()) {
+            internal.insert(submission.id(), steps);
+        }
+    }
+    BarrierSet { batches, internal }
+}
+
+/// Resource links accessed only by upload submissions.
+pub(crate) struct Uploads(FnvHashSet<(Id, usize)>);
+
+impl Uploads {
+    pub(crate) fn new<S>(schedule: &Schedule<S>) -> Self {
+        let mut links: FnvHashMap<(Id, usize), bool> = FnvHashMap::default();
+        for submission in schedule.ordered() {
+            for (&id, &link) in submission.resource_links() {
+                *links.entry((id, link)).or_insert(true) &= submission.upload();
+            }
+        }
+        Uploads(
+            links
+                .into_iter()
+                .filter(|&(_, upload)| upload)
+                .map(|(link, _)| link)
+                .collect(),
+        )
+    }
+
+    /// Check if barrier of the resource recorded at the side of the submission is static.
+    /// Acquire side synchronizes with the previous link of the resource.
+    /// Release side synchronizes with the submission itself.
+    pub(crate) fn is_static<S>(
+        &self,
+        submission: &Submission<S>,
+        placement: Placement,
+        id: Id,
+    ) -> bool {
+        if submission.upload() {
+            return true;
+        }
+        match (placement, submission.resource_links().get(&id)) {
+            (Placement::Acquire, Some(&link)) if link > 0 => self.0.contains(&(id, link - 1)),
+            _ => false,
+        }
+    }
+}
+
+/// Copy barriers of resources that `keep` accepts.
+pub(crate) fn filter<F>(guard: &Guard, keep: F) -> Guard
+where
+    F: Fn(Id) -> bool,
+{
+    fn retain<R: Resource, F: Fn(Id) -> bool>(barriers: &Barriers<R>, keep: &F) -> Barriers<R> {
+        barriers
+            .iter()
+            .filter(|&(&id, _)| keep(id))
+            .map(|(&id, barrier)| (id, barrier.clone()))
+            .collect()
+    }
+
+    Guard {
+        buffers: retain(&guard.buffers, &keep),
+        images: retain(&guard.images, &keep),
+        acceleration_structures: retain(&guard.acceleration_structures, &keep),
+    }
+}
+
+pub(crate) fn batch(guard: &Guard) -> BarrierBatch {
+    let mut batch = BarrierBatch::new();
+    let buffers = batch.add(&guard.buffers);
+    let (global, buffers) = collapse(buffers);
+    batch.global = global;
+    batch.buffers = buffers;
+    batch.images = batch.add(&guard.images);
+    batch.acceleration_structures = batch.add(&guard.acceleration_structures);
+    batch
+}
+
+/// Replace buffer barriers with identical access and stages transitions
+/// by global memory barrier, one per transition shared by more than one buffer.
+/// Barriers that transfer ownership are kept as global barriers can't do that.
+fn collapse(
+    buffers: Vec<(Id, Barrier<Buffer>)>,
+) -> (Vec<GlobalBarrier>, Vec<(Id, Barrier<Buffer>)>) {
+    let mut global: Vec<GlobalBarrier> = Vec::new();
+    for &(id, ref barrier) in &buffers {
+        if barrier.queues.is_some() {
+            continue;
+        }
+        let states = (barrier.states.start.0, barrier.states.start.2)
+            ..(barrier.states.end.0, barrier.states.end.2);
+        match global.iter_mut().find(|global| global.states == states) {
+            Some(global) => global.buffers.push(id),
+            None => global.push(GlobalBarrier {
+                states,
+                buffers: vec![id],
+            }),
+        }
+    }
+    global.retain(|global| global.buffers.len() > 1);
+
+    let buffers = buffers
+        .into_iter()
+        .filter(|&(id, _)| !global.iter().any(|global| global.buffers.contains(&id)))
+        .collect();
+    (global, buffers)
+}
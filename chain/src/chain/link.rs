@@ -1,3 +1,6 @@
+use std::cmp::{max, min};
+use std::ops::Range;
+
 use access::AccessFlags;
 use node::State;
 use resource::Resource;
@@ -20,24 +23,85 @@ impl LinkQueueState {
             first: node.sid.index(),
             last: node.sid.index(),
             access: node.state.access,
-            stages: node.state.stages,
+            stages: node.state.stages | node.state.access.required_stages(),
         }
     }
 
     fn push<R: Resource>(&mut self, node: &LinkNode<R>) {
         assert!(self.last < node.sid.index());
         self.access |= node.state.access;
-        self.stages |= node.state.stages;
+        self.stages |= node.state.stages | node.state.access.required_stages();
         self.last = node.sid.index();
     }
 }
 
+/// Explicit synchronization scope.
+/// Stages and access that barrier analysis should use verbatim instead of inferred ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyncScope {
+    /// Pipeline stages of the scope.
+    pub stages: PipelineStageFlags,
+
+    /// Access types of the scope.
+    pub access: AccessFlags,
+}
+
+/// User-supplied synchronization scopes for the link.
+/// `src` is used when link is source of the dependency (toward next link).
+/// `dst` is used when link is destination of the dependency (from previous link).
+/// `None` means inferred scope is used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LinkOverride {
+    /// Scope used when link is source of the dependency.
+    pub src: Option<SyncScope>,
+
+    /// Scope used when link is destination of the dependency.
+    pub dst: Option<SyncScope>,
+}
+
+/// Override would under-synchronize relative to inferred scope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Fail)]
+pub enum OverrideError {
+    /// Scope has no stages.
+    #[fail(display = "Override scope has no stages")]
+    EmptyStages,
+
+    /// Scope stages has nothing in common with stages at which resource is accessed.
+    #[fail(
+        display = "Override stages {:?} don't cover any of accessed stages {:?}",
+        _0, _1
+    )]
+    DisjointStages(PipelineStageFlags, PipelineStageFlags),
+
+    /// Scope stages don't include stages required by accesses performed by the link.
+    #[fail(
+        display = "Override stages {:?} don't contain required stages {:?}",
+        _0, _1
+    )]
+    MissingStages(PipelineStageFlags, PipelineStageFlags),
+
+    /// Source scope doesn't include all write accesses performed by the link.
+    #[fail(display = "Override source access {:?} drops writes {:?}", _0, _1)]
+    DroppedWrites(AccessFlags, AccessFlags),
+
+    /// Destination scope has nothing in common with accesses performed by the link.
+    #[fail(
+        display = "Override destination access {:?} don't cover any of accesses {:?}",
+        _0, _1
+    )]
+    DisjointAccess(AccessFlags, AccessFlags),
+
+    /// There is no link with specified index.
+    #[fail(display = "Link {} doesn't exist", _0)]
+    NoLink(usize),
+}
+
 /// This type defines what states resource are at some point in time when commands recorded into
 /// corresponding submissions are executed.
 /// Those commands doesn't required to perform actions with all access types declared by the link.
 /// But performing actions with access types not declared by the link is prohibited.
 #[derive(Clone, Debug)]
-pub(crate) struct Link<R: Resource> {
+pub struct Link<R: Resource> {
     /// Combination of all accesses.
     access: AccessFlags,
 
@@ -58,6 +122,23 @@ pub(crate) struct Link<R: Resource> {
 
     /// Family of queues.
     family: FamilyId,
+
+    /// All submissions associated with the link in order of addition.
+    submissions: Vec<SubmissionId>,
+
+    /// States declared by submissions in the same order.
+    states: Vec<State<R>>,
+
+    /// User-supplied synchronization scopes.
+    sync_override: LinkOverride,
+
+    /// Render pass shared by all submissions.
+    /// `None` if submissions are not recorded in single render pass.
+    render_pass: Option<usize>,
+
+    /// Range covering bytes accessed by all submissions.
+    /// `None` if resource is accessed as a whole.
+    range: Option<Range<u64>>,
 }
 
 /// Node for the link.
@@ -68,6 +149,12 @@ pub(crate) struct LinkNode<R: Resource> {
 
     /// Resource state of the node.
     pub(crate) state: State<R>,
+
+    /// Render pass of the node.
+    pub(crate) render_pass: Option<usize>,
+
+    /// Byte range accessed by the node. `None` for the whole resource.
+    pub(crate) range: Option<Range<u64>>,
 }
 
 impl<R> Link<R>
@@ -85,10 +172,15 @@ where
             access: node.state.access,
             usage: node.state.usage,
             layout: node.state.layout,
-            stages: node.state.stages,
+            stages: node.state.stages | node.state.access.required_stages(),
             queue_count: 1,
             queues: Vec::new(),
             family: node.sid.family(),
+            submissions: vec![node.sid],
+            states: vec![node.state],
+            sync_override: LinkOverride::default(),
+            render_pass: node.render_pass,
+            range: node.range.clone(),
         };
         link.ensure_queue(node.sid.queue().index());
         link.queues[node.sid.queue().index()] = Some(LinkQueueState::new(&node));
@@ -107,12 +199,12 @@ where
 
     /// Get queue family that owns the resource at the link.
     /// All associated submissions must be from the same queue family.
-    pub(crate) fn family(&self) -> FamilyId {
+    pub fn family(&self) -> FamilyId {
         self.family
     }
 
     /// Get usage.
-    pub(crate) fn state(&self) -> State<R> {
+    pub fn state(&self) -> State<R> {
         State {
             access: self.access,
             layout: self.layout,
@@ -121,36 +213,158 @@ where
         }
     }
 
+    /// Get state to synchronize with when link is source of the dependency.
+    /// Equals to `state` unless overridden.
+    pub(crate) fn src_state(&self) -> State<R> {
+        self.scoped_state(self.sync_override.src)
+    }
+
+    /// Get state to synchronize with when link is destination of the dependency.
+    /// Equals to `state` unless overridden.
+    pub(crate) fn dst_state(&self) -> State<R> {
+        self.scoped_state(self.sync_override.dst)
+    }
+
+    fn scoped_state(&self, scope: Option<SyncScope>) -> State<R> {
+        let mut state = self.state();
+        if let Some(scope) = scope {
+            state.access = scope.access;
+            state.stages = scope.stages;
+        }
+        state
+    }
+
+    /// Set user-supplied synchronization scopes.
+    /// Fails if any scope would under-synchronize relative to inferred one.
+    pub(crate) fn set_sync_override(
+        &mut self,
+        sync_override: LinkOverride,
+    ) -> Result<(), OverrideError> {
+        if let Some(src) = sync_override.src {
+            self.check_stages(src.stages)?;
+            let writes = self.access.writes();
+            if !src.access.contains(writes) {
+                return Err(OverrideError::DroppedWrites(src.access, writes));
+            }
+        }
+        if let Some(dst) = sync_override.dst {
+            self.check_stages(dst.stages)?;
+            if !self.access.is_empty() && !dst.access.intersects(self.access) {
+                return Err(OverrideError::DisjointAccess(dst.access, self.access));
+            }
+        }
+        self.sync_override = sync_override;
+        Ok(())
+    }
+
+    fn check_stages(&self, stages: PipelineStageFlags) -> Result<(), OverrideError> {
+        let required = self.access.required_stages();
+        if stages.is_empty() {
+            Err(OverrideError::EmptyStages)
+        } else if stages.contains(PipelineStageFlags::ALL_COMMANDS) {
+            Ok(())
+        } else if !stages.contains(required) {
+            Err(OverrideError::MissingStages(stages, required))
+        } else if !stages.intersects(self.stages) {
+            Err(OverrideError::DisjointStages(stages, self.stages))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Get access.
-    pub(crate) fn access(&self) -> AccessFlags {
+    pub fn access(&self) -> AccessFlags {
         self.access
     }
 
     /// Get layout.
-    pub(crate) fn layout(&self) -> R::Layout {
+    pub fn layout(&self) -> R::Layout {
         self.layout
     }
 
     /// Get usage.
-    pub(crate) fn usage(&self) -> R::Usage {
+    pub fn usage(&self) -> R::Usage {
         self.usage
     }
 
-    // /// Get usage.
-    // pub(crate) fn stages(&self) -> PipelineStageFlags {
-    //     self.stages
-    // }
+    /// Get stages.
+    pub fn stages(&self) -> PipelineStageFlags {
+        self.stages
+    }
+
+    /// Get submissions associated with the link in order of addition.
+    pub fn submissions(&self) -> &[SubmissionId] {
+        &self.submissions
+    }
+
+    /// Get state in which the submission accesses the resource as declared by its node.
+    /// Returns `None` if the submission is not associated with the link.
+    pub fn submission_state(&self, sid: SubmissionId) -> Option<State<R>> {
+        self.submissions
+            .iter()
+            .position(|&other| other == sid)
+            .map(|index| self.states[index])
+    }
+
+    /// Get render pass shared by all submissions of the link.
+    pub fn render_pass(&self) -> Option<usize> {
+        self.render_pass
+    }
+
+    /// Get range covering bytes accessed at the link.
+    /// `None` if resource is accessed as a whole.
+    pub fn range(&self) -> Option<Range<u64>> {
+        self.range.clone()
+    }
+
+    /// Check if the link accesses no bytes accessed by `range`.
+    pub(crate) fn disjoint(&self, range: &Option<Range<u64>>) -> bool {
+        match (&self.range, range) {
+            (&Some(ref left), &Some(ref right)) => {
+                left.end <= right.start || right.end <= left.start
+            }
+            _ => false,
+        }
+    }
+
+    /// Check if resource is accessed by the host at the link.
+    /// Host-accessed link performs no device accesses.
+    pub fn host_access(&self) -> bool {
+        self.stages == PipelineStageFlags::HOST
+    }
+
+    /// Check if resource is both read and written at the link,
+    /// e.g. by compute shader updating storage buffer in place.
+    /// Such link never has more than one submission accessing overlapping bytes,
+    /// hazards between accesses of that submission are handled by the submission itself,
+    /// and barriers with neighbor links use the union of the accesses.
+    pub fn read_modify_write(&self) -> bool {
+        self.access.is_read() && self.access.is_write()
+    }
 
     /// Check if the link is associated with only one queue.
-    pub(crate) fn single_queue(&self) -> bool {
+    pub fn single_queue(&self) -> bool {
         self.queue_count == 1
     }
 
     /// Check if the given state and submission are compatible with link.
     /// If compatible then the submission can be associated with the link.
+    /// Writes are never compatible with other accesses, including other writes.
+    /// This way write-after-write hazard always gets memory barrier between links.
+    /// Read-modify-write access of single submission is a write as well,
+    /// so it starts its own link and gets barriers on both sides.
+    /// Reads are compatible only if performed in the same layout,
+    /// so that reads in another layout get transition barrier.
+    /// Any accesses of disjoint byte ranges are compatible,
+    /// as there is no hazard between them.
+    /// Subsequent access of the same submission always starts new link,
+    /// so each link holds single access of a submission.
     pub(crate) fn compatible(&self, node: &LinkNode<R>) -> bool {
         // If queue the same and states are compatible.
-        self.family == node.sid.family() && !(self.access | node.state.access).is_write()
+        self.family == node.sid.family()
+            && self.submissions.last() != Some(&node.sid)
+            && self.layout == node.state.layout
+            && (!(self.access | node.state.access).is_write() || self.disjoint(&node.range))
     }
 
     /// Insert submission with specified state to the link.
@@ -170,7 +384,13 @@ where
 
         self.access |= node.state.access;
         self.usage |= node.state.usage;
-        self.stages |= node.state.stages;
+        self.stages |= node.state.stages | node.state.access.required_stages();
+        self.submissions.push(node.sid);
+        self.states.push(node.state);
+        if self.render_pass != node.render_pass {
+            self.render_pass = None;
+        }
+        self.range = hull(&self.range, &node.range);
 
         match &mut self.queues[node.sid.queue().index()] {
             &mut Some(ref mut queue) => {
@@ -183,6 +403,77 @@ where
         }
     }
 
+    /// Shift indices of all submissions of the link
+    /// by `offset` of their queues.
+    pub(crate) fn shift_submissions<F>(&mut self, offset: F)
+    where
+        F: Fn(QueueId) -> usize,
+    {
+        for sid in &mut self.submissions {
+            *sid = SubmissionId::new(sid.queue(), sid.index() + offset(sid.queue()));
+        }
+        let family = self.family;
+        for (index, queue) in self.queues.iter_mut().enumerate() {
+            if let Some(ref mut queue) = *queue {
+                let offset = offset(QueueId::new(family, index));
+                queue.first += offset;
+                queue.last += offset;
+            }
+        }
+    }
+
+    /// Check if `next` link can be merged into this one.
+    /// Same rules as in `compatible` apply to whole links.
+    /// Links with overridden synchronization are never merged.
+    pub(crate) fn mergeable(&self, next: &Self) -> bool {
+        self.family == next.family
+            && !(self.access | next.access).is_write()
+            && self.layout == next.layout
+            && self.sync_override == LinkOverride::default()
+            && next.sync_override == LinkOverride::default()
+    }
+
+    /// Merge `next` link into this one.
+    /// All submissions of `next` must be submitted after submissions of this link.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `Link::mergeable` didn't return `true` for the links.
+    ///
+    pub(crate) fn merge(&mut self, next: Self) {
+        assert!(self.mergeable(&next));
+
+        self.access |= next.access;
+        self.usage |= next.usage;
+        self.stages |= next.stages;
+        self.submissions.extend(next.submissions);
+        self.states.extend(next.states);
+        if self.render_pass != next.render_pass {
+            self.render_pass = None;
+        }
+        self.range = hull(&self.range, &next.range);
+
+        for (index, state) in next.queues.into_iter().enumerate() {
+            let state = match state {
+                Some(state) => state,
+                None => continue,
+            };
+            self.ensure_queue(index);
+            match &mut self.queues[index] {
+                &mut Some(ref mut queue) => {
+                    assert!(queue.last < state.first);
+                    queue.access |= state.access;
+                    queue.stages |= state.stages;
+                    queue.last = state.last;
+                }
+                slot @ &mut None => {
+                    self.queue_count += 1;
+                    *slot = Some(state);
+                }
+            }
+        }
+    }
+
     // /// Check if ownership transfer is required between those links.
     // pub(crate) fn transfer_required(&self, next: &Self) -> bool {
     //     self.family != next.family
@@ -207,3 +498,22 @@ where
         self.queues[qid.index()].as_ref().unwrap()
     }
 }
+
+/// Get range covering both ranges. `None` stands for the whole resource.
+fn hull(left: &Option<Range<u64>>, right: &Option<Range<u64>>) -> Option<Range<u64>> {
+    match (left, right) {
+        (&Some(ref left), &Some(ref right)) => {
+            Some(min(left.start, right.start)..max(left.end, right.end))
+        }
+        _ => None,
+    }
+}
+
+/// Get range of bytes accessed by both ranges. `None` stands for the whole resource.
+pub(crate) fn overlap(left: Option<Range<u64>>, right: Option<Range<u64>>) -> Option<Range<u64>> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(max(left.start, right.start)..min(left.end, right.end)),
+        (Some(range), None) | (None, Some(range)) => Some(range),
+        (None, None) => None,
+    }
+}
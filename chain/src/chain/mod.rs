@@ -6,13 +6,18 @@
 
 mod link;
 
-use std::ops::BitOr;
 use fnv::FnvHashMap;
+use std::fmt::Write;
+use std::ops::BitOr;
 
-use resource::{Buffer, Image, Resource};
+use collect::Chains;
+use node::{PassMetadata, State};
+use resource::{AccelerationStructure, Buffer, Image, Resource};
+use schedule::SubmissionId;
 use Id;
 
-pub(crate) use self::link::{Link, LinkNode};
+pub(crate) use self::link::{overlap, LinkNode};
+pub use self::link::{Link, LinkOverride, OverrideError, SyncScope};
 
 /// This type corresponds to resource category.
 /// All resources from the same category must be accessed as permitted by links of the chain.
@@ -46,6 +51,11 @@ where
         self.links.last_mut().unwrap()
     }
 
+    /// Take links out of the chain.
+    pub(crate) fn into_links(self) -> Vec<Link<R>> {
+        self.links
+    }
+
     // /// Get link by index.
     // pub(crate) fn link(&self, index: usize) -> &Link<R> {
     //     &self.links[index]
@@ -80,6 +90,108 @@ where
     //     self.link_mut(index)
     // }
 
+    /// Get number of links in the chain.
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Check if chain has no links.
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Supply explicit synchronization scopes for the link.
+    /// Barrier analysis will use them verbatim instead of inferred ones.
+    ///
+    /// Returns error if link doesn't exist or scopes would under-synchronize.
+    pub fn override_link(
+        &mut self,
+        index: usize,
+        sync_override: LinkOverride,
+    ) -> Result<(), OverrideError> {
+        self.links
+            .get_mut(index)
+            .ok_or(OverrideError::NoLink(index))?
+            .set_sync_override(sync_override)
+    }
+
+    /// Iterate over links with their indices.
+    /// Yields full access timeline of the resource.
+    pub fn history(&self) -> impl Iterator<Item = (usize, &Link<R>)> {
+        self.links.iter().enumerate()
+    }
+
+    /// Get human-readable dump of the access timeline.
+    /// Each link is written on separate line.
+    pub fn describe(&self) -> String {
+        self.describe_with(&FnvHashMap::default())
+    }
+
+    /// Get human-readable dump of the access timeline
+    /// with submissions annotated by names of their passes from `metadata`.
+    pub fn describe_with(&self, metadata: &FnvHashMap<SubmissionId, PassMetadata>) -> String {
+        let mut dump = String::new();
+        writeln!(dump, "Chain of {} links", self.links.len()).unwrap();
+        for (index, link) in self.history() {
+            write!(
+                dump,
+                "  #{}: family {}, access {:?}, stages {:?}, usage {:?}, layout {:?}, submissions [",
+                index,
+                link.family().0,
+                link.access(),
+                link.stages(),
+                link.usage(),
+                link.layout(),
+            ).unwrap();
+            for (i, sid) in link.submissions().iter().enumerate() {
+                if i > 0 {
+                    dump.push_str(", ");
+                }
+                write!(
+                    dump,
+                    "{}:{}:{}",
+                    sid.family().0,
+                    sid.queue().index(),
+                    sid.index()
+                )
+                .unwrap();
+                if let Some(pass) = metadata.get(sid) {
+                    write!(dump, " ({})", pass.name).unwrap();
+                }
+            }
+            dump.push_str("]\n");
+        }
+        dump
+    }
+
+    /// Check if resource content is carried between frames.
+    /// This is the case when chain starts with reading the resource which is written later,
+    /// so frame reads what previous frame has written.
+    pub fn reads_previous_frame(&self) -> bool {
+        match self.links.first() {
+            Some(first) => {
+                first.access().is_read() && self.links.iter().any(|link| link.access().is_write())
+            }
+            None => false,
+        }
+    }
+
+    /// Get minimal number of physical resources required for the chain
+    /// to not serialize `frames_in_flight` frames.
+    /// Read-only resource can be shared by all frames.
+    /// Written resource requires a copy per frame in flight.
+    /// Resource that carries content between frames requires at least two copies
+    /// so that frame can read previous copy while writing its own.
+    pub fn required_copies(&self, frames_in_flight: usize) -> usize {
+        if !self.links.iter().any(|link| link.access().is_write()) {
+            1
+        } else if self.reads_previous_frame() {
+            frames_in_flight.max(2)
+        } else {
+            frames_in_flight.max(1)
+        }
+    }
+
     /// Get total usage.
     pub fn usage(&self) -> R::Usage {
         self.links
@@ -94,3 +206,71 @@ pub(crate) type BufferChains = FnvHashMap<Id, Chain<Buffer>>;
 
 /// Type alias for map of chains by id for images.
 pub(crate) type ImageChains = FnvHashMap<Id, Chain<Image>>;
+
+/// Type alias for map of chains by id for acceleration structures.
+pub(crate) type AccelStructChains = FnvHashMap<Id, Chain<AccelerationStructure>>;
+
+/// Access of the submission to the resource.
+#[derive(Clone, Copy, Debug)]
+pub struct LinkAccess<R: Resource> {
+    /// Index of the link in resource chain that includes the submission.
+    pub link: usize,
+
+    /// State in which the submission accesses the resource.
+    pub state: State<R>,
+}
+
+/// Resources accessed by single submission.
+#[derive(Clone, Debug)]
+pub struct SubmissionResources {
+    /// Buffers accessed by the submission.
+    pub buffers: FnvHashMap<Id, LinkAccess<Buffer>>,
+
+    /// Images accessed by the submission.
+    pub images: FnvHashMap<Id, LinkAccess<Image>>,
+
+    /// Acceleration structures accessed by the submission.
+    pub acceleration_structures: FnvHashMap<Id, LinkAccess<AccelerationStructure>>,
+}
+
+impl SubmissionResources {
+    fn new() -> Self {
+        SubmissionResources {
+            buffers: FnvHashMap::default(),
+            images: FnvHashMap::default(),
+            acceleration_structures: FnvHashMap::default(),
+        }
+    }
+}
+
+/// Build inverse view of the chains.
+/// For each submission lists resources it accesses together with the links they are accessed in
+/// and states declared by the submission.
+pub fn by_submission<S>(chains: &Chains<S>) -> FnvHashMap<SubmissionId, SubmissionResources> {
+    let mut result = FnvHashMap::default();
+    invert(&chains.buffers, &mut result, |resources| &mut resources.buffers);
+    invert(&chains.images, &mut result, |resources| &mut resources.images);
+    invert(&chains.acceleration_structures, &mut result, |resources| {
+        &mut resources.acceleration_structures
+    });
+    result
+}
+
+fn invert<R, F>(
+    chains: &FnvHashMap<Id, Chain<R>>,
+    result: &mut FnvHashMap<SubmissionId, SubmissionResources>,
+    accesses: F,
+) where
+    R: Resource,
+    F: Fn(&mut SubmissionResources) -> &mut FnvHashMap<Id, LinkAccess<R>>,
+{
+    for (&id, chain) in chains {
+        for (index, link) in chain.history() {
+            for &sid in link.submissions() {
+                let state = link.submission_state(sid).unwrap();
+                let resources = result.entry(sid).or_insert_with(SubmissionResources::new);
+                accesses(resources).insert(id, LinkAccess { link: index, state });
+            }
+        }
+    }
+}
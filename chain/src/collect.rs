@@ -1,12 +1,14 @@
 use std::cmp::max;
+use std::collections::HashMap;
 use std::hash::Hash;
 use std::ops::Range;
 
 use fnv::FnvHashMap;
+use rendy_resource::{image, DeviceFeatures};
 
-use chain::{BufferChains, Chain, ImageChains, Link, LinkNode};
-use node::{Node, State};
-use resource::{Buffer, Image, Resource};
+use chain::{AccelStructChains, BufferChains, Chain, ImageChains, Link, LinkNode};
+use node::{ImageAspects, Node, PassMetadata, State};
+use resource::{AccelerationStructure, Buffer, Image, Resource};
 
 use schedule::{FamilyId, Queue, QueueId, Schedule, Submission, SubmissionId};
 
@@ -27,6 +29,96 @@ pub struct Chains<S = Unsynchronized> {
 
     /// Contains all image chains.
     pub images: ImageChains,
+
+    /// Aspects tracked by image chains that track only part of an image.
+    /// Image chains absent here track whole images.
+    pub image_aspects: FnvHashMap<Id, ImageAspects>,
+
+    /// Contains all acceleration structure chains.
+    pub acceleration_structures: AccelStructChains,
+
+    /// Metadata of passes by their submissions.
+    /// Submissions of nodes without metadata are absent.
+    pub metadata: FnvHashMap<SubmissionId, PassMetadata>,
+
+    /// User-defined ordering edges between submissions.
+    pub(crate) orderings: Vec<Range<SubmissionId>>,
+
+    /// Layouts images are in before their first links.
+    pub(crate) initial_layouts: FnvHashMap<Id, image::Layout>,
+}
+
+impl<S> Chains<S> {
+    /// Require submission of node `after` to execute after submission of node `before`,
+    /// even though they access no resource in common.
+    /// Synchronization derivation honors the edge like a resource dependency,
+    /// but it carries execution dependency only, without any memory barrier.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if either node is not scheduled
+    /// or if node `after` is submitted before node `before`.
+    /// Listing `before` in `dependencies` of `after` guarantees the order.
+    pub fn add_ordering(&mut self, before: usize, after: usize) {
+        let ((before_order, before_sid), (after_order, after_sid)) = {
+            let find = |node: usize| {
+                self.schedule
+                    .ordered()
+                    .find(|submission| submission.node() == node)
+                    .map(|submission| (submission.submit_order(), submission.id()))
+                    .unwrap_or_else(|| panic!("Node {} is not scheduled", node))
+            };
+            (find(before), find(after))
+        };
+        assert!(
+            before_order < after_order,
+            "Node {} is submitted before node {} it must be ordered after",
+            after,
+            before
+        );
+        self.orderings.push(before_sid..after_sid);
+    }
+
+    /// Get user-defined ordering edges between submissions.
+    pub fn orderings(&self) -> &[Range<SubmissionId>] {
+        &self.orderings
+    }
+
+    /// Declare layout image is in before the first link of its chain.
+    /// `Undefined` discards content, so the first link must not read the image.
+    /// Any other layout, e.g. `Preinitialized` or layout of uploaded data, preserves content.
+    /// Images without declared layout are assumed to start in `Undefined` layout.
+    pub fn set_initial_layout(&mut self, id: Id, layout: image::Layout) {
+        self.initial_layouts.insert(id, layout);
+    }
+
+    /// Get layout image is in before the first link of its chain.
+    pub fn initial_layout(&self, id: Id) -> image::Layout {
+        self.initial_layouts
+            .get(&id)
+            .cloned()
+            .unwrap_or(image::Layout::Undefined)
+    }
+}
+
+/// Options for nodes scheduling.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CollectOptions {
+    /// Family of dedicated transfer queues.
+    /// Upload nodes are scheduled to this family instead of their own
+    /// so that uploads run in parallel with other work.
+    /// Synchronization derivation then inserts semaphores and ownership transfers.
+    pub upload_family: Option<FamilyId>,
+
+    /// Optional features of the device.
+    /// Image layouts not supported by the device are replaced with supported ones.
+    pub features: DeviceFeatures,
+
+    /// Visit resources of each node in order of their ids
+    /// instead of iteration order of node's maps which differs from run to run.
+    /// Makes schedule and synchronization derived from the same nodes reproducible,
+    /// e.g. for golden-output tests.
+    pub deterministic: bool,
 }
 
 #[derive(PartialEq, PartialOrd, Eq, Ord)]
@@ -37,22 +129,32 @@ struct Fitness {
 
 struct ResolvedNode {
     id: usize,
+    node: usize,
     family: FamilyId,
     queues: Range<usize>,
     rev_deps: Vec<usize>,
     buffers: Vec<(usize, State<Buffer>)>,
+    buffer_ranges: FnvHashMap<usize, Range<u64>>,
     images: Vec<(usize, State<Image>)>,
+    acceleration_structures: Vec<(usize, State<AccelerationStructure>)>,
+    render_pass: Option<usize>,
+    upload: bool,
 }
 
 impl Default for ResolvedNode {
     fn default() -> Self {
         ResolvedNode {
             id: 0,
+            node: 0,
             family: FamilyId(0),
             queues: 0..0,
             rev_deps: Vec::new(),
             buffers: Vec::new(),
+            buffer_ranges: FnvHashMap::default(),
             images: Vec::new(),
+            acceleration_structures: Vec::new(),
+            render_pass: None,
+            upload: false,
         }
     }
 }
@@ -62,6 +164,9 @@ struct ResolvedNodeSet {
     queues: Vec<QueueId>,
     buffers: Vec<Id>,
     images: Vec<Id>,
+    image_aspects: FnvHashMap<Id, ImageAspects>,
+    acceleration_structures: Vec<Id>,
+    metadata: FnvHashMap<usize, PassMetadata>,
 }
 
 struct ChainData<R: Resource> {
@@ -89,16 +194,26 @@ struct QueueData {
 /// Calculate automatic `Chains` for nodes.
 /// This function tries to find most appropriate schedule for nodes execution.
 pub fn collect<Q>(nodes: Vec<Node>, max_queues: Q) -> Chains
+where
+    Q: Fn(FamilyId) -> usize,
+{
+    collect_with(nodes, max_queues, CollectOptions::default())
+}
+
+/// Calculate automatic `Chains` for nodes using specified options.
+pub fn collect_with<Q>(nodes: Vec<Node>, max_queues: Q, options: CollectOptions) -> Chains
 where
     Q: Fn(FamilyId) -> usize,
 {
     // Resolve nodes into a form faster to work with.
-    let (nodes, mut unscheduled_nodes) = resolve_nodes(nodes, max_queues);
+    let (nodes, mut unscheduled_nodes) = resolve_nodes(nodes, max_queues, options);
     let mut ready_nodes = Vec::new();
 
     // Chains.
     let mut images: Vec<ChainData<Image>> = fill(nodes.images.len());
     let mut buffers: Vec<ChainData<Buffer>> = fill(nodes.buffers.len());
+    let mut acceleration_structures: Vec<ChainData<AccelerationStructure>> =
+        fill(nodes.acceleration_structures.len());
 
     // Schedule
     let mut schedule = Vec::with_capacity(nodes.queues.len());
@@ -131,6 +246,7 @@ where
                 &mut schedule,
                 &mut images,
                 &mut buffers,
+                &mut acceleration_structures,
             );
             scheduled += 1;
         }
@@ -141,7 +257,13 @@ where
                 .iter()
                 .enumerate()
                 .map(|(index, &node)| {
-                    let (fitness, qid) = fitness(node, &mut images, &mut buffers, &mut schedule);
+                    let (fitness, qid) = fitness(
+                        node,
+                        &mut images,
+                        &mut buffers,
+                        &mut acceleration_structures,
+                        &mut schedule,
+                    );
                     (fitness, qid, index)
                 }).min()
                 .unwrap();
@@ -158,16 +280,40 @@ where
                 &mut schedule,
                 &mut images,
                 &mut buffers,
+                &mut acceleration_structures,
             );
             scheduled += 1;
         }
     }
     assert_eq!(scheduled, nodes.nodes.len(), "Dependency loop found!");
 
+    let schedule = reify_schedule(schedule);
+    let metadata = if nodes.metadata.is_empty() {
+        FnvHashMap::default()
+    } else {
+        let mut pass_metadata = nodes.metadata;
+        schedule
+            .ordered()
+            .filter_map(|submission| {
+                pass_metadata
+                    .remove(&submission.node())
+                    .map(|pass| (submission.id(), pass))
+            })
+            .collect()
+    };
+
     Chains {
-        schedule: reify_schedule(schedule),
+        schedule,
         buffers: reify_chain(&nodes.buffers, buffers),
         images: reify_chain(&nodes.images, images),
+        image_aspects: nodes.image_aspects,
+        acceleration_structures: reify_chain(
+            &nodes.acceleration_structures,
+            acceleration_structures,
+        ),
+        metadata,
+        orderings: Vec::new(),
+        initial_layouts: FnvHashMap::default(),
     }
 }
 
@@ -203,7 +349,11 @@ impl<I: Hash + Eq + Copy> LookupBuilder<I> {
     }
 }
 
-fn resolve_nodes<Q>(nodes: Vec<Node>, max_queues: Q) -> (ResolvedNodeSet, Vec<usize>)
+fn resolve_nodes<Q>(
+    nodes: Vec<Node>,
+    max_queues: Q,
+    options: CollectOptions,
+) -> (ResolvedNodeSet, Vec<usize>)
 where
     Q: Fn(FamilyId) -> usize,
 {
@@ -215,10 +365,16 @@ where
     let mut queues = LookupBuilder::new();
     let mut buffers = LookupBuilder::new();
     let mut images = LookupBuilder::new();
+    let mut acceleration_structures = LookupBuilder::new();
+    let mut image_aspects: FnvHashMap<Id, ImageAspects> = FnvHashMap::default();
+    let mut metadata = FnvHashMap::default();
 
     let mut family_full = FnvHashMap::default();
     for node in nodes {
-        let family = node.family;
+        let family = match options.upload_family {
+            Some(upload_family) if node.upload => upload_family,
+            _ => node.family,
+        };
         if !family_full.contains_key(&family) {
             let count = max_queues(family);
             assert!(count > 0, "Cannot create a family with 0 max queues.");
@@ -244,31 +400,159 @@ where
 
         // We set these manually, and notably, do *not* touch rev_deps.
         reified_nodes[id].id = id;
-        reified_nodes[id].family = node.family;
+        reified_nodes[id].node = node.id;
+        reified_nodes[id].family = family;
+        reified_nodes[id].render_pass = node.render_pass;
+        reified_nodes[id].upload = node.upload;
+        if let Some(pass) = node.metadata {
+            metadata.insert(node.id, pass);
+        }
         reified_nodes[id].queues = family_full[&family].clone();
-        reified_nodes[id].buffers = node
-            .buffers
+        for (buffer, range) in &node.buffer_ranges {
+            assert!(
+                node.buffers.contains_key(&buffer),
+                "Range declared for buffer {:?} not accessed by node {}",
+                buffer,
+                node.id
+            );
+            assert!(
+                range.start < range.end,
+                "Empty range {:?} declared for buffer {:?}",
+                range,
+                buffer
+            );
+        }
+        let node_buffers = accesses(node.buffers, node.subsequent_buffers, node.id);
+        reified_nodes[id].buffers = entries(node_buffers, options.deterministic)
             .into_iter()
             .map(|(k, v)| (buffers.forward(k), v))
             .collect();
-        reified_nodes[id].images = node
-            .images
+        // Buffers are already forwarded, so order of ranges doesn't affect ids.
+        reified_nodes[id].buffer_ranges = node
+            .buffer_ranges
+            .into_iter()
+            .map(|(k, range)| (buffers.forward(k), range))
+            .collect();
+        for (&image, &aspects) in &node.image_aspects {
+            assert!(
+                node.images.contains_key(&image),
+                "Aspects declared for image {:?} not accessed by node {}",
+                image,
+                node.id
+            );
+            let old = image_aspects.entry(image).or_insert(aspects);
+            assert_eq!(
+                *old, aspects,
+                "Aspects of image {:?} are declared inconsistently",
+                image
+            );
+        }
+        let node_images = accesses(node.images, node.subsequent_images, node.id);
+        reified_nodes[id].images = entries(node_images, options.deterministic)
             .into_iter()
-            .map(|(k, v)| (images.forward(k), v))
+            .map(|(k, mut v)| {
+                v.layout = options.features.image_layout(v.layout);
+                (images.forward(k), v)
+            })
             .collect();
+        reified_nodes[id].acceleration_structures =
+            entries(node.acceleration_structures, options.deterministic)
+                .into_iter()
+                .map(|(k, v)| (acceleration_structures.forward(k), v))
+                .collect();
     }
 
+    check_image_aspects(&image_aspects, options.features);
+
     (
         ResolvedNodeSet {
             nodes: reified_nodes,
             queues: queues.backward,
             buffers: buffers.backward,
             images: images.backward,
+            image_aspects,
+            acceleration_structures: acceleration_structures.backward,
+            metadata,
         },
         unscheduled_nodes,
     )
 }
 
+/// Collect entries of the map, sorted by id if `sorted` is set.
+fn entries<V, I>(map: I, sorted: bool) -> Vec<(Id, V)>
+where
+    I: IntoIterator<Item = (Id, V)>,
+{
+    let mut entries: Vec<_> = map.into_iter().collect();
+    if sorted {
+        entries.sort_by_key(|&(id, _)| id);
+    }
+    entries
+}
+
+/// Get all accesses of the node to resources of one kind.
+/// Subsequent accesses of each resource follow its first access in order of execution.
+/// Sorting by id is stable and keeps that order.
+fn accesses<R: Resource>(
+    first: HashMap<Id, State<R>>,
+    subsequent: HashMap<Id, Vec<State<R>>>,
+    node: usize,
+) -> Vec<(Id, State<R>)> {
+    for id in subsequent.keys() {
+        assert!(
+            first.contains_key(id),
+            "Subsequent accesses declared for resource {:?} not accessed by node {}",
+            id,
+            node
+        );
+    }
+    first
+        .into_iter()
+        .chain(
+            subsequent
+                .into_iter()
+                .flat_map(|(id, states)| states.into_iter().map(move |state| (id, state))),
+        )
+        .collect()
+}
+
+/// Check that chains tracking aspects of the same image don't overlap.
+/// Without separate depth-stencil layouts both aspects are always in the same layout,
+/// so they can't be tracked by different chains.
+fn check_image_aspects(image_aspects: &FnvHashMap<Id, ImageAspects>, features: DeviceFeatures) {
+    let depth_stencil = image::ImageAspectFlags::DEPTH | image::ImageAspectFlags::STENCIL;
+    let mut by_image: FnvHashMap<Id, Vec<(Id, ImageAspects)>> = FnvHashMap::default();
+    for (&id, &aspects) in image_aspects {
+        by_image
+            .entry(aspects.image)
+            .or_insert_with(Vec::new)
+            .push((id, aspects));
+    }
+    for parts in by_image.values() {
+        for (index, &(left_id, left)) in parts.iter().enumerate() {
+            for &(right_id, right) in &parts[index + 1..] {
+                assert!(
+                    !left.aspects.intersects(right.aspects),
+                    "Aspects {:?} of image {:?} are tracked by both {:?} and {:?}",
+                    left.aspects & right.aspects,
+                    left.image,
+                    left_id,
+                    right_id
+                );
+                assert!(
+                    features.separate_depth_stencil_layouts
+                        || !(left.aspects | right.aspects).contains(depth_stencil),
+                    "Depth and stencil aspects of image {:?} are tracked by {:?} and {:?} \
+                     while device doesn't support separate depth-stencil layouts",
+                    left.image,
+                    left_id,
+                    right_id
+                );
+            }
+        }
+    }
+}
+
 fn reify_chain<R: Resource>(ids: &[Id], vec: Vec<ChainData<R>>) -> FnvHashMap<Id, Chain<R>> {
     let mut map = FnvHashMap::with_capacity_and_hasher(vec.len(), Default::default());
     for (chain, &i) in vec.into_iter().zip(ids) {
@@ -289,32 +573,34 @@ fn fitness(
     node: &ResolvedNode,
     images: &mut Vec<ChainData<Image>>,
     buffers: &mut Vec<ChainData<Buffer>>,
+    acceleration_structures: &mut Vec<ChainData<AccelerationStructure>>,
     schedule: &mut Vec<QueueData>,
 ) -> (Fitness, usize) {
     let mut transfers = 0;
     let mut wait_factor_from_chains = 0;
 
     // Collect minimal waits required and resource transfers count.
-    for &(id, _) in &node.buffers {
-        let chain = &buffers[id];
-        if chain
-            .current_family
-            .map_or(false, |family| family != node.family)
-        {
-            transfers += 1;
-        }
-        wait_factor_from_chains = max(wait_factor_from_chains, chain.last_link_wait_factor);
-    }
-    for &(id, _) in &node.images {
-        let chain = &images[id];
-        if chain
-            .current_family
-            .map_or(false, |family| family != node.family)
-        {
-            transfers += 1;
-        }
-        wait_factor_from_chains = max(wait_factor_from_chains, chain.last_link_wait_factor);
-    }
+    chains_fitness(
+        node.family,
+        &node.buffers,
+        buffers,
+        &mut transfers,
+        &mut wait_factor_from_chains,
+    );
+    chains_fitness(
+        node.family,
+        &node.images,
+        images,
+        &mut transfers,
+        &mut wait_factor_from_chains,
+    );
+    chains_fitness(
+        node.family,
+        &node.acceleration_structures,
+        acceleration_structures,
+        &mut transfers,
+        &mut wait_factor_from_chains,
+    );
 
     // Find best queue for node.
     let (wait_factor_from_queue, queue) = node
@@ -332,6 +618,25 @@ fn fitness(
     )
 }
 
+fn chains_fitness<R: Resource>(
+    family: FamilyId,
+    states: &[(usize, State<R>)],
+    chains: &[ChainData<R>],
+    transfers: &mut usize,
+    wait_factor: &mut usize,
+) {
+    for &(id, _) in states {
+        let chain = &chains[id];
+        if chain
+            .current_family
+            .map_or(false, |current| current != family)
+        {
+            *transfers += 1;
+        }
+        *wait_factor = max(*wait_factor, chain.last_link_wait_factor);
+    }
+}
+
 fn schedule_node<'a>(
     ready_nodes: &mut Vec<&'a ResolvedNode>,
     unscheduled_nodes: &mut Vec<usize>,
@@ -343,13 +648,15 @@ fn schedule_node<'a>(
     schedule: &mut Vec<QueueData>,
     images: &mut Vec<ChainData<Image>>,
     buffers: &mut Vec<ChainData<Buffer>>,
+    acceleration_structures: &mut Vec<ChainData<AccelerationStructure>>,
 ) {
     let ref mut queue_data = schedule[queue];
     queue_data.wait_factor = max(queue_data.wait_factor, wait_factor + 1);
     let sid = queue_data
         .queue
-        .add_submission(node.id, wait_factor, submitted, Unsynchronized);
+        .add_submission(node.node, wait_factor, submitted, Unsynchronized);
     let submission = queue_data.queue.submission_mut(sid).unwrap();
+    submission.set_upload(node.upload);
 
     for &(id, state) in &node.buffers {
         add_to_chain(
@@ -359,6 +666,8 @@ fn schedule_node<'a>(
             sid,
             submission,
             state,
+            node.render_pass,
+            node.buffer_ranges.get(&id).cloned(),
         );
     }
     for &(id, state) in &node.images {
@@ -369,6 +678,20 @@ fn schedule_node<'a>(
             sid,
             submission,
             state,
+            node.render_pass,
+            None,
+        );
+    }
+    for &(id, state) in &node.acceleration_structures {
+        add_to_chain(
+            nodes.acceleration_structures[id],
+            node.family,
+            &mut acceleration_structures[id],
+            sid,
+            submission,
+            state,
+            node.render_pass,
+            None,
         );
     }
 
@@ -387,10 +710,17 @@ fn add_to_chain<R, S>(
     sid: SubmissionId,
     submission: &mut Submission<S>,
     state: State<R>,
+    render_pass: Option<usize>,
+    range: Option<Range<u64>>,
 ) where
     R: Resource,
 {
-    let node = LinkNode { sid, state };
+    let node = LinkNode {
+        sid,
+        state,
+        render_pass,
+        range,
+    };
 
     chain_data.current_family = Some(family);
     chain_data.current_link_wait_factor = max(
@@ -0,0 +1,256 @@
+//! This module compiles synchronized schedule into flat list of commands
+//! that can be replayed each frame without repeating the analysis.
+//!
+
+use std::ops::Range;
+
+use batch::{batch, filter, GlobalBarrier, Uploads};
+use resource::{AccelerationStructure, Buffer, Image};
+use schedule::{Schedule, SubmissionId};
+use stage::PipelineStageFlags;
+use sync::{Barrier, DependencyFlags, Guard, Placement, SyncData};
+use Id;
+
+/// Pipeline barrier command replayed by `CompiledChain`.
+#[derive(Clone, Debug)]
+pub struct BarrierRecord<'a> {
+    /// Union of source stages and union of destination stages of all barriers.
+    pub stages: Range<PipelineStageFlags>,
+
+    /// Dependency flags shared by all barriers.
+    pub dependencies: DependencyFlags,
+
+    /// Global memory barriers replacing buffer barriers with identical transitions.
+    pub global: &'a [GlobalBarrier],
+
+    /// Buffer barriers not replaced by global ones, sorted by id.
+    pub buffers: &'a [(Id, Barrier<Buffer>)],
+
+    /// Image barriers sorted by id.
+    pub images: &'a [(Id, Barrier<Image>)],
+
+    /// Acceleration structure barriers sorted by id.
+    pub acceleration_structures: &'a [(Id, Barrier<AccelerationStructure>)],
+}
+
+/// Target of `CompiledChain` replay.
+/// Usually a command buffer wrapper that finds raw resources by ids in `T`.
+pub trait Replay<T: ?Sized> {
+    /// Record pipeline barrier.
+    fn pipeline_barrier(&mut self, record: BarrierRecord<'_>, resources: &T);
+
+    /// Record commands of the node submitted with `submission`.
+    /// `internal` barriers must be recorded between node's own accesses of resources.
+    fn submission(
+        &mut self,
+        submission: SubmissionId,
+        node: usize,
+        internal: InternalBarriers<'_>,
+        resources: &T,
+    );
+}
+
+/// Pipeline barriers a submission records between its own accesses of resources.
+#[derive(Clone, Copy, Debug)]
+pub struct InternalBarriers<'a> {
+    compiled: &'a CompiledChain,
+    batches: &'a [Option<usize>],
+}
+
+impl<'a> InternalBarriers<'a> {
+    /// Get number of steps between consecutive accesses.
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Check if submission records no barriers between its accesses.
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// Get barrier recorded between `step`-th and next access of resources.
+    /// Returns `None` if no barriers are needed there.
+    pub fn get(&self, step: usize) -> Option<BarrierRecord<'a>> {
+        let compiled = self.compiled;
+        self.batches
+            .get(step)
+            .and_then(|&batch| batch)
+            .map(|index| compiled.barrier_record(index))
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Barrier(usize),
+    /// Submission, its node and range of its internal barriers.
+    Submission(SubmissionId, usize, Range<usize>),
+}
+
+#[derive(Clone, Debug)]
+struct BatchRanges {
+    stages: Range<PipelineStageFlags>,
+    dependencies: DependencyFlags,
+    global: Range<usize>,
+    buffers: Range<usize>,
+    images: Range<usize>,
+    acceleration_structures: Range<usize>,
+}
+
+/// Submissions and pipeline barriers of synchronized schedule in the order they must be recorded.
+/// Barrier descriptors of all batches are stored in flat arrays.
+/// Barriers synchronized with events are not included, same as in `BarrierSet`.
+#[derive(Clone, Debug)]
+pub struct CompiledChain {
+    ops: Vec<Op>,
+    batches: Vec<BatchRanges>,
+    global: Vec<GlobalBarrier>,
+    buffers: Vec<(Id, Barrier<Buffer>)>,
+    images: Vec<(Id, Barrier<Image>)>,
+    acceleration_structures: Vec<(Id, Barrier<AccelerationStructure>)>,
+    /// Internal barriers of all submissions, `None` for steps with no barriers.
+    internal: Vec<Option<usize>>,
+}
+
+impl CompiledChain {
+    /// Get number of submissions.
+    pub fn submissions(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| match **op {
+                Op::Submission(..) => true,
+                Op::Barrier(_) => false,
+            })
+            .count()
+    }
+
+    /// Get number of pipeline barrier commands.
+    pub fn pipeline_barriers(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Replay submissions and barriers into `target` in the order they must be recorded.
+    pub fn record<E, T>(&self, target: &mut E, resources: &T)
+    where
+        E: Replay<T>,
+        T: ?Sized,
+    {
+        for op in &self.ops {
+            match *op {
+                Op::Barrier(index) => {
+                    target.pipeline_barrier(self.barrier_record(index), resources)
+                }
+                Op::Submission(sid, node, ref internal) => {
+                    let internal = InternalBarriers {
+                        compiled: self,
+                        batches: &self.internal[internal.clone()],
+                    };
+                    target.submission(sid, node, internal, resources)
+                }
+            }
+        }
+    }
+
+    fn barrier_record(&self, index: usize) -> BarrierRecord<'_> {
+        let ref ranges = self.batches[index];
+        BarrierRecord {
+            stages: ranges.stages.clone(),
+            dependencies: ranges.dependencies,
+            global: &self.global[ranges.global.clone()],
+            buffers: &self.buffers[ranges.buffers.clone()],
+            images: &self.images[ranges.images.clone()],
+            acceleration_structures: &self.acceleration_structures
+                [ranges.acceleration_structures.clone()],
+        }
+    }
+
+    fn add_batch(&mut self, guard: &Guard) {
+        if let Some(index) = self.push_batch(guard) {
+            self.ops.push(Op::Barrier(index));
+        }
+    }
+
+    /// Store barriers of the batch in flat arrays.
+    /// Returns index of the batch unless it is empty.
+    fn push_batch(&mut self, guard: &Guard) -> Option<usize> {
+        let batch = batch(guard);
+        if batch.is_empty() {
+            return None;
+        }
+        let ranges = BatchRanges {
+            stages: batch.stages,
+            dependencies: batch.dependencies,
+            global: extend(&mut self.global, batch.global),
+            buffers: extend(&mut self.buffers, batch.buffers),
+            images: extend(&mut self.images, batch.images),
+            acceleration_structures: extend(
+                &mut self.acceleration_structures,
+                batch.acceleration_structures,
+            ),
+        };
+        self.batches.push(ranges);
+        Some(self.batches.len() - 1)
+    }
+}
+
+/// Compile synchronized schedule for replay.
+pub fn compile<S, W>(schedule: &Schedule<SyncData<S, W>>) -> CompiledChain {
+    compile_with(schedule, None)
+}
+
+/// Compile per-frame portion of synchronized schedule for replay.
+/// Upload submissions and barriers of static prologue are skipped
+/// as they are recorded once, see `Schedule::split_static`.
+pub fn compile_frame<S, W>(schedule: &Schedule<SyncData<S, W>>) -> CompiledChain {
+    compile_with(schedule, Some(&Uploads::new(schedule)))
+}
+
+fn compile_with<S, W>(
+    schedule: &Schedule<SyncData<S, W>>,
+    uploads: Option<&Uploads>,
+) -> CompiledChain {
+    let mut compiled = CompiledChain {
+        ops: Vec::new(),
+        batches: Vec::new(),
+        global: Vec::new(),
+        buffers: Vec::new(),
+        images: Vec::new(),
+        acceleration_structures: Vec::new(),
+        internal: Vec::new(),
+    };
+
+    for submission in schedule.ordered() {
+        if uploads.is_some() && submission.upload() {
+            continue;
+        }
+        let dynamic = |placement, id| {
+            uploads.map_or(true, |uploads| {
+                !uploads.is_static(submission, placement, id)
+            })
+        };
+        let sync = submission.sync();
+        compiled.add_batch(&filter(&sync.acquire, |id| dynamic(Placement::Acquire, id)));
+
+        // Internal barriers are recorded after some commands of the submission,
+        // so they are static or dynamic the same way as release ones.
+        let start = compiled.internal.len();
+        for guard in &sync.internal {
+            let batch = compiled.push_batch(&filter(guard, |id| dynamic(Placement::Release, id)));
+            compiled.internal.push(batch);
+        }
+        if compiled.internal[start..].iter().all(Option::is_none) {
+            compiled.internal.truncate(start);
+        }
+        let internal = start..compiled.internal.len();
+        compiled
+            .ops
+            .push(Op::Submission(submission.id(), submission.node(), internal));
+        compiled.add_batch(&filter(&sync.release, |id| dynamic(Placement::Release, id)));
+    }
+    compiled
+}
+
+fn extend<T>(flat: &mut Vec<T>, items: Vec<T>) -> Range<usize> {
+    let start = flat.len();
+    flat.extend(items);
+    start..flat.len()
+}
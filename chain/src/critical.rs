@@ -0,0 +1,87 @@
+//! This module finds the longest chain of dependent submissions.
+//! Submissions on it can't overlap, so its duration bounds frame time from below.
+//!
+
+use fnv::FnvHashMap;
+
+use chain::Chain;
+use collect::Chains;
+use resource::Resource;
+use schedule::SubmissionId;
+use Id;
+
+/// Find critical path through the submissions of `Chains`.
+/// Submission depends on submissions of the previous link of each chain it is part of
+/// and on submissions ordered before it with `Chains::add_ordering`.
+///
+/// `duration` provides weight of each submission,
+/// e.g. GPU time measured with timestamp queries in previous frames.
+/// Use `|_| 1` to find path with the most submissions.
+///
+/// Returns submissions on the path in execution order.
+pub fn critical_path<S, F>(chains: &Chains<S>, duration: F) -> Vec<SubmissionId>
+where
+    F: Fn(SubmissionId) -> u64,
+{
+    let mut predecessors: FnvHashMap<SubmissionId, Vec<SubmissionId>> = FnvHashMap::default();
+    add_chain_edges(&chains.buffers, &mut predecessors);
+    add_chain_edges(&chains.images, &mut predecessors);
+    add_chain_edges(&chains.acceleration_structures, &mut predecessors);
+    for ordering in &chains.orderings {
+        predecessors
+            .entry(ordering.end)
+            .or_insert_with(Vec::new)
+            .push(ordering.start);
+    }
+
+    // Dependencies are always submitted first, so submit order is topological.
+    let mut longest: FnvHashMap<SubmissionId, (u64, Option<SubmissionId>)> = FnvHashMap::default();
+    let mut end = None;
+    for submission in chains.schedule.ordered() {
+        let sid = submission.id();
+        let (before, predecessor) = predecessors
+            .get(&sid)
+            .into_iter()
+            .flat_map(|predecessors| predecessors.iter())
+            .map(|&predecessor| (longest[&predecessor].0, Some(predecessor)))
+            .max()
+            .unwrap_or((0, None));
+        let total = before + duration(sid);
+        longest.insert(sid, (total, predecessor));
+        if end.map_or(true, |(best, _)| total > best) {
+            end = Some((total, sid));
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut next = end.map(|(_, sid)| sid);
+    while let Some(sid) = next {
+        path.push(sid);
+        next = longest[&sid].1;
+    }
+    path.reverse();
+    path
+}
+
+pub(crate) fn add_chain_edges<'a, R, I>(
+    chains: I,
+    predecessors: &mut FnvHashMap<SubmissionId, Vec<SubmissionId>>,
+) where
+    R: Resource,
+    I: IntoIterator<Item = (&'a Id, &'a Chain<R>)>,
+{
+    for (_, chain) in chains {
+        for pair in chain.links().windows(2) {
+            for &sid in pair[1].submissions() {
+                // Consecutive accesses of the same submission are not an edge.
+                predecessors.entry(sid).or_insert_with(Vec::new).extend(
+                    pair[0]
+                        .submissions()
+                        .iter()
+                        .cloned()
+                        .filter(|&predecessor| predecessor != sid),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! This module finds writes whose results are never read within the graph
+//! and submissions that can be culled because they produce only such writes.
+//!
+
+use fnv::FnvHashSet;
+
+use chain::Chain;
+use collect::Chains;
+use resource::Resource;
+use schedule::SubmissionId;
+use Id;
+
+/// Write link whose result is never read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeadWrite {
+    /// Id of the resource.
+    pub resource: Id,
+
+    /// Index of the write link in the chain.
+    pub link: usize,
+}
+
+/// Dead writes of all resources and submissions that can be culled.
+#[derive(Clone, Debug)]
+pub struct DeadWrites {
+    /// Dead writes of buffers sorted by resource and link.
+    pub buffers: Vec<DeadWrite>,
+
+    /// Dead writes of images sorted by resource and link.
+    pub images: Vec<DeadWrite>,
+
+    /// Dead writes of acceleration structures sorted by resource and link.
+    pub acceleration_structures: Vec<DeadWrite>,
+
+    /// Submissions that write something and all their writes are dead.
+    /// Culling them all leaves results of the graph intact.
+    pub submissions: Vec<SubmissionId>,
+}
+
+impl DeadWrites {
+    /// Check if nothing can be culled.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+            && self.images.is_empty()
+            && self.acceleration_structures.is_empty()
+    }
+}
+
+/// Find writes never read within the graph.
+/// Reads by submissions that are culled themselves don't count,
+/// so whole branches of the graph producing nothing are culled.
+///
+/// `outputs` lists resources consumed outside of the graph, e.g. presented images.
+/// Their last writes are never dead.
+/// So are last writes of resources which content is read by the next frame.
+///
+/// Submissions that only read resources are never culled
+/// as they may have effects not tracked by chains.
+pub fn dead_writes<S>(chains: &Chains<S>, outputs: &[Id]) -> DeadWrites {
+    let mut culled = FnvHashSet::default();
+    loop {
+        let mut writers = FnvHashSet::default();
+        let mut live = FnvHashSet::default();
+        let mut dead = DeadWrites {
+            buffers: find_dead(&chains.buffers, outputs, &culled, &mut writers, &mut live),
+            images: find_dead(&chains.images, outputs, &culled, &mut writers, &mut live),
+            acceleration_structures: find_dead(
+                &chains.acceleration_structures,
+                outputs,
+                &culled,
+                &mut writers,
+                &mut live,
+            ),
+            submissions: writers.difference(&live).cloned().collect(),
+        };
+
+        if dead.submissions.len() == culled.len() {
+            dead.submissions.sort();
+            return dead;
+        }
+        culled = dead.submissions.into_iter().collect();
+    }
+}
+
+fn find_dead<'a, R, I>(
+    chains: I,
+    outputs: &[Id],
+    culled: &FnvHashSet<SubmissionId>,
+    writers: &mut FnvHashSet<SubmissionId>,
+    live: &mut FnvHashSet<SubmissionId>,
+) -> Vec<DeadWrite>
+where
+    R: Resource,
+    I: IntoIterator<Item = (&'a Id, &'a Chain<R>)>,
+{
+    let mut dead = Vec::new();
+    for (&id, chain) in chains {
+        let links = chain.links();
+        let last_write = links.iter().rposition(|link| link.access().is_write());
+        let keep_last = outputs.contains(&id) || chain.reads_previous_frame();
+
+        for (index, link) in links.iter().enumerate() {
+            if !link.access().is_write() {
+                continue;
+            }
+            writers.extend(link.submissions().iter().cloned());
+
+            let read = (keep_last && Some(index) == last_write)
+                || links[index + 1..]
+                    .iter()
+                    .scan(false, |overwritten, next| {
+                        // Links after next write read its result instead.
+                        if *overwritten {
+                            return None;
+                        }
+                        *overwritten = next.access().is_write();
+                        Some(next)
+                    })
+                    .any(|next| {
+                        next.access().is_read()
+                            && next.submissions().iter().any(|sid| !culled.contains(sid))
+                    });
+
+            if read {
+                live.extend(link.submissions().iter().cloned());
+            } else {
+                dead.push(DeadWrite {
+                    resource: id,
+                    link: index,
+                });
+            }
+        }
+    }
+    dead.sort();
+    dead
+}
@@ -0,0 +1,162 @@
+//! This module finds submissions that must signal fences for the host to track the frame.
+//!
+
+use fnv::{FnvHashMap, FnvHashSet};
+
+use chain::Chain;
+use collect::Chains;
+use critical::add_chain_edges;
+use resource::Resource;
+use schedule::{Schedule, SubmissionId};
+use sync::latest;
+use Id;
+
+/// Submissions that need fences attached.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameFences {
+    /// Submissions host waits for before accessing resources they write,
+    /// e.g. before reading back results.
+    /// Sorted by submission id.
+    pub readbacks: Vec<SubmissionId>,
+
+    /// Submissions host waits for to know that the whole frame is complete.
+    /// Completion of every other submission is implied by completion of these and `readbacks`.
+    /// Sorted by submission id.
+    pub completion: Vec<SubmissionId>,
+}
+
+impl FrameFences {
+    /// Get all submissions that need fences, sorted by submission id.
+    pub fn submissions(&self) -> Vec<SubmissionId> {
+        let mut submissions = self
+            .readbacks
+            .iter()
+            .chain(&self.completion)
+            .cloned()
+            .collect::<Vec<_>>();
+        submissions.sort();
+        submissions
+    }
+
+    /// Get number of fences required.
+    pub fn len(&self) -> usize {
+        self.readbacks.len() + self.completion.len()
+    }
+
+    /// Check if no fences required.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Find minimal set of submissions that need fences attached.
+///
+/// Each submission read back by the host through host-accessed link needs a fence.
+/// Frame is complete when the last submission of each queue is complete.
+/// Fence signaled by a submission implies completion of earlier submissions of its queue
+/// and, through semaphores, completion of submissions it depends on.
+/// So the last submission of a queue needs a fence only if completion of no other fenced
+/// submission implies its completion.
+pub fn frame_fences<S>(chains: &Chains<S>) -> FrameFences {
+    let mut readbacks = FnvHashSet::default();
+    add_readbacks(&chains.buffers, &chains.schedule, &mut readbacks);
+    add_readbacks(&chains.images, &chains.schedule, &mut readbacks);
+    add_readbacks(
+        &chains.acceleration_structures,
+        &chains.schedule,
+        &mut readbacks,
+    );
+
+    let mut predecessors: FnvHashMap<SubmissionId, Vec<SubmissionId>> = FnvHashMap::default();
+    add_chain_edges(&chains.buffers, &mut predecessors);
+    add_chain_edges(&chains.images, &mut predecessors);
+    add_chain_edges(&chains.acceleration_structures, &mut predecessors);
+    for ordering in &chains.orderings {
+        predecessors
+            .entry(ordering.end)
+            .or_insert_with(Vec::new)
+            .push(ordering.start);
+    }
+
+    let mut lasts = Vec::new();
+    for queue in chains.schedule.iter().flat_map(|family| family.iter()) {
+        let mut previous = None;
+        for submission in queue.iter() {
+            let sid = submission.id();
+            if let Some(previous) = previous {
+                predecessors
+                    .entry(sid)
+                    .or_insert_with(Vec::new)
+                    .push(previous);
+            }
+            previous = Some(sid);
+        }
+        lasts.extend(previous);
+    }
+
+    let fenced = readbacks
+        .iter()
+        .chain(&lasts)
+        .cloned()
+        .collect::<FnvHashSet<_>>();
+    let mut completion = lasts
+        .iter()
+        .cloned()
+        .filter(|last| !readbacks.contains(last))
+        .filter(|&last| {
+            !fenced
+                .iter()
+                .any(|&other| other != last && reaches(&predecessors, other, last))
+        })
+        .collect::<Vec<_>>();
+    completion.sort();
+
+    let mut readbacks = readbacks.into_iter().collect::<Vec<_>>();
+    readbacks.sort();
+
+    FrameFences {
+        readbacks,
+        completion,
+    }
+}
+
+/// Check if completion of `from` implies completion of `to`.
+fn reaches(
+    predecessors: &FnvHashMap<SubmissionId, Vec<SubmissionId>>,
+    from: SubmissionId,
+    to: SubmissionId,
+) -> bool {
+    let mut visited = FnvHashSet::default();
+    let mut stack = vec![from];
+    while let Some(sid) = stack.pop() {
+        if sid == to {
+            return true;
+        }
+        if visited.insert(sid) {
+            stack.extend(
+                predecessors
+                    .get(&sid)
+                    .into_iter()
+                    .flat_map(|p| p.iter().cloned()),
+            );
+        }
+    }
+    false
+}
+
+fn add_readbacks<'a, R, I, S>(
+    chains: I,
+    schedule: &Schedule<S>,
+    readbacks: &mut FnvHashSet<SubmissionId>,
+) where
+    R: Resource,
+    I: IntoIterator<Item = (&'a Id, &'a Chain<R>)>,
+{
+    for (_, chain) in chains {
+        for pair in chain.links().windows(2) {
+            if pair[1].host_access() {
+                readbacks.insert(latest(&pair[0], schedule));
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! This module maps history resources, carried from frame to frame,
+//! to rings of physical resources and finds dependencies between frames.
+//!
+
+use access::AccessFlags;
+use chain::Chain;
+use collect::Chains;
+use resource::Resource;
+use schedule::SubmissionId;
+use stage::PipelineStageFlags;
+use Id;
+
+/// Logical resource which content written by a frame is read by the next frame,
+/// e.g. accumulation image of temporal anti-aliasing.
+/// It is backed by a ring of `versions` physical resources.
+///
+/// Nodes access version written by the frame with `current` id
+/// and version written by the previous frame with `previous` id.
+/// Those are different physical resources, so they are tracked by separate chains
+/// and reading previous version never waits for writing current one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct History {
+    /// Id of current version.
+    pub current: Id,
+
+    /// Id of previous version.
+    pub previous: Id,
+
+    /// Number of physical resources in the ring.
+    pub versions: usize,
+}
+
+/// Physical resources of history resource selected for a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HistoryFrame {
+    /// Index of physical resource written as current version.
+    pub current: usize,
+
+    /// Index of physical resource read as previous version.
+    /// `None` for the first frame which has no history yet.
+    pub previous: Option<usize>,
+
+    /// Frame that must be complete before physical resource of current version is overwritten.
+    /// This is the last frame that read it as previous version.
+    /// `None` if no frame did.
+    pub reuse_after: Option<u64>,
+}
+
+impl History {
+    /// Create history resource.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `versions` is less than 2
+    /// as frame can't read previous version while writing its own.
+    pub fn new(current: Id, previous: Id, versions: usize) -> Self {
+        assert!(
+            versions >= 2,
+            "History resource requires at least two versions"
+        );
+        History {
+            current,
+            previous,
+            versions,
+        }
+    }
+
+    /// Select physical resources for frame with given index.
+    /// Ring is rotated by one resource each frame.
+    pub fn frame(&self, frame: u64) -> HistoryFrame {
+        let versions = self.versions as u64;
+        HistoryFrame {
+            current: (frame % versions) as usize,
+            previous: frame.checked_sub(1).map(|prev| (prev % versions) as usize),
+            // Frame `0` reads nothing.
+            reuse_after: match frame.checked_sub(versions - 1) {
+                Some(0) | None => None,
+                reader => reader,
+            },
+        }
+    }
+}
+
+/// History resource is accessed in a way that its versions can't express.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Fail)]
+pub enum HistoryError {
+    /// Current version is never written,
+    /// so the next frame would read stale content.
+    #[fail(
+        display = "Current version {:?} of history resource is never written",
+        _0
+    )]
+    NotWritten(Id),
+
+    /// Previous version is written
+    /// while it is content of the previous frame.
+    #[fail(display = "Previous version {:?} of history resource is written", _0)]
+    PreviousWritten(Id),
+}
+
+/// Dependency of frame on the previous one through history resource.
+/// Readers of previous version in frame `N`
+/// must wait for writers of current version in frame `N - 1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryDependency {
+    /// Submissions of the last write of current version.
+    pub writers: Vec<SubmissionId>,
+
+    /// Stages of the last write of current version.
+    pub write_stages: PipelineStageFlags,
+
+    /// Submissions that read previous version in order of links.
+    pub readers: Vec<SubmissionId>,
+
+    /// Stages at which previous version is read.
+    pub read_stages: PipelineStageFlags,
+}
+
+/// Find submissions linked by history resource across frames.
+/// Versions may be buffers, images or acceleration structures.
+pub fn history_dependency<S>(
+    chains: &Chains<S>,
+    history: &History,
+) -> Result<HistoryDependency, HistoryError> {
+    let current = links(chains, history.current);
+    let previous = links(chains, history.previous);
+
+    if previous.iter().any(|&(access, _, _)| access.is_write()) {
+        return Err(HistoryError::PreviousWritten(history.previous));
+    }
+
+    let (_, write_stages, writers) = current
+        .iter()
+        .rev()
+        .find(|&&(access, _, _)| access.is_write())
+        .cloned()
+        .ok_or(HistoryError::NotWritten(history.current))?;
+
+    Ok(HistoryDependency {
+        writers: writers.to_vec(),
+        write_stages,
+        readers: previous
+            .iter()
+            .flat_map(|&(_, _, submissions)| submissions.iter().cloned())
+            .collect(),
+        read_stages: previous
+            .iter()
+            .fold(PipelineStageFlags::empty(), |acc, &(_, stages, _)| {
+                acc | stages
+            }),
+    })
+}
+
+/// Get access, stages and submissions of all links of the resource chain.
+fn links<S>(chains: &Chains<S>, id: Id) -> Vec<(AccessFlags, PipelineStageFlags, &[SubmissionId])> {
+    fn collect<R: Resource>(
+        chain: &Chain<R>,
+    ) -> Vec<(AccessFlags, PipelineStageFlags, &[SubmissionId])> {
+        chain
+            .links()
+            .iter()
+            .map(|link| (link.access(), link.stages(), link.submissions()))
+            .collect()
+    }
+
+    chains
+        .buffers
+        .get(&id)
+        .map(collect)
+        .or_else(|| chains.images.get(&id).map(collect))
+        .or_else(|| chains.acceleration_structures.get(&id).map(collect))
+        .unwrap_or_default()
+}
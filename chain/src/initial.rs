@@ -0,0 +1,60 @@
+//! This module validates that images are not read before their content is defined.
+//!
+
+use rendy_resource::image::Layout;
+
+use collect::Chains;
+use schedule::SubmissionId;
+use Id;
+
+/// Image is read while its content is undefined.
+/// Image without declared initial layout starts in `Undefined` layout
+/// and links that read it before the first write observe garbage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Fail)]
+#[fail(
+    display = "Image {:?} is read by link {} in submission {:?} before its content is defined",
+    resource, link, submission
+)]
+pub struct UninitializedRead {
+    /// Id of the image.
+    pub resource: Id,
+
+    /// Index of the first link that reads undefined content.
+    pub link: usize,
+
+    /// First submission of the link.
+    pub submission: SubmissionId,
+}
+
+/// Find images read before they are written
+/// while their initial layout is `Undefined`.
+/// Declare initial layout with `Chains::set_initial_layout`
+/// for images with content defined outside of the graph.
+///
+/// Reports at most one read per image. Result is sorted by image id.
+pub fn validate_initial_layouts<S>(chains: &Chains<S>) -> Vec<UninitializedRead> {
+    let mut errors = chains
+        .images
+        .iter()
+        .filter(|&(&id, _)| chains.initial_layout(id) == Layout::Undefined)
+        .filter_map(|(&id, chain)| {
+            let links = chain.links();
+            // Write link may read content it modifies as well.
+            let defined = links
+                .iter()
+                .position(|link| link.access().is_write())
+                .map_or(links.len(), |index| index + 1);
+
+            links[..defined]
+                .iter()
+                .position(|link| link.access().is_read())
+                .map(|index| UninitializedRead {
+                    resource: id,
+                    link: index,
+                    submission: links[index].submissions()[0],
+                })
+        })
+        .collect::<Vec<_>>();
+    errors.sort();
+    errors
+}
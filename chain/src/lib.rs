@@ -21,23 +21,60 @@
 #[macro_use]
 extern crate bitflags;
 
+#[macro_use]
+extern crate failure;
+
 extern crate fnv;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+#[cfg(all(test, feature = "serde"))]
+#[macro_use]
+extern crate serde_json;
+
 extern crate rendy_resource;
 
 /// Unique resource id.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Id(pub u64);
 
+#[cfg(test)]
+mod test;
+
 /// ???
 mod access;
 /// ???
+mod batch;
+/// ???
 mod chain;
 /// ???
 mod collect;
 /// ???
+mod compiled;
+/// ???
+mod critical;
+/// ???
+mod dead;
+/// ???
+mod fence;
+/// ???
+mod history;
+/// ???
+mod initial;
+/// ???
+mod lint;
+/// ???
+mod merge;
+/// ???
 mod node;
 /// ???
+mod pass;
+/// ???
+mod report;
+/// ???
 mod resource;
 /// ???
 mod schedule;
@@ -46,10 +83,37 @@ mod stage;
 /// ???
 mod sync;
 
-pub use chain::Chain;
-pub use node::{Node, State};
-pub use resource::{Buffer, Image, Resource};
+pub use access::AccessFlags;
+pub use batch::{barrier_set, BarrierBatch, BarrierSet, GlobalBarrier, StaticSplit, SyncPoint};
+pub use compiled::{
+    compile, compile_frame, BarrierRecord, CompiledChain, InternalBarriers, Replay,
+};
+pub use collect::{collect, collect_with, Chains, CollectOptions, Unsynchronized};
+pub use critical::critical_path;
+pub use chain::{
+    by_submission, Chain, Link, LinkAccess, LinkOverride, OverrideError, SubmissionResources,
+    SyncScope,
+};
+pub use dead::{dead_writes, DeadWrite, DeadWrites};
+pub use fence::{frame_fences, FrameFences};
+pub use history::{history_dependency, History, HistoryDependency, HistoryError, HistoryFrame};
+pub use initial::{validate_initial_layouts, UninitializedRead};
+pub use lint::{lint, OverSync, OverSyncWarning, OverSyncWarnings};
+pub use merge::{merge, MergeError};
+pub use node::{ImageAspects, Node, PassMetadata, State};
+pub use pass::{validate_render_pass_barriers, IllegalBarrier, RenderPassIssue};
+pub use report::{
+    report, BarrierReport, GuardSide, LinkReport, Report, ResourceKind, ResourceReport,
+    SemaphoreReport, WarningReport,
+};
+pub use resource::{
+    AccelerationStructure, AccelerationStructureUsageFlags, Buffer, Image, Resource,
+};
 pub use stage::{PipelineStageFlags, GraphicsPipelineStage, ComputePipelineStage};
-pub use sync::SyncData;
-pub use schedule::Schedule;
+pub use sync::{
+    sync, sync_timeline, sync_with, sync_with_features, sync_with_stages, Barrier, DependencyFlags,
+    Hazard, Placement, PlacementPolicy, SemaphoreSync, SyncData, SyncMethod, SyncOptions,
+    TimelineValue,
+};
+pub use schedule::{FamilyId, QueueId, Schedule, SubmissionId};
 
@@ -0,0 +1,200 @@
+//! This module provides analysis that finds synchronization wider than necessary.
+//! Unlike validation it never reports errors, only opportunities to synchronize less.
+//!
+
+use std::ops::Range;
+
+use fnv::FnvHashMap;
+
+use chain::{Chain, Link};
+use collect::Chains;
+use node::PassMetadata;
+use resource::{AccelerationStructure, Buffer, Image, Resource};
+use schedule::SubmissionId;
+use stage::PipelineStageFlags;
+use Id;
+
+/// Kind of over-synchronization with suggested tighter option.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OverSync<R: Resource> {
+    /// Barrier uses stages at which resource is not accessed.
+    /// Usually caused by too wide override of the link.
+    WideStages {
+        /// Index of the link in the chain.
+        link: usize,
+
+        /// Stages used by the barrier.
+        stages: PipelineStageFlags,
+
+        /// Stages at which resource is actually accessed.
+        suggested: PipelineStageFlags,
+    },
+
+    /// Resource is transitioned between layouts while only read.
+    /// Reading in single layout would require no barrier at all.
+    RedundantTransition {
+        /// Index of the link in the chain that requires transition.
+        link: usize,
+
+        /// Layout transition performed.
+        layouts: Range<R::Layout>,
+
+        /// Layout suggested for both links.
+        suggested: R::Layout,
+    },
+
+    /// Resource is never written yet it is synchronized every frame.
+    /// Resource can be transitioned once after upload and read in single layout afterwards.
+    Immutable {
+        /// Number of links in the chain.
+        links: usize,
+
+        /// Layout suggested for all reads.
+        suggested: R::Layout,
+    },
+}
+
+/// Warning about over-synchronization of single resource.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OverSyncWarning<R: Resource> {
+    /// Id of the resource.
+    pub resource: Id,
+
+    /// Submission where excessive synchronization occurs.
+    pub submission: SubmissionId,
+
+    /// What is excessive and how to do better.
+    pub issue: OverSync<R>,
+
+    /// Metadata of the pass of the submission.
+    pub pass: Option<PassMetadata>,
+}
+
+/// Over-synchronization warnings for all resources.
+#[derive(Clone, Debug)]
+pub struct OverSyncWarnings {
+    /// Warnings for buffers.
+    pub buffers: Vec<OverSyncWarning<Buffer>>,
+
+    /// Warnings for images.
+    pub images: Vec<OverSyncWarning<Image>>,
+
+    /// Warnings for acceleration structures.
+    pub acceleration_structures: Vec<OverSyncWarning<AccelerationStructure>>,
+}
+
+impl OverSyncWarnings {
+    /// Get total number of warnings.
+    pub fn len(&self) -> usize {
+        self.buffers.len() + self.images.len() + self.acceleration_structures.len()
+    }
+
+    /// Check if there are no warnings.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Find synchronization in `Chains` that is wider than necessary.
+pub fn lint<S>(chains: &Chains<S>) -> OverSyncWarnings {
+    OverSyncWarnings {
+        buffers: lint_chains(&chains.buffers, &chains.metadata),
+        images: lint_chains(&chains.images, &chains.metadata),
+        acceleration_structures: lint_chains(&chains.acceleration_structures, &chains.metadata),
+    }
+}
+
+fn lint_chains<'a, R, I>(
+    chains: I,
+    metadata: &FnvHashMap<SubmissionId, PassMetadata>,
+) -> Vec<OverSyncWarning<R>>
+where
+    R: Resource,
+    I: IntoIterator<Item = (&'a Id, &'a Chain<R>)>,
+{
+    let mut warnings = Vec::new();
+    for (&id, chain) in chains {
+        lint_chain(id, chain, &mut warnings);
+    }
+    for warning in &mut warnings {
+        warning.pass = metadata.get(&warning.submission).cloned();
+    }
+    warnings.sort_by_key(|warning| (warning.resource, warning.submission));
+    warnings
+}
+
+fn lint_chain<R>(id: Id, chain: &Chain<R>, warnings: &mut Vec<OverSyncWarning<R>>)
+where
+    R: Resource,
+{
+    let links = chain.links();
+    let immutable = links.len() > 1 && !links.iter().any(|link| link.access().is_write());
+
+    if immutable {
+        warnings.push(OverSyncWarning {
+            resource: id,
+            submission: first_submission(&links[1]),
+            issue: OverSync::Immutable {
+                links: links.len(),
+                suggested: links[0].layout(),
+            },
+            pass: None,
+        });
+    }
+
+    for (index, pair) in links.windows(2).enumerate() {
+        let (prev_link, link) = (&pair[0], &pair[1]);
+        let index = index + 1;
+
+        let src = prev_link.src_state().stages;
+        if !prev_link.stages().contains(src) {
+            warnings.push(OverSyncWarning {
+                resource: id,
+                submission: first_submission(link),
+                issue: OverSync::WideStages {
+                    link: index - 1,
+                    stages: src,
+                    suggested: prev_link.stages(),
+                },
+                pass: None,
+            });
+        }
+
+        let dst = link.dst_state().stages;
+        if !link.stages().contains(dst) {
+            warnings.push(OverSyncWarning {
+                resource: id,
+                submission: first_submission(link),
+                issue: OverSync::WideStages {
+                    link: index,
+                    stages: dst,
+                    suggested: link.stages(),
+                },
+                pass: None,
+            });
+        }
+
+        // Immutable resources are already reported once per chain.
+        if !immutable
+            && prev_link.family() == link.family()
+            && !prev_link.access().is_write()
+            && !link.access().is_write()
+            && prev_link.layout() != link.layout()
+        {
+            warnings.push(OverSyncWarning {
+                resource: id,
+                submission: first_submission(link),
+                issue: OverSync::RedundantTransition {
+                    link: index,
+                    layouts: prev_link.layout()..link.layout(),
+                    suggested: prev_link.layout(),
+                },
+                pass: None,
+            });
+        }
+    }
+}
+
+fn first_submission<R: Resource>(link: &Link<R>) -> SubmissionId {
+    link.submissions()[0]
+}
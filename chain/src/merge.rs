@@ -0,0 +1,216 @@
+//! This module merges chains collected independently from separate sets of nodes,
+//! e.g. when frame graph is assembled from modular subsystems.
+//!
+
+use fnv::{FnvHashMap, FnvHashSet};
+
+use chain::Chain;
+use collect::Chains;
+use resource::Resource;
+use schedule::{Queue, QueueId, SubmissionId};
+use Id;
+
+/// Chain sets can't be merged into consistent schedule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Fail)]
+pub enum MergeError {
+    /// Both chain sets contain submissions of the same node.
+    #[fail(display = "Node {} is scheduled in both chain sets", _0)]
+    DuplicateNode(usize),
+
+    /// Same id is used for resources of different kinds.
+    #[fail(
+        display = "Resource {:?} is accessed as resources of different kinds",
+        _0
+    )]
+    ResourceKind(Id),
+
+    /// Chain sets track aspects of the image inconsistently.
+    /// Either the same chain tracks different aspects,
+    /// or chains of one set track aspects that chains of another set track as well.
+    #[fail(display = "Aspects of image {:?} are tracked inconsistently", _0)]
+    ImageAspects(Id),
+}
+
+/// Merge chains `b` into chains `a`.
+/// Submissions of `b` are submitted after all submissions of `a`
+/// and placed on the same queues after submissions of `a`.
+/// Chains of resources accessed in both sets are linked across the boundary,
+/// so that accesses from `b` are synchronized with accesses from `a`.
+/// Boundary links that only read the resource in the same layout are merged into one.
+///
+/// Metadata and orderings of both sets are preserved.
+/// Initial layouts declared in `b` for images accessed by `a` are dropped,
+/// as content of such images is defined by `a`.
+pub fn merge(mut a: Chains, b: Chains) -> Result<Chains, MergeError> {
+    check_nodes(&a, &b)?;
+    check_kinds(&a, &b)?;
+    check_aspects(&a, &b)?;
+
+    let queue_offsets: FnvHashMap<QueueId, usize> = a
+        .schedule
+        .iter()
+        .flat_map(|family| family.iter())
+        .map(|queue| (queue.id(), queue.len()))
+        .collect();
+    let queue_offset = |qid: QueueId| queue_offsets.get(&qid).cloned().unwrap_or(0);
+    let shift =
+        |sid: SubmissionId| SubmissionId::new(sid.queue(), sid.index() + queue_offset(sid.queue()));
+    let submit_offset = a.schedule.ordered().count();
+    let wait_factor_offset = a
+        .schedule
+        .ordered()
+        .map(|submission| submission.wait_factor() + 1)
+        .max()
+        .unwrap_or(0);
+
+    for (id, layout) in b.initial_layouts {
+        if !a.images.contains_key(&id) {
+            a.initial_layouts.insert(id, layout);
+        }
+    }
+
+    let mut link_offsets = FnvHashMap::default();
+    merge_chains(&mut a.buffers, b.buffers, &queue_offset, &mut link_offsets);
+    merge_chains(&mut a.images, b.images, &queue_offset, &mut link_offsets);
+    merge_chains(
+        &mut a.acceleration_structures,
+        b.acceleration_structures,
+        &queue_offset,
+        &mut link_offsets,
+    );
+    a.image_aspects.extend(b.image_aspects);
+
+    for queue in b.schedule.iter().flat_map(|family| family.iter()) {
+        let qid = queue.id();
+        if a.schedule.queue(qid).is_none() {
+            a.schedule.set_queue(Queue::new(qid));
+        }
+        for submission in queue.iter() {
+            let mut submission = submission.clone();
+            submission.rebase(
+                shift(submission.id()),
+                submission.wait_factor() + wait_factor_offset,
+                submission.submit_order() + submit_offset,
+                |id| link_offsets.get(&id).cloned().unwrap_or(0),
+            );
+            a.schedule[qid].add_submission_checked(submission);
+        }
+    }
+    a.schedule.build_order();
+
+    a.metadata
+        .extend(b.metadata.into_iter().map(|(sid, pass)| (shift(sid), pass)));
+    a.orderings.extend(
+        b.orderings
+            .into_iter()
+            .map(|ordering| shift(ordering.start)..shift(ordering.end)),
+    );
+
+    Ok(a)
+}
+
+fn check_nodes(a: &Chains, b: &Chains) -> Result<(), MergeError> {
+    let nodes: FnvHashSet<usize> = a.schedule.ordered().map(|s| s.node()).collect();
+    match b.schedule.ordered().find(|s| nodes.contains(&s.node())) {
+        Some(submission) => Err(MergeError::DuplicateNode(submission.node())),
+        None => Ok(()),
+    }
+}
+
+/// Get kind of the resource with `id` as index of the chains map it's found in.
+fn kind(chains: &Chains, id: Id) -> Option<usize> {
+    if chains.buffers.contains_key(&id) {
+        Some(0)
+    } else if chains.images.contains_key(&id) {
+        Some(1)
+    } else if chains.acceleration_structures.contains_key(&id) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+fn check_kinds(a: &Chains, b: &Chains) -> Result<(), MergeError> {
+    let ids = b
+        .buffers
+        .keys()
+        .chain(b.images.keys())
+        .chain(b.acceleration_structures.keys());
+    for &id in ids {
+        match (kind(a, id), kind(b, id)) {
+            (Some(left), Some(right)) if left != right => {
+                return Err(MergeError::ResourceKind(id));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn check_aspects(a: &Chains, b: &Chains) -> Result<(), MergeError> {
+    check_aspects_of(a, b)?;
+    check_aspects_of(b, a)
+}
+
+/// Check aspects tracked by chains of `parts` against chains of `other`.
+fn check_aspects_of(parts: &Chains, other: &Chains) -> Result<(), MergeError> {
+    for (&id, &aspects) in &parts.image_aspects {
+        let conflict = match other.image_aspects.get(&id) {
+            Some(&other_aspects) => other_aspects != aspects,
+            None => other.images.contains_key(&id),
+        };
+        // Chain of the whole image covers all aspects.
+        if conflict || other.images.contains_key(&aspects.image) {
+            return Err(MergeError::ImageAspects(aspects.image));
+        }
+        let overlaps = other
+            .image_aspects
+            .iter()
+            .any(|(&other_id, &other_aspects)| {
+                other_id != id
+                    && other_aspects.image == aspects.image
+                    && other_aspects.aspects.intersects(aspects.aspects)
+            });
+        if overlaps {
+            return Err(MergeError::ImageAspects(aspects.image));
+        }
+    }
+    Ok(())
+}
+
+/// Append links of chains `b` to chains `a`,
+/// recording by how much link indices of submissions from `b` must be shifted.
+fn merge_chains<R, F>(
+    a: &mut FnvHashMap<Id, Chain<R>>,
+    b: FnvHashMap<Id, Chain<R>>,
+    queue_offset: &F,
+    link_offsets: &mut FnvHashMap<Id, usize>,
+) where
+    R: Resource,
+    F: Fn(QueueId) -> usize,
+{
+    for (id, chain) in b {
+        let chain_a = a.entry(id).or_insert_with(Chain::new);
+        let mut links = chain.into_links().into_iter().map(|mut link| {
+            link.shift_submissions(queue_offset);
+            link
+        });
+
+        let mut offset = chain_a.len();
+        if let Some(first) = links.next() {
+            let mergeable = chain_a
+                .last_link_mut()
+                .map_or(false, |last| last.mergeable(&first));
+            if mergeable {
+                chain_a.last_link_mut().unwrap().merge(first);
+                offset -= 1;
+            } else {
+                chain_a.add_link(first);
+            }
+        }
+        for link in links {
+            chain_a.add_link(link);
+        }
+        link_offsets.insert(id, offset);
+    }
+}
@@ -1,13 +1,16 @@
 use std::collections::hash_map::{HashMap, Iter as HashMapIter};
+use std::ops::Range;
+
+use rendy_resource::image::ImageAspectFlags;
 
 use access::AccessFlags;
-use resource::{Buffer, Image, Resource};
+use resource::{AccelerationStructure, Buffer, Image, Resource};
 use schedule::FamilyId;
 use stage::PipelineStageFlags;
 use Id;
 
 /// State in which node uses resource and usage flags.
-#[derive(Clone, Copy, Debug)]
+#[derive(Debug)]
 pub struct State<R: Resource> {
     /// Access performed by the node.
     pub access: AccessFlags,
@@ -22,6 +25,44 @@ pub struct State<R: Resource> {
     pub usage: R::Usage,
 }
 
+// Derived impls would require `R: Copy` while only layout and usage are stored.
+impl<R: Resource> Clone for State<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R: Resource> Copy for State<R> {}
+
+/// Aspects of an image tracked by a separate chain.
+/// Depth and stencil aspects of an image tracked by different chains
+/// can be in different layouts simultaneously.
+/// See `VK_KHR_separate_depth_stencil_layouts`.
+/// Without `DeviceFeatures::separate_depth_stencil_layouts` depth and stencil aspects
+/// can't be tracked separately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageAspects {
+    /// Id of the whole image.
+    pub image: Id,
+
+    /// Aspects tracked by the chain.
+    pub aspects: ImageAspectFlags,
+}
+
+/// User-supplied description of the pass that is carried through chain construction.
+/// It is reported alongside submissions of the pass in dumps and lints.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PassMetadata {
+    /// Name of the pass.
+    pub name: String,
+
+    /// Color to display the pass with.
+    pub color: Option<[f32; 4]>,
+
+    /// Scheduling priority. Higher value means more important pass.
+    pub priority: i32,
+}
+
 /// Description of node.
 #[derive(Clone, Debug)]
 pub struct Node {
@@ -38,8 +79,42 @@ pub struct Node {
     /// Buffer category ids and required state.
     pub buffers: HashMap<Id, State<Buffer>>,
 
+    /// Byte ranges of buffers from `buffers` accessed by the node.
+    /// Accesses of disjoint ranges need no synchronization.
+    /// Buffers not listed here are accessed as a whole.
+    pub buffer_ranges: HashMap<Id, Range<u64>>,
+
+    /// Accesses of buffers from `buffers` performed by the node after the declared one,
+    /// in order of execution.
+    /// Barriers between them are recorded by the node itself. See `SyncData::internal`.
+    pub subsequent_buffers: HashMap<Id, Vec<State<Buffer>>>,
+
     /// Image category ids and required state.
     pub images: HashMap<Id, State<Image>>,
+
+    /// Accesses of images from `images` performed by the node after the declared one,
+    /// in order of execution, e.g. sampling image after copying into it.
+    /// Barriers between them are recorded by the node itself. See `SyncData::internal`.
+    pub subsequent_images: HashMap<Id, Vec<State<Image>>>,
+
+    /// Aspects of images from `images` that are tracked separately.
+    /// Each separately tracked aspect has its own id.
+    /// Images not listed here are tracked as a whole.
+    pub image_aspects: HashMap<Id, ImageAspects>,
+
+    /// Acceleration structure category ids and required state.
+    pub acceleration_structures: HashMap<Id, State<AccelerationStructure>>,
+
+    /// Render pass in which node is recorded as a subpass.
+    /// Dependencies between nodes of the same render pass can be framebuffer-local.
+    pub render_pass: Option<usize>,
+
+    /// Node only uploads data to resources.
+    /// Such node can be scheduled to dedicated transfer family.
+    pub upload: bool,
+
+    /// Optional description of the pass.
+    pub metadata: Option<PassMetadata>,
 }
 
 impl Node {
@@ -53,13 +128,55 @@ impl Node {
         &self.dependencies
     }
 
+    /// Get render pass in which node is recorded as a subpass.
+    pub fn render_pass(&self) -> Option<usize> {
+        self.render_pass
+    }
+
+    /// Check if node only uploads data to resources.
+    pub fn upload(&self) -> bool {
+        self.upload
+    }
+
+    /// Get description of the pass.
+    pub fn metadata(&self) -> Option<&PassMetadata> {
+        self.metadata.as_ref()
+    }
+
     /// Get iterator to buffer states this node accesses.
     pub fn buffers(&self) -> HashMapIter<'_, Id, State<Buffer>> {
         self.buffers.iter()
     }
 
+    /// Get iterator to byte ranges of buffers this node accesses partially.
+    pub fn buffer_ranges(&self) -> HashMapIter<'_, Id, Range<u64>> {
+        self.buffer_ranges.iter()
+    }
+
+    /// Get iterator to buffer accesses this node performs after the ones from `buffers`.
+    pub fn subsequent_buffers(&self) -> HashMapIter<'_, Id, Vec<State<Buffer>>> {
+        self.subsequent_buffers.iter()
+    }
+
     /// Get iterator to image states this node accesses.
     pub fn images(&self) -> HashMapIter<'_, Id, State<Image>> {
         self.images.iter()
     }
+
+    /// Get iterator to image accesses this node performs after the ones from `images`.
+    pub fn subsequent_images(&self) -> HashMapIter<'_, Id, Vec<State<Image>>> {
+        self.subsequent_images.iter()
+    }
+
+    /// Get iterator to separately tracked image aspects this node accesses.
+    pub fn image_aspects(&self) -> HashMapIter<'_, Id, ImageAspects> {
+        self.image_aspects.iter()
+    }
+
+    /// Get iterator to acceleration structure states this node accesses.
+    pub fn acceleration_structures(
+        &self,
+    ) -> HashMapIter<'_, Id, State<AccelerationStructure>> {
+        self.acceleration_structures.iter()
+    }
 }
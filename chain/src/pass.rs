@@ -0,0 +1,112 @@
+//! This module validates barriers recorded inside render passes.
+//! Such barriers are subpass self-dependencies and allow only a subset of pipeline barrier.
+//!
+
+use resource::Resource;
+use schedule::{Schedule, SubmissionId};
+use sync::{Barriers, Guard, Placement, SyncData, SyncMethod};
+use Id;
+
+/// Reason barrier can't be recorded inside render pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RenderPassIssue {
+    /// Image layout can't be changed by subpass self-dependency.
+    /// Layout must be changed by render pass itself or outside of it.
+    LayoutTransition,
+
+    /// Events can't be set inside render pass.
+    Event,
+}
+
+/// Barrier is recorded inside render pass in form not allowed there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Fail)]
+#[fail(
+    display = "Barrier of {:?} at {:?} side of submission {:?} inside render pass {}: {:?}",
+    resource, placement, submission, render_pass, issue
+)]
+pub struct IllegalBarrier {
+    /// Id of the resource.
+    pub resource: Id,
+
+    /// Submission the barrier is recorded in.
+    pub submission: SubmissionId,
+
+    /// Side of submission synchronization where the barrier is placed.
+    pub placement: Placement,
+
+    /// Render pass the barrier is recorded inside of.
+    pub render_pass: usize,
+
+    /// Why barrier is not allowed.
+    pub issue: RenderPassIssue,
+}
+
+/// Find barriers recorded inside render passes that can't be expressed as
+/// subpass self-dependencies.
+/// Other barriers inside render passes must be recorded as self-dependencies,
+/// with buffer barriers turned into global memory barriers.
+///
+/// Result is sorted by resource id and submission.
+pub fn validate_render_pass_barriers<S, W>(
+    schedule: &Schedule<SyncData<S, W>>,
+) -> Vec<IllegalBarrier> {
+    let mut errors = Vec::new();
+    for submission in schedule.ordered() {
+        let sync = submission.sync();
+        for &(ref guard, placement) in &[
+            (&sync.acquire, Placement::Acquire),
+            (&sync.release, Placement::Release),
+        ] {
+            validate_guard(guard, submission.id(), placement, &mut errors);
+        }
+    }
+    errors.sort();
+    errors
+}
+
+fn validate_guard(
+    guard: &Guard,
+    submission: SubmissionId,
+    placement: Placement,
+    errors: &mut Vec<IllegalBarrier>,
+) {
+    validate_barriers(&guard.buffers, submission, placement, errors);
+    validate_barriers(&guard.images, submission, placement, errors);
+    validate_barriers(
+        &guard.acceleration_structures,
+        submission,
+        placement,
+        errors,
+    );
+}
+
+fn validate_barriers<R>(
+    barriers: &Barriers<R>,
+    submission: SubmissionId,
+    placement: Placement,
+    errors: &mut Vec<IllegalBarrier>,
+) where
+    R: Resource,
+{
+    for (&resource, barrier) in barriers {
+        let render_pass = match barrier.render_pass {
+            Some(render_pass) => render_pass,
+            None => continue,
+        };
+        let mut error = |issue| {
+            errors.push(IllegalBarrier {
+                resource,
+                submission,
+                placement,
+                render_pass,
+                issue,
+            })
+        };
+        if barrier.is_layout_transition() {
+            error(RenderPassIssue::LayoutTransition);
+        }
+        if barrier.method == SyncMethod::Event {
+            error(RenderPassIssue::Event);
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! This module gathers the complete analysis of chains into single plain report.
+//! With `serde` feature enabled the report can be serialized, e.g. into JSON,
+//! and reports of two frames can be diffed to see what changed in their synchronization.
+//!
+
+use std::fmt::Debug;
+use std::ops::Range;
+
+use chain::Chain;
+use collect::Chains;
+use lint::{lint, OverSyncWarning};
+use resource::Resource;
+use schedule::{FamilyId, QueueId, Schedule, SubmissionId};
+use sync::{Barrier, Barriers, Guard, SyncData, SyncMethod};
+use Id;
+
+/// Kind of reported resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ResourceKind {
+    /// Buffer.
+    Buffer,
+
+    /// Image.
+    Image,
+
+    /// Acceleration structure.
+    AccelerationStructure,
+}
+
+/// Single link of resource chain.
+/// Flags and layouts are formatted with `Debug` to keep report independent of resource type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LinkReport {
+    /// Family of the link.
+    pub family: FamilyId,
+
+    /// Submissions of the link.
+    pub submissions: Vec<SubmissionId>,
+
+    /// Combined access of the link.
+    pub access: String,
+
+    /// Combined stages of the link.
+    pub stages: String,
+
+    /// Layout of the resource in the link.
+    pub layout: String,
+
+    /// Byte range accessed by the link.
+    /// `None` if the whole resource is accessed.
+    pub range: Option<Range<u64>>,
+}
+
+/// Timeline of links of single resource.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResourceReport {
+    /// Id of the resource.
+    pub id: Id,
+
+    /// Kind of the resource.
+    pub kind: ResourceKind,
+
+    /// Links of the resource chain in order.
+    pub links: Vec<LinkReport>,
+}
+
+/// Side of submission synchronization where barrier is recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GuardSide {
+    /// Before commands of the submission.
+    Acquire,
+
+    /// Between `n`-th and next accesses of the resource by the submission.
+    Internal(usize),
+
+    /// After commands of the submission.
+    Release,
+}
+
+/// Barrier computed for single resource.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BarrierReport {
+    /// Submission that records the barrier.
+    pub submission: SubmissionId,
+
+    /// Side of the submission where barrier is recorded.
+    pub side: GuardSide,
+
+    /// Id of the resource.
+    pub resource: Id,
+
+    /// Kind of the resource.
+    pub kind: ResourceKind,
+
+    /// Queues of ownership transfer.
+    pub queues: Option<Range<QueueId>>,
+
+    /// Access transition.
+    pub access: Range<String>,
+
+    /// Stages transition.
+    pub stages: Range<String>,
+
+    /// Layout transition.
+    pub layout: Range<String>,
+
+    /// Dependency flags.
+    pub dependencies: String,
+
+    /// How the barrier is recorded.
+    pub method: SyncMethod,
+
+    /// Render pass the barrier is recorded inside of.
+    pub render_pass: Option<usize>,
+
+    /// Byte range of the buffer the barrier applies to.
+    pub range: Option<Range<u64>>,
+}
+
+/// Semaphore signaled by one submission and waited by another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SemaphoreReport {
+    /// Submission that signals the semaphore.
+    pub signal: SubmissionId,
+
+    /// Submission that waits for the semaphore.
+    pub wait: SubmissionId,
+
+    /// Stages at which semaphore is waited.
+    pub stages: String,
+}
+
+/// Over-synchronization warning.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WarningReport {
+    /// Id of the resource.
+    pub resource: Id,
+
+    /// Kind of the resource.
+    pub kind: ResourceKind,
+
+    /// Submission where excessive synchronization occurs.
+    pub submission: SubmissionId,
+
+    /// Name of the pass of the submission.
+    pub pass: Option<String>,
+
+    /// What is excessive and how to do better.
+    pub issue: String,
+}
+
+/// Complete analysis of chains and their synchronization.
+/// Every list is sorted, so reports of equal frames are equal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report {
+    /// Link timelines of all resources sorted by kind and id.
+    pub resources: Vec<ResourceReport>,
+
+    /// All barriers sorted by submission, side and resource.
+    pub barriers: Vec<BarrierReport>,
+
+    /// All semaphores sorted by signaling and waiting submissions.
+    pub semaphores: Vec<SemaphoreReport>,
+
+    /// Groups of resources sharing memory.
+    /// Chains know nothing about memory so `report` leaves it empty
+    /// for whoever places resources to fill.
+    pub aliasing: Vec<Vec<Id>>,
+
+    /// Over-synchronization warnings found by `lint`
+    /// sorted by kind and id of the resource and submission.
+    pub warnings: Vec<WarningReport>,
+}
+
+/// Gather the complete analysis of synchronized chains.
+/// Signal and wait of the same semaphore are paired by equality.
+pub fn report<C, S, W>(chains: &Chains<C>, schedule: &Schedule<SyncData<S, W>>) -> Report
+where
+    S: PartialEq<W>,
+{
+    let mut resources = Vec::new();
+    add_resources(&chains.buffers, ResourceKind::Buffer, &mut resources);
+    add_resources(&chains.images, ResourceKind::Image, &mut resources);
+    add_resources(
+        &chains.acceleration_structures,
+        ResourceKind::AccelerationStructure,
+        &mut resources,
+    );
+    resources.sort_by_key(|resource| (resource.kind, resource.id));
+
+    let mut barriers = Vec::new();
+    let mut signals = Vec::new();
+    for submission in schedule.ordered() {
+        let sid = submission.id();
+        let sync = submission.sync();
+        add_guard(sid, GuardSide::Acquire, &sync.acquire, &mut barriers);
+        for (index, guard) in sync.internal.iter().enumerate() {
+            add_guard(sid, GuardSide::Internal(index), guard, &mut barriers);
+        }
+        add_guard(sid, GuardSide::Release, &sync.release, &mut barriers);
+        signals.extend(sync.signal.iter().map(|signal| (sid, signal.semaphore())));
+    }
+    barriers.sort_by_key(|barrier| {
+        (
+            barrier.submission,
+            barrier.side,
+            barrier.kind,
+            barrier.resource,
+        )
+    });
+
+    let mut semaphores = Vec::new();
+    for submission in schedule.ordered() {
+        for wait in &submission.sync().wait {
+            semaphores.extend(
+                signals
+                    .iter()
+                    .filter(|&&(_, semaphore)| *semaphore == *wait.semaphore())
+                    .map(|&(signal, _)| SemaphoreReport {
+                        signal,
+                        wait: submission.id(),
+                        stages: debug(wait.stage()),
+                    }),
+            );
+        }
+    }
+    semaphores.sort_by_key(|semaphore| (semaphore.signal, semaphore.wait));
+
+    let lints = lint(chains);
+    let mut warnings = Vec::new();
+    add_warnings(lints.buffers, ResourceKind::Buffer, &mut warnings);
+    add_warnings(lints.images, ResourceKind::Image, &mut warnings);
+    add_warnings(
+        lints.acceleration_structures,
+        ResourceKind::AccelerationStructure,
+        &mut warnings,
+    );
+
+    Report {
+        resources,
+        barriers,
+        semaphores,
+        aliasing: Vec::new(),
+        warnings,
+    }
+}
+
+fn add_resources<'a, R, I>(chains: I, kind: ResourceKind, resources: &mut Vec<ResourceReport>)
+where
+    R: Resource,
+    I: IntoIterator<Item = (&'a Id, &'a Chain<R>)>,
+{
+    for (&id, chain) in chains {
+        resources.push(ResourceReport {
+            id,
+            kind,
+            links: chain
+                .links()
+                .iter()
+                .map(|link| LinkReport {
+                    family: link.family(),
+                    submissions: link.submissions().to_vec(),
+                    access: debug(link.access()),
+                    stages: debug(link.stages()),
+                    layout: debug(link.layout()),
+                    range: link.range(),
+                })
+                .collect(),
+        });
+    }
+}
+
+fn add_guard(sid: SubmissionId, side: GuardSide, guard: &Guard, barriers: &mut Vec<BarrierReport>) {
+    add_barriers(sid, side, &guard.buffers, ResourceKind::Buffer, barriers);
+    add_barriers(sid, side, &guard.images, ResourceKind::Image, barriers);
+    add_barriers(
+        sid,
+        side,
+        &guard.acceleration_structures,
+        ResourceKind::AccelerationStructure,
+        barriers,
+    );
+}
+
+fn add_barriers<R>(
+    submission: SubmissionId,
+    side: GuardSide,
+    guard: &Barriers<R>,
+    kind: ResourceKind,
+    barriers: &mut Vec<BarrierReport>,
+) where
+    R: Resource,
+{
+    for (&resource, barrier) in guard {
+        barriers.push(barrier_report(submission, side, resource, kind, barrier));
+    }
+}
+
+fn barrier_report<R>(
+    submission: SubmissionId,
+    side: GuardSide,
+    resource: Id,
+    kind: ResourceKind,
+    barrier: &Barrier<R>,
+) -> BarrierReport
+where
+    R: Resource,
+{
+    let Range { start, end } = barrier.states;
+    BarrierReport {
+        submission,
+        side,
+        resource,
+        kind,
+        queues: barrier.queues.clone(),
+        access: debug(start.0)..debug(end.0),
+        layout: debug(start.1)..debug(end.1),
+        stages: debug(start.2)..debug(end.2),
+        dependencies: debug(barrier.dependencies),
+        method: barrier.method,
+        render_pass: barrier.render_pass,
+        range: barrier.range.clone(),
+    }
+}
+
+fn add_warnings<R>(
+    lints: Vec<OverSyncWarning<R>>,
+    kind: ResourceKind,
+    warnings: &mut Vec<WarningReport>,
+) where
+    R: Resource + Debug,
+{
+    warnings.extend(lints.into_iter().map(|warning| WarningReport {
+        resource: warning.resource,
+        kind,
+        submission: warning.submission,
+        pass: warning.pass.map(|pass| pass.name),
+        issue: debug(warning.issue),
+    }));
+}
+
+fn debug<T: Debug>(value: T) -> String {
+    format!("{:?}", value)
+}
@@ -12,7 +12,7 @@ pub trait Resource: 'static {
     type Usage: Copy + Debug + BitOr<Output = Self::Usage> + BitOrAssign + 'static;
 
     /// Layout type for the resource.
-    type Layout: Copy + Debug + 'static;
+    type Layout: Copy + Debug + PartialEq + 'static;
 
     /// Empty usage.
     fn no_usage() -> Self::Usage;
@@ -156,3 +156,54 @@ impl Resource for Image {
         })
     }
 }
+
+bitflags! {
+    /// Bitmask specifying allowed usage of an acceleration structure.
+    #[repr(transparent)]
+    pub struct AccelerationStructureUsageFlags: u32 {
+        /// Acceleration structure can be built or updated into.
+        const BUILD_DST = 0x00000001;
+
+        /// Acceleration structure can be used as source of update or copy.
+        const BUILD_SRC = 0x00000002;
+
+        /// Acceleration structure can be traced by ray tracing shaders.
+        const TRACE = 0x00000004;
+    }
+}
+
+const ACCELERATION_STRUCTURE_ACCESSES: [AccessFlags; 2] = [
+    AccessFlags::ACCELERATION_STRUCTURE_READ_NV,
+    AccessFlags::ACCELERATION_STRUCTURE_WRITE_NV,
+];
+
+/// Acceleration structure resource type.
+/// Acceleration structures have no layouts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct AccelerationStructure;
+
+impl Resource for AccelerationStructure {
+    type Usage = AccelerationStructureUsageFlags;
+    type Layout = ();
+
+    fn no_usage() -> Self::Usage {
+        AccelerationStructureUsageFlags::empty()
+    }
+
+    fn layout_for(_access: AccessFlags) {}
+
+    fn valid_usage(access: AccessFlags, usage: AccelerationStructureUsageFlags) -> bool {
+        ACCELERATION_STRUCTURE_ACCESSES.iter().all(|&access_bit| {
+            !access.contains(access_bit) || usage.intersects(match access_bit {
+                AccessFlags::ACCELERATION_STRUCTURE_READ_NV => {
+                    AccelerationStructureUsageFlags::BUILD_SRC
+                        | AccelerationStructureUsageFlags::TRACE
+                }
+                AccessFlags::ACCELERATION_STRUCTURE_WRITE_NV => {
+                    AccelerationStructureUsageFlags::BUILD_DST
+                }
+                _ => unreachable!(),
+            })
+        })
+    }
+}
@@ -5,6 +5,7 @@ use super::{
 
 /// Family id value.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FamilyId(pub u32);
 
 /// Instances of this type contains array of `Queue`s.
@@ -104,10 +104,25 @@ impl<S> Schedule<S> {
             .and_then(|queue| queue.submission_mut(sid))
     }
 
-    /// Set queue to the schedule
+    /// Set queue to the schedule.
+    /// Order of submissions is rebuilt from their submit orders.
     pub fn set_queue(&mut self, queue: Queue<S>) {
         let qid = queue.id();
         *self.ensure_queue(qid) = queue;
+        self.build_order();
+    }
+
+    /// Rebuild order of submissions from their submit orders.
+    /// Must be called after submissions are added to queues directly.
+    pub(crate) fn build_order(&mut self) {
+        let mut ordered = self
+            .iter()
+            .flat_map(|family| family.iter())
+            .flat_map(|queue| queue.iter())
+            .map(|submission| (submission.submit_order(), submission.id()))
+            .collect::<Vec<_>>();
+        ordered.sort();
+        self.ordered = ordered.into_iter().map(|(_, sid)| sid).collect();
     }
 
     /// Get mutable reference to `Family` instance by the id.
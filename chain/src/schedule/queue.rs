@@ -5,6 +5,7 @@ use super::{
 
 /// Queue id.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct QueueId {
     /// Family id of the queue.
     pub family: FamilyId,
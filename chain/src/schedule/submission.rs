@@ -5,6 +5,7 @@ use Id;
 
 /// Submission id.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SubmissionId {
     /// Queue id of the submission.
     pub queue: QueueId,
@@ -43,6 +44,7 @@ pub struct Submission<S> {
     resource_links: FnvHashMap<Id, usize>,
     wait_factor: usize,
     submit_order: usize,
+    upload: bool,
     sync: S,
 }
 
@@ -72,7 +74,14 @@ impl<S> Submission<S> {
         self.submit_order
     }
 
+    /// Check if node of the submission only uploads data to resources.
+    /// Such submission runs once at load instead of every frame.
+    pub fn upload(&self) -> bool {
+        self.upload
+    }
+
     /// Get link index for buffer by id.
+    /// For resource accessed multiple times it is the link of the first access.
     pub fn resource_link_index(&self, id: Id) -> usize {
         self.resource_links[&id]
     }
@@ -85,6 +94,7 @@ impl<S> Submission<S> {
             id,
             wait_factor,
             submit_order,
+            upload: false,
             sync,
         }
     }
@@ -97,11 +107,43 @@ impl<S> Submission<S> {
             id: self.id,
             wait_factor: self.wait_factor,
             submit_order: self.submit_order,
+            upload: self.upload,
             sync,
         }
     }
 
+    /// Get link indices of all resources by their ids.
+    pub(crate) fn resource_links(&self) -> &FnvHashMap<Id, usize> {
+        &self.resource_links
+    }
+
+    /// Mark submission as the one that only uploads data.
+    pub(crate) fn set_upload(&mut self, upload: bool) {
+        self.upload = upload;
+    }
+
+    /// Set link of the resource.
+    /// Submission accessing resource multiple times keeps link of its first access.
     pub(crate) fn set_link(&mut self, id: Id, link: usize) {
-        self.resource_links.insert(id, link);
+        self.resource_links.entry(id).or_insert(link);
+    }
+
+    /// Move submission to another position in the schedule.
+    /// Link indices are shifted by `link_offset` of the resource.
+    pub(crate) fn rebase<F>(
+        &mut self,
+        id: SubmissionId,
+        wait_factor: usize,
+        submit_order: usize,
+        link_offset: F,
+    ) where
+        F: Fn(Id) -> usize,
+    {
+        self.id = id;
+        self.wait_factor = wait_factor;
+        self.submit_order = submit_order;
+        for (&resource, link) in &mut self.resource_links {
+            *link += link_offset(resource);
+        }
     }
 }
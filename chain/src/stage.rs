@@ -82,6 +82,30 @@ bitflags! {
 
         /// Specifies the stage of the pipeline where device-side generation of commands via vkCmdProcessCommandsNVX is handled.
         const COMMAND_PROCESS_NVX = 0x00020000;
+
+        /// Specifies execution of ray tracing shader stages.
+        const RAY_TRACING_SHADER_NV = 0x00200000;
+
+        /// Specifies execution of acceleration structure build and copy commands.
+        const ACCELERATION_STRUCTURE_BUILD_NV = 0x02000000;
+    }
+}
+
+impl PipelineStageFlags {
+    /// Stages that operate on framebuffer regions.
+    /// See Vulkan docs for detailed info:
+    /// <https://www.khronos.org/registry/vulkan/specs/1.1/html/vkspec.html#synchronization-framebuffer-regions>
+    pub fn framebuffer_space() -> Self {
+        Self::FRAGMENT_SHADER
+            | Self::EARLY_FRAGMENT_TESTS
+            | Self::LATE_FRAGMENT_TESTS
+            | Self::COLOR_ATTACHMENT_OUTPUT
+    }
+
+    /// Check if all stages are framebuffer-space stages.
+    /// Empty set is not considered framebuffer-space.
+    pub fn is_framebuffer_space(&self) -> bool {
+        !self.is_empty() && Self::framebuffer_space().contains(*self)
     }
 }
 
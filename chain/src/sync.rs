@@ -2,28 +2,31 @@
 //!
 
 use fnv::FnvHashMap;
+use rendy_resource::DeviceFeatures;
+use std::cmp::max;
 use std::ops::{Range, RangeFrom, RangeTo};
 
 use access::AccessFlags;
-use chain::{Chain, Link};
+use chain::{overlap, Chain, Link};
 use collect::{Chains, Unsynchronized};
 use node::State;
-use resource::{Buffer, Image, Resource};
-use schedule::{Queue, QueueId, Schedule, SubmissionId};
+use resource::{AccelerationStructure, Buffer, Image, Resource};
+use schedule::{FamilyId, Queue, QueueId, Schedule, SubmissionId};
 use stage::PipelineStageFlags;
 use Id;
 
 /// Semaphore identifier.
 /// It allows to distinguish different semaphores to be later replaced in `Signal`s and `Wait`s
 /// for references to semaphores (or tokens associated with real semaphores).
+/// Semaphores of user-defined ordering edges have no resource id.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct Semaphore {
-    id: Id,
+    id: Option<Id>,
     points: Range<SubmissionId>,
 }
 
 impl Semaphore {
-    fn new(id: Id, points: Range<SubmissionId>) -> Self {
+    fn new(id: Option<Id>, points: Range<SubmissionId>) -> Self {
         Semaphore { id, points }
     }
 }
@@ -71,21 +74,209 @@ impl<S> Wait<S> {
     }
 }
 
-/// Pipeline barrier info.
+/// Value of the timeline semaphore of the queue.
+/// Each queue has single timeline semaphore.
+/// Submission with index `n` signals value `n + 1` on the timeline of its queue,
+/// so waiting for the value implies all previous submissions of the queue are complete.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimelineValue {
+    /// Queue which timeline semaphore is signaled or waited.
+    pub queue: QueueId,
+
+    /// Counter value.
+    pub value: u64,
+}
+
+impl TimelineValue {
+    fn signaled_by(sid: SubmissionId) -> Self {
+        TimelineValue {
+            queue: sid.queue(),
+            value: sid.index() as u64 + 1,
+        }
+    }
+}
+
+/// Synchronization of all submissions with semaphores of kind supported by the device.
 #[derive(Clone, Debug)]
+pub enum SemaphoreSync<S, W> {
+    /// Binary semaphore per cross-queue dependency.
+    Binary(Schedule<SyncData<S, W>>),
+
+    /// Timeline semaphore per queue.
+    Timeline(Schedule<SyncData<TimelineValue, TimelineValue>>),
+}
+
+bitflags! {
+    /// Bitmask specifying how execution and memory dependencies are formed.
+    /// See Vulkan docs for detailed info:
+    /// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkDependencyFlagBits.html>
+    #[repr(transparent)]
+    pub struct DependencyFlags: u32 {
+        /// Specifies that dependencies will be framebuffer-local.
+        const BY_REGION = 0x00000001;
+
+        /// Specifies that dependencies will be view-local.
+        const VIEW_LOCAL = 0x00000002;
+
+        /// Specifies that dependencies are non-device-local.
+        const DEVICE_GROUP = 0x00000004;
+    }
+}
+
+/// Method used to synchronize dependency between submissions of the same queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SyncMethod {
+    /// Pipeline barrier recorded in acquire side of the consumer.
+    Barrier,
+
+    /// Split barrier.
+    /// Event is set in release side of the producer
+    /// and waited in acquire side of the consumer.
+    /// Work submitted in between can overlap with the dependency.
+    Event,
+}
+
+/// Side of submission synchronization where barrier is recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Placement {
+    /// Barrier is recorded after commands of the last submission of the previous link.
+    Release,
+
+    /// Barrier is recorded before commands of the first submission of the next link.
+    Acquire,
+}
+
+/// Kind of hazard between consecutive links of the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hazard {
+    /// Next link reads what previous link has written.
+    ReadAfterWrite,
+
+    /// Next link overwrites what previous link has read.
+    WriteAfterRead,
+
+    /// Next link overwrites what previous link has written.
+    WriteAfterWrite,
+}
+
+impl Hazard {
+    fn between<R: Resource>(prev_link: &Link<R>, link: &Link<R>) -> Self {
+        if !prev_link.access().is_write() {
+            Hazard::WriteAfterRead
+        } else if link.access().is_write() {
+            Hazard::WriteAfterWrite
+        } else {
+            Hazard::ReadAfterWrite
+        }
+    }
+}
+
+/// Policy of barrier placement between submissions of the same family.
+///
+/// By default read-after-write barriers are placed at the start of the consumer
+/// so that producer's queue can proceed with other work,
+/// and write-after-read barriers are placed at the end of the reader that precedes the consumer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlacementPolicy {
+    /// Placement of read-after-write barriers.
+    pub read_after_write: Placement,
+
+    /// Placement of write-after-read barriers.
+    pub write_after_read: Placement,
+
+    /// Placement of write-after-write barriers.
+    pub write_after_write: Placement,
+}
+
+impl PlacementPolicy {
+    /// Get placement for the hazard.
+    pub fn placement(&self, hazard: Hazard) -> Placement {
+        match hazard {
+            Hazard::ReadAfterWrite => self.read_after_write,
+            Hazard::WriteAfterRead => self.write_after_read,
+            Hazard::WriteAfterWrite => self.write_after_write,
+        }
+    }
+}
+
+impl Default for PlacementPolicy {
+    fn default() -> Self {
+        PlacementPolicy {
+            read_after_write: Placement::Acquire,
+            write_after_read: Placement::Release,
+            write_after_write: Placement::Acquire,
+        }
+    }
+}
+
+/// Options for synchronization derivation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SyncOptions {
+    /// Use events instead of pipeline barriers
+    /// when there are other submissions between producer and consumer on the same queue.
+    pub split_barriers: bool,
+
+    /// Where to place barriers between links of the same family.
+    /// Ignored for split barriers and queue family ownership transfers.
+    pub placement: PlacementPolicy,
+}
+
+/// Pipeline barrier info.
+#[derive(Debug)]
 pub struct Barrier<R: Resource> {
     /// `Some` queue for ownership transfer. Or `None`
     pub queues: Option<Range<QueueId>>,
 
     /// State transition.
     pub states: Range<(AccessFlags, R::Layout, PipelineStageFlags)>,
+
+    /// Dependency flags for the barrier.
+    pub dependencies: DependencyFlags,
+
+    /// How the barrier must be recorded.
+    /// With `SyncMethod::Event` the same barrier is placed into both the producer's release side
+    /// where event is set and the consumer's acquire side where event is waited.
+    pub method: SyncMethod,
+
+    /// Side of submission synchronization where the barrier is placed.
+    pub placement: Placement,
+
+    /// Render pass the barrier is recorded inside of.
+    /// `Some` if both sides of the dependency are recorded in the same render pass.
+    /// Such barrier must be recorded as subpass self-dependency:
+    /// buffer barriers become global memory barriers and image layout can't change.
+    /// See `validate_render_pass_barriers`.
+    pub render_pass: Option<usize>,
+
+    /// Byte range of the buffer the barrier applies to.
+    /// `None` if the barrier applies to the whole resource.
+    pub range: Option<Range<u64>>,
+}
+
+// Derived `Clone` would require resource type itself to be `Clone`.
+impl<R> Clone for Barrier<R>
+where
+    R: Resource,
+{
+    fn clone(&self) -> Self {
+        Barrier {
+            queues: self.queues.clone(),
+            states: self.states.clone(),
+            dependencies: self.dependencies,
+            method: self.method,
+            placement: self.placement,
+            render_pass: self.render_pass,
+            range: self.range.clone(),
+        }
+    }
 }
 
 impl<R> Barrier<R>
 where
     R: Resource,
 {
-    fn new(states: Range<State<R>>) -> Self {
+    fn new(states: Range<State<R>>, dependencies: DependencyFlags) -> Self {
         Barrier {
             queues: None,
             states: (
@@ -93,10 +284,74 @@ where
                 states.start.layout,
                 states.start.stages,
             )..(states.end.access, states.end.layout, states.end.stages),
+            dependencies,
+            method: SyncMethod::Barrier,
+            placement: Placement::Acquire,
+            render_pass: None,
+            range: None,
         }
     }
 
-    fn transfer(queues: Range<QueueId>, states: Range<(AccessFlags, R::Layout)>) -> Self {
+    /// Build barrier between two consecutive links of the same family.
+    /// Barrier is inside render pass if both links are within the same render pass.
+    /// Dependency is framebuffer-local if barrier is inside render pass
+    /// and both sides are framebuffer-space operations.
+    ///
+    /// Between read-only links there is nothing to make available.
+    /// Such barrier is a pure execution dependency unless layout transition is required.
+    ///
+    /// Barrier between links accessing parts of the buffer covers only bytes accessed by both.
+    pub(crate) fn between(prev_link: &Link<R>, link: &Link<R>) -> Self {
+        let mut src = prev_link.src_state();
+        let mut dst = link.dst_state();
+        if !src.access.is_write() && !dst.access.is_write() {
+            src.access = AccessFlags::empty();
+            if src.layout == dst.layout {
+                dst.access = AccessFlags::empty();
+            }
+        }
+        let render_pass = prev_link
+            .render_pass()
+            .filter(|&pass| Some(pass) == link.render_pass());
+        let by_region = render_pass.is_some()
+            && src.stages.is_framebuffer_space()
+            && dst.stages.is_framebuffer_space();
+
+        let dependencies = if by_region {
+            DependencyFlags::BY_REGION
+        } else {
+            DependencyFlags::empty()
+        };
+        let mut barrier = Self::new(src..dst, dependencies);
+        barrier.render_pass = render_pass;
+        barrier.range = overlap(prev_link.range(), link.range());
+        barrier
+    }
+
+    /// Check if barrier is recorded inside render pass.
+    pub fn inside_render_pass(&self) -> bool {
+        self.render_pass.is_some()
+    }
+
+    /// Check if barrier changes layout of the resource.
+    pub fn is_layout_transition(&self) -> bool {
+        self.states.start.1 != self.states.end.1
+    }
+
+    /// Check if barrier is pure execution dependency.
+    /// Such barrier neither makes memory available or visible nor transitions layout.
+    pub fn is_execution_only(&self) -> bool {
+        self.queues.is_none()
+            && self.states.start.0.is_empty()
+            && self.states.end.0.is_empty()
+            && !self.is_layout_transition()
+    }
+
+    fn transfer(
+        queues: Range<QueueId>,
+        states: Range<(AccessFlags, R::Layout)>,
+        placement: Placement,
+    ) -> Self {
         Barrier {
             queues: Some(queues),
             states: (
@@ -109,6 +364,11 @@ where
                     states.end.1,
                     PipelineStageFlags::BOTTOM_OF_PIPE,
                 ),
+            dependencies: DependencyFlags::empty(),
+            method: SyncMethod::Barrier,
+            placement,
+            render_pass: None,
+            range: None,
         }
     }
 
@@ -120,6 +380,7 @@ where
         Self::transfer(
             queues,
             (AccessFlags::empty(), left.start)..(right.end.0, right.end.1),
+            Placement::Acquire,
         )
     }
 
@@ -131,6 +392,7 @@ where
         Self::transfer(
             queues,
             (left.start.0, left.start.1)..(AccessFlags::empty(), right.end),
+            Placement::Release,
         )
     }
 }
@@ -144,6 +406,9 @@ pub type BufferBarriers = Barriers<Buffer>;
 /// Map of barriers by image id.
 pub type ImageBarriers = Barriers<Image>;
 
+/// Map of barriers by acceleration structure id.
+pub type AccelStructBarriers = Barriers<AccelerationStructure>;
+
 /// Synchronization for submission at one side.
 #[derive(Clone, Debug)]
 pub struct Guard {
@@ -152,6 +417,9 @@ pub struct Guard {
 
     /// Image pipeline barriers to be inserted before or after (depends on the side) commands of the submission.
     pub images: ImageBarriers,
+
+    /// Acceleration structure pipeline barriers to be inserted before or after (depends on the side) commands of the submission.
+    pub acceleration_structures: AccelStructBarriers,
 }
 
 impl Guard {
@@ -159,6 +427,7 @@ impl Guard {
         Guard {
             buffers: FnvHashMap::default(),
             images: FnvHashMap::default(),
+            acceleration_structures: FnvHashMap::default(),
         }
     }
 
@@ -167,10 +436,12 @@ impl Guard {
         let Guard {
             ref mut buffers,
             ref mut images,
+            ref mut acceleration_structures,
         } = *self;
-        Any::downcast_mut(buffers)
-            .or_else(move || Any::downcast_mut(images))
-            .expect("`R` should be `Buffer` or `Image`")
+        <dyn Any>::downcast_mut(buffers)
+            .or_else(move || <dyn Any>::downcast_mut(images))
+            .or_else(move || <dyn Any>::downcast_mut(acceleration_structures))
+            .expect("`R` should be `Buffer`, `Image` or `AccelerationStructure`")
     }
 }
 
@@ -190,6 +461,21 @@ pub struct SyncData<S, W> {
 
     /// Points at other queues that can run after barriers above.
     pub signal: Vec<Signal<S>>,
+
+    /// Submission must signal a fence.
+    /// Host must wait for it before accessing resources written by the submission.
+    pub fence: bool,
+
+    /// Execution-only pipeline barrier with `ALL_COMMANDS` stages
+    /// must be recorded before commands of the submission.
+    /// Set for user-defined ordering edges between submissions of the same queue.
+    pub execution_barrier: bool,
+
+    /// Barriers the submission records between its own accesses of the same resource,
+    /// e.g. layout transition between copying into image and sampling it.
+    /// `internal[i]` holds barriers between `i`-th and next access of each resource.
+    /// Resources with no hazard between those accesses are absent.
+    pub internal: Vec<Guard>,
 }
 
 impl<S, W> SyncData<S, W> {
@@ -199,6 +485,9 @@ impl<S, W> SyncData<S, W> {
             acquire: Guard::new(),
             release: Guard::new(),
             signal: Vec::new(),
+            fence: false,
+            execution_barrier: false,
+            internal: Vec::new(),
         }
     }
 
@@ -208,19 +497,16 @@ impl<S, W> SyncData<S, W> {
     {
         SyncData {
             wait: self.wait,
-            acquire: Guard {
-                buffers: self.acquire.buffers,
-                images: self.acquire.images,
-            },
-            release: Guard {
-                buffers: self.release.buffers,
-                images: self.release.images,
-            },
+            acquire: self.acquire,
+            release: self.release,
             signal: self
                 .signal
                 .into_iter()
                 .map(|Signal(semaphore)| Signal(f(semaphore)))
                 .collect(),
+            fence: self.fence,
+            execution_barrier: self.execution_barrier,
+            internal: self.internal,
         }
     }
 
@@ -234,15 +520,12 @@ impl<S, W> SyncData<S, W> {
                 .into_iter()
                 .map(|Wait(semaphore, stage)| Wait(f(semaphore), stage))
                 .collect(),
-            acquire: Guard {
-                buffers: self.acquire.buffers,
-                images: self.acquire.images,
-            },
-            release: Guard {
-                buffers: self.release.buffers,
-                images: self.release.images,
-            },
+            acquire: self.acquire,
+            release: self.release,
             signal: self.signal,
+            fence: self.fence,
+            execution_barrier: self.execution_barrier,
+            internal: self.internal,
         }
     }
 }
@@ -255,28 +538,50 @@ impl SyncTemp {
 }
 
 /// Find required synchronization for all submissions in `Chains`.
-pub fn sync<F, S, W>(
+pub fn sync<F, S, W>(chains: &Chains<Unsynchronized>, new_semaphore: F) -> Schedule<SyncData<S, W>>
+where
+    F: FnMut() -> (S, W),
+{
+    sync_with(chains, SyncOptions::default(), new_semaphore)
+}
+
+/// Find required synchronization for all submissions in `Chains` using specified options.
+pub fn sync_with<F, S, W>(
+    chains: &Chains<Unsynchronized>,
+    options: SyncOptions,
+    new_semaphore: F,
+) -> Schedule<SyncData<S, W>>
+where
+    F: FnMut() -> (S, W),
+{
+    sync_with_stages(
+        chains,
+        options,
+        |_| PipelineStageFlags::all(),
+        new_semaphore,
+    )
+}
+
+/// Find required synchronization for all submissions in `Chains`
+/// for queue families that support only some pipeline stages.
+/// E.g. transfer-only family supports only `TRANSFER` stage.
+///
+/// Stage masks of barriers and semaphore waits of each submission
+/// are clamped to stages supported by its family as reported by `supported_stages`.
+/// `TOP_OF_PIPE`, `BOTTOM_OF_PIPE`, `HOST` and `ALL_COMMANDS` are always supported.
+/// Mask with no supported stages left is replaced with `ALL_COMMANDS`.
+pub fn sync_with_stages<F, Q, S, W>(
     chains: &Chains<Unsynchronized>,
+    options: SyncOptions,
+    supported_stages: Q,
     mut new_semaphore: F,
 ) -> Schedule<SyncData<S, W>>
 where
     F: FnMut() -> (S, W),
+    Q: Fn(FamilyId) -> PipelineStageFlags,
 {
     let ref schedule = chains.schedule;
-    let ref buffers = chains.buffers;
-    let ref images = chains.images;
-
-    let mut sync = SyncTemp(FnvHashMap::default());
-    for (&id, chain) in buffers {
-        sync_chain(id, chain, schedule, &mut sync);
-    }
-    for (&id, chain) in images {
-        sync_chain(id, chain, schedule, &mut sync);
-    }
-
-    if schedule.queue_count() > 1 {
-        optimize(schedule, &mut sync);
-    }
+    let mut sync = sync_temp(chains, options);
 
     let mut result = Schedule::new();
     let mut signals: FnvHashMap<Semaphore, Option<S>> = FnvHashMap::default();
@@ -308,6 +613,7 @@ where
             } else {
                 SyncData::new()
             };
+            let sync = clamp_sync(sync, supported_stages(queue.id().family()));
             new_queue.add_submission_checked(submission.set_sync(sync));
         }
         result.set_queue(new_queue);
@@ -320,9 +626,161 @@ where
     result
 }
 
+/// Find required synchronization for all submissions in `Chains`
+/// using timeline semaphore per queue instead of binary semaphore per dependency.
+///
+/// Submission signals its value on the timeline of its queue at most once,
+/// no matter how many submissions on other queues depend on it.
+/// Submission waits for single value per timeline it depends on.
+///
+/// Stage masks are clamped to stages supported by family of each submission
+/// the same way `sync_with_stages` does.
+pub fn sync_timeline<Q>(
+    chains: &Chains<Unsynchronized>,
+    options: SyncOptions,
+    supported_stages: Q,
+) -> Schedule<SyncData<TimelineValue, TimelineValue>>
+where
+    Q: Fn(FamilyId) -> PipelineStageFlags,
+{
+    let ref schedule = chains.schedule;
+    let mut sync = sync_temp(chains, options);
+
+    let mut result = Schedule::new();
+    for queue in schedule.iter().flat_map(|family| family.iter()) {
+        let mut new_queue = Queue::new(queue.id());
+        for submission in queue.iter() {
+            let sync = match sync.0.remove(&submission.id()) {
+                Some(sync) => {
+                    let value =
+                        |semaphore: Semaphore| TimelineValue::signaled_by(semaphore.points.start);
+                    let mut sync = sync.convert_signal(value).convert_wait(value);
+                    sync.signal.truncate(1);
+                    sync.wait = merge_timeline_waits(sync.wait);
+                    sync
+                }
+                None => SyncData::new(),
+            };
+            let sync = clamp_sync(sync, supported_stages(queue.id().family()));
+            new_queue.add_submission_checked(submission.set_sync(sync));
+        }
+        result.set_queue(new_queue);
+    }
+
+    debug_assert!(sync.0.is_empty());
+
+    result
+}
+
+/// Find required synchronization for all submissions in `Chains`
+/// with timeline semaphores if `features` has them
+/// or falling back to binary semaphores created with `new_semaphore` otherwise.
+/// Stage masks are clamped to stages supported by family as reported by `supported_stages`.
+pub fn sync_with_features<F, Q, S, W>(
+    chains: &Chains<Unsynchronized>,
+    options: SyncOptions,
+    features: DeviceFeatures,
+    supported_stages: Q,
+    new_semaphore: F,
+) -> SemaphoreSync<S, W>
+where
+    F: FnMut() -> (S, W),
+    Q: Fn(FamilyId) -> PipelineStageFlags,
+{
+    if features.timeline_semaphores {
+        SemaphoreSync::Timeline(sync_timeline(chains, options, supported_stages))
+    } else {
+        SemaphoreSync::Binary(sync_with_stages(
+            chains,
+            options,
+            supported_stages,
+            new_semaphore,
+        ))
+    }
+}
+
+fn sync_temp(chains: &Chains<Unsynchronized>, options: SyncOptions) -> SyncTemp {
+    let ref schedule = chains.schedule;
+
+    let mut sync = SyncTemp(FnvHashMap::default());
+    for (&id, chain) in &chains.buffers {
+        sync_chain(id, chain, schedule, options, &mut sync);
+    }
+    for (&id, chain) in &chains.images {
+        sync_chain(id, chain, schedule, options, &mut sync);
+    }
+    for (&id, chain) in &chains.acceleration_structures {
+        sync_chain(id, chain, schedule, options, &mut sync);
+    }
+    sync_orderings(&chains.orderings, &mut sync);
+
+    if schedule.queue_count() > 1 {
+        optimize(schedule, &mut sync);
+        reduce(schedule, &mut sync);
+    }
+    sync
+}
+
+// Waiting for greater value on the timeline implies waiting for smaller ones.
+fn merge_timeline_waits(waits: Vec<Wait<TimelineValue>>) -> Vec<Wait<TimelineValue>> {
+    let mut merged: Vec<Wait<TimelineValue>> = Vec::with_capacity(waits.len());
+    for Wait(value, stages) in waits {
+        match merged.iter_mut().find(|wait| wait.0.queue == value.queue) {
+            Some(wait) => {
+                wait.0.value = max(wait.0.value, value.value);
+                wait.1 |= stages;
+            }
+            None => merged.push(Wait(value, stages)),
+        }
+    }
+    merged
+}
+
+fn clamp_stages(stages: PipelineStageFlags, supported: PipelineStageFlags) -> PipelineStageFlags {
+    let supported = supported
+        | PipelineStageFlags::TOP_OF_PIPE
+        | PipelineStageFlags::BOTTOM_OF_PIPE
+        | PipelineStageFlags::HOST
+        | PipelineStageFlags::ALL_COMMANDS;
+    let clamped = stages & supported;
+    if clamped.is_empty() {
+        PipelineStageFlags::ALL_COMMANDS
+    } else {
+        clamped
+    }
+}
+
+fn clamp_barriers<R: Resource>(barriers: &mut Barriers<R>, supported: PipelineStageFlags) {
+    for barrier in barriers.values_mut() {
+        barrier.states.start.2 = clamp_stages(barrier.states.start.2, supported);
+        barrier.states.end.2 = clamp_stages(barrier.states.end.2, supported);
+    }
+}
+
+fn clamp_guard(guard: &mut Guard, supported: PipelineStageFlags) {
+    clamp_barriers(&mut guard.buffers, supported);
+    clamp_barriers(&mut guard.images, supported);
+    clamp_barriers(&mut guard.acceleration_structures, supported);
+}
+
+fn clamp_sync<S, W>(mut sync: SyncData<S, W>, supported: PipelineStageFlags) -> SyncData<S, W> {
+    if supported.contains(PipelineStageFlags::all()) {
+        return sync;
+    }
+    for wait in &mut sync.wait {
+        wait.1 = clamp_stages(wait.1, supported);
+    }
+    clamp_guard(&mut sync.acquire, supported);
+    clamp_guard(&mut sync.release, supported);
+    for guard in &mut sync.internal {
+        clamp_guard(guard, supported);
+    }
+    sync
+}
+
 // submit_order creates a consistent direction in which semaphores are generated, avoiding issues
 // with deadlocks.
-fn latest<R, S>(link: &Link<R>, schedule: &Schedule<S>) -> SubmissionId
+pub(crate) fn latest<R, S>(link: &Link<R>, schedule: &Schedule<S>) -> SubmissionId
 where
     R: Resource,
 {
@@ -357,7 +815,7 @@ fn generate_semaphore_pair<R: Resource>(
     range: Range<SubmissionId>,
 ) {
     if range.start.queue() != range.end.queue() {
-        let semaphore = Semaphore::new(id, range.clone());
+        let semaphore = Semaphore::new(Some(id), range.clone());
         sync.get_sync(range.start)
             .signal
             .push(Signal::new(semaphore.clone()));
@@ -367,27 +825,79 @@ fn generate_semaphore_pair<R: Resource>(
     }
 }
 
-fn sync_chain<R, S>(id: Id, chain: &Chain<R>, schedule: &Schedule<S>, sync: &mut SyncTemp)
-where
+fn sync_chain<R, S>(
+    id: Id,
+    chain: &Chain<R>,
+    schedule: &Schedule<S>,
+    options: SyncOptions,
+    sync: &mut SyncTemp,
+) where
     R: Resource,
 {
-    let uid = id.into();
+    // Submission and index of the last barrier between its own accesses.
+    let mut internal: Option<(SubmissionId, usize)> = None;
+
     for (prev_link, link) in chain.links().windows(2).map(|pair| (&pair[0], &pair[1])) {
-        if prev_link.family() == link.family() {
+        let first_sid = earliest(link, schedule);
+        if prev_link.submissions().contains(&first_sid) {
+            // Subsequent access of the same submission.
+            // Barrier is recorded by the submission between the accesses.
+            // Pipeline barrier covers other submissions of its queue,
+            // while submissions on other queues are synchronized with semaphores.
+            let step = match internal {
+                Some((sid, step)) if sid == first_sid => step + 1,
+                _ => 0,
+            };
+            internal = Some((first_sid, step));
+
+            for (queue_id, queue) in prev_link.queues() {
+                let tail = SubmissionId::new(queue_id, queue.last);
+                generate_semaphore_pair(sync, id, link, tail..first_sid);
+            }
+            for (queue_id, queue) in link.queues() {
+                let head = SubmissionId::new(queue_id, queue.first);
+                generate_semaphore_pair(sync, id, link, first_sid..head);
+            }
+
+            let barrier = Barrier::between(prev_link, link);
+            let ref mut guards = sync.get_sync(first_sid).internal;
+            while guards.len() <= step {
+                guards.push(Guard::new());
+            }
+            if !barrier.is_execution_only() {
+                guards[step].pick::<R>().insert(id, barrier);
+            }
+            continue;
+        }
+
+        if link.host_access() {
+            // Host can't wait for semaphores or record barriers.
+            // Make writes visible to the host at the end of the last submission
+            // on each queue of previous link and signal fences.
+            let mut barrier = Barrier::between(prev_link, link);
+            barrier.placement = Placement::Release;
+            for (queue_id, queue) in prev_link.queues() {
+                let sync_data = sync.get_sync(SubmissionId::new(queue_id, queue.last));
+                sync_data.release.pick::<R>().insert(id, barrier.clone());
+                sync_data.fence = true;
+            }
+        } else if prev_link.family() == link.family() {
             // Prefer to generate barriers on the acquire side, if possible.
             if prev_link.single_queue() && !link.single_queue() {
                 let signal_sid = latest(prev_link, schedule);
 
                 // Generate barrier in prev link's last submission.
+                let mut barrier = Barrier::between(prev_link, link);
+                barrier.placement = Placement::Release;
                 sync.get_sync(signal_sid)
                     .release
                     .pick::<R>()
-                    .insert(id, Barrier::new(prev_link.state()..link.state()));
+                    .insert(id, barrier);
 
                 // Generate semaphores between queues in the previous link and the current one.
                 for (queue_id, queue) in link.queues() {
                     let head = SubmissionId::new(queue_id, queue.first);
-                    generate_semaphore_pair(sync, uid, link, signal_sid..head);
+                    generate_semaphore_pair(sync, id, link, signal_sid..head);
                 }
             } else {
                 let wait_sid = earliest(link, schedule);
@@ -395,14 +905,42 @@ where
                 // Generate semaphores between queues in the previous link and the current one.
                 for (queue_id, queue) in prev_link.queues() {
                     let tail = SubmissionId::new(queue_id, queue.last);
-                    generate_semaphore_pair(sync, uid, link, tail..wait_sid);
+                    generate_semaphore_pair(sync, id, link, tail..wait_sid);
                 }
 
-                // Generate barrier in next link's first submission.
-                sync.get_sync(wait_sid)
-                    .acquire
-                    .pick()
-                    .insert(id, Barrier::new(prev_link.state()..link.state()));
+                let mut barrier = Barrier::between(prev_link, link);
+
+                if prev_link.single_queue() {
+                    let set_sid = latest(prev_link, schedule);
+
+                    // Split barrier if there is work between producer and consumer on the same queue.
+                    if options.split_barriers
+                        && link.single_queue()
+                        && set_sid.queue() == wait_sid.queue()
+                        && set_sid.index() + 1 < wait_sid.index()
+                    {
+                        barrier.method = SyncMethod::Event;
+                        sync.get_sync(set_sid)
+                            .release
+                            .pick::<R>()
+                            .insert(id, barrier.clone());
+                    } else {
+                        barrier.placement =
+                            options.placement.placement(Hazard::between(prev_link, link));
+                    }
+                }
+
+                match barrier.placement {
+                    // Generate barrier in prev link's last submission.
+                    Placement::Release => {
+                        let set_sid = latest(prev_link, schedule);
+                        sync.get_sync(set_sid).release.pick().insert(id, barrier);
+                    }
+                    // Generate barrier in next link's first submission.
+                    Placement::Acquire => {
+                        sync.get_sync(wait_sid).acquire.pick().insert(id, barrier);
+                    }
+                }
 
                 if !link.single_queue() {
                     unimplemented!("This case is unimplemented");
@@ -417,14 +955,14 @@ where
             }
 
             // Generate a semaphore between the signal and wait sides of the transfer.
-            generate_semaphore_pair(sync, uid, link, signal_sid..wait_sid);
+            generate_semaphore_pair(sync, id, link, signal_sid..wait_sid);
 
             // Generate barriers to transfer the resource to another queue.
             sync.get_sync(signal_sid).release.pick::<R>().insert(
                 id,
                 Barrier::release(
                     signal_sid.queue()..wait_sid.queue(),
-                    (prev_link.access(), prev_link.layout())..,
+                    (prev_link.src_state().access, prev_link.layout())..,
                     ..link.layout(),
                 ),
             );
@@ -433,7 +971,7 @@ where
                 Barrier::acquire(
                     signal_sid.queue()..wait_sid.queue(),
                     prev_link.layout()..,
-                    ..(link.access(), link.layout()),
+                    ..(link.dst_state().access, link.layout()),
                 ),
             );
 
@@ -444,6 +982,28 @@ where
     }
 }
 
+fn sync_orderings(orderings: &[Range<SubmissionId>], sync: &mut SyncTemp) {
+    let mut orderings = orderings.to_vec();
+    orderings.sort_by_key(|range| (range.start, range.end));
+    orderings.dedup();
+
+    for range in orderings {
+        if range.start.queue() != range.end.queue() {
+            // Wait before any commands of the submission.
+            let semaphore = Semaphore::new(None, range.clone());
+            sync.get_sync(range.start)
+                .signal
+                .push(Signal::new(semaphore.clone()));
+            sync.get_sync(range.end)
+                .wait
+                .push(Wait::new(semaphore, PipelineStageFlags::ALL_COMMANDS));
+        } else {
+            // Submission order alone doesn't guarantee completion order.
+            sync.get_sync(range.end).execution_barrier = true;
+        }
+    }
+}
+
 fn optimize_submission(
     sid: SubmissionId,
     found: &mut FnvHashMap<QueueId, usize>,
@@ -491,3 +1051,107 @@ fn optimize<S>(schedule: &Schedule<S>, sync: &mut SyncTemp) {
         }
     }
 }
+
+/// Points on queues known to be complete.
+/// Submission with index `n` on the queue and all submissions before it are complete.
+type Completed = FnvHashMap<QueueId, usize>;
+
+fn covers(completed: &Completed, sid: SubmissionId) -> bool {
+    completed
+        .get(&sid.queue())
+        .map_or(false, |&index| index >= sid.index())
+}
+
+fn mark(completed: &mut Completed, sid: SubmissionId) {
+    let index = completed.entry(sid.queue()).or_insert(sid.index());
+    *index = max(*index, sid.index());
+}
+
+fn join(completed: &mut Completed, other: &Completed) {
+    for (&qid, &index) in other {
+        mark(completed, SubmissionId::new(qid, index));
+    }
+}
+
+/// Remove semaphores implied by paths through other semaphores.
+/// E.g. if `A` signals `B` and `B` signals `C` then `A` doesn't need to signal `C`.
+///
+/// Semaphore signal operation waits for all previously submitted commands of the queue,
+/// and semaphore wait blocks all subsequent commands of the queue at the wait stages.
+/// So wait is redundant if another wait of the submission or of the earlier submission
+/// on the same queue blocks at least the same stages
+/// until point the redundant wait waits for is complete.
+fn reduce<S>(schedule: &Schedule<S>, sync: &mut SyncTemp) {
+    // Points complete before signal of the submission.
+    let mut signaled: FnvHashMap<SubmissionId, Completed> = FnvHashMap::default();
+    // Points complete before subsequent commands of the queue execute at stages.
+    let mut blocked: FnvHashMap<QueueId, Vec<(PipelineStageFlags, Completed)>> =
+        FnvHashMap::default();
+
+    // Signaling submission is always submitted before waiting one.
+    for submission in schedule.ordered() {
+        let sid = submission.id();
+        let queue_blocked = blocked.entry(sid.queue()).or_insert_with(Vec::new);
+
+        let mut to_remove = Vec::new();
+        if let Some(sync_data) = sync.0.get_mut(&sid) {
+            let waits = sync_data
+                .wait
+                .iter()
+                .map(|wait| {
+                    let start = wait.semaphore().points.start;
+                    let mut completed = signaled.get(&start).cloned().unwrap_or_default();
+                    mark(&mut completed, start);
+                    (wait.stage(), completed)
+                })
+                .collect::<Vec<_>>();
+
+            let mut index = 0;
+            sync_data.wait.retain(|wait| {
+                let start = wait.semaphore().points.start;
+                let stage = wait.stage();
+                let redundant = queue_blocked
+                    .iter()
+                    .chain(
+                        waits
+                            .iter()
+                            .enumerate()
+                            .filter(|&(other, _)| other != index)
+                            .map(|(_, blocked)| blocked),
+                    )
+                    .any(|&(stages, ref completed)| {
+                        stages.contains(stage) && covers(completed, start)
+                    });
+                index += 1;
+                if redundant {
+                    to_remove.push(wait.semaphore().clone());
+                }
+                !redundant
+            });
+
+            for (stages, completed) in waits {
+                match queue_blocked.iter_mut().find(|blocked| blocked.0 == stages) {
+                    Some(blocked) => join(&mut blocked.1, &completed),
+                    None => queue_blocked.push((stages, completed)),
+                }
+            }
+        }
+
+        let mut completed = Completed::default();
+        for &(_, ref blocked) in queue_blocked.iter() {
+            join(&mut completed, blocked);
+        }
+        mark(&mut completed, sid);
+        signaled.insert(sid, completed);
+
+        for semaphore in to_remove {
+            // Delete signal as well.
+            let ref mut signal = sync.0.get_mut(&semaphore.points.start).unwrap().signal;
+            let index = signal
+                .iter()
+                .position(|signal| signal.0 == semaphore)
+                .unwrap();
+            signal.swap_remove(index);
+        }
+    }
+}
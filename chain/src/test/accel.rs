@@ -0,0 +1,95 @@
+use resource::{AccelerationStructure, AccelerationStructureUsageFlags, Resource};
+use sync::sync;
+
+use super::*;
+
+fn accel_node(
+    id: usize,
+    dependencies: Vec<usize>,
+    access: AccessFlags,
+    stages: PipelineStageFlags,
+    usage: AccelerationStructureUsageFlags,
+) -> Node {
+    let state: State<AccelerationStructure> = State {
+        access,
+        layout: (),
+        stages,
+        usage,
+    };
+    Node {
+        id,
+        family: FamilyId(0),
+        dependencies,
+        buffers: HashMap::new(),
+        buffer_ranges: HashMap::new(),
+        subsequent_buffers: HashMap::new(),
+        images: HashMap::new(),
+        subsequent_images: HashMap::new(),
+        image_aspects: HashMap::new(),
+        acceleration_structures: Some((Id(0), state)).into_iter().collect(),
+        render_pass: None,
+        upload: false,
+        metadata: None,
+    }
+}
+
+#[test]
+fn build_then_trace() {
+    let chains = single_queue(vec![
+        accel_node(
+            0,
+            vec![],
+            AccessFlags::ACCELERATION_STRUCTURE_WRITE_NV,
+            PipelineStageFlags::ACCELERATION_STRUCTURE_BUILD_NV,
+            AccelerationStructureUsageFlags::BUILD_DST,
+        ),
+        accel_node(
+            1,
+            vec![0],
+            AccessFlags::ACCELERATION_STRUCTURE_READ_NV,
+            PipelineStageFlags::RAY_TRACING_SHADER_NV,
+            AccelerationStructureUsageFlags::TRACE,
+        ),
+    ]);
+    assert!(chains.buffers.is_empty());
+    assert!(chains.images.is_empty());
+
+    let ref chain = chains.acceleration_structures[&Id(0)];
+    assert_eq!(chain.len(), 2);
+    assert_eq!(
+        chain.usage(),
+        AccelerationStructureUsageFlags::BUILD_DST | AccelerationStructureUsageFlags::TRACE
+    );
+    assert!(AccelerationStructure::valid_usage(
+        chain.links()[1].access(),
+        chain.usage()
+    ));
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let ref barrier = submission_for(&schedule, 1)
+        .sync()
+        .acquire
+        .acceleration_structures[&Id(0)];
+    assert_eq!(
+        barrier.states.start.0,
+        AccessFlags::ACCELERATION_STRUCTURE_WRITE_NV
+    );
+    assert_eq!(
+        barrier.states.start.2,
+        PipelineStageFlags::ACCELERATION_STRUCTURE_BUILD_NV
+    );
+    assert_eq!(
+        barrier.states.end.0,
+        AccessFlags::ACCELERATION_STRUCTURE_READ_NV
+    );
+    assert_eq!(
+        barrier.states.end.2,
+        PipelineStageFlags::RAY_TRACING_SHADER_NV
+    );
+    assert!(barrier.queues.is_none());
+}
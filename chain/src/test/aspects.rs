@@ -0,0 +1,210 @@
+use rendy_resource::image::{self, ImageAspectFlags};
+use rendy_resource::DeviceFeatures;
+
+use collect::{collect_with, CollectOptions};
+use node::ImageAspects;
+use resource::Image;
+use sync::sync;
+
+use super::*;
+
+const DEPTH: Id = Id(1);
+const STENCIL: Id = Id(2);
+
+fn aspect_state(
+    access: AccessFlags,
+    layout: image::Layout,
+    stages: PipelineStageFlags,
+) -> State<Image> {
+    State {
+        access,
+        layout,
+        stages,
+        usage: image::UsageFlags::DEPTH_STENCIL_ATTACHMENT | image::UsageFlags::SAMPLED,
+    }
+}
+
+fn attachment(layout: image::Layout) -> State<Image> {
+    aspect_state(
+        AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+        layout,
+        PipelineStageFlags::EARLY_FRAGMENT_TESTS | PipelineStageFlags::LATE_FRAGMENT_TESTS,
+    )
+}
+
+fn aspects_node(
+    id: usize,
+    dependencies: Vec<usize>,
+    depth: State<Image>,
+    stencil: State<Image>,
+    stencil_aspects: ImageAspectFlags,
+) -> Node {
+    Node {
+        id,
+        family: FamilyId(0),
+        dependencies,
+        buffers: HashMap::new(),
+        buffer_ranges: HashMap::new(),
+        subsequent_buffers: HashMap::new(),
+        images: vec![(DEPTH, depth), (STENCIL, stencil)]
+            .into_iter()
+            .collect(),
+        subsequent_images: HashMap::new(),
+        image_aspects: vec![
+            (
+                DEPTH,
+                ImageAspects {
+                    image: Id(0),
+                    aspects: ImageAspectFlags::DEPTH,
+                },
+            ),
+            (
+                STENCIL,
+                ImageAspects {
+                    image: Id(0),
+                    aspects: stencil_aspects,
+                },
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        acceleration_structures: HashMap::new(),
+        render_pass: None,
+        upload: false,
+        metadata: None,
+    }
+}
+
+fn collect_aspects(nodes: Vec<Node>, separate_depth_stencil_layouts: bool) -> Chains {
+    collect_with(
+        nodes,
+        |_| 1,
+        CollectOptions {
+            features: DeviceFeatures {
+                separate_depth_stencil_layouts,
+                ..DeviceFeatures::default()
+            },
+            ..CollectOptions::default()
+        },
+    )
+}
+
+fn read_depth_write_stencil_nodes() -> Vec<Node> {
+    vec![
+        aspects_node(
+            0,
+            vec![],
+            attachment(image::Layout::DepthAttachmentOptimal),
+            attachment(image::Layout::StencilAttachmentOptimal),
+            ImageAspectFlags::STENCIL,
+        ),
+        aspects_node(
+            1,
+            vec![0],
+            aspect_state(
+                AccessFlags::SHADER_READ,
+                image::Layout::DepthReadOnlyOptimal,
+                PipelineStageFlags::FRAGMENT_SHADER,
+            ),
+            attachment(image::Layout::StencilAttachmentOptimal),
+            ImageAspectFlags::STENCIL,
+        ),
+    ]
+}
+
+#[test]
+fn read_depth_write_stencil() {
+    let chains = collect_aspects(read_depth_write_stencil_nodes(), true);
+    assert_eq!(
+        chains.image_aspects[&DEPTH].aspects,
+        ImageAspectFlags::DEPTH
+    );
+    assert_eq!(
+        chains.image_aspects[&STENCIL].aspects,
+        ImageAspectFlags::STENCIL
+    );
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let ref acquire = submission_for(&schedule, 1).sync().acquire;
+    assert_eq!(acquire.images.len(), 2);
+
+    let ref depth = acquire.images[&DEPTH];
+    assert!(depth.is_layout_transition());
+    assert_eq!(depth.states.start.1, image::Layout::DepthAttachmentOptimal);
+    assert_eq!(depth.states.end.1, image::Layout::DepthReadOnlyOptimal);
+    assert_eq!(depth.states.end.0, AccessFlags::SHADER_READ);
+
+    // Stencil stays in its own layout while depth is transitioned.
+    let ref stencil = acquire.images[&STENCIL];
+    assert!(!stencil.is_layout_transition());
+    assert_eq!(
+        stencil.states.end.1,
+        image::Layout::StencilAttachmentOptimal
+    );
+    assert_eq!(
+        stencil.states.end.0,
+        AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE
+    );
+}
+
+#[test]
+#[should_panic(expected = "while device doesn't support separate depth-stencil layouts")]
+fn split_aspects_without_feature() {
+    collect_aspects(read_depth_write_stencil_nodes(), false);
+}
+
+#[test]
+fn combined_layouts_without_feature() {
+    let depth_stencil = |id, dependencies, state| {
+        let mut node = node(id, dependencies, vec![]);
+        node.images.insert(Id(0), state);
+        node
+    };
+    let nodes = vec![
+        depth_stencil(0, vec![], attachment(image::Layout::DepthAttachmentOptimal)),
+        depth_stencil(
+            1,
+            vec![0],
+            aspect_state(
+                AccessFlags::SHADER_READ,
+                image::Layout::DepthReadOnlyOptimal,
+                PipelineStageFlags::FRAGMENT_SHADER,
+            ),
+        ),
+    ];
+    let chains = collect_aspects(nodes, false);
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    // Whole image is transitioned between combined layouts.
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.images[&Id(0)];
+    assert_eq!(
+        barrier.states.start.1,
+        image::Layout::DepthStencilAttachmentOptimal
+    );
+    assert_eq!(
+        barrier.states.end.1,
+        image::Layout::DepthStencilReadOnlyOptimal
+    );
+}
+
+#[test]
+#[should_panic(expected = "are tracked by both")]
+fn overlapping_aspects() {
+    single_queue(vec![aspects_node(
+        0,
+        vec![],
+        attachment(image::Layout::DepthAttachmentOptimal),
+        attachment(image::Layout::DepthStencilAttachmentOptimal),
+        ImageAspectFlags::DEPTH | ImageAspectFlags::STENCIL,
+    )]);
+}
@@ -0,0 +1,143 @@
+use batch::{barrier_set, GlobalBarrier, SyncPoint};
+use schedule::{QueueId, SubmissionId};
+use sync::{sync, Placement};
+
+use super::*;
+
+#[test]
+fn resources_at_same_point_share_batch() {
+    let chains = single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![
+                (
+                    0,
+                    buffer_state(
+                        AccessFlags::SHADER_WRITE,
+                        PipelineStageFlags::COMPUTE_SHADER,
+                    ),
+                ),
+                (
+                    1,
+                    buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+                ),
+                (
+                    2,
+                    buffer_state(
+                        AccessFlags::SHADER_WRITE,
+                        PipelineStageFlags::COMPUTE_SHADER,
+                    ),
+                ),
+            ],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![
+                (
+                    0,
+                    buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER),
+                ),
+                (
+                    1,
+                    buffer_state(
+                        AccessFlags::VERTEX_ATTRIBUTE_READ,
+                        PipelineStageFlags::VERTEX_INPUT,
+                    ),
+                ),
+                (
+                    2,
+                    buffer_state(
+                        AccessFlags::UNIFORM_READ,
+                        PipelineStageFlags::FRAGMENT_SHADER,
+                    ),
+                ),
+            ],
+        ),
+    ]);
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let set = barrier_set(&schedule);
+    assert_eq!(set.len(), 1);
+
+    let point = SyncPoint {
+        submission: SubmissionId::new(QueueId::new(FamilyId(0), 0), 1),
+        placement: Placement::Acquire,
+    };
+    let batch = set.get(point).unwrap();
+    assert_eq!(batch.len(), 3);
+    assert_eq!(
+        batch.buffers.iter().map(|&(id, _)| id).collect::<Vec<_>>(),
+        vec![Id(0), Id(1), Id(2)]
+    );
+    assert_eq!(
+        batch.stages.start,
+        PipelineStageFlags::COMPUTE_SHADER | PipelineStageFlags::TRANSFER
+    );
+    assert_eq!(
+        batch.stages.end,
+        PipelineStageFlags::VERTEX_SHADER
+            | PipelineStageFlags::VERTEX_INPUT
+            | PipelineStageFlags::FRAGMENT_SHADER
+    );
+    assert!(batch.dependencies.is_empty());
+}
+
+#[test]
+fn identical_buffer_transitions_collapse_to_global_barrier() {
+    let write = buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    );
+    let read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER);
+    let other = buffer_state(
+        AccessFlags::UNIFORM_READ,
+        PipelineStageFlags::FRAGMENT_SHADER,
+    );
+    let chains = single_queue(vec![
+        node(0, vec![], (0..6).map(|id| (id, write)).collect()),
+        node(
+            1,
+            vec![0],
+            (0..5)
+                .map(|id| (id, read))
+                .chain(Some((5, other)))
+                .collect(),
+        ),
+    ]);
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let set = barrier_set(&schedule);
+    let point = SyncPoint {
+        submission: SubmissionId::new(QueueId::new(FamilyId(0), 0), 1),
+        placement: Placement::Acquire,
+    };
+    let batch = set.get(point).unwrap();
+    assert_eq!(
+        batch.global,
+        vec![GlobalBarrier {
+            states: (
+                AccessFlags::SHADER_WRITE,
+                PipelineStageFlags::COMPUTE_SHADER
+            )..(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER),
+            buffers: (0..5).map(Id).collect(),
+        }]
+    );
+    // Buffer with different transition keeps its own barrier.
+    assert_eq!(
+        batch.buffers.iter().map(|&(id, _)| id).collect::<Vec<_>>(),
+        vec![Id(5)]
+    );
+    assert_eq!(batch.len(), 2);
+}
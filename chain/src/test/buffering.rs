@@ -0,0 +1,42 @@
+use super::*;
+
+fn read() -> State<Buffer> {
+    buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER)
+}
+
+fn write() -> State<Buffer> {
+    buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    )
+}
+
+#[test]
+fn ping_pong_double_buffered() {
+    let chains = single_queue(vec![
+        node(0, vec![], vec![(0, read())]),
+        node(1, vec![0], vec![(0, write())]),
+    ]);
+    let ref chain = chains.buffers[&Id(0)];
+    assert!(chain.reads_previous_frame());
+    assert_eq!(chain.required_copies(1), 2);
+    assert_eq!(chain.required_copies(2), 2);
+    assert_eq!(chain.required_copies(3), 3);
+}
+
+#[test]
+fn frame_local_and_read_only() {
+    let chains = single_queue(vec![
+        node(0, vec![], vec![(0, write()), (1, read())]),
+        node(1, vec![0], vec![(0, read()), (1, read())]),
+    ]);
+
+    let ref local = chains.buffers[&Id(0)];
+    assert!(!local.reads_previous_frame());
+    assert_eq!(local.required_copies(1), 1);
+    assert_eq!(local.required_copies(2), 2);
+
+    let ref read_only = chains.buffers[&Id(1)];
+    assert!(!read_only.reads_previous_frame());
+    assert_eq!(read_only.required_copies(2), 1);
+}
@@ -0,0 +1,140 @@
+use batch::{barrier_set, BarrierBatch, BarrierSet, SyncPoint};
+use compiled::{compile, BarrierRecord, InternalBarriers, Replay};
+use schedule::SubmissionId;
+use sync::{sync, Placement};
+
+use super::*;
+
+/// Logs recorded commands.
+/// Resources map is ignored as only ids are compared.
+#[derive(Debug, Default)]
+struct Log(Vec<String>);
+
+impl Replay<()> for Log {
+    fn pipeline_barrier(&mut self, record: BarrierRecord<'_>, _resources: &()) {
+        self.0.push(format!(
+            "barrier {:?} {:?} {:?} {:?} {:?}",
+            record.stages, record.dependencies, record.global, record.buffers, record.images
+        ));
+    }
+
+    fn submission(
+        &mut self,
+        submission: SubmissionId,
+        node: usize,
+        internal: InternalBarriers<'_>,
+        resources: &(),
+    ) {
+        self.0.push(format!("submit {:?} {}", submission, node));
+        for step in 0..internal.len() {
+            if let Some(record) = internal.get(step) {
+                self.pipeline_barrier(record, resources);
+            }
+        }
+    }
+}
+
+fn chains() -> Chains {
+    let write = buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    );
+    let read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER);
+    single_queue(vec![
+        node(0, vec![], vec![(0, write), (1, write)]),
+        node(1, vec![0], vec![(0, read), (1, read)]),
+        node(2, vec![1], vec![(0, write)]),
+        node(3, vec![2], vec![(0, read), (1, read)]),
+    ])
+}
+
+fn record_batch(log: &mut Log, batch: &BarrierBatch) {
+    let record = BarrierRecord {
+        stages: batch.stages.clone(),
+        dependencies: batch.dependencies,
+        global: &batch.global,
+        buffers: &batch.buffers,
+        images: &batch.images,
+        acceleration_structures: &batch.acceleration_structures,
+    };
+    log.pipeline_barrier(record, &());
+}
+
+fn record_point(log: &mut Log, set: &BarrierSet, submission: SubmissionId, placement: Placement) {
+    let point = SyncPoint {
+        submission,
+        placement,
+    };
+    if let Some(batch) = set.get(point) {
+        record_batch(log, batch);
+    }
+}
+
+/// Record the schedule looking up barriers of each point while recording.
+fn record_live(schedule: &Synced) -> Log {
+    let set = barrier_set(schedule);
+    let mut live = Log::default();
+    for submission in schedule.ordered() {
+        record_point(&mut live, &set, submission.id(), Placement::Acquire);
+        live.0.push(format!(
+            "submit {:?} {}",
+            submission.id(),
+            submission.node()
+        ));
+        for batch in set.internal(submission.id()) {
+            if !batch.is_empty() {
+                record_batch(&mut live, batch);
+            }
+        }
+        record_point(&mut live, &set, submission.id(), Placement::Release);
+    }
+    live
+}
+
+#[test]
+fn replay_matches_live_path() {
+    let chains = chains();
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let live = record_live(&schedule);
+
+    let compiled = compile(&schedule);
+    assert_eq!(compiled.submissions(), 4);
+    assert_eq!(compiled.pipeline_barriers(), barrier_set(&schedule).len());
+    assert!(compiled.pipeline_barriers() > 0);
+
+    let mut replayed = Log::default();
+    compiled.record(&mut replayed, &());
+    assert_eq!(replayed.0, live.0);
+
+    // Replay is repeatable.
+    let mut again = Log::default();
+    compiled.record(&mut again, &());
+    assert_eq!(again.0, replayed.0);
+}
+
+#[test]
+fn internal_barriers_are_replayed() {
+    let write = buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER);
+    let read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER);
+    let mut write_then_read = node(0, vec![], vec![(0, write)]);
+    write_then_read.subsequent_buffers.insert(Id(0), vec![read]);
+    let chains = single_queue(vec![write_then_read]);
+    let schedule: Synced = sync(&chains, || (0, 0));
+
+    let set = barrier_set(&schedule);
+    let sid = submission_for(&schedule, 0).id();
+    assert_eq!(set.internal(sid).len(), 1);
+    assert_eq!(set.internal(sid)[0].buffers[0].0, Id(0));
+
+    let compiled = compile(&schedule);
+    assert_eq!(compiled.pipeline_barriers(), 1);
+    let mut replayed = Log::default();
+    compiled.record(&mut replayed, &());
+    assert_eq!(replayed.0, record_live(&schedule).0);
+    assert_eq!(replayed.0.len(), 2);
+}
@@ -0,0 +1,32 @@
+use critical::critical_path;
+use schedule::SubmissionId;
+
+use super::*;
+
+#[test]
+fn heavy_branch_is_critical() {
+    let write = buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    );
+    let read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER);
+    let chains = single_queue(vec![
+        node(0, vec![], vec![(0, write), (1, write)]),
+        // Long branch of light passes.
+        node(1, vec![0], vec![(0, read), (2, write)]),
+        node(2, vec![1], vec![(2, read), (3, write)]),
+        node(3, vec![2], vec![(3, read)]),
+        // Short branch of single heavy pass.
+        node(4, vec![0], vec![(1, read)]),
+    ]);
+    let ref schedule = chains.schedule;
+    let nodes = |path: Vec<SubmissionId>| -> Vec<usize> {
+        path.into_iter().map(|sid| schedule[sid].node()).collect()
+    };
+
+    assert_eq!(nodes(critical_path(&chains, |_| 1)), vec![0, 1, 2, 3]);
+
+    let durations = [100, 100, 100, 100, 1000];
+    let path = critical_path(&chains, |sid| durations[schedule[sid].node()]);
+    assert_eq!(nodes(path), vec![0, 4]);
+}
@@ -0,0 +1,132 @@
+use dead::{dead_writes, DeadWrite};
+use schedule::SubmissionId;
+
+use super::*;
+
+fn sid(chains: &Chains, node: usize) -> SubmissionId {
+    submission_for(&chains.schedule, node).id()
+}
+
+#[test]
+fn texture_written_never_sampled() {
+    let chains = single_queue(vec![
+        with_images(node(0, vec![], vec![]), vec![(10, render())]),
+        node(
+            1,
+            vec![],
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER,
+                ),
+            )],
+        ),
+        with_images(
+            node(
+                2,
+                vec![1],
+                vec![(
+                    0,
+                    buffer_state(
+                        AccessFlags::SHADER_READ,
+                        PipelineStageFlags::FRAGMENT_SHADER,
+                    ),
+                )],
+            ),
+            vec![(11, render())],
+        ),
+    ]);
+
+    let dead = dead_writes(&chains, &[Id(11)]);
+    assert_eq!(
+        dead.images,
+        vec![DeadWrite {
+            resource: Id(10),
+            link: 0,
+        }]
+    );
+    assert!(dead.buffers.is_empty());
+    assert_eq!(dead.submissions, vec![sid(&chains, 0)]);
+}
+
+#[test]
+fn culling_propagates_to_producers() {
+    let chains = single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER,
+                ),
+            )],
+        ),
+        with_images(
+            node(
+                1,
+                vec![0],
+                vec![(
+                    0,
+                    buffer_state(
+                        AccessFlags::SHADER_READ,
+                        PipelineStageFlags::FRAGMENT_SHADER,
+                    ),
+                )],
+            ),
+            vec![(10, render())],
+        ),
+    ]);
+
+    let dead = dead_writes(&chains, &[]);
+    assert_eq!(
+        dead.buffers,
+        vec![DeadWrite {
+            resource: Id(0),
+            link: 0,
+        }]
+    );
+    assert_eq!(
+        dead.images,
+        vec![DeadWrite {
+            resource: Id(10),
+            link: 0,
+        }]
+    );
+    let mut expected = vec![sid(&chains, 0), sid(&chains, 1)];
+    expected.sort();
+    assert_eq!(dead.submissions, expected);
+}
+
+#[test]
+fn sampled_texture_is_live() {
+    let chains = single_queue(vec![
+        with_images(node(0, vec![], vec![]), vec![(10, render())]),
+        with_images(node(1, vec![0], vec![]), vec![(10, sample())]),
+    ]);
+
+    let dead = dead_writes(&chains, &[]);
+    assert!(dead.is_empty());
+    assert!(dead.submissions.is_empty());
+}
+
+#[test]
+fn overwritten_before_read() {
+    let chains = single_queue(vec![
+        with_images(node(0, vec![], vec![]), vec![(10, render())]),
+        with_images(node(1, vec![0], vec![]), vec![(10, render())]),
+        with_images(node(2, vec![1], vec![]), vec![(10, sample())]),
+    ]);
+
+    let dead = dead_writes(&chains, &[]);
+    assert_eq!(
+        dead.images,
+        vec![DeadWrite {
+            resource: Id(10),
+            link: 0,
+        }]
+    );
+    assert_eq!(dead.submissions, vec![sid(&chains, 0)]);
+}
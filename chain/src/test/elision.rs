@@ -0,0 +1,118 @@
+use rendy_resource::image;
+
+use chain::{Link, LinkNode};
+use resource::Image;
+use schedule::{QueueId, SubmissionId};
+use sync::{sync, Barrier};
+
+use super::*;
+
+fn image_state(access: AccessFlags, layout: image::Layout) -> State<Image> {
+    State {
+        access,
+        layout,
+        stages: PipelineStageFlags::FRAGMENT_SHADER,
+        usage: image::UsageFlags::SAMPLED,
+    }
+}
+
+fn image_node(id: usize, dependencies: Vec<usize>, state: State<Image>) -> Node {
+    Node {
+        id,
+        family: FamilyId(0),
+        dependencies,
+        buffers: HashMap::new(),
+        buffer_ranges: HashMap::new(),
+        subsequent_buffers: HashMap::new(),
+        images: Some((Id(0), state)).into_iter().collect(),
+        subsequent_images: HashMap::new(),
+        image_aspects: HashMap::new(),
+        acceleration_structures: HashMap::new(),
+        render_pass: None,
+        upload: false,
+        metadata: None,
+    }
+}
+
+fn sampled() -> State<Image> {
+    image_state(
+        AccessFlags::SHADER_READ,
+        image::Layout::ShaderReadOnlyOptimal,
+    )
+}
+
+fn link(index: usize, state: State<Image>) -> Link<Image> {
+    Link::new(LinkNode {
+        sid: SubmissionId::new(QueueId::new(FamilyId(0), 0), index),
+        state,
+        render_pass: None,
+        range: None,
+    })
+}
+
+#[test]
+fn sampled_reads_share_link() {
+    let chains = single_queue(vec![
+        image_node(0, vec![], sampled()),
+        image_node(1, vec![0], sampled()),
+    ]);
+    assert_eq!(chains.images[&Id(0)].len(), 1);
+
+    let mut counter = 0;
+    let schedule = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let barriers = schedule
+        .iter()
+        .flat_map(|family| family.iter())
+        .flat_map(|queue| queue.iter())
+        .flat_map(|submission| {
+            let sync = submission.sync();
+            sync.acquire
+                .images
+                .values()
+                .chain(sync.release.images.values())
+        })
+        .collect::<Vec<_>>();
+    assert!(barriers.len() <= 1);
+    assert!(barriers.iter().all(|barrier| barrier.is_execution_only()));
+}
+
+#[test]
+fn read_links_at_same_layout_need_execution_only() {
+    let barrier = Barrier::between(&link(0, sampled()), &link(1, sampled()));
+    assert!(barrier.is_execution_only());
+    assert!(!barrier.is_layout_transition());
+}
+
+#[test]
+fn reads_at_different_layouts_transition() {
+    let chains = single_queue(vec![
+        image_node(0, vec![], sampled()),
+        image_node(
+            1,
+            vec![0],
+            image_state(
+                AccessFlags::TRANSFER_READ,
+                image::Layout::TransferSrcOptimal,
+            ),
+        ),
+    ]);
+    assert_eq!(chains.images[&Id(0)].len(), 2);
+
+    let mut counter = 0;
+    let schedule = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    // Layout transition overwrites the image, so it is placed as a write-after-read barrier.
+    let ref barrier = submission_for(&schedule, 0).sync().release.images[&Id(0)];
+    assert!(barrier.is_layout_transition());
+    assert!(!barrier.is_execution_only());
+    // Nothing was written, so there is nothing to make available.
+    assert_eq!(barrier.states.start.0, AccessFlags::empty());
+    assert_eq!(barrier.states.end.0, AccessFlags::TRANSFER_READ);
+}
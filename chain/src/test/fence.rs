@@ -0,0 +1,66 @@
+use collect::collect;
+use fence::{frame_fences, FrameFences};
+
+use super::*;
+
+fn write() -> State<Buffer> {
+    buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    )
+}
+
+fn read() -> State<Buffer> {
+    buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER)
+}
+
+/// Node `0` writes buffer `0` read back by host in node `2`
+/// and buffer `1` processed on the other queue by node `1`.
+/// Node `3` consumes result of node `1` if `join` is set.
+fn frame(join: bool) -> Chains {
+    let host_read = buffer_state(AccessFlags::HOST_READ, PipelineStageFlags::HOST);
+    let last = if join {
+        vec![(2, read())]
+    } else {
+        vec![(3, write())]
+    };
+    collect(
+        vec![
+            on_family(0, vec![], 0, vec![(0, write()), (1, write())]),
+            on_family(1, vec![0], 1, vec![(1, read()), (2, write())]),
+            on_family(2, vec![0], 0, vec![(0, host_read)]),
+            on_family(3, vec![1, 2], 0, last),
+        ],
+        |_| 1,
+    )
+}
+
+#[test]
+fn readback_and_joined_frame_completion() {
+    let chains = frame(true);
+    let sid = |node| submission_for(&chains.schedule, node).id();
+
+    let fences = frame_fences(&chains);
+    assert_eq!(
+        fences,
+        FrameFences {
+            readbacks: vec![sid(0)],
+            // Last submission of the other queue is waited for by node `3`.
+            completion: vec![sid(3)],
+        }
+    );
+    assert_eq!(fences.len(), 2);
+}
+
+#[test]
+fn independent_queues_fenced_separately() {
+    let chains = frame(false);
+    let sid = |node| submission_for(&chains.schedule, node).id();
+
+    let fences = frame_fences(&chains);
+    assert_eq!(fences.readbacks, vec![sid(0)]);
+    let mut completion = vec![sid(1), sid(3)];
+    completion.sort();
+    assert_eq!(fences.completion, completion);
+    assert_eq!(fences.submissions().len(), 3);
+}
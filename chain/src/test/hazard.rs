@@ -0,0 +1,45 @@
+use sync::sync;
+
+use super::*;
+
+#[test]
+fn write_after_write_barrier() {
+    let chains = single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER,
+                ),
+            )],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER,
+                ),
+            )],
+        ),
+    ]);
+    assert_eq!(chains.buffers[&Id(0)].len(), 2);
+
+    let mut counter = 0;
+    let schedule = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.states.start.0, AccessFlags::SHADER_WRITE);
+    assert_eq!(barrier.states.end.0, AccessFlags::SHADER_WRITE);
+    assert_eq!(barrier.states.start.2, PipelineStageFlags::COMPUTE_SHADER);
+    assert_eq!(barrier.states.end.2, PipelineStageFlags::COMPUTE_SHADER);
+    assert!(barrier.queues.is_none());
+}
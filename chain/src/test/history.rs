@@ -0,0 +1,119 @@
+use history::{history_dependency, History, HistoryError, HistoryFrame};
+
+use super::*;
+
+fn read(stages: PipelineStageFlags) -> State<Buffer> {
+    buffer_state(AccessFlags::SHADER_READ, stages)
+}
+
+fn write(stages: PipelineStageFlags) -> State<Buffer> {
+    buffer_state(AccessFlags::SHADER_WRITE, stages)
+}
+
+/// Scene is rendered into buffer `0`,
+/// resolve pass blends it with previous version `2` of history into current version `1`
+/// and post-processing reads current version.
+fn taa() -> Chains {
+    single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(0, write(PipelineStageFlags::COMPUTE_SHADER))],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![
+                (0, read(PipelineStageFlags::COMPUTE_SHADER)),
+                (2, read(PipelineStageFlags::FRAGMENT_SHADER)),
+                (1, write(PipelineStageFlags::COMPUTE_SHADER)),
+            ],
+        ),
+        node(
+            2,
+            vec![1],
+            vec![(1, read(PipelineStageFlags::FRAGMENT_SHADER))],
+        ),
+    ])
+}
+
+#[test]
+fn versions_rotate() {
+    let history = History::new(Id(1), Id(2), 2);
+    assert_eq!(
+        history.frame(0),
+        HistoryFrame {
+            current: 0,
+            previous: None,
+            reuse_after: None,
+        }
+    );
+    assert_eq!(
+        history.frame(1),
+        HistoryFrame {
+            current: 1,
+            previous: Some(0),
+            reuse_after: None,
+        }
+    );
+    // Frame `1` read resource `0` as previous version.
+    assert_eq!(
+        history.frame(2),
+        HistoryFrame {
+            current: 0,
+            previous: Some(1),
+            reuse_after: Some(1),
+        }
+    );
+
+    let history = History::new(Id(1), Id(2), 3);
+    assert_eq!(
+        history.frame(4),
+        HistoryFrame {
+            current: 1,
+            previous: Some(0),
+            reuse_after: Some(2),
+        }
+    );
+}
+
+#[test]
+fn taa_reads_previous_while_writing_current() {
+    let chains = taa();
+    let history = History::new(Id(1), Id(2), 2);
+    let resolve = submission_for(&chains.schedule, 1).id();
+
+    let dependency = history_dependency(&chains, &history).unwrap();
+    assert_eq!(dependency.writers, vec![resolve]);
+    assert_eq!(dependency.write_stages, PipelineStageFlags::COMPUTE_SHADER);
+    assert_eq!(dependency.readers, vec![resolve]);
+    assert_eq!(dependency.read_stages, PipelineStageFlags::FRAGMENT_SHADER);
+
+    // Versions are tracked separately, so neither chain carries content between frames.
+    assert!(!chains.buffers[&Id(1)].reads_previous_frame());
+    assert!(!chains.buffers[&Id(2)].reads_previous_frame());
+}
+
+#[test]
+fn previous_version_is_read_only() {
+    let chains = single_queue(vec![node(
+        0,
+        vec![],
+        vec![
+            (1, write(PipelineStageFlags::COMPUTE_SHADER)),
+            (2, write(PipelineStageFlags::COMPUTE_SHADER)),
+        ],
+    )]);
+    assert_eq!(
+        history_dependency(&chains, &History::new(Id(1), Id(2), 2)),
+        Err(HistoryError::PreviousWritten(Id(2)))
+    );
+    assert_eq!(
+        history_dependency(&chains, &History::new(Id(3), Id(1), 2)),
+        Err(HistoryError::PreviousWritten(Id(1)))
+    );
+    assert_eq!(
+        history_dependency(&chains, &History::new(Id(3), Id(4), 2)),
+        Err(HistoryError::NotWritten(Id(3)))
+    );
+}
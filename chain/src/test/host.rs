@@ -0,0 +1,98 @@
+use collect::collect;
+use sync::sync;
+
+use super::*;
+
+#[test]
+fn compute_write_host_read() {
+    let chains = single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER,
+                ),
+            )],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![(
+                0,
+                buffer_state(AccessFlags::HOST_READ, PipelineStageFlags::HOST),
+            )],
+        ),
+    ]);
+    assert!(chains.buffers[&Id(0)].links()[1].host_access());
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let producer = submission_for(&schedule, 0).sync();
+    assert!(producer.fence);
+    let ref barrier = producer.release.buffers[&Id(0)];
+    assert_eq!(barrier.states.start.0, AccessFlags::SHADER_WRITE);
+    assert_eq!(barrier.states.start.2, PipelineStageFlags::COMPUTE_SHADER);
+    assert_eq!(barrier.states.end.0, AccessFlags::HOST_READ);
+    assert_eq!(barrier.states.end.2, PipelineStageFlags::HOST);
+
+    let consumer = submission_for(&schedule, 1).sync();
+    assert!(consumer.acquire.buffers.is_empty());
+    assert!(consumer.wait.is_empty());
+    assert!(!consumer.fence);
+}
+
+#[test]
+fn host_write_after_multiple_queues() {
+    let read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER);
+    let chains = collect(
+        vec![
+            node(
+                0,
+                vec![],
+                vec![(
+                    0,
+                    buffer_state(
+                        AccessFlags::SHADER_WRITE,
+                        PipelineStageFlags::COMPUTE_SHADER,
+                    ),
+                )],
+            ),
+            node(1, vec![0], vec![(0, read)]),
+            node(2, vec![0], vec![(0, read)]),
+            node(
+                3,
+                vec![1, 2],
+                vec![(
+                    0,
+                    buffer_state(AccessFlags::HOST_WRITE, PipelineStageFlags::HOST),
+                )],
+            ),
+        ],
+        |_| 2,
+    );
+    let ref links = chains.buffers[&Id(0)].links();
+    assert!(!links[1].single_queue());
+    assert!(links[2].host_access());
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    // Host waits for fences of each queue reading the buffer.
+    for &node in &[1, 2] {
+        let reader = submission_for(&schedule, node).sync();
+        assert!(reader.fence);
+        let ref barrier = reader.release.buffers[&Id(0)];
+        assert_eq!(barrier.states.start.0, AccessFlags::SHADER_READ);
+        assert_eq!(barrier.states.end.0, AccessFlags::HOST_WRITE);
+    }
+}
@@ -0,0 +1,68 @@
+use sync::sync;
+
+use super::*;
+
+fn compute_then_indirect(stages: PipelineStageFlags) -> Synced {
+    let chains = single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER,
+                ),
+            )],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![(0, buffer_state(AccessFlags::INDIRECT_COMMAND_READ, stages))],
+        ),
+    ]);
+    assert_eq!(chains.buffers[&Id(0)].len(), 2);
+
+    let mut counter = 0;
+    sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    })
+}
+
+#[test]
+fn compute_write_indirect_draw() {
+    let schedule = compute_then_indirect(PipelineStageFlags::DRAW_INDIRECT);
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.states.start.0, AccessFlags::SHADER_WRITE);
+    assert_eq!(barrier.states.start.2, PipelineStageFlags::COMPUTE_SHADER);
+    assert_eq!(barrier.states.end.0, AccessFlags::INDIRECT_COMMAND_READ);
+    assert_eq!(barrier.states.end.2, PipelineStageFlags::DRAW_INDIRECT);
+}
+
+#[test]
+fn indirect_read_implies_draw_indirect_stage() {
+    // Node declares only shader stage where arguments are used afterwards.
+    let schedule = compute_then_indirect(PipelineStageFlags::VERTEX_SHADER);
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.states.start.2, PipelineStageFlags::COMPUTE_SHADER);
+    assert_eq!(
+        barrier.states.end.2,
+        PipelineStageFlags::DRAW_INDIRECT | PipelineStageFlags::VERTEX_SHADER
+    );
+}
+
+#[test]
+fn access_required_stages() {
+    assert_eq!(
+        AccessFlags::INDIRECT_COMMAND_READ.required_stages(),
+        PipelineStageFlags::DRAW_INDIRECT
+    );
+    assert_eq!(
+        (AccessFlags::INDEX_READ | AccessFlags::TRANSFER_WRITE).required_stages(),
+        PipelineStageFlags::VERTEX_INPUT | PipelineStageFlags::TRANSFER
+    );
+    assert!(AccessFlags::SHADER_WRITE.required_stages().is_empty());
+}
@@ -0,0 +1,74 @@
+use rendy_resource::image;
+
+use initial::{validate_initial_layouts, UninitializedRead};
+use resource::Image;
+
+use super::*;
+
+fn blend() -> State<Image> {
+    texture_state(
+        AccessFlags::COLOR_ATTACHMENT_READ | AccessFlags::COLOR_ATTACHMENT_WRITE,
+        image::Layout::ColorAttachmentOptimal,
+    )
+}
+
+#[test]
+fn sampling_undefined_image_is_flagged() {
+    let chains = single_queue(vec![
+        with_images(node(0, vec![], vec![]), vec![(10, sample())]),
+        with_images(node(1, vec![0], vec![]), vec![(10, render())]),
+    ]);
+
+    assert_eq!(
+        validate_initial_layouts(&chains),
+        vec![UninitializedRead {
+            resource: Id(10),
+            link: 0,
+            submission: submission_for(&chains.schedule, 0).id(),
+        }]
+    );
+}
+
+#[test]
+fn declared_initial_layout_defines_content() {
+    let mut chains = single_queue(vec![with_images(
+        node(0, vec![], vec![]),
+        vec![(10, sample())],
+    )]);
+    chains.set_initial_layout(Id(10), image::Layout::ShaderReadOnlyOptimal);
+
+    assert_eq!(validate_initial_layouts(&chains), vec![]);
+}
+
+#[test]
+fn declared_undefined_layout_is_flagged() {
+    let mut chains = single_queue(vec![with_images(
+        node(0, vec![], vec![]),
+        vec![(10, sample())],
+    )]);
+    chains.set_initial_layout(Id(10), image::Layout::Undefined);
+
+    assert_eq!(validate_initial_layouts(&chains).len(), 1);
+}
+
+#[test]
+fn read_after_write_is_not_flagged() {
+    let chains = single_queue(vec![
+        with_images(node(0, vec![], vec![]), vec![(10, render())]),
+        with_images(node(1, vec![0], vec![]), vec![(10, sample())]),
+    ]);
+
+    assert_eq!(validate_initial_layouts(&chains), vec![]);
+}
+
+#[test]
+fn first_write_that_reads_is_flagged() {
+    let chains = single_queue(vec![with_images(
+        node(0, vec![], vec![]),
+        vec![(10, blend())],
+    )]);
+
+    let errors = validate_initial_layouts(&chains);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].link, 0);
+}
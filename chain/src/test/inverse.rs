@@ -0,0 +1,59 @@
+use chain::by_submission;
+
+use super::*;
+
+#[test]
+fn inverse_matches_chains() {
+    let read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER);
+    let fragment_read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::FRAGMENT_SHADER);
+    let write = buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    );
+    let chains = single_queue(vec![
+        node(0, vec![], vec![(0, write), (1, write)]),
+        node(1, vec![0], vec![(0, read)]),
+        node(2, vec![1], vec![(0, fragment_read), (1, read)]),
+    ]);
+
+    let inverse = by_submission(&chains);
+
+    // Every access of the forward chains is present in the inverse view.
+    let mut accesses = 0;
+    for (id, chain) in &chains.buffers {
+        for (index, link) in chain.history() {
+            for sid in link.submissions() {
+                let access = inverse[sid].buffers[id];
+                assert_eq!(access.link, index);
+                assert!(link.access().contains(access.state.access));
+                assert!(link.stages().contains(access.state.stages));
+                accesses += 1;
+            }
+        }
+    }
+
+    // And nothing else.
+    let total: usize = inverse
+        .values()
+        .map(|resources| {
+            resources.buffers.len()
+                + resources.images.len()
+                + resources.acceleration_structures.len()
+        })
+        .sum();
+    assert_eq!(total, accesses);
+    assert_eq!(total, 5);
+
+    let first = submission_for(&chains.schedule, 0).id();
+    assert_eq!(inverse[&first].buffers.len(), 2);
+    let second = submission_for(&chains.schedule, 1).id();
+    assert_eq!(inverse[&second].buffers.len(), 1);
+    assert!(inverse[&second].buffers.contains_key(&Id(0)));
+
+    // Reads of the buffer 0 share the link, but each submission reports its own stages.
+    let third = submission_for(&chains.schedule, 2).id();
+    let (second, third) = (inverse[&second].buffers[&Id(0)], inverse[&third].buffers[&Id(0)]);
+    assert_eq!(second.link, third.link);
+    assert_eq!(second.state.stages, PipelineStageFlags::COMPUTE_SHADER);
+    assert_eq!(third.state.stages, PipelineStageFlags::FRAGMENT_SHADER);
+}
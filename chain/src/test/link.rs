@@ -0,0 +1,242 @@
+use chain::{LinkOverride, OverrideError, SyncScope};
+use node::PassMetadata;
+use sync::sync;
+
+use super::*;
+
+fn write_then_read() -> Chains {
+    single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER | PipelineStageFlags::FRAGMENT_SHADER,
+                ),
+            )],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![(
+                0,
+                buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER),
+            )],
+        ),
+    ])
+}
+
+fn barrier_src_stages(schedule: &Synced) -> PipelineStageFlags {
+    submission_for(schedule, 1).sync().acquire.buffers[&Id(0)]
+        .states
+        .start
+        .2
+}
+
+#[test]
+fn override_narrows_barrier() {
+    let mut chains = write_then_read();
+    assert_eq!(chains.buffers[&Id(0)].len(), 2);
+
+    let mut counter = 0;
+    let schedule = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+    assert_eq!(
+        barrier_src_stages(&schedule),
+        PipelineStageFlags::COMPUTE_SHADER | PipelineStageFlags::FRAGMENT_SHADER
+    );
+
+    chains
+        .buffers
+        .get_mut(&Id(0))
+        .unwrap()
+        .override_link(
+            0,
+            LinkOverride {
+                src: Some(SyncScope {
+                    stages: PipelineStageFlags::COMPUTE_SHADER,
+                    access: AccessFlags::SHADER_WRITE,
+                }),
+                dst: None,
+            },
+        )
+        .unwrap();
+
+    let schedule = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+    assert_eq!(
+        barrier_src_stages(&schedule),
+        PipelineStageFlags::COMPUTE_SHADER
+    );
+}
+
+#[test]
+fn unsafe_override_rejected() {
+    let mut chains = write_then_read();
+    let chain = chains.buffers.get_mut(&Id(0)).unwrap();
+
+    let dropped_writes = chain.override_link(
+        0,
+        LinkOverride {
+            src: Some(SyncScope {
+                stages: PipelineStageFlags::COMPUTE_SHADER,
+                access: AccessFlags::SHADER_READ,
+            }),
+            dst: None,
+        },
+    );
+    match dropped_writes {
+        Err(OverrideError::DroppedWrites(_, writes)) => {
+            assert_eq!(writes, AccessFlags::SHADER_WRITE)
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let disjoint_stages = chain.override_link(
+        1,
+        LinkOverride {
+            src: None,
+            dst: Some(SyncScope {
+                stages: PipelineStageFlags::TRANSFER,
+                access: AccessFlags::SHADER_READ,
+            }),
+        },
+    );
+    match disjoint_stages {
+        Err(OverrideError::DisjointStages(..)) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    assert_eq!(
+        chain.override_link(2, LinkOverride::default()),
+        Err(OverrideError::NoLink(2))
+    );
+}
+
+#[test]
+fn override_keeps_required_stages() {
+    let mut chains = single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+            )],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![(
+                0,
+                buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER),
+            )],
+        ),
+    ]);
+    let chain = chains.buffers.get_mut(&Id(0)).unwrap();
+
+    let widened = chain.override_link(
+        0,
+        LinkOverride {
+            src: Some(SyncScope {
+                stages: PipelineStageFlags::TRANSFER | PipelineStageFlags::COMPUTE_SHADER,
+                access: AccessFlags::TRANSFER_WRITE,
+            }),
+            dst: None,
+        },
+    );
+    assert_eq!(widened, Ok(()));
+
+    let missing_stages = chain.override_link(
+        0,
+        LinkOverride {
+            src: Some(SyncScope {
+                stages: PipelineStageFlags::COMPUTE_SHADER,
+                access: AccessFlags::TRANSFER_WRITE,
+            }),
+            dst: None,
+        },
+    );
+    assert_eq!(
+        missing_stages,
+        Err(OverrideError::MissingStages(
+            PipelineStageFlags::COMPUTE_SHADER,
+            PipelineStageFlags::TRANSFER
+        ))
+    );
+}
+
+#[test]
+fn describe_history() {
+    let chains = write_then_read();
+    let ref chain = chains.buffers[&Id(0)];
+
+    let history = chain
+        .history()
+        .map(|(index, link)| (index, link.access(), link.submissions().len()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        history,
+        vec![
+            (0, AccessFlags::SHADER_WRITE, 1),
+            (1, AccessFlags::SHADER_READ, 1),
+        ]
+    );
+
+    assert_eq!(
+        chain.describe(),
+        "Chain of 2 links\n  \
+         #0: family 0, access SHADER_WRITE, stages FRAGMENT_SHADER | COMPUTE_SHADER, \
+         usage STORAGE_BUFFER, layout (), submissions [0:0:0]\n  \
+         #1: family 0, access SHADER_READ, stages VERTEX_SHADER, \
+         usage STORAGE_BUFFER, layout (), submissions [0:0:1]\n"
+    );
+}
+
+#[test]
+fn describe_with_pass_metadata() {
+    let shadow = PassMetadata {
+        name: "shadow".into(),
+        color: Some([0.5, 0.5, 0.5, 1.0]),
+        priority: 1,
+    };
+    let mut nodes = vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(AccessFlags::SHADER_WRITE, PipelineStageFlags::COMPUTE_SHADER),
+            )],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![(
+                0,
+                buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER),
+            )],
+        ),
+    ];
+    nodes[0].metadata = Some(shadow.clone());
+    let chains = single_queue(nodes);
+
+    let sid = submission_for(&chains.schedule, 0).id();
+    assert_eq!(chains.metadata.len(), 1);
+    assert_eq!(chains.metadata[&sid], shadow);
+
+    assert_eq!(
+        chains.buffers[&Id(0)].describe_with(&chains.metadata),
+        "Chain of 2 links\n  \
+         #0: family 0, access SHADER_WRITE, stages COMPUTE_SHADER, \
+         usage STORAGE_BUFFER, layout (), submissions [0:0:0 (shadow)]\n  \
+         #1: family 0, access SHADER_READ, stages VERTEX_SHADER, \
+         usage STORAGE_BUFFER, layout (), submissions [0:0:1]\n"
+    );
+}
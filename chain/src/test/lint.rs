@@ -0,0 +1,131 @@
+use rendy_resource::image;
+
+use lint::{lint, OverSync};
+use node::PassMetadata;
+use resource::Image;
+use schedule::{QueueId, SubmissionId};
+
+use super::*;
+
+fn image_node(
+    id: usize,
+    dependencies: Vec<usize>,
+    access: AccessFlags,
+    layout: image::Layout,
+) -> Node {
+    Node {
+        id,
+        family: FamilyId(0),
+        dependencies,
+        buffers: HashMap::new(),
+        buffer_ranges: HashMap::new(),
+        subsequent_buffers: HashMap::new(),
+        images: Some((
+            Id(0),
+            State {
+                access,
+                layout,
+                stages: PipelineStageFlags::FRAGMENT_SHADER,
+                usage: image::UsageFlags::SAMPLED | image::UsageFlags::TRANSFER_SRC,
+            },
+        ))
+        .into_iter()
+        .collect(),
+        subsequent_images: HashMap::new(),
+        image_aspects: HashMap::new(),
+        acceleration_structures: HashMap::new(),
+        render_pass: None,
+        upload: false,
+        metadata: None,
+    }
+}
+
+#[test]
+fn immutable_texture_retransitioned_each_frame() {
+    let chains = single_queue(vec![
+        image_node(
+            0,
+            vec![],
+            AccessFlags::SHADER_READ,
+            image::Layout::ShaderReadOnlyOptimal,
+        ),
+        image_node(
+            1,
+            vec![0],
+            AccessFlags::TRANSFER_READ,
+            image::Layout::TransferSrcOptimal,
+        ),
+        image_node(
+            2,
+            vec![1],
+            AccessFlags::SHADER_READ,
+            image::Layout::ShaderReadOnlyOptimal,
+        ),
+    ]);
+    assert_eq!(chains.images[&Id(0)].len(), 3);
+
+    let warnings = lint(&chains);
+    assert_eq!(warnings.len(), 1);
+
+    let warning = &warnings.images[0];
+    assert_eq!(warning.resource, Id(0));
+    assert_eq!(
+        warning.submission,
+        SubmissionId::new(QueueId::new(FamilyId(0), 0), 1)
+    );
+    assert_eq!(
+        warning.issue,
+        OverSync::<Image>::Immutable {
+            links: 3,
+            suggested: image::Layout::ShaderReadOnlyOptimal,
+        }
+    );
+    assert_eq!(warning.pass, None);
+}
+
+#[test]
+fn written_texture_is_not_reported() {
+    let chains = single_queue(vec![
+        image_node(
+            0,
+            vec![],
+            AccessFlags::TRANSFER_WRITE,
+            image::Layout::TransferDstOptimal,
+        ),
+        image_node(
+            1,
+            vec![0],
+            AccessFlags::SHADER_READ,
+            image::Layout::ShaderReadOnlyOptimal,
+        ),
+    ]);
+    assert!(lint(&chains).is_empty());
+}
+
+#[test]
+fn warning_names_pass() {
+    let mut transfer = image_node(
+        1,
+        vec![0],
+        AccessFlags::TRANSFER_READ,
+        image::Layout::TransferSrcOptimal,
+    );
+    transfer.metadata = Some(PassMetadata {
+        name: "readback".into(),
+        ..PassMetadata::default()
+    });
+    let chains = single_queue(vec![
+        image_node(
+            0,
+            vec![],
+            AccessFlags::SHADER_READ,
+            image::Layout::ShaderReadOnlyOptimal,
+        ),
+        transfer,
+    ]);
+
+    let warnings = lint(&chains);
+    assert_eq!(warnings.len(), 1);
+    let pass = warnings.images[0].pass.as_ref().expect("Pass has metadata");
+    assert_eq!(pass.name, "readback");
+}
@@ -0,0 +1,122 @@
+use merge::{merge, MergeError};
+
+use super::*;
+
+#[test]
+fn shared_texture_is_linked_across_boundary() {
+    let scene = single_queue(vec![with_images(
+        node(0, vec![], vec![]),
+        vec![(10, render())],
+    )]);
+    let ui = single_queue(vec![with_images(
+        node(1, vec![], vec![]),
+        vec![(10, sample())],
+    )]);
+
+    let merged = merge(scene, ui).unwrap();
+    let nodes: Vec<_> = merged.schedule.ordered().map(|s| s.node()).collect();
+    assert_eq!(nodes, vec![0, 1]);
+
+    let chain = &merged.images[&Id(10)];
+    assert_eq!(chain.len(), 2);
+
+    let render_sid = submission_for(&merged.schedule, 0).id();
+    let sample = submission_for(&merged.schedule, 1);
+    assert_eq!(sample.id().queue(), render_sid.queue());
+    assert_eq!(sample.id().index(), render_sid.index() + 1);
+    assert_eq!(sample.resource_link_index(Id(10)), 1);
+    assert_eq!(chain.links()[1].submissions(), &[sample.id()]);
+}
+
+#[test]
+fn boundary_reads_share_link() {
+    let first = single_queue(vec![with_images(
+        node(0, vec![], vec![]),
+        vec![(10, sample())],
+    )]);
+    let second = single_queue(vec![with_images(
+        node(1, vec![], vec![]),
+        vec![(10, sample())],
+    )]);
+
+    let merged = merge(first, second).unwrap();
+    let chain = &merged.images[&Id(10)];
+    assert_eq!(chain.len(), 1);
+    assert_eq!(chain.links()[0].submissions().len(), 2);
+    assert_eq!(
+        submission_for(&merged.schedule, 1).resource_link_index(Id(10)),
+        0
+    );
+}
+
+#[test]
+fn resources_of_one_set_are_untouched() {
+    let first = single_queue(vec![node(
+        0,
+        vec![],
+        vec![(
+            1,
+            buffer_state(
+                AccessFlags::SHADER_WRITE,
+                PipelineStageFlags::COMPUTE_SHADER,
+            ),
+        )],
+    )]);
+    let second = single_queue(vec![node(
+        1,
+        vec![],
+        vec![(
+            2,
+            buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER),
+        )],
+    )]);
+
+    let merged = merge(first, second).unwrap();
+    assert_eq!(merged.buffers[&Id(1)].len(), 1);
+    assert_eq!(merged.buffers[&Id(2)].len(), 1);
+    assert_eq!(
+        submission_for(&merged.schedule, 1).resource_link_index(Id(2)),
+        0
+    );
+}
+
+#[test]
+fn duplicate_node_is_rejected() {
+    let first = single_queue(vec![with_images(
+        node(0, vec![], vec![]),
+        vec![(10, render())],
+    )]);
+    let second = single_queue(vec![with_images(
+        node(0, vec![], vec![]),
+        vec![(10, sample())],
+    )]);
+
+    assert_eq!(
+        merge(first, second).unwrap_err(),
+        MergeError::DuplicateNode(0)
+    );
+}
+
+#[test]
+fn resource_kind_mismatch_is_rejected() {
+    let first = single_queue(vec![node(
+        0,
+        vec![],
+        vec![(
+            10,
+            buffer_state(
+                AccessFlags::SHADER_WRITE,
+                PipelineStageFlags::COMPUTE_SHADER,
+            ),
+        )],
+    )]);
+    let second = single_queue(vec![with_images(
+        node(1, vec![], vec![]),
+        vec![(10, sample())],
+    )]);
+
+    assert_eq!(
+        merge(first, second).unwrap_err(),
+        MergeError::ResourceKind(Id(10))
+    );
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use rendy_resource::{buffer, image};
+
+use access::AccessFlags;
+use collect::{collect, Chains};
+use node::{Node, State};
+use resource::{Buffer, Image};
+use schedule::{FamilyId, Schedule, Submission};
+use stage::PipelineStageFlags;
+use sync::SyncData;
+use Id;
+
+mod accel;
+mod aspects;
+mod batch;
+mod buffering;
+mod compiled;
+mod critical;
+mod dead;
+mod deterministic;
+mod elision;
+mod fence;
+mod hazard;
+mod history;
+mod host;
+mod indirect;
+mod initial;
+mod inverse;
+mod link;
+mod lint;
+mod merge;
+mod ordering;
+mod pass;
+mod placement;
+mod prologue;
+mod range;
+mod reduction;
+mod report;
+mod region;
+mod rmw;
+mod schedule;
+mod split;
+mod stages;
+mod subsequent;
+mod timeline;
+mod upload;
+
+fn buffer_state(access: AccessFlags, stages: PipelineStageFlags) -> State<Buffer> {
+    State {
+        access,
+        layout: (),
+        stages,
+        usage: buffer::UsageFlags::STORAGE_BUFFER,
+    }
+}
+
+fn node(id: usize, dependencies: Vec<usize>, buffers: Vec<(u64, State<Buffer>)>) -> Node {
+    Node {
+        id,
+        family: FamilyId(0),
+        dependencies,
+        buffers: buffers
+            .into_iter()
+            .map(|(id, state)| (Id(id), state))
+            .collect(),
+        buffer_ranges: HashMap::new(),
+        subsequent_buffers: HashMap::new(),
+        images: HashMap::new(),
+        subsequent_images: HashMap::new(),
+        image_aspects: HashMap::new(),
+        acceleration_structures: HashMap::new(),
+        render_pass: None,
+        upload: false,
+        metadata: None,
+    }
+}
+
+fn on_family(
+    id: usize,
+    dependencies: Vec<usize>,
+    family: u32,
+    buffers: Vec<(u64, State<Buffer>)>,
+) -> Node {
+    let mut node = node(id, dependencies, buffers);
+    node.family = FamilyId(family);
+    node
+}
+
+fn texture_state(access: AccessFlags, layout: image::Layout) -> State<Image> {
+    State {
+        access,
+        layout,
+        stages: PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | PipelineStageFlags::FRAGMENT_SHADER,
+        usage: image::UsageFlags::COLOR_ATTACHMENT | image::UsageFlags::SAMPLED,
+    }
+}
+
+fn render() -> State<Image> {
+    texture_state(
+        AccessFlags::COLOR_ATTACHMENT_WRITE,
+        image::Layout::ColorAttachmentOptimal,
+    )
+}
+
+fn sample() -> State<Image> {
+    texture_state(
+        AccessFlags::SHADER_READ,
+        image::Layout::ShaderReadOnlyOptimal,
+    )
+}
+
+fn with_images(mut node: Node, images: Vec<(u64, State<Image>)>) -> Node {
+    node.images = images
+        .into_iter()
+        .map(|(id, state)| (Id(id), state))
+        .collect();
+    node
+}
+
+fn single_queue(nodes: Vec<Node>) -> Chains {
+    collect(nodes, |_| 1)
+}
+
+fn submission_for<S>(schedule: &Schedule<S>, node: usize) -> &Submission<S> {
+    schedule
+        .iter()
+        .flat_map(|family| family.iter())
+        .flat_map(|queue| queue.iter())
+        .find(|submission| submission.node() == node)
+        .expect("Node must be scheduled")
+}
+
+type Synced = Schedule<SyncData<usize, usize>>;
@@ -0,0 +1,70 @@
+use collect::collect;
+use sync::sync;
+
+use super::*;
+
+fn writer(id: usize, dependencies: Vec<usize>, family: u32, buffer: u64) -> Node {
+    let write = buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    );
+    on_family(id, dependencies, family, vec![(buffer, write)])
+}
+
+fn two_families() -> Chains {
+    collect(
+        vec![writer(0, vec![], 0, 0), writer(1, vec![0], 1, 1)],
+        |_| 1,
+    )
+}
+
+fn synced(chains: &Chains) -> Synced {
+    let mut counter = 0;
+    sync(chains, || {
+        counter += 1;
+        (counter, counter)
+    })
+}
+
+#[test]
+fn independent_submissions_are_not_synchronized() {
+    let schedule = synced(&two_families());
+    assert!(submission_for(&schedule, 0).sync().signal.is_empty());
+    assert!(submission_for(&schedule, 1).sync().wait.is_empty());
+}
+
+#[test]
+fn ordering_edge_forces_semaphore() {
+    let mut chains = two_families();
+    chains.add_ordering(0, 1);
+    let schedule = synced(&chains);
+
+    let scene = submission_for(&schedule, 0).sync();
+    let overlay = submission_for(&schedule, 1).sync();
+    assert_eq!(scene.signal.len(), 1);
+    assert_eq!(overlay.wait.len(), 1);
+    assert_eq!(scene.signal[0].semaphore(), overlay.wait[0].semaphore());
+    assert_eq!(overlay.wait[0].stage(), PipelineStageFlags::ALL_COMMANDS);
+
+    // No memory barriers for ordering edges.
+    assert!(scene.release.buffers.is_empty());
+    assert!(overlay.acquire.buffers.is_empty());
+}
+
+#[test]
+fn ordering_edge_on_same_queue_needs_execution_barrier() {
+    let mut chains = single_queue(vec![writer(0, vec![], 0, 0), writer(1, vec![0], 0, 1)]);
+    chains.add_ordering(0, 1);
+    let schedule = synced(&chains);
+
+    assert!(!submission_for(&schedule, 0).sync().execution_barrier);
+    assert!(submission_for(&schedule, 1).sync().execution_barrier);
+    assert!(submission_for(&schedule, 1).sync().wait.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "is submitted before node")]
+fn ordering_against_submission_order() {
+    let mut chains = two_families();
+    chains.add_ordering(1, 0);
+}
@@ -0,0 +1,102 @@
+use rendy_resource::image;
+
+use pass::{validate_render_pass_barriers, IllegalBarrier, RenderPassIssue};
+use resource::Image;
+use sync::{sync, Placement};
+
+use super::*;
+
+fn attachment_node(
+    id: usize,
+    dependencies: Vec<usize>,
+    render_pass: usize,
+    access: AccessFlags,
+    stages: PipelineStageFlags,
+    layout: image::Layout,
+) -> Node {
+    let state: State<Image> = State {
+        access,
+        layout,
+        stages,
+        usage: image::UsageFlags::COLOR_ATTACHMENT | image::UsageFlags::INPUT_ATTACHMENT,
+    };
+    Node {
+        id,
+        family: FamilyId(0),
+        dependencies,
+        buffers: HashMap::new(),
+        buffer_ranges: HashMap::new(),
+        subsequent_buffers: HashMap::new(),
+        images: Some((Id(0), state)).into_iter().collect(),
+        subsequent_images: HashMap::new(),
+        image_aspects: HashMap::new(),
+        acceleration_structures: HashMap::new(),
+        render_pass: Some(render_pass),
+        upload: false,
+        metadata: None,
+    }
+}
+
+/// Color write followed by input attachment read in `layout`.
+fn write_then_read(first: usize, second: usize, layout: image::Layout) -> Synced {
+    let chains = single_queue(vec![
+        attachment_node(
+            0,
+            vec![],
+            first,
+            AccessFlags::COLOR_ATTACHMENT_WRITE,
+            PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            image::Layout::General,
+        ),
+        attachment_node(
+            1,
+            vec![0],
+            second,
+            AccessFlags::INPUT_ATTACHMENT_READ,
+            PipelineStageFlags::FRAGMENT_SHADER,
+            layout,
+        ),
+    ]);
+
+    let mut counter = 0;
+    sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    })
+}
+
+#[test]
+fn self_dependency_inside_render_pass() {
+    let schedule = write_then_read(3, 3, image::Layout::General);
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.images[&Id(0)];
+    assert_eq!(barrier.render_pass, Some(3));
+    assert!(barrier.inside_render_pass());
+    assert!(validate_render_pass_barriers(&schedule).is_empty());
+}
+
+#[test]
+fn layout_transition_inside_render_pass_rejected() {
+    let schedule = write_then_read(3, 3, image::Layout::ShaderReadOnlyOptimal);
+
+    assert_eq!(
+        validate_render_pass_barriers(&schedule),
+        vec![IllegalBarrier {
+            resource: Id(0),
+            submission: submission_for(&schedule, 1).id(),
+            placement: Placement::Acquire,
+            render_pass: 3,
+            issue: RenderPassIssue::LayoutTransition,
+        }]
+    );
+}
+
+#[test]
+fn layout_transition_between_render_passes_allowed() {
+    let schedule = write_then_read(3, 4, image::Layout::ShaderReadOnlyOptimal);
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.images[&Id(0)];
+    assert_eq!(barrier.render_pass, None);
+    assert!(barrier.is_layout_transition());
+    assert!(validate_render_pass_barriers(&schedule).is_empty());
+}
@@ -0,0 +1,85 @@
+use sync::{sync, sync_with, Placement, PlacementPolicy, SyncOptions};
+
+use super::*;
+
+fn write() -> State<Buffer> {
+    buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    )
+}
+
+fn read() -> State<Buffer> {
+    buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER)
+}
+
+fn pair(first: State<Buffer>, second: State<Buffer>) -> Chains {
+    single_queue(vec![
+        node(0, vec![], vec![(0, first)]),
+        node(1, vec![0], vec![(0, second)]),
+    ])
+}
+
+fn counter() -> impl FnMut() -> (usize, usize) {
+    let mut counter = 0;
+    move || {
+        counter += 1;
+        (counter, counter)
+    }
+}
+
+#[test]
+fn read_after_write_on_consumer() {
+    let chains = pair(write(), read());
+    let schedule: Synced = sync(&chains, counter());
+
+    assert!(submission_for(&schedule, 0)
+        .sync()
+        .release
+        .buffers
+        .is_empty());
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.placement, Placement::Acquire);
+    assert_eq!(barrier.states.start.0, AccessFlags::SHADER_WRITE);
+    assert_eq!(barrier.states.end.0, AccessFlags::SHADER_READ);
+}
+
+#[test]
+fn write_after_read_on_predecessor() {
+    let chains = pair(read(), write());
+    let schedule: Synced = sync(&chains, counter());
+
+    assert!(submission_for(&schedule, 1)
+        .sync()
+        .acquire
+        .buffers
+        .is_empty());
+    let ref barrier = submission_for(&schedule, 0).sync().release.buffers[&Id(0)];
+    assert_eq!(barrier.placement, Placement::Release);
+    assert_eq!(barrier.states.start.0, AccessFlags::SHADER_READ);
+    assert_eq!(barrier.states.end.0, AccessFlags::SHADER_WRITE);
+}
+
+#[test]
+fn placement_override() {
+    let chains = pair(read(), write());
+    let schedule: Synced = sync_with(
+        &chains,
+        SyncOptions {
+            placement: PlacementPolicy {
+                write_after_read: Placement::Acquire,
+                ..PlacementPolicy::default()
+            },
+            ..SyncOptions::default()
+        },
+        counter(),
+    );
+
+    assert!(submission_for(&schedule, 0)
+        .sync()
+        .release
+        .buffers
+        .is_empty());
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.placement, Placement::Acquire);
+}
@@ -0,0 +1,97 @@
+use batch::SyncPoint;
+use compiled::{compile, compile_frame};
+use sync::{sync, Placement};
+
+use super::*;
+
+fn upload(id: usize, buffer: u64) -> Node {
+    let mut node = node(
+        id,
+        vec![],
+        vec![(
+            buffer,
+            buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+        )],
+    );
+    node.upload = true;
+    node
+}
+
+fn draw(id: usize, dependencies: Vec<usize>, buffer: u64) -> Node {
+    node(
+        id,
+        dependencies,
+        vec![(
+            buffer,
+            buffer_state(
+                AccessFlags::VERTEX_ATTRIBUTE_READ,
+                PipelineStageFlags::VERTEX_INPUT,
+            ),
+        )],
+    )
+}
+
+fn synced(nodes: Vec<Node>) -> Synced {
+    let mut counter = 0;
+    sync(&single_queue(nodes), || {
+        counter += 1;
+        (counter, counter)
+    })
+}
+
+#[test]
+fn only_static_transitions() {
+    let schedule = synced(vec![upload(0, 0), draw(1, vec![0], 0)]);
+    assert!(submission_for(&schedule, 0).upload());
+    assert!(!submission_for(&schedule, 1).upload());
+
+    let split = schedule.split_static();
+    assert!(split.frame.is_empty());
+    assert!(split
+        .prologue
+        .get(SyncPoint {
+            submission: submission_for(&schedule, 1).id(),
+            placement: Placement::Acquire,
+        })
+        .is_some());
+
+    let frame = compile_frame(&schedule);
+    assert_eq!(frame.submissions(), 1);
+    assert_eq!(frame.pipeline_barriers(), 0);
+    assert_eq!(compile(&schedule).pipeline_barriers(), 1);
+}
+
+#[test]
+fn dynamic_barriers_stay_in_frame() {
+    let compute = node(
+        2,
+        vec![1],
+        vec![(
+            1,
+            buffer_state(
+                AccessFlags::SHADER_WRITE,
+                PipelineStageFlags::COMPUTE_SHADER,
+            ),
+        )],
+    );
+    let schedule = synced(vec![
+        upload(0, 0),
+        draw(1, vec![0], 0),
+        compute,
+        draw(3, vec![2], 1),
+    ]);
+    let acquire = |node| SyncPoint {
+        submission: submission_for(&schedule, node).id(),
+        placement: Placement::Acquire,
+    };
+
+    let split = schedule.split_static();
+    assert_eq!(split.prologue.len(), 1);
+    assert!(split.prologue.get(acquire(1)).is_some());
+    assert_eq!(split.frame.len(), 1);
+    assert!(split.frame.get(acquire(3)).is_some());
+
+    let frame = compile_frame(&schedule);
+    assert_eq!(frame.submissions(), 3);
+    assert_eq!(frame.pipeline_barriers(), 1);
+}
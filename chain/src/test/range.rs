@@ -0,0 +1,111 @@
+use std::ops::Range;
+
+use sync::sync;
+
+use super::*;
+
+fn write(id: usize, dependencies: Vec<usize>, range: Range<u64>) -> Node {
+    ranged(
+        node(
+            id,
+            dependencies,
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER,
+                ),
+            )],
+        ),
+        range,
+    )
+}
+
+fn read(id: usize, dependencies: Vec<usize>, range: Range<u64>) -> Node {
+    ranged(
+        node(
+            id,
+            dependencies,
+            vec![(
+                0,
+                buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER),
+            )],
+        ),
+        range,
+    )
+}
+
+fn ranged(mut node: Node, range: Range<u64>) -> Node {
+    node.buffer_ranges.insert(Id(0), range);
+    node
+}
+
+fn synced(chains: &Chains) -> Synced {
+    let mut counter = 0;
+    sync(chains, || {
+        counter += 1;
+        (counter, counter)
+    })
+}
+
+#[test]
+fn disjoint_writes_share_link() {
+    let chains = single_queue(vec![
+        write(0, vec![], 0..256),
+        write(1, vec![], 256..512),
+        read(2, vec![0, 1], 128..384),
+    ]);
+    let links = chains.buffers[&Id(0)].links();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].submissions().len(), 2);
+    assert_eq!(links[0].range(), Some(0..512));
+    assert_eq!(links[1].range(), Some(128..384));
+
+    let schedule = synced(&chains);
+    for node in 0..2 {
+        let sync = submission_for(&schedule, node).sync();
+        assert!(sync.acquire.buffers.is_empty());
+        assert!(sync.release.buffers.is_empty());
+    }
+
+    let ref barrier = submission_for(&schedule, 2).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.range, Some(128..384));
+    assert_eq!(barrier.states.start.0, AccessFlags::SHADER_WRITE);
+}
+
+#[test]
+fn overlapping_writes_get_range_barrier() {
+    let chains = single_queue(vec![write(0, vec![], 0..256), write(1, vec![0], 128..512)]);
+    let links = chains.buffers[&Id(0)].links();
+    assert_eq!(links.len(), 2);
+
+    let schedule = synced(&chains);
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.range, Some(128..256));
+    assert_eq!(
+        barrier.states.start,
+        (
+            AccessFlags::SHADER_WRITE,
+            (),
+            PipelineStageFlags::COMPUTE_SHADER
+        )
+    );
+}
+
+#[test]
+fn whole_buffer_access_is_scoped_by_partial_one() {
+    let whole = node(
+        1,
+        vec![0],
+        vec![(
+            0,
+            buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER),
+        )],
+    );
+    let chains = single_queue(vec![write(0, vec![], 64..128), whole]);
+    assert_eq!(chains.buffers[&Id(0)].links()[1].range(), None);
+
+    let schedule = synced(&chains);
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.range, Some(64..128));
+}
@@ -0,0 +1,71 @@
+use collect::collect;
+use sync::sync;
+
+use super::*;
+
+/// `A` writes buffers read by `B` and `C`, `B` writes buffer read by `C`.
+/// Each node is on its own queue.
+fn triangle(a_to_c: PipelineStageFlags, b_to_c: PipelineStageFlags) -> Synced {
+    let write = buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    );
+    let read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER);
+    let chains = collect(
+        vec![
+            on_family(0, vec![], 0, vec![(0, write), (1, write)]),
+            on_family(1, vec![0], 1, vec![(0, read), (2, write)]),
+            on_family(
+                2,
+                vec![1],
+                2,
+                vec![
+                    (1, buffer_state(AccessFlags::SHADER_READ, a_to_c)),
+                    (2, buffer_state(AccessFlags::SHADER_READ, b_to_c)),
+                ],
+            ),
+        ],
+        |_| 1,
+    );
+
+    let mut counter = 0;
+    sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    })
+}
+
+#[test]
+fn transitive_semaphore_dropped() {
+    let schedule = triangle(
+        PipelineStageFlags::COMPUTE_SHADER,
+        PipelineStageFlags::COMPUTE_SHADER,
+    );
+
+    let a = submission_for(&schedule, 0).sync();
+    let b = submission_for(&schedule, 1).sync();
+    let c = submission_for(&schedule, 2).sync();
+    assert_eq!(a.signal.len(), 1);
+    assert_eq!(b.wait.len(), 1);
+    assert_eq!(a.signal[0].semaphore(), b.wait[0].semaphore());
+    assert_eq!(b.signal.len(), 1);
+    assert_eq!(c.wait.len(), 1);
+    assert_eq!(b.signal[0].semaphore(), c.wait[0].semaphore());
+
+    // Ownership of the buffer is still transferred directly from `A` to `C`.
+    assert!(a.release.buffers.contains_key(&Id(1)));
+    assert!(c.acquire.buffers.contains_key(&Id(1)));
+}
+
+#[test]
+fn semaphore_kept_for_earlier_stages() {
+    // Path through `B` blocks only fragment shader of `C`,
+    // while buffer of `A` is read in vertex shader.
+    let schedule = triangle(
+        PipelineStageFlags::VERTEX_SHADER,
+        PipelineStageFlags::FRAGMENT_SHADER,
+    );
+
+    assert_eq!(submission_for(&schedule, 0).sync().signal.len(), 2);
+    assert_eq!(submission_for(&schedule, 2).sync().wait.len(), 2);
+}
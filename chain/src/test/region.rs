@@ -0,0 +1,87 @@
+use rendy_resource::image;
+
+use resource::Image;
+use sync::{sync, DependencyFlags};
+
+use super::*;
+
+fn attachment_node(
+    id: usize,
+    dependencies: Vec<usize>,
+    render_pass: usize,
+    access: AccessFlags,
+    stages: PipelineStageFlags,
+    layout: image::Layout,
+) -> Node {
+    let state: State<Image> = State {
+        access,
+        layout,
+        stages,
+        usage: image::UsageFlags::COLOR_ATTACHMENT | image::UsageFlags::INPUT_ATTACHMENT,
+    };
+    Node {
+        id,
+        family: FamilyId(0),
+        dependencies,
+        buffers: HashMap::new(),
+        buffer_ranges: HashMap::new(),
+        subsequent_buffers: HashMap::new(),
+        images: Some((Id(0), state)).into_iter().collect(),
+        subsequent_images: HashMap::new(),
+        image_aspects: HashMap::new(),
+        acceleration_structures: HashMap::new(),
+        render_pass: Some(render_pass),
+        upload: false,
+        metadata: None,
+    }
+}
+
+/// Color write followed by input attachment read.
+fn input_attachment_dependency(first: usize, second: usize) -> DependencyFlags {
+    let chains = single_queue(vec![
+        attachment_node(
+            0,
+            vec![],
+            first,
+            AccessFlags::COLOR_ATTACHMENT_WRITE,
+            PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            image::Layout::ColorAttachmentOptimal,
+        ),
+        attachment_node(
+            1,
+            vec![0],
+            second,
+            AccessFlags::INPUT_ATTACHMENT_READ,
+            PipelineStageFlags::FRAGMENT_SHADER,
+            image::Layout::ShaderReadOnlyOptimal,
+        ),
+    ]);
+    assert_eq!(chains.images[&Id(0)].len(), 2);
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.images[&Id(0)];
+    assert_eq!(
+        barrier.states.start.2,
+        PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+    );
+    assert_eq!(barrier.states.end.2, PipelineStageFlags::FRAGMENT_SHADER);
+    barrier.dependencies
+}
+
+#[test]
+fn same_render_pass_by_region() {
+    assert_eq!(
+        input_attachment_dependency(0, 0),
+        DependencyFlags::BY_REGION
+    );
+}
+
+#[test]
+fn different_render_pass_not_by_region() {
+    assert_eq!(input_attachment_dependency(0, 1), DependencyFlags::empty());
+}
@@ -0,0 +1,121 @@
+use report::{report, Report, ResourceKind, SemaphoreReport};
+use sync::sync;
+
+use super::*;
+
+fn counter() -> impl FnMut() -> (usize, usize) {
+    let mut counter = 0;
+    move || {
+        counter += 1;
+        (counter, counter)
+    }
+}
+
+/// Node `0` uploads buffer `0` that node `1` reads in the vertex shader.
+fn upload(family: u32) -> Chains {
+    let mut read = node(
+        1,
+        vec![0],
+        vec![(
+            0,
+            buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER),
+        )],
+    );
+    read.family = FamilyId(family);
+    collect(
+        vec![
+            node(
+                0,
+                vec![],
+                vec![(
+                    0,
+                    buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+                )],
+            ),
+            read,
+        ],
+        |_| 1,
+    )
+}
+
+#[test]
+fn semaphore_edges_between_families() {
+    let chains = upload(1);
+    let schedule: Synced = sync(&chains, counter());
+    let sid = |node| submission_for(&schedule, node).id();
+
+    let report = report(&chains, &schedule);
+    assert_eq!(report.resources.len(), 1);
+    assert_eq!(report.resources[0].kind, ResourceKind::Buffer);
+    assert_eq!(report.resources[0].links.len(), 2);
+    assert_eq!(report.resources[0].links[1].family, FamilyId(1));
+    assert_eq!(
+        report.semaphores,
+        vec![SemaphoreReport {
+            signal: sid(0),
+            wait: sid(1),
+            stages: "VERTEX_SHADER".to_owned(),
+        }]
+    );
+    assert!(report.aliasing.is_empty());
+}
+
+#[test]
+fn equal_frames_report_equally() {
+    let chains = upload(0);
+    let first = report(&chains, &sync(&chains, counter()));
+    let second = report(&chains, &sync(&chains, counter()));
+    assert_eq!(first, second);
+    assert_ne!(first, Report::default());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_snapshot_round_trip() {
+    let chains = upload(0);
+    let schedule: Synced = sync(&chains, counter());
+    let report = report(&chains, &schedule);
+
+    let sid = |index: usize| json!({ "queue": { "family": 0, "index": 0 }, "index": index });
+    let link = |index: usize, access: &str, stages: &str| {
+        json!({
+            "family": 0,
+            "submissions": [sid(index)],
+            "access": access,
+            "stages": stages,
+            "layout": "()",
+            "range": null,
+        })
+    };
+    let snapshot = json!({
+        "resources": [{
+            "id": 0,
+            "kind": "Buffer",
+            "links": [
+                link(0, "TRANSFER_WRITE", "TRANSFER"),
+                link(1, "SHADER_READ", "VERTEX_SHADER"),
+            ],
+        }],
+        "barriers": [{
+            "submission": sid(1),
+            "side": "Acquire",
+            "resource": 0,
+            "kind": "Buffer",
+            "queues": null,
+            "access": { "start": "TRANSFER_WRITE", "end": "SHADER_READ" },
+            "stages": { "start": "TRANSFER", "end": "VERTEX_SHADER" },
+            "layout": { "start": "()", "end": "()" },
+            "dependencies": "(empty)",
+            "method": "Barrier",
+            "render_pass": null,
+            "range": null,
+        }],
+        "semaphores": [],
+        "aliasing": [],
+        "warnings": [],
+    });
+
+    let value = ::serde_json::to_value(&report).unwrap();
+    assert_eq!(value, snapshot);
+    assert_eq!(::serde_json::from_value::<Report>(value).unwrap(), report);
+}
@@ -0,0 +1,115 @@
+use sync::sync;
+
+use super::*;
+
+fn rmw_between_write_and_read() -> Chains {
+    single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+            )],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![(
+                0,
+                buffer_state(
+                    AccessFlags::SHADER_READ | AccessFlags::SHADER_WRITE,
+                    PipelineStageFlags::COMPUTE_SHADER,
+                ),
+            )],
+        ),
+        node(
+            2,
+            vec![1],
+            vec![(
+                0,
+                buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER),
+            )],
+        ),
+    ])
+}
+
+#[test]
+fn rmw_link_is_separate() {
+    let chains = rmw_between_write_and_read();
+    let links = chains.buffers[&Id(0)].links();
+    assert_eq!(links.len(), 3);
+
+    assert!(!links[0].read_modify_write());
+    assert!(links[1].read_modify_write());
+    assert!(!links[2].read_modify_write());
+    assert_eq!(links[1].submissions().len(), 1);
+    assert_eq!(
+        links[1].access(),
+        AccessFlags::SHADER_READ | AccessFlags::SHADER_WRITE
+    );
+}
+
+#[test]
+fn rmw_barriers_use_union() {
+    let chains = rmw_between_write_and_read();
+
+    let mut counter = 0;
+    let schedule = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let ref before = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(
+        before.states.start,
+        (
+            AccessFlags::TRANSFER_WRITE,
+            (),
+            PipelineStageFlags::TRANSFER
+        )
+    );
+    assert_eq!(
+        before.states.end,
+        (
+            AccessFlags::SHADER_READ | AccessFlags::SHADER_WRITE,
+            (),
+            PipelineStageFlags::COMPUTE_SHADER
+        )
+    );
+
+    let ref after = submission_for(&schedule, 2).sync().acquire.buffers[&Id(0)];
+    assert_eq!(
+        after.states.start,
+        (
+            AccessFlags::SHADER_READ | AccessFlags::SHADER_WRITE,
+            (),
+            PipelineStageFlags::COMPUTE_SHADER
+        )
+    );
+    assert_eq!(
+        after.states.end,
+        (
+            AccessFlags::SHADER_READ,
+            (),
+            PipelineStageFlags::VERTEX_SHADER
+        )
+    );
+}
+
+#[test]
+fn rmw_has_no_self_barrier() {
+    let chains = rmw_between_write_and_read();
+
+    let mut counter = 0;
+    let schedule = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    // The only barrier of RMW submission synchronizes it with the previous writer.
+    let sync = submission_for(&schedule, 1).sync();
+    assert_eq!(sync.acquire.buffers.len(), 1);
+    assert!(sync.release.buffers.is_empty());
+    assert!(!sync.execution_barrier);
+}
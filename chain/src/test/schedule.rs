@@ -0,0 +1,34 @@
+use sync::sync;
+
+use super::*;
+
+fn write(id: usize, dependencies: Vec<usize>) -> Node {
+    node(
+        id,
+        dependencies,
+        vec![(
+            0,
+            buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+        )],
+    )
+}
+
+fn nodes<S>(schedule: &Schedule<S>) -> Vec<usize> {
+    schedule
+        .ordered()
+        .map(|submission| submission.node())
+        .collect()
+}
+
+#[test]
+fn ordered_follows_submit_order() {
+    let chains = single_queue(vec![write(0, vec![]), write(1, vec![0]), write(2, vec![1])]);
+    assert_eq!(nodes(&chains.schedule), vec![0, 1, 2]);
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+    assert_eq!(nodes(&schedule), vec![0, 1, 2]);
+}
@@ -0,0 +1,64 @@
+use sync::{sync_with, SyncMethod, SyncOptions};
+
+use super::*;
+
+fn write() -> State<Buffer> {
+    buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    )
+}
+
+fn read() -> State<Buffer> {
+    buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER)
+}
+
+fn split(nodes: Vec<Node>) -> Synced {
+    let chains = single_queue(nodes);
+    let mut counter = 0;
+    sync_with(
+        &chains,
+        SyncOptions {
+            split_barriers: true,
+            ..SyncOptions::default()
+        },
+        || {
+            counter += 1;
+            (counter, counter)
+        },
+    )
+}
+
+#[test]
+fn event_when_gap() {
+    let schedule = split(vec![
+        node(0, vec![], vec![(0, write())]),
+        node(1, vec![0], vec![(1, write())]),
+        node(2, vec![0, 1], vec![(0, read())]),
+    ]);
+
+    let ref wait = submission_for(&schedule, 2).sync().acquire.buffers[&Id(0)];
+    assert_eq!(wait.method, SyncMethod::Event);
+    assert_eq!(wait.states.start.0, AccessFlags::SHADER_WRITE);
+    assert_eq!(wait.states.end.0, AccessFlags::SHADER_READ);
+
+    let ref set = submission_for(&schedule, 0).sync().release.buffers[&Id(0)];
+    assert_eq!(set.method, SyncMethod::Event);
+    assert_eq!(set.states, wait.states);
+}
+
+#[test]
+fn barrier_when_adjacent() {
+    let schedule = split(vec![
+        node(0, vec![], vec![(0, write())]),
+        node(1, vec![0], vec![(0, read())]),
+    ]);
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.method, SyncMethod::Barrier);
+    assert!(submission_for(&schedule, 0)
+        .sync()
+        .release
+        .buffers
+        .is_empty());
+}
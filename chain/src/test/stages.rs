@@ -0,0 +1,80 @@
+use sync::{sync_with_stages, SyncOptions};
+
+use super::*;
+
+fn transfer_chains(read_stages: PipelineStageFlags) -> Chains {
+    single_queue(vec![
+        node(
+            0,
+            vec![],
+            vec![(
+                0,
+                buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+            )],
+        ),
+        node(
+            1,
+            vec![0],
+            vec![(0, buffer_state(AccessFlags::SHADER_READ, read_stages))],
+        ),
+    ])
+}
+
+fn transfer_only(chains: &Chains) -> Synced {
+    let mut counter = 0;
+    sync_with_stages(
+        chains,
+        SyncOptions::default(),
+        |_| PipelineStageFlags::TRANSFER,
+        || {
+            counter += 1;
+            (counter, counter)
+        },
+    )
+}
+
+#[test]
+fn fragment_stage_clamped_on_transfer_family() {
+    let chains =
+        transfer_chains(PipelineStageFlags::TRANSFER | PipelineStageFlags::FRAGMENT_SHADER);
+    let schedule = transfer_only(&chains);
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.states.start.2, PipelineStageFlags::TRANSFER);
+    assert_eq!(barrier.states.end.2, PipelineStageFlags::TRANSFER);
+}
+
+#[test]
+fn no_supported_stages_left() {
+    let chains = transfer_chains(PipelineStageFlags::FRAGMENT_SHADER);
+    let schedule = transfer_only(&chains);
+
+    let ref barrier = submission_for(&schedule, 1).sync().acquire.buffers[&Id(0)];
+    assert_eq!(barrier.states.start.2, PipelineStageFlags::TRANSFER);
+    assert_eq!(barrier.states.end.2, PipelineStageFlags::ALL_COMMANDS);
+}
+
+#[test]
+fn internal_barriers_clamped() {
+    let mut write_then_read = node(
+        0,
+        vec![],
+        vec![(
+            0,
+            buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+        )],
+    );
+    write_then_read.subsequent_buffers.insert(
+        Id(0),
+        vec![buffer_state(
+            AccessFlags::SHADER_READ,
+            PipelineStageFlags::FRAGMENT_SHADER,
+        )],
+    );
+    let chains = single_queue(vec![write_then_read]);
+    let schedule = transfer_only(&chains);
+
+    let ref barrier = submission_for(&schedule, 0).sync().internal[0].buffers[&Id(0)];
+    assert_eq!(barrier.states.start.2, PipelineStageFlags::TRANSFER);
+    assert_eq!(barrier.states.end.2, PipelineStageFlags::ALL_COMMANDS);
+}
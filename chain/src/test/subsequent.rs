@@ -0,0 +1,110 @@
+use rendy_resource::image;
+
+use critical::critical_path;
+use resource::Image;
+use sync::sync;
+
+use super::*;
+
+fn image_state(
+    access: AccessFlags,
+    stages: PipelineStageFlags,
+    layout: image::Layout,
+) -> State<Image> {
+    State {
+        access,
+        layout,
+        stages,
+        usage: image::UsageFlags::TRANSFER_DST | image::UsageFlags::SAMPLED,
+    }
+}
+
+fn copy() -> State<Image> {
+    image_state(
+        AccessFlags::TRANSFER_WRITE,
+        PipelineStageFlags::TRANSFER,
+        image::Layout::TransferDstOptimal,
+    )
+}
+
+fn sample() -> State<Image> {
+    image_state(
+        AccessFlags::SHADER_READ,
+        PipelineStageFlags::FRAGMENT_SHADER,
+        image::Layout::ShaderReadOnlyOptimal,
+    )
+}
+
+/// Node accessing image 0 with `first` and then with each of `subsequent` in order.
+fn image_node(
+    id: usize,
+    dependencies: Vec<usize>,
+    first: State<Image>,
+    subsequent: Vec<State<Image>>,
+) -> Node {
+    let mut node = node(id, dependencies, vec![]);
+    node.images.insert(Id(0), first);
+    if !subsequent.is_empty() {
+        node.subsequent_images.insert(Id(0), subsequent);
+    }
+    node
+}
+
+fn synced(chains: &Chains) -> Synced {
+    let mut counter = 0;
+    sync(chains, || {
+        counter += 1;
+        (counter, counter)
+    })
+}
+
+#[test]
+fn copy_then_sample_transitions_inside_submission() {
+    let chains = single_queue(vec![image_node(0, vec![], copy(), vec![sample()])]);
+    let links = chains.images[&Id(0)].links();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].submissions(), links[1].submissions());
+
+    let schedule = synced(&chains);
+    let sync = submission_for(&schedule, 0).sync();
+    assert!(sync.acquire.images.is_empty());
+    assert!(sync.release.images.is_empty());
+    assert!(sync.wait.is_empty() && sync.signal.is_empty());
+
+    assert_eq!(sync.internal.len(), 1);
+    let ref barrier = sync.internal[0].images[&Id(0)];
+    assert_eq!(
+        barrier.states.start,
+        (
+            AccessFlags::TRANSFER_WRITE,
+            image::Layout::TransferDstOptimal,
+            PipelineStageFlags::TRANSFER
+        )
+    );
+    assert_eq!(
+        barrier.states.end,
+        (
+            AccessFlags::SHADER_READ,
+            image::Layout::ShaderReadOnlyOptimal,
+            PipelineStageFlags::FRAGMENT_SHADER
+        )
+    );
+}
+
+#[test]
+fn later_reader_joins_last_access() {
+    let chains = single_queue(vec![
+        image_node(0, vec![], copy(), vec![sample()]),
+        image_node(1, vec![0], sample(), vec![]),
+    ]);
+    let links = chains.images[&Id(0)].links();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[1].submissions().len(), 2);
+    assert_eq!(critical_path(&chains, |_| 1).len(), 2);
+
+    let schedule = synced(&chains);
+    assert_eq!(submission_for(&schedule, 0).sync().internal.len(), 1);
+    let later = submission_for(&schedule, 1).sync();
+    assert!(later.acquire.images.is_empty());
+    assert!(later.internal.is_empty());
+}
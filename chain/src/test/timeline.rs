@@ -0,0 +1,83 @@
+use collect::collect;
+use rendy_resource::DeviceFeatures;
+use sync::{sync, sync_timeline, sync_with_features, SemaphoreSync, SyncOptions, TimelineValue};
+
+use super::*;
+
+fn fan_out() -> Chains {
+    let write = buffer_state(
+        AccessFlags::SHADER_WRITE,
+        PipelineStageFlags::COMPUTE_SHADER,
+    );
+    let read = buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::COMPUTE_SHADER);
+    collect(
+        vec![
+            on_family(0, vec![], 0, vec![(0, write), (1, write)]),
+            on_family(1, vec![0], 1, vec![(0, read)]),
+            on_family(2, vec![0], 2, vec![(1, read)]),
+        ],
+        |_| 1,
+    )
+}
+
+#[test]
+fn fan_out_signals_timeline_once() {
+    let chains = fan_out();
+
+    let mut counter = 0;
+    let binary: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+    assert_eq!(submission_for(&binary, 0).sync().signal.len(), 2);
+
+    let schedule = sync_timeline(&chains, SyncOptions::default(), |_| PipelineStageFlags::all());
+    let producer = submission_for(&schedule, 0);
+    let value = TimelineValue {
+        queue: producer.id().queue(),
+        value: producer.id().index() as u64 + 1,
+    };
+    assert_eq!(producer.sync().signal.len(), 1);
+    assert_eq!(*producer.sync().signal[0].semaphore(), value);
+
+    for &consumer in &[1, 2] {
+        let waits = &submission_for(&schedule, consumer).sync().wait;
+        assert_eq!(waits.len(), 1);
+        assert_eq!(*waits[0].semaphore(), value);
+    }
+}
+
+#[test]
+fn binary_semaphores_without_timeline_feature() {
+    let chains = fan_out();
+    let mut counter = 0;
+    let new_semaphore = || {
+        counter += 1;
+        (counter, counter)
+    };
+
+    match sync_with_features(
+        &chains,
+        SyncOptions::default(),
+        DeviceFeatures::default(),
+        |_| PipelineStageFlags::all(),
+        new_semaphore,
+    ) {
+        SemaphoreSync::Binary(_) => {}
+        SemaphoreSync::Timeline(_) => panic!("Timeline semaphores are not supported"),
+    }
+}
+
+#[test]
+fn timeline_waits_clamped_to_family_stages() {
+    let chains = fan_out();
+    let schedule = sync_timeline(&chains, SyncOptions::default(), |family| match family {
+        FamilyId(0) => PipelineStageFlags::all(),
+        _ => PipelineStageFlags::TRANSFER,
+    });
+
+    for &consumer in &[1, 2] {
+        let waits = &submission_for(&schedule, consumer).sync().wait;
+        assert_eq!(waits[0].stage(), PipelineStageFlags::ALL_COMMANDS);
+    }
+}
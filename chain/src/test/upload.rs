@@ -0,0 +1,67 @@
+use collect::{collect_with, CollectOptions};
+use sync::sync;
+
+use super::*;
+
+const GRAPHICS: FamilyId = FamilyId(0);
+const TRANSFER: FamilyId = FamilyId(1);
+
+#[test]
+fn upload_on_transfer_queue() {
+    let mut upload = node(
+        0,
+        vec![],
+        vec![(
+            0,
+            buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER),
+        )],
+    );
+    upload.upload = true;
+    let draw = node(
+        1,
+        vec![0],
+        vec![(
+            0,
+            buffer_state(
+                AccessFlags::VERTEX_ATTRIBUTE_READ,
+                PipelineStageFlags::VERTEX_INPUT,
+            ),
+        )],
+    );
+
+    let chains = collect_with(
+        vec![upload, draw],
+        |_| 1,
+        CollectOptions {
+            upload_family: Some(TRANSFER),
+            ..CollectOptions::default()
+        },
+    );
+    assert_eq!(submission_for(&chains.schedule, 0).id().family(), TRANSFER);
+    assert_eq!(submission_for(&chains.schedule, 1).id().family(), GRAPHICS);
+
+    let mut counter = 0;
+    let schedule: Synced = sync(&chains, || {
+        counter += 1;
+        (counter, counter)
+    });
+
+    let producer = submission_for(&schedule, 0);
+    let consumer = submission_for(&schedule, 1);
+    let queues = producer.id().queue()..consumer.id().queue();
+
+    assert_eq!(producer.sync().signal.len(), 1);
+    assert_eq!(consumer.sync().wait.len(), 1);
+    assert_eq!(
+        producer.sync().signal[0].semaphore(),
+        consumer.sync().wait[0].semaphore()
+    );
+
+    let ref release = producer.sync().release.buffers[&Id(0)];
+    assert_eq!(release.queues, Some(queues.clone()));
+    assert_eq!(release.states.start.0, AccessFlags::TRANSFER_WRITE);
+
+    let ref acquire = consumer.sync().acquire.buffers[&Id(0)];
+    assert_eq!(acquire.queues, Some(queues));
+    assert_eq!(acquire.states.end.0, AccessFlags::VERTEX_ATTRIBUTE_READ);
+}
@@ -1,12 +1,21 @@
 //! Buffer module docs.
 
 use relevant::Relevant;
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::Range};
 
+use chain::{AccessFlags, PipelineStageFlags};
+use resource::image;
+
+use capability::Capability;
 use device::CommandBuffer;
-use encoder::Encoder;
+use encoder::{
+    AttachmentClear, ClearColor, ClearDepthStencil, ClearRect, ClearValue, DepthBias, Encoder,
+    LabelStack, Rect, Viewport,
+};
+use error::SubmitPending;
 use family::FamilyId;
 use frame::FrameBound;
+use query::QueryControlFlags;
 
 /// Command buffers of this level can be submitted to the command queues.
 #[derive(Clone, Copy, Debug)]
@@ -16,6 +25,35 @@ pub struct PrimaryLevel;
 #[derive(Clone, Copy, Debug)]
 pub struct SecondaryLevel;
 
+/// Level of raw command buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RawLevel {
+    /// Primary command buffer.
+    Primary,
+
+    /// Secondary command buffer.
+    Secondary,
+}
+
+/// Specifies level of command buffers.
+/// Implemented by `PrimaryLevel` and `SecondaryLevel`.
+pub trait Level: Copy + Debug {
+    /// Get level of raw command buffers.
+    fn raw_level(&self) -> RawLevel;
+}
+
+impl Level for PrimaryLevel {
+    fn raw_level(&self) -> RawLevel {
+        RawLevel::Primary
+    }
+}
+
+impl Level for SecondaryLevel {
+    fn raw_level(&self) -> RawLevel {
+        RawLevel::Secondary
+    }
+}
+
 /// This flag specify that buffer can be reset individually.
 /// Without this flag buffer can be reset only together with all other buffers from pool.
 #[derive(Clone, Copy, Debug)]
@@ -27,8 +65,9 @@ pub struct IndividualReset;
 pub struct InitialState;
 
 /// Command buffer in recording state could be populated with commands.
+/// Tracks debug label regions opened during recording.
 #[derive(Clone, Copy, Debug)]
-pub struct RecordingState<U>(U);
+pub struct RecordingState<U>(U, LabelStack);
 
 /// Command buffer in executable state can be submitted.
 #[derive(Clone, Copy, Debug)]
@@ -59,6 +98,28 @@ impl<U> Resettable for RecordingState<U> {}
 impl<U> Resettable for ExecutableState<U> {}
 impl Resettable for InvalidState {}
 
+/// States in which pending command buffer can end up after completion.
+/// Those are encoding usage the buffer was begun with.
+pub trait CompletionState {
+    /// Get usage flags the buffer was begun with.
+    fn usage_flags(&self) -> UsageFlags;
+}
+
+impl CompletionState for InvalidState {
+    fn usage_flags(&self) -> UsageFlags {
+        UsageFlags::ONE_TIME_SUBMIT
+    }
+}
+
+impl<U> CompletionState for ExecutableState<U>
+where
+    U: Usage,
+{
+    fn usage_flags(&self) -> UsageFlags {
+        self.0.flags()
+    }
+}
+
 /// Buffer with this usage flag will move to invalid state after execution.
 /// Resubmitting will require reset and rerecording commands.
 #[derive(Clone, Copy, Debug)]
@@ -68,6 +129,19 @@ pub struct OneShot;
 #[derive(Clone, Copy, Debug)]
 pub struct MultiShot<S = ()>(S);
 
+impl Default for MultiShot {
+    fn default() -> Self {
+        MultiShot(())
+    }
+}
+
+impl MultiShot {
+    /// Allow buffer to be resubmitted while it is pending.
+    pub fn simultaneous(self) -> MultiShot<SimultaneousUse> {
+        MultiShot(SimultaneousUse)
+    }
+}
+
 /// Additional flag for `MultiShot` that allows to resubmit buffer in pending state.
 /// Note that resubmitting pending buffers can hurt performance.
 #[derive(Clone, Copy, Debug)]
@@ -77,6 +151,16 @@ pub struct SimultaneousUse;
 #[derive(Clone, Copy, Debug)]
 pub struct RenderPassContinue;
 
+/// Render pass and subpass in which secondary command buffer will be executed.
+#[derive(Clone, Copy, Debug)]
+pub struct InheritanceInfo<'a, P: 'a> {
+    /// Render pass secondary buffer will be executed within.
+    pub render_pass: &'a P,
+
+    /// Index of the subpass secondary buffer will be executed within.
+    pub subpass: u32,
+}
+
 bitflags!{
     /// Bitmask specifying usage behavior for command buffer
     /// See Vulkan docs for detailed info:
@@ -136,17 +220,113 @@ pub struct Buffer<B, C, S, L, R = ()> {
     relevant: Relevant,
 }
 
-impl<B, C, R> Buffer<B, C, InitialState, PrimaryLevel, R> {
+impl<B, C, L, R> Buffer<B, C, InitialState, L, R> {
+    /// Wrap raw command buffer allocated from pool.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must be allocated with `level` from pool created for the family with `family` id.
+    pub(crate) unsafe fn from_raw(
+        inner: B,
+        capability: C,
+        level: L,
+        reset: R,
+        family: FamilyId,
+    ) -> Self {
+        Buffer {
+            inner,
+            capability,
+            state: InitialState,
+            level,
+            reset,
+            family,
+            relevant: Relevant,
+        }
+    }
+}
+
+impl<B, C, S, L, R> Buffer<B, C, S, L, R> {
+    /// Get id of the family this buffer is associated with.
+    pub fn family(&self) -> FamilyId {
+        self.family
+    }
+
+    /// Move buffer to the state derived from current one.
+    fn map_state<T, F>(self, f: F) -> Buffer<B, C, T, L, R>
+    where
+        F: FnOnce(S) -> T,
+    {
+        Buffer {
+            inner: self.inner,
+            capability: self.capability,
+            state: f(self.state),
+            level: self.level,
+            reset: self.reset,
+            family: self.family,
+            relevant: self.relevant,
+        }
+    }
+}
+
+impl<B, C, S, L, R> Buffer<B, C, S, L, R>
+where
+    S: Droppable,
+{
+    /// Unwrap raw command buffer to free it.
+    pub(crate) fn into_raw(self) -> B {
+        self.relevant.dispose();
+        self.inner
+    }
+}
+
+impl<B, C, R> Buffer<B, C, InitialState, PrimaryLevel, R>
+where
+    B: CommandBuffer,
+{
     /// Begin recording command buffer.
     ///
     /// # Parameters
     ///
     /// `usage` - specifies usage of the command buffer. Possible types are `OneShot`, `MultiShot`.
-    pub fn begin<U>(self, usage: U) -> Buffer<B, C, RecordingState<U>, PrimaryLevel, R>
+    pub fn begin<U>(mut self, usage: U) -> Buffer<B, C, RecordingState<U>, PrimaryLevel, R>
     where
         U: Usage,
     {
-        unimplemented!()
+        unsafe {
+            self.inner.begin(usage.flags());
+        }
+        self.map_state(|_| RecordingState(usage, LabelStack::default()))
+    }
+}
+
+impl<B, C, R> Buffer<B, C, InitialState, SecondaryLevel, R>
+where
+    B: CommandBuffer,
+{
+    /// Begin recording secondary command buffer.
+    ///
+    /// # Parameters
+    ///
+    /// `usage` - specifies usage of the command buffer. Possible types are `OneShot`, `MultiShot`.
+    ///
+    /// `inheritance` - render pass and subpass the buffer will be executed within.
+    /// `None` if the buffer will be executed outside of render pass.
+    pub fn begin<'a, U>(
+        mut self,
+        usage: U,
+        inheritance: Option<InheritanceInfo<'a, B::RenderPass>>,
+    ) -> Buffer<B, C, RecordingState<U>, SecondaryLevel, R>
+    where
+        U: Usage,
+    {
+        let mut flags = usage.flags();
+        if inheritance.is_some() {
+            flags |= UsageFlags::RENDER_PASS_CONTINUE;
+        }
+        unsafe {
+            self.inner.begin_secondary(flags, inheritance);
+        }
+        self.map_state(|_| RecordingState(usage, LabelStack::default()))
     }
 }
 
@@ -169,6 +349,19 @@ impl<S> Submit<S> {
     }
 }
 
+/// Check that command buffer associated with `found` family is used with `expected` family.
+///
+/// # Panics
+///
+/// This function panics if families differ.
+pub(crate) fn assert_family(expected: FamilyId, found: FamilyId, action: &str) {
+    assert_eq!(
+        expected, found,
+        "Command buffer of family {:?} cannot be {} family {:?}",
+        found, action, expected
+    );
+}
+
 impl<B, C, R> Buffer<B, C, ExecutableState<OneShot>, PrimaryLevel, R>
 where
     B: CommandBuffer,
@@ -180,7 +373,11 @@ where
         Submit<B::Submit>,
         Buffer<B, C, PendingState<InvalidState>, PrimaryLevel, R>,
     ) {
-        unimplemented!()
+        let submit = Submit {
+            raw: unsafe { self.inner.submit() },
+            family: self.family,
+        };
+        (submit, self.map_state(|_| PendingState(InvalidState)))
     }
 }
 
@@ -195,7 +392,32 @@ where
         Submit<B::Submit>,
         Buffer<B, C, PendingState<ExecutableState<MultiShot<S>>>, PrimaryLevel, R>,
     ) {
-        unimplemented!()
+        let submit = Submit {
+            raw: unsafe { self.inner.submit() },
+            family: self.family,
+        };
+        (submit, self.map_state(PendingState))
+    }
+}
+
+impl<B, C, N, R> Buffer<B, C, PendingState<N>, PrimaryLevel, R>
+where
+    B: CommandBuffer,
+    N: CompletionState,
+{
+    /// Produce `Submit` object to submit command buffer again while it is pending.
+    /// Only buffers begun with `MultiShot<SimultaneousUse>` can be resubmitted,
+    /// others can be submitted again only after completion.
+    /// Buffer completes when all its submissions complete.
+    pub fn resubmit(&self) -> Result<Submit<B::Submit>, SubmitPending> {
+        let usage = self.state.0.usage_flags();
+        if !usage.contains(UsageFlags::SIMULTANEOUS_USE) {
+            return Err(SubmitPending(usage));
+        }
+        Ok(Submit {
+            raw: unsafe { self.inner.submit() },
+            family: self.family,
+        })
     }
 }
 
@@ -205,18 +427,73 @@ impl<B, C, N, L, R> Buffer<B, C, PendingState<N>, L, R> {
     /// # Safety
     ///
     /// User must ensure that recorded commands are complete.
+    /// Resubmitted buffer must complete all its submissions.
     pub unsafe fn complete(self) -> Buffer<B, C, N, L, R> {
-        unimplemented!()
+        self.map_state(|PendingState(next)| next)
     }
 }
 
 impl<B, C, S, L> Buffer<B, C, S, L, IndividualReset>
 where
+    B: CommandBuffer,
     S: Resettable,
 {
     /// Reset command buffer.
-    pub fn reset(self) -> Buffer<B, C, InitialState, L, IndividualReset> {
-        unimplemented!()
+    /// Debug label regions left open by previous recording are discarded.
+    pub fn reset(mut self) -> Buffer<B, C, InitialState, L, IndividualReset> {
+        unsafe {
+            self.inner.reset();
+        }
+        self.map_state(|_| InitialState)
+    }
+}
+
+impl<B, C, S> Buffer<B, C, S, PrimaryLevel, IndividualReset>
+where
+    B: CommandBuffer,
+    S: Resettable,
+{
+    /// Reset command buffer and begin recording it again.
+    /// This is the re-record loop of long-lived command buffer.
+    /// See `reset` and `begin`.
+    ///
+    /// # Parameters
+    ///
+    /// `usage` - specifies usage of the command buffer. Possible types are `OneShot`, `MultiShot`.
+    pub fn reset_and_begin<U>(
+        self,
+        usage: U,
+    ) -> Buffer<B, C, RecordingState<U>, PrimaryLevel, IndividualReset>
+    where
+        U: Usage,
+    {
+        self.reset().begin(usage)
+    }
+}
+
+impl<B, C, S> Buffer<B, C, S, SecondaryLevel, IndividualReset>
+where
+    B: CommandBuffer,
+    S: Resettable,
+{
+    /// Reset secondary command buffer and begin recording it again.
+    /// See `reset` and `begin`.
+    ///
+    /// # Parameters
+    ///
+    /// `usage` - specifies usage of the command buffer. Possible types are `OneShot`, `MultiShot`.
+    ///
+    /// `inheritance` - render pass and subpass the buffer will be executed within.
+    /// `None` if the buffer will be executed outside of render pass.
+    pub fn reset_and_begin<'a, U>(
+        self,
+        usage: U,
+        inheritance: Option<InheritanceInfo<'a, B::RenderPass>>,
+    ) -> Buffer<B, C, RecordingState<U>, SecondaryLevel, IndividualReset>
+    where
+        U: Usage,
+    {
+        self.reset().begin(usage, inheritance)
     }
 }
 
@@ -238,24 +515,298 @@ where
 impl<B, C, U, L, R> Encoder<C> for Buffer<B, C, RecordingState<U>, L, R>
 where
     B: CommandBuffer,
+    C: Capability,
 {
     type Buffer = B;
 
+    fn capability(&self) -> C {
+        self.capability
+    }
+
     unsafe fn buffer(&mut self) -> &mut B {
         &mut self.inner
     }
 }
 
+impl<B, C, U, L, R> Buffer<B, C, RecordingState<U>, L, R>
+where
+    B: CommandBuffer,
+{
+    /// Open labeled region of commands.
+    /// Capture tools will show commands recorded until matching `end_debug_label` under `name`.
+    pub fn begin_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        self.state.1.begin();
+        unsafe {
+            self.inner.begin_debug_label(name, color);
+        }
+    }
+
+    /// Close innermost labeled region of commands.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds panics if there is no region opened.
+    pub fn end_debug_label(&mut self) {
+        self.state.1.end();
+        unsafe {
+            self.inner.end_debug_label();
+        }
+    }
+
+    /// Insert single label.
+    pub fn insert_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        unsafe {
+            self.inner.insert_debug_label(name, color);
+        }
+    }
+
+    /// Get number of labeled regions currently opened.
+    pub fn debug_label_depth(&self) -> usize {
+        self.state.1.depth()
+    }
+
+    /// Finish recording command buffer.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds panics if labeled regions are left opened.
+    pub fn finish(mut self) -> Buffer<B, C, ExecutableState<U>, L, R> {
+        debug_assert_eq!(
+            self.state.1.depth(),
+            0,
+            "Recording finished with labeled regions opened"
+        );
+        unsafe {
+            self.inner.finish();
+        }
+        self.map_state(|RecordingState(usage, _)| ExecutableState(usage))
+    }
+}
+
+impl<B, C, U, R> Buffer<B, C, RecordingState<U>, PrimaryLevel, R>
+where
+    B: CommandBuffer,
+{
+    /// Execute secondary command buffers.
+    /// Only secondary buffers that finished recording can be executed.
+    ///
+    /// ```compile_fail
+    /// # extern crate rendy_command;
+    /// # use std::fmt::Debug;
+    /// # use rendy_command::{CommandBuffer, Device, Graphics, OneShot, OwningPool, PrimaryLevel, SecondaryPool};
+    /// fn record<D, B>(
+    ///     device: &D,
+    ///     primaries: &mut OwningPool<D::CommandPool, B, Graphics>,
+    ///     secondaries: &mut SecondaryPool<D::CommandPool, B, Graphics>,
+    /// ) where
+    ///     B: CommandBuffer + Debug + 'static,
+    ///     D: Device<CommandBuffer = B, Submit = B::Submit>,
+    ///     D::CommandPool: Debug,
+    /// {
+    ///     let secondary = secondaries.acquire_buffer(device).begin(OneShot, None);
+    ///     let mut primary = primaries.acquire_buffer(device, PrimaryLevel).begin(OneShot);
+    ///     // Secondary buffer is still recording.
+    ///     primary.execute_commands(&[&secondary]);
+    /// }
+    /// # fn main() {}
+    /// ```
+    ///
+    /// Secondary buffers can't execute other secondary buffers.
+    ///
+    /// ```compile_fail
+    /// # extern crate rendy_command;
+    /// # use std::fmt::Debug;
+    /// # use rendy_command::{CommandBuffer, Device, Graphics, OneShot, SecondaryPool};
+    /// fn record<D, B>(
+    ///     device: &D,
+    ///     outer: &mut SecondaryPool<D::CommandPool, B, Graphics>,
+    ///     inner: &mut SecondaryPool<D::CommandPool, B, Graphics>,
+    /// ) where
+    ///     B: CommandBuffer + Debug + 'static,
+    ///     D: Device<CommandBuffer = B, Submit = B::Submit>,
+    ///     D::CommandPool: Debug,
+    /// {
+    ///     let mut outer = outer.acquire_buffer(device).begin(OneShot, None);
+    ///     let inner = inner.acquire_buffer(device).begin(OneShot, None).finish();
+    ///     outer.execute_commands(&[&inner]);
+    /// }
+    /// # fn main() {}
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any secondary buffer belongs to another family.
+    pub fn execute_commands<S, T>(
+        &mut self,
+        secondaries: &[&Buffer<B, C, ExecutableState<S>, SecondaryLevel, T>],
+    ) {
+        for secondary in secondaries {
+            assert_family(self.family, secondary.family, "executed in buffer of");
+        }
+        unsafe {
+            self.inner
+                .execute_commands(secondaries.iter().map(|secondary| &secondary.inner));
+        }
+    }
+}
+
 impl<'a, F: 'a, B> CommandBuffer for FrameBound<'a, F, B>
 where
     B: CommandBuffer,
     F: Debug,
 {
     type Submit = FrameBound<'a, F, B::Submit>;
+    type QueryPool = B::QueryPool;
+    type Buffer = B::Buffer;
+    type Image = B::Image;
+    type RenderPass = B::RenderPass;
+    type Framebuffer = B::Framebuffer;
 
     unsafe fn submit(&self) -> FrameBound<'a, F, B::Submit> {
         FrameBound::bind(self.inner_ref().submit(), self.frame())
     }
+
+    unsafe fn begin(&mut self, usage: UsageFlags) {
+        self.inner_mut().begin(usage)
+    }
+
+    unsafe fn begin_secondary<'b>(
+        &mut self,
+        usage: UsageFlags,
+        inheritance: Option<InheritanceInfo<'b, B::RenderPass>>,
+    ) {
+        self.inner_mut().begin_secondary(usage, inheritance)
+    }
+
+    unsafe fn finish(&mut self) {
+        self.inner_mut().finish()
+    }
+
+    unsafe fn reset(&mut self) {
+        self.inner_mut().reset()
+    }
+
+    unsafe fn begin_query(&mut self, pool: &B::QueryPool, query: u32, flags: QueryControlFlags) {
+        self.inner_mut().begin_query(pool, query, flags)
+    }
+
+    unsafe fn end_query(&mut self, pool: &B::QueryPool, query: u32) {
+        self.inner_mut().end_query(pool, query)
+    }
+
+    unsafe fn reset_query_pool(&mut self, pool: &B::QueryPool, queries: Range<u32>) {
+        self.inner_mut().reset_query_pool(pool, queries)
+    }
+
+    unsafe fn clear_color_image(
+        &mut self,
+        image: &B::Image,
+        layout: image::Layout,
+        color: ClearColor,
+        ranges: &[image::SubresourceRange],
+    ) {
+        self.inner_mut().clear_color_image(image, layout, color, ranges)
+    }
+
+    unsafe fn clear_depth_stencil_image(
+        &mut self,
+        image: &B::Image,
+        layout: image::Layout,
+        value: ClearDepthStencil,
+        ranges: &[image::SubresourceRange],
+    ) {
+        self.inner_mut()
+            .clear_depth_stencil_image(image, layout, value, ranges)
+    }
+
+    unsafe fn clear_attachments(&mut self, clears: &[AttachmentClear], rects: &[ClearRect]) {
+        self.inner_mut().clear_attachments(clears, rects)
+    }
+
+    unsafe fn begin_render_pass(
+        &mut self,
+        render_pass: &B::RenderPass,
+        framebuffer: &B::Framebuffer,
+        area: Rect,
+        clears: &[ClearValue],
+    ) {
+        self.inner_mut()
+            .begin_render_pass(render_pass, framebuffer, area, clears)
+    }
+
+    unsafe fn end_render_pass(&mut self) {
+        self.inner_mut().end_render_pass()
+    }
+
+    unsafe fn set_viewport(&mut self, first: u32, viewports: &[Viewport]) {
+        self.inner_mut().set_viewport(first, viewports)
+    }
+
+    unsafe fn set_scissor(&mut self, first: u32, scissors: &[Rect]) {
+        self.inner_mut().set_scissor(first, scissors)
+    }
+
+    unsafe fn set_line_width(&mut self, width: f32) {
+        self.inner_mut().set_line_width(width)
+    }
+
+    unsafe fn set_depth_bias(&mut self, bias: DepthBias) {
+        self.inner_mut().set_depth_bias(bias)
+    }
+
+    unsafe fn set_blend_constants(&mut self, constants: [f32; 4]) {
+        self.inner_mut().set_blend_constants(constants)
+    }
+
+    unsafe fn fill_buffer(&mut self, buffer: &B::Buffer, offset: u64, size: u64, value: u32) {
+        self.inner_mut().fill_buffer(buffer, offset, size, value)
+    }
+
+    unsafe fn update_buffer(&mut self, buffer: &B::Buffer, offset: u64, data: &[u8]) {
+        self.inner_mut().update_buffer(buffer, offset, data)
+    }
+
+    unsafe fn copy_buffer_to_image(
+        &mut self,
+        buffer: &B::Buffer,
+        image: &B::Image,
+        layout: image::Layout,
+        regions: &[image::BufferImageCopy],
+    ) {
+        self.inner_mut().copy_buffer_to_image(buffer, image, layout, regions)
+    }
+
+    unsafe fn image_barrier(
+        &mut self,
+        image: &B::Image,
+        stages: Range<PipelineStageFlags>,
+        access: Range<AccessFlags>,
+        layouts: Range<image::Layout>,
+        range: image::SubresourceRange,
+    ) {
+        self.inner_mut().image_barrier(image, stages, access, layouts, range)
+    }
+
+    unsafe fn execute_commands<'b, I>(&mut self, buffers: I)
+    where
+        I: IntoIterator<Item = &'b Self>,
+        Self: 'b,
+    {
+        self.inner_mut()
+            .execute_commands(buffers.into_iter().map(|buffer| buffer.inner_ref()))
+    }
+
+    unsafe fn begin_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        self.inner_mut().begin_debug_label(name, color)
+    }
+
+    unsafe fn end_debug_label(&mut self) {
+        self.inner_mut().end_debug_label()
+    }
+
+    unsafe fn insert_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        self.inner_mut().insert_debug_label(name, color)
+    }
 }
 
 impl<'a, F: 'a, B, S, L, C> Buffer<FrameBound<'a, F, B>, C, S, L> {
@@ -276,3 +276,35 @@ pub fn required_queue_capability(stages: PipelineStageFlags) -> CapabilityFlags
     }
     capability
 }
+
+/// Get pipeline stages supported by queues with specified capabilities.
+pub fn supported_stages(capability: CapabilityFlags) -> PipelineStageFlags {
+    let mut stages = PipelineStageFlags::TOP_OF_PIPE
+        | PipelineStageFlags::BOTTOM_OF_PIPE
+        | PipelineStageFlags::HOST
+        | PipelineStageFlags::ALL_COMMANDS;
+    if capability.intersects(
+        CapabilityFlags::GRAPHICS | CapabilityFlags::COMPUTE | CapabilityFlags::TRANSFER,
+    ) {
+        stages |= PipelineStageFlags::TRANSFER;
+    }
+    if capability.intersects(CapabilityFlags::GRAPHICS | CapabilityFlags::COMPUTE) {
+        stages |= PipelineStageFlags::DRAW_INDIRECT;
+    }
+    if capability.contains(CapabilityFlags::GRAPHICS) {
+        stages |= PipelineStageFlags::VERTEX_INPUT
+            | PipelineStageFlags::VERTEX_SHADER
+            | PipelineStageFlags::TESSELLATION_CONTROL_SHADER
+            | PipelineStageFlags::TESSELLATION_EVALUATION_SHADER
+            | PipelineStageFlags::GEOMETRY_SHADER
+            | PipelineStageFlags::FRAGMENT_SHADER
+            | PipelineStageFlags::EARLY_FRAGMENT_TESTS
+            | PipelineStageFlags::LATE_FRAGMENT_TESTS
+            | PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+            | PipelineStageFlags::ALL_GRAPHICS;
+    }
+    if capability.contains(CapabilityFlags::COMPUTE) {
+        stages |= PipelineStageFlags::COMPUTE_SHADER;
+    }
+    stages
+}
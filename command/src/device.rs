@@ -1,9 +1,19 @@
 //! Device module docs.
 
-use std::{borrow::Borrow, fmt::Debug};
+use std::{borrow::Borrow, fmt::Debug, ops::Range};
 
-use resource;
+use chain::{AccessFlags, PipelineStageFlags};
+use resource::{self, image};
+use buffer::{InheritanceInfo, RawLevel, UsageFlags};
+use encoder::{
+    AttachmentClear, ClearColor, ClearDepthStencil, ClearRect, ClearValue, DepthBias, Rect,
+    Viewport,
+};
+use error::DeviceLost;
+use family::FamilyId;
 use fence::FenceCreateInfo;
+use pool::PoolCreateFlags;
+use query::{QueryControlFlags, QueryType};
 
 /// Abstract logical device.
 /// It inherits methods to allocate memory and create resources.
@@ -20,8 +30,19 @@ pub trait Device: resource::Device {
     /// Command pool type that can be used with this device.
     type CommandPool: 'static;
 
+    /// Pipeline cache type that can be used with this device.
+    type PipelineCache: 'static;
+
+    /// Query pool type that can be used with this device.
+    type QueryPool: Debug + 'static;
+
     /// Command buffer type that can be used with this device.
-    type CommandBuffer: CommandBuffer<Submit = Self::Submit> + 'static;
+    type CommandBuffer: CommandBuffer<
+            Submit = Self::Submit,
+            QueryPool = Self::QueryPool,
+            Buffer = <Self as resource::Device>::Buffer,
+            Image = <Self as resource::Device>::Image,
+        > + 'static;
 
     /// Command queue type that can be used with this device.
     type CommandQueue: CommandQueue<
@@ -33,6 +54,10 @@ pub trait Device: resource::Device {
     /// Create new fence.
     unsafe fn create_fence(&self, info: FenceCreateInfo) -> Self::Fence;
 
+    /// Destroy fence.
+    /// Fence must not be used by pending submissions.
+    unsafe fn destroy_fence(&self, fence: Self::Fence);
+
     /// Reset fence.
     unsafe fn reset_fence(&self, fence: &Self::Fence) {
         self.reset_fences(Some(fence))
@@ -46,6 +71,93 @@ pub trait Device: resource::Device {
     {
         fences.into_iter().for_each(|fence| self.reset_fence(fence.borrow()));
     }
+
+    /// Create new semaphore.
+    unsafe fn create_semaphore(&self) -> Self::Semaphore;
+
+    /// Destroy semaphore.
+    /// Semaphore must not be used by pending submissions.
+    unsafe fn destroy_semaphore(&self, semaphore: Self::Semaphore);
+
+    /// Create new command pool for specified family.
+    unsafe fn create_command_pool(
+        &self,
+        family: FamilyId,
+        flags: PoolCreateFlags,
+    ) -> Self::CommandPool;
+
+    /// Destroy command pool.
+    /// All buffers allocated from the pool must be freed.
+    unsafe fn destroy_command_pool(&self, pool: Self::CommandPool);
+
+    /// Allocate command buffer of `level` from the pool.
+    unsafe fn allocate_command_buffer(
+        &self,
+        pool: &mut Self::CommandPool,
+        level: RawLevel,
+    ) -> Self::CommandBuffer;
+
+    /// Free command buffer.
+    /// Buffer must be allocated from the pool and must not be in pending state.
+    unsafe fn free_command_buffer(&self, pool: &mut Self::CommandPool, buffer: Self::CommandBuffer);
+
+    /// Reset command pool moving all buffers allocated from it to the initial state.
+    /// No buffer allocated from the pool may be in pending state.
+    unsafe fn reset_command_pool(&self, pool: &mut Self::CommandPool);
+
+    /// Submit command buffers to the queue without waiting or signaling semaphores.
+    /// `fence` gets signaled when all buffers complete.
+    unsafe fn queue_submit(
+        &self,
+        queue: &mut Self::CommandQueue,
+        buffers: &[Self::Submit],
+        fence: Option<&Self::Fence>,
+    ) {
+        let buffers = buffers.iter().collect::<Vec<_>>();
+        self.queue_submit_batch(queue, &[], &buffers, &[], fence)
+    }
+
+    /// Submit batch of command buffers to the queue.
+    /// Buffers wait for `waits` semaphores at specified stages
+    /// and `signals` semaphores get signaled when all buffers complete.
+    /// `fence` gets signaled when all buffers and all commands submitted to the queue before complete.
+    unsafe fn queue_submit_batch(
+        &self,
+        queue: &mut Self::CommandQueue,
+        waits: &[(&Self::Semaphore, PipelineStageFlags)],
+        buffers: &[&Self::Submit],
+        signals: &[&Self::Semaphore],
+        fence: Option<&Self::Fence>,
+    );
+
+    /// Block until fence is signaled.
+    unsafe fn wait_for_fence(&self, fence: &Self::Fence) -> Result<(), DeviceLost>;
+
+    /// Create pipeline cache initialized with data previously retrieved from compatible device.
+    /// Implementation ignores data that is incompatible.
+    unsafe fn create_pipeline_cache(&self, data: &[u8]) -> Self::PipelineCache;
+
+    /// Retrieve data from pipeline cache.
+    unsafe fn get_pipeline_cache_data(&self, cache: &Self::PipelineCache) -> Vec<u8>;
+
+    /// Destroy pipeline cache.
+    unsafe fn destroy_pipeline_cache(&self, cache: Self::PipelineCache);
+
+    /// Create pool of `count` queries of specified type.
+    unsafe fn create_query_pool(&self, ty: QueryType, count: u32) -> Self::QueryPool;
+
+    /// Destroy query pool.
+    unsafe fn destroy_query_pool(&self, pool: Self::QueryPool);
+
+    /// Read results of the `queries` without waiting for them.
+    /// For each query all its values are written followed by availability value
+    /// which is non-zero if results of the query are available.
+    unsafe fn get_query_pool_results(
+        &self,
+        pool: &Self::QueryPool,
+        queries: Range<u32>,
+        data: &mut [u64],
+    ) -> Result<(), DeviceLost>;
 }
 
 /// Abstract command buffer.
@@ -55,9 +167,169 @@ pub trait CommandBuffer {
     /// Raw command buffer can be cloned.
     type Submit;
 
+    /// Query pool type that can be used with this buffer.
+    type QueryPool;
+
+    /// Buffer type that can be used with this buffer.
+    type Buffer;
+
+    /// Image type that can be used with this buffer.
+    type Image;
+
+    /// Render pass type that can be used with this buffer.
+    type RenderPass;
+
+    /// Framebuffer type that can be used with this buffer.
+    type Framebuffer;
+
     /// Get submittable object.
     /// Buffer must be in executable state.
     unsafe fn submit(&self) -> Self::Submit;
+
+    /// Begin recording.
+    /// Buffer must be in initial state.
+    unsafe fn begin(&mut self, usage: UsageFlags);
+
+    /// Begin recording secondary buffer.
+    /// Buffer must be allocated with secondary level and be in initial state.
+    /// `inheritance` is `Some` if and only if `usage` contains `RENDER_PASS_CONTINUE`.
+    unsafe fn begin_secondary<'a>(
+        &mut self,
+        usage: UsageFlags,
+        inheritance: Option<InheritanceInfo<'a, Self::RenderPass>>,
+    );
+
+    /// Finish recording.
+    /// Buffer moves to executable state.
+    unsafe fn finish(&mut self);
+
+    /// Reset buffer.
+    /// Buffer moves to initial state.
+    /// Pool buffer was allocated from must allow individual reset.
+    unsafe fn reset(&mut self);
+
+    /// Begin query.
+    /// Buffer must be in recording state.
+    unsafe fn begin_query(&mut self, pool: &Self::QueryPool, query: u32, flags: QueryControlFlags);
+
+    /// End query started by `begin_query`.
+    /// Buffer must be in recording state.
+    unsafe fn end_query(&mut self, pool: &Self::QueryPool, query: u32);
+
+    /// Reset queries so they can be used again.
+    /// Buffer must be in recording state outside of render pass.
+    unsafe fn reset_query_pool(&mut self, pool: &Self::QueryPool, queries: Range<u32>);
+
+    /// Clear color image ranges.
+    /// Buffer must be in recording state outside of render pass.
+    unsafe fn clear_color_image(
+        &mut self,
+        image: &Self::Image,
+        layout: image::Layout,
+        color: ClearColor,
+        ranges: &[image::SubresourceRange],
+    );
+
+    /// Clear depth-stencil image ranges.
+    /// Buffer must be in recording state outside of render pass.
+    unsafe fn clear_depth_stencil_image(
+        &mut self,
+        image: &Self::Image,
+        layout: image::Layout,
+        value: ClearDepthStencil,
+        ranges: &[image::SubresourceRange],
+    );
+
+    /// Clear regions of attachments of the current subpass.
+    /// Buffer must be in recording state inside render pass.
+    unsafe fn clear_attachments(&mut self, clears: &[AttachmentClear], rects: &[ClearRect]);
+
+    /// Begin render pass instance.
+    /// Buffer must be in recording state outside of render pass.
+    /// Subpass contents are recorded inline.
+    unsafe fn begin_render_pass(
+        &mut self,
+        render_pass: &Self::RenderPass,
+        framebuffer: &Self::Framebuffer,
+        area: Rect,
+        clears: &[ClearValue],
+    );
+
+    /// End render pass instance started by `begin_render_pass`.
+    /// Buffer must be in recording state inside render pass.
+    unsafe fn end_render_pass(&mut self);
+
+    /// Set viewports starting from `first`.
+    /// Buffer must be in recording state.
+    unsafe fn set_viewport(&mut self, first: u32, viewports: &[Viewport]);
+
+    /// Set scissors starting from `first`.
+    /// Buffer must be in recording state.
+    unsafe fn set_scissor(&mut self, first: u32, scissors: &[Rect]);
+
+    /// Set width of rasterized lines.
+    /// Buffer must be in recording state.
+    unsafe fn set_line_width(&mut self, width: f32);
+
+    /// Set depth bias parameters.
+    /// Buffer must be in recording state.
+    unsafe fn set_depth_bias(&mut self, bias: DepthBias);
+
+    /// Set blend constants.
+    /// Buffer must be in recording state.
+    unsafe fn set_blend_constants(&mut self, constants: [f32; 4]);
+
+    /// Fill `size` bytes of the buffer starting from `offset` with repeated `value`.
+    /// Buffer must be in recording state outside of render pass.
+    unsafe fn fill_buffer(&mut self, buffer: &Self::Buffer, offset: u64, size: u64, value: u32);
+
+    /// Write inline `data` to the buffer starting from `offset`.
+    /// Buffer must be in recording state outside of render pass.
+    unsafe fn update_buffer(&mut self, buffer: &Self::Buffer, offset: u64, data: &[u8]);
+
+    /// Copy regions of the buffer into the image in `layout`.
+    /// Buffer must be in recording state outside of render pass.
+    unsafe fn copy_buffer_to_image(
+        &mut self,
+        buffer: &Self::Buffer,
+        image: &Self::Image,
+        layout: image::Layout,
+        regions: &[image::BufferImageCopy],
+    );
+
+    /// Record pipeline barrier transitioning `range` of the image between layouts.
+    /// Buffer must be in recording state outside of render pass.
+    unsafe fn image_barrier(
+        &mut self,
+        image: &Self::Image,
+        stages: Range<PipelineStageFlags>,
+        access: Range<AccessFlags>,
+        layouts: Range<image::Layout>,
+        range: image::SubresourceRange,
+    );
+
+    /// Execute secondary buffers.
+    /// Buffer must be primary buffer in recording state.
+    /// Secondary buffers must be in executable state.
+    unsafe fn execute_commands<'a, I>(&mut self, buffers: I)
+    where
+        I: IntoIterator<Item = &'a Self>,
+        Self: 'a;
+
+    /// Open labeled region of commands for capture tools.
+    /// Buffer must be in recording state.
+    /// Does nothing if debug labels aren't supported by the backend.
+    unsafe fn begin_debug_label(&mut self, _name: &str, _color: [f32; 4]) {}
+
+    /// Close labeled region of commands opened by `begin_debug_label`.
+    /// Buffer must be in recording state.
+    /// Does nothing if debug labels aren't supported by the backend.
+    unsafe fn end_debug_label(&mut self) {}
+
+    /// Insert single label for capture tools.
+    /// Buffer must be in recording state.
+    /// Does nothing if debug labels aren't supported by the backend.
+    unsafe fn insert_debug_label(&mut self, _name: &str, _color: [f32; 4]) {}
 }
 
 impl<'a, B: 'a> CommandBuffer for &'a mut B
@@ -65,10 +337,154 @@ where
     B: CommandBuffer,
 {
     type Submit = B::Submit;
+    type QueryPool = B::QueryPool;
+    type Buffer = B::Buffer;
+    type Image = B::Image;
+    type RenderPass = B::RenderPass;
+    type Framebuffer = B::Framebuffer;
 
     unsafe fn submit(&self) -> B::Submit {
         B::submit(&**self)
     }
+
+    unsafe fn begin(&mut self, usage: UsageFlags) {
+        B::begin(&mut **self, usage)
+    }
+
+    unsafe fn begin_secondary<'b>(
+        &mut self,
+        usage: UsageFlags,
+        inheritance: Option<InheritanceInfo<'b, B::RenderPass>>,
+    ) {
+        B::begin_secondary(&mut **self, usage, inheritance)
+    }
+
+    unsafe fn finish(&mut self) {
+        B::finish(&mut **self)
+    }
+
+    unsafe fn reset(&mut self) {
+        B::reset(&mut **self)
+    }
+
+    unsafe fn begin_query(&mut self, pool: &B::QueryPool, query: u32, flags: QueryControlFlags) {
+        B::begin_query(&mut **self, pool, query, flags)
+    }
+
+    unsafe fn end_query(&mut self, pool: &B::QueryPool, query: u32) {
+        B::end_query(&mut **self, pool, query)
+    }
+
+    unsafe fn reset_query_pool(&mut self, pool: &B::QueryPool, queries: Range<u32>) {
+        B::reset_query_pool(&mut **self, pool, queries)
+    }
+
+    unsafe fn clear_color_image(
+        &mut self,
+        image: &B::Image,
+        layout: image::Layout,
+        color: ClearColor,
+        ranges: &[image::SubresourceRange],
+    ) {
+        B::clear_color_image(&mut **self, image, layout, color, ranges)
+    }
+
+    unsafe fn clear_depth_stencil_image(
+        &mut self,
+        image: &B::Image,
+        layout: image::Layout,
+        value: ClearDepthStencil,
+        ranges: &[image::SubresourceRange],
+    ) {
+        B::clear_depth_stencil_image(&mut **self, image, layout, value, ranges)
+    }
+
+    unsafe fn clear_attachments(&mut self, clears: &[AttachmentClear], rects: &[ClearRect]) {
+        B::clear_attachments(&mut **self, clears, rects)
+    }
+
+    unsafe fn begin_render_pass(
+        &mut self,
+        render_pass: &B::RenderPass,
+        framebuffer: &B::Framebuffer,
+        area: Rect,
+        clears: &[ClearValue],
+    ) {
+        B::begin_render_pass(&mut **self, render_pass, framebuffer, area, clears)
+    }
+
+    unsafe fn end_render_pass(&mut self) {
+        B::end_render_pass(&mut **self)
+    }
+
+    unsafe fn set_viewport(&mut self, first: u32, viewports: &[Viewport]) {
+        B::set_viewport(&mut **self, first, viewports)
+    }
+
+    unsafe fn set_scissor(&mut self, first: u32, scissors: &[Rect]) {
+        B::set_scissor(&mut **self, first, scissors)
+    }
+
+    unsafe fn set_line_width(&mut self, width: f32) {
+        B::set_line_width(&mut **self, width)
+    }
+
+    unsafe fn set_depth_bias(&mut self, bias: DepthBias) {
+        B::set_depth_bias(&mut **self, bias)
+    }
+
+    unsafe fn set_blend_constants(&mut self, constants: [f32; 4]) {
+        B::set_blend_constants(&mut **self, constants)
+    }
+
+    unsafe fn fill_buffer(&mut self, buffer: &B::Buffer, offset: u64, size: u64, value: u32) {
+        B::fill_buffer(&mut **self, buffer, offset, size, value)
+    }
+
+    unsafe fn update_buffer(&mut self, buffer: &B::Buffer, offset: u64, data: &[u8]) {
+        B::update_buffer(&mut **self, buffer, offset, data)
+    }
+
+    unsafe fn copy_buffer_to_image(
+        &mut self,
+        buffer: &B::Buffer,
+        image: &B::Image,
+        layout: image::Layout,
+        regions: &[image::BufferImageCopy],
+    ) {
+        B::copy_buffer_to_image(&mut **self, buffer, image, layout, regions)
+    }
+
+    unsafe fn image_barrier(
+        &mut self,
+        image: &B::Image,
+        stages: Range<PipelineStageFlags>,
+        access: Range<AccessFlags>,
+        layouts: Range<image::Layout>,
+        range: image::SubresourceRange,
+    ) {
+        B::image_barrier(&mut **self, image, stages, access, layouts, range)
+    }
+
+    unsafe fn execute_commands<'b, I>(&mut self, buffers: I)
+    where
+        I: IntoIterator<Item = &'b Self>,
+        Self: 'b,
+    {
+        B::execute_commands(&mut **self, buffers.into_iter().map(|buffer| &**buffer))
+    }
+
+    unsafe fn begin_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        B::begin_debug_label(&mut **self, name, color)
+    }
+
+    unsafe fn end_debug_label(&mut self) {
+        B::end_debug_label(&mut **self)
+    }
+
+    unsafe fn insert_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        B::insert_debug_label(&mut **self, name, color)
+    }
 }
 
 /// Abstract command queue.
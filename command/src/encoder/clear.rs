@@ -1,3 +1,10 @@
+//! Clear values and clear regions.
+
+use std::ops::Range;
+
+use chain::{AccessFlags, Image, PipelineStageFlags, State};
+use resource::image;
+
 /// Value to clear color.
 #[derive(Clone, Copy, Debug)]
 pub enum ClearColor {
@@ -42,3 +49,68 @@ impl From<ClearDepthStencil> for ClearValue {
         ClearValue::DepthStencil(value)
     }
 }
+
+/// Attachment of the current subpass to clear with `clear_attachments`.
+#[derive(Clone, Copy, Debug)]
+pub enum AttachmentClear {
+    /// Clear color attachment.
+    Color {
+        /// Index of the color attachment in the subpass.
+        index: usize,
+
+        /// Color clear value.
+        value: ClearColor,
+    },
+
+    /// Clear depth-stencil attachment.
+    /// Aspect is cleared only if its value is specified.
+    DepthStencil {
+        /// Depth clear value.
+        depth: Option<f32>,
+
+        /// Stencil clear value.
+        stencil: Option<u32>,
+    },
+}
+
+/// Region of attachments to clear with `clear_attachments`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClearRect {
+    /// Offset of the region.
+    pub offset: [i32; 2],
+
+    /// Size of the region.
+    pub extent: [u32; 2],
+
+    /// Array layers to clear.
+    pub layers: Range<u32>,
+}
+
+/// State in which standalone clear commands access the image.
+/// Node that records `clear_color_image` or `clear_depth_stencil_image`
+/// must declare this state for the image so that synchronization
+/// accounts for `TRANSFER_WRITE` access performed by the clear.
+pub fn clear_image_state(layout: image::Layout) -> State<Image> {
+    State {
+        access: AccessFlags::TRANSFER_WRITE,
+        layout,
+        stages: PipelineStageFlags::TRANSFER,
+        usage: image::UsageFlags::TRANSFER_DST,
+    }
+}
+
+/// Check that image can be cleared by standalone clear commands in specified layout.
+pub(crate) fn assert_clear_target(info: &image::CreateInfo, layout: image::Layout) {
+    assert!(
+        info.usage.contains(image::UsageFlags::TRANSFER_DST),
+        "Image with usage {:?} can't be cleared, it must be created with TRANSFER_DST usage",
+        info.usage
+    );
+    assert!(
+        layout == image::Layout::TransferDstOptimal
+            || layout == image::Layout::General
+            || layout == image::Layout::SharedPresentSrc,
+        "Image can't be cleared in {:?} layout",
+        layout
+    );
+}
@@ -0,0 +1,43 @@
+//! Validation of copy commands.
+
+use resource::{buffer, image};
+
+/// Check that regions of the buffer can be copied into the image in specified layout.
+pub(crate) fn assert_copy_to_image(
+    buffer: &buffer::CreateInfo,
+    image: &image::CreateInfo,
+    layout: image::Layout,
+    regions: &[image::BufferImageCopy],
+) {
+    assert!(
+        buffer.usage.contains(buffer::UsageFlags::TRANSFER_SRC),
+        "Buffer with usage {:?} can't be copied from, it must be created with TRANSFER_SRC usage",
+        buffer.usage
+    );
+    assert!(
+        image.usage.contains(image::UsageFlags::TRANSFER_DST),
+        "Image with usage {:?} can't be copied to, it must be created with TRANSFER_DST usage",
+        image.usage
+    );
+    assert!(
+        layout == image::Layout::TransferDstOptimal
+            || layout == image::Layout::General
+            || layout == image::Layout::SharedPresentSrc,
+        "Image can't be copied to in {:?} layout",
+        layout
+    );
+    for region in regions {
+        assert!(
+            region.buffer_offset < buffer.size,
+            "Region offset {} is out of buffer bounds 0..{}",
+            region.buffer_offset,
+            buffer.size
+        );
+        assert!(
+            region.mip_level < image.mips && region.layers.end <= image.array,
+            "Region of level {} and layers {:?} is out of image bounds",
+            region.mip_level,
+            region.layers
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Dynamic state of graphics pipelines.
+
+use std::ops::Range;
+
+/// Viewport transformation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Viewport {
+    /// Upper left corner of the viewport.
+    pub offset: [f32; 2],
+
+    /// Width and height of the viewport.
+    pub extent: [f32; 2],
+
+    /// Depth range of the viewport.
+    pub depth: Range<f32>,
+}
+
+/// Depth bias parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthBias {
+    /// Constant depth value added to each fragment.
+    pub constant_factor: f32,
+
+    /// Maximum (or minimum) depth bias of a fragment.
+    pub clamp: f32,
+
+    /// Factor applied to fragment's slope.
+    pub slope_factor: f32,
+}
+
+bitflags!{
+    /// Pipeline state that is set by commands instead of being baked into pipeline.
+    /// Each flag corresponds to `VkDynamicState` value `n` as `1 << n`.
+    /// See Vulkan docs for detailed info:
+    /// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkDynamicState.html>
+    #[repr(transparent)]
+    pub struct DynamicStateFlags: u32 {
+        /// Viewports are set by `set_viewport`.
+        const VIEWPORT = 0x00000001;
+
+        /// Scissors are set by `set_scissor`.
+        const SCISSOR = 0x00000002;
+
+        /// Line width is set by `set_line_width`.
+        const LINE_WIDTH = 0x00000004;
+
+        /// Depth bias is set by `set_depth_bias`.
+        const DEPTH_BIAS = 0x00000008;
+
+        /// Blend constants are set by `set_blend_constants`.
+        const BLEND_CONSTANTS = 0x00000010;
+    }
+}
+
+/// Check that dynamic state is enabled for bound pipeline.
+/// Skipped if dynamic states of the pipeline are unknown.
+pub(crate) fn assert_dynamic_state(enabled: Option<DynamicStateFlags>, state: DynamicStateFlags) {
+    if let Some(enabled) = enabled {
+        debug_assert!(
+            enabled.contains(state),
+            "Dynamic state {:?} is not enabled for bound pipeline with dynamic states {:?}",
+            state,
+            enabled
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Validation and access of buffer fill and update commands.
+
+use chain::{AccessFlags, Buffer, PipelineStageFlags, State};
+use resource::buffer;
+
+/// Maximum size of inline data that can be written with `update_buffer`.
+pub const MAX_UPDATE_SIZE: u64 = 65536;
+
+/// State in which `fill_buffer` and `update_buffer` access the buffer.
+/// Node that records these commands must declare this state for the buffer
+/// so that synchronization accounts for `TRANSFER_WRITE` access performed by them.
+pub fn fill_buffer_state() -> State<Buffer> {
+    State {
+        access: AccessFlags::TRANSFER_WRITE,
+        layout: (),
+        stages: PipelineStageFlags::TRANSFER,
+        usage: buffer::UsageFlags::TRANSFER_DST,
+    }
+}
+
+/// Check that range of the buffer can be written by fill and update commands.
+pub(crate) fn assert_fill_target(info: &buffer::CreateInfo, offset: u64, size: u64) {
+    assert!(
+        info.usage.contains(buffer::UsageFlags::TRANSFER_DST),
+        "Buffer with usage {:?} can't be filled, it must be created with TRANSFER_DST usage",
+        info.usage
+    );
+    assert!(
+        offset % 4 == 0 && size % 4 == 0,
+        "Offset {} and size {} of the write must be multiples of 4",
+        offset,
+        size
+    );
+    assert!(
+        offset <= info.size && size <= info.size - offset,
+        "Range {}..{} is out of buffer bounds 0..{}",
+        offset,
+        offset + size,
+        info.size
+    );
+}
+
+/// Check that data can be written inline with `update_buffer`.
+pub(crate) fn assert_update_size(size: u64) {
+    assert!(
+        size <= MAX_UPDATE_SIZE,
+        "Update of {} bytes exceeds limit of {} bytes, use staging buffer instead",
+        size,
+        MAX_UPDATE_SIZE
+    );
+}
@@ -0,0 +1,27 @@
+/// Tracks nesting of debug labels recorded into command buffer.
+/// Mismatched `begin`/`end` pairs are caught in debug builds.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct LabelStack {
+    depth: usize,
+}
+
+impl LabelStack {
+    /// Open new label region.
+    pub(crate) fn begin(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Close innermost label region.
+    pub(crate) fn end(&mut self) {
+        debug_assert!(
+            self.depth > 0,
+            "`end_debug_label` called without matching `begin_debug_label`"
+        );
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Get number of label regions currently open.
+    pub(crate) fn depth(&self) -> usize {
+        self.depth
+    }
+}
@@ -2,11 +2,33 @@
 //!
 
 mod clear;
+mod copy;
+mod dynamic;
+mod fill;
+mod label;
+mod render_pass;
 
-pub use self::clear::*;
+pub use self::clear::{
+    clear_image_state, AttachmentClear, ClearColor, ClearDepthStencil, ClearRect, ClearValue,
+};
+pub use self::dynamic::{DepthBias, DynamicStateFlags, Viewport};
+pub use self::fill::{fill_buffer_state, MAX_UPDATE_SIZE};
+pub(crate) use self::label::LabelStack;
+pub use self::render_pass::Rect;
 
-use capability::CapabilityFlags;
+use std::ops::Range;
+
+use resource::buffer::Buffer;
+use resource::image::{self, Image, SubresourceRange};
+
+use self::clear::assert_clear_target;
+use self::copy::assert_copy_to_image;
+use self::dynamic::assert_dynamic_state;
+use self::fill::{assert_fill_target, assert_update_size};
+use capability::{Capability, CapabilityFlags, Execute, Graphics, Supports, Transfer};
 use device::CommandBuffer;
+use multiview::ViewMask;
+use query::{QueryControlFlags, QueryPool};
 
 /// Encoder allow command recording in safe-ish abstract manner.
 pub trait Encoder<C = CapabilityFlags> {
@@ -22,4 +44,359 @@ pub trait Encoder<C = CapabilityFlags> {
     /// * Moving out raw buffer is very unsafe and should be avoided.
     /// * Creating copies can be safe only if copies don't outlive encoder instance.
     unsafe fn buffer(&mut self) -> &mut Self::Buffer;
+
+    /// Get capability of the family commands are recorded for.
+    fn capability(&self) -> C;
+
+    /// Get dynamic states enabled by currently bound graphics pipeline.
+    /// Used to validate dynamic state commands in debug builds.
+    /// Returns `None` if encoder doesn't track bound pipeline, validation is skipped then.
+    fn dynamic_states(&self) -> Option<DynamicStateFlags> {
+        None
+    }
+
+    /// Begin occlusion query.
+    /// Samples that pass per-fragment tests are counted until `end_query`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `pool` is not a pool of occlusion queries,
+    /// if family is not capable of graphics
+    /// or `query` is out of pool bounds.
+    fn begin_occlusion_query(
+        &mut self,
+        pool: &QueryPool<<Self::Buffer as CommandBuffer>::QueryPool>,
+        query: u32,
+        precise: bool,
+    ) where
+        C: Capability + Supports<Graphics>,
+    {
+        pool.assert_recording(true, self.capability().into_flags(), query);
+        let flags = if precise {
+            QueryControlFlags::PRECISE
+        } else {
+            QueryControlFlags::empty()
+        };
+        unsafe {
+            self.buffer().begin_query(pool.raw(), query, flags);
+        }
+    }
+
+    /// Begin pipeline statistics query.
+    /// Operations of pipeline stages are counted until `end_query`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `pool` is not a pool of pipeline statistics queries,
+    /// if family is capable of neither graphics nor compute
+    /// or `query` is out of pool bounds.
+    fn begin_statistics_query(
+        &mut self,
+        pool: &QueryPool<<Self::Buffer as CommandBuffer>::QueryPool>,
+        query: u32,
+    ) where
+        C: Capability + Supports<Execute>,
+    {
+        pool.assert_recording(false, self.capability().into_flags(), query);
+        unsafe {
+            self.buffer()
+                .begin_query(pool.raw(), query, QueryControlFlags::empty());
+        }
+    }
+
+    /// End query started by `begin_occlusion_query` or `begin_statistics_query`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `query` is out of pool bounds.
+    fn end_query(
+        &mut self,
+        pool: &QueryPool<<Self::Buffer as CommandBuffer>::QueryPool>,
+        query: u32,
+    ) {
+        pool.assert_queries(query..query + 1);
+        unsafe {
+            self.buffer().end_query(pool.raw(), query);
+        }
+    }
+
+    /// Clear ranges of color image.
+    /// Must be recorded outside of render pass.
+    /// Clear performs `TRANSFER_WRITE` access declared by `clear_image_state`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if image wasn't created with `TRANSFER_DST` usage
+    /// or `layout` is not one of `TransferDstOptimal`, `General` or `SharedPresentSrc`.
+    fn clear_color_image<M>(
+        &mut self,
+        image: &Image<M, <Self::Buffer as CommandBuffer>::Image>,
+        layout: image::Layout,
+        color: ClearColor,
+        ranges: &[SubresourceRange],
+    ) where
+        C: Supports<Execute>,
+    {
+        assert_clear_target(image.info(), layout);
+        unsafe {
+            self.buffer()
+                .clear_color_image(image.raw(), layout, color, ranges);
+        }
+    }
+
+    /// Clear ranges of depth-stencil image.
+    /// Must be recorded outside of render pass.
+    /// Clear performs `TRANSFER_WRITE` access declared by `clear_image_state`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if image wasn't created with `TRANSFER_DST` usage
+    /// or `layout` is not one of `TransferDstOptimal`, `General` or `SharedPresentSrc`.
+    fn clear_depth_stencil_image<M>(
+        &mut self,
+        image: &Image<M, <Self::Buffer as CommandBuffer>::Image>,
+        layout: image::Layout,
+        value: ClearDepthStencil,
+        ranges: &[SubresourceRange],
+    ) where
+        C: Supports<Graphics>,
+    {
+        assert_clear_target(image.info(), layout);
+        unsafe {
+            self.buffer()
+                .clear_depth_stencil_image(image.raw(), layout, value, ranges);
+        }
+    }
+
+    /// Clear regions of attachments of the current subpass.
+    /// Must be recorded inside render pass.
+    /// Attachments are accessed as declared by the render pass,
+    /// no additional access is performed.
+    fn clear_attachments(&mut self, clears: &[AttachmentClear], rects: &[ClearRect])
+    where
+        C: Supports<Graphics>,
+    {
+        unsafe {
+            self.buffer().clear_attachments(clears, rects);
+        }
+    }
+
+    /// Begin render pass instance rendering into attachments of the `framebuffer`.
+    /// Multiview subpasses render view `n` into layer `n` of each attachment,
+    /// so `view_mask` must be the mask render pass was created with
+    /// and `attachments` must describe images the framebuffer views.
+    /// Barriers derived from chains cover all layers of the attachments
+    /// (see `image_barrier_range`), so every view is transitioned.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any attachment has fewer layers than `view_mask` requires.
+    fn begin_render_pass(
+        &mut self,
+        render_pass: &<Self::Buffer as CommandBuffer>::RenderPass,
+        framebuffer: &<Self::Buffer as CommandBuffer>::Framebuffer,
+        attachments: &[&image::CreateInfo],
+        view_mask: ViewMask,
+        area: Rect,
+        clears: &[ClearValue],
+    ) where
+        C: Supports<Graphics>,
+    {
+        for info in attachments {
+            view_mask.assert_fits(info);
+        }
+        unsafe {
+            self.buffer()
+                .begin_render_pass(render_pass, framebuffer, area, clears);
+        }
+    }
+
+    /// End render pass instance started by `begin_render_pass`.
+    fn end_render_pass(&mut self)
+    where
+        C: Supports<Graphics>,
+    {
+        unsafe {
+            self.buffer().end_render_pass();
+        }
+    }
+
+    /// Set viewports starting from `first`.
+    /// Bound pipeline must have `VIEWPORT` dynamic state enabled.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this function panics if bound pipeline is known to have
+    /// `VIEWPORT` dynamic state disabled.
+    fn set_viewport(&mut self, first: u32, viewports: &[Viewport])
+    where
+        C: Supports<Graphics>,
+    {
+        assert_dynamic_state(self.dynamic_states(), DynamicStateFlags::VIEWPORT);
+        unsafe {
+            self.buffer().set_viewport(first, viewports);
+        }
+    }
+
+    /// Set scissors starting from `first`.
+    /// Bound pipeline must have `SCISSOR` dynamic state enabled.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this function panics if bound pipeline is known to have
+    /// `SCISSOR` dynamic state disabled.
+    fn set_scissor(&mut self, first: u32, scissors: &[Rect])
+    where
+        C: Supports<Graphics>,
+    {
+        assert_dynamic_state(self.dynamic_states(), DynamicStateFlags::SCISSOR);
+        unsafe {
+            self.buffer().set_scissor(first, scissors);
+        }
+    }
+
+    /// Set width of rasterized lines.
+    /// Bound pipeline must have `LINE_WIDTH` dynamic state enabled.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this function panics if bound pipeline is known to have
+    /// `LINE_WIDTH` dynamic state disabled.
+    fn set_line_width(&mut self, width: f32)
+    where
+        C: Supports<Graphics>,
+    {
+        assert_dynamic_state(self.dynamic_states(), DynamicStateFlags::LINE_WIDTH);
+        unsafe {
+            self.buffer().set_line_width(width);
+        }
+    }
+
+    /// Set depth bias parameters.
+    /// Bound pipeline must have `DEPTH_BIAS` dynamic state enabled.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this function panics if bound pipeline is known to have
+    /// `DEPTH_BIAS` dynamic state disabled.
+    fn set_depth_bias(&mut self, bias: DepthBias)
+    where
+        C: Supports<Graphics>,
+    {
+        assert_dynamic_state(self.dynamic_states(), DynamicStateFlags::DEPTH_BIAS);
+        unsafe {
+            self.buffer().set_depth_bias(bias);
+        }
+    }
+
+    /// Set blend constants.
+    /// Bound pipeline must have `BLEND_CONSTANTS` dynamic state enabled.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this function panics if bound pipeline is known to have
+    /// `BLEND_CONSTANTS` dynamic state disabled.
+    fn set_blend_constants(&mut self, constants: [f32; 4])
+    where
+        C: Supports<Graphics>,
+    {
+        assert_dynamic_state(self.dynamic_states(), DynamicStateFlags::BLEND_CONSTANTS);
+        unsafe {
+            self.buffer().set_blend_constants(constants);
+        }
+    }
+
+    /// Fill `size` bytes of the buffer starting from `offset` with repeated 4-byte `value`.
+    /// Must be recorded outside of render pass.
+    /// Fill performs `TRANSFER_WRITE` access declared by `fill_buffer_state`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if buffer wasn't created with `TRANSFER_DST` usage,
+    /// if `offset` or `size` is not a multiple of 4
+    /// or range is out of buffer bounds.
+    fn fill_buffer<M>(
+        &mut self,
+        buffer: &Buffer<M, <Self::Buffer as CommandBuffer>::Buffer>,
+        offset: u64,
+        size: u64,
+        value: u32,
+    ) where
+        C: Supports<Transfer>,
+    {
+        assert_fill_target(buffer.info(), offset, size);
+        unsafe {
+            self.buffer().fill_buffer(buffer.raw(), offset, size, value);
+        }
+    }
+
+    /// Write small inline `data` to the buffer starting from `offset`.
+    /// Data is recorded into command buffer, so no staging buffer is required.
+    /// Must be recorded outside of render pass.
+    /// Update performs `TRANSFER_WRITE` access declared by `fill_buffer_state`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `data` is larger than `MAX_UPDATE_SIZE`,
+    /// if buffer wasn't created with `TRANSFER_DST` usage,
+    /// if `offset` or size of `data` is not a multiple of 4
+    /// or range is out of buffer bounds.
+    fn update_buffer<M>(
+        &mut self,
+        buffer: &Buffer<M, <Self::Buffer as CommandBuffer>::Buffer>,
+        offset: u64,
+        data: &[u8],
+    ) where
+        C: Supports<Transfer>,
+    {
+        assert_update_size(data.len() as u64);
+        assert_fill_target(buffer.info(), offset, data.len() as u64);
+        unsafe {
+            self.buffer().update_buffer(buffer.raw(), offset, data);
+        }
+    }
+
+    /// Copy regions of the buffer into the image in `layout`.
+    /// Must be recorded outside of render pass.
+    /// Copy performs `TRANSFER_READ` access to the buffer
+    /// and `TRANSFER_WRITE` access to the image.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if buffer wasn't created with `TRANSFER_SRC` usage,
+    /// if image wasn't created with `TRANSFER_DST` usage,
+    /// if `layout` is not one of `TransferDstOptimal`, `General` or `SharedPresentSrc`
+    /// or any region is out of bounds of the buffer or the image.
+    fn copy_buffer_to_image<M, N>(
+        &mut self,
+        buffer: &Buffer<M, <Self::Buffer as CommandBuffer>::Buffer>,
+        image: &Image<N, <Self::Buffer as CommandBuffer>::Image>,
+        layout: image::Layout,
+        regions: &[image::BufferImageCopy],
+    ) where
+        C: Supports<Transfer>,
+    {
+        assert_copy_to_image(buffer.info(), image.info(), layout, regions);
+        unsafe {
+            self.buffer()
+                .copy_buffer_to_image(buffer.raw(), image.raw(), layout, regions);
+        }
+    }
+
+    /// Reset queries so they can be used again.
+    /// Must be recorded outside of render pass.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `queries` are out of pool bounds.
+    fn reset_query_pool(
+        &mut self,
+        pool: &QueryPool<<Self::Buffer as CommandBuffer>::QueryPool>,
+        queries: Range<u32>,
+    ) {
+        pool.assert_queries(queries.clone());
+        unsafe {
+            self.buffer().reset_query_pool(pool.raw(), queries);
+        }
+    }
 }
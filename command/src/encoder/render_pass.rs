@@ -0,0 +1,9 @@
+/// Rectangle of the framebuffer, e.g. render area or scissor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    /// Offset of the rectangle.
+    pub offset: [i32; 2],
+
+    /// Size of the rectangle.
+    pub extent: [u32; 2],
+}
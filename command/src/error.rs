@@ -1,5 +1,7 @@
 //! Error module docs.
 
+use buffer::UsageFlags;
+
 /// Error that can be returned by some functions
 /// indicating that logical device is lost.
 /// Those methods on objects created from the device will likely result in this error again.
@@ -9,3 +11,10 @@
 #[derive(Clone, Copy, Debug, Fail)]
 #[fail(display = "Device lost. Re-initialization required")]
 pub struct DeviceLost;
+
+/// Error that can be returned by attempt to submit command buffer while it is pending.
+/// Only command buffers with `SIMULTANEOUS_USE` usage can be.
+/// Contains usage flags the buffer was begun with.
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "Command buffer with usage {:?} submitted while pending", _0)]
+pub struct SubmitPending(pub UsageFlags);
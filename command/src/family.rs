@@ -1,12 +1,17 @@
 //! Family module docs.
 
-use capability::{Capability, CapabilityFlags};
+use std::borrow::Borrow;
+
+use chain::PipelineStageFlags;
+
+use buffer::Submit;
+use capability::{Capability, CapabilityFlags, Transfer};
 use device::Device;
-use pool::Pool;
-use queue::Queue;
+use pool::{Pool, Reset, TransferPool};
+use queue::{Queue, Submission};
 
 /// Unique family index.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct FamilyId(pub u32);
 
 /// Family of the command queues.
@@ -20,6 +25,49 @@ pub struct Family<Q, C> {
 }
 
 impl<Q, C> Family<Q, C> {
+    /// Wrap raw queues of the family.
+    ///
+    /// # Safety
+    ///
+    /// `queues` must be created from family with `index` specified
+    /// and the family must support `capability`.
+    pub unsafe fn from_raw<I>(index: FamilyId, queues: I, capability: C) -> Self
+    where
+        I: IntoIterator<Item = Q>,
+        C: Capability,
+    {
+        Family {
+            index,
+            queues: queues
+                .into_iter()
+                .map(|inner| Queue {
+                    inner,
+                    capability,
+                    family: index,
+                })
+                .collect(),
+            capability,
+        }
+    }
+
+    /// Get id of the family.
+    pub fn id(&self) -> FamilyId {
+        self.index
+    }
+
+    /// Get capability of the family.
+    pub fn capability(&self) -> C
+    where
+        C: Capability,
+    {
+        self.capability
+    }
+
+    /// Get number of queues of the family.
+    pub fn queue_count(&self) -> usize {
+        self.queues.len()
+    }
+
     /// Get queues of the family.
     pub fn queues(&mut self) -> &mut [Queue<Q, C>] {
         &mut self.queues
@@ -27,11 +75,56 @@ impl<Q, C> Family<Q, C> {
 
     /// Create command pool associated with the family.
     /// Command buffers created from the pool could be submitted to the queues of the family.
-    pub fn create_pool<D, R>(&mut self, device: &mut D, reset: R) -> Pool<D::CommandPool, C, R>
+    pub fn create_pool<D, R>(&self, device: &D, reset: R) -> Pool<D::CommandPool, C, R>
     where
         D: Device,
+        C: Capability,
+        R: Reset,
     {
-        unimplemented!()
+        unsafe {
+            let inner = device.create_command_pool(self.index, reset.flags());
+            Pool::from_raw(inner, self.capability, reset, self.index)
+        }
+    }
+
+    /// Submit command buffers to the queue with specified index.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the family has no queue with specified index.
+    ///
+    /// # Safety
+    ///
+    /// See `Queue::submit`.
+    pub unsafe fn submit<D, I, WI, BI, SI, W, B, S, F>(
+        &mut self,
+        device: &D,
+        queue: usize,
+        submissions: I,
+        fence: Option<F>,
+    ) where
+        D: Device<CommandQueue = Q>,
+        I: IntoIterator<Item = Submission<WI, BI, SI>>,
+        WI: IntoIterator<Item = (W, PipelineStageFlags)>,
+        BI: IntoIterator<Item = Submit<B>>,
+        SI: IntoIterator<Item = S>,
+        W: Borrow<D::Semaphore>,
+        B: Borrow<D::Submit>,
+        S: Borrow<D::Semaphore>,
+        F: Borrow<D::Fence>,
+    {
+        self.queues[queue].submit(device, submissions, fence)
+    }
+}
+
+impl<Q> Family<Q, Transfer> {
+    /// Create pool for transfer commands.
+    /// Buffers from the pool are reset together.
+    pub fn create_transfer_pool<D>(&self, device: &D) -> TransferPool<D::CommandPool>
+    where
+        D: Device,
+    {
+        self.create_pool(device, ())
     }
 }
 
@@ -50,6 +143,7 @@ where
                 .map(|queue| Queue {
                     inner: queue.inner,
                     capability: queue.capability.into_flags(),
+                    family: queue.family,
                 }).collect::<Vec<_>>(),
             capability: family.capability.into_flags(),
         }
@@ -68,6 +162,7 @@ where
                         inner: queue.inner,
                         capability: C::from_flags(queue.capability)
                             .expect("Unable to convert queue capability to a CapabilityFlag"),
+                        family: queue.family,
                     }).collect::<Vec<_>>(),
                 capability,
             })
@@ -98,4 +193,14 @@ impl<Q> Families<Q> {
     {
         self.families.push(Family::from(family));
     }
+
+    /// Get family with specified id.
+    pub fn family(&self, id: FamilyId) -> Option<&Family<Q, CapabilityFlags>> {
+        self.families.iter().find(|family| family.id() == id)
+    }
+
+    /// Get family with specified id.
+    pub fn family_mut(&mut self, id: FamilyId) -> Option<&mut Family<Q, CapabilityFlags>> {
+        self.families.iter_mut().find(|family| family.id() == id)
+    }
 }
@@ -1,4 +1,8 @@
+//! Fence module docs.
 
+use relevant::Relevant;
+
+use device::Device;
 
 bitflags!{
     /// Flags to specify initial state and behavior of the fence.
@@ -16,3 +20,75 @@ pub struct FenceCreateInfo {
     pub flags: FenceCreateFlags,
 }
 
+/// Pool of fences.
+/// Recycles released fences instead of destroying them
+/// to avoid creating new fences every frame.
+#[derive(Debug)]
+pub struct FencePool<F> {
+    free: Vec<F>,
+    relevant: Relevant,
+}
+
+impl<F> FencePool<F> {
+    /// Create empty pool.
+    pub fn new() -> Self {
+        FencePool {
+            free: Vec::new(),
+            relevant: Relevant,
+        }
+    }
+
+    /// Get unsignaled fence.
+    /// Recycled fence is reset before it is returned.
+    /// New fence is created if pool has no free fences.
+    pub fn acquire<D>(&mut self, device: &D) -> F
+    where
+        D: Device<Fence = F>,
+    {
+        match self.free.pop() {
+            Some(fence) => {
+                unsafe {
+                    device.reset_fence(&fence);
+                }
+                fence
+            }
+            None => unsafe { device.create_fence(FenceCreateInfo::default()) },
+        }
+    }
+
+    /// Return fence to the pool.
+    ///
+    /// # Safety
+    ///
+    /// Fence must be created by the same device that is used with this pool.
+    /// Fence must not be used by pending submissions,
+    /// i.e. it must be either signaled or never submitted.
+    pub unsafe fn release(&mut self, fence: F) {
+        self.free.push(fence);
+    }
+
+    /// Get number of free fences in the pool.
+    pub fn free(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Destroy all free fences.
+    /// All acquired fences must be released or destroyed before.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<Fence = F>,
+    {
+        for fence in self.free {
+            unsafe {
+                device.destroy_fence(fence);
+            }
+        }
+        self.relevant.dispose();
+    }
+}
+
+impl<F> Default for FencePool<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -90,6 +90,17 @@ impl<F> Frame<F> {
     }
 }
 
+#[cfg(test)]
+impl<F> Frame<F> {
+    /// Treat frame as complete without checking fences.
+    pub(crate) fn assume_complete(self) -> CompleteFrame<F> {
+        CompleteFrame {
+            index: self.index,
+            fences: self.fences,
+        }
+    }
+}
+
 /// Frame that is fully submitted for execution.
 /// User can wait for it to become `CompleteFrame`.
 #[derive(Debug)]
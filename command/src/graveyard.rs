@@ -0,0 +1,87 @@
+//! Graveyard module docs.
+
+use relevant::Relevant;
+
+use frame::{CompleteFrame, Frame, FrameIndex};
+
+/// Holds resources pending destruction until frames that use them are complete.
+/// Each resource is tagged with index of the last frame that may use it
+/// and released once that frame is complete.
+#[derive(Debug)]
+pub struct Graveyard<T> {
+    buried: Vec<(FrameIndex, T)>,
+    relevant: Relevant,
+}
+
+impl<T> Graveyard<T> {
+    /// Create empty graveyard.
+    pub fn new() -> Self {
+        Graveyard {
+            buried: Vec::new(),
+            relevant: Relevant,
+        }
+    }
+
+    /// Retire resource used by the frame.
+    /// Resource is released by `collect` after the frame is complete.
+    pub fn retire<F>(&mut self, frame: &Frame<F>, resource: T) {
+        self.retire_at(frame.index(), resource);
+    }
+
+    /// Retire resource that may be used by frames up to `index`.
+    /// Resource is released by `collect` after frame with `index` is complete.
+    pub fn retire_at(&mut self, index: FrameIndex, resource: T) {
+        self.buried.push((index, resource));
+    }
+
+    /// Release all resources retired on or before completed frame.
+    /// `release` is called for each resource to destroy it.
+    pub fn collect<F, R>(&mut self, complete: &CompleteFrame<F>, mut release: R)
+    where
+        R: FnMut(T),
+    {
+        let index = complete.index();
+        let mut buried = Vec::with_capacity(self.buried.len());
+        for (retired, resource) in self.buried.drain(..) {
+            if retired <= index {
+                release(resource);
+            } else {
+                buried.push((retired, resource));
+            }
+        }
+        self.buried = buried;
+    }
+
+    /// Get number of resources pending destruction.
+    pub fn len(&self) -> usize {
+        self.buried.len()
+    }
+
+    /// Check if there are no resources pending destruction.
+    pub fn is_empty(&self) -> bool {
+        self.buried.is_empty()
+    }
+
+    /// Release all resources regardless of frames.
+    ///
+    /// # Safety
+    ///
+    /// All frames resources were retired at must be complete.
+    /// Typically device is waited to become idle before this call.
+    pub unsafe fn dispose<R>(self, release: R)
+    where
+        R: FnMut(T),
+    {
+        self.buried
+            .into_iter()
+            .map(|(_, resource)| resource)
+            .for_each(release);
+        self.relevant.dispose();
+    }
+}
+
+impl<T> Default for Graveyard<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
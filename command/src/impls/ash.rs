@@ -1,11 +1,27 @@
 use ash::{
     self,
-    version::{DeviceV1_0, FunctionPointers},
+    version::{DeviceV1_0, EntryV1_0, FunctionPointers},
     vk,
 };
 
+use std::ffi::CString;
+use std::mem;
+use std::ops::Range;
+
+use chain::{AccessFlags, PipelineStageFlags};
+use resource::{image, DeviceFeatures};
+
+use buffer::{InheritanceInfo, RawLevel, UsageFlags};
 use device::{CommandBuffer, CommandQueue, Device};
+use encoder::{
+    AttachmentClear, ClearColor, ClearDepthStencil, ClearRect, ClearValue, DepthBias, Rect,
+    Viewport,
+};
+use error::DeviceLost;
+use family::FamilyId;
 use fence;
+use pool;
+use query;
 
 impl From<fence::FenceCreateFlags> for vk::FenceCreateFlags {
     fn from(flags: fence::FenceCreateFlags) -> Self {
@@ -13,6 +29,145 @@ impl From<fence::FenceCreateFlags> for vk::FenceCreateFlags {
     }
 }
 
+impl From<pool::PoolCreateFlags> for vk::CommandPoolCreateFlags {
+    fn from(flags: pool::PoolCreateFlags) -> Self {
+        Self::from_flags(flags.bits()).expect("Unsupported flags")
+    }
+}
+
+/// `VkDebugUtilsLabelEXT`. `VK_EXT_debug_utils` is missing from ash bindings.
+#[repr(C)]
+struct DebugUtilsLabel {
+    s_type: i32,
+    p_next: *const vk::c_void,
+    p_label_name: *const vk::c_char,
+    color: [f32; 4],
+}
+
+const STRUCTURE_TYPE_DEBUG_UTILS_LABEL_EXT: i32 = 1000128002;
+
+type CmdLabel = unsafe extern "system" fn(vk::CommandBuffer, *const DebugUtilsLabel);
+type CmdEndLabel = unsafe extern "system" fn(vk::CommandBuffer);
+
+/// Label commands of `VK_EXT_debug_utils` extension loaded for an instance.
+/// Command buffers that carry loaded commands forward debug labels to capture tools
+/// and ignore them otherwise.
+/// Buffers allocated from a command pool carry commands stored in the pool,
+/// e.g. `unsafe { pool.raw_mut().1 = Some(debug_utils) }`.
+#[derive(Clone, Copy, Debug)]
+pub struct DebugUtils {
+    begin: CmdLabel,
+    end: CmdEndLabel,
+    insert: CmdLabel,
+}
+
+impl DebugUtils {
+    /// Load label commands of `VK_EXT_debug_utils` extension enabled on the `instance`.
+    /// Returns names of commands that can't be loaded.
+    /// Nothing is loaded unless `features` report the extension enabled,
+    /// as instance may return commands of extensions that are not enabled.
+    ///
+    /// # Safety
+    ///
+    /// `instance` must be created from `entry` and outlive all command buffers recording labels.
+    pub unsafe fn load<E>(
+        entry: &E,
+        instance: vk::Instance,
+        features: DeviceFeatures,
+    ) -> Result<Self, Vec<&'static str>>
+    where
+        E: EntryV1_0,
+    {
+        let names = [
+            "vkCmdBeginDebugUtilsLabelEXT",
+            "vkCmdEndDebugUtilsLabelEXT",
+            "vkCmdInsertDebugUtilsLabelEXT",
+        ];
+        if !features.debug_utils {
+            return Err(names.to_vec());
+        }
+        let addresses = names.iter().map(|&name| {
+            let cname = CString::new(name).unwrap();
+            let address: usize = mem::transmute(entry.get_instance_proc_addr(instance, cname.as_ptr()));
+            address
+        }).collect::<Vec<_>>();
+
+        let missing = names.iter().zip(&addresses)
+            .filter(|&(_, &address)| address == 0)
+            .map(|(&name, _)| name)
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(missing);
+        }
+
+        Ok(DebugUtils {
+            begin: mem::transmute(addresses[0]),
+            end: mem::transmute(addresses[1]),
+            insert: mem::transmute(addresses[2]),
+        })
+    }
+}
+
+/// Record label command with name and color.
+/// Name is cut at first nul character as C string can't contain it.
+unsafe fn record_label(command: CmdLabel, buffer: vk::CommandBuffer, name: &str, color: [f32; 4]) {
+    let name = CString::new(name.split('\0').next().unwrap()).unwrap();
+    command(buffer, &DebugUtilsLabel {
+        s_type: STRUCTURE_TYPE_DEBUG_UTILS_LABEL_EXT,
+        p_next: ::std::ptr::null(),
+        p_label_name: name.as_ptr(),
+        color,
+    });
+}
+
+fn image_layout(layout: image::Layout) -> vk::ImageLayout {
+    match layout {
+        image::Layout::Undefined => vk::ImageLayout::Undefined,
+        image::Layout::General => vk::ImageLayout::General,
+        image::Layout::ColorAttachmentOptimal => vk::ImageLayout::ColorAttachmentOptimal,
+        image::Layout::DepthStencilAttachmentOptimal => vk::ImageLayout::DepthStencilAttachmentOptimal,
+        image::Layout::DepthStencilReadOnlyOptimal => vk::ImageLayout::DepthStencilReadOnlyOptimal,
+        image::Layout::ShaderReadOnlyOptimal => vk::ImageLayout::ShaderReadOnlyOptimal,
+        image::Layout::TransferSrcOptimal => vk::ImageLayout::TransferSrcOptimal,
+        image::Layout::TransferDstOptimal => vk::ImageLayout::TransferDstOptimal,
+        image::Layout::Preinitialized => vk::ImageLayout::Preinitialized,
+        image::Layout::PresentSrc => vk::ImageLayout::PresentSrcKhr,
+        layout => panic!("Layout {:?} is not supported by ash", layout),
+    }
+}
+
+fn subresource_range(range: &image::SubresourceRange) -> vk::ImageSubresourceRange {
+    vk::ImageSubresourceRange {
+        aspect_mask: vk::ImageAspectFlags::from_flags(range.aspects.bits()).expect("Unsupported aspects"),
+        base_mip_level: range.levels.start,
+        level_count: range.levels.end - range.levels.start,
+        base_array_layer: range.layers.start,
+        layer_count: range.layers.end - range.layers.start,
+    }
+}
+
+fn stage_flags(stages: PipelineStageFlags) -> vk::PipelineStageFlags {
+    vk::PipelineStageFlags::from_flags(stages.bits()).expect("Unsupported stages")
+}
+
+fn access_flags(access: AccessFlags) -> vk::AccessFlags {
+    vk::AccessFlags::from_flags(access.bits()).expect("Unsupported access")
+}
+
+fn clear_color(color: ClearColor) -> vk::ClearColorValue {
+    match color {
+        ClearColor::Float(float32) => vk::ClearColorValue { float32 },
+        ClearColor::Int(int32) => vk::ClearColorValue { int32 },
+        ClearColor::UInt(uint32) => vk::ClearColorValue { uint32 },
+    }
+}
+
+impl From<query::QueryControlFlags> for vk::QueryControlFlags {
+    fn from(flags: query::QueryControlFlags) -> Self {
+        Self::from_flags(flags.bits()).expect("Unsupported flags")
+    }
+}
+
 impl<V> Device for ash::Device<V>
 where
     V: FunctionPointers,
@@ -21,8 +176,10 @@ where
     type Semaphore = vk::Semaphore;
     type Fence = vk::Fence;
     type Submit = vk::CommandBuffer;
-    type CommandPool = (vk::DeviceFnV1_0, vk::CommandBuffer);
-    type CommandBuffer = (vk::DeviceFnV1_0, vk::CommandBuffer);
+    type CommandPool = (vk::CommandPool, Option<DebugUtils>);
+    type PipelineCache = vk::PipelineCache;
+    type QueryPool = vk::QueryPool;
+    type CommandBuffer = (vk::DeviceFnV1_0, vk::CommandBuffer, Option<DebugUtils>);
     type CommandQueue = vk::Queue;
 
     unsafe fn create_fence(&self, info: fence::FenceCreateInfo) -> Self::Fence {
@@ -34,14 +191,479 @@ where
             flags: info.flags.into(),
         }, None).unwrap()
     }
+
+    unsafe fn destroy_fence(&self, fence: Self::Fence) {
+        DeviceV1_0::destroy_fence(self, fence, None)
+    }
+
+    unsafe fn reset_fence(&self, fence: &Self::Fence) {
+        DeviceV1_0::reset_fences(self, &[*fence]).unwrap()
+    }
+
+    unsafe fn create_semaphore(&self) -> Self::Semaphore {
+        use std::ptr::null;
+
+        DeviceV1_0::create_semaphore(self, &vk::SemaphoreCreateInfo {
+            s_type: vk::StructureType::SemaphoreCreateInfo,
+            p_next: null(),
+            flags: vk::SemaphoreCreateFlags::empty(),
+        }, None).unwrap()
+    }
+
+    unsafe fn destroy_semaphore(&self, semaphore: Self::Semaphore) {
+        DeviceV1_0::destroy_semaphore(self, semaphore, None)
+    }
+
+    unsafe fn create_command_pool(
+        &self,
+        family: FamilyId,
+        flags: pool::PoolCreateFlags,
+    ) -> Self::CommandPool {
+        use std::ptr::null;
+
+        let pool = DeviceV1_0::create_command_pool(self, &vk::CommandPoolCreateInfo {
+            s_type: vk::StructureType::CommandPoolCreateInfo,
+            p_next: null(),
+            flags: flags.into(),
+            queue_family_index: family.0,
+        }, None).unwrap();
+        (pool, None)
+    }
+
+    unsafe fn destroy_command_pool(&self, pool: Self::CommandPool) {
+        DeviceV1_0::destroy_command_pool(self, pool.0, None)
+    }
+
+    unsafe fn allocate_command_buffer(
+        &self,
+        pool: &mut Self::CommandPool,
+        level: RawLevel,
+    ) -> Self::CommandBuffer {
+        use std::ptr::null;
+
+        let buffers = DeviceV1_0::allocate_command_buffers(self, &vk::CommandBufferAllocateInfo {
+            s_type: vk::StructureType::CommandBufferAllocateInfo,
+            p_next: null(),
+            command_pool: pool.0,
+            level: match level {
+                RawLevel::Primary => vk::CommandBufferLevel::Primary,
+                RawLevel::Secondary => vk::CommandBufferLevel::Secondary,
+            },
+            command_buffer_count: 1,
+        }).unwrap();
+        (DeviceV1_0::fp_v1_0(self).clone(), buffers[0], pool.1)
+    }
+
+    unsafe fn free_command_buffer(&self, pool: &mut Self::CommandPool, buffer: Self::CommandBuffer) {
+        DeviceV1_0::free_command_buffers(self, pool.0, &[buffer.1])
+    }
+
+    unsafe fn reset_command_pool(&self, pool: &mut Self::CommandPool) {
+        DeviceV1_0::reset_command_pool(self, pool.0, vk::CommandPoolResetFlags::empty()).unwrap()
+    }
+
+    unsafe fn queue_submit_batch(
+        &self,
+        queue: &mut Self::CommandQueue,
+        waits: &[(&Self::Semaphore, PipelineStageFlags)],
+        buffers: &[&Self::Submit],
+        signals: &[&Self::Semaphore],
+        fence: Option<&Self::Fence>,
+    ) {
+        use std::ptr::null;
+
+        let wait_semaphores = waits.iter().map(|wait| *wait.0).collect::<Vec<_>>();
+        let wait_stages = waits.iter().map(|wait| stage_flags(wait.1)).collect::<Vec<_>>();
+        let buffers = buffers.iter().map(|&&buffer| buffer).collect::<Vec<_>>();
+        let signals = signals.iter().map(|&&signal| signal).collect::<Vec<_>>();
+
+        DeviceV1_0::queue_submit(self, *queue, &[vk::SubmitInfo {
+            s_type: vk::StructureType::SubmitInfo,
+            p_next: null(),
+            wait_semaphore_count: wait_semaphores.len() as u32,
+            p_wait_semaphores: wait_semaphores.as_ptr(),
+            p_wait_dst_stage_mask: wait_stages.as_ptr(),
+            command_buffer_count: buffers.len() as u32,
+            p_command_buffers: buffers.as_ptr(),
+            signal_semaphore_count: signals.len() as u32,
+            p_signal_semaphores: signals.as_ptr(),
+        }], fence.cloned().unwrap_or(vk::Fence::null())).unwrap()
+    }
+
+    unsafe fn wait_for_fence(&self, fence: &Self::Fence) -> Result<(), DeviceLost> {
+        match DeviceV1_0::wait_for_fences(self, &[*fence], true, !0) {
+            Ok(()) => Ok(()),
+            Err(vk::Result::ErrorDeviceLost) => Err(DeviceLost),
+            Err(error) => panic!("Unexpected error: {:?}", error),
+        }
+    }
+
+    unsafe fn create_pipeline_cache(&self, data: &[u8]) -> Self::PipelineCache {
+        use std::ptr::null;
+
+        DeviceV1_0::create_pipeline_cache(self, &vk::PipelineCacheCreateInfo {
+            s_type: vk::StructureType::PipelineCacheCreateInfo,
+            p_next: null(),
+            flags: vk::PipelineCacheCreateFlags::empty(),
+            initial_data_size: data.len(),
+            p_initial_data: data.as_ptr() as *const _,
+        }, None).unwrap()
+    }
+
+    unsafe fn get_pipeline_cache_data(&self, cache: &Self::PipelineCache) -> Vec<u8> {
+        let fp = DeviceV1_0::fp_v1_0(self);
+        let mut size = 0;
+        let result = fp.get_pipeline_cache_data(self.handle(), *cache, &mut size, ::std::ptr::null_mut());
+        assert_eq!(result, vk::Result::Success, "Failed to query pipeline cache data size");
+        let mut data = vec![0u8; size];
+        let result = fp.get_pipeline_cache_data(self.handle(), *cache, &mut size, data.as_mut_ptr() as *mut _);
+        assert_eq!(result, vk::Result::Success, "Failed to read pipeline cache data");
+        data.truncate(size);
+        data
+    }
+
+    unsafe fn destroy_pipeline_cache(&self, cache: Self::PipelineCache) {
+        DeviceV1_0::destroy_pipeline_cache(self, cache, None)
+    }
+
+    unsafe fn create_query_pool(&self, ty: query::QueryType, count: u32) -> Self::QueryPool {
+        use std::ptr::null;
+
+        let (query_type, statistics) = match ty {
+            query::QueryType::Occlusion => (vk::QueryType::Occlusion, vk::QueryPipelineStatisticFlags::empty()),
+            query::QueryType::PipelineStatistics(flags) => (
+                vk::QueryType::PipelineStatistics,
+                vk::QueryPipelineStatisticFlags::from_flags(flags.bits()).expect("Unsupported flags"),
+            ),
+        };
+
+        DeviceV1_0::create_query_pool(self, &vk::QueryPoolCreateInfo {
+            s_type: vk::StructureType::QueryPoolCreateInfo,
+            p_next: null(),
+            flags: vk::QueryPoolCreateFlags::empty(),
+            query_type,
+            query_count: count,
+            pipeline_statistics: statistics,
+        }, None).unwrap()
+    }
+
+    unsafe fn destroy_query_pool(&self, pool: Self::QueryPool) {
+        DeviceV1_0::destroy_query_pool(self, pool, None)
+    }
+
+    unsafe fn get_query_pool_results(
+        &self,
+        pool: &Self::QueryPool,
+        queries: Range<u32>,
+        data: &mut [u64],
+    ) -> Result<(), DeviceLost> {
+        match DeviceV1_0::get_query_pool_results(
+            self,
+            *pool,
+            queries.start,
+            queries.end - queries.start,
+            data,
+            vk::QUERY_RESULT_64_BIT | vk::QUERY_RESULT_WITH_AVAILABILITY_BIT,
+        ) {
+            Ok(()) | Err(vk::Result::NotReady) => Ok(()),
+            Err(vk::Result::ErrorDeviceLost) => Err(DeviceLost),
+            Err(error) => panic!("Unexpected error: {:?}", error),
+        }
+    }
 }
 
-impl CommandBuffer for (vk::DeviceFnV1_0, vk::CommandBuffer) {
+impl CommandBuffer for (vk::DeviceFnV1_0, vk::CommandBuffer, Option<DebugUtils>) {
     type Submit = vk::CommandBuffer;
+    type QueryPool = vk::QueryPool;
+    type Buffer = vk::Buffer;
+    type Image = vk::Image;
+    type RenderPass = vk::RenderPass;
+    type Framebuffer = vk::Framebuffer;
 
     unsafe fn submit(&self) -> Self::Submit {
         self.1
     }
+
+    unsafe fn begin_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        if let Some(debug_utils) = self.2 {
+            record_label(debug_utils.begin, self.1, name, color)
+        }
+    }
+
+    unsafe fn end_debug_label(&mut self) {
+        if let Some(debug_utils) = self.2 {
+            (debug_utils.end)(self.1)
+        }
+    }
+
+    unsafe fn insert_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        if let Some(debug_utils) = self.2 {
+            record_label(debug_utils.insert, self.1, name, color)
+        }
+    }
+
+    unsafe fn begin(&mut self, usage: UsageFlags) {
+        use std::ptr::null;
+
+        let result = self.0.begin_command_buffer(self.1, &vk::CommandBufferBeginInfo {
+            s_type: vk::StructureType::CommandBufferBeginInfo,
+            p_next: null(),
+            flags: vk::CommandBufferUsageFlags::from_flags(usage.bits()).expect("Unsupported flags"),
+            p_inheritance_info: null(),
+        });
+        assert_eq!(result, vk::Result::Success);
+    }
+
+    unsafe fn begin_secondary<'a>(
+        &mut self,
+        usage: UsageFlags,
+        inheritance: Option<InheritanceInfo<'a, vk::RenderPass>>,
+    ) {
+        use std::ptr::null;
+
+        let (render_pass, subpass) = inheritance.map_or((vk::RenderPass::null(), 0), |inheritance| {
+            (*inheritance.render_pass, inheritance.subpass)
+        });
+        let result = self.0.begin_command_buffer(self.1, &vk::CommandBufferBeginInfo {
+            s_type: vk::StructureType::CommandBufferBeginInfo,
+            p_next: null(),
+            flags: vk::CommandBufferUsageFlags::from_flags(usage.bits()).expect("Unsupported flags"),
+            p_inheritance_info: &vk::CommandBufferInheritanceInfo {
+                s_type: vk::StructureType::CommandBufferInheritanceInfo,
+                p_next: null(),
+                render_pass,
+                subpass,
+                framebuffer: vk::Framebuffer::null(),
+                occlusion_query_enable: 0,
+                query_flags: vk::QueryControlFlags::empty(),
+                pipeline_statistics: vk::QueryPipelineStatisticFlags::empty(),
+            },
+        });
+        assert_eq!(result, vk::Result::Success);
+    }
+
+    unsafe fn finish(&mut self) {
+        let result = self.0.end_command_buffer(self.1);
+        assert_eq!(result, vk::Result::Success);
+    }
+
+    unsafe fn reset(&mut self) {
+        let result = self.0.reset_command_buffer(self.1, vk::CommandBufferResetFlags::empty());
+        assert_eq!(result, vk::Result::Success);
+    }
+
+    unsafe fn begin_query(&mut self, pool: &vk::QueryPool, query: u32, flags: query::QueryControlFlags) {
+        self.0.cmd_begin_query(self.1, *pool, query, flags.into())
+    }
+
+    unsafe fn end_query(&mut self, pool: &vk::QueryPool, query: u32) {
+        self.0.cmd_end_query(self.1, *pool, query)
+    }
+
+    unsafe fn reset_query_pool(&mut self, pool: &vk::QueryPool, queries: Range<u32>) {
+        self.0.cmd_reset_query_pool(self.1, *pool, queries.start, queries.end - queries.start)
+    }
+
+    unsafe fn clear_color_image(
+        &mut self,
+        image: &vk::Image,
+        layout: image::Layout,
+        color: ClearColor,
+        ranges: &[image::SubresourceRange],
+    ) {
+        let color = clear_color(color);
+        let ranges = ranges.iter().map(subresource_range).collect::<Vec<_>>();
+        self.0.cmd_clear_color_image(self.1, *image, image_layout(layout), &color, ranges.len() as u32, ranges.as_ptr())
+    }
+
+    unsafe fn clear_depth_stencil_image(
+        &mut self,
+        image: &vk::Image,
+        layout: image::Layout,
+        value: ClearDepthStencil,
+        ranges: &[image::SubresourceRange],
+    ) {
+        let value = vk::ClearDepthStencilValue {
+            depth: value.depth,
+            stencil: value.stencil,
+        };
+        let ranges = ranges.iter().map(subresource_range).collect::<Vec<_>>();
+        self.0.cmd_clear_depth_stencil_image(self.1, *image, image_layout(layout), &value, ranges.len() as u32, ranges.as_ptr())
+    }
+
+    unsafe fn clear_attachments(&mut self, clears: &[AttachmentClear], rects: &[ClearRect]) {
+        let clears = clears.iter().map(|clear| match *clear {
+            AttachmentClear::Color { index, value } => vk::ClearAttachment {
+                aspect_mask: vk::IMAGE_ASPECT_COLOR_BIT,
+                color_attachment: index as u32,
+                clear_value: vk::ClearValue { color: clear_color(value) },
+            },
+            AttachmentClear::DepthStencil { depth, stencil } => vk::ClearAttachment {
+                aspect_mask: depth.map_or(vk::ImageAspectFlags::empty(), |_| vk::IMAGE_ASPECT_DEPTH_BIT)
+                    | stencil.map_or(vk::ImageAspectFlags::empty(), |_| vk::IMAGE_ASPECT_STENCIL_BIT),
+                color_attachment: 0,
+                clear_value: vk::ClearValue {
+                    depth: vk::ClearDepthStencilValue {
+                        depth: depth.unwrap_or(0.0),
+                        stencil: stencil.unwrap_or(0),
+                    },
+                },
+            },
+        }).collect::<Vec<_>>();
+        let rects = rects.iter().map(|rect| vk::ClearRect {
+            rect: vk::Rect2D {
+                offset: vk::Offset2D { x: rect.offset[0], y: rect.offset[1] },
+                extent: vk::Extent2D { width: rect.extent[0], height: rect.extent[1] },
+            },
+            base_array_layer: rect.layers.start,
+            layer_count: rect.layers.end - rect.layers.start,
+        }).collect::<Vec<_>>();
+        self.0.cmd_clear_attachments(self.1, clears.len() as u32, clears.as_ptr(), rects.len() as u32, rects.as_ptr())
+    }
+
+    unsafe fn begin_render_pass(
+        &mut self,
+        render_pass: &vk::RenderPass,
+        framebuffer: &vk::Framebuffer,
+        area: Rect,
+        clears: &[ClearValue],
+    ) {
+        let clears = clears.iter().map(|clear| match *clear {
+            ClearValue::Color(color) => vk::ClearValue { color: clear_color(color) },
+            ClearValue::DepthStencil(value) => vk::ClearValue {
+                depth: vk::ClearDepthStencilValue {
+                    depth: value.depth,
+                    stencil: value.stencil,
+                },
+            },
+        }).collect::<Vec<_>>();
+        self.0.cmd_begin_render_pass(self.1, &vk::RenderPassBeginInfo {
+            s_type: vk::StructureType::RenderPassBeginInfo,
+            p_next: ::std::ptr::null(),
+            render_pass: *render_pass,
+            framebuffer: *framebuffer,
+            render_area: vk::Rect2D {
+                offset: vk::Offset2D { x: area.offset[0], y: area.offset[1] },
+                extent: vk::Extent2D { width: area.extent[0], height: area.extent[1] },
+            },
+            clear_value_count: clears.len() as u32,
+            p_clear_values: clears.as_ptr(),
+        }, vk::SubpassContents::Inline)
+    }
+
+    unsafe fn end_render_pass(&mut self) {
+        self.0.cmd_end_render_pass(self.1)
+    }
+
+    unsafe fn set_viewport(&mut self, first: u32, viewports: &[Viewport]) {
+        let viewports = viewports.iter().map(|viewport| vk::Viewport {
+            x: viewport.offset[0],
+            y: viewport.offset[1],
+            width: viewport.extent[0],
+            height: viewport.extent[1],
+            min_depth: viewport.depth.start,
+            max_depth: viewport.depth.end,
+        }).collect::<Vec<_>>();
+        self.0.cmd_set_viewport(self.1, first, viewports.len() as u32, viewports.as_ptr())
+    }
+
+    unsafe fn set_scissor(&mut self, first: u32, scissors: &[Rect]) {
+        let scissors = scissors.iter().map(|rect| vk::Rect2D {
+            offset: vk::Offset2D { x: rect.offset[0], y: rect.offset[1] },
+            extent: vk::Extent2D { width: rect.extent[0], height: rect.extent[1] },
+        }).collect::<Vec<_>>();
+        self.0.cmd_set_scissor(self.1, first, scissors.len() as u32, scissors.as_ptr())
+    }
+
+    unsafe fn set_line_width(&mut self, width: f32) {
+        self.0.cmd_set_line_width(self.1, width)
+    }
+
+    unsafe fn set_depth_bias(&mut self, bias: DepthBias) {
+        self.0.cmd_set_depth_bias(self.1, bias.constant_factor, bias.clamp, bias.slope_factor)
+    }
+
+    unsafe fn set_blend_constants(&mut self, constants: [f32; 4]) {
+        self.0.cmd_set_blend_constants(self.1, &constants)
+    }
+
+    unsafe fn fill_buffer(&mut self, buffer: &vk::Buffer, offset: u64, size: u64, value: u32) {
+        self.0.cmd_fill_buffer(self.1, *buffer, offset, size, value)
+    }
+
+    unsafe fn update_buffer(&mut self, buffer: &vk::Buffer, offset: u64, data: &[u8]) {
+        self.0.cmd_update_buffer(self.1, *buffer, offset, data.len() as u64, data.as_ptr() as *const _)
+    }
+
+    unsafe fn copy_buffer_to_image(
+        &mut self,
+        buffer: &vk::Buffer,
+        image: &vk::Image,
+        layout: image::Layout,
+        regions: &[image::BufferImageCopy],
+    ) {
+        let regions = regions.iter().map(|region| vk::BufferImageCopy {
+            buffer_offset: region.buffer_offset,
+            buffer_row_length: region.buffer_row_length,
+            buffer_image_height: region.buffer_image_height,
+            image_subresource: vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::from_flags(region.aspects.bits()).expect("Unsupported aspects"),
+                mip_level: region.mip_level,
+                base_array_layer: region.layers.start,
+                layer_count: region.layers.end - region.layers.start,
+            },
+            image_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            image_extent: vk::Extent3D {
+                width: region.image_extent.width,
+                height: region.image_extent.height,
+                depth: region.image_extent.depth,
+            },
+        }).collect::<Vec<_>>();
+        self.0.cmd_copy_buffer_to_image(self.1, *buffer, *image, image_layout(layout), regions.len() as u32, regions.as_ptr())
+    }
+
+    unsafe fn image_barrier(
+        &mut self,
+        image: &vk::Image,
+        stages: Range<PipelineStageFlags>,
+        access: Range<AccessFlags>,
+        layouts: Range<image::Layout>,
+        range: image::SubresourceRange,
+    ) {
+        let barrier = vk::ImageMemoryBarrier {
+            s_type: vk::StructureType::ImageMemoryBarrier,
+            p_next: ::std::ptr::null(),
+            src_access_mask: access_flags(access.start),
+            dst_access_mask: access_flags(access.end),
+            old_layout: image_layout(layouts.start),
+            new_layout: image_layout(layouts.end),
+            src_queue_family_index: vk::VK_QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::VK_QUEUE_FAMILY_IGNORED,
+            image: *image,
+            subresource_range: subresource_range(&range),
+        };
+        self.0.cmd_pipeline_barrier(
+            self.1,
+            stage_flags(stages.start),
+            stage_flags(stages.end),
+            vk::DependencyFlags::empty(),
+            0,
+            ::std::ptr::null(),
+            0,
+            ::std::ptr::null(),
+            1,
+            &barrier,
+        )
+    }
+
+    unsafe fn execute_commands<'a, I>(&mut self, buffers: I)
+    where
+        I: IntoIterator<Item = &'a Self>,
+        Self: 'a,
+    {
+        let buffers: Vec<_> = buffers.into_iter().map(|buffer| buffer.1).collect();
+        self.0.cmd_execute_commands(self.1, buffers.len() as u32, buffers.as_ptr())
+    }
 }
 
 impl CommandQueue for vk::Queue {
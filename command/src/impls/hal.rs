@@ -1,10 +1,72 @@
-use std::borrow::Borrow;
+use std::borrow::{Borrow, BorrowMut};
 use std::marker::PhantomData;
+use std::ops::Range;
 
 use hal;
 
+use chain::{AccessFlags, PipelineStageFlags};
+use resource::image;
+
+use buffer::{InheritanceInfo, RawLevel, UsageFlags};
 use device::{CommandBuffer, CommandQueue, Device};
+use encoder::{
+    AttachmentClear, ClearColor, ClearDepthStencil, ClearRect, ClearValue, DepthBias, Rect,
+    Viewport,
+};
+use error::DeviceLost;
+use family::FamilyId;
 use fence;
+use pool;
+use query;
+
+fn image_layout(layout: image::Layout) -> hal::image::Layout {
+    match layout {
+        image::Layout::Undefined => hal::image::Layout::Undefined,
+        image::Layout::General => hal::image::Layout::General,
+        image::Layout::ColorAttachmentOptimal => hal::image::Layout::ColorAttachmentOptimal,
+        image::Layout::DepthStencilAttachmentOptimal => hal::image::Layout::DepthStencilAttachmentOptimal,
+        image::Layout::DepthStencilReadOnlyOptimal => hal::image::Layout::DepthStencilReadOnlyOptimal,
+        image::Layout::ShaderReadOnlyOptimal => hal::image::Layout::ShaderReadOnlyOptimal,
+        image::Layout::TransferSrcOptimal => hal::image::Layout::TransferSrcOptimal,
+        image::Layout::TransferDstOptimal => hal::image::Layout::TransferDstOptimal,
+        image::Layout::Preinitialized => hal::image::Layout::Preinitialized,
+        image::Layout::PresentSrc => hal::image::Layout::Present,
+        layout => panic!("Layout {:?} is not supported by gfx-hal", layout),
+    }
+}
+
+fn subresource_range(range: &image::SubresourceRange) -> hal::image::SubresourceRange {
+    hal::image::SubresourceRange {
+        aspects: hal::format::Aspects::from_bits_truncate(range.aspects.bits() as _),
+        levels: range.levels.start as _..range.levels.end as _,
+        layers: range.layers.start as _..range.layers.end as _,
+    }
+}
+
+fn stage_flags(stages: PipelineStageFlags) -> hal::pso::PipelineStage {
+    hal::pso::PipelineStage::from_bits_truncate(stages.bits())
+}
+
+fn access_flags(access: AccessFlags) -> hal::image::Access {
+    hal::image::Access::from_bits_truncate(access.bits())
+}
+
+fn rect(rect: &Rect) -> hal::pso::Rect {
+    hal::pso::Rect {
+        x: rect.offset[0] as _,
+        y: rect.offset[1] as _,
+        w: rect.extent[0] as _,
+        h: rect.extent[1] as _,
+    }
+}
+
+fn clear_color(color: ClearColor) -> hal::command::ClearColor {
+    match color {
+        ClearColor::Float(value) => hal::command::ClearColor::Float(value),
+        ClearColor::Int(value) => hal::command::ClearColor::Int(value),
+        ClearColor::UInt(value) => hal::command::ClearColor::Uint(value),
+    }
+}
 
 impl<D, B> Device for (D, PhantomData<B>)
 where
@@ -15,24 +77,432 @@ where
     type Fence = B::Fence;
     type Submit = B::CommandBuffer;
     type CommandPool = B::CommandPool;
+    type PipelineCache = B::PipelineCache;
+    type QueryPool = B::QueryPool;
     type CommandBuffer = (B::CommandBuffer, PhantomData<B>);
     type CommandQueue = (B::CommandQueue, PhantomData<B>);
 
     unsafe fn create_fence(&self, info: fence::FenceCreateInfo) -> Self::Fence {
         hal::Device::create_fence(self.0.borrow(), info.flags.contains(fence::FenceCreateFlags::CREATE_SIGNALED))
     }
+
+    unsafe fn destroy_fence(&self, fence: Self::Fence) {
+        hal::Device::destroy_fence(self.0.borrow(), fence)
+    }
+
+    unsafe fn reset_fence(&self, fence: &Self::Fence) {
+        hal::Device::reset_fence(self.0.borrow(), fence).unwrap()
+    }
+
+    unsafe fn create_semaphore(&self) -> Self::Semaphore {
+        hal::Device::create_semaphore(self.0.borrow()).unwrap()
+    }
+
+    unsafe fn destroy_semaphore(&self, semaphore: Self::Semaphore) {
+        hal::Device::destroy_semaphore(self.0.borrow(), semaphore)
+    }
+
+    unsafe fn create_command_pool(
+        &self,
+        family: FamilyId,
+        flags: pool::PoolCreateFlags,
+    ) -> Self::CommandPool {
+        hal::Device::create_command_pool(
+            self.0.borrow(),
+            hal::queue::QueueFamilyId(family.0 as usize),
+            hal::pool::CommandPoolCreateFlags::from_bits_truncate(flags.bits() as _),
+        )
+    }
+
+    unsafe fn destroy_command_pool(&self, pool: Self::CommandPool) {
+        hal::Device::destroy_command_pool(self.0.borrow(), pool)
+    }
+
+    unsafe fn allocate_command_buffer(
+        &self,
+        pool: &mut Self::CommandPool,
+        level: RawLevel,
+    ) -> Self::CommandBuffer {
+        let level = match level {
+            RawLevel::Primary => hal::command::RawLevel::Primary,
+            RawLevel::Secondary => hal::command::RawLevel::Secondary,
+        };
+        (
+            hal::pool::RawCommandPool::allocate_one(pool, level),
+            PhantomData,
+        )
+    }
+
+    unsafe fn free_command_buffer(&self, pool: &mut Self::CommandPool, buffer: Self::CommandBuffer) {
+        hal::pool::RawCommandPool::free(pool, Some(buffer.0))
+    }
+
+    unsafe fn reset_command_pool(&self, pool: &mut Self::CommandPool) {
+        hal::pool::RawCommandPool::reset(pool)
+    }
+
+    unsafe fn queue_submit_batch(
+        &self,
+        queue: &mut Self::CommandQueue,
+        waits: &[(&Self::Semaphore, PipelineStageFlags)],
+        buffers: &[&Self::Submit],
+        signals: &[&Self::Semaphore],
+        fence: Option<&Self::Fence>,
+    ) {
+        hal::queue::RawCommandQueue::submit(
+            &mut queue.0,
+            hal::queue::Submission {
+                command_buffers: buffers,
+                wait_semaphores: waits
+                    .iter()
+                    .map(|&(semaphore, stages)| (semaphore, stage_flags(stages))),
+                signal_semaphores: signals.iter().cloned(),
+            },
+            fence,
+        )
+    }
+
+    unsafe fn wait_for_fence(&self, fence: &Self::Fence) -> Result<(), DeviceLost> {
+        hal::Device::wait_for_fence(self.0.borrow(), fence, !0)
+            .map(|_| ())
+            .map_err(|_| DeviceLost)
+    }
+
+    unsafe fn create_pipeline_cache(&self, data: &[u8]) -> Self::PipelineCache {
+        hal::Device::create_pipeline_cache(self.0.borrow(), Some(data)).unwrap()
+    }
+
+    unsafe fn get_pipeline_cache_data(&self, cache: &Self::PipelineCache) -> Vec<u8> {
+        hal::Device::get_pipeline_cache_data(self.0.borrow(), cache).unwrap()
+    }
+
+    unsafe fn destroy_pipeline_cache(&self, cache: Self::PipelineCache) {
+        hal::Device::destroy_pipeline_cache(self.0.borrow(), cache)
+    }
+
+    unsafe fn create_query_pool(&self, ty: query::QueryType, count: u32) -> Self::QueryPool {
+        let ty = match ty {
+            query::QueryType::Occlusion => hal::query::Type::Occlusion,
+            query::QueryType::PipelineStatistics(flags) => hal::query::Type::PipelineStatistics(
+                hal::query::PipelineStatistic::from_bits_truncate(flags.bits() as _),
+            ),
+        };
+        hal::Device::create_query_pool(self.0.borrow(), ty, count).unwrap()
+    }
+
+    unsafe fn destroy_query_pool(&self, pool: Self::QueryPool) {
+        hal::Device::destroy_query_pool(self.0.borrow(), pool)
+    }
+
+    unsafe fn get_query_pool_results(
+        &self,
+        pool: &Self::QueryPool,
+        queries: Range<u32>,
+        data: &mut [u64],
+    ) -> Result<(), DeviceLost> {
+        let stride = (data.len() / (queries.end - queries.start) as usize) * 8;
+        let data = ::std::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, data.len() * 8);
+        hal::Device::get_query_pool_results(
+            self.0.borrow(),
+            pool,
+            queries,
+            data,
+            stride as _,
+            hal::query::ResultFlags::BITS_64 | hal::query::ResultFlags::WITH_AVAILABILITY,
+        ).map(|_| ()).map_err(|_| DeviceLost)
+    }
 }
 
 impl<C, B> CommandBuffer for (C, PhantomData<B>)
 where
     B: hal::Backend,
-    C: Borrow<B::CommandBuffer>,
+    C: BorrowMut<B::CommandBuffer>,
 {
     type Submit = B::CommandBuffer;
+    type QueryPool = B::QueryPool;
+    type Buffer = B::Buffer;
+    type Image = B::Image;
+    type RenderPass = B::RenderPass;
+    type Framebuffer = B::Framebuffer;
 
     unsafe fn submit(&self) -> Self::Submit {
         self.0.borrow().clone()
     }
+
+    unsafe fn begin_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        hal::command::RawCommandBuffer::begin_debug_marker(self.0.borrow_mut(), name, pack_color(color))
+    }
+
+    unsafe fn end_debug_label(&mut self) {
+        hal::command::RawCommandBuffer::end_debug_marker(self.0.borrow_mut())
+    }
+
+    unsafe fn insert_debug_label(&mut self, name: &str, color: [f32; 4]) {
+        hal::command::RawCommandBuffer::insert_debug_marker(self.0.borrow_mut(), name, pack_color(color))
+    }
+
+    unsafe fn begin(&mut self, usage: UsageFlags) {
+        hal::command::RawCommandBuffer::begin(
+            self.0.borrow_mut(),
+            hal::command::CommandBufferFlags::from_bits_truncate(usage.bits() as _),
+            hal::command::CommandBufferInheritanceInfo::default(),
+        )
+    }
+
+    unsafe fn begin_secondary<'a>(
+        &mut self,
+        usage: UsageFlags,
+        inheritance: Option<InheritanceInfo<'a, B::RenderPass>>,
+    ) {
+        hal::command::RawCommandBuffer::begin(
+            self.0.borrow_mut(),
+            hal::command::CommandBufferFlags::from_bits_truncate(usage.bits() as _),
+            hal::command::CommandBufferInheritanceInfo {
+                subpass: inheritance.map(|inheritance| hal::pass::Subpass {
+                    index: inheritance.subpass as _,
+                    main_pass: inheritance.render_pass,
+                }),
+                ..hal::command::CommandBufferInheritanceInfo::default()
+            },
+        )
+    }
+
+    unsafe fn finish(&mut self) {
+        hal::command::RawCommandBuffer::finish(self.0.borrow_mut())
+    }
+
+    unsafe fn reset(&mut self) {
+        hal::command::RawCommandBuffer::reset(self.0.borrow_mut(), false)
+    }
+
+    unsafe fn begin_query(&mut self, pool: &B::QueryPool, query: u32, flags: query::QueryControlFlags) {
+        hal::command::RawCommandBuffer::begin_query(
+            self.0.borrow_mut(),
+            hal::query::Query { pool, id: query },
+            hal::query::ControlFlags::from_bits_truncate(flags.bits() as _),
+        )
+    }
+
+    unsafe fn end_query(&mut self, pool: &B::QueryPool, query: u32) {
+        hal::command::RawCommandBuffer::end_query(
+            self.0.borrow_mut(),
+            hal::query::Query { pool, id: query },
+        )
+    }
+
+    unsafe fn reset_query_pool(&mut self, pool: &B::QueryPool, queries: Range<u32>) {
+        hal::command::RawCommandBuffer::reset_query_pool(self.0.borrow_mut(), pool, queries)
+    }
+
+    unsafe fn clear_color_image(
+        &mut self,
+        image: &B::Image,
+        layout: image::Layout,
+        color: ClearColor,
+        ranges: &[image::SubresourceRange],
+    ) {
+        hal::command::RawCommandBuffer::clear_image(
+            self.0.borrow_mut(),
+            image,
+            image_layout(layout),
+            clear_color(color).into(),
+            hal::command::ClearDepthStencil(0.0, 0).into(),
+            ranges.iter().map(subresource_range),
+        )
+    }
+
+    unsafe fn clear_depth_stencil_image(
+        &mut self,
+        image: &B::Image,
+        layout: image::Layout,
+        value: ClearDepthStencil,
+        ranges: &[image::SubresourceRange],
+    ) {
+        hal::command::RawCommandBuffer::clear_image(
+            self.0.borrow_mut(),
+            image,
+            image_layout(layout),
+            hal::command::ClearColor::Float([0.0; 4]).into(),
+            hal::command::ClearDepthStencil(value.depth, value.stencil).into(),
+            ranges.iter().map(subresource_range),
+        )
+    }
+
+    unsafe fn clear_attachments(&mut self, clears: &[AttachmentClear], rects: &[ClearRect]) {
+        hal::command::RawCommandBuffer::clear_attachments(
+            self.0.borrow_mut(),
+            clears.iter().map(|clear| match *clear {
+                AttachmentClear::Color { index, value } => hal::command::AttachmentClear::Color {
+                    index,
+                    value: clear_color(value),
+                },
+                AttachmentClear::DepthStencil { depth, stencil } => {
+                    hal::command::AttachmentClear::DepthStencil { depth, stencil }
+                }
+            }),
+            rects.iter().map(|rect| hal::pso::ClearRect {
+                rect: hal::pso::Rect {
+                    x: rect.offset[0] as _,
+                    y: rect.offset[1] as _,
+                    w: rect.extent[0] as _,
+                    h: rect.extent[1] as _,
+                },
+                layers: rect.layers.start as _..rect.layers.end as _,
+            }),
+        )
+    }
+
+    unsafe fn begin_render_pass(
+        &mut self,
+        render_pass: &B::RenderPass,
+        framebuffer: &B::Framebuffer,
+        area: Rect,
+        clears: &[ClearValue],
+    ) {
+        hal::command::RawCommandBuffer::begin_render_pass(
+            self.0.borrow_mut(),
+            render_pass,
+            framebuffer,
+            rect(&area),
+            clears.iter().map(|clear| match *clear {
+                ClearValue::Color(color) => hal::command::ClearValueRaw::from(clear_color(color)),
+                ClearValue::DepthStencil(value) => hal::command::ClearValueRaw::from(
+                    hal::command::ClearDepthStencil(value.depth, value.stencil),
+                ),
+            }),
+            hal::command::SubpassContents::Inline,
+        )
+    }
+
+    unsafe fn end_render_pass(&mut self) {
+        hal::command::RawCommandBuffer::end_render_pass(self.0.borrow_mut())
+    }
+
+    unsafe fn set_viewport(&mut self, first: u32, viewports: &[Viewport]) {
+        hal::command::RawCommandBuffer::set_viewports(
+            self.0.borrow_mut(),
+            first,
+            viewports.iter().map(|viewport| hal::pso::Viewport {
+                rect: hal::pso::Rect {
+                    x: viewport.offset[0] as _,
+                    y: viewport.offset[1] as _,
+                    w: viewport.extent[0] as _,
+                    h: viewport.extent[1] as _,
+                },
+                depth: viewport.depth.clone(),
+            }),
+        )
+    }
+
+    unsafe fn set_scissor(&mut self, first: u32, scissors: &[Rect]) {
+        hal::command::RawCommandBuffer::set_scissors(
+            self.0.borrow_mut(),
+            first,
+            scissors.iter().map(rect),
+        )
+    }
+
+    unsafe fn set_line_width(&mut self, width: f32) {
+        hal::command::RawCommandBuffer::set_line_width(self.0.borrow_mut(), width)
+    }
+
+    unsafe fn set_depth_bias(&mut self, bias: DepthBias) {
+        hal::command::RawCommandBuffer::set_depth_bias(
+            self.0.borrow_mut(),
+            hal::pso::DepthBias {
+                const_factor: bias.constant_factor,
+                clamp: bias.clamp,
+                slope_factor: bias.slope_factor,
+            },
+        )
+    }
+
+    unsafe fn set_blend_constants(&mut self, constants: [f32; 4]) {
+        hal::command::RawCommandBuffer::set_blend_constants(self.0.borrow_mut(), constants)
+    }
+
+    unsafe fn fill_buffer(&mut self, buffer: &B::Buffer, offset: u64, size: u64, value: u32) {
+        hal::command::RawCommandBuffer::fill_buffer(
+            self.0.borrow_mut(),
+            buffer,
+            offset..offset + size,
+            value,
+        )
+    }
+
+    unsafe fn update_buffer(&mut self, buffer: &B::Buffer, offset: u64, data: &[u8]) {
+        hal::command::RawCommandBuffer::update_buffer(self.0.borrow_mut(), buffer, offset, data)
+    }
+
+    unsafe fn copy_buffer_to_image(
+        &mut self,
+        buffer: &B::Buffer,
+        image: &B::Image,
+        layout: image::Layout,
+        regions: &[image::BufferImageCopy],
+    ) {
+        hal::command::RawCommandBuffer::copy_buffer_to_image(
+            self.0.borrow_mut(),
+            buffer,
+            image,
+            image_layout(layout),
+            regions.iter().map(|region| hal::command::BufferImageCopy {
+                buffer_offset: region.buffer_offset,
+                buffer_width: region.buffer_row_length,
+                buffer_height: region.buffer_image_height,
+                image_layers: hal::image::SubresourceLayers {
+                    aspects: hal::format::Aspects::from_bits_truncate(region.aspects.bits() as _),
+                    level: region.mip_level as _,
+                    layers: region.layers.start as _..region.layers.end as _,
+                },
+                image_offset: hal::image::Offset { x: 0, y: 0, z: 0 },
+                image_extent: hal::image::Extent {
+                    width: region.image_extent.width,
+                    height: region.image_extent.height,
+                    depth: region.image_extent.depth,
+                },
+            }),
+        )
+    }
+
+    unsafe fn image_barrier(
+        &mut self,
+        image: &B::Image,
+        stages: Range<PipelineStageFlags>,
+        access: Range<AccessFlags>,
+        layouts: Range<image::Layout>,
+        range: image::SubresourceRange,
+    ) {
+        hal::command::RawCommandBuffer::pipeline_barrier(
+            self.0.borrow_mut(),
+            stage_flags(stages.start)..stage_flags(stages.end),
+            hal::memory::Dependencies::empty(),
+            Some(hal::memory::Barrier::Image {
+                states: (access_flags(access.start), image_layout(layouts.start))
+                    ..(access_flags(access.end), image_layout(layouts.end)),
+                target: image,
+                range: subresource_range(&range),
+            }),
+        )
+    }
+
+    unsafe fn execute_commands<'a, I>(&mut self, buffers: I)
+    where
+        I: IntoIterator<Item = &'a Self>,
+        Self: 'a,
+    {
+        hal::command::RawCommandBuffer::execute_commands(
+            self.0.borrow_mut(),
+            buffers.into_iter().map(|buffer| buffer.0.borrow()),
+        )
+    }
+}
+
+/// Pack normalized RGBA color into `0xRRGGBBAA` as gfx-hal markers expect.
+fn pack_color(color: [f32; 4]) -> u32 {
+    color.iter().fold(0, |packed, channel| {
+        (packed << 8) | (channel.max(0.0).min(1.0) * 255.0).round() as u32
+    })
 }
 
 impl<C, B> CommandQueue for (C, PhantomData<B>)
@@ -3,3 +3,6 @@ mod hal;
 
 #[cfg(feature = "ash")]
 mod ash;
+
+#[cfg(feature = "ash")]
+pub use self::ash::DebugUtils;
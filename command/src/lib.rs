@@ -27,12 +27,17 @@ extern crate relevant;
 extern crate rendy_chain as chain;
 extern crate rendy_resource as resource;
 
+extern crate rendy_memory as memory;
+
 #[cfg(feature = "hal")]
 extern crate gfx_hal as hal;
 
 #[cfg(feature = "ash")]
 extern crate ash;
 
+#[cfg(test)]
+mod test;
+
 mod impls;
 
 mod device;
@@ -40,18 +45,55 @@ mod error;
 mod family;
 mod fence;
 mod frame;
+mod graveyard;
+mod linear;
+mod multiview;
+mod once;
+mod pipeline;
 mod buffer;
 mod capability;
 mod encoder;
 mod pool;
+mod query;
 mod queue;
+mod scratch;
+mod semaphore;
+mod stream;
+mod uniform;
+mod upload;
 
-pub use buffer::{Buffer, Submit};
-pub use capability::{Capability, CapabilityFlags};
-pub use device::{CommandBuffer, Device};
-pub use encoder::Encoder;
+pub use buffer::{
+    Buffer, CompletionState, IndividualReset, InheritanceInfo, Level, MultiShot, OneShot,
+    PrimaryLevel, RawLevel, RenderPassContinue, SecondaryLevel, SimultaneousUse, Submit,
+    UsageFlags,
+};
+pub use capability::{
+    supported_stages, Capability, CapabilityFlags, Compute, Execute, General, Graphics, Supports,
+    Transfer,
+};
+pub use device::{CommandBuffer, CommandQueue, Device};
+pub use encoder::{
+    clear_image_state, fill_buffer_state, AttachmentClear, ClearColor, ClearDepthStencil,
+    ClearRect, ClearValue, DepthBias, DynamicStateFlags, Encoder, Rect, Viewport,
+    MAX_UPDATE_SIZE,
+};
+pub use error::{DeviceLost, SubmitPending};
 pub use family::{Family, FamilyId, Families};
-pub use fence::{FenceCreateInfo, FenceCreateFlags};
+pub use fence::{FenceCreateInfo, FenceCreateFlags, FencePool};
 pub use frame::{Frame, FrameBound, FrameIndex, CompleteFrame, FrameGen};
-pub use pool::{Pool, OwningPool, FramePool};
+pub use graveyard::Graveyard;
+pub use linear::LinearAllocator;
+pub use multiview::{image_barrier_range, ViewMask};
+pub use once::{OneShotEncoder, OneShotPool};
+pub use pipeline::PipelineCache;
+pub use pool::{Pool, OwningPool, FramePool, PoolCreateFlags, Reset, SecondaryPool, TransferPool};
+pub use query::{PipelineStatisticFlags, QueryControlFlags, QueryPool, QueryType};
 pub use queue::{Submission, Queue};
+pub use scratch::{ScratchArena, ThreadLocalArena};
+pub use semaphore::SemaphorePool;
+pub use stream::StreamBuffer;
+pub use uniform::UniformPool;
+pub use upload::upload_texture;
+
+#[cfg(feature = "ash")]
+pub use impls::DebugUtils;
@@ -0,0 +1,183 @@
+//! Per-frame linear sub-allocation.
+
+use std::ops::Range;
+
+use memory::Block;
+use resource::buffer::Buffer;
+
+use frame::{CompleteFrame, Frame, FrameIndex};
+
+/// Region of the memory range reserved for single frame.
+#[derive(Clone, Copy, Debug)]
+struct FrameRegion {
+    frame: Option<FrameIndex>,
+    offset: u64,
+    cursor: u64,
+}
+
+/// Linear allocator that hands out scratch ranges of a buffer or memory block bound to frames.
+/// Range of the memory is split into a ring of equal regions, one per frame in flight.
+/// Allocations are made by bumping a cursor in the region of the current frame.
+/// Region is reclaimed all at once by `reset_frame` when the frame is complete,
+/// so allocations of frames still in flight are never reused.
+#[derive(Debug)]
+pub struct LinearAllocator<T> {
+    memory: T,
+    region_size: u64,
+    regions: Vec<FrameRegion>,
+    current: Option<usize>,
+}
+
+impl<T> LinearAllocator<T> {
+    /// Create new allocator that splits `range` of the `memory` into `frames` regions.
+    /// Allocated ranges are offsets into the `memory`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `frames` is zero.
+    pub fn new(memory: T, range: Range<u64>, frames: usize) -> Self {
+        assert_ne!(frames, 0, "At least one frame region required");
+        let region_size = (range.end - range.start) / frames as u64;
+        LinearAllocator {
+            memory,
+            region_size,
+            regions: (0..frames as u64)
+                .map(|index| FrameRegion {
+                    frame: None,
+                    offset: range.start + index * region_size,
+                    cursor: 0,
+                })
+                .collect(),
+            current: None,
+        }
+    }
+
+    /// Get memory ranges are allocated from.
+    pub fn memory(&self) -> &T {
+        &self.memory
+    }
+
+    /// Take the memory back.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any region is bound to a frame that was not reset yet.
+    pub fn into_memory(self) -> T {
+        assert!(
+            self.regions.iter().all(|region| region.frame.is_none()),
+            "All frames must be reset before the memory is taken back"
+        );
+        self.memory
+    }
+
+    /// Size of the region available for each frame.
+    pub fn region_size(&self) -> u64 {
+        self.region_size
+    }
+
+    /// Number of frames that can be in flight simultaneously.
+    pub fn frames(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Bind free region to the frame.
+    /// Subsequent allocations are served from this region.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if all regions are bound to frames that were not reset yet.
+    pub fn bind_frame<F>(&mut self, frame: &Frame<F>) {
+        let index = self
+            .regions
+            .iter()
+            .position(|region| region.frame.is_none())
+            .expect("`LinearAllocator::reset_frame` must be called before binding more frames");
+        self.regions[index].frame = Some(frame.index());
+        self.current = Some(index);
+    }
+
+    /// Allocate range from the region of the current frame.
+    /// Returns `None` if region has not enough space left
+    /// or the range can't be represented.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if no frame is bound.
+    pub fn allocate(&mut self, size: u64, align: u64) -> Option<Range<u64>> {
+        let index = self
+            .current
+            .expect("`LinearAllocator::bind_frame` must be called before allocation");
+        let ref mut region = self.regions[index];
+        let start = aligned(region.offset + region.cursor, align)?;
+        let end = start.checked_add(size)?;
+        if end > region.offset + self.region_size {
+            return None;
+        }
+        region.cursor = end - region.offset;
+        Some(start..end)
+    }
+
+    /// Number of bytes allocated for the frame.
+    /// Returns `None` if frame is not bound to any region.
+    pub fn allocated(&self, frame: FrameIndex) -> Option<u64> {
+        self.regions
+            .iter()
+            .find(|region| region.frame == Some(frame))
+            .map(|region| region.cursor)
+    }
+
+    /// Reclaim region of the complete frame.
+    /// Regions of the frames still in flight are left intact.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if allocator wasn't bound to the specified frame.
+    pub fn reset_frame<F>(&mut self, complete: &CompleteFrame<F>) {
+        let index = self
+            .regions
+            .iter()
+            .position(|region| region.frame == Some(complete.index()))
+            .expect("Allocator must be bound to the specified frame");
+        let ref mut region = self.regions[index];
+        region.frame = None;
+        region.cursor = 0;
+        if self.current == Some(index) {
+            self.current = None;
+        }
+    }
+}
+
+impl<M, B> LinearAllocator<Buffer<M, B>> {
+    /// Create new allocator that splits whole `buffer` into `frames` regions.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `frames` is zero.
+    pub fn from_buffer(buffer: Buffer<M, B>, frames: usize) -> Self {
+        let size = buffer.info().size;
+        Self::new(buffer, 0..size, frames)
+    }
+}
+
+impl<T> LinearAllocator<T>
+where
+    T: Block,
+{
+    /// Create new allocator that splits whole memory `block` into `frames` regions.
+    /// Allocated ranges are offsets into the `block`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `frames` is zero.
+    pub fn from_block(block: T, frames: usize) -> Self {
+        let range = block.range();
+        Self::new(block, 0..range.end - range.start, frames)
+    }
+}
+
+/// Round `value` up to multiple of `align`.
+/// Returns `None` on overflow.
+pub(crate) fn aligned(value: u64, align: u64) -> Option<u64> {
+    debug_assert_ne!(align, 0);
+    Some(value.checked_add(align - 1)? / align * align)
+}
@@ -0,0 +1,57 @@
+//! Multiview render pass support.
+
+use std::ops::Range;
+
+use resource::image;
+
+/// Mask of views rendered by multiview subpass.
+/// View `n` is rendered to layer `n` of each attachment.
+/// Zero mask disables multiview.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ViewMask(pub u32);
+
+impl ViewMask {
+    /// Check if multiview is enabled.
+    pub fn is_multiview(&self) -> bool {
+        self.0 != 0
+    }
+
+    /// Get number of views rendered.
+    pub fn views(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Get range of layers that must exist in attachments.
+    pub fn layers(&self) -> Range<u32> {
+        0..32 - self.0.leading_zeros()
+    }
+
+    /// Check that image has a layer for each view in the mask.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if view mask refers layers not present in the image.
+    pub fn assert_fits(&self, info: &image::CreateInfo) {
+        assert!(
+            self.layers().end <= info.array,
+            "View mask {:#b} requires {} layers but image has only {}",
+            self.0,
+            self.layers().end,
+            info.array
+        );
+    }
+}
+
+/// Get subresource range to be transitioned by barriers derived from chains.
+/// Chains track images as a whole, so the range covers all levels and layers.
+/// This includes all views of multiview render passes.
+pub fn image_barrier_range(
+    info: &image::CreateInfo,
+    aspects: image::ImageAspectFlags,
+) -> image::SubresourceRange {
+    image::SubresourceRange {
+        aspects,
+        levels: 0..info.mips,
+        layers: 0..info.array,
+    }
+}
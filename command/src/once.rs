@@ -0,0 +1,172 @@
+//! Helper for commands that are executed once outside of frame loop.
+
+use relevant::Relevant;
+
+use buffer::{RawLevel, UsageFlags};
+use capability::Capability;
+use device::{CommandBuffer, Device};
+use encoder::Encoder;
+use error::DeviceLost;
+use family::{Family, FamilyId};
+use fence::FencePool;
+use pool::PoolCreateFlags;
+
+/// Encoder passed to the closure that records commands in `OneShotPool::run_once`.
+#[derive(Debug)]
+pub struct OneShotEncoder<'a, B: 'a, C> {
+    buffer: &'a mut B,
+    capability: C,
+}
+
+impl<B, C> Encoder<C> for OneShotEncoder<'_, B, C>
+where
+    B: CommandBuffer,
+    C: Capability,
+{
+    type Buffer = B;
+
+    unsafe fn buffer(&mut self) -> &mut B {
+        self.buffer
+    }
+
+    fn capability(&self) -> C {
+        self.capability
+    }
+}
+
+/// Transient command pool for initialization-time work
+/// like initial layout transitions and uploads of static data.
+/// Each `run_once` call records one-time-submit buffer, submits it and waits for completion.
+/// Pool and fence are reused between calls.
+#[derive(Debug)]
+pub struct OneShotPool<P, F, C> {
+    pool: P,
+    fences: FencePool<F>,
+    capability: C,
+    family: FamilyId,
+    relevant: Relevant,
+}
+
+impl<P, F, C> OneShotPool<P, F, C> {
+    /// Create transient pool for the family.
+    pub fn new<D, Q>(device: &D, family: &Family<Q, C>) -> Self
+    where
+        D: Device<CommandPool = P, Fence = F>,
+        C: Capability,
+    {
+        let pool = unsafe { device.create_command_pool(family.id(), PoolCreateFlags::TRANSIENT) };
+        OneShotPool {
+            pool,
+            fences: FencePool::new(),
+            capability: family.capability(),
+            family: family.id(),
+            relevant: Relevant,
+        }
+    }
+
+    /// Get id of the family this pool is created for.
+    pub fn family(&self) -> FamilyId {
+        self.family
+    }
+
+    /// Get raw command pool.
+    ///
+    /// # Safety
+    ///
+    /// Raw pool must not be destroyed or reset through the reference.
+    pub unsafe fn raw_mut(&mut self) -> &mut P {
+        &mut self.pool
+    }
+
+    /// Record commands with `record`, submit them to the first queue of the family
+    /// and block until they are complete.
+    /// Returns value returned by `record`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `family` is not the family the pool was created for
+    /// or the family has no queues.
+    pub fn run_once<D, R>(
+        &mut self,
+        device: &D,
+        family: &mut Family<D::CommandQueue, C>,
+        record: impl FnOnce(&mut OneShotEncoder<'_, D::CommandBuffer, C>) -> R,
+    ) -> Result<R, DeviceLost>
+    where
+        D: Device<CommandPool = P, Fence = F>,
+        C: Capability,
+    {
+        assert_eq!(
+            self.family,
+            family.id(),
+            "Commands from pool of family {:?} cannot be submitted to family {:?}",
+            self.family,
+            family.id()
+        );
+        let queue = &mut family
+            .queues()
+            .first_mut()
+            .expect("Family must have at least one queue")
+            .inner;
+
+        unsafe {
+            let buffer = device.allocate_command_buffer(&mut self.pool, RawLevel::Primary);
+            let mut guard = FreeGuard {
+                device,
+                pool: &mut self.pool,
+                buffer: Some(buffer),
+            };
+            let buffer = guard.buffer.as_mut().unwrap();
+
+            buffer.begin(UsageFlags::ONE_TIME_SUBMIT);
+            let result = record(&mut OneShotEncoder {
+                buffer,
+                capability: self.capability,
+            });
+            buffer.finish();
+
+            let fence = self.fences.acquire(device);
+            device.queue_submit(queue, &[buffer.submit()], Some(&fence));
+            let waited = device.wait_for_fence(&fence);
+            match waited {
+                Ok(()) => self.fences.release(fence),
+                Err(DeviceLost) => device.destroy_fence(fence),
+            }
+            waited.map(|()| result)
+        }
+    }
+
+    /// Destroy pool.
+    /// No `run_once` call may be in progress.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<CommandPool = P, Fence = F>,
+    {
+        self.fences.dispose(device);
+        unsafe {
+            device.destroy_command_pool(self.pool);
+        }
+        self.relevant.dispose();
+    }
+}
+
+/// Frees command buffer back to the pool when dropped,
+/// including unwinding from panic in the recording closure.
+struct FreeGuard<'a, D: Device + 'a> {
+    device: &'a D,
+    pool: &'a mut D::CommandPool,
+    buffer: Option<D::CommandBuffer>,
+}
+
+impl<'a, D> Drop for FreeGuard<'a, D>
+where
+    D: Device + 'a,
+{
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            unsafe {
+                self.device.free_command_buffer(self.pool, buffer);
+            }
+        }
+    }
+}
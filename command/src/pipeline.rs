@@ -0,0 +1,60 @@
+//! Pipeline module docs.
+
+use relevant::Relevant;
+
+use device::Device;
+
+/// Pipeline cache wrapper.
+/// Cache data can be stored to disk and used to speed up pipeline creation in subsequent runs.
+/// Data is opaque and only valid for compatible device and driver.
+#[derive(Debug)]
+pub struct PipelineCache<P> {
+    raw: P,
+    relevant: Relevant,
+}
+
+impl<P> PipelineCache<P> {
+    /// Create empty pipeline cache.
+    pub fn new<D>(device: &D) -> Self
+    where
+        D: Device<PipelineCache = P>,
+    {
+        Self::from_data(device, &[])
+    }
+
+    /// Create pipeline cache initialized with data retrieved by `get_data`.
+    /// Incompatible data is ignored by the device.
+    pub fn from_data<D>(device: &D, data: &[u8]) -> Self
+    where
+        D: Device<PipelineCache = P>,
+    {
+        PipelineCache {
+            raw: unsafe { device.create_pipeline_cache(data) },
+            relevant: Relevant,
+        }
+    }
+
+    /// Retrieve data from the cache to be stored.
+    pub fn get_data<D>(&self, device: &D) -> Vec<u8>
+    where
+        D: Device<PipelineCache = P>,
+    {
+        unsafe { device.get_pipeline_cache_data(&self.raw) }
+    }
+
+    /// Get raw pipeline cache to pass into pipeline creation.
+    pub fn raw(&self) -> &P {
+        &self.raw
+    }
+
+    /// Destroy the pipeline cache.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<PipelineCache = P>,
+    {
+        unsafe {
+            device.destroy_pipeline_cache(self.raw);
+        }
+        self.relevant.dispose();
+    }
+}
@@ -1,6 +1,13 @@
 //! Pool module docs.
 
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use relevant::Relevant;
 
@@ -10,6 +17,40 @@ use device::{CommandBuffer, Device};
 use family::FamilyId;
 use frame::{CompleteFrame, Frame, FrameBound, FrameIndex};
 
+bitflags!{
+    /// Bitmask specifying usage behavior for command pool.
+    /// See Vulkan docs for detailed info:
+    /// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkCommandPoolCreateFlagBits.html>
+    #[derive(Default)]
+    #[repr(transparent)]
+    pub struct PoolCreateFlags: u32 {
+        /// Specifies that command buffers allocated from the pool will be short-lived.
+        const TRANSIENT = 0x00000001;
+
+        /// Allows any command buffer allocated from a pool to be individually reset.
+        const RESET_COMMAND_BUFFER = 0x00000002;
+    }
+}
+
+/// Specifies whether command buffers from the pool can be reset individually.
+/// Implemented by `()` and `IndividualReset`.
+pub trait Reset: Copy + Debug {
+    /// Get pool creation flags required by this reset mode.
+    fn flags(&self) -> PoolCreateFlags;
+}
+
+impl Reset for () {
+    fn flags(&self) -> PoolCreateFlags {
+        PoolCreateFlags::empty()
+    }
+}
+
+impl Reset for IndividualReset {
+    fn flags(&self) -> PoolCreateFlags {
+        PoolCreateFlags::RESET_COMMAND_BUFFER
+    }
+}
+
 /// Simple pool wrapper.
 /// Doesn't provide any guarantees.
 /// Wraps raw buffers into `Buffer`.
@@ -23,8 +64,59 @@ pub struct Pool<P, C, R = ()> {
 }
 
 impl<P, C, R> Pool<P, C, R> {
-    /// Allocate new buffer.
-    fn allocate_buffers<D, L>(
+    /// Wrap raw command pool.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must be created for the family with `family` id
+    /// and `capability` must be supported by that family.
+    pub(crate) unsafe fn from_raw(inner: P, capability: C, reset: R, family: FamilyId) -> Self {
+        Pool {
+            inner,
+            capability,
+            reset,
+            family,
+            relevant: Relevant,
+        }
+    }
+
+    /// Get id of the family this pool is associated with.
+    pub fn family(&self) -> FamilyId {
+        self.family
+    }
+
+    /// Get raw command pool.
+    ///
+    /// # Safety
+    ///
+    /// Raw pool must not be destroyed or reset through the reference.
+    pub unsafe fn raw_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+
+    /// Get capability of the pool.
+    pub fn capability(&self) -> C
+    where
+        C: Capability,
+    {
+        self.capability
+    }
+
+    /// Destroy the pool.
+    /// All buffers allocated from the pool must be freed.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<CommandPool = P>,
+    {
+        unsafe {
+            device.destroy_command_pool(self.inner);
+        }
+        self.relevant.dispose();
+    }
+
+    /// Allocate `count` new buffers of `level`.
+    /// Buffers carry capability of the pool.
+    pub fn allocate_buffers<D, L>(
         &mut self,
         device: &D,
         level: L,
@@ -33,13 +125,25 @@ impl<P, C, R> Pool<P, C, R> {
     where
         P: Debug,
         D: Device<CommandPool = P>,
+        C: Capability,
+        L: Level,
+        R: Reset,
     {
-        unimplemented!()
+        (0..count)
+            .map(|_| unsafe {
+                let raw = device.allocate_command_buffer(&mut self.inner, level.raw_level());
+                Buffer::from_raw(raw, self.capability, level, self.reset, self.family)
+            })
+            .collect()
     }
 
     /// Free buffers.
     /// Buffers must be in droppable state.
-    fn free_buffers<D, L, S>(
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any buffer was allocated from pool of another family.
+    pub fn free_buffers<D, L, S>(
         &mut self,
         device: &D,
         buffers: Vec<Buffer<D::CommandBuffer, C, S, L, R>>,
@@ -48,15 +152,50 @@ impl<P, C, R> Pool<P, C, R> {
         D: Device<CommandPool = P>,
         S: Droppable,
     {
-        unimplemented!()
+        for buffer in &buffers {
+            assert_family(self.family, buffer.family(), "freed to pool of");
+        }
+        for buffer in buffers {
+            unsafe {
+                device.free_command_buffer(&mut self.inner, buffer.into_raw());
+            }
+        }
     }
 
     /// Reset all buffers of this pool.
-    pub unsafe fn reset(&mut self) {
-        unimplemented!()
+    /// Buffers are moved to the initial state with single device call.
+    ///
+    /// # Safety
+    ///
+    /// All buffers allocated from this pool must be complete.
+    /// None of them may be pending execution on device.
+    pub unsafe fn reset<D>(&mut self, device: &D)
+    where
+        D: Device<CommandPool = P>,
+    {
+        device.reset_command_pool(&mut self.inner);
     }
 }
 
+/// Pool of buffers capable of transfer commands only.
+/// Typical for uploads on dedicated transfer queue.
+///
+/// Commands that require other capabilities can't be recorded into its buffers.
+///
+/// ```compile_fail
+/// # extern crate rendy_command;
+/// # use rendy_command::{CommandBuffer, Encoder, QueryPool, Transfer};
+/// fn record<E: Encoder<Transfer>>(
+///     encoder: &mut E,
+///     pool: &QueryPool<<E::Buffer as CommandBuffer>::QueryPool>,
+/// ) {
+///     // Occlusion queries are recorded around draws and require graphics capability.
+///     encoder.begin_occlusion_query(pool, 0, false);
+/// }
+/// # fn main() {}
+/// ```
+pub type TransferPool<P, R = ()> = Pool<P, Transfer, R>;
+
 impl<P, R> Pool<P, CapabilityFlags, R> {
     /// Convert capability level
     pub fn cast_capability<C>(self) -> Result<Pool<P, C, R>, Self>
@@ -77,37 +216,131 @@ impl<P, R> Pool<P, CapabilityFlags, R> {
     }
 }
 
-/// Command pool that owns allocated buffers.
+/// Command pool that owns allocated buffers of level `L`.
 /// It can be used to borrow buffers one by one.
 /// All buffers will be reset together via pool.
 /// Prior reset user must ensure all buffers are complete.
 #[derive(Debug)]
-pub struct OwningPool<P, B, C, R = ()> {
+pub struct OwningPool<P, B, C, L = PrimaryLevel, R = ()> {
     inner: Pool<P, C, R>,
     buffers: Vec<B>,
+    level: PhantomData<L>,
     next: usize,
+    secondaries: Vec<(Arc<AtomicUsize>, usize)>,
 }
 
-impl<P, B, C, R> OwningPool<P, B, C, R> {
-    /// Reserve at least `count` buffers.
+impl<P, B, C, L, R> OwningPool<P, B, C, L, R> {
+    /// Wrap pool to own buffers allocated from it.
+    pub fn new(inner: Pool<P, C, R>) -> Self {
+        OwningPool {
+            inner,
+            buffers: Vec::new(),
+            level: PhantomData,
+            next: 0,
+            secondaries: Vec::new(),
+        }
+    }
+
+    /// Number of buffers allocated by the pool.
+    pub fn allocated(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Number of buffers acquired since last reset.
+    pub fn in_use(&self) -> usize {
+        self.next
+    }
+
+    /// Number of allocated buffers that can be acquired without allocation.
+    pub fn available(&self) -> usize {
+        self.buffers.len() - self.next
+    }
+
+    /// Destroy the pool.
+    /// Owned buffers are freed together with the pool.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<CommandPool = P>,
+    {
+        self.inner.dispose(device)
+    }
+
+    /// Reserve at least `count` unused buffers of `level`.
     /// Allocate if there are not enough unused buffers.
-    pub fn reserve(&mut self, count: usize) {
-        unimplemented!()
+    pub fn reserve<D>(&mut self, device: &D, level: L, count: usize)
+    where
+        L: Level,
+        D: Device<CommandPool = P, CommandBuffer = B>,
+    {
+        let available = self.available();
+        if count > available {
+            self.allocate(device, level, count - available);
+        }
     }
 
     /// Acquire command buffer from pool.
-    /// The command buffer could be submitted only as part of submission for associated frame.
+    /// Unused buffer is acquired if there is one, otherwise new buffer is allocated.
+    /// Buffers are acquired again only after reset.
     /// TODO: Check that buffer cannot be moved out.
-    pub fn acquire_buffer<D, L>(
+    pub fn acquire_buffer<D>(
         &mut self,
         device: &D,
         level: L,
     ) -> Buffer<&mut B, C, InitialState, L>
     where
         B: CommandBuffer + Debug + 'static,
-        D: Device<CommandBuffer = B, Submit = B::Submit>,
+        C: Capability,
+        L: Level,
+        D: Device<CommandPool = P, CommandBuffer = B, Submit = B::Submit>,
     {
-        unimplemented!()
+        if self.next == self.buffers.len() {
+            self.allocate(device, level, 1);
+        }
+        let buffer = &mut self.buffers[self.next];
+        self.next += 1;
+        unsafe { Buffer::from_raw(buffer, self.inner.capability, level, (), self.inner.family) }
+    }
+
+    /// Allocate `count` raw buffers of `level`.
+    fn allocate<D>(&mut self, device: &D, level: L, count: usize)
+    where
+        L: Level,
+        D: Device<CommandPool = P, CommandBuffer = B>,
+    {
+        let pool = &mut self.inner.inner;
+        self.buffers.extend(
+            (0..count).map(|_| unsafe { device.allocate_command_buffer(pool, level.raw_level()) }),
+        );
+    }
+
+    /// Record that buffers acquired from this pool since last reset
+    /// execute secondary buffers acquired from `secondary` pool.
+    /// `secondary` pool must not be reset before this pool.
+    pub fn reference_secondary<S>(&mut self, secondary: &SecondaryPool<P, S, C, R>) {
+        let epoch = secondary.epoch.load(Ordering::Acquire);
+        if !self
+            .secondaries
+            .iter()
+            .any(|&(ref shared, _)| Arc::ptr_eq(shared, &secondary.epoch))
+        {
+            self.secondaries.push((secondary.epoch.clone(), epoch));
+        }
+    }
+
+    /// Forget referenced secondary pools.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this function will panic
+    /// if any referenced secondary pool was reset after it was referenced.
+    pub(crate) fn release_secondaries(&mut self) {
+        for (shared, epoch) in self.secondaries.drain(..) {
+            debug_assert_eq!(
+                shared.load(Ordering::Acquire),
+                epoch,
+                "Secondary pool was reset before primary pool that references its buffers"
+            );
+        }
     }
 
     /// Reset all buffers at once.
@@ -116,14 +349,24 @@ impl<P, B, C, R> OwningPool<P, B, C, R> {
     ///
     /// All buffers from this pool must be in resettable state.
     /// Any primary buffer that references secondary buffer from this pool will be invalidated.
-    pub unsafe fn reset(&mut self) {
-        unimplemented!()
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this function will panic
+    /// if any secondary pool referenced by this pool was reset first.
+    pub unsafe fn reset<D>(&mut self, device: &D)
+    where
+        D: Device<CommandPool = P>,
+    {
+        self.release_secondaries();
+        self.inner.reset(device);
+        self.next = 0;
     }
 }
 
-impl<P, B, R> OwningPool<P, B, CapabilityFlags, R> {
+impl<P, B, L, R> OwningPool<P, B, CapabilityFlags, L, R> {
     /// Convert capability level
-    pub fn cast_capability<C>(self) -> Result<OwningPool<P, B, C, R>, Self>
+    pub fn cast_capability<C>(self) -> Result<OwningPool<P, B, C, L, R>, Self>
     where
         C: Capability,
     {
@@ -131,17 +374,99 @@ impl<P, B, R> OwningPool<P, B, CapabilityFlags, R> {
             Ok(inner) => Ok(OwningPool {
                 inner,
                 buffers: self.buffers,
+                level: self.level,
                 next: self.next,
+                secondaries: self.secondaries,
             }),
             Err(inner) => Err(OwningPool {
                 inner,
                 buffers: self.buffers,
+                level: self.level,
                 next: self.next,
+                secondaries: self.secondaries,
             }),
         }
     }
 }
 
+/// Command pool that owns secondary buffers.
+/// Secondary buffers are kept apart from primaries
+/// so that resetting one pool invalidates only primary buffers that reference its buffers.
+/// Primary pools record references via `OwningPool::reference_secondary`
+/// and must be reset before the secondary pools they reference.
+#[derive(Debug)]
+pub struct SecondaryPool<P, B, C, R = ()> {
+    inner: OwningPool<P, B, C, SecondaryLevel, R>,
+    epoch: Arc<AtomicUsize>,
+}
+
+impl<P, B, C, R> SecondaryPool<P, B, C, R> {
+    /// Wrap owning pool to allocate secondary buffers.
+    pub fn new(inner: OwningPool<P, B, C, SecondaryLevel, R>) -> Self {
+        SecondaryPool {
+            inner,
+            epoch: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Number of buffers allocated by the pool.
+    pub fn allocated(&self) -> usize {
+        self.inner.allocated()
+    }
+
+    /// Number of buffers acquired since last reset.
+    pub fn in_use(&self) -> usize {
+        self.inner.in_use()
+    }
+
+    /// Number of allocated buffers that can be acquired without allocation.
+    pub fn available(&self) -> usize {
+        self.inner.available()
+    }
+
+    /// Destroy the pool.
+    /// Owned buffers are freed together with the pool.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<CommandPool = P>,
+    {
+        self.inner.dispose(device)
+    }
+
+    /// Acquire secondary command buffer from pool.
+    pub fn acquire_buffer<D>(
+        &mut self,
+        device: &D,
+    ) -> Buffer<&mut B, C, InitialState, SecondaryLevel>
+    where
+        B: CommandBuffer + Debug + 'static,
+        C: Capability,
+        D: Device<CommandPool = P, CommandBuffer = B, Submit = B::Submit>,
+    {
+        self.inner.acquire_buffer(device, SecondaryLevel)
+    }
+
+    /// Mark buffers of the pool invalidated.
+    /// Primary pools that referenced this pool before will detect out-of-order reset.
+    pub(crate) fn advance_epoch(&mut self) {
+        self.epoch.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Reset all buffers at once.
+    ///
+    /// # Safety
+    ///
+    /// All buffers from this pool must be in resettable state.
+    /// Primary pools that reference buffers from this pool must be reset first.
+    pub unsafe fn reset<D>(&mut self, device: &D)
+    where
+        D: Device<CommandPool = P>,
+    {
+        self.advance_epoch();
+        self.inner.reset(device)
+    }
+}
+
 /// `OwningPool` that can be bound to frame execution.
 /// All command buffers acquired from bound `FramePool` are guarantee
 /// to complete when frame's fence is set, and buffers can be reset.
@@ -152,6 +477,42 @@ pub struct FramePool<P, B, C> {
 }
 
 impl<P, B, C> FramePool<P, B, C> {
+    /// Wrap owning pool to bind it to frames.
+    pub fn new(inner: OwningPool<P, B, C>) -> Self {
+        FramePool { inner, frame: None }
+    }
+
+    /// Number of buffers allocated by the pool.
+    pub fn allocated(&self) -> usize {
+        self.inner.allocated()
+    }
+
+    /// Number of buffers acquired since last reset.
+    pub fn in_use(&self) -> usize {
+        self.inner.in_use()
+    }
+
+    /// Number of allocated buffers that can be acquired without allocation.
+    pub fn available(&self) -> usize {
+        self.inner.available()
+    }
+
+    /// Destroy the pool.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if pool is still bound to frame.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<CommandPool = P>,
+    {
+        assert!(
+            self.frame.is_none(),
+            "`FramePool::reset` must be called before disposal"
+        );
+        self.inner.dispose(device)
+    }
+
     /// Bind pool to particular frame.
     ///
     /// Command pools acquired from the bound pool could be submitted only within frame borrowing lifetime.
@@ -176,18 +537,22 @@ impl<P, B, C> FramePool<P, B, C> {
     }
 
     /// Reset all buffers at once.
+    /// Completion of the bound frame guarantees that all buffers are complete.
     ///
     /// # Panics
     ///
     /// This function will panic if pool wasn't bound to the specified frame.
     ///
-    pub fn reset<F>(&mut self, complete: &CompleteFrame<F>) {
+    pub fn reset<D, F>(&mut self, device: &D, complete: &CompleteFrame<F>)
+    where
+        D: Device<CommandPool = P>,
+    {
         assert_eq!(
             self.frame.take(),
             Some(complete.index()),
             "Pool must be bound to the specified frame"
         );
-        unimplemented!()
+        unsafe { self.inner.reset(device) }
     }
 }
 
@@ -0,0 +1,218 @@
+//! Query module docs.
+
+use std::ops::Range;
+
+use relevant::Relevant;
+
+use capability::CapabilityFlags;
+use device::Device;
+use error::DeviceLost;
+
+bitflags! {
+    /// Bitmask specifying counters queried by pipeline statistics query.
+    /// See Vulkan docs for detailed info:
+    /// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkQueryPipelineStatisticFlagBits.html>
+    #[repr(transparent)]
+    pub struct PipelineStatisticFlags: u32 {
+        /// Count vertices processed by input assembly stage.
+        const INPUT_ASSEMBLY_VERTICES = 0x00000001;
+
+        /// Count primitives processed by input assembly stage.
+        const INPUT_ASSEMBLY_PRIMITIVES = 0x00000002;
+
+        /// Count vertex shader invocations.
+        const VERTEX_SHADER_INVOCATIONS = 0x00000004;
+
+        /// Count geometry shader invocations.
+        const GEOMETRY_SHADER_INVOCATIONS = 0x00000008;
+
+        /// Count primitives generated by geometry shader invocations.
+        const GEOMETRY_SHADER_PRIMITIVES = 0x00000010;
+
+        /// Count primitives processed by clipping stage.
+        const CLIPPING_INVOCATIONS = 0x00000020;
+
+        /// Count primitives output by clipping stage.
+        const CLIPPING_PRIMITIVES = 0x00000040;
+
+        /// Count fragment shader invocations.
+        const FRAGMENT_SHADER_INVOCATIONS = 0x00000080;
+
+        /// Count patches processed by tessellation control shader.
+        const TESSELLATION_CONTROL_SHADER_PATCHES = 0x00000100;
+
+        /// Count tessellation evaluation shader invocations.
+        const TESSELLATION_EVALUATION_SHADER_INVOCATIONS = 0x00000200;
+
+        /// Count compute shader invocations.
+        const COMPUTE_SHADER_INVOCATIONS = 0x00000400;
+    }
+}
+
+bitflags! {
+    /// Bitmask specifying constraints on a query.
+    /// See Vulkan docs for detailed info:
+    /// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkQueryControlFlagBits.html>
+    #[repr(transparent)]
+    pub struct QueryControlFlags: u32 {
+        /// Occlusion query must return exact number of samples.
+        const PRECISE = 0x00000001;
+    }
+}
+
+/// Type of queries in the pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryType {
+    /// Query counts samples that pass per-fragment tests.
+    Occlusion,
+
+    /// Query counts operations performed by pipeline stages.
+    PipelineStatistics(PipelineStatisticFlags),
+}
+
+impl QueryType {
+    /// Get number of values written for single query of this type.
+    pub fn values(&self) -> usize {
+        match *self {
+            QueryType::Occlusion => 1,
+            QueryType::PipelineStatistics(flags) => flags.bits().count_ones() as usize,
+        }
+    }
+
+    /// Get capabilities family must have to record queries of this type.
+    /// Family must have at least one of the returned flags.
+    pub fn required_capability(&self) -> CapabilityFlags {
+        match *self {
+            QueryType::Occlusion => CapabilityFlags::GRAPHICS,
+            QueryType::PipelineStatistics(_) => {
+                CapabilityFlags::GRAPHICS | CapabilityFlags::COMPUTE
+            }
+        }
+    }
+}
+
+/// Query pool wrapper.
+/// Remembers type and number of queries to validate recording commands.
+#[derive(Debug)]
+pub struct QueryPool<P> {
+    raw: P,
+    ty: QueryType,
+    count: u32,
+    relevant: Relevant,
+}
+
+impl<P> QueryPool<P> {
+    /// Create pool of `count` queries of specified type.
+    pub fn new<D>(device: &D, ty: QueryType, count: u32) -> Self
+    where
+        D: Device<QueryPool = P>,
+    {
+        QueryPool {
+            raw: unsafe { device.create_query_pool(ty, count) },
+            ty,
+            count,
+            relevant: Relevant,
+        }
+    }
+
+    /// Get type of queries in the pool.
+    pub fn ty(&self) -> QueryType {
+        self.ty
+    }
+
+    /// Get number of queries in the pool.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Get raw query pool.
+    pub fn raw(&self) -> &P {
+        &self.raw
+    }
+
+    /// Read results of the queries.
+    /// Result of a query is `None` if it is not available yet.
+    /// Otherwise it contains `ty().values()` values.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `queries` is out of pool bounds.
+    pub fn get_results<D>(
+        &self,
+        device: &D,
+        queries: Range<u32>,
+    ) -> Result<Vec<Option<Vec<u64>>>, DeviceLost>
+    where
+        D: Device<QueryPool = P>,
+    {
+        self.assert_queries(queries.clone());
+        let values = self.ty.values();
+        let stride = values + 1;
+        let mut data = vec![0; stride * (queries.end - queries.start) as usize];
+        unsafe {
+            device.get_query_pool_results(&self.raw, queries, &mut data)?;
+        }
+        Ok(data
+            .chunks(stride)
+            .map(|chunk| {
+                if chunk[values] != 0 {
+                    Some(chunk[..values].to_vec())
+                } else {
+                    None
+                }
+            }).collect())
+    }
+
+    /// Destroy the query pool.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<QueryPool = P>,
+    {
+        unsafe {
+            device.destroy_query_pool(self.raw);
+        }
+        self.relevant.dispose();
+    }
+
+    /// Check that occlusion or pipeline statistics query, as specified by `occlusion`,
+    /// is recorded on family with `capability`.
+    pub(crate) fn assert_recording(
+        &self,
+        occlusion: bool,
+        capability: CapabilityFlags,
+        query: u32,
+    ) {
+        let name = if occlusion {
+            "Occlusion"
+        } else {
+            "Pipeline statistics"
+        };
+        let matches = match self.ty {
+            QueryType::Occlusion => occlusion,
+            QueryType::PipelineStatistics(_) => !occlusion,
+        };
+        assert!(
+            matches,
+            "{} query recorded to pool of {:?} queries",
+            name, self.ty
+        );
+        assert!(
+            capability.intersects(self.ty.required_capability()),
+            "{} query requires one of {:?} capabilities, family has {:?}",
+            name,
+            self.ty.required_capability(),
+            capability
+        );
+        self.assert_queries(query..query + 1);
+    }
+
+    /// Check that `queries` are in pool bounds.
+    pub(crate) fn assert_queries(&self, queries: Range<u32>) {
+        assert!(
+            queries.start <= queries.end && queries.end <= self.count,
+            "Queries {:?} are out of pool bounds 0..{}",
+            queries,
+            self.count
+        );
+    }
+}
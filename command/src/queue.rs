@@ -2,8 +2,11 @@
 
 use std::borrow::Borrow;
 
-use buffer::Submit;
-use device::CommandQueue;
+use chain::PipelineStageFlags;
+
+use buffer::{assert_family, Submit};
+use device::Device;
+use family::FamilyId;
 
 /// Submission is a list of command buffers in executable state (in form of `Submit`s)
 /// together with semaphores to wait and semaphores signal.
@@ -24,10 +27,19 @@ pub struct Submission<W, L, S> {
 pub struct Queue<Q, C> {
     pub(super) inner: Q,
     pub(super) capability: C,
+    pub(super) family: FamilyId,
 }
 
 impl<Q, C> Queue<Q, C> {
+    /// Get id of the family this queue belongs to.
+    pub fn family(&self) -> FamilyId {
+        self.family
+    }
+
     /// Submit command buffers to the queue.
+    /// Each submission waits for its semaphores at specified stages
+    /// and signals its semaphores when its buffers complete.
+    /// `fence` gets signaled when all submissions complete.
     ///
     /// # Panics
     ///
@@ -39,18 +51,71 @@ impl<Q, C> Queue<Q, C> {
     /// User must ensure that for each semaphore to wait there must be queued signal of that semaphore.
     /// [See Vulkan spec for details](https://www.khronos.org/registry/vulkan/specs/1.1-extensions/html/vkspec.html#commandbuffers-submission-progress)
     ///
-    pub unsafe fn submit<I, WI, BI, SI, W, B, S, F>(&mut self, submission: I, fence: Option<F>)
-    where
-        Q: CommandQueue,
+    pub unsafe fn submit<D, I, WI, BI, SI, W, B, S, F>(
+        &mut self,
+        device: &D,
+        submissions: I,
+        fence: Option<F>,
+    ) where
+        D: Device<CommandQueue = Q>,
         I: IntoIterator<Item = Submission<WI, BI, SI>>,
-        WI: IntoIterator<Item = W>,
+        WI: IntoIterator<Item = (W, PipelineStageFlags)>,
         BI: IntoIterator<Item = Submit<B>>,
         SI: IntoIterator<Item = S>,
-        W: Borrow<Q::Semaphore>,
-        B: Borrow<Q::Submit>,
-        S: Borrow<Q::Semaphore>,
-        F: Borrow<Q::Fence>,
+        W: Borrow<D::Semaphore>,
+        B: Borrow<D::Submit>,
+        S: Borrow<D::Semaphore>,
+        F: Borrow<D::Fence>,
     {
-        unimplemented!()
+        // Check all buffers before anything is submitted.
+        let family = self.family;
+        let submissions = submissions
+            .into_iter()
+            .map(|submission| {
+                let buffers = submission.buffers.into_iter().collect::<Vec<_>>();
+                for submit in &buffers {
+                    assert_family(family, submit.family(), "submitted to queue of");
+                }
+                Submission {
+                    waits: submission.waits.into_iter().collect::<Vec<_>>(),
+                    buffers: buffers
+                        .into_iter()
+                        .map(Submit::into_inner)
+                        .collect::<Vec<_>>(),
+                    signals: submission.signals.into_iter().collect::<Vec<_>>(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Fence signaled by the last batch covers all commands submitted before.
+        let last = submissions.len().saturating_sub(1);
+        for (index, submission) in submissions.iter().enumerate() {
+            let waits = submission
+                .waits
+                .iter()
+                .map(|&(ref semaphore, stages)| (semaphore.borrow(), stages))
+                .collect::<Vec<_>>();
+            let buffers = submission
+                .buffers
+                .iter()
+                .map(Borrow::borrow)
+                .collect::<Vec<_>>();
+            let signals = submission
+                .signals
+                .iter()
+                .map(Borrow::borrow)
+                .collect::<Vec<_>>();
+            let fence = if index == last {
+                fence.as_ref().map(Borrow::borrow)
+            } else {
+                None
+            };
+            device.queue_submit_batch(&mut self.inner, &waits, &buffers, &signals, fence);
+        }
+        if submissions.is_empty() {
+            if let Some(ref fence) = fence {
+                device.queue_submit_batch(&mut self.inner, &[], &[], &[], Some(fence.borrow()));
+            }
+        }
     }
 }
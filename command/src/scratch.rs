@@ -0,0 +1,148 @@
+//! Per-thread scratch sub-allocation for parallel command recording.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use linear::aligned;
+
+#[derive(Debug)]
+struct Chunks {
+    free: Vec<u64>,
+    used: Vec<u64>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    chunk_size: u64,
+    chunks: Mutex<Chunks>,
+    epoch: AtomicUsize,
+    acquisitions: AtomicUsize,
+}
+
+impl Shared {
+    fn acquire(&self) -> Option<u64> {
+        let mut chunks = self.chunks.lock().unwrap();
+        let offset = chunks.free.pop()?;
+        chunks.used.push(offset);
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        Some(offset)
+    }
+}
+
+/// Pool of scratch chunks shared by recording threads.
+/// Memory range is split into chunks of equal size.
+/// Each thread allocates through its own `ScratchArena` handle
+/// by bumping a cursor in the chunk it owns.
+/// Lock is taken only to acquire next chunk when current one is exhausted.
+#[derive(Debug)]
+pub struct ThreadLocalArena {
+    shared: Arc<Shared>,
+}
+
+impl ThreadLocalArena {
+    /// Create new arena that splits `range` into chunks of `chunk_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `chunk_size` is zero.
+    pub fn new(range: Range<u64>, chunk_size: u64) -> Self {
+        assert_ne!(chunk_size, 0, "Chunk size must be non-zero");
+        let count = (range.end - range.start) / chunk_size;
+        let free = (0..count)
+            .rev()
+            .map(|index| range.start + index * chunk_size)
+            .collect();
+        ThreadLocalArena {
+            shared: Arc::new(Shared {
+                chunk_size,
+                chunks: Mutex::new(Chunks {
+                    free,
+                    used: Vec::new(),
+                }),
+                epoch: AtomicUsize::new(0),
+                acquisitions: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Size of each chunk.
+    /// This is the largest allocation that can be made.
+    pub fn chunk_size(&self) -> u64 {
+        self.shared.chunk_size
+    }
+
+    /// Create handle for a recording thread.
+    pub fn handle(&self) -> ScratchArena {
+        ScratchArena {
+            shared: self.shared.clone(),
+            epoch: self.shared.epoch.load(Ordering::Acquire),
+            chunk: None,
+            cursor: 0,
+        }
+    }
+
+    /// Number of chunks acquired by all handles so far.
+    /// Each acquisition takes the lock.
+    pub fn chunk_acquisitions(&self) -> usize {
+        self.shared.acquisitions.load(Ordering::Relaxed)
+    }
+
+    /// Reclaim all chunks acquired by all handles.
+    /// Handles drop their chunks on next allocation.
+    /// Must be called once per frame when device no longer uses allocated ranges,
+    /// e.g. after the frame is complete.
+    pub fn reset(&self) {
+        let mut chunks = self.shared.chunks.lock().unwrap();
+        let Chunks {
+            ref mut free,
+            ref mut used,
+        } = *chunks;
+        free.extend(used.drain(..));
+        self.shared.epoch.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Handle of `ThreadLocalArena` owned by single recording thread.
+#[derive(Debug)]
+pub struct ScratchArena {
+    shared: Arc<Shared>,
+    epoch: usize,
+    chunk: Option<u64>,
+    cursor: u64,
+}
+
+impl ScratchArena {
+    /// Allocate scratch range.
+    /// Returns `None` if `size` exceeds chunk size or no chunks left.
+    pub fn allocate(&mut self, size: u64, align: u64) -> Option<Range<u64>> {
+        if size > self.shared.chunk_size {
+            return None;
+        }
+
+        let epoch = self.shared.epoch.load(Ordering::Acquire);
+        if epoch != self.epoch {
+            // Chunk was reclaimed by `ThreadLocalArena::reset`.
+            self.epoch = epoch;
+            self.chunk = None;
+        }
+
+        if let Some(range) = self.bump(size, align) {
+            return Some(range);
+        }
+        self.chunk = Some(self.shared.acquire()?);
+        self.cursor = 0;
+        self.bump(size, align)
+    }
+
+    fn bump(&mut self, size: u64, align: u64) -> Option<Range<u64>> {
+        let chunk = self.chunk?;
+        let start = aligned(chunk + self.cursor, align)?;
+        let end = start.checked_add(size)?;
+        if end > chunk + self.shared.chunk_size {
+            return None;
+        }
+        self.cursor = end - chunk;
+        Some(start..end)
+    }
+}
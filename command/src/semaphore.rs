@@ -0,0 +1,71 @@
+//! Semaphore module docs.
+
+use relevant::Relevant;
+
+use device::Device;
+
+/// Pool of semaphores.
+/// Recycles released semaphores instead of destroying them
+/// to avoid creating new semaphores every frame.
+#[derive(Debug)]
+pub struct SemaphorePool<S> {
+    free: Vec<S>,
+    relevant: Relevant,
+}
+
+impl<S> SemaphorePool<S> {
+    /// Create empty pool.
+    pub fn new() -> Self {
+        SemaphorePool {
+            free: Vec::new(),
+            relevant: Relevant,
+        }
+    }
+
+    /// Get unsignaled semaphore.
+    /// New semaphore is created if pool has no free semaphores.
+    pub fn acquire<D>(&mut self, device: &D) -> S
+    where
+        D: Device<Semaphore = S>,
+    {
+        self.free
+            .pop()
+            .unwrap_or_else(|| unsafe { device.create_semaphore() })
+    }
+
+    /// Return semaphore to the pool.
+    ///
+    /// # Safety
+    ///
+    /// Semaphore must be created by the same device that is used with this pool.
+    /// Semaphore must be unsignaled and must not be used by pending submissions,
+    /// i.e. wait operation on it must be complete or it was never submitted.
+    pub unsafe fn release(&mut self, semaphore: S) {
+        self.free.push(semaphore);
+    }
+
+    /// Get number of free semaphores in the pool.
+    pub fn free(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Destroy all free semaphores.
+    /// All acquired semaphores must be released or destroyed before.
+    pub fn dispose<D>(self, device: &D)
+    where
+        D: Device<Semaphore = S>,
+    {
+        for semaphore in self.free {
+            unsafe {
+                device.destroy_semaphore(semaphore);
+            }
+        }
+        self.relevant.dispose();
+    }
+}
+
+impl<S> Default for SemaphorePool<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
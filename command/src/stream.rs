@@ -0,0 +1,168 @@
+//! Persistently mapped buffer for data streamed every frame.
+
+use std::{
+    collections::VecDeque,
+    mem::size_of,
+    ptr::{copy_nonoverlapping, NonNull},
+    slice::from_raw_parts_mut,
+};
+
+use memory::{Block, Device, MappingError, Properties};
+use relevant::Relevant;
+use resource::buffer::Buffer;
+
+use frame::{CompleteFrame, Frame, FrameIndex};
+
+/// Persistently mapped `HOST_VISIBLE | HOST_COHERENT` buffer
+/// for dynamic vertex, index and uniform data that changes every frame.
+/// Data is written directly into the mapped memory at the head of a ring.
+/// Space is reclaimed by `reset_frame` when frames that used the data are complete.
+#[derive(Debug)]
+pub struct StreamBuffer<M, B> {
+    buffer: Buffer<M, B>,
+    ptr: NonNull<u8>,
+    size: u64,
+    align: u64,
+    // Head and tail grow monotonically. Offset in the buffer is the value modulo `size`.
+    head: u64,
+    tail: u64,
+    frames: VecDeque<(FrameIndex, u64)>,
+    relevant: Relevant,
+}
+
+impl<M, B> StreamBuffer<M, B>
+where
+    M: 'static,
+{
+    /// Map whole buffer persistently.
+    /// Every pushed data starts at offset multiple of `align`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if buffer memory is not `HOST_COHERENT`
+    /// or buffer size is not multiple of `align`.
+    pub fn new<D>(device: &D, mut buffer: Buffer<M, B>, align: u64) -> Result<Self, MappingError>
+    where
+        D: Device<Memory = M>,
+    {
+        let size = buffer.info().size;
+        assert_ne!(align, 0, "Alignment must not be zero");
+        assert_eq!(
+            size % align,
+            0,
+            "Stream buffer size {} must be multiple of alignment {}",
+            size,
+            align
+        );
+        let properties = buffer.block().properties();
+        assert!(
+            !properties.contains(Properties::HOST_VISIBLE)
+                || properties.contains(Properties::HOST_COHERENT),
+            "Stream buffer memory must be HOST_COHERENT"
+        );
+        let ptr = buffer.block_mut().map(device, 0..size)?.ptr();
+
+        Ok(StreamBuffer {
+            buffer,
+            ptr,
+            size,
+            align,
+            head: 0,
+            tail: 0,
+            frames: VecDeque::new(),
+            relevant: Relevant,
+        })
+    }
+
+    /// Get the buffer to bind pushed data from.
+    pub fn buffer(&self) -> &Buffer<M, B> {
+        &self.buffer
+    }
+
+    /// Get number of bytes not occupied by data of frames in flight.
+    /// Alignment and wrapping may waste part of it.
+    pub fn available(&self) -> u64 {
+        self.size - (self.head - self.tail)
+    }
+
+    /// Write data into the buffer.
+    /// Returns offset in the buffer and length in bytes of the written data.
+    /// Returns `None` if there is not enough free space
+    /// until more frames are complete.
+    pub fn push<T>(&mut self, data: &[T]) -> Option<(u64, u64)>
+    where
+        T: Copy,
+    {
+        let len = (data.len() * size_of::<T>()) as u64;
+        let (offset, bytes) = self.reserve(len)?;
+        unsafe {
+            copy_nonoverlapping(data.as_ptr() as *const u8, bytes.as_mut_ptr(), len as usize);
+        }
+        Some((offset, len))
+    }
+
+    /// Reserve `len` bytes in the buffer to be written by the caller.
+    /// Returns offset in the buffer and mapped bytes of the reserved range.
+    /// Returns `None` if there is not enough free space
+    /// until more frames are complete.
+    pub fn reserve(&mut self, len: u64) -> Option<(u64, &mut [u8])> {
+        let mut start = aligned(self.head, self.align);
+        if start % self.size + len > self.size {
+            // Data can't be split. Wrap to the beginning of the buffer.
+            start = (start / self.size + 1) * self.size;
+        }
+        if start + len - self.tail > self.size {
+            return None;
+        }
+
+        let offset = start % self.size;
+        self.head = start + len;
+        let bytes =
+            unsafe { from_raw_parts_mut(self.ptr.as_ptr().offset(offset as isize), len as usize) };
+        Some((offset, bytes))
+    }
+
+    /// Mark all data pushed since previous mark as used by the frame.
+    /// The data is kept intact until the frame is complete.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if frames are marked out of order.
+    pub fn mark_frame<F>(&mut self, frame: &Frame<F>) {
+        let index = frame.index();
+        if let Some(last) = self.frames.back_mut() {
+            assert!(last.0 <= index, "Frames must be marked in order");
+            if last.0 == index {
+                last.1 = self.head;
+                return;
+            }
+        }
+        self.frames.push_back((index, self.head));
+    }
+
+    /// Reclaim space occupied by data of the complete frame and all frames before it.
+    pub fn reset_frame<F>(&mut self, complete: &CompleteFrame<F>) {
+        while let Some(&(index, end)) = self.frames.front() {
+            if index > complete.index() {
+                break;
+            }
+            self.tail = end;
+            self.frames.pop_front();
+        }
+    }
+
+    /// Unmap buffer and return it.
+    /// All frames that use data from the buffer must be complete.
+    pub fn dispose<D>(mut self, device: &D) -> Buffer<M, B>
+    where
+        D: Device<Memory = M>,
+    {
+        self.buffer.block_mut().unmap(device);
+        self.relevant.dispose();
+        self.buffer
+    }
+}
+
+fn aligned(value: u64, align: u64) -> u64 {
+    (value + align - 1) / align * align
+}
@@ -0,0 +1,155 @@
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use chain::{collect, sync, AccessFlags, Id, PipelineStageFlags, State};
+use memory::{usage::Data, Properties};
+use resource::{image, Resources, SharingMode};
+
+use capability::Graphics;
+use encoder::{clear_image_state, ClearColor, Encoder};
+
+use super::{chain_node, heaps, recorder, MockDevice};
+
+fn image_info(usage: image::UsageFlags) -> image::CreateInfo {
+    image::CreateInfo {
+        kind: image::Kind::D2,
+        format: image::Format::R8G8B8A8_UNORM,
+        extent: image::Extent3D {
+            width: 16,
+            height: 16,
+            depth: 1,
+        },
+        mips: 1,
+        array: 1,
+        samples: image::SampleCountFlags::SAMPLE_COUNT_1,
+        tiling: image::ImageTiling::Optimal,
+        usage,
+        sharing: SharingMode::Exclusive,
+        flags: image::ImageCreateFlags::empty(),
+    }
+}
+
+fn color_range() -> image::SubresourceRange {
+    image::SubresourceRange {
+        aspects: image::ImageAspectFlags::COLOR,
+        levels: 0..1,
+        layers: 0..1,
+    }
+}
+
+#[test]
+fn color_clear_is_recorded_and_tracked() {
+    let ref device = MockDevice::default();
+    let mut heaps = heaps(Properties::DEVICE_LOCAL);
+    let mut resources: Resources<(), (), ()> = Resources::new();
+    let image = resources
+        .create_image(
+            device,
+            &mut heaps,
+            image_info(image::UsageFlags::TRANSFER_DST | image::UsageFlags::SAMPLED),
+            1,
+            Data,
+        )
+        .unwrap();
+
+    let mut encoder = recorder(Graphics);
+    encoder.clear_color_image(
+        &image,
+        image::Layout::TransferDstOptimal,
+        ClearColor::Float([0.0, 0.0, 0.0, 1.0]),
+        &[color_range()],
+    );
+    assert_eq!(
+        encoder.buffer.clears,
+        vec![(image::Layout::TransferDstOptimal, vec![color_range()])]
+    );
+
+    // Node recording the clear declares this state for the image.
+    let state = clear_image_state(image::Layout::TransferDstOptimal);
+    assert!(image.info().usage.contains(state.usage));
+
+    unsafe {
+        Resources::<(), (), ()>::destroy_image(image, device, &mut heaps);
+    }
+    heaps.dispose(device);
+}
+
+#[test]
+#[should_panic(expected = "must be created with TRANSFER_DST usage")]
+fn clear_requires_transfer_dst() {
+    let ref device = MockDevice::default();
+    let mut heaps = heaps(Properties::DEVICE_LOCAL);
+    let mut resources: Resources<(), (), ()> = Resources::new();
+    let image = resources
+        .create_image(
+            device,
+            &mut heaps,
+            image_info(image::UsageFlags::SAMPLED),
+            1,
+            Data,
+        )
+        .unwrap();
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        recorder(Graphics).clear_color_image(
+            &image,
+            image::Layout::TransferDstOptimal,
+            ClearColor::Float([0.0; 4]),
+            &[color_range()],
+        );
+    }));
+
+    unsafe {
+        Resources::<(), (), ()>::destroy_image(image, device, &mut heaps);
+    }
+    heaps.dispose(device);
+    if let Err(panic) = result {
+        resume_unwind(panic);
+    }
+}
+
+#[test]
+fn cleared_image_is_synchronized_with_sampling() {
+    let mut clear = chain_node(0, vec![]);
+    clear
+        .images
+        .insert(Id(0), clear_image_state(image::Layout::TransferDstOptimal));
+    let mut sample = chain_node(1, vec![0]);
+    sample.images.insert(
+        Id(0),
+        State {
+            access: AccessFlags::SHADER_READ,
+            layout: image::Layout::ShaderReadOnlyOptimal,
+            stages: PipelineStageFlags::FRAGMENT_SHADER,
+            usage: image::UsageFlags::SAMPLED,
+        },
+    );
+
+    let chains = collect(vec![clear, sample], |_| 1);
+    let ref chain = chains.images[&Id(0)];
+    let (_, link) = chain.history().next().unwrap();
+    assert_eq!(link.access(), AccessFlags::TRANSFER_WRITE);
+    assert_eq!(link.stages(), PipelineStageFlags::TRANSFER);
+    assert_eq!(link.layout(), image::Layout::TransferDstOptimal);
+    assert_eq!(link.usage(), image::UsageFlags::TRANSFER_DST);
+
+    // Clear must be made visible to sampling and image transitioned out of clear layout.
+    let schedule = sync(&chains, || ((), ()));
+    let sampling = schedule.ordered().find(|s| s.node() == 1).unwrap();
+    let ref barrier = sampling.sync().acquire.images[&Id(0)];
+    assert_eq!(
+        barrier.states.start,
+        (
+            AccessFlags::TRANSFER_WRITE,
+            image::Layout::TransferDstOptimal,
+            PipelineStageFlags::TRANSFER,
+        )
+    );
+    assert_eq!(
+        barrier.states.end,
+        (
+            AccessFlags::SHADER_READ,
+            image::Layout::ShaderReadOnlyOptimal,
+            PipelineStageFlags::FRAGMENT_SHADER,
+        )
+    );
+}
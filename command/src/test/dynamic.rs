@@ -0,0 +1,72 @@
+use capability::Graphics;
+use encoder::{DepthBias, DynamicStateFlags, Encoder, Rect, Viewport};
+
+use super::recorder;
+
+fn viewport() -> Viewport {
+    Viewport {
+        offset: [0.0, 0.0],
+        extent: [800.0, 600.0],
+        depth: 0.0..1.0,
+    }
+}
+
+fn scissor() -> Rect {
+    Rect {
+        offset: [0, 0],
+        extent: [800, 600],
+    }
+}
+
+#[test]
+fn viewport_and_scissor_are_recorded_once_per_call() {
+    let mut encoder = recorder(Graphics);
+    encoder.set_viewport(0, &[viewport()]);
+    encoder.set_scissor(0, &[scissor()]);
+    encoder.set_scissor(1, &[scissor(), scissor()]);
+
+    assert_eq!(
+        encoder.buffer.dynamic,
+        vec![
+            DynamicStateFlags::VIEWPORT,
+            DynamicStateFlags::SCISSOR,
+            DynamicStateFlags::SCISSOR,
+        ]
+    );
+    assert_eq!(encoder.buffer.viewports, vec![(0, vec![viewport()])]);
+    assert_eq!(
+        encoder.buffer.scissors,
+        vec![(0, vec![scissor()]), (1, vec![scissor(), scissor()])]
+    );
+}
+
+#[test]
+fn enabled_dynamic_states_are_accepted() {
+    let mut encoder = recorder(Graphics);
+    encoder.dynamic_states = Some(DynamicStateFlags::all());
+    encoder.set_line_width(2.0);
+    encoder.set_depth_bias(DepthBias {
+        constant_factor: 1.0,
+        clamp: 0.0,
+        slope_factor: 1.5,
+    });
+    encoder.set_blend_constants([0.0, 0.0, 0.0, 1.0]);
+
+    assert_eq!(
+        encoder.buffer.dynamic,
+        vec![
+            DynamicStateFlags::LINE_WIDTH,
+            DynamicStateFlags::DEPTH_BIAS,
+            DynamicStateFlags::BLEND_CONSTANTS,
+        ]
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "is not enabled for bound pipeline")]
+fn disabled_dynamic_state_is_rejected() {
+    let mut encoder = recorder(Graphics);
+    encoder.dynamic_states = Some(DynamicStateFlags::VIEWPORT);
+    encoder.set_scissor(0, &[scissor()]);
+}
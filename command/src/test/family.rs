@@ -0,0 +1,67 @@
+use buffer::IndividualReset;
+use chain::PipelineStageFlags;
+
+use capability::{supported_stages, Capability, CapabilityFlags, Graphics};
+use family::{Families, Family, FamilyId};
+
+use super::{MockDevice, MockQueue};
+
+#[test]
+fn graphics_family_pool() {
+    let ref device = MockDevice::default();
+    let family = unsafe {
+        Family::from_raw(
+            FamilyId(1),
+            vec![MockQueue, MockQueue],
+            CapabilityFlags::GRAPHICS | CapabilityFlags::TRANSFER,
+        )
+    };
+    let mut family = Family::<_, Graphics>::into(family).expect("Family supports graphics");
+    assert_eq!(family.id(), FamilyId(1));
+    assert_eq!(family.capability().into_flags(), CapabilityFlags::GRAPHICS);
+    assert_eq!(family.queues().len(), 2);
+
+    let pool = family.create_pool(device, IndividualReset);
+    assert_eq!(pool.family(), FamilyId(1));
+    assert_eq!(pool.capability().into_flags(), CapabilityFlags::GRAPHICS);
+    assert_eq!(device.0.borrow().pools, 1);
+
+    pool.dispose(device);
+    assert_eq!(device.0.borrow().pools, 0);
+}
+
+#[test]
+fn transfer_family_is_not_graphics() {
+    let family =
+        unsafe { Family::from_raw(FamilyId(2), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    assert!(Family::<_, Graphics>::into(family).is_none());
+}
+
+#[test]
+fn families_lookup_by_id() {
+    let mut families = Families::new();
+    unsafe {
+        families.add_family(Family::from_raw(FamilyId(3), vec![MockQueue], Graphics));
+        families.add_family(Family::from_raw(
+            FamilyId(1),
+            vec![MockQueue, MockQueue],
+            CapabilityFlags::TRANSFER,
+        ));
+    }
+    assert_eq!(families.family(FamilyId(1)).unwrap().queue_count(), 2);
+    assert_eq!(
+        families.family_mut(FamilyId(3)).unwrap().capability(),
+        CapabilityFlags::GRAPHICS
+    );
+    assert!(families.family(FamilyId(2)).is_none());
+}
+
+#[test]
+fn transfer_family_supports_no_shader_stages() {
+    let stages = supported_stages(CapabilityFlags::TRANSFER);
+    assert!(stages.contains(PipelineStageFlags::TRANSFER | PipelineStageFlags::HOST));
+    assert!(
+        !stages.intersects(PipelineStageFlags::VERTEX_SHADER | PipelineStageFlags::COMPUTE_SHADER)
+    );
+    assert!(supported_stages(CapabilityFlags::COMPUTE).contains(PipelineStageFlags::COMPUTE_SHADER));
+}
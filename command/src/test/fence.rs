@@ -0,0 +1,59 @@
+use std::rc::Rc;
+
+use fence::FencePool;
+use semaphore::SemaphorePool;
+
+use super::MockDevice;
+
+#[test]
+fn released_fence_is_reset_and_reused() {
+    let ref device = MockDevice::default();
+    let mut pool = FencePool::new();
+
+    let fence = pool.acquire(device);
+    assert!(!fence.get());
+    assert_eq!(device.0.borrow().fences, 1);
+
+    // Pretend the submission has completed.
+    fence.set(true);
+    let raw = fence.clone();
+    unsafe {
+        pool.release(fence);
+    }
+    assert_eq!(pool.free(), 1);
+
+    let fence = pool.acquire(device);
+    assert!(Rc::ptr_eq(&fence, &raw));
+    assert!(!fence.get());
+    assert_eq!(pool.free(), 0);
+    assert_eq!(device.0.borrow().fences, 1);
+
+    unsafe {
+        pool.release(fence);
+    }
+    pool.dispose(device);
+    assert_eq!(device.0.borrow().fences, 0);
+}
+
+#[test]
+fn released_semaphore_is_reused() {
+    let ref device = MockDevice::default();
+    let mut pool = SemaphorePool::new();
+
+    let first = pool.acquire(device);
+    let second = pool.acquire(device);
+    assert_eq!(device.0.borrow().semaphores, 2);
+
+    unsafe {
+        pool.release(first);
+        pool.release(second);
+    }
+    let semaphore = pool.acquire(device);
+    assert_eq!(device.0.borrow().semaphores, 2);
+
+    unsafe {
+        pool.release(semaphore);
+    }
+    pool.dispose(device);
+    assert_eq!(device.0.borrow().semaphores, 0);
+}
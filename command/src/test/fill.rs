@@ -0,0 +1,119 @@
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use chain::{collect, sync, AccessFlags, Id, PipelineStageFlags, State};
+use memory::{usage::Data, Properties};
+use resource::{buffer, Resources, SharingMode};
+
+use capability::Transfer;
+use encoder::{fill_buffer_state, Encoder, MAX_UPDATE_SIZE};
+
+use super::{chain_node, heaps, recorder, MockDevice};
+
+/// Create buffer, pass it to `f` and destroy it even if `f` panics.
+fn with_buffer<F>(size: u64, usage: buffer::UsageFlags, f: F)
+where
+    F: FnOnce(&buffer::Buffer<(), ()>),
+{
+    let ref device = MockDevice::default();
+    let mut heaps = heaps(Properties::DEVICE_LOCAL);
+    let mut resources: Resources<(), (), ()> = Resources::new();
+    let buffer = resources
+        .create_buffer(
+            device,
+            &mut heaps,
+            buffer::CreateInfo {
+                size,
+                usage,
+                sharing: SharingMode::Exclusive,
+            },
+            1,
+            Data,
+        )
+        .unwrap();
+
+    let result = catch_unwind(AssertUnwindSafe(|| f(&buffer)));
+
+    unsafe {
+        Resources::<(), (), ()>::destroy_buffer(buffer, device, &mut heaps);
+    }
+    heaps.dispose(device);
+    if let Err(panic) = result {
+        resume_unwind(panic);
+    }
+}
+
+#[test]
+fn fill_and_update_are_recorded_and_tracked() {
+    let usage = buffer::UsageFlags::TRANSFER_DST | buffer::UsageFlags::STORAGE_BUFFER;
+    with_buffer(1024, usage, |buffer| {
+        let mut encoder = recorder(Transfer);
+        encoder.fill_buffer(buffer, 0, 16, 0);
+        encoder.update_buffer(buffer, 16, &[1, 0, 0, 0]);
+        assert_eq!(encoder.buffer.fills, vec![(0, 16, 0)]);
+        assert_eq!(encoder.buffer.updates, vec![(16, vec![1, 0, 0, 0])]);
+
+        // Node recording the commands declares this state for the buffer.
+        assert!(buffer.info().usage.contains(fill_buffer_state().usage));
+    });
+}
+
+#[test]
+fn filled_buffer_is_synchronized_with_shader_read() {
+    let mut fill = chain_node(0, vec![]);
+    fill.buffers.insert(Id(0), fill_buffer_state());
+    let mut read = chain_node(1, vec![0]);
+    read.buffers.insert(
+        Id(0),
+        State {
+            access: AccessFlags::SHADER_READ,
+            layout: (),
+            stages: PipelineStageFlags::COMPUTE_SHADER,
+            usage: buffer::UsageFlags::STORAGE_BUFFER,
+        },
+    );
+
+    let chains = collect(vec![fill, read], |_| 1);
+    let ref chain = chains.buffers[&Id(0)];
+    let (_, link) = chain.history().next().unwrap();
+    assert_eq!(link.access(), AccessFlags::TRANSFER_WRITE);
+    assert_eq!(link.stages(), PipelineStageFlags::TRANSFER);
+    assert_eq!(link.usage(), buffer::UsageFlags::TRANSFER_DST);
+
+    // Written data must be made visible to the shader.
+    let schedule = sync(&chains, || ((), ()));
+    let reading = schedule.ordered().find(|s| s.node() == 1).unwrap();
+    let ref barrier = reading.sync().acquire.buffers[&Id(0)];
+    assert_eq!(
+        barrier.states.start,
+        (
+            AccessFlags::TRANSFER_WRITE,
+            (),
+            PipelineStageFlags::TRANSFER
+        )
+    );
+    assert_eq!(
+        barrier.states.end,
+        (
+            AccessFlags::SHADER_READ,
+            (),
+            PipelineStageFlags::COMPUTE_SHADER,
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "exceeds limit of 65536 bytes")]
+fn oversized_update_is_rejected() {
+    let size = MAX_UPDATE_SIZE + 4;
+    with_buffer(size, buffer::UsageFlags::TRANSFER_DST, |buffer| {
+        recorder(Transfer).update_buffer(buffer, 0, &vec![0; size as usize]);
+    });
+}
+
+#[test]
+#[should_panic(expected = "out of buffer bounds")]
+fn fill_out_of_bounds() {
+    with_buffer(64, buffer::UsageFlags::TRANSFER_DST, |buffer| {
+        recorder(Transfer).fill_buffer(buffer, 32, 64, 0);
+    });
+}
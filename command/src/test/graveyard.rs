@@ -0,0 +1,33 @@
+use frame::FrameGen;
+use graveyard::Graveyard;
+
+#[test]
+fn resource_freed_after_its_frame_completes() {
+    let mut frames = unsafe { FrameGen::new() };
+    let mut graveyard = Graveyard::new();
+    let mut freed = Vec::new();
+
+    let first = frames.next::<()>();
+    let second = frames.next::<()>();
+    graveyard.retire(&first, "used by first");
+    graveyard.retire(&second, "used by second");
+
+    // Second frame is still in flight.
+    graveyard.collect(&first.assume_complete(), |resource| freed.push(resource));
+    assert_eq!(freed, vec!["used by first"]);
+    assert_eq!(graveyard.len(), 1);
+
+    let third = frames.next::<()>();
+    graveyard.retire(&third, "used by third");
+    graveyard.collect(&second.assume_complete(), |resource| freed.push(resource));
+    assert_eq!(freed, vec!["used by first", "used by second"]);
+
+    unsafe {
+        graveyard.dispose(|resource| freed.push(resource));
+    }
+    assert_eq!(
+        freed,
+        vec!["used by first", "used by second", "used by third"]
+    );
+    drop(third);
+}
@@ -0,0 +1,24 @@
+use encoder::LabelStack;
+
+#[test]
+fn balanced_labels() {
+    let mut labels = LabelStack::default();
+    labels.begin();
+    labels.begin();
+    assert_eq!(labels.depth(), 2);
+    labels.end();
+    labels.begin();
+    labels.end();
+    labels.end();
+    assert_eq!(labels.depth(), 0);
+}
+
+#[test]
+#[should_panic]
+#[cfg(debug_assertions)]
+fn unbalanced_end_panics() {
+    let mut labels = LabelStack::default();
+    labels.begin();
+    labels.end();
+    labels.end();
+}
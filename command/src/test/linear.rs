@@ -0,0 +1,58 @@
+use frame::{Frame, FrameGen};
+use linear::LinearAllocator;
+
+#[test]
+fn reset_reclaims_only_complete_frame() {
+    let mut frames = unsafe { FrameGen::new() };
+    let mut allocator = LinearAllocator::new((), 1024..4096, 3);
+    assert_eq!(allocator.region_size(), 1024);
+
+    let mut ranges = Vec::new();
+    let mut pending: Vec<Frame<()>> = Vec::new();
+    for _ in 0..3 {
+        let frame = frames.next();
+        allocator.bind_frame(&frame);
+        ranges.push(allocator.allocate(100, 64).unwrap());
+        pending.push(frame);
+    }
+    assert_eq!(ranges, vec![1024..1124, 2048..2148, 3072..3172]);
+
+    // Frame N-2 is complete while N-1 and N are still in flight.
+    let newest = pending.pop().unwrap();
+    let complete = pending.remove(0).assume_complete();
+    allocator.reset_frame(&complete);
+    assert_eq!(allocator.allocated(complete.index()), None);
+    assert_eq!(allocator.allocated(newest.index()), Some(100));
+
+    // Next frame reuses region of frame N-2.
+    let next: Frame<()> = frames.next();
+    allocator.bind_frame(&next);
+    assert_eq!(allocator.allocate(1000, 8), Some(1024..2024));
+    assert_eq!(allocator.allocate(100, 8), None);
+    assert_eq!(allocator.allocated(newest.index()), Some(100));
+}
+
+#[test]
+#[should_panic]
+fn all_frames_in_flight() {
+    let mut frames = unsafe { FrameGen::new() };
+    let mut allocator = LinearAllocator::new((), 0..2048, 2);
+    for _ in 0..3 {
+        let frame: Frame<()> = frames.next();
+        allocator.bind_frame(&frame);
+    }
+}
+
+#[test]
+fn overflowing_range_not_allocated() {
+    let mut frames = unsafe { FrameGen::new() };
+    let mut allocator = LinearAllocator::new((), 0..u64::max_value(), 1);
+    let frame: Frame<()> = frames.next();
+    allocator.bind_frame(&frame);
+
+    assert_eq!(allocator.allocate(u64::max_value() - 1, 1), Some(0..u64::max_value() - 1));
+    assert_eq!(allocator.allocate(1, u64::max_value()), None);
+    assert_eq!(allocator.allocate(1, 4), None);
+    assert_eq!(allocator.allocate(2, 1), None);
+    assert_eq!(allocator.allocate(1, 1), Some(u64::max_value() - 1..u64::max_value()));
+}
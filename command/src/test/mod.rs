@@ -0,0 +1,625 @@
+#![allow(dead_code)]
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ops::Range,
+    ptr::NonNull,
+    rc::Rc,
+};
+
+use chain;
+use memory;
+use resource::{self, image};
+
+use buffer::{InheritanceInfo, RawLevel, UsageFlags};
+use capability::Capability;
+use device::{CommandBuffer, CommandQueue, Device};
+use encoder::{
+    AttachmentClear, ClearColor, ClearDepthStencil, ClearRect, ClearValue, DepthBias,
+    DynamicStateFlags, Encoder, Rect, Viewport,
+};
+use error::DeviceLost;
+use family::FamilyId;
+use fence::{FenceCreateFlags, FenceCreateInfo};
+use pool::PoolCreateFlags;
+use query::{QueryControlFlags, QueryType};
+
+mod clear;
+mod dynamic;
+mod family;
+mod fence;
+mod fill;
+mod graveyard;
+mod label;
+mod linear;
+mod multiview;
+mod once;
+mod pipeline;
+mod pool;
+mod query;
+mod rerecord;
+mod scratch;
+mod secondary;
+mod stream;
+mod submit;
+mod uniform;
+mod upload;
+
+/// Heaps with single memory type of specified properties.
+fn heaps(properties: memory::Properties) -> memory::Heaps<()> {
+    unsafe {
+        memory::Heaps::new(
+            vec![(
+                properties,
+                0,
+                memory::Config {
+                    arena: None,
+                    dynamic: None,
+                },
+            )],
+            vec![1024 * 1024],
+        )
+    }
+}
+
+/// Node of the chain that accesses no resources yet.
+fn chain_node(id: usize, dependencies: Vec<usize>) -> chain::Node {
+    chain::Node {
+        id,
+        family: chain::FamilyId(0),
+        dependencies,
+        buffers: HashMap::new(),
+        buffer_ranges: HashMap::new(),
+        subsequent_buffers: HashMap::new(),
+        images: HashMap::new(),
+        subsequent_images: HashMap::new(),
+        image_aspects: HashMap::new(),
+        acceleration_structures: HashMap::new(),
+        render_pass: None,
+        upload: false,
+        metadata: None,
+    }
+}
+
+#[derive(Debug)]
+struct MockPool {
+    family: FamilyId,
+    flags: PoolCreateFlags,
+}
+
+/// Results of mock queries. Query writes the same value for all counters.
+#[derive(Debug)]
+struct MockQueries {
+    values: usize,
+    results: Vec<Option<u64>>,
+}
+
+type MockQueryPool = Rc<RefCell<MockQueries>>;
+
+/// Mock fence. Holds `true` when signaled.
+type MockFence = Rc<Cell<bool>>;
+
+#[derive(Debug, Default)]
+struct MockBuffer {
+    /// Active query and number of samples counted so far.
+    active: Option<(MockQueryPool, u32, u64)>,
+
+    /// Layouts and ranges of recorded image clears.
+    clears: Vec<(image::Layout, Vec<image::SubresourceRange>)>,
+
+    /// Offsets, sizes and values of recorded buffer fills.
+    fills: Vec<(u64, u64, u32)>,
+
+    /// Offsets and data of recorded buffer updates.
+    updates: Vec<(u64, Vec<u8>)>,
+
+    /// Layouts and regions of recorded buffer to image copies.
+    copies: Vec<(image::Layout, Vec<image::BufferImageCopy>)>,
+
+    /// Layout transitions and ranges of recorded image barriers.
+    image_barriers: Vec<(Range<image::Layout>, image::SubresourceRange)>,
+
+    /// Areas of recorded render pass instances.
+    render_passes: Vec<Rect>,
+
+    /// Render pass instance is being recorded.
+    in_render_pass: bool,
+
+    /// Dynamic state set by each recorded dynamic state command.
+    dynamic: Vec<DynamicStateFlags>,
+
+    /// Recorded viewports.
+    viewports: Vec<(u32, Vec<Viewport>)>,
+
+    /// Recorded scissors.
+    scissors: Vec<(u32, Vec<Rect>)>,
+
+    /// Usage flags recording began with.
+    usage: Option<UsageFlags>,
+
+    /// Recording is finished.
+    finished: bool,
+
+    /// Number of times buffer was reset.
+    resets: usize,
+
+    /// Level buffer was allocated with.
+    level: Option<RawLevel>,
+
+    /// Subpass secondary buffer recording began in.
+    subpass: Option<u32>,
+
+    /// Number of executed secondary buffers.
+    executed: usize,
+}
+
+impl MockBuffer {
+    /// Pretend to draw `samples` samples.
+    fn draw(&mut self, samples: u64) {
+        if let Some((_, _, ref mut counted)) = self.active {
+            *counted += samples;
+        }
+    }
+}
+
+impl CommandBuffer for MockBuffer {
+    type Submit = ();
+    type QueryPool = MockQueryPool;
+    type Buffer = ();
+    type Image = ();
+    type RenderPass = ();
+    type Framebuffer = ();
+
+    unsafe fn submit(&self) {
+        assert!(self.finished, "Recording must be finished before submission");
+    }
+
+    unsafe fn begin(&mut self, usage: UsageFlags) {
+        assert!(self.usage.is_none(), "Recording already began");
+        self.usage = Some(usage);
+    }
+
+    unsafe fn begin_secondary<'a>(
+        &mut self,
+        usage: UsageFlags,
+        inheritance: Option<InheritanceInfo<'a, ()>>,
+    ) {
+        self.begin(usage);
+        self.subpass = inheritance.map(|inheritance| inheritance.subpass);
+    }
+
+    unsafe fn finish(&mut self) {
+        assert!(self.usage.is_some(), "Recording never began");
+        self.finished = true;
+    }
+
+    unsafe fn reset(&mut self) {
+        self.usage = None;
+        self.finished = false;
+        self.resets += 1;
+    }
+
+    unsafe fn begin_query(&mut self, pool: &MockQueryPool, query: u32, _flags: QueryControlFlags) {
+        assert!(self.active.is_none(), "Query is already active");
+        self.active = Some((pool.clone(), query, 0));
+    }
+
+    unsafe fn end_query(&mut self, pool: &MockQueryPool, query: u32) {
+        let (active, index, counted) = self.active.take().expect("No active query");
+        assert!(Rc::ptr_eq(&active, pool) && index == query);
+        pool.borrow_mut().results[query as usize] = Some(counted);
+    }
+
+    unsafe fn reset_query_pool(&mut self, pool: &MockQueryPool, queries: Range<u32>) {
+        for result in &mut pool.borrow_mut().results[queries.start as usize..queries.end as usize] {
+            *result = None;
+        }
+    }
+
+    unsafe fn clear_color_image(
+        &mut self,
+        _image: &(),
+        layout: image::Layout,
+        _color: ClearColor,
+        ranges: &[image::SubresourceRange],
+    ) {
+        self.clears.push((layout, ranges.to_vec()));
+    }
+
+    unsafe fn clear_depth_stencil_image(
+        &mut self,
+        _image: &(),
+        layout: image::Layout,
+        _value: ClearDepthStencil,
+        ranges: &[image::SubresourceRange],
+    ) {
+        self.clears.push((layout, ranges.to_vec()));
+    }
+
+    unsafe fn clear_attachments(&mut self, _clears: &[AttachmentClear], _rects: &[ClearRect]) {}
+
+    unsafe fn begin_render_pass(
+        &mut self,
+        _render_pass: &(),
+        _framebuffer: &(),
+        area: Rect,
+        _clears: &[ClearValue],
+    ) {
+        assert!(!self.in_render_pass, "Render pass is already begun");
+        self.in_render_pass = true;
+        self.render_passes.push(area);
+    }
+
+    unsafe fn end_render_pass(&mut self) {
+        assert!(self.in_render_pass, "No render pass to end");
+        self.in_render_pass = false;
+    }
+
+    unsafe fn set_viewport(&mut self, first: u32, viewports: &[Viewport]) {
+        self.dynamic.push(DynamicStateFlags::VIEWPORT);
+        self.viewports.push((first, viewports.to_vec()));
+    }
+
+    unsafe fn set_scissor(&mut self, first: u32, scissors: &[Rect]) {
+        self.dynamic.push(DynamicStateFlags::SCISSOR);
+        self.scissors.push((first, scissors.to_vec()));
+    }
+
+    unsafe fn set_line_width(&mut self, _width: f32) {
+        self.dynamic.push(DynamicStateFlags::LINE_WIDTH);
+    }
+
+    unsafe fn set_depth_bias(&mut self, _bias: DepthBias) {
+        self.dynamic.push(DynamicStateFlags::DEPTH_BIAS);
+    }
+
+    unsafe fn set_blend_constants(&mut self, _constants: [f32; 4]) {
+        self.dynamic.push(DynamicStateFlags::BLEND_CONSTANTS);
+    }
+
+    unsafe fn fill_buffer(&mut self, _buffer: &(), offset: u64, size: u64, value: u32) {
+        self.fills.push((offset, size, value));
+    }
+
+    unsafe fn update_buffer(&mut self, _buffer: &(), offset: u64, data: &[u8]) {
+        self.updates.push((offset, data.to_vec()));
+    }
+
+    unsafe fn copy_buffer_to_image(
+        &mut self,
+        _buffer: &(),
+        _image: &(),
+        layout: image::Layout,
+        regions: &[image::BufferImageCopy],
+    ) {
+        self.copies.push((layout, regions.to_vec()));
+    }
+
+    unsafe fn image_barrier(
+        &mut self,
+        _image: &(),
+        _stages: Range<chain::PipelineStageFlags>,
+        _access: Range<chain::AccessFlags>,
+        layouts: Range<image::Layout>,
+        range: image::SubresourceRange,
+    ) {
+        self.image_barriers.push((layouts, range));
+    }
+
+    unsafe fn execute_commands<'a, I>(&mut self, buffers: I)
+    where
+        I: IntoIterator<Item = &'a Self>,
+    {
+        for buffer in buffers {
+            assert!(
+                buffer.finished,
+                "Secondary buffer must be finished before execution"
+            );
+            self.executed += 1;
+        }
+    }
+}
+
+/// Encoder that records straight into mock buffer.
+struct Recorder<C> {
+    buffer: MockBuffer,
+    capability: C,
+    dynamic_states: Option<DynamicStateFlags>,
+}
+
+impl<C> Encoder<C> for Recorder<C>
+where
+    C: Capability,
+{
+    type Buffer = MockBuffer;
+
+    unsafe fn buffer(&mut self) -> &mut MockBuffer {
+        &mut self.buffer
+    }
+
+    fn capability(&self) -> C {
+        self.capability
+    }
+
+    fn dynamic_states(&self) -> Option<DynamicStateFlags> {
+        self.dynamic_states
+    }
+}
+
+fn recorder<C>(capability: C) -> Recorder<C> {
+    Recorder {
+        buffer: MockBuffer::default(),
+        capability,
+        dynamic_states: None,
+    }
+}
+
+#[derive(Debug)]
+struct MockQueue;
+
+impl CommandQueue for MockQueue {
+    type Semaphore = ();
+    type Fence = MockFence;
+    type Submit = ();
+}
+
+#[derive(Default)]
+struct Inner {
+    pools: usize,
+    /// Number of command pool resets.
+    pool_resets: usize,
+    buffers: usize,
+    /// Number of allocated buffers of secondary level.
+    secondaries: usize,
+    fences: usize,
+    semaphores: usize,
+    submits: usize,
+    /// Wait stages and numbers of buffers and signals of submitted batches.
+    batches: Vec<(Vec<chain::PipelineStageFlags>, usize, usize)>,
+    waits: usize,
+    /// Host storage backing mapped memory.
+    host: Vec<u8>,
+}
+
+#[derive(Default)]
+struct MockDevice(RefCell<Inner>);
+
+impl memory::Device for MockDevice {
+    type Memory = ();
+
+    unsafe fn allocate(&self, _index: u32, _size: u64) -> Result<(), memory::AllocationError> {
+        Ok(())
+    }
+
+    unsafe fn free(&self, _memory: ()) {}
+
+    unsafe fn map(
+        &self,
+        _memory: &(),
+        range: Range<u64>,
+    ) -> Result<NonNull<u8>, memory::MappingError> {
+        let ref mut host = self.0.borrow_mut().host;
+        if (host.len() as u64) < range.end {
+            host.resize(range.end as usize, 0);
+        }
+        Ok(NonNull::new_unchecked(
+            host.as_mut_ptr().offset(range.start as isize),
+        ))
+    }
+
+    unsafe fn unmap(&self, _memory: &()) {}
+
+    unsafe fn invalidate<'a>(
+        &self,
+        _regions: impl IntoIterator<Item = (&'a (), Range<u64>)>,
+    ) -> Result<(), memory::OutOfMemoryError> {
+        unimplemented!()
+    }
+
+    unsafe fn flush<'a>(
+        &self,
+        _regions: impl IntoIterator<Item = (&'a (), Range<u64>)>,
+    ) -> Result<(), memory::OutOfMemoryError> {
+        unimplemented!()
+    }
+}
+
+impl resource::Device for MockDevice {
+    type Sampler = ();
+    type Buffer = ();
+    type UnboundBuffer = ();
+    type BufferView = ();
+    type Image = ();
+    type UnboundImage = ();
+    type ImageView = ();
+
+    fn create_buffer(
+        &self,
+        _info: resource::buffer::CreateInfo,
+    ) -> Result<(), memory::OutOfMemoryError> {
+        Ok(())
+    }
+
+    fn buffer_requirements(&self, _buffer: &()) -> resource::MemoryRequirements {
+        resource::MemoryRequirements {
+            size: 1024,
+            align: 1,
+            mask: 1,
+        }
+    }
+
+    unsafe fn bind_buffer(
+        &self,
+        _buffer: (),
+        _memory: &(),
+        _offset: u64,
+    ) -> Result<(), resource::BindError> {
+        Ok(())
+    }
+
+    unsafe fn destroy_buffer(&self, _buffer: ()) {}
+
+    fn create_image(
+        &self,
+        _info: resource::image::CreateInfo,
+    ) -> Result<(), resource::ImageCreationError> {
+        Ok(())
+    }
+
+    fn image_requirements(&self, _image: &()) -> resource::MemoryRequirements {
+        resource::MemoryRequirements {
+            size: 1024,
+            align: 1,
+            mask: 1,
+        }
+    }
+
+    unsafe fn bind_image(
+        &self,
+        _image: (),
+        _memory: &(),
+        _offset: u64,
+    ) -> Result<(), resource::BindError> {
+        Ok(())
+    }
+
+    unsafe fn destroy_image(&self, _image: ()) {}
+}
+
+impl Device for MockDevice {
+    type Semaphore = ();
+    type Fence = MockFence;
+    type Submit = ();
+    type CommandPool = MockPool;
+    type PipelineCache = Vec<u8>;
+    type QueryPool = MockQueryPool;
+    type CommandBuffer = MockBuffer;
+    type CommandQueue = MockQueue;
+
+    unsafe fn create_fence(&self, info: FenceCreateInfo) -> MockFence {
+        self.0.borrow_mut().fences += 1;
+        Rc::new(Cell::new(
+            info.flags.contains(FenceCreateFlags::CREATE_SIGNALED),
+        ))
+    }
+
+    unsafe fn destroy_fence(&self, _fence: MockFence) {
+        self.0.borrow_mut().fences -= 1;
+    }
+
+    unsafe fn reset_fence(&self, fence: &MockFence) {
+        fence.set(false);
+    }
+
+    unsafe fn create_semaphore(&self) {
+        self.0.borrow_mut().semaphores += 1;
+    }
+
+    unsafe fn destroy_semaphore(&self, _semaphore: ()) {
+        self.0.borrow_mut().semaphores -= 1;
+    }
+
+    unsafe fn create_command_pool(&self, family: FamilyId, flags: PoolCreateFlags) -> MockPool {
+        self.0.borrow_mut().pools += 1;
+        MockPool { family, flags }
+    }
+
+    unsafe fn destroy_command_pool(&self, _pool: MockPool) {
+        self.0.borrow_mut().pools -= 1;
+    }
+
+    unsafe fn allocate_command_buffer(
+        &self,
+        _pool: &mut MockPool,
+        level: RawLevel,
+    ) -> MockBuffer {
+        let mut inner = self.0.borrow_mut();
+        inner.buffers += 1;
+        if level == RawLevel::Secondary {
+            inner.secondaries += 1;
+        }
+        MockBuffer {
+            level: Some(level),
+            ..MockBuffer::default()
+        }
+    }
+
+    unsafe fn free_command_buffer(&self, _pool: &mut MockPool, buffer: MockBuffer) {
+        let mut inner = self.0.borrow_mut();
+        inner.buffers -= 1;
+        if buffer.level == Some(RawLevel::Secondary) {
+            inner.secondaries -= 1;
+        }
+    }
+
+    unsafe fn reset_command_pool(&self, _pool: &mut MockPool) {
+        self.0.borrow_mut().pool_resets += 1;
+    }
+
+    /// Mock queue completes submissions immediately.
+    unsafe fn queue_submit_batch(
+        &self,
+        _queue: &mut MockQueue,
+        waits: &[(&(), chain::PipelineStageFlags)],
+        buffers: &[&()],
+        signals: &[&()],
+        fence: Option<&MockFence>,
+    ) {
+        let mut inner = self.0.borrow_mut();
+        inner.submits += 1;
+        inner.batches.push((
+            waits.iter().map(|wait| wait.1).collect(),
+            buffers.len(),
+            signals.len(),
+        ));
+        if let Some(fence) = fence {
+            fence.set(true);
+        }
+    }
+
+    unsafe fn wait_for_fence(&self, fence: &MockFence) -> Result<(), DeviceLost> {
+        assert!(fence.get(), "Waiting for fence that is never signaled");
+        self.0.borrow_mut().waits += 1;
+        Ok(())
+    }
+
+    unsafe fn create_pipeline_cache(&self, data: &[u8]) -> Vec<u8> {
+        data.to_vec()
+    }
+
+    unsafe fn get_pipeline_cache_data(&self, cache: &Vec<u8>) -> Vec<u8> {
+        cache.clone()
+    }
+
+    unsafe fn destroy_pipeline_cache(&self, _cache: Vec<u8>) {}
+
+    unsafe fn create_query_pool(&self, ty: QueryType, count: u32) -> MockQueryPool {
+        Rc::new(RefCell::new(MockQueries {
+            values: ty.values(),
+            results: vec![None; count as usize],
+        }))
+    }
+
+    unsafe fn destroy_query_pool(&self, _pool: MockQueryPool) {}
+
+    unsafe fn get_query_pool_results(
+        &self,
+        pool: &MockQueryPool,
+        queries: Range<u32>,
+        data: &mut [u64],
+    ) -> Result<(), DeviceLost> {
+        let pool = pool.borrow();
+        let stride = pool.values + 1;
+        let results = &pool.results[queries.start as usize..queries.end as usize];
+        for (chunk, result) in data.chunks_mut(stride).zip(results) {
+            if let Some(value) = *result {
+                for counter in &mut chunk[..pool.values] {
+                    *counter = value;
+                }
+                chunk[pool.values] = 1;
+            }
+        }
+        Ok(())
+    }
+}
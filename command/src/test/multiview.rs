@@ -0,0 +1,70 @@
+use resource::{image, SharingMode};
+
+use capability::Graphics;
+use encoder::{ClearColor, Encoder, Rect};
+use multiview::{image_barrier_range, ViewMask};
+
+use super::recorder;
+
+const AREA: Rect = Rect {
+    offset: [0, 0],
+    extent: [16, 16],
+};
+
+fn layered_info(array: u32) -> image::CreateInfo {
+    image::CreateInfo {
+        kind: image::Kind::D2,
+        format: image::Format::R8G8B8A8_UNORM,
+        extent: image::Extent3D {
+            width: 16,
+            height: 16,
+            depth: 1,
+        },
+        mips: 1,
+        array,
+        samples: image::SampleCountFlags::SAMPLE_COUNT_1,
+        tiling: image::ImageTiling::Optimal,
+        usage: image::UsageFlags::COLOR_ATTACHMENT,
+        sharing: SharingMode::Exclusive,
+        flags: image::ImageCreateFlags::empty(),
+    }
+}
+
+#[test]
+fn stereo_pass_transitions_both_layers() {
+    let info = layered_info(2);
+    let mask = ViewMask(0b11);
+    let mut encoder = recorder(Graphics);
+    encoder.begin_render_pass(
+        &(),
+        &(),
+        &[&info],
+        mask,
+        AREA,
+        &[ClearColor::Float([0.0; 4]).into()],
+    );
+    encoder.end_render_pass();
+    assert_eq!(encoder.buffer.render_passes, vec![AREA]);
+    assert!(!encoder.buffer.in_render_pass);
+
+    assert!(mask.is_multiview());
+    assert_eq!(mask.views(), 2);
+    assert_eq!(mask.layers(), 0..2);
+
+    let range = image_barrier_range(&info, image::ImageAspectFlags::COLOR);
+    assert_eq!(range.layers, 0..2);
+    assert_eq!(range.levels, 0..1);
+}
+
+#[test]
+#[should_panic(expected = "requires 3 layers but image has only 2")]
+fn view_mask_exceeds_layers() {
+    recorder(Graphics).begin_render_pass(
+        &(),
+        &(),
+        &[&layered_info(2)],
+        ViewMask(0b101),
+        AREA,
+        &[],
+    );
+}
@@ -0,0 +1,98 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use buffer::UsageFlags;
+use capability::Graphics;
+use encoder::Encoder;
+use family::{Family, FamilyId};
+use once::OneShotPool;
+use query::{QueryPool, QueryType};
+
+use super::{MockDevice, MockQueue};
+
+#[test]
+fn records_submits_and_waits() {
+    let ref device = MockDevice::default();
+    let mut family = unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], Graphics) };
+    let mut pool = OneShotPool::new(device, &family);
+    let queries = QueryPool::new(device, QueryType::Occlusion, 1);
+
+    let result = pool
+        .run_once(device, &mut family, |encoder| {
+            encoder.begin_occlusion_query(&queries, 0, false);
+            unsafe {
+                let buffer = encoder.buffer();
+                assert_eq!(buffer.usage, Some(UsageFlags::ONE_TIME_SUBMIT));
+                assert!(!buffer.finished);
+                buffer.draw(16);
+            }
+            encoder.end_query(&queries, 0);
+            42
+        })
+        .unwrap();
+
+    assert_eq!(result, 42);
+    assert_eq!(
+        queries.get_results(device, 0..1).unwrap(),
+        vec![Some(vec![16])]
+    );
+    {
+        let inner = device.0.borrow();
+        assert_eq!(inner.submits, 1);
+        assert_eq!(inner.waits, 1);
+        assert_eq!(inner.buffers, 0);
+    }
+
+    queries.dispose(device);
+    pool.dispose(device);
+}
+
+#[test]
+fn pool_and_fence_are_reused() {
+    let ref device = MockDevice::default();
+    let mut family = unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], Graphics) };
+    let mut pool = OneShotPool::new(device, &family);
+
+    for _ in 0..3 {
+        pool.run_once(device, &mut family, |_| ()).unwrap();
+    }
+    {
+        let inner = device.0.borrow();
+        assert_eq!(inner.submits, 3);
+        assert_eq!(inner.pools, 1);
+        assert_eq!(inner.fences, 1);
+        assert_eq!(inner.buffers, 0);
+    }
+
+    pool.dispose(device);
+    assert_eq!(device.0.borrow().pools, 0);
+    assert_eq!(device.0.borrow().fences, 0);
+}
+
+#[test]
+fn buffer_freed_when_recording_panics() {
+    let ref device = MockDevice::default();
+    let mut family = unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], Graphics) };
+    let mut pool = OneShotPool::new(device, &family);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.run_once(device, &mut family, |_| panic!("Recording failed"))
+    }));
+    assert!(result.is_err());
+    {
+        let inner = device.0.borrow();
+        assert_eq!(inner.submits, 0);
+        assert_eq!(inner.buffers, 0);
+    }
+
+    pool.dispose(device);
+}
+
+#[test]
+#[should_panic(expected = "cannot be submitted to family")]
+fn family_mismatch() {
+    let ref device = MockDevice::default();
+    let family = unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], Graphics) };
+    let mut other = unsafe { Family::from_raw(FamilyId(1), vec![MockQueue], Graphics) };
+    let mut pool = OneShotPool::new(device, &family);
+    let _ = pool.run_once(device, &mut other, |_| ());
+}
@@ -0,0 +1,22 @@
+use pipeline::PipelineCache;
+
+use super::MockDevice;
+
+#[test]
+fn pipeline_cache_round_trip() {
+    let ref device = MockDevice::default();
+
+    let empty = PipelineCache::new(device);
+    assert!(empty.get_data(device).is_empty());
+    empty.dispose(device);
+
+    let cache = PipelineCache::from_data(device, &[1, 2, 3, 4]);
+    let data = cache.get_data(device);
+    assert_eq!(data, vec![1, 2, 3, 4]);
+    cache.dispose(device);
+
+    let restored = PipelineCache::from_data(device, &data);
+    assert_eq!(restored.get_data(device), data);
+    assert_eq!(*restored.raw(), data);
+    restored.dispose(device);
+}
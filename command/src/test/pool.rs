@@ -0,0 +1,294 @@
+use buffer::{Buffer, InitialState, OneShot, PrimaryLevel, SecondaryLevel};
+use capability::{CapabilityFlags, Graphics, Transfer};
+use family::{Family, FamilyId};
+use frame::FrameGen;
+use pool::{FramePool, OwningPool, SecondaryPool, TransferPool};
+
+use super::{MockBuffer, MockDevice, MockFence, MockQueue};
+
+#[test]
+fn empty_pool_counts() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let pool: OwningPool<_, MockBuffer, _> = OwningPool::new(family.create_pool(device, ()));
+    assert_eq!(pool.allocated(), 0);
+    assert_eq!(pool.in_use(), 0);
+    assert_eq!(pool.available(), 0);
+
+    let pool = FramePool::new(pool);
+    assert_eq!(pool.allocated(), 0);
+    assert_eq!(pool.in_use(), 0);
+    assert_eq!(pool.available(), 0);
+
+    pool.dispose(device);
+    assert_eq!(device.0.borrow().pools, 0);
+}
+
+#[test]
+fn primary_reset_before_secondary() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let mut primary: OwningPool<_, MockBuffer, _> = OwningPool::new(family.create_pool(device, ()));
+    let mut secondary: SecondaryPool<_, MockBuffer, _> =
+        SecondaryPool::new(OwningPool::new(family.create_pool(device, ())));
+
+    primary.reference_secondary(&secondary);
+    primary.reference_secondary(&secondary);
+    primary.release_secondaries();
+    secondary.advance_epoch();
+
+    // Secondary pool may be reset again when no primary references it.
+    primary.reference_secondary(&secondary);
+    primary.release_secondaries();
+
+    primary.dispose(device);
+    secondary.dispose(device);
+    assert_eq!(device.0.borrow().pools, 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Secondary pool was reset before primary pool")]
+fn secondary_reset_before_primary() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let mut primary: OwningPool<_, MockBuffer, _> = OwningPool::new(family.create_pool(device, ()));
+    let mut secondary: SecondaryPool<_, MockBuffer, _> =
+        SecondaryPool::new(OwningPool::new(family.create_pool(device, ())));
+
+    primary.reference_secondary(&secondary);
+    secondary.advance_epoch();
+    primary.release_secondaries();
+}
+
+#[test]
+#[should_panic(
+    expected = "Command buffer of family FamilyId(1) cannot be freed to pool of family FamilyId(0)"
+)]
+fn free_buffer_of_another_family() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let mut pool = family.create_pool(device, ());
+    let buffer = unsafe {
+        Buffer::from_raw(
+            MockBuffer::default(),
+            Transfer,
+            PrimaryLevel,
+            (),
+            FamilyId(1),
+        )
+    };
+    pool.free_buffers(device, vec![buffer]);
+}
+
+#[test]
+fn transfer_pool_from_generic_family() {
+    let ref device = MockDevice::default();
+    let family = unsafe {
+        Family::from_raw(
+            FamilyId(0),
+            vec![MockQueue],
+            CapabilityFlags::GRAPHICS | CapabilityFlags::TRANSFER,
+        )
+    };
+
+    let pool = family
+        .create_pool(device, ())
+        .cast_capability::<Transfer>()
+        .expect("Family supports transfer");
+    let pool: TransferPool<_> = pool;
+    pool.dispose(device);
+
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+    let pool = family.create_transfer_pool(device);
+    assert_eq!(pool.family(), FamilyId(0));
+    assert_eq!(device.0.borrow().pools, 1);
+    pool.dispose(device);
+    assert_eq!(device.0.borrow().pools, 0);
+}
+
+#[test]
+fn buffers_allocated_lazily() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let mut pool: OwningPool<_, MockBuffer, _> = OwningPool::new(family.create_pool(device, ()));
+    pool.reserve(device, PrimaryLevel, 2);
+    assert_eq!(device.0.borrow().buffers, 2);
+    assert_eq!((pool.allocated(), pool.available()), (2, 2));
+
+    // Enough buffers are available already.
+    pool.reserve(device, PrimaryLevel, 1);
+    assert_eq!(device.0.borrow().buffers, 2);
+
+    for acquired in 1..5 {
+        let buffer = pool.acquire_buffer(device, PrimaryLevel);
+        assert_eq!(buffer.family(), FamilyId(0));
+        buffer.into_raw();
+        assert_eq!(pool.in_use(), acquired);
+        assert_eq!(pool.allocated(), acquired.max(2));
+        assert_eq!(device.0.borrow().buffers, acquired.max(2));
+    }
+
+    // Only unused buffers count towards reservation.
+    pool.reserve(device, PrimaryLevel, 2);
+    assert_eq!(
+        (pool.allocated(), pool.in_use(), pool.available()),
+        (6, 4, 2)
+    );
+    assert_eq!(device.0.borrow().buffers, 6);
+
+    pool.dispose(device);
+}
+
+#[test]
+fn counts_across_reset_cycles() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let mut pool: OwningPool<_, MockBuffer, _> = OwningPool::new(family.create_pool(device, ()));
+    let counts =
+        |pool: &OwningPool<_, MockBuffer, _>| (pool.allocated(), pool.in_use(), pool.available());
+
+    // Each cycle acquires `count` buffers and resets them.
+    // Expected counts after acquisition and after reset.
+    let cycles = [
+        (2, (2, 2, 0), (2, 0, 2)),
+        (3, (3, 3, 0), (3, 0, 3)),
+        (1, (3, 1, 2), (3, 0, 3)),
+    ];
+    for (resets, &(count, acquired, reset)) in (1..).zip(&cycles) {
+        for _ in 0..count {
+            pool.acquire_buffer(device, PrimaryLevel).into_raw();
+        }
+        assert_eq!(counts(&pool), acquired);
+
+        unsafe { pool.reset(device) };
+        assert_eq!(counts(&pool), reset);
+        assert_eq!(device.0.borrow().pool_resets, resets);
+    }
+    assert_eq!(device.0.borrow().buffers, 3);
+
+    pool.dispose(device);
+}
+
+#[test]
+fn allocate_and_free_buffers() {
+    let ref device = MockDevice::default();
+    let family = unsafe {
+        Family::from_raw(
+            FamilyId(0),
+            vec![MockQueue],
+            CapabilityFlags::GRAPHICS | CapabilityFlags::TRANSFER,
+        )
+    };
+    let family = Family::<_, Graphics>::into(family).expect("Family supports graphics");
+
+    let mut pool = family.create_pool(device, ());
+    let primaries: Vec<Buffer<MockBuffer, Graphics, InitialState, PrimaryLevel>> =
+        pool.allocate_buffers(device, PrimaryLevel, 3);
+    let secondaries = pool.allocate_buffers(device, SecondaryLevel, 2);
+    assert_eq!(device.0.borrow().buffers, 5);
+    assert_eq!(device.0.borrow().secondaries, 2);
+    assert!(secondaries
+        .iter()
+        .all(|buffer| buffer.family() == FamilyId(0)));
+
+    // Buffers in any droppable state are freed.
+    let recording = primaries
+        .into_iter()
+        .map(|buffer| buffer.begin(OneShot))
+        .collect();
+    pool.free_buffers(device, recording);
+    assert_eq!(device.0.borrow().buffers, 2);
+    pool.free_buffers(device, secondaries);
+    assert_eq!(device.0.borrow().buffers, 0);
+    assert_eq!(device.0.borrow().secondaries, 0);
+
+    pool.dispose(device);
+}
+
+#[test]
+fn reset_reuses_acquired_buffers() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let mut pool: OwningPool<_, MockBuffer, _> = OwningPool::new(family.create_pool(device, ()));
+    let acquire = |pool: &mut OwningPool<_, MockBuffer, _>| -> Vec<*const MockBuffer> {
+        (0..3)
+            .map(|_| pool.acquire_buffer(device, PrimaryLevel).into_raw() as *const _)
+            .collect()
+    };
+
+    let first = acquire(&mut pool);
+    assert_eq!(pool.in_use(), 3);
+    unsafe { pool.reset(device) };
+    assert_eq!(device.0.borrow().pool_resets, 1);
+    assert_eq!((pool.in_use(), pool.available()), (0, 3));
+
+    // Same buffers are handed out again without allocation.
+    assert_eq!(acquire(&mut pool), first);
+    assert_eq!(device.0.borrow().buffers, 3);
+
+    pool.dispose(device);
+}
+
+#[test]
+fn frame_pool_reset_unbinds_frame() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let mut pool: FramePool<_, MockBuffer, _> =
+        FramePool::new(OwningPool::new(family.create_pool(device, ())));
+    let mut frames = unsafe { FrameGen::new() };
+
+    let frame = frames.next::<MockFence>();
+    pool.bind(&frame);
+    pool.reset(device, &frame.assume_complete());
+    assert_eq!(device.0.borrow().pool_resets, 1);
+
+    // Pool can be bound to the next frame after reset.
+    let frame = frames.next::<MockFence>();
+    pool.bind(&frame);
+    pool.reset(device, &frame.assume_complete());
+    pool.dispose(device);
+    assert_eq!(device.0.borrow().pools, 0);
+}
+
+#[test]
+#[should_panic(expected = "Pool must be bound to the specified frame")]
+fn frame_pool_reset_with_another_frame() {
+    let ref device = MockDevice::default();
+    let family =
+        unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], CapabilityFlags::TRANSFER) };
+    let family = Family::<_, Transfer>::into(family).expect("Family supports transfer");
+
+    let mut pool: FramePool<_, MockBuffer, _> =
+        FramePool::new(OwningPool::new(family.create_pool(device, ())));
+    let mut frames = unsafe { FrameGen::new() };
+
+    let bound = frames.next::<MockFence>();
+    let other = frames.next::<MockFence>();
+    pool.bind(&bound);
+    pool.reset(device, &other.assume_complete());
+}
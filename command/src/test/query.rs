@@ -0,0 +1,61 @@
+use capability::{CapabilityFlags, Graphics};
+use encoder::Encoder;
+use query::{PipelineStatisticFlags, QueryPool, QueryType};
+
+use super::{recorder, MockDevice};
+
+#[test]
+fn occlusion_query_around_draw() {
+    let ref device = MockDevice::default();
+    let pool = QueryPool::new(device, QueryType::Occlusion, 2);
+    let mut encoder = recorder(Graphics);
+
+    encoder.reset_query_pool(&pool, 0..2);
+    encoder.begin_occlusion_query(&pool, 1, true);
+    unsafe {
+        encoder.buffer().draw(640);
+    }
+    encoder.end_query(&pool, 1);
+
+    let results = pool.get_results(device, 0..2).unwrap();
+    assert_eq!(results, vec![None, Some(vec![640])]);
+
+    encoder.reset_query_pool(&pool, 1..2);
+    assert_eq!(pool.get_results(device, 1..2).unwrap(), vec![None]);
+
+    pool.dispose(device);
+}
+
+#[test]
+fn statistics_results_per_counter() {
+    let ref device = MockDevice::default();
+    let flags = PipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS
+        | PipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS;
+    let pool = QueryPool::new(device, QueryType::PipelineStatistics(flags), 1);
+    let mut encoder = recorder(Graphics);
+
+    encoder.begin_statistics_query(&pool, 0);
+    encoder.end_query(&pool, 0);
+
+    let results = pool.get_results(device, 0..1).unwrap();
+    assert_eq!(results, vec![Some(vec![0, 0])]);
+
+    pool.dispose(device);
+}
+
+#[test]
+#[should_panic(expected = "Pipeline statistics query recorded to pool of Occlusion queries")]
+fn query_type_mismatch() {
+    let ref device = MockDevice::default();
+    let pool = QueryPool::new(device, QueryType::Occlusion, 1);
+    recorder(Graphics).begin_statistics_query(&pool, 0);
+}
+
+#[test]
+#[should_panic(expected = "Pipeline statistics query requires one of")]
+fn statistics_on_transfer_family() {
+    let ref device = MockDevice::default();
+    let flags = PipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS;
+    let pool = QueryPool::new(device, QueryType::PipelineStatistics(flags), 1);
+    recorder(CapabilityFlags::TRANSFER).begin_statistics_query(&pool, 0);
+}
@@ -0,0 +1,89 @@
+use buffer::{
+    Buffer, IndividualReset, InheritanceInfo, OneShot, PrimaryLevel, SecondaryLevel, UsageFlags,
+};
+use capability::{Capability, CapabilityFlags, Graphics};
+use encoder::Encoder;
+use family::FamilyId;
+
+use super::MockBuffer;
+
+#[test]
+fn reset_and_begin_rerecords_same_buffer() {
+    let buffer = unsafe {
+        Buffer::from_raw(
+            MockBuffer::default(),
+            Graphics,
+            PrimaryLevel,
+            IndividualReset,
+            FamilyId(3),
+        )
+    };
+    let mut buffer = buffer.begin(OneShot);
+    buffer.begin_debug_label("frame", [1.0; 4]);
+
+    for cycle in 1..4 {
+        buffer = buffer.reset_and_begin(OneShot);
+        assert_eq!(buffer.family(), FamilyId(3));
+        assert_eq!(
+            Encoder::capability(&buffer).into_flags(),
+            CapabilityFlags::GRAPHICS
+        );
+        assert_eq!(buffer.debug_label_depth(), 0);
+
+        let raw = unsafe { Encoder::buffer(&mut buffer) };
+        assert_eq!(raw.resets, cycle);
+        assert_eq!(raw.usage, Some(UsageFlags::ONE_TIME_SUBMIT));
+        assert!(!raw.finished);
+    }
+
+    let raw = buffer.into_raw();
+    assert_eq!(raw.resets, 3);
+}
+
+#[test]
+fn reset_returns_to_initial_state() {
+    let buffer = unsafe {
+        Buffer::from_raw(
+            MockBuffer::default(),
+            Graphics,
+            PrimaryLevel,
+            IndividualReset,
+            FamilyId(0),
+        )
+    };
+    let buffer = buffer.begin(OneShot).finish().reset();
+
+    let raw = buffer.into_raw();
+    assert_eq!(raw.resets, 1);
+    assert_eq!(raw.usage, None);
+    assert!(!raw.finished);
+}
+
+#[test]
+fn reset_and_begin_rerecords_secondary_buffer() {
+    let buffer = unsafe {
+        Buffer::from_raw(
+            MockBuffer::default(),
+            Graphics,
+            SecondaryLevel,
+            IndividualReset,
+            FamilyId(0),
+        )
+    };
+    let buffer = buffer.begin(OneShot, None).finish();
+
+    let inheritance = InheritanceInfo {
+        render_pass: &(),
+        subpass: 2,
+    };
+    let raw = buffer
+        .reset_and_begin(OneShot, Some(inheritance))
+        .finish()
+        .into_raw();
+    assert_eq!(raw.resets, 1);
+    assert_eq!(raw.subpass, Some(2));
+    assert_eq!(
+        raw.usage,
+        Some(UsageFlags::ONE_TIME_SUBMIT | UsageFlags::RENDER_PASS_CONTINUE)
+    );
+}
@@ -0,0 +1,47 @@
+use std::thread;
+
+use scratch::ThreadLocalArena;
+
+#[test]
+fn threads_allocate_without_contention() {
+    let arena = ThreadLocalArena::new(0..64 * 1024, 4096);
+
+    let threads: Vec<_> = (0..2)
+        .map(|_| {
+            let mut handle = arena.handle();
+            thread::spawn(move || {
+                (0..100)
+                    .map(|_| handle.allocate(16, 16).unwrap())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let ranges: Vec<_> = threads
+        .into_iter()
+        .map(|thread| thread.join().unwrap())
+        .collect();
+
+    // Lock is taken once per thread to acquire its chunk.
+    assert_eq!(arena.chunk_acquisitions(), 2);
+    for range in &ranges[0] {
+        assert!(ranges[1]
+            .iter()
+            .all(|other| range.end <= other.start || other.end <= range.start));
+    }
+}
+
+#[test]
+fn reset_reclaims_chunks_of_all_threads() {
+    let arena = ThreadLocalArena::new(0..8192, 4096);
+    let mut first = arena.handle();
+    let mut second = arena.handle();
+    assert!(first.allocate(4000, 1).is_some());
+    assert!(second.allocate(4000, 1).is_some());
+    assert_eq!(first.allocate(4000, 1), None);
+    assert_eq!(first.allocate(5000, 1), None);
+
+    arena.reset();
+    assert!(first.allocate(4000, 1).is_some());
+    assert!(second.allocate(4000, 1).is_some());
+    assert_eq!(arena.chunk_acquisitions(), 4);
+}
@@ -0,0 +1,89 @@
+use buffer::{Buffer, InheritanceInfo, OneShot, PrimaryLevel, SecondaryLevel, UsageFlags};
+use capability::{CapabilityFlags, Graphics};
+use family::{Family, FamilyId};
+use pool::{OwningPool, SecondaryPool};
+
+use super::{MockBuffer, MockDevice, MockQueue};
+
+fn graphics() -> Family<MockQueue, Graphics> {
+    let family = unsafe {
+        Family::from_raw(
+            FamilyId(0),
+            vec![MockQueue],
+            CapabilityFlags::GRAPHICS | CapabilityFlags::TRANSFER,
+        )
+    };
+    Family::<_, Graphics>::into(family).expect("Family supports graphics")
+}
+
+#[test]
+fn execute_recorded_secondaries() {
+    let ref device = MockDevice::default();
+    let family = graphics();
+
+    let mut primaries: OwningPool<_, MockBuffer, _> =
+        OwningPool::new(family.create_pool(device, ()));
+    // Each pool hands out single buffer at a time.
+    let mut inside: SecondaryPool<_, MockBuffer, _> =
+        SecondaryPool::new(OwningPool::new(family.create_pool(device, ())));
+    let mut outside: SecondaryPool<_, MockBuffer, _> =
+        SecondaryPool::new(OwningPool::new(family.create_pool(device, ())));
+
+    let inheritance = InheritanceInfo {
+        render_pass: &(),
+        subpass: 1,
+    };
+    let first = inside
+        .acquire_buffer(device)
+        .begin(OneShot, Some(inheritance))
+        .finish();
+    let second = outside.acquire_buffer(device).begin(OneShot, None).finish();
+
+    let mut primary = primaries
+        .acquire_buffer(device, PrimaryLevel)
+        .begin(OneShot);
+    primary.execute_commands(&[&first, &second]);
+    assert_eq!(primary.finish().into_raw().executed, 2);
+
+    let first = first.into_raw();
+    assert_eq!(first.subpass, Some(1));
+    assert_eq!(
+        first.usage,
+        Some(UsageFlags::ONE_TIME_SUBMIT | UsageFlags::RENDER_PASS_CONTINUE)
+    );
+    let second = second.into_raw();
+    assert_eq!(second.subpass, None);
+    assert_eq!(second.usage, Some(UsageFlags::ONE_TIME_SUBMIT));
+
+    primaries.dispose(device);
+    inside.dispose(device);
+    outside.dispose(device);
+    assert_eq!(device.0.borrow().pools, 0);
+}
+
+#[test]
+#[should_panic(
+    expected = "Command buffer of family FamilyId(1) cannot be executed in buffer of family FamilyId(0)"
+)]
+fn execute_secondary_of_another_family() {
+    let secondary = unsafe {
+        Buffer::from_raw(
+            MockBuffer::default(),
+            Graphics,
+            SecondaryLevel,
+            (),
+            FamilyId(1),
+        )
+    };
+    let secondary = secondary.begin(OneShot, None).finish();
+    let primary = unsafe {
+        Buffer::from_raw(
+            MockBuffer::default(),
+            Graphics,
+            PrimaryLevel,
+            (),
+            FamilyId(0),
+        )
+    };
+    primary.begin(OneShot).execute_commands(&[&secondary]);
+}
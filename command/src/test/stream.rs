@@ -0,0 +1,59 @@
+use memory::{usage::Dynamic, Properties};
+use resource::{buffer, Resources, SharingMode};
+
+use frame::FrameGen;
+use stream::StreamBuffer;
+
+use super::{heaps, MockDevice};
+
+#[test]
+fn push_reclaims_complete_frames() {
+    let ref device = MockDevice::default();
+    let mut heaps = heaps(Properties::HOST_VISIBLE | Properties::HOST_COHERENT);
+    let mut resources: Resources<(), (), ()> = Resources::new();
+    let buffer = resources
+        .create_buffer(
+            device,
+            &mut heaps,
+            buffer::CreateInfo {
+                size: 1024,
+                usage: buffer::UsageFlags::VERTEX_BUFFER | buffer::UsageFlags::UNIFORM_BUFFER,
+                sharing: SharingMode::Exclusive,
+            },
+            1,
+            Dynamic,
+        )
+        .unwrap();
+    let mut stream = StreamBuffer::new(device, buffer, 256).unwrap();
+    let mut frames = unsafe { FrameGen::new() };
+
+    let first = frames.next::<()>();
+    assert_eq!(stream.push(&[1u32; 64]), Some((0, 256)));
+    assert_eq!(stream.push(&[2u8; 100]), Some((256, 100)));
+    stream.mark_frame(&first);
+
+    let second = frames.next::<()>();
+    assert_eq!(stream.push(&[3u8; 300]), Some((512, 300)));
+    stream.mark_frame(&second);
+    assert_eq!(&device.0.borrow().host[512..812], &[3u8; 300][..]);
+
+    // Data of both frames in flight must stay intact.
+    let third = frames.next::<()>();
+    assert_eq!(stream.push(&[4u8; 256]), None);
+
+    stream.reset_frame(&first.assume_complete());
+    assert_eq!(stream.push(&[4u8; 256]), Some((0, 256)));
+    stream.mark_frame(&third);
+    assert_eq!(&device.0.borrow().host[0..256], &[4u8; 256][..]);
+    assert_eq!(stream.push(&[5u8; 256]), None);
+
+    stream.reset_frame(&second.assume_complete());
+    stream.reset_frame(&third.assume_complete());
+    assert_eq!(stream.available(), 1024);
+
+    let buffer = stream.dispose(device);
+    unsafe {
+        Resources::<(), (), ()>::destroy_buffer(buffer, device, &mut heaps);
+    }
+    heaps.dispose(device);
+}
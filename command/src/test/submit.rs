@@ -0,0 +1,120 @@
+use buffer::{Buffer, IndividualReset, InitialState, MultiShot, OneShot, PrimaryLevel, UsageFlags};
+use capability::Graphics;
+use chain::PipelineStageFlags;
+use error::SubmitPending;
+use family::{Family, FamilyId};
+
+use queue::Submission;
+
+use super::{MockBuffer, MockDevice, MockFence, MockQueue};
+
+fn buffer() -> Buffer<MockBuffer, Graphics, InitialState, PrimaryLevel, IndividualReset> {
+    unsafe {
+        Buffer::from_raw(
+            MockBuffer::default(),
+            Graphics,
+            PrimaryLevel,
+            IndividualReset,
+            FamilyId(0),
+        )
+    }
+}
+
+#[test]
+fn pending_buffer_rejects_resubmit() {
+    let (_, pending) = buffer().begin(OneShot).finish().submit_once();
+    match pending.resubmit() {
+        Err(SubmitPending(usage)) => assert_eq!(usage, UsageFlags::ONE_TIME_SUBMIT),
+        Ok(_) => panic!("One-shot buffer resubmitted while pending"),
+    }
+    unsafe { pending.complete() }.into_raw();
+
+    let (submit, pending) = buffer().begin(MultiShot::default()).finish().submit();
+    assert_eq!(submit.family(), FamilyId(0));
+    match pending.resubmit() {
+        Err(SubmitPending(usage)) => assert_eq!(usage, UsageFlags::empty()),
+        Ok(_) => panic!("Multi-shot buffer resubmitted while pending"),
+    }
+
+    // Completed buffer can be submitted again.
+    let executable = unsafe { pending.complete() };
+    let (_, pending) = executable.submit();
+    unsafe { pending.complete() }.into_raw();
+}
+
+#[test]
+fn simultaneous_use_allows_resubmit() {
+    let (_, pending) = buffer()
+        .begin(MultiShot::default().simultaneous())
+        .finish()
+        .submit();
+    for _ in 0..3 {
+        let submit = pending.resubmit().unwrap();
+        assert_eq!(submit.family(), FamilyId(0));
+    }
+
+    let raw = unsafe { pending.complete() }.into_raw();
+    assert_eq!(raw.usage, Some(UsageFlags::SIMULTANEOUS_USE));
+    assert!(raw.finished);
+}
+
+#[test]
+#[should_panic(expected = "cannot be submitted to queue of family")]
+fn foreign_buffer_rejected_before_submission() {
+    let mut family = unsafe { Family::from_raw(FamilyId(1), vec![MockQueue], Graphics) };
+    let (submit, pending) = buffer().begin(OneShot).finish().submit_once();
+    unsafe { pending.complete() }.into_raw();
+
+    let submission = Submission {
+        waits: None::<((), PipelineStageFlags)>,
+        buffers: Some(submit),
+        signals: None::<()>,
+    };
+    unsafe {
+        family.submit(
+            &MockDevice::default(),
+            0,
+            Some(submission),
+            None::<MockFence>,
+        )
+    };
+}
+
+#[test]
+fn submissions_are_batched_with_fence_on_last() {
+    let ref device = MockDevice::default();
+    let mut family = unsafe { Family::from_raw(FamilyId(0), vec![MockQueue], Graphics) };
+    let (first, first_pending) = buffer().begin(OneShot).finish().submit_once();
+    let (second, second_pending) = buffer().begin(OneShot).finish().submit_once();
+
+    let fence = MockFence::default();
+    unsafe {
+        family.submit(
+            device,
+            0,
+            vec![
+                Submission {
+                    waits: Vec::new(),
+                    buffers: vec![first],
+                    signals: vec![()],
+                },
+                Submission {
+                    waits: vec![((), PipelineStageFlags::VERTEX_INPUT)],
+                    buffers: vec![second],
+                    signals: Vec::new(),
+                },
+            ],
+            Some(&fence),
+        );
+        first_pending.complete().into_raw();
+        second_pending.complete().into_raw();
+    }
+    assert!(fence.get());
+    assert_eq!(
+        device.0.borrow().batches,
+        vec![
+            (Vec::new(), 1, 1),
+            (vec![PipelineStageFlags::VERTEX_INPUT], 1, 0),
+        ]
+    );
+}
@@ -0,0 +1,96 @@
+use memory::{usage::Dynamic, Heaps, Properties};
+use resource::{buffer, Resources, SharingMode};
+
+use frame::FrameGen;
+use uniform::UniformPool;
+
+use super::{heaps, MockDevice};
+
+fn uniform_pool(
+    device: &MockDevice,
+    heaps: &mut Heaps<()>,
+    resources: &mut Resources<(), (), ()>,
+    size: u64,
+    slot_size: u64,
+    min_offset_alignment: u64,
+) -> UniformPool<(), ()> {
+    let buffer = resources
+        .create_buffer(
+            device,
+            heaps,
+            buffer::CreateInfo {
+                size,
+                usage: buffer::UsageFlags::UNIFORM_BUFFER,
+                sharing: SharingMode::Exclusive,
+            },
+            1,
+            Dynamic,
+        )
+        .unwrap();
+    UniformPool::new(device, buffer, slot_size, min_offset_alignment).unwrap()
+}
+
+fn dispose(pool: UniformPool<(), ()>, device: &MockDevice, mut heaps: Heaps<()>) {
+    let buffer = pool.dispose(device);
+    unsafe {
+        Resources::<(), (), ()>::destroy_buffer(buffer, device, &mut heaps);
+    }
+    heaps.dispose(device);
+}
+
+#[test]
+fn slots_respect_offset_alignment() {
+    let ref device = MockDevice::default();
+    let mut heaps = heaps(Properties::HOST_VISIBLE | Properties::HOST_COHERENT);
+    let mut resources = Resources::new();
+    let mut pool = uniform_pool(device, &mut heaps, &mut resources, 1024, 80, 64);
+    assert_eq!(pool.stride(), 128);
+    assert_eq!(pool.available_slots(), 8);
+
+    let first = pool.push(&[1u32; 20]).unwrap();
+    let second = pool.push(&[2u32; 20]).unwrap();
+    let (third, bytes) = pool.alloc().unwrap();
+    assert_eq!(bytes.len(), 80);
+    for byte in bytes {
+        *byte = 3;
+    }
+    assert_eq!((first, second, third), (0, 128, 256));
+    assert_eq!(&device.0.borrow().host[256..336], &[3u8; 80][..]);
+    dispose(pool, device, heaps);
+}
+
+#[test]
+fn slots_reclaimed_across_frames() {
+    let ref device = MockDevice::default();
+    let mut heaps = heaps(Properties::HOST_VISIBLE | Properties::HOST_COHERENT);
+    let mut resources = Resources::new();
+    let mut pool = uniform_pool(device, &mut heaps, &mut resources, 1024, 64, 256);
+    assert_eq!(pool.stride(), 256);
+    let mut frames = unsafe { FrameGen::new() };
+
+    let first = frames.next::<()>();
+    assert_eq!(pool.push(&1u64), Some(0));
+    assert_eq!(pool.push(&2u64), Some(256));
+    pool.mark_frame(&first);
+
+    let second = frames.next::<()>();
+    assert_eq!(pool.push(&3u64), Some(512));
+    assert_eq!(pool.push(&4u64), Some(768));
+    pool.mark_frame(&second);
+
+    // Slots of both frames in flight must stay intact.
+    let third = frames.next::<()>();
+    assert_eq!(pool.push(&5u64), None);
+
+    pool.reset_frame(&first.assume_complete());
+    assert_eq!(pool.available_slots(), 2);
+    assert_eq!(pool.push(&5u64), Some(0));
+    assert_eq!(pool.push(&6u64), Some(256));
+    pool.mark_frame(&third);
+    assert_eq!(pool.push(&7u64), None);
+
+    pool.reset_frame(&second.assume_complete());
+    pool.reset_frame(&third.assume_complete());
+    assert_eq!(pool.available_slots(), 4);
+    dispose(pool, device, heaps);
+}
@@ -0,0 +1,106 @@
+use chain::{AccessFlags, PipelineStageFlags, State};
+use memory::{usage::Data, Block, Properties};
+use resource::{image, Resources, SharingMode};
+
+use capability::Transfer;
+use upload::upload_texture;
+
+use super::{heaps, recorder, MockDevice};
+
+fn bc1_info() -> image::CreateInfo {
+    image::CreateInfo {
+        kind: image::Kind::D2,
+        format: image::Format::BC1_RGBA_UNORM_BLOCK,
+        extent: image::Extent3D {
+            width: 10,
+            height: 6,
+            depth: 1,
+        },
+        mips: 4,
+        array: 1,
+        samples: image::SampleCountFlags::SAMPLE_COUNT_1,
+        tiling: image::ImageTiling::Optimal,
+        usage: image::UsageFlags::SAMPLED | image::UsageFlags::TRANSFER_DST,
+        sharing: SharingMode::Exclusive,
+        flags: image::ImageCreateFlags::empty(),
+    }
+}
+
+#[test]
+fn texture_is_staged_copied_and_transitioned() {
+    let ref device = MockDevice::default();
+    let mut heaps = heaps(
+        Properties::DEVICE_LOCAL | Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+    );
+    let mut resources: Resources<(), (), ()> = Resources::new();
+    let image = resources
+        .create_image(device, &mut heaps, bc1_info(), 1, Data)
+        .unwrap();
+
+    // 10x6 -> 3x2 blocks, 5x3 -> 2x1 blocks, 2x1 -> 1x1 block, 1x1 -> 1x1 block.
+    let mip0: Vec<u8> = (0..48).collect();
+    let mip1 = vec![0xA1; 16];
+    let mip2 = vec![0xA2; 8];
+    let mip3 = vec![0xA3; 8];
+
+    let mut encoder = recorder(Transfer);
+    let staging = upload_texture(
+        &mut encoder,
+        device,
+        &mut heaps,
+        &mut resources,
+        &image,
+        &[&mip0[..], &mip1[..], &mip2[..], &mip3[..]],
+        image::StagingAlignment::default(),
+        State {
+            access: AccessFlags::SHADER_READ,
+            layout: image::Layout::ShaderReadOnlyOptimal,
+            stages: PipelineStageFlags::FRAGMENT_SHADER,
+            usage: image::UsageFlags::SAMPLED,
+        },
+    )
+    .unwrap();
+
+    // Mips are tightly packed with default alignment.
+    assert_eq!(staging.info().size, 80);
+    let start = staging.block().range().start as usize;
+    let expected: Vec<u8> = mip0
+        .iter()
+        .chain(&mip1)
+        .chain(&mip2)
+        .chain(&mip3)
+        .cloned()
+        .collect();
+    assert_eq!(&device.0.borrow().host[start..start + 80], &expected[..]);
+
+    let ref copies = encoder.buffer.copies;
+    assert_eq!(copies.len(), 1);
+    assert_eq!(copies[0].0, image::Layout::TransferDstOptimal);
+    let offsets: Vec<_> = copies[0].1.iter().map(|r| r.buffer_offset).collect();
+    assert_eq!(offsets, vec![0, 48, 64, 72]);
+
+    let range = image::SubresourceRange {
+        aspects: image::ImageAspectFlags::COLOR,
+        levels: 0..4,
+        layers: 0..1,
+    };
+    assert_eq!(
+        encoder.buffer.image_barriers,
+        vec![
+            (
+                image::Layout::Undefined..image::Layout::TransferDstOptimal,
+                range.clone(),
+            ),
+            (
+                image::Layout::TransferDstOptimal..image::Layout::ShaderReadOnlyOptimal,
+                range,
+            ),
+        ]
+    );
+
+    unsafe {
+        Resources::<(), (), ()>::destroy_buffer(staging, device, &mut heaps);
+        Resources::<(), (), ()>::destroy_image(image, device, &mut heaps);
+    }
+    heaps.dispose(device);
+}
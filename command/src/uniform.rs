@@ -0,0 +1,135 @@
+//! Uniform buffer slots selected by dynamic offsets.
+
+use std::{mem::size_of, ptr::copy_nonoverlapping};
+
+use memory::{Device, MappingError};
+use resource::buffer::Buffer;
+
+use frame::{CompleteFrame, Frame};
+use stream::StreamBuffer;
+
+/// Pool of equally sized uniform slots sub-allocated from single buffer.
+/// All slots are bound with one dynamic uniform buffer descriptor
+/// and selected by dynamic offset, so objects don't need a descriptor set each.
+/// Slots are allocated from the ring of `StreamBuffer`
+/// and reclaimed by `reset_frame` when frames that used them are complete.
+#[derive(Debug)]
+pub struct UniformPool<M, B> {
+    stream: StreamBuffer<M, B>,
+    slot_size: u64,
+    stride: u64,
+}
+
+impl<M, B> UniformPool<M, B>
+where
+    M: 'static,
+{
+    /// Create pool of slots for uniforms of `slot_size` bytes.
+    /// Slot offsets are aligned to `min_offset_alignment`
+    /// which must be taken from `minUniformBufferOffsetAlignment` device limit.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if buffer memory is not `HOST_COHERENT`,
+    /// buffer size is not multiple of slot stride
+    /// or offsets in the buffer don't fit in `u32`.
+    pub fn new<D>(
+        device: &D,
+        buffer: Buffer<M, B>,
+        slot_size: u64,
+        min_offset_alignment: u64,
+    ) -> Result<Self, MappingError>
+    where
+        D: Device<Memory = M>,
+    {
+        assert_ne!(slot_size, 0, "Slot size must not be zero");
+        assert!(
+            min_offset_alignment.is_power_of_two(),
+            "Offset alignment must be power of two"
+        );
+        assert!(
+            buffer.info().size <= u32::max_value() as u64,
+            "Dynamic offsets must fit in u32"
+        );
+        let stride = (slot_size + min_offset_alignment - 1) & !(min_offset_alignment - 1);
+        Ok(UniformPool {
+            stream: StreamBuffer::new(device, buffer, stride)?,
+            slot_size,
+            stride,
+        })
+    }
+
+    /// Get the buffer to bind with dynamic uniform buffer descriptor.
+    pub fn buffer(&self) -> &Buffer<M, B> {
+        self.stream.buffer()
+    }
+
+    /// Get size of the slot.
+    /// This is the range of dynamic uniform buffer descriptor.
+    pub fn slot_size(&self) -> u64 {
+        self.slot_size
+    }
+
+    /// Get distance between offsets of consecutive slots.
+    pub fn stride(&self) -> u64 {
+        self.stride
+    }
+
+    /// Get number of slots not occupied by frames in flight.
+    pub fn available_slots(&self) -> u64 {
+        self.stream.available() / self.stride
+    }
+
+    /// Allocate slot to be written by the caller.
+    /// Returns dynamic offset of the slot and its mapped bytes.
+    /// Returns `None` if all slots are used by frames in flight.
+    pub fn alloc(&mut self) -> Option<(u32, &mut [u8])> {
+        let (offset, bytes) = self.stream.reserve(self.slot_size)?;
+        Some((offset as u32, bytes))
+    }
+
+    /// Allocate slot and write `value` into it.
+    /// Returns dynamic offset of the slot.
+    /// Returns `None` if all slots are used by frames in flight.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `T` doesn't fit in the slot.
+    pub fn push<T>(&mut self, value: &T) -> Option<u32>
+    where
+        T: Copy,
+    {
+        let len = size_of::<T>();
+        assert!(
+            len as u64 <= self.slot_size,
+            "Value of {} bytes doesn't fit in slot of {} bytes",
+            len,
+            self.slot_size
+        );
+        let (offset, bytes) = self.alloc()?;
+        unsafe {
+            copy_nonoverlapping(value as *const T as *const u8, bytes.as_mut_ptr(), len);
+        }
+        Some(offset)
+    }
+
+    /// Mark all slots allocated since previous mark as used by the frame.
+    /// See `StreamBuffer::mark_frame`.
+    pub fn mark_frame<F>(&mut self, frame: &Frame<F>) {
+        self.stream.mark_frame(frame)
+    }
+
+    /// Reclaim slots used by the complete frame and all frames before it.
+    pub fn reset_frame<F>(&mut self, complete: &CompleteFrame<F>) {
+        self.stream.reset_frame(complete)
+    }
+
+    /// Unmap buffer and return it.
+    /// All frames that use slots from the pool must be complete.
+    pub fn dispose<D>(self, device: &D) -> Buffer<M, B>
+    where
+        D: Device<Memory = M>,
+    {
+        self.stream.dispose(device)
+    }
+}
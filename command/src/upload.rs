@@ -0,0 +1,116 @@
+//! Texture uploads through staging buffer.
+
+use chain::{self, AccessFlags, PipelineStageFlags, State};
+use memory::{Block, Heaps, MemoryError};
+use resource::{
+    buffer::{self, Buffer, Usage, UploadBuffer},
+    image::{self, stage_texture, Image, StagingAlignment},
+    Device, ResourceError, Resources, SharingMode,
+};
+
+use capability::{Supports, Transfer};
+use device::CommandBuffer;
+use encoder::Encoder;
+
+/// Upload pixel data of all mip levels of the image through new staging buffer.
+/// Data is laid out by `stage_texture` and written into the staging buffer.
+///
+/// Recorded commands transition all levels and layers of the image into `TransferDstOptimal` layout,
+/// copy each mip level from the staging buffer and transition the image into layout of the `next` state.
+/// Written data is made visible to the access of the `next` state.
+/// Previous content of the image is discarded.
+///
+/// Returns staging buffer that must be kept alive until the commands are complete,
+/// e.g. by passing it to `Graveyard`.
+///
+/// # Panics
+///
+/// This function panics if image wasn't created with `TRANSFER_DST` usage
+/// or if `mips` don't match the image as `stage_texture` requires.
+pub fn upload_texture<E, C, D>(
+    encoder: &mut E,
+    device: &D,
+    heaps: &mut Heaps<D::Memory>,
+    resources: &mut Resources<D::Memory, D::Buffer, D::Image>,
+    image: &Image<D::Memory, D::Image>,
+    mips: &[&[u8]],
+    alignment: StagingAlignment,
+    next: State<chain::Image>,
+) -> Result<Buffer<D::Memory, D::Buffer>, ResourceError>
+where
+    E: Encoder<C>,
+    E::Buffer: CommandBuffer<Buffer = D::Buffer, Image = D::Image>,
+    C: Supports<Transfer>,
+    D: Device,
+{
+    let info = image.info().clone();
+    let staged = stage_texture(&info, mips, alignment);
+    let mut staging = resources.create_buffer(
+        device,
+        heaps,
+        buffer::CreateInfo {
+            size: staged.data.len() as u64,
+            usage: UploadBuffer.flags(),
+            sharing: SharingMode::Exclusive,
+        },
+        1,
+        UploadBuffer.memory(),
+    )?;
+
+    if let Err(error) = write_staging(device, &mut staging, &staged.data) {
+        unsafe {
+            Resources::<_, _, D::Image>::destroy_buffer(staging, device, heaps);
+        }
+        return Err(error.into());
+    }
+
+    let range = image::SubresourceRange {
+        aspects: info.format.aspects(),
+        levels: 0..info.mips,
+        layers: 0..info.array,
+    };
+    unsafe {
+        encoder.buffer().image_barrier(
+            image.raw(),
+            PipelineStageFlags::TOP_OF_PIPE..PipelineStageFlags::TRANSFER,
+            AccessFlags::empty()..AccessFlags::TRANSFER_WRITE,
+            image::Layout::Undefined..image::Layout::TransferDstOptimal,
+            range.clone(),
+        );
+    }
+    encoder.copy_buffer_to_image(
+        &staging,
+        image,
+        image::Layout::TransferDstOptimal,
+        &staged.regions,
+    );
+    unsafe {
+        encoder.buffer().image_barrier(
+            image.raw(),
+            PipelineStageFlags::TRANSFER..next.stages,
+            AccessFlags::TRANSFER_WRITE..next.access,
+            image::Layout::TransferDstOptimal..next.layout,
+            range,
+        );
+    }
+    Ok(staging)
+}
+
+/// Write data to the beginning of the host-visible buffer.
+fn write_staging<D>(
+    device: &D,
+    buffer: &mut Buffer<D::Memory, D::Buffer>,
+    data: &[u8],
+) -> Result<(), MemoryError>
+where
+    D: Device,
+{
+    let block = buffer.block_mut();
+    let result = unsafe {
+        block
+            .map(device, 0..data.len() as u64)?
+            .write_slice(device, 0, data)
+    };
+    block.unmap(device);
+    result
+}
@@ -0,0 +1,18 @@
+extern crate rendy_command;
+
+use std::fmt::Debug;
+
+use rendy_command::{CommandBuffer, Device, Graphics, OwningPool, PrimaryLevel, SecondaryLevel};
+
+fn acquire<D, B>(
+    device: &D,
+    secondaries: &mut OwningPool<D::CommandPool, B, Graphics, SecondaryLevel>,
+) where
+    B: CommandBuffer + Debug + 'static,
+    D: Device<CommandBuffer = B, Submit = B::Submit>,
+{
+    // Buffers allocated as secondary can't be acquired as primary.
+    secondaries.acquire_buffer(device, PrimaryLevel);
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+extern crate rendy_command;
+
+use std::fmt::Debug;
+
+use rendy_command::{CommandBuffer, Device, Graphics, OneShot, SecondaryPool};
+
+fn record<D, B>(
+    device: &D,
+    outer: &mut SecondaryPool<D::CommandPool, B, Graphics>,
+    inner: &mut SecondaryPool<D::CommandPool, B, Graphics>,
+) where
+    B: CommandBuffer + Debug + 'static,
+    D: Device<CommandBuffer = B, Submit = B::Submit>,
+    D::CommandPool: Debug,
+{
+    let mut outer = outer.acquire_buffer(device).begin(OneShot, None);
+    let inner = inner.acquire_buffer(device).begin(OneShot, None).finish();
+    // Only primary buffers execute secondary ones.
+    outer.execute_commands(&[&inner]);
+}
+
+fn main() {}
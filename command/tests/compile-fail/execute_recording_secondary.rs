@@ -0,0 +1,24 @@
+extern crate rendy_command;
+
+use std::fmt::Debug;
+
+use rendy_command::{
+    CommandBuffer, Device, Graphics, OneShot, OwningPool, PrimaryLevel, SecondaryPool,
+};
+
+fn record<D, B>(
+    device: &D,
+    primaries: &mut OwningPool<D::CommandPool, B, Graphics>,
+    secondaries: &mut SecondaryPool<D::CommandPool, B, Graphics>,
+) where
+    B: CommandBuffer + Debug + 'static,
+    D: Device<CommandBuffer = B, Submit = B::Submit>,
+    D::CommandPool: Debug,
+{
+    let secondary = secondaries.acquire_buffer(device).begin(OneShot, None);
+    let mut primary = primaries.acquire_buffer(device, PrimaryLevel).begin(OneShot);
+    // Secondary buffer is still recording.
+    primary.execute_commands(&[&secondary]);
+}
+
+fn main() {}
@@ -0,0 +1,785 @@
+//! Frame graph ties chains, memory and command recording together.
+//! Passes declare resources they access and closures recording their commands.
+//! Frame graph schedules passes, places transient resources into single aliased arena,
+//! derives barriers and semaphores and submits recorded commands to the queues.
+//!
+
+use std::cmp::max;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use chain::{
+    barrier_set, collect_with, frame_fences, report, sync_with_stages, BarrierBatch, BarrierSet,
+    Buffer, Chains, CollectOptions, FamilyId, FrameFences, Id, Image, PassMetadata,
+    PipelineStageFlags, Placement, Report, Resource, Schedule, State, SubmissionId, SyncData,
+    SyncOptions, SyncPoint,
+};
+use command::{supported_stages, Device, Families, Submission, Submit};
+use memory::{allocator::Allocator, Block, MemoryError};
+use resource::{buffer, image, MemoryRequirements, ResourceError};
+
+/// Target frame is recorded into.
+/// Usually wrapper over command buffers of submissions.
+pub trait FrameTarget<D: Device> {
+    /// Record execution-only pipeline barrier with `ALL_COMMANDS` stages
+    /// before commands of the submission.
+    fn execution_barrier(&mut self, submission: SubmissionId);
+
+    /// Record pipeline barrier into command buffer of the submission.
+    fn pipeline_barrier(
+        &mut self,
+        submission: SubmissionId,
+        batch: &BarrierBatch,
+        transients: &Transients<D>,
+    );
+
+    /// Provide barriers the pass of the submission records between its own accesses
+    /// of resources, see `Pass::then_buffer` and `Pass::then_image`.
+    /// `batches[i]` must be recorded between `i`-th and next access,
+    /// it is empty if no barriers are needed there.
+    /// Called right before commands of the pass are recorded, only if there are any barriers.
+    fn internal_barriers(
+        &mut self,
+        submission: SubmissionId,
+        batches: &[BarrierBatch],
+        transients: &Transients<D>,
+    );
+
+    /// Finish recording of the submission.
+    /// Returned command buffer is submitted to the queue of the submission.
+    fn finish(&mut self, submission: SubmissionId) -> Submit<D::Submit>;
+}
+
+/// Pass of the frame graph.
+/// Declares resources it accesses and records commands into `E`.
+pub struct Pass<'a, D: Device, E> {
+    name: String,
+    family: FamilyId,
+    dependencies: Vec<usize>,
+    buffers: HashMap<Id, State<Buffer>>,
+    subsequent_buffers: HashMap<Id, Vec<State<Buffer>>>,
+    images: HashMap<Id, State<Image>>,
+    subsequent_images: HashMap<Id, Vec<State<Image>>>,
+    record: Box<dyn FnMut(&mut E, &Transients<D>, SubmissionId) + 'a>,
+}
+
+impl<'a, D, E> fmt::Debug for Pass<'a, D, E>
+where
+    D: Device,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Pass")
+            .field("name", &self.name)
+            .field("family", &self.family)
+            .field("dependencies", &self.dependencies)
+            .field("buffers", &self.buffers)
+            .field("subsequent_buffers", &self.subsequent_buffers)
+            .field("images", &self.images)
+            .field("subsequent_images", &self.subsequent_images)
+            .finish()
+    }
+}
+
+impl<'a, D, E> Pass<'a, D, E>
+where
+    D: Device,
+{
+    /// Create pass executed on queue of `family`.
+    /// `record` is called with the target, transient resources and submission of the pass
+    /// to record its commands.
+    pub fn new<F>(name: &str, family: FamilyId, record: F) -> Self
+    where
+        F: FnMut(&mut E, &Transients<D>, SubmissionId) + 'a,
+    {
+        Pass {
+            name: name.to_owned(),
+            family,
+            dependencies: Vec::new(),
+            buffers: HashMap::new(),
+            subsequent_buffers: HashMap::new(),
+            images: HashMap::new(),
+            subsequent_images: HashMap::new(),
+            record: Box::new(record),
+        }
+    }
+
+    /// Declare buffer access.
+    pub fn with_buffer(mut self, buffer: Id, state: State<Buffer>) -> Self {
+        self.buffers.insert(buffer, state);
+        self
+    }
+
+    /// Declare image access.
+    pub fn with_image(mut self, image: Id, state: State<Image>) -> Self {
+        self.images.insert(image, state);
+        self
+    }
+
+    /// Declare buffer access performed by the pass after accesses declared before.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the first access of the buffer wasn't declared with `with_buffer`.
+    pub fn then_buffer(mut self, buffer: Id, state: State<Buffer>) -> Self {
+        assert!(
+            self.buffers.contains_key(&buffer),
+            "First access of the buffer must be declared"
+        );
+        self.subsequent_buffers
+            .entry(buffer)
+            .or_insert_with(Vec::new)
+            .push(state);
+        self
+    }
+
+    /// Declare image access performed by the pass after accesses declared before,
+    /// e.g. sampling image after copying into it.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the first access of the image wasn't declared with `with_image`.
+    pub fn then_image(mut self, image: Id, state: State<Image>) -> Self {
+        assert!(
+            self.images.contains_key(&image),
+            "First access of the image must be declared"
+        );
+        self.subsequent_images
+            .entry(image)
+            .or_insert_with(Vec::new)
+            .push(state);
+        self
+    }
+
+    /// Add dependency.
+    /// Pass will be placed after pass with index `dependency`.
+    pub fn with_dependency(mut self, dependency: usize) -> Self {
+        self.dependencies.push(dependency);
+        self
+    }
+
+    /// Get name of the pass.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Builder of the frame.
+/// Resources are either transient, created by the frame graph and placed into its arena,
+/// or imported, owned by the user.
+pub struct FrameGraph<'a, D: Device, E> {
+    next_id: u64,
+    buffers: Vec<(Id, buffer::CreateInfo)>,
+    images: Vec<(Id, image::CreateInfo)>,
+    passes: Vec<Pass<'a, D, E>>,
+}
+
+impl<'a, D, E> fmt::Debug for FrameGraph<'a, D, E>
+where
+    D: Device,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("FrameGraph")
+            .field("buffers", &self.buffers)
+            .field("images", &self.images)
+            .field("passes", &self.passes)
+            .finish()
+    }
+}
+
+impl<'a, D, E> Default for FrameGraph<'a, D, E>
+where
+    D: Device,
+{
+    fn default() -> Self {
+        FrameGraph::new()
+    }
+}
+
+impl<'a, D, E> FrameGraph<'a, D, E>
+where
+    D: Device,
+{
+    /// Create empty frame graph.
+    pub fn new() -> Self {
+        FrameGraph {
+            next_id: 0,
+            buffers: Vec::new(),
+            images: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+
+    /// Create transient buffer.
+    pub fn create_buffer(&mut self, info: buffer::CreateInfo) -> Id {
+        let id = self.import();
+        self.buffers.push((id, info));
+        id
+    }
+
+    /// Create transient image.
+    pub fn create_image(&mut self, info: image::CreateInfo) -> Id {
+        let id = self.import();
+        self.images.push((id, info));
+        id
+    }
+
+    /// Get id for resource owned by the user.
+    /// Frame graph synchronizes accesses to it but doesn't allocate memory for it.
+    pub fn import(&mut self) -> Id {
+        let id = Id(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Add pass to the frame.
+    /// Returns index of the pass to be used as dependency.
+    pub fn add_pass(&mut self, pass: Pass<'a, D, E>) -> usize {
+        self.passes.push(pass);
+        self.passes.len() - 1
+    }
+
+    /// Schedule passes onto queues of `families`, create and place transient resources
+    /// and derive synchronization.
+    ///
+    /// Transient resources which lifetimes don't overlap share memory.
+    /// Pass that first accesses resource is ordered after pass that last accessed
+    /// resource previously occupying the memory.
+    /// Transient resources not accessed by any pass are not created.
+    ///
+    /// Image layouts are adjusted to features of the `device`
+    /// and stages of barriers and semaphore waits are clamped to stages supported by the families.
+    pub fn build(
+        self,
+        device: &D,
+        families: &Families<D::CommandQueue>,
+    ) -> Result<CompiledFrame<'a, D, E>, ResourceError> {
+        let nodes = self
+            .passes
+            .iter()
+            .enumerate()
+            .map(|(index, pass)| chain::Node {
+                id: index,
+                family: pass.family,
+                dependencies: pass.dependencies.clone(),
+                buffers: pass.buffers.clone(),
+                buffer_ranges: HashMap::new(),
+                subsequent_buffers: pass.subsequent_buffers.clone(),
+                images: pass.images.clone(),
+                subsequent_images: pass.subsequent_images.clone(),
+                image_aspects: HashMap::new(),
+                acceleration_structures: HashMap::new(),
+                render_pass: None,
+                upload: false,
+                metadata: Some(PassMetadata {
+                    name: pass.name.clone(),
+                    ..PassMetadata::default()
+                }),
+            })
+            .collect();
+
+        let mut chains = collect_with(
+            nodes,
+            |family| {
+                families
+                    .family(command::FamilyId(family.0))
+                    .map_or(0, |family| family.queue_count())
+            },
+            CollectOptions {
+                features: device.features(),
+                ..CollectOptions::default()
+            },
+        );
+
+        let mut transients = Transients {
+            unbound_buffers: Vec::new(),
+            unbound_images: Vec::new(),
+            buffers: HashMap::new(),
+            images: HashMap::new(),
+        };
+        let mut requirements = Vec::new();
+        for (id, info) in self.buffers {
+            if chains.buffers.contains_key(&id) {
+                let buffer = device.create_buffer(info).map_err(MemoryError::from)?;
+                requirements.push((id, device.buffer_requirements(&buffer)));
+                transients.unbound_buffers.push((id, buffer));
+            }
+        }
+        for (id, info) in self.images {
+            if chains.images.contains_key(&id) {
+                let image = device.create_image(info)?;
+                requirements.push((id, device.image_requirements(&image)));
+                transients.unbound_images.push((id, image));
+            }
+        }
+        let layout = place_transients(&mut chains, &requirements);
+
+        let mut semaphores = 0;
+        let schedule = sync_with_stages(
+            &chains,
+            SyncOptions::default(),
+            |family| {
+                families
+                    .family(command::FamilyId(family.0))
+                    .map_or(PipelineStageFlags::empty(), |family| {
+                        supported_stages(family.capability())
+                    })
+            },
+            || {
+                semaphores += 1;
+                (semaphores - 1, semaphores - 1)
+            },
+        );
+
+        Ok(CompiledFrame {
+            barriers: barrier_set(&schedule),
+            fences: frame_fences(&chains),
+            schedule,
+            semaphores,
+            layout,
+            chains,
+            transients,
+            passes: self.passes,
+        })
+    }
+}
+
+/// Placement of transient resources in the arena.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransientLayout {
+    /// Offsets of transient resources in the arena by their ids.
+    /// Resources not accessed by any pass are absent.
+    pub offsets: HashMap<Id, u64>,
+
+    /// Size of the arena.
+    pub size: u64,
+
+    /// Alignment of the arena. The largest alignment of placed resources.
+    pub align: u64,
+
+    /// Memory types suitable for all placed resources.
+    pub mask: u32,
+
+    /// Groups of resources sharing memory, sorted.
+    /// Resources that share memory with no other resource are absent.
+    pub aliasing: Vec<Vec<Id>>,
+}
+
+/// Transient resources of the frame.
+/// Resources are created by `FrameGraph::build` and bound by `CompiledFrame::allocate`.
+pub struct Transients<D: Device> {
+    unbound_buffers: Vec<(Id, D::UnboundBuffer)>,
+    unbound_images: Vec<(Id, D::UnboundImage)>,
+    buffers: HashMap<Id, D::Buffer>,
+    images: HashMap<Id, D::Image>,
+}
+
+impl<D> fmt::Debug for Transients<D>
+where
+    D: Device,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Transients")
+            .field(
+                "unbound_buffers",
+                &self
+                    .unbound_buffers
+                    .iter()
+                    .map(|&(id, _)| id)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "unbound_images",
+                &self
+                    .unbound_images
+                    .iter()
+                    .map(|&(id, _)| id)
+                    .collect::<Vec<_>>(),
+            )
+            .field("buffers", &self.buffers.keys().collect::<Vec<_>>())
+            .field("images", &self.images.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<D> Transients<D>
+where
+    D: Device,
+{
+    /// Get bound transient buffer.
+    pub fn buffer(&self, id: Id) -> Option<&D::Buffer> {
+        self.buffers.get(&id)
+    }
+
+    /// Get bound transient image.
+    pub fn image(&self, id: Id) -> Option<&D::Image> {
+        self.images.get(&id)
+    }
+}
+
+/// Frame built by `FrameGraph`.
+/// Can be recorded and submitted any number of times.
+pub struct CompiledFrame<'a, D: Device, E> {
+    passes: Vec<Pass<'a, D, E>>,
+    schedule: Schedule<SyncData<usize, usize>>,
+    barriers: BarrierSet,
+    fences: FrameFences,
+    semaphores: usize,
+    layout: TransientLayout,
+    chains: Chains,
+    transients: Transients<D>,
+}
+
+impl<'a, D, E> fmt::Debug for CompiledFrame<'a, D, E>
+where
+    D: Device,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("CompiledFrame")
+            .field("passes", &self.passes)
+            .field("schedule", &self.schedule)
+            .field("barriers", &self.barriers)
+            .field("fences", &self.fences)
+            .field("semaphores", &self.semaphores)
+            .field("layout", &self.layout)
+            .field("chains", &self.chains)
+            .field("transients", &self.transients)
+            .finish()
+    }
+}
+
+impl<'a, D, E> CompiledFrame<'a, D, E>
+where
+    D: Device,
+    E: FrameTarget<D>,
+{
+    /// Get synchronized schedule of passes.
+    /// Nodes of submissions are indices of passes.
+    pub fn schedule(&self) -> &Schedule<SyncData<usize, usize>> {
+        &self.schedule
+    }
+
+    /// Get barriers recorded around submissions.
+    pub fn barriers(&self) -> &BarrierSet {
+        &self.barriers
+    }
+
+    /// Get submissions that signal fences.
+    pub fn fences(&self) -> &FrameFences {
+        &self.fences
+    }
+
+    /// Get number of semaphores the frame uses.
+    pub fn semaphores(&self) -> usize {
+        self.semaphores
+    }
+
+    /// Get placement of transient resources.
+    pub fn layout(&self) -> &TransientLayout {
+        &self.layout
+    }
+
+    /// Get transient resources.
+    pub fn transients(&self) -> &Transients<D> {
+        &self.transients
+    }
+
+    /// Gather the complete analysis of the frame synchronization
+    /// along with groups of transient resources sharing memory.
+    pub fn report(&self) -> Report {
+        let mut report = report(&self.chains, &self.schedule);
+        report.aliasing = self.layout.aliasing.clone();
+        report
+    }
+
+    /// Allocate the arena and bind transient resources to it.
+    /// Resource is bound to the block at `block.range().start` plus its offset.
+    /// Allocator must allocate from memory type allowed by `TransientLayout::mask`.
+    ///
+    /// Returned block must be freed after the frame is disposed.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if transient resources are already bound.
+    pub fn allocate<A>(&mut self, allocator: &mut A, device: &D) -> Result<A::Block, ResourceError>
+    where
+        A: Allocator<Memory = D::Memory>,
+    {
+        assert!(
+            self.transients.buffers.is_empty() && self.transients.images.is_empty(),
+            "Transient resources are already bound"
+        );
+        let (block, _) = allocator.alloc(device, max(self.layout.size, 1), self.layout.align)?;
+        if let Err(error) = self.bind(&block, device) {
+            unsafe {
+                self.destroy_transients(device);
+            }
+            allocator.free(device, block);
+            return Err(error);
+        }
+        Ok(block)
+    }
+
+    fn bind<B>(&mut self, block: &B, device: &D) -> Result<(), ResourceError>
+    where
+        B: Block<Memory = D::Memory>,
+    {
+        let Transients {
+            ref mut unbound_buffers,
+            ref mut unbound_images,
+            ref mut buffers,
+            ref mut images,
+        } = self.transients;
+        let ref offsets = self.layout.offsets;
+        let start = block.range().start;
+        for (id, buffer) in unbound_buffers.drain(..) {
+            let buffer =
+                unsafe { device.bind_buffer(buffer, block.memory(), start + offsets[&id])? };
+            buffers.insert(id, buffer);
+        }
+        for (id, image) in unbound_images.drain(..) {
+            let image = unsafe { device.bind_image(image, block.memory(), start + offsets[&id])? };
+            images.insert(id, image);
+        }
+        Ok(())
+    }
+
+    unsafe fn destroy_transients(&mut self, device: &D) {
+        for (_, buffer) in self.transients.buffers.drain() {
+            device.destroy_buffer(buffer);
+        }
+        for (_, image) in self.transients.images.drain() {
+            device.destroy_image(image);
+        }
+    }
+
+    /// Record passes with barriers and submit them to the queues of `families` in order.
+    ///
+    /// `semaphores[i]` is the semaphore with index `i` in the schedule.
+    /// `fences[i]` is signaled by `i`-th submission of `FrameFences::submissions`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if there are fewer semaphores or fences than the frame uses
+    /// or if `families` lack queue the frame is scheduled to.
+    ///
+    /// # Safety
+    ///
+    /// Transient resources must be bound.
+    /// Semaphores and fences must be unsignaled and not used by pending submissions,
+    /// e.g. previous run of the frame must be complete.
+    pub unsafe fn run(
+        &mut self,
+        device: &D,
+        families: &mut Families<D::CommandQueue>,
+        semaphores: &[D::Semaphore],
+        fences: &[D::Fence],
+        target: &mut E,
+    ) {
+        assert!(
+            semaphores.len() >= self.semaphores,
+            "Frame uses {} semaphores",
+            self.semaphores
+        );
+        assert!(
+            fences.len() >= self.fences.len(),
+            "Frame uses {} fences",
+            self.fences.len()
+        );
+
+        let CompiledFrame {
+            ref mut passes,
+            ref schedule,
+            ref barriers,
+            fences: ref frame_fences,
+            ref transients,
+            ..
+        } = *self;
+        let fenced = frame_fences.submissions();
+
+        for submission in schedule.ordered() {
+            let sid = submission.id();
+            let sync = submission.sync();
+            let point = |placement| SyncPoint {
+                submission: sid,
+                placement,
+            };
+
+            if sync.execution_barrier {
+                target.execution_barrier(sid);
+            }
+            if let Some(batch) = barriers.get(point(Placement::Acquire)) {
+                target.pipeline_barrier(sid, batch, transients);
+            }
+            let internal = barriers.internal(sid);
+            if !internal.is_empty() {
+                target.internal_barriers(sid, internal, transients);
+            }
+            (passes[submission.node()].record)(target, transients, sid);
+            if let Some(batch) = barriers.get(point(Placement::Release)) {
+                target.pipeline_barrier(sid, batch, transients);
+            }
+
+            let fence = fenced.binary_search(&sid).ok().map(|index| &fences[index]);
+            debug_assert!(!sync.fence || fence.is_some());
+            let qid = sid.queue();
+            families
+                .family_mut(command::FamilyId(qid.family().0))
+                .expect("Frame is scheduled to family absent in `families`")
+                .submit(
+                    device,
+                    qid.index(),
+                    Some(Submission {
+                        waits: sync
+                            .wait
+                            .iter()
+                            .map(|wait| (&semaphores[*wait.semaphore()], wait.stage())),
+                        buffers: Some(target.finish(sid)),
+                        signals: sync
+                            .signal
+                            .iter()
+                            .map(|signal| &semaphores[*signal.semaphore()]),
+                    }),
+                    fence,
+                );
+        }
+    }
+
+    /// Destroy transient resources.
+    ///
+    /// # Safety
+    ///
+    /// Frame must not be used by pending submissions.
+    pub unsafe fn dispose(mut self, device: &D) {
+        self.destroy_transients(device);
+    }
+}
+
+/// Lifetime of transient resource in submission order.
+struct Lifetime {
+    orders: Range<usize>,
+    first_node: usize,
+    last_node: usize,
+}
+
+fn lifetime<R: Resource>(chains: &Chains, chain: &chain::Chain<R>) -> Lifetime {
+    let mut submissions = chain
+        .history()
+        .flat_map(|(_, link)| link.submissions())
+        .map(|&sid| {
+            let submission = chains.schedule.submission(sid).unwrap();
+            (submission.submit_order(), submission.node())
+        })
+        .collect::<Vec<_>>();
+    submissions.sort();
+    let first = submissions[0];
+    let last = submissions[submissions.len() - 1];
+    Lifetime {
+        orders: first.0..last.0 + 1,
+        first_node: first.1,
+        last_node: last.1,
+    }
+}
+
+/// Place transient resources into single arena.
+/// Resources are placed in order of their first accesses at the lowest offset
+/// not used by resources which lifetimes overlap.
+fn place_transients(
+    chains: &mut Chains,
+    transients: &[(Id, MemoryRequirements)],
+) -> TransientLayout {
+    let mut resources = transients
+        .iter()
+        .filter_map(|&(id, requirements)| {
+            let lifetime = match chains.buffers.get(&id) {
+                Some(chain) => lifetime(chains, chain),
+                None => lifetime(chains, chains.images.get(&id)?),
+            };
+            Some((id, requirements, lifetime))
+        })
+        .collect::<Vec<_>>();
+    resources.sort_by_key(|&(id, _, ref lifetime)| (lifetime.orders.start, id));
+
+    let mut placed: Vec<(Id, Range<u64>, &Lifetime)> = Vec::new();
+    let mut layout = TransientLayout {
+        offsets: HashMap::new(),
+        size: 0,
+        align: 1,
+        mask: !0,
+        aliasing: Vec::new(),
+    };
+    let mut orderings = Vec::new();
+    let mut groups: HashMap<Id, usize> = HashMap::new();
+
+    for &(id, requirements, ref lifetime) in &resources {
+        let mut alive = placed
+            .iter()
+            .filter(|&&(_, _, other)| overlaps(&other.orders, &lifetime.orders))
+            .map(|&(_, ref bytes, _)| bytes.clone())
+            .collect::<Vec<_>>();
+        alive.sort_by_key(|bytes| bytes.start);
+
+        let mut offset = 0;
+        for bytes in alive {
+            if offset + requirements.size <= bytes.start {
+                break;
+            }
+            offset = max(offset, align_up(bytes.end, requirements.align));
+        }
+        let bytes = offset..offset + requirements.size;
+
+        // Resources placed before with overlapping bytes are dead by now.
+        for &(other_id, ref other_bytes, other) in &placed {
+            if overlaps(other_bytes, &bytes) {
+                debug_assert!(other.orders.end <= lifetime.orders.start);
+                orderings.push((other.last_node, lifetime.first_node));
+                join_groups(&mut groups, other_id, id);
+            }
+        }
+
+        layout.offsets.insert(id, offset);
+        layout.size = max(layout.size, bytes.end);
+        layout.align = max(layout.align, requirements.align);
+        layout.mask &= requirements.mask;
+        placed.push((id, bytes, lifetime));
+    }
+
+    let mut aliasing: HashMap<usize, Vec<Id>> = HashMap::new();
+    for (id, group) in groups {
+        aliasing.entry(group).or_insert_with(Vec::new).push(id);
+    }
+    layout.aliasing = aliasing
+        .into_iter()
+        .map(|(_, mut group)| {
+            group.sort();
+            group
+        })
+        .collect();
+    layout.aliasing.sort();
+
+    for (before, after) in orderings {
+        chains.add_ordering(before, after);
+    }
+    layout
+}
+
+/// Put resources into the same group, merging groups they are already in.
+fn join_groups(groups: &mut HashMap<Id, usize>, left: Id, right: Id) {
+    let next = groups.len();
+    let left_group = *groups.entry(left).or_insert(next);
+    let next = groups.len();
+    let right_group = *groups.entry(right).or_insert(next);
+    for group in groups.values_mut() {
+        if *group == right_group {
+            *group = left_group;
+        }
+    }
+}
+
+fn overlaps<T: Ord>(left: &Range<T>, right: &Range<T>) -> bool {
+    left.start < right.end && right.start < left.end
+}
+
+fn align_up(value: u64, align: u64) -> u64 {
+    (value + align - 1) & !(align - 1)
+}
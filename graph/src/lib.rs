@@ -19,12 +19,18 @@
 
 extern crate rendy_chain as chain;
 extern crate rendy_command as command;
+extern crate rendy_memory as memory;
 extern crate rendy_resource as resource;
 extern crate smallvec;
 
+#[cfg(test)]
+mod test;
+
+mod frame;
 mod node;
 mod graph;
 
 
+pub use frame::{CompiledFrame, FrameGraph, FrameTarget, Pass, TransientLayout, Transients};
 pub use node::{Node, NodeDesc, NodeBuilder};
 pub use graph::Graph;
@@ -0,0 +1,300 @@
+use chain::{FamilyId, PipelineStageFlags, State};
+use command::{Family, Graphics, Transfer};
+use memory::allocator::{Allocator, DedicatedAllocator};
+use memory::{Block, Properties};
+use resource::{buffer, SharingMode};
+
+use frame::{FrameGraph, Pass};
+
+use super::*;
+
+fn buffer_state(access: AccessFlags, stages: PipelineStageFlags) -> State<chain::Buffer> {
+    State {
+        access,
+        layout: (),
+        stages,
+        usage: buffer::UsageFlags::STORAGE_BUFFER,
+    }
+}
+
+fn write() -> State<chain::Buffer> {
+    buffer_state(AccessFlags::TRANSFER_WRITE, PipelineStageFlags::TRANSFER)
+}
+
+fn read() -> State<chain::Buffer> {
+    buffer_state(AccessFlags::SHADER_READ, PipelineStageFlags::VERTEX_SHADER)
+}
+
+fn buffer_info(size: u64) -> buffer::CreateInfo {
+    buffer::CreateInfo {
+        size,
+        usage: buffer::UsageFlags::STORAGE_BUFFER,
+        sharing: SharingMode::Exclusive,
+    }
+}
+
+fn pass<'a, 'b>(name: &'static str) -> Pass<'a, MockDevice, MockTarget<'b>> {
+    pass_on(name, 0)
+}
+
+fn pass_on<'a, 'b>(name: &'static str, family: u32) -> Pass<'a, MockDevice, MockTarget<'b>> {
+    Pass::new(
+        name,
+        FamilyId(family),
+        move |target: &mut MockTarget<'b>, _, _| target.events.push(Event::Record(name)),
+    )
+}
+
+/// Single graphics family with one queue.
+fn graphics() -> Families<MockQueue> {
+    let mut families = Families::new();
+    families.add_family(unsafe {
+        Family::from_raw(command::FamilyId(0), vec![MockQueue(0)], Graphics)
+    });
+    families
+}
+
+fn batch(buffer: usize, fence: Option<usize>) -> Batch {
+    Batch {
+        queue: 0,
+        waits: Vec::new(),
+        buffers: vec![buffer],
+        signals: Vec::new(),
+        fence,
+    }
+}
+
+#[test]
+fn two_pass_frame() {
+    let ref device = MockDevice::default();
+    let mut families = graphics();
+
+    let mut graph = FrameGraph::new();
+    let data = graph.create_buffer(buffer_info(1024));
+    let upload = graph.add_pass(pass("upload").with_buffer(data, write()));
+    graph.add_pass(
+        Pass::new(
+            "draw",
+            FamilyId(0),
+            move |target: &mut MockTarget<'_>, transients, _| {
+                // Transient buffer is bound at its offset in the arena.
+                assert_eq!(transients.buffer(data), Some(&(0, 0)));
+                target.events.push(Event::Record("draw"))
+            },
+        )
+        .with_buffer(data, read())
+        .with_dependency(upload),
+    );
+
+    let mut frame = graph.build(device, &families).unwrap();
+    assert_eq!(frame.semaphores(), 0);
+    assert_eq!(frame.fences().len(), 1);
+    assert_eq!(frame.layout().size, 1024);
+    assert_eq!(frame.layout().mask, 1);
+    assert_eq!(frame.layout().offsets[&data], 0);
+    assert_eq!(frame.transients().buffer(data), None);
+
+    let mut allocator = DedicatedAllocator::new(0, Properties::DEVICE_LOCAL);
+    let block = frame.allocate(&mut allocator, device).unwrap();
+    assert_eq!(block.range(), 0..1024);
+    assert_eq!(device.resources.get(), 1);
+
+    let mut target = MockTarget::new(device, &families, &[0]);
+    unsafe {
+        frame.run(device, &mut families, &[], &[7], &mut target);
+    }
+    assert_eq!(
+        target.events,
+        vec![
+            Event::Record("upload"),
+            Event::Finish(0),
+            Event::Barrier(
+                1,
+                vec![(data, AccessFlags::TRANSFER_WRITE..AccessFlags::SHADER_READ)],
+            ),
+            Event::Record("draw"),
+            Event::Finish(1),
+        ]
+    );
+    assert_eq!(
+        *device.batches.borrow(),
+        vec![batch(0, None), batch(1, Some(7))]
+    );
+
+    unsafe {
+        frame.dispose(device);
+    }
+    assert_eq!(device.resources.get(), 0);
+    target.dispose();
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[test]
+fn pass_records_barriers_between_own_accesses() {
+    let ref device = MockDevice::default();
+    let mut families = graphics();
+
+    let mut graph = FrameGraph::new();
+    let data = graph.create_buffer(buffer_info(1024));
+    graph.add_pass(
+        pass("upload and draw")
+            .with_buffer(data, write())
+            .then_buffer(data, read()),
+    );
+
+    let mut frame = graph.build(device, &families).unwrap();
+    let mut allocator = DedicatedAllocator::new(0, Properties::DEVICE_LOCAL);
+    let block = frame.allocate(&mut allocator, device).unwrap();
+    let mut target = MockTarget::new(device, &families, &[0]);
+    unsafe {
+        frame.run(device, &mut families, &[], &[0], &mut target);
+    }
+    assert_eq!(
+        target.events,
+        vec![
+            Event::InternalBarriers(
+                0,
+                vec![vec![(
+                    data,
+                    AccessFlags::TRANSFER_WRITE..AccessFlags::SHADER_READ,
+                )]],
+            ),
+            Event::Record("upload and draw"),
+            Event::Finish(0),
+        ]
+    );
+    assert_eq!(*device.batches.borrow(), vec![batch(0, Some(0))]);
+
+    unsafe {
+        frame.dispose(device);
+    }
+    target.dispose();
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[test]
+fn transients_with_disjoint_lifetimes_alias() {
+    let ref device = MockDevice::default();
+    let mut families = graphics();
+
+    let mut graph = FrameGraph::new();
+    let first = graph.create_buffer(buffer_info(1024));
+    let bridge = graph.create_buffer(buffer_info(100));
+    let second = graph.create_buffer(buffer_info(512));
+    let unused = graph.create_buffer(buffer_info(64));
+
+    graph.add_pass(pass("a").with_buffer(first, write()));
+    graph.add_pass(
+        pass("b")
+            .with_buffer(first, read())
+            .with_buffer(bridge, write())
+            .with_dependency(0),
+    );
+    graph.add_pass(
+        pass("c")
+            .with_buffer(bridge, read())
+            .with_buffer(second, write())
+            .with_dependency(1),
+    );
+    graph.add_pass(pass("d").with_buffer(second, read()).with_dependency(2));
+
+    let mut frame = graph.build(device, &families).unwrap();
+    let layout = frame.layout().clone();
+    assert_eq!(layout.offsets[&first], 0);
+    assert_eq!(layout.offsets[&bridge], 1024);
+    assert_eq!(layout.offsets[&second], 0);
+    assert!(!layout.offsets.contains_key(&unused));
+    assert_eq!((layout.size, layout.align), (1124, 256));
+    assert_eq!(layout.aliasing, vec![vec![first, second]]);
+
+    let report = frame.report();
+    assert_eq!(report.resources.len(), 3);
+    assert_eq!(report.aliasing, layout.aliasing);
+
+    // Resources sharing memory are bound to the same range of the arena.
+    let mut allocator = DedicatedAllocator::new(0, Properties::DEVICE_LOCAL);
+    let block = frame.allocate(&mut allocator, device).unwrap();
+    assert_eq!(device.resources.get(), 3);
+    let transients = frame.transients();
+    assert_eq!(transients.buffer(first), transients.buffer(second));
+    assert_eq!(transients.buffer(bridge), Some(&(0, 1024)));
+    assert_eq!(transients.buffer(unused), None);
+
+    // Pass writing `second` waits for pass last reading `first` in the same memory.
+    let mut target = MockTarget::new(device, &families, &[0]);
+    unsafe {
+        frame.run(device, &mut families, &[], &[0], &mut target);
+    }
+    let barriers = target
+        .events
+        .iter()
+        .filter(|event| match **event {
+            Event::ExecutionBarrier(_) => true,
+            _ => false,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(barriers, vec![&Event::ExecutionBarrier(2)]);
+    assert_eq!(device.batches.borrow().len(), 4);
+
+    unsafe {
+        frame.dispose(device);
+    }
+    assert_eq!(device.resources.get(), 0);
+    target.dispose();
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[test]
+fn queues_of_different_families_synchronize_with_semaphores() {
+    let ref device = MockDevice::default();
+    let mut families = graphics();
+    families.add_family(unsafe {
+        Family::from_raw(command::FamilyId(1), vec![MockQueue(0)], Transfer)
+    });
+
+    let mut graph = FrameGraph::new();
+    let data = graph.create_buffer(buffer_info(1024));
+    let draw = graph.add_pass(pass("draw").with_buffer(data, write()));
+    // Vertex shader stage is not supported by transfer queue.
+    graph.add_pass(
+        pass_on("copy", 1)
+            .with_buffer(
+                data,
+                buffer_state(
+                    AccessFlags::TRANSFER_READ,
+                    PipelineStageFlags::TRANSFER | PipelineStageFlags::VERTEX_SHADER,
+                ),
+            )
+            .with_dependency(draw),
+    );
+
+    let mut frame = graph.build(device, &families).unwrap();
+    assert_eq!(frame.semaphores(), 1);
+    let mut allocator = DedicatedAllocator::new(0, Properties::DEVICE_LOCAL);
+    let block = frame.allocate(&mut allocator, device).unwrap();
+    let mut target = MockTarget::new(device, &families, &[0, 1]);
+    unsafe {
+        frame.run(device, &mut families, &[3], &[0], &mut target);
+    }
+
+    // Copy waits for the draw on the transfer queue at transfer stage only.
+    let batches = device.batches.borrow();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(
+        (batches[0].signals.clone(), batches[0].fence),
+        (vec![3], None)
+    );
+    assert_eq!(batches[1].waits, vec![(3, PipelineStageFlags::TRANSFER)]);
+    assert_eq!(batches[1].fence, Some(0));
+    drop(batches);
+
+    unsafe {
+        frame.dispose(device);
+    }
+    target.dispose();
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
@@ -0,0 +1,490 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ops::Range,
+    ptr::NonNull,
+};
+
+use chain::{AccessFlags, BarrierBatch, Id, PipelineStageFlags, SubmissionId};
+use command::{
+    AttachmentClear, CapabilityFlags, ClearColor, ClearDepthStencil, ClearRect, ClearValue,
+    CommandBuffer, DepthBias, DeviceLost, Families, FamilyId, FenceCreateInfo, IndividualReset,
+    InheritanceInfo, OneShot, Pool, PoolCreateFlags, PrimaryLevel, QueryControlFlags, QueryType,
+    RawLevel, Rect, Submit, UsageFlags, Viewport,
+};
+use memory::{AllocationError, MappingError, OutOfMemoryError};
+use resource::{buffer, image, BindError, ImageCreationError, MemoryRequirements};
+
+use frame::{FrameTarget, Transients};
+
+mod frame;
+
+/// Mock buffer and image are memory and offset they are bound to.
+type MockResource = (u64, u64);
+
+/// Submitted batch with semaphores, fence and command buffers identified by indices.
+#[derive(Debug, PartialEq)]
+struct Batch {
+    queue: usize,
+    waits: Vec<(usize, PipelineStageFlags)>,
+    buffers: Vec<usize>,
+    signals: Vec<usize>,
+    fence: Option<usize>,
+}
+
+#[derive(Default)]
+struct MockDevice {
+    memory: Cell<u64>,
+    /// Number of command buffers allocated so far.
+    command_buffers: Cell<usize>,
+    /// Number of bound resources not destroyed yet.
+    resources: Cell<usize>,
+    batches: RefCell<Vec<Batch>>,
+}
+
+/// Mock queue with index in its family.
+#[derive(Debug)]
+struct MockQueue(usize);
+
+impl command::CommandQueue for MockQueue {
+    type Semaphore = usize;
+    type Fence = usize;
+    type Submit = usize;
+}
+
+/// Mock command buffer with index in allocation order.
+#[derive(Debug)]
+struct MockBuffer(usize);
+
+impl CommandBuffer for MockBuffer {
+    type Submit = usize;
+    type QueryPool = ();
+    type Buffer = MockResource;
+    type Image = MockResource;
+    type RenderPass = ();
+    type Framebuffer = ();
+
+    unsafe fn submit(&self) -> usize {
+        self.0
+    }
+
+    unsafe fn begin(&mut self, _usage: UsageFlags) {}
+
+    unsafe fn begin_secondary<'a>(
+        &mut self,
+        _usage: UsageFlags,
+        _inheritance: Option<InheritanceInfo<'a, ()>>,
+    ) {
+        unimplemented!()
+    }
+
+    unsafe fn finish(&mut self) {}
+
+    unsafe fn reset(&mut self) {
+        unimplemented!()
+    }
+
+    unsafe fn begin_query(&mut self, _pool: &(), _query: u32, _flags: QueryControlFlags) {
+        unimplemented!()
+    }
+
+    unsafe fn end_query(&mut self, _pool: &(), _query: u32) {
+        unimplemented!()
+    }
+
+    unsafe fn reset_query_pool(&mut self, _pool: &(), _queries: Range<u32>) {
+        unimplemented!()
+    }
+
+    unsafe fn clear_color_image(
+        &mut self,
+        _image: &MockResource,
+        _layout: image::Layout,
+        _color: ClearColor,
+        _ranges: &[image::SubresourceRange],
+    ) {
+        unimplemented!()
+    }
+
+    unsafe fn clear_depth_stencil_image(
+        &mut self,
+        _image: &MockResource,
+        _layout: image::Layout,
+        _value: ClearDepthStencil,
+        _ranges: &[image::SubresourceRange],
+    ) {
+        unimplemented!()
+    }
+
+    unsafe fn clear_attachments(&mut self, _clears: &[AttachmentClear], _rects: &[ClearRect]) {
+        unimplemented!()
+    }
+
+    unsafe fn begin_render_pass(
+        &mut self,
+        _render_pass: &(),
+        _framebuffer: &(),
+        _area: Rect,
+        _clears: &[ClearValue],
+    ) {
+        unimplemented!()
+    }
+
+    unsafe fn end_render_pass(&mut self) {
+        unimplemented!()
+    }
+
+    unsafe fn set_viewport(&mut self, _first: u32, _viewports: &[Viewport]) {
+        unimplemented!()
+    }
+
+    unsafe fn set_scissor(&mut self, _first: u32, _scissors: &[Rect]) {
+        unimplemented!()
+    }
+
+    unsafe fn set_line_width(&mut self, _width: f32) {
+        unimplemented!()
+    }
+
+    unsafe fn set_depth_bias(&mut self, _bias: DepthBias) {
+        unimplemented!()
+    }
+
+    unsafe fn set_blend_constants(&mut self, _constants: [f32; 4]) {
+        unimplemented!()
+    }
+
+    unsafe fn fill_buffer(
+        &mut self,
+        _buffer: &MockResource,
+        _offset: u64,
+        _size: u64,
+        _value: u32,
+    ) {
+        unimplemented!()
+    }
+
+    unsafe fn update_buffer(&mut self, _buffer: &MockResource, _offset: u64, _data: &[u8]) {
+        unimplemented!()
+    }
+
+    unsafe fn copy_buffer_to_image(
+        &mut self,
+        _buffer: &MockResource,
+        _image: &MockResource,
+        _layout: image::Layout,
+        _regions: &[image::BufferImageCopy],
+    ) {
+        unimplemented!()
+    }
+
+    unsafe fn image_barrier(
+        &mut self,
+        _image: &MockResource,
+        _stages: Range<PipelineStageFlags>,
+        _access: Range<AccessFlags>,
+        _layouts: Range<image::Layout>,
+        _range: image::SubresourceRange,
+    ) {
+        unimplemented!()
+    }
+
+    unsafe fn execute_commands<'a, I>(&mut self, _buffers: I)
+    where
+        I: IntoIterator<Item = &'a Self>,
+    {
+        unimplemented!()
+    }
+}
+
+impl memory::Device for MockDevice {
+    type Memory = u64;
+
+    unsafe fn allocate(&self, _index: u32, _size: u64) -> Result<u64, AllocationError> {
+        let id = self.memory.get();
+        self.memory.set(id + 1);
+        Ok(id)
+    }
+
+    unsafe fn free(&self, _memory: u64) {}
+
+    unsafe fn map(&self, _memory: &u64, _range: Range<u64>) -> Result<NonNull<u8>, MappingError> {
+        unimplemented!()
+    }
+
+    unsafe fn unmap(&self, _memory: &u64) {}
+
+    unsafe fn invalidate<'a>(
+        &self,
+        _regions: impl IntoIterator<Item = (&'a u64, Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        Ok(())
+    }
+
+    unsafe fn flush<'a>(
+        &self,
+        _regions: impl IntoIterator<Item = (&'a u64, Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        Ok(())
+    }
+}
+
+/// Buffers are aligned to 256 bytes.
+/// Images take 4 bytes per texel and are aligned to 1024 bytes.
+impl resource::Device for MockDevice {
+    type Sampler = ();
+    type Buffer = MockResource;
+    type UnboundBuffer = MemoryRequirements;
+    type BufferView = ();
+    type Image = MockResource;
+    type UnboundImage = MemoryRequirements;
+    type ImageView = ();
+
+    fn create_buffer(
+        &self,
+        info: buffer::CreateInfo,
+    ) -> Result<MemoryRequirements, OutOfMemoryError> {
+        Ok(MemoryRequirements {
+            size: info.size,
+            align: 256,
+            mask: 1,
+        })
+    }
+
+    fn buffer_requirements(&self, buffer: &MemoryRequirements) -> MemoryRequirements {
+        *buffer
+    }
+
+    unsafe fn bind_buffer(
+        &self,
+        _buffer: MemoryRequirements,
+        memory: &u64,
+        offset: u64,
+    ) -> Result<MockResource, BindError> {
+        self.resources.set(self.resources.get() + 1);
+        Ok((*memory, offset))
+    }
+
+    unsafe fn destroy_buffer(&self, _buffer: MockResource) {
+        self.resources.set(self.resources.get() - 1);
+    }
+
+    fn create_image(
+        &self,
+        info: image::CreateInfo,
+    ) -> Result<MemoryRequirements, ImageCreationError> {
+        let extent = info.extent;
+        Ok(MemoryRequirements {
+            size: extent.width as u64 * extent.height as u64 * extent.depth as u64 * 4,
+            align: 1024,
+            mask: 1,
+        })
+    }
+
+    fn image_requirements(&self, image: &MemoryRequirements) -> MemoryRequirements {
+        *image
+    }
+
+    unsafe fn bind_image(
+        &self,
+        _image: MemoryRequirements,
+        memory: &u64,
+        offset: u64,
+    ) -> Result<MockResource, BindError> {
+        self.resources.set(self.resources.get() + 1);
+        Ok((*memory, offset))
+    }
+
+    unsafe fn destroy_image(&self, _image: MockResource) {
+        self.resources.set(self.resources.get() - 1);
+    }
+}
+
+/// Semaphores and fences are indices in slices passed to `CompiledFrame::run`.
+impl command::Device for MockDevice {
+    type Semaphore = usize;
+    type Fence = usize;
+    type Submit = usize;
+    type CommandPool = ();
+    type PipelineCache = ();
+    type QueryPool = ();
+    type CommandBuffer = MockBuffer;
+    type CommandQueue = MockQueue;
+
+    unsafe fn create_fence(&self, _info: FenceCreateInfo) -> usize {
+        unimplemented!()
+    }
+
+    unsafe fn destroy_fence(&self, _fence: usize) {
+        unimplemented!()
+    }
+
+    unsafe fn create_semaphore(&self) -> usize {
+        unimplemented!()
+    }
+
+    unsafe fn destroy_semaphore(&self, _semaphore: usize) {
+        unimplemented!()
+    }
+
+    unsafe fn create_command_pool(&self, _family: FamilyId, _flags: PoolCreateFlags) {}
+
+    unsafe fn destroy_command_pool(&self, _pool: ()) {}
+
+    unsafe fn allocate_command_buffer(&self, _pool: &mut (), _level: RawLevel) -> MockBuffer {
+        let index = self.command_buffers.get();
+        self.command_buffers.set(index + 1);
+        MockBuffer(index)
+    }
+
+    unsafe fn free_command_buffer(&self, _pool: &mut (), _buffer: MockBuffer) {}
+
+    unsafe fn reset_command_pool(&self, _pool: &mut ()) {
+        unimplemented!()
+    }
+
+    unsafe fn queue_submit_batch(
+        &self,
+        queue: &mut MockQueue,
+        waits: &[(&usize, PipelineStageFlags)],
+        buffers: &[&usize],
+        signals: &[&usize],
+        fence: Option<&usize>,
+    ) {
+        self.batches.borrow_mut().push(Batch {
+            queue: queue.0,
+            waits: waits
+                .iter()
+                .map(|&(&semaphore, stages)| (semaphore, stages))
+                .collect(),
+            buffers: buffers.iter().map(|&&buffer| buffer).collect(),
+            signals: signals.iter().map(|&&signal| signal).collect(),
+            fence: fence.cloned(),
+        });
+    }
+
+    unsafe fn wait_for_fence(&self, _fence: &usize) -> Result<(), DeviceLost> {
+        unimplemented!()
+    }
+
+    unsafe fn create_pipeline_cache(&self, _data: &[u8]) {
+        unimplemented!()
+    }
+
+    unsafe fn get_pipeline_cache_data(&self, _cache: &()) -> Vec<u8> {
+        unimplemented!()
+    }
+
+    unsafe fn destroy_pipeline_cache(&self, _cache: ()) {
+        unimplemented!()
+    }
+
+    unsafe fn create_query_pool(&self, _ty: QueryType, _count: u32) {
+        unimplemented!()
+    }
+
+    unsafe fn destroy_query_pool(&self, _pool: ()) {
+        unimplemented!()
+    }
+
+    unsafe fn get_query_pool_results(
+        &self,
+        _pool: &(),
+        _queries: Range<u32>,
+        _data: &mut [u64],
+    ) -> Result<(), DeviceLost> {
+        unimplemented!()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    ExecutionBarrier(usize),
+    /// Buffer barriers with access transitions.
+    Barrier(usize, Vec<(Id, Range<AccessFlags>)>),
+    /// Buffer barriers recorded between accesses of the pass.
+    InternalBarriers(usize, Vec<Vec<(Id, Range<AccessFlags>)>>),
+    Record(&'static str),
+    Finish(usize),
+}
+
+fn transitions(batch: &BarrierBatch) -> Vec<(Id, Range<AccessFlags>)> {
+    batch
+        .buffers
+        .iter()
+        .map(|&(id, ref barrier)| (id, barrier.states.start.0..barrier.states.end.0))
+        .collect()
+}
+
+/// Target recording events and allocating one-shot command buffer per submission
+/// from pool of the submission's family.
+struct MockTarget<'a> {
+    device: &'a MockDevice,
+    pools: HashMap<u32, Pool<(), CapabilityFlags, IndividualReset>>,
+    events: Vec<Event>,
+}
+
+impl<'a> MockTarget<'a> {
+    fn new(device: &'a MockDevice, families: &Families<MockQueue>, ids: &[u32]) -> Self {
+        MockTarget {
+            device,
+            pools: ids
+                .iter()
+                .map(|&id| {
+                    let family = families.family(FamilyId(id)).unwrap();
+                    (id, family.create_pool(device, IndividualReset))
+                })
+                .collect(),
+            events: Vec::new(),
+        }
+    }
+
+    fn dispose(self) {
+        for (_, pool) in self.pools {
+            pool.dispose(self.device);
+        }
+    }
+}
+
+impl<'a> FrameTarget<MockDevice> for MockTarget<'a> {
+    fn execution_barrier(&mut self, submission: SubmissionId) {
+        self.events
+            .push(Event::ExecutionBarrier(submission.index()));
+    }
+
+    fn pipeline_barrier(
+        &mut self,
+        submission: SubmissionId,
+        batch: &BarrierBatch,
+        _transients: &Transients<MockDevice>,
+    ) {
+        self.events
+            .push(Event::Barrier(submission.index(), transitions(batch)));
+    }
+
+    fn internal_barriers(
+        &mut self,
+        submission: SubmissionId,
+        batches: &[BarrierBatch],
+        _transients: &Transients<MockDevice>,
+    ) {
+        self.events.push(Event::InternalBarriers(
+            submission.index(),
+            batches.iter().map(transitions).collect(),
+        ));
+    }
+
+    fn finish(&mut self, submission: SubmissionId) -> Submit<usize> {
+        self.events.push(Event::Finish(submission.index()));
+        let pool = self.pools.get_mut(&submission.family().0).unwrap();
+        let buffer = pool
+            .allocate_buffers(self.device, PrimaryLevel, 1)
+            .pop()
+            .unwrap();
+        let (submit, pending) = buffer.begin(OneShot).finish().submit_once();
+        // Mock queue executes nothing, the buffer is complete right away.
+        let complete = unsafe { pending.complete() };
+        pool.free_buffers(self.device, vec![complete]);
+        submit
+    }
+}
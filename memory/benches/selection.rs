@@ -0,0 +1,119 @@
+//! Overhead of memory type selection in `Heaps::allocate`.
+//!
+//! Compares allocation with explicit memory type to allocation that selects memory type
+//! with cold and warm cache of suitable types.
+
+#[macro_use]
+extern crate criterion;
+extern crate rendy_memory;
+
+use std::{ops::Range, ptr::NonNull};
+
+use criterion::{BatchSize, Criterion};
+use rendy_memory::{
+    usage::UsageValue, AllocationError, Config, Device, Heaps, MappingError, OutOfMemoryError,
+    Properties,
+};
+
+/// Device that allocates nothing.
+struct NullDevice;
+
+impl Device for NullDevice {
+    type Memory = ();
+
+    unsafe fn allocate(&self, _index: u32, _size: u64) -> Result<(), AllocationError> {
+        Ok(())
+    }
+
+    unsafe fn free(&self, _memory: ()) {}
+
+    unsafe fn map(&self, _memory: &(), _range: Range<u64>) -> Result<NonNull<u8>, MappingError> {
+        unimplemented!()
+    }
+
+    unsafe fn unmap(&self, _memory: &()) {}
+
+    unsafe fn invalidate<'a>(
+        &self,
+        _regions: impl IntoIterator<Item = (&'a (), Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        unimplemented!()
+    }
+
+    unsafe fn flush<'a>(
+        &self,
+        _regions: impl IntoIterator<Item = (&'a (), Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        unimplemented!()
+    }
+}
+
+/// Heaps with memory types typical for discrete GPU.
+fn heaps() -> Heaps<()> {
+    let config = Config {
+        arena: None,
+        dynamic: None,
+    };
+    let types = vec![
+        (Properties::DEVICE_LOCAL, 0, config),
+        (Properties::DEVICE_LOCAL, 0, config),
+        (
+            Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+            1,
+            config,
+        ),
+        (
+            Properties::HOST_VISIBLE | Properties::HOST_COHERENT | Properties::HOST_CACHED,
+            1,
+            config,
+        ),
+        (
+            Properties::DEVICE_LOCAL | Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+            2,
+            config,
+        ),
+    ];
+    unsafe { Heaps::new(types, vec![1 << 32, 1 << 32, 1 << 28]) }
+}
+
+const MASK: u32 = 0b11111;
+const SIZE: u64 = 1 << 20;
+
+fn selection(c: &mut Criterion) {
+    c.bench_function("allocate from type", |b| {
+        let mut heaps = heaps();
+        b.iter(|| {
+            let block = heaps
+                .allocate_from_type(&NullDevice, 3, MASK, UsageValue::Download, SIZE, 1)
+                .unwrap();
+            heaps.free(&NullDevice, block);
+        })
+    });
+
+    c.bench_function("allocate with cold selection", |b| {
+        b.iter_batched(
+            heaps,
+            |mut heaps| {
+                let block = heaps
+                    .allocate(&NullDevice, MASK, UsageValue::Download, SIZE, 1)
+                    .unwrap();
+                heaps.free(&NullDevice, block);
+                heaps
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    c.bench_function("allocate with cached selection", |b| {
+        let mut heaps = heaps();
+        b.iter(|| {
+            let block = heaps
+                .allocate(&NullDevice, MASK, UsageValue::Download, SIZE, 1)
+                .unwrap();
+            heaps.free(&NullDevice, block);
+        })
+    });
+}
+
+criterion_group!(benches, selection);
+criterion_main!(benches);
@@ -2,8 +2,10 @@ use std::{collections::VecDeque, fmt::Debug, ops::Range, ptr::NonNull};
 
 use relevant::Relevant;
 
-use allocator::Allocator;
+use allocator::{Allocator, AllocatorStats};
 use block::Block;
+#[cfg(feature = "tags")]
+use block::TagSlot;
 use device::Device;
 use error::*;
 use mapping::*;
@@ -19,6 +21,8 @@ pub struct ArenaBlock<T> {
     arena_index: u64,
     ptr: NonNull<u8>,
     range: Range<u64>,
+    #[cfg(feature = "tags")]
+    tag: TagSlot,
     #[derivative(Debug = "ignore")]
     relevant: Relevant,
 }
@@ -27,6 +31,8 @@ unsafe impl<T: Send> Send for ArenaBlock<T> {}
 unsafe impl<T: Sync> Sync for ArenaBlock<T> {}
 
 impl<T> ArenaBlock<T> {
+    tag_accessors!();
+
     fn shared_memory(&self) -> &Memory<T> {
         // Memory won't be freed until last block created from it deallocated.
         unsafe { &*self.memory }
@@ -85,20 +91,28 @@ impl<T: 'static> Block for ArenaBlock<T> {
     }
 }
 
-/// Config for `DynamicAllocator`.
+/// Config for `ArenaAllocator`.
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ArenaConfig {
     /// Size of the arena chunk.
     /// Keep it big.
     pub arena_size: u64,
+
+    /// Maximum number of exhausted arenas retained for reuse
+    /// after all their blocks are freed.
+    /// Arenas beyond this number are freed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_retained: usize,
 }
 
 /// Linear allocator that return memory from chunk sequentially.
 /// It keeps only number of bytes allocated from each chunk.
 /// Once chunk is exhausted it is placed into list.
 /// When all blocks allocated from head of that list are freed,
-/// head is freed as well.
+/// head is retained for reuse or freed if enough arenas are retained.
+/// When all blocks allocated from the current chunk are freed,
+/// it is reset to be allocated from the beginning.
 ///
 /// This allocator suites best short-lived types of allocations.
 /// Allocation strategy requires minimal overhead and implementation is fast.
@@ -108,8 +122,12 @@ pub struct ArenaAllocator<T> {
     memory_type: u32,
     memory_properties: Properties,
     arena_size: u64,
+    max_retained: usize,
     offset: u64,
     arenas: VecDeque<Arena<T>>,
+    // Arenas are retired from the head only, in order they were created,
+    // so reuse doesn't depend on order blocks are freed in.
+    retained: Vec<Arena<T>>,
 }
 
 #[derive(Derivative)]
@@ -149,17 +167,48 @@ impl<T: 'static> ArenaAllocator<T> {
             memory_type,
             memory_properties,
             arena_size: config.arena_size,
+            max_retained: config.max_retained,
             offset: 0,
             arenas: VecDeque::new(),
+            retained: Vec::new(),
         }
     }
 
+    /// Get memory counters of the allocator.
+    /// Arenas retained for reuse count as allocated memory.
+    pub fn stats(&self) -> AllocatorStats {
+        let count = (self.arenas.len() + self.retained.len()) as u64;
+        AllocatorStats {
+            allocated: count * self.arena_size,
+            used: self
+                .arenas
+                .iter()
+                .map(|arena| arena.used - arena.free)
+                .sum(),
+            memory_objects: count,
+        }
+    }
+
+    /// Free all exhausted arenas retained for reuse.
+    /// Returns amount of memory returned to the device.
+    pub fn clear<D>(&mut self, device: &D) -> u64
+    where
+        D: Device<Memory = T>,
+    {
+        let mut freed = 0;
+        for arena in self.retained.drain(..) {
+            freed += free_arena(device, arena);
+        }
+        freed
+    }
+
     /// Perform full cleanup of the memory allocated.
     pub fn dispose<D>(mut self, device: &D)
     where
         D: Device<Memory = T>,
     {
         self.cleanup(device, 0);
+        self.clear(device);
         assert!(
             self.arenas.is_empty(),
             "Arenas are not empty during allocator disposal. Arenas: {:#?}",
@@ -177,19 +226,33 @@ impl<T: 'static> ArenaAllocator<T> {
                 break;
             }
 
-            let arena = self.arenas.pop_front().unwrap();
+            let mut arena = self.arenas.pop_front().unwrap();
+            self.offset += 1;
 
-            unsafe {
-                device.unmap(arena.memory.raw());
-
-                freed += arena.memory.size();
-                device.free(arena.memory.into_raw());
+            if self.retained.len() < self.max_retained {
+                arena.used = 0;
+                arena.free = 0;
+                self.retained.push(arena);
+            } else {
+                freed += free_arena(device, arena);
             }
         }
         freed
     }
 }
 
+fn free_arena<T, D>(device: &D, arena: Arena<T>) -> u64
+where
+    D: Device<Memory = T>,
+{
+    unsafe {
+        device.unmap(arena.memory.raw());
+        let size = arena.memory.size();
+        device.free(arena.memory.into_raw());
+        size
+    }
+}
+
 impl<T: 'static> Allocator for ArenaAllocator<T> {
     type Memory = T;
 
@@ -226,6 +289,8 @@ impl<T: 'static> Allocator for ArenaAllocator<T> {
                         memory: &*arena.memory,
                         ptr,
                         range,
+                        #[cfg(feature = "tags")]
+                        tag: TagSlot::default(),
                         relevant: Relevant,
                     },
                     0,
@@ -233,30 +298,39 @@ impl<T: 'static> Allocator for ArenaAllocator<T> {
             }
         }
 
-        let (memory, ptr) = unsafe {
-            let raw = device.allocate(self.memory_type, self.arena_size)?;
-
-            let ptr = match device.map(&raw, 0..self.arena_size) {
-                Ok(ptr) => ptr,
-                Err(error) => {
-                    device.free(raw);
-                    return Err(error.into());
-                }
-            };
-
-            let memory = Memory::from_raw(raw, self.arena_size, self.memory_properties);
-
-            (memory, ptr)
-        };
-
-        let arena = Arena {
-            used: size,
-            free: 0,
-            ptr,
-            memory: Box::new(memory),
+        let (arena, allocated) = match self.retained.pop() {
+            Some(mut arena) => {
+                arena.used = size;
+                (arena, 0)
+            }
+            None => {
+                let (memory, ptr) = unsafe {
+                    let raw = device.allocate(self.memory_type, self.arena_size)?;
+
+                    let ptr = match device.map(&raw, 0..self.arena_size) {
+                        Ok(ptr) => ptr,
+                        Err(error) => {
+                            device.free(raw);
+                            return Err(error.into());
+                        }
+                    };
+
+                    let memory = Memory::from_raw(raw, self.arena_size, self.memory_properties);
+
+                    (memory, ptr)
+                };
+
+                let arena = Arena {
+                    used: size,
+                    free: 0,
+                    ptr,
+                    memory: Box::new(memory),
+                };
+                (arena, self.arena_size)
+            }
         };
 
-        let (ptr, range) = mapped_sub_range(ptr, 0..self.arena_size, 0..size)
+        let (ptr, range) = mapped_sub_range(arena.ptr, 0..self.arena_size, 0..size)
             .expect("This sub-range must fit in arena mapping");
 
         let block = ArenaBlock {
@@ -264,11 +338,13 @@ impl<T: 'static> Allocator for ArenaAllocator<T> {
             memory: &*arena.memory,
             ptr,
             range,
+            #[cfg(feature = "tags")]
+            tag: TagSlot::default(),
             relevant: Relevant,
         };
 
         self.arenas.push_back(arena);
-        Ok((block, self.arena_size))
+        Ok((block, allocated))
     }
 
     fn free<D>(&mut self, device: &D, block: Self::Block) -> u64
@@ -286,8 +362,14 @@ impl<T: 'static> Allocator for ArenaAllocator<T> {
             "Can't be allocated from not yet created arena"
         );
         {
+            let last = index + 1 == self.arenas.len();
             let ref mut arena = self.arenas[index];
             arena.free += block.size();
+            if last && arena.free == arena.used {
+                // Current arena has no blocks left, allocate from its beginning again.
+                arena.used = 0;
+                arena.free = 0;
+            }
         }
         block.dispose();
 
@@ -0,0 +1,106 @@
+use allocator::Allocator;
+use block::Block;
+use device::Device;
+use error::{MemoryError, OutOfMemoryError};
+
+/// Allocator wrapper that enforces hard budget in bytes.
+/// Once budget is exhausted allocations fail with `OutOfDeviceMemory`
+/// regardless of what wrapped allocator and device could serve.
+/// Useful to simulate low-memory conditions deterministically.
+///
+/// Budget is consumed by sizes of allocated blocks
+/// and restored when blocks are freed.
+#[derive(Debug)]
+pub struct CappedAllocator<A> {
+    inner: A,
+    cap: u64,
+    used: u64,
+}
+
+impl<A> CappedAllocator<A> {
+    /// Wrap allocator limiting it to `cap` bytes.
+    pub fn new(inner: A, cap: u64) -> Self {
+        CappedAllocator {
+            inner,
+            cap,
+            used: 0,
+        }
+    }
+
+    /// Get bytes that still can be allocated.
+    pub fn remaining(&self) -> u64 {
+        self.cap.saturating_sub(self.used)
+    }
+
+    /// Get bytes occupied by allocated blocks.
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// Get budget.
+    pub fn cap(&self) -> u64 {
+        self.cap
+    }
+
+    /// Change budget.
+    /// Already allocated blocks are kept even if new budget is below used bytes.
+    pub fn set_cap(&mut self, cap: u64) {
+        self.cap = cap;
+    }
+
+    /// Get wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Unwrap allocator.
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<A> Allocator for CappedAllocator<A>
+where
+    A: Allocator,
+{
+    type Memory = A::Memory;
+    type Block = A::Block;
+
+    fn alloc<D>(
+        &mut self,
+        device: &D,
+        size: u64,
+        align: u64,
+    ) -> Result<(A::Block, u64), MemoryError>
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        if size > self.remaining() {
+            return Err(OutOfMemoryError::OutOfDeviceMemory.into());
+        }
+
+        let (block, allocated) = self.inner.alloc(device, size, align)?;
+        let taken = block_size(&block);
+        if taken > self.remaining() {
+            // Wrapped allocator rounded size up past the budget.
+            self.inner.free(device, block);
+            return Err(OutOfMemoryError::OutOfDeviceMemory.into());
+        }
+
+        self.used += taken;
+        Ok((block, allocated))
+    }
+
+    fn free<D>(&mut self, device: &D, block: A::Block) -> u64
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        self.used -= block_size(&block);
+        self.inner.free(device, block)
+    }
+}
+
+fn block_size<B: Block>(block: &B) -> u64 {
+    let range = block.range();
+    range.end - range.start
+}
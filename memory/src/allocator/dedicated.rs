@@ -1,17 +1,28 @@
-use std::{marker::PhantomData, ops::Range, ptr::NonNull};
+use std::{collections::BTreeMap, marker::PhantomData, mem::replace, ops::Range, ptr::NonNull};
 
-use allocator::Allocator;
+use allocator::{Allocator, AllocatorSnapshot, AllocatorStats};
 use block::Block;
+#[cfg(feature = "tags")]
+use block::TagSlot;
 use device::Device;
 use error::*;
+use external::{ExternalHandle, ExternalHandleTypes};
 use mapping::{mapped_fitting_range, MappedRange};
 use memory::*;
 
+/// Allocations smaller than this are never routed to `DedicatedAllocator` by `Heaps`
+/// if memory type has dynamic sub-allocator capable to serve them.
+/// Memory object per tiny allocation wastes device allocation count limit
+/// which can be as low as 4096.
+pub const MIN_DEDICATED_SIZE: u64 = 4 * 1024;
+
 /// Memory block allocated from `DedicatedAllocator`
 #[derive(Debug)]
 pub struct DedicatedBlock<T> {
     memory: Memory<T>,
     mapping: Option<(NonNull<u8>, Range<u64>)>,
+    #[cfg(feature = "tags")]
+    tag: TagSlot,
 }
 
 unsafe impl<T: Send> Send for DedicatedBlock<T> {}
@@ -30,8 +41,31 @@ impl<T> DedicatedBlock<T> {
         DedicatedBlock {
             memory,
             mapping: None,
+            #[cfg(feature = "tags")]
+            tag: TagSlot::default(),
         }
     }
+
+    tag_accessors!();
+
+    /// Get handle types memory of the block can be exported as.
+    pub fn exportable(&self) -> ExternalHandleTypes {
+        self.memory.exportable()
+    }
+
+    /// Export memory of the block as OS handle of `handle_type`.
+    /// See `Memory::export_handle`.
+    pub fn export_handle<D>(
+        &self,
+        device: &D,
+        handle_type: ExternalHandleTypes,
+    ) -> Result<ExternalHandle, ExportError>
+    where
+        T: 'static,
+        D: Device<Memory = T>,
+    {
+        self.memory.export_handle(device, handle_type)
+    }
 }
 
 impl<T: 'static> Block for DedicatedBlock<T> {
@@ -64,6 +98,15 @@ impl<T: 'static> Block for DedicatedBlock<T> {
             range.start <= range.end,
             "Memory mapping region must have valid size"
         );
+        if self.memory.properties().protected() {
+            return Err(MappingError::Protected);
+        }
+        if self.memory.properties().lazily_allocated() {
+            return Err(MappingError::LazilyAllocated);
+        }
+        if !self.memory.host_visible() {
+            return Err(MappingError::HostInvisible);
+        }
 
         unsafe {
             if let Some(ptr) = self
@@ -95,6 +138,64 @@ impl<T: 'static> Block for DedicatedBlock<T> {
     }
 }
 
+/// Config for free-memory cache of `DedicatedAllocator`.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DedicatedCacheConfig {
+    /// Maximum number of freed memory objects retained per size.
+    pub max_per_size: usize,
+
+    /// Maximum total size of retained memory objects.
+    pub max_bytes: u64,
+}
+
+/// Statistics of free-memory cache of `DedicatedAllocator`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DedicatedCacheStats {
+    /// Number of allocations served from the cache.
+    pub hits: u64,
+
+    /// Number of allocations that required new memory object.
+    pub misses: u64,
+
+    /// Total size of memory objects retained in the cache.
+    pub cached: u64,
+}
+
+#[derive(Debug)]
+struct MemoryCache<T> {
+    config: DedicatedCacheConfig,
+    // Ordered so that memory objects are freed in the same order on every run.
+    buckets: BTreeMap<u64, Vec<T>>,
+    stats: DedicatedCacheStats,
+}
+
+impl<T> MemoryCache<T> {
+    fn take(&mut self, size: u64) -> Option<T> {
+        let memory = self.buckets.get_mut(&size).and_then(Vec::pop);
+        if memory.is_some() {
+            self.stats.hits += 1;
+            self.stats.cached -= size;
+        } else {
+            self.stats.misses += 1;
+        }
+        memory
+    }
+
+    fn put(&mut self, size: u64, memory: T) -> Option<T> {
+        if self.stats.cached + size > self.config.max_bytes {
+            return Some(memory);
+        }
+        let bucket = self.buckets.entry(size).or_insert_with(Vec::new);
+        if bucket.len() >= self.config.max_per_size {
+            return Some(memory);
+        }
+        bucket.push(memory);
+        self.stats.cached += size;
+        None
+    }
+}
+
 /// Dummy memory allocator that uses memory object per allocation requested.
 ///
 /// This allocator suites best huge allocations.
@@ -107,6 +208,9 @@ pub struct DedicatedAllocator<T> {
     memory_type: u32,
     memory_properties: Properties,
     used: u64,
+    /// Number of live memory objects by their sizes.
+    live: BTreeMap<u64, u64>,
+    cache: Option<MemoryCache<T>>,
     pd: PhantomData<T>,
 }
 
@@ -116,16 +220,146 @@ impl<T> DedicatedAllocator<T> {
         Properties::empty()
     }
 
-    /// Create new `ArenaAllocator`
+    /// Create new `DedicatedAllocator`
     /// for `memory_type` with `memory_properties` specified
     pub fn new(memory_type: u32, memory_properties: Properties) -> Self {
         DedicatedAllocator {
             memory_type,
             memory_properties,
             used: 0,
+            live: BTreeMap::new(),
+            cache: None,
             pd: PhantomData,
         }
     }
+
+    /// Create new `DedicatedAllocator`
+    /// for `memory_type` with `memory_properties` specified
+    /// that retains freed memory objects to reuse them for allocations of the same size.
+    pub fn with_cache(
+        memory_type: u32,
+        memory_properties: Properties,
+        config: DedicatedCacheConfig,
+    ) -> Self {
+        let mut allocator = Self::new(memory_type, memory_properties);
+        allocator.cache = Some(MemoryCache {
+            config,
+            buckets: BTreeMap::new(),
+            stats: DedicatedCacheStats::default(),
+        });
+        allocator
+    }
+
+    /// Get statistics of free-memory cache.
+    /// Returns zeroed statistics if cache is disabled.
+    pub fn cache_stats(&self) -> DedicatedCacheStats {
+        self.cache
+            .as_ref()
+            .map_or(DedicatedCacheStats::default(), |cache| cache.stats)
+    }
+
+    /// Get snapshot of allocator state.
+    /// Each block is a memory object of its own, so there is no fragmentation.
+    /// Memory objects retained in the cache count as chunks
+    /// and the largest of them is the largest free region.
+    pub fn snapshot(&self) -> AllocatorSnapshot {
+        let mut snapshot = AllocatorSnapshot::default();
+        for (&size, &count) in &self.live {
+            snapshot.add_blocks(size, count);
+            snapshot.chunks += count;
+        }
+        if let Some(ref cache) = self.cache {
+            for (&size, bucket) in &cache.buckets {
+                snapshot.chunks += bucket.len() as u64;
+                if !bucket.is_empty() {
+                    snapshot.largest_free = snapshot.largest_free.max(size);
+                }
+            }
+        }
+        snapshot
+    }
+
+    /// Get memory counters of the allocator.
+    /// Memory objects retained in the cache count as allocated memory.
+    pub fn stats(&self) -> AllocatorStats {
+        let mut stats = AllocatorStats {
+            allocated: self.used,
+            used: self.used,
+            memory_objects: self.live.values().sum(),
+        };
+        if let Some(ref cache) = self.cache {
+            stats.allocated += cache.stats.cached;
+            stats.memory_objects += cache
+                .buckets
+                .values()
+                .map(|bucket| bucket.len() as u64)
+                .sum::<u64>();
+        }
+        stats
+    }
+
+    /// Count live memory object of `size`.
+    fn add_live(&mut self, size: u64) {
+        self.used += size;
+        *self.live.entry(size).or_insert(0) += 1;
+    }
+
+    /// Free all memory objects retained in the cache.
+    /// Returns amount of memory returned to the device.
+    pub fn clear_cache<D>(&mut self, device: &D) -> u64
+    where
+        D: Device<Memory = T>,
+    {
+        let mut freed = 0;
+        if let Some(ref mut cache) = self.cache {
+            for (size, bucket) in replace(&mut cache.buckets, BTreeMap::new()) {
+                for memory in bucket {
+                    freed += size;
+                    unsafe {
+                        device.free(memory);
+                    }
+                }
+            }
+            cache.stats.cached = 0;
+        }
+        freed
+    }
+
+    /// Allocate memory object that can be exported as handles of `handle_types`.
+    /// Exportable memory is never taken from nor retained in the cache,
+    /// as handles exported earlier may still reference it.
+    pub fn alloc_exportable<D>(
+        &mut self,
+        device: &D,
+        size: u64,
+        handle_types: ExternalHandleTypes,
+    ) -> Result<(DedicatedBlock<T>, u64), MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        let raw = unsafe { device.allocate_exportable(self.memory_type, size, handle_types)? };
+        let memory = unsafe {
+            Memory::from_raw_exportable(raw, size, self.memory_properties, handle_types)
+        };
+
+        self.add_live(size);
+
+        Ok((DedicatedBlock::from_memory(memory), size))
+    }
+
+    /// Dispose of allocator.
+    /// Frees memory objects retained in the cache.
+    /// Will panic if blocks are left allocated.
+    pub fn dispose<D>(mut self, device: &D)
+    where
+        D: Device<Memory = T>,
+    {
+        self.clear_cache(device);
+        assert_eq!(
+            self.used, 0,
+            "Blocks are left allocated during allocator disposal"
+        );
+    }
 }
 
 impl<T: 'static> Allocator for DedicatedAllocator<T> {
@@ -137,22 +371,34 @@ impl<T: 'static> Allocator for DedicatedAllocator<T> {
         &mut self,
         device: &D,
         size: u64,
-        _align: u64,
+        align: u64,
     ) -> Result<(DedicatedBlock<T>, u64), MemoryError>
     where
         D: Device<Memory = T>,
     {
-        let memory = unsafe {
-            Memory::from_raw(
-                device.allocate(self.memory_type, size)?,
-                size,
-                self.memory_properties,
-            )
+        // Block always starts at offset `0` of its memory object.
+        let supported = device.allocation_alignment(self.memory_type);
+        if align > supported {
+            return Err(MemoryError::InvalidAlignment { align, supported });
+        }
+
+        if size < MIN_DEDICATED_SIZE {
+            debug!(
+                "Dedicated allocation of {} bytes is below {} bytes threshold. Consider sub-allocation",
+                size, MIN_DEDICATED_SIZE
+            );
+        }
+
+        let (raw, allocated) = match self.cache.as_mut().and_then(|cache| cache.take(size)) {
+            Some(raw) => (raw, 0),
+            None => (unsafe { device.allocate(self.memory_type, size)? }, size),
         };
 
-        self.used += size;
+        let memory = unsafe { Memory::from_raw(raw, size, self.memory_properties) };
 
-        Ok((DedicatedBlock::from_memory(memory), size))
+        self.add_live(size);
+
+        Ok((DedicatedBlock::from_memory(memory), allocated))
     }
 
     #[inline]
@@ -163,8 +409,28 @@ impl<T: 'static> Allocator for DedicatedAllocator<T> {
         block.unmap(device);
         let size = block.memory.size();
         self.used -= size;
+        let last = {
+            let count = self
+                .live
+                .get_mut(&size)
+                .expect("Block must be allocated here");
+            *count -= 1;
+            *count == 0
+        };
+        if last {
+            self.live.remove(&size);
+        }
+        let exportable = !block.memory.exportable().is_empty();
+        let raw = block.memory.into_raw();
+        let raw = match self.cache {
+            Some(ref mut cache) if !exportable => match cache.put(size, raw) {
+                Some(raw) => raw,
+                None => return 0,
+            },
+            _ => raw,
+        };
         unsafe {
-            device.free(block.memory.into_raw());
+            device.free(raw);
         }
         size
     }
@@ -172,6 +438,8 @@ impl<T: 'static> Allocator for DedicatedAllocator<T> {
 
 impl<T> Drop for DedicatedAllocator<T> {
     fn drop(&mut self) {
-        assert_eq!(self.used, 0);
+        if !::std::thread::panicking() {
+            assert_eq!(self.used, 0);
+        }
     }
 }
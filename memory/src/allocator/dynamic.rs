@@ -1,16 +1,25 @@
 use hibitset::{BitSet, BitSetLike};
 use relevant::Relevant;
 use std::{fmt::Debug, ops::Range, ptr::NonNull};
-use veclist::VecList;
 
-use allocator::Allocator;
+use allocator::{Allocator, AllocatorSnapshot, AllocatorStats};
 use block::Block;
+#[cfg(feature = "tags")]
+use block::TagSlot;
 use device::Device;
 use error::*;
 use mapping::*;
 use memory::*;
 use util::*;
 
+/// Size of the guard padding blocks with.
+#[cfg(feature = "guard-pages")]
+const GUARD_SIZE: u64 = 16;
+
+/// Pattern guard bytes are filled with.
+#[cfg(feature = "guard-pages")]
+const GUARD_PATTERN: u8 = 0xFD;
+
 /// Memory block allocated from `DynamicAllocator`
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -20,6 +29,9 @@ pub struct DynamicBlock<T> {
     memory: *const Memory<T>,
     ptr: Option<NonNull<u8>>,
     range: Range<u64>,
+    requested: u64,
+    #[cfg(feature = "tags")]
+    tag: TagSlot,
     #[derivative(Debug = "ignore")]
     relevant: Relevant,
 }
@@ -28,6 +40,8 @@ unsafe impl<T: Send> Send for DynamicBlock<T> {}
 unsafe impl<T: Sync> Sync for DynamicBlock<T> {}
 
 impl<T> DynamicBlock<T> {
+    tag_accessors!();
+
     fn shared_memory(&self) -> &Memory<T> {
         // Memory won't be freed until last block created from it deallocated.
         unsafe { &*self.memory }
@@ -40,6 +54,50 @@ impl<T> DynamicBlock<T> {
     fn dispose(self) {
         self.relevant.dispose();
     }
+
+    /// Fill bytes past requested size with guard pattern.
+    /// Only blocks of host-visible memory are guarded.
+    #[cfg(feature = "guard-pages")]
+    fn fill_guard(&mut self) {
+        if let Some(ptr) = self.ptr {
+            let guard = unsafe {
+                ::std::slice::from_raw_parts_mut(
+                    ptr.as_ptr().offset(self.requested as isize),
+                    (self.size() - self.requested) as usize,
+                )
+            };
+            for byte in guard {
+                *byte = GUARD_PATTERN;
+            }
+        }
+    }
+
+    /// Check that guard bytes are intact.
+    ///
+    /// # Panics
+    ///
+    /// This function panics reporting the block if any guard byte was overwritten.
+    #[cfg(feature = "guard-pages")]
+    fn check_guard(&self, memory_type: u32) {
+        if let Some(ptr) = self.ptr {
+            let guard = unsafe {
+                ::std::slice::from_raw_parts(
+                    ptr.as_ptr().offset(self.requested as isize),
+                    (self.size() - self.requested) as usize,
+                )
+            };
+            if let Some(offset) = guard.iter().position(|&byte| byte != GUARD_PATTERN) {
+                panic!(
+                    "Block {:?} of {} bytes from memory type {} was overrun: guard byte at offset {} is {:#x}",
+                    self.range,
+                    self.requested,
+                    memory_type,
+                    self.requested + offset as u64,
+                    guard[offset]
+                );
+            }
+        }
+    }
 }
 
 impl<T: 'static> Block for DynamicBlock<T> {
@@ -70,6 +128,12 @@ impl<T: 'static> Block for DynamicBlock<T> {
             range.start <= range.end,
             "Memory mapping region must have valid size"
         );
+        if self.shared_memory().properties().protected() {
+            return Err(MappingError::Protected);
+        }
+        if self.shared_memory().properties().lazily_allocated() {
+            return Err(MappingError::LazilyAllocated);
+        }
         if !self.shared_memory().host_visible() {
             return Err(MappingError::HostInvisible);
         }
@@ -90,6 +154,33 @@ impl<T: 'static> Block for DynamicBlock<T> {
     fn unmap<D>(&mut self, _device: &D) {}
 }
 
+/// Strategy of choosing size class for allocation.
+/// Request can be served by block of any size class that fits it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FitStrategy {
+    /// Take block of the tightest size class.
+    /// New chunk is allocated if the class has no free blocks
+    /// even if larger classes have.
+    /// Fastest as no search is performed.
+    FirstFit,
+
+    /// Take free block of the smallest size class that has one.
+    /// Reuses free blocks instead of allocating new chunks
+    /// at the cost of searching size classes.
+    BestFit,
+
+    /// Take free block of the largest size class that has one.
+    /// Keeps blocks of small classes for small requests.
+    WorstFit,
+}
+
+impl Default for FitStrategy {
+    fn default() -> Self {
+        FitStrategy::FirstFit
+    }
+}
+
 /// Config for `DynamicAllocator`.
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -104,13 +195,24 @@ pub struct DynamicConfig {
     /// Maximum block size.
     /// For any request larger than this won't be allocated with this allocator.
     pub max_block_size: u64,
+
+    /// Strategy of choosing size class for allocation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fit: FitStrategy,
 }
 
 /// Low-fragmentation allocator.
 /// Suitable for any type of small allocations.
 /// Have up to `block_size_granularity - 1` memory overhead.
+/// Alignment requirements don't introduce additional free-lists.
+/// Blocks of each size class are aligned to the largest power of two dividing block size,
+/// and requests with larger alignment are served from the next size class that satisfies it.
 /// Every freed block can be recycled independently.
-/// Memory objects can be returned to the system if whole memory object become unused (not implemented yet).
+/// Chunk is returned to the system, or to the chunk of larger blocks it was taken from,
+/// once all its blocks are freed.
+///
+/// With `guard-pages` feature blocks of host-visible memory are padded with guard bytes
+/// which are checked on free to catch writes past requested size.
 #[derive(Debug)]
 pub struct DynamicAllocator<T> {
     /// Memory type that this allocator allocates.
@@ -125,22 +227,60 @@ pub struct DynamicAllocator<T> {
     /// All requests are rounded up to multiple of this value.
     block_size_granularity: u64,
 
+    /// Strategy of choosing size class for allocation.
+    fit: FitStrategy,
+
     /// List of chunk lists.
     /// Each index corresponds to `block_size_granularity * index` size.
     sizes: Vec<Size<T>>,
+
+    /// Total size of blocks in use.
+    used: u64,
+
+    /// Total size requested for blocks in use.
+    requested: u64,
+
+    /// Total size of chunks allocated from device.
+    allocated: u64,
+
+    /// Number of chunks allocated from device.
+    memory_objects: u64,
 }
 
 /// List of chunks
 #[derive(Debug)]
 struct Size<T> {
-    /// List of chunks.
-    chunks: VecList<Chunk<T>>,
+    /// Slots of chunks.
+    /// New chunk takes the lowest vacant slot, so chunk indices,
+    /// and therefore blocks taken from the free-list,
+    /// don't depend on order chunks were freed in.
+    chunks: Vec<Option<Chunk<T>>>,
 
     /// Total chunks count.
     total_chunks: u32,
 
     /// Bits per free blocks.
     blocks: BitSet,
+
+    /// Alignment of every block of this size.
+    align: u64,
+}
+
+impl<T> Size<T> {
+    /// Place chunk into the lowest vacant slot and return its index.
+    fn add_chunk(&mut self, chunk: Chunk<T>) -> u32 {
+        let index = match self.chunks.iter().position(Option::is_none) {
+            Some(index) => {
+                self.chunks[index] = Some(chunk);
+                index
+            }
+            None => {
+                self.chunks.push(Some(chunk));
+                self.chunks.len() - 1
+            }
+        };
+        index as u32
+    }
 }
 
 impl<T: 'static> DynamicAllocator<T> {
@@ -150,13 +290,14 @@ impl<T: 'static> DynamicAllocator<T> {
     }
 
     /// Maximum allocation size.
+    /// With `guard-pages` feature the biggest block must also fit the guard.
     pub fn max_allocation(&self) -> u64 {
-        self.max_block_size()
+        self.max_block_size() - guarded_size(0)
     }
 
-    /// Create new `ArenaAllocator`
+    /// Create new `DynamicAllocator`
     /// for `memory_type` with `memory_properties` specified,
-    /// with `ArenaConfig` provided.
+    /// with `DynamicConfig` provided.
     pub fn new(memory_type: u32, memory_properties: Properties, mut config: DynamicConfig) -> Self {
         // This is hack to simplify implementation of chunk cleaning.
         config.blocks_per_chunk = ::std::mem::size_of::<usize>() as u32 * 8;
@@ -191,15 +332,102 @@ impl<T: 'static> DynamicAllocator<T> {
             memory_properties,
             block_size_granularity: config.block_size_granularity,
             blocks_per_chunk: config.blocks_per_chunk,
-            sizes: (0..sizes)
-                .map(|_| Size {
-                    chunks: VecList::new(),
-                    blocks: BitSet::new(),
-                    total_chunks: 0,
+            fit: config.fit,
+            sizes: (0..sizes as u64)
+                .map(|index| {
+                    let block_size = config.block_size_granularity * (index + 1);
+                    Size {
+                        chunks: Vec::new(),
+                        blocks: BitSet::new(),
+                        total_chunks: 0,
+                        align: block_size & block_size.wrapping_neg(),
+                    }
                 }).collect(),
+            used: 0,
+            requested: 0,
+            allocated: 0,
+            memory_objects: 0,
+        }
+    }
+
+    /// Total size of blocks in use.
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// Memory wasted by rounding requests up to size classes.
+    /// This includes memory lost to over-aligned blocks.
+    pub fn wasted(&self) -> u64 {
+        self.used - self.requested
+    }
+
+    /// Get memory counters of the allocator.
+    /// Only chunks allocated from device are memory objects,
+    /// chunks allocated from bigger blocks are part of them.
+    pub fn stats(&self) -> AllocatorStats {
+        AllocatorStats {
+            allocated: self.allocated,
+            used: self.used,
+            memory_objects: self.memory_objects,
         }
     }
 
+    /// Get snapshot of allocator state.
+    /// Blocks are counted by size of their class.
+    /// Blocks serving as chunks of smaller size classes are not counted as live blocks,
+    /// but their free blocks count towards fragmentation and free regions.
+    pub fn snapshot(&self) -> AllocatorSnapshot {
+        let blocks_per_chunk = self.blocks_per_chunk as u64;
+        let mut snapshot = AllocatorSnapshot::default();
+        let mut live = self
+            .sizes
+            .iter()
+            .map(|size| size.total_chunks as u64 * blocks_per_chunk)
+            .collect::<Vec<_>>();
+        let mut free_blocks = 0;
+
+        for (index, size) in self.sizes.iter().enumerate() {
+            let block_size = self.block_size(index);
+
+            // Free blocks are iterated in order, so neighbors within chunk form a free region.
+            let mut run: Option<(u32, u64)> = None;
+            for block_index in (&size.blocks).iter() {
+                let length = match run {
+                    Some((last, length))
+                        if last + 1 == block_index && block_index % self.blocks_per_chunk != 0 =>
+                    {
+                        length + 1
+                    }
+                    _ => 1,
+                };
+                run = Some((block_index, length));
+                snapshot.largest_free = snapshot.largest_free.max(length * block_size);
+                live[index] -= 1;
+                free_blocks += 1;
+            }
+
+            let chunk_size = block_size * blocks_per_chunk;
+            if chunk_size <= self.max_block_size() {
+                live[self.size_index(chunk_size)] -= size.total_chunks as u64;
+            }
+            snapshot.chunks += size.total_chunks as u64;
+        }
+
+        for (index, &count) in live.iter().enumerate() {
+            if count > 0 {
+                snapshot.add_blocks(self.block_size(index), count);
+            }
+        }
+
+        // All chunks have the same number of blocks,
+        // so average of free fractions equals fraction of all free blocks.
+        if snapshot.chunks > 0 {
+            snapshot.fragmentation =
+                free_blocks as f64 / (snapshot.chunks * blocks_per_chunk) as f64;
+        }
+        snapshot
+    }
+
     /// Maximum block size.
     /// Any request bigger will result in panic.
     pub fn max_block_size(&self) -> u64 {
@@ -216,6 +444,37 @@ impl<T: 'static> DynamicAllocator<T> {
         ((size - 1) / self.block_size_granularity) as usize
     }
 
+    /// Returns index of the smallest size class
+    /// that fits `size` and which blocks are aligned to `align`.
+    fn class_index(&self, size: u64, align: u64) -> Result<usize, MemoryError> {
+        if !align.is_power_of_two() {
+            return Err(AllocationError::BadAlignment(align).into());
+        }
+        let classes = self.size_index(size)..self.sizes.len();
+        classes
+            .clone()
+            .find(|&index| self.sizes[index].align % align == 0)
+            .ok_or_else(|| MemoryError::InvalidAlignment {
+                align,
+                supported: classes.map(|index| self.sizes[index].align).max().unwrap_or(0),
+            })
+    }
+
+    /// Returns index of the size class chosen by fit strategy
+    /// among classes starting from the tightest one
+    /// which blocks are aligned to `align`.
+    fn fit_index(&self, tightest: usize, align: u64) -> usize {
+        let free = |&index: &usize| {
+            self.sizes[index].align % align == 0
+                && (&self.sizes[index].blocks).iter().next().is_some()
+        };
+        match self.fit {
+            FitStrategy::FirstFit => None,
+            FitStrategy::BestFit => (tightest..self.sizes.len()).find(free),
+            FitStrategy::WorstFit => (tightest..self.sizes.len()).rev().find(free),
+        }.unwrap_or(tightest)
+    }
+
     /// Get block size for the size index.
     fn block_size(&self, index: usize) -> u64 {
         // Index must be acquired from `size_index` methods. Hence result is less than `max_block_size` and fits u64
@@ -247,10 +506,13 @@ impl<T: 'static> DynamicAllocator<T> {
                 let memory = Memory::from_raw(raw, size, self.memory_properties);
                 (memory, mapping)
             };
+            self.allocated += size;
+            self.memory_objects += 1;
             Ok((Chunk::Dedicated(Box::new(memory), mapping), size))
         } else {
             // Allocate from larger chunks.
-            let (dynamic_block, allocated) = self.alloc_from_chunk(device, size)?;
+            let size_index = self.size_index(size);
+            let (dynamic_block, allocated) = self.alloc_from_chunk(device, size_index)?;
             Ok((Chunk::Dynamic(dynamic_block), allocated))
         }
     }
@@ -262,15 +524,19 @@ impl<T: 'static> DynamicAllocator<T> {
         D: Device<Memory = T>,
     {
         match chunk {
-            Chunk::Dedicated(boxed, _) => {
+            Chunk::Dedicated(boxed, mapping) => {
                 let size = boxed.size();
+                self.allocated -= size;
+                self.memory_objects -= 1;
                 unsafe {
-                    device.unmap(boxed.raw());
+                    if mapping.is_some() {
+                        device.unmap(boxed.raw());
+                    }
                     device.free(boxed.into_raw());
                 }
                 size
             }
-            Chunk::Dynamic(dynamic_block) => self.free(device, dynamic_block),
+            Chunk::Dynamic(dynamic_block) => self.free_block(device, dynamic_block),
         }
     }
 
@@ -278,24 +544,26 @@ impl<T: 'static> DynamicAllocator<T> {
     fn alloc_from_chunk<D>(
         &mut self,
         device: &D,
-        size: u64,
+        size_index: usize,
     ) -> Result<(DynamicBlock<T>, u64), MemoryError>
     where
         D: Device<Memory = T>,
     {
-        let size_index = self.size_index(size);
+        let block_size = self.block_size(size_index);
         let (block_index, allocated) = match (&self.sizes[size_index].blocks).iter().next() {
             Some(block_index) => {
                 self.sizes[size_index].blocks.remove(block_index);
                 (block_index, 0)
             }
             None => {
-                if self.sizes[self.size_index(size)].total_chunks == self.max_chunks_per_size() {
+                if self.sizes[size_index].total_chunks == self.max_chunks_per_size() {
                     return Err(OutOfMemoryError::OutOfHostMemory.into());
                 }
-                let chunk_size = size * self.blocks_per_chunk as u64;
+                // Chunk size is multiple of `block_size * blocks_per_chunk`
+                // so chunk is aligned at least as blocks of this size.
+                let chunk_size = block_size * self.blocks_per_chunk as u64;
                 let (chunk, allocated) = self.alloc_chunk(device, chunk_size)?;
-                let chunk_index = self.sizes[size_index].chunks.push(chunk) as u32;
+                let chunk_index = self.sizes[size_index].add_chunk(chunk);
                 self.sizes[size_index].total_chunks += 1;
                 let block_index_start = chunk_index * self.blocks_per_chunk;
                 let block_index_end = block_index_start + self.blocks_per_chunk;
@@ -309,9 +577,10 @@ impl<T: 'static> DynamicAllocator<T> {
 
         let chunk_index = block_index / self.blocks_per_chunk;
 
-        let ref chunk = self.sizes[size_index].chunks[chunk_index as usize];
+        let ref chunk = self.sizes[size_index].chunks[chunk_index as usize]
+            .as_ref()
+            .expect("Chunk must exist");
         let chunk_range = chunk.range();
-        let block_size = self.block_size(size_index);
         let block_offset =
             chunk_range.start + (block_index % self.blocks_per_chunk) as u64 * block_size;
         let block_range = block_offset..block_offset + block_size;
@@ -319,40 +588,23 @@ impl<T: 'static> DynamicAllocator<T> {
         Ok((
             DynamicBlock {
                 range: block_range.clone(),
+                requested: block_size,
                 memory: chunk.shared_memory(),
                 index: block_index,
                 ptr: chunk.ptr().map(|ptr| {
                     mapped_fitting_range(ptr, chunk.range(), block_range)
                         .expect("Block must be in sub-range of chunk")
                 }),
+                #[cfg(feature = "tags")]
+                tag: TagSlot::default(),
                 relevant: Relevant,
             },
             allocated,
         ))
     }
-}
-
-impl<T: 'static> Allocator for DynamicAllocator<T> {
-    type Memory = T;
-    type Block = DynamicBlock<T>;
-
-    fn alloc<D>(
-        &mut self,
-        device: &D,
-        size: u64,
-        align: u64,
-    ) -> Result<(DynamicBlock<T>, u64), MemoryError>
-    where
-        D: Device<Memory = T>,
-    {
-        use std::cmp::max;
-        let size = max(size, align);
-
-        assert!(size <= self.max_block_size());
-        self.alloc_from_chunk(device, size)
-    }
 
-    fn free<D>(&mut self, device: &D, block: DynamicBlock<T>) -> u64
+    /// Return block to the free-list of its size.
+    fn free_block<D>(&mut self, device: &D, block: DynamicBlock<T>) -> u64
     where
         D: Device<Memory = T>,
     {
@@ -372,10 +624,10 @@ impl<T: 'static> Allocator for DynamicAllocator<T> {
                 let old = self.sizes[size_index].blocks.remove(index);
                 debug_assert!(old);
             }
-            let chunk = self.sizes[size_index]
-                .chunks
-                .pop(chunk_index as usize)
+            let chunk = self.sizes[size_index].chunks[chunk_index as usize]
+                .take()
                 .expect("Chunk must exist");
+            self.sizes[size_index].total_chunks -= 1;
             self.free_chunk(device, chunk)
         } else {
             0
@@ -383,6 +635,43 @@ impl<T: 'static> Allocator for DynamicAllocator<T> {
     }
 }
 
+impl<T: 'static> Allocator for DynamicAllocator<T> {
+    type Memory = T;
+    type Block = DynamicBlock<T>;
+
+    fn alloc<D>(
+        &mut self,
+        device: &D,
+        size: u64,
+        align: u64,
+    ) -> Result<(DynamicBlock<T>, u64), MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        assert!(size <= self.max_allocation());
+        let size_index = self.class_index(guarded_size(size), align)?;
+        let size_index = self.fit_index(size_index, align);
+        let (mut block, allocated) = self.alloc_from_chunk(device, size_index)?;
+        block.requested = size;
+        #[cfg(feature = "guard-pages")]
+        block.fill_guard();
+        self.used += block.size();
+        self.requested += size;
+        Ok((block, allocated))
+    }
+
+    fn free<D>(&mut self, device: &D, block: DynamicBlock<T>) -> u64
+    where
+        D: Device<Memory = T>,
+    {
+        #[cfg(feature = "guard-pages")]
+        block.check_guard(self.memory_type);
+        self.used -= block.size();
+        self.requested -= block.requested;
+        self.free_block(device, block)
+    }
+}
+
 /// Block allocated for chunk.
 #[derive(Debug)]
 enum Chunk<T> {
@@ -419,6 +708,18 @@ impl<T: 'static> Chunk<T> {
     }
 }
 
+/// Get size of the block required to fit `size` bytes and guard after them.
+#[cfg(feature = "guard-pages")]
+fn guarded_size(size: u64) -> u64 {
+    size + GUARD_SIZE
+}
+
+/// Get size of the block required to fit `size` bytes.
+#[cfg(not(feature = "guard-pages"))]
+fn guarded_size(size: u64) -> u64 {
+    size
+}
+
 fn max_blocks_per_size() -> u32 {
     let value = (::std::mem::size_of::<usize>() * 8).pow(4);
     assert!(fits_u32(value));
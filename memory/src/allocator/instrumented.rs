@@ -0,0 +1,234 @@
+use std::{fmt, ops::Range, sync::Arc};
+
+use allocator::Allocator;
+use block::Block;
+use device::Device;
+use error::{MappingError, MemoryError};
+use mapping::MappedRange;
+use memory::Properties;
+
+/// Event reported by `InstrumentedAllocator`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AllocatorEvent {
+    /// Block was allocated.
+    Alloc {
+        /// Memory type of the block.
+        memory_type: u32,
+        /// Requested size.
+        size: u64,
+        /// Requested alignment.
+        align: u64,
+        /// Range of the allocated block.
+        range: Range<u64>,
+        /// Memory consumed from device.
+        allocated: u64,
+    },
+
+    /// Block was freed.
+    Free {
+        /// Memory type of the block.
+        memory_type: u32,
+        /// Range of the freed block.
+        range: Range<u64>,
+        /// Memory returned to device.
+        freed: u64,
+    },
+
+    /// Block was mapped.
+    Map {
+        /// Memory type of the block.
+        memory_type: u32,
+        /// Requested mapping range.
+        range: Range<u64>,
+    },
+
+    /// Block was unmapped.
+    Unmap {
+        /// Memory type of the block.
+        memory_type: u32,
+    },
+}
+
+/// Callback invoked by `InstrumentedAllocator` and its blocks.
+pub type AllocatorCallback = Arc<dyn Fn(&AllocatorEvent) + Send + Sync>;
+
+#[derive(Clone, Default)]
+struct Hook(Option<AllocatorCallback>);
+
+impl Hook {
+    fn report<F>(&self, event: F)
+    where
+        F: FnOnce() -> AllocatorEvent,
+    {
+        // Event is not even constructed without callback.
+        if let Some(ref callback) = self.0 {
+            callback(&event())
+        }
+    }
+}
+
+impl fmt::Debug for Hook {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_some() {
+            write!(fmt, "Some(AllocatorCallback)")
+        } else {
+            write!(fmt, "None")
+        }
+    }
+}
+
+/// Block allocated from `InstrumentedAllocator`.
+/// Reports mapping and unmapping to the allocator's callback.
+#[derive(Debug)]
+pub struct InstrumentedBlock<B> {
+    block: B,
+    memory_type: u32,
+    hook: Hook,
+}
+
+impl<B> InstrumentedBlock<B> {
+    /// Get wrapped block.
+    pub fn inner(&self) -> &B {
+        &self.block
+    }
+}
+
+impl<B> Block for InstrumentedBlock<B>
+where
+    B: Block,
+{
+    type Memory = B::Memory;
+
+    #[inline]
+    fn properties(&self) -> Properties {
+        self.block.properties()
+    }
+
+    #[inline]
+    fn memory(&self) -> &B::Memory {
+        self.block.memory()
+    }
+
+    #[inline]
+    fn range(&self) -> Range<u64> {
+        self.block.range()
+    }
+
+    fn map<'a, D>(
+        &'a mut self,
+        device: &D,
+        range: Range<u64>,
+    ) -> Result<MappedRange<'a, B::Memory>, MappingError>
+    where
+        D: Device<Memory = B::Memory>,
+    {
+        let memory_type = self.memory_type;
+        self.hook.report(|| AllocatorEvent::Map {
+            memory_type,
+            range: range.clone(),
+        });
+        self.block.map(device, range)
+    }
+
+    fn unmap<D>(&mut self, device: &D)
+    where
+        D: Device<Memory = B::Memory>,
+    {
+        let memory_type = self.memory_type;
+        self.hook.report(|| AllocatorEvent::Unmap { memory_type });
+        self.block.unmap(device)
+    }
+}
+
+/// Allocator wrapper that reports every allocation, deallocation, mapping and unmapping
+/// to the callback.
+/// Without callback installed wrapper only forwards calls.
+#[derive(Debug)]
+pub struct InstrumentedAllocator<A> {
+    inner: A,
+    memory_type: u32,
+    hook: Hook,
+}
+
+impl<A> InstrumentedAllocator<A> {
+    /// Wrap allocator of the `memory_type`.
+    pub fn new(inner: A, memory_type: u32) -> Self {
+        InstrumentedAllocator {
+            inner,
+            memory_type,
+            hook: Hook::default(),
+        }
+    }
+
+    /// Install callback.
+    /// Blocks allocated after this call report to this callback.
+    pub fn set_callback(&mut self, callback: AllocatorCallback) {
+        self.hook = Hook(Some(callback));
+    }
+
+    /// Remove callback.
+    pub fn clear_callback(&mut self) {
+        self.hook = Hook(None);
+    }
+
+    /// Get wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Unwrap allocator.
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<A> Allocator for InstrumentedAllocator<A>
+where
+    A: Allocator,
+{
+    type Memory = A::Memory;
+    type Block = InstrumentedBlock<A::Block>;
+
+    fn alloc<D>(
+        &mut self,
+        device: &D,
+        size: u64,
+        align: u64,
+    ) -> Result<(InstrumentedBlock<A::Block>, u64), MemoryError>
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        let (block, allocated) = self.inner.alloc(device, size, align)?;
+        let memory_type = self.memory_type;
+        self.hook.report(|| AllocatorEvent::Alloc {
+            memory_type,
+            size,
+            align,
+            range: block.range(),
+            allocated,
+        });
+        Ok((
+            InstrumentedBlock {
+                block,
+                memory_type,
+                hook: self.hook.clone(),
+            },
+            allocated,
+        ))
+    }
+
+    fn free<D>(&mut self, device: &D, block: InstrumentedBlock<A::Block>) -> u64
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        let range = block.block.range();
+        let freed = self.inner.free(device, block.block);
+        let memory_type = self.memory_type;
+        self.hook.report(|| AllocatorEvent::Free {
+            memory_type,
+            range,
+            freed,
+        });
+        freed
+    }
+}
@@ -1,10 +1,13 @@
 //! This module provides `Allocator` trait and few allocators that implements the trait.
 
-use std::{any::Any, fmt};
+use std::{any::Any, fmt, ops::AddAssign};
 
 mod arena;
+mod capped;
 mod dedicated;
 mod dynamic;
+mod instrumented;
+mod recycling;
 // mod chunk;
 
 use block::Block;
@@ -14,10 +17,89 @@ use memory::Memory;
 
 pub use self::{
     arena::{ArenaAllocator, ArenaBlock, ArenaConfig},
-    dedicated::{DedicatedAllocator, DedicatedBlock},
-    dynamic::{DynamicAllocator, DynamicBlock, DynamicConfig},
+    capped::CappedAllocator,
+    dedicated::{
+        DedicatedAllocator, DedicatedBlock, DedicatedCacheConfig, DedicatedCacheStats,
+        MIN_DEDICATED_SIZE,
+    },
+    dynamic::{DynamicAllocator, DynamicBlock, DynamicConfig, FitStrategy},
+    instrumented::{
+        AllocatorCallback, AllocatorEvent, InstrumentedAllocator, InstrumentedBlock,
+    },
+    recycling::{RecyclingAllocator, RecyclingStats},
 };
 
+/// Kind of allocator that served the allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// Memory object per allocation. See `DedicatedAllocator`.
+    Dedicated,
+
+    /// Linear sub-allocation. See `ArenaAllocator`.
+    Arena,
+
+    /// Sub-allocation of fixed size blocks. See `DynamicAllocator`.
+    Dynamic,
+}
+
+/// Snapshot of allocator state for memory profiling.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AllocatorSnapshot {
+    /// Number of live blocks by size.
+    /// Bucket `i` counts blocks which size is at least `2^i` and less than `2^(i + 1)`.
+    /// Trailing empty buckets are omitted.
+    pub histogram: Vec<u64>,
+
+    /// Number of chunks blocks are sub-allocated from.
+    pub chunks: u64,
+
+    /// Average fraction of chunk memory not occupied by blocks.
+    pub fragmentation: f64,
+
+    /// Size of the largest contiguous free region
+    /// that can serve allocation without allocating new chunk.
+    pub largest_free: u64,
+}
+
+impl AllocatorSnapshot {
+    /// Get bucket of the histogram that counts blocks of `size`.
+    pub fn bucket(size: u64) -> usize {
+        debug_assert_ne!(size, 0);
+        63 - size.leading_zeros() as usize
+    }
+
+    /// Count `count` live blocks of `size` in the histogram.
+    pub(crate) fn add_blocks(&mut self, size: u64, count: u64) {
+        let bucket = Self::bucket(size);
+        if self.histogram.len() <= bucket {
+            self.histogram.resize(bucket + 1, 0);
+        }
+        self.histogram[bucket] += count;
+    }
+}
+
+/// Memory counters of allocator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocatorStats {
+    /// Bytes of device memory held by the allocator.
+    /// This includes memory retained for reuse.
+    pub allocated: u64,
+
+    /// Bytes handed out to live blocks.
+    pub used: u64,
+
+    /// Number of device memory objects held by the allocator.
+    pub memory_objects: u64,
+}
+
+impl AddAssign for AllocatorStats {
+    fn add_assign(&mut self, other: AllocatorStats) {
+        self.allocated += other.allocated;
+        self.used += other.used;
+        self.memory_objects += other.memory_objects;
+    }
+}
+
 /// Allocator trait implemented for various allocators.
 pub trait Allocator {
     /// Memory type.
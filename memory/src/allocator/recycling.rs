@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use allocator::Allocator;
+use block::Block;
+use device::Device;
+use error::{AllocationError, MemoryError};
+
+/// Statistics of `RecyclingAllocator`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecyclingStats {
+    /// Number of allocations served from the cache.
+    pub hits: u64,
+
+    /// Number of allocations forwarded to wrapped allocator.
+    pub misses: u64,
+
+    /// Number of cached blocks freed to wrapped allocator to fit the budget.
+    pub evictions: u64,
+
+    /// Total size of blocks retained in the cache.
+    pub cached: u64,
+}
+
+impl RecyclingStats {
+    /// Get fraction of allocations served from the cache.
+    /// Zero if nothing was allocated yet.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// Allocator wrapper that retains freed blocks to reuse them for later allocations.
+/// Smooths allocation spikes of streaming workloads
+/// where resources of similar sizes are created and destroyed every frame.
+///
+/// Blocks are bucketed by size.
+/// Request is served by the smallest cached block that is at least as large,
+/// but not twice as large, and satisfies alignment.
+/// When total size of cached blocks exceeds the budget
+/// least recently freed blocks are freed to wrapped allocator.
+///
+/// Wrapped allocator serves single memory type,
+/// so blocks of different memory types are never mixed.
+///
+/// Cached blocks must be released by `clear` or `into_inner` before the wrapper is dropped.
+#[derive(Debug)]
+pub struct RecyclingAllocator<A: Allocator> {
+    inner: A,
+    max_bytes: u64,
+    // Blocks of each size from least to most recently freed, with stamps of their frees.
+    buckets: BTreeMap<u64, VecDeque<(u64, A::Block)>>,
+    // Sizes of cached blocks by stamps of their frees.
+    order: BTreeMap<u64, u64>,
+    next_stamp: u64,
+    stats: RecyclingStats,
+}
+
+impl<A> RecyclingAllocator<A>
+where
+    A: Allocator,
+{
+    /// Wrap allocator retaining up to `max_bytes` of freed blocks.
+    pub fn new(inner: A, max_bytes: u64) -> Self {
+        RecyclingAllocator {
+            inner,
+            max_bytes,
+            buckets: BTreeMap::new(),
+            order: BTreeMap::new(),
+            next_stamp: 0,
+            stats: RecyclingStats::default(),
+        }
+    }
+
+    /// Get statistics of the cache.
+    pub fn stats(&self) -> RecyclingStats {
+        self.stats
+    }
+
+    /// Get budget of the cache.
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// Get wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Free all cached blocks to wrapped allocator.
+    /// Returns amount of memory returned to the device.
+    pub fn clear<D>(&mut self, device: &D) -> u64
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        let mut freed = 0;
+        while !self.order.is_empty() {
+            freed += self.evict(device);
+        }
+        freed
+    }
+
+    /// Free all cached blocks and unwrap allocator.
+    pub fn into_inner<D>(mut self, device: &D) -> A
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        self.clear(device);
+        self.inner
+    }
+
+    fn take(&mut self, size: u64, align: u64) -> Option<A::Block> {
+        let found = self
+            .buckets
+            .range(size..size.saturating_mul(2))
+            .filter_map(|(&block_size, bucket)| {
+                // Prefer most recently freed block.
+                bucket
+                    .iter()
+                    .rposition(|&(_, ref block)| block.range().start % align == 0)
+                    .map(|index| (block_size, index))
+            })
+            .next();
+
+        let (block_size, index) = found?;
+        let (stamp, block) = {
+            let bucket = self.buckets.get_mut(&block_size).unwrap();
+            let entry = bucket.remove(index).unwrap();
+            if bucket.is_empty() {
+                self.buckets.remove(&block_size);
+            }
+            entry
+        };
+        self.order.remove(&stamp);
+        self.stats.cached -= block_size;
+        Some(block)
+    }
+
+    /// Free least recently cached block to wrapped allocator.
+    fn evict<D>(&mut self, device: &D) -> u64
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        let (stamp, size) = self
+            .order
+            .iter()
+            .map(|(&stamp, &size)| (stamp, size))
+            .next()
+            .expect("Cache must not be empty");
+        self.order.remove(&stamp);
+        let block = {
+            let bucket = self.buckets.get_mut(&size).unwrap();
+            // Oldest block of the bucket is the oldest one overall.
+            let (oldest, block) = bucket.pop_front().unwrap();
+            debug_assert_eq!(oldest, stamp);
+            if bucket.is_empty() {
+                self.buckets.remove(&size);
+            }
+            block
+        };
+        self.stats.cached -= size;
+        self.stats.evictions += 1;
+        self.inner.free(device, block)
+    }
+}
+
+impl<A> Allocator for RecyclingAllocator<A>
+where
+    A: Allocator,
+{
+    type Memory = A::Memory;
+    type Block = A::Block;
+
+    fn alloc<D>(
+        &mut self,
+        device: &D,
+        size: u64,
+        align: u64,
+    ) -> Result<(A::Block, u64), MemoryError>
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        if !align.is_power_of_two() {
+            return Err(AllocationError::BadAlignment(align).into());
+        }
+        match self.take(size, align) {
+            Some(block) => {
+                self.stats.hits += 1;
+                Ok((block, 0))
+            }
+            None => {
+                self.stats.misses += 1;
+                self.inner.alloc(device, size, align)
+            }
+        }
+    }
+
+    fn free<D>(&mut self, device: &D, block: A::Block) -> u64
+    where
+        D: Device<Memory = A::Memory>,
+    {
+        let range = block.range();
+        let size = range.end - range.start;
+        if size > self.max_bytes {
+            return self.inner.free(device, block);
+        }
+
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.buckets
+            .entry(size)
+            .or_insert_with(VecDeque::new)
+            .push_back((stamp, block));
+        self.order.insert(stamp, size);
+        self.stats.cached += size;
+
+        let mut freed = 0;
+        while self.stats.cached > self.max_bytes {
+            freed += self.evict(device);
+        }
+        freed
+    }
+}
@@ -4,6 +4,70 @@ use mapping::MappedRange;
 use memory::Properties;
 use std::{any::Any, ops::Range};
 
+#[cfg(feature = "tags")]
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+/// Application-defined data attached to the block.
+#[cfg(feature = "tags")]
+pub type Tag = Box<dyn Any + Send + Sync>;
+
+/// Optional tag stored in blocks.
+/// Nothing is allocated until tag is set.
+#[cfg(feature = "tags")]
+#[derive(Default)]
+pub(crate) struct TagSlot(Option<Tag>);
+
+#[cfg(feature = "tags")]
+impl TagSlot {
+    pub(crate) fn set<U: Any + Send + Sync>(&mut self, tag: U) {
+        self.0 = Some(Box::new(tag));
+    }
+
+    pub(crate) fn get<U: Any>(&self) -> Option<&U> {
+        self.0.as_ref().and_then(|tag| tag.downcast_ref())
+    }
+
+    pub(crate) fn take(&mut self) -> Option<Tag> {
+        self.0.take()
+    }
+}
+
+#[cfg(feature = "tags")]
+impl Debug for TagSlot {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        if self.0.is_some() {
+            write!(fmt, "Some(Tag)")
+        } else {
+            write!(fmt, "None")
+        }
+    }
+}
+
+/// Tag accessors of the block type with `tag: TagSlot` field.
+/// Blocks have tags only with `tags` feature.
+macro_rules! tag_accessors {
+    () => {
+        /// Attach application-defined tag to the block.
+        /// Previous tag is dropped.
+        #[cfg(feature = "tags")]
+        pub fn set_tag<U: ::std::any::Any + Send + Sync>(&mut self, tag: U) {
+            self.tag.set(tag)
+        }
+
+        /// Get tag attached to the block if it is of type `U`.
+        #[cfg(feature = "tags")]
+        pub fn tag<U: ::std::any::Any>(&self) -> Option<&U> {
+            self.tag.get()
+        }
+
+        /// Detach tag from the block.
+        #[cfg(feature = "tags")]
+        pub fn take_tag(&mut self) -> Option<::block::Tag> {
+            self.tag.take()
+        }
+    };
+}
+
 /// Block that owns a `Range` of the `Memory`.
 /// Implementor must ensure that there can't be any other blocks
 /// with overlapping range (either through type system or safety notes for unsafe functions).
@@ -19,8 +83,37 @@ pub trait Block {
     fn memory(&self) -> &Self::Memory;
 
     /// Get memory range owned by this block.
+    /// Range is relative to the start of the memory object returned by `memory`
+    /// and is not affected by mapping.
     fn range(&self) -> Range<u64>;
 
+    /// Check if memory of the block is visible to host and can be mapped.
+    fn is_host_visible(&self) -> bool {
+        self.properties().host_visible()
+    }
+
+    /// Check if host access to the mapped range of the block is coherent.
+    /// Non-coherent ranges must be flushed and invalidated.
+    fn is_host_coherent(&self) -> bool {
+        self.properties().host_coherent()
+    }
+
+    /// Check if memory of the block is local for device.
+    fn is_device_local(&self) -> bool {
+        self.properties().device_local()
+    }
+
+    /// Check if host access to the mapped range of the block is cached.
+    fn is_cached(&self) -> bool {
+        self.properties().host_cached()
+    }
+
+    /// Get offset of the block in the memory object returned by `memory`.
+    /// This is the offset resources are bound at.
+    fn offset(&self) -> u64 {
+        self.range().start
+    }
+
     /// Get mapping for the buffer range.
     /// Memory writes to the region performed by device become available for the host.
     fn map<'a, D>(
@@ -1,6 +1,11 @@
 use error::*;
+use external::{ExternalHandle, ExternalHandleTypes};
 use std::{ops::Range, ptr::NonNull};
 
+/// Largest `nonCoherentAtomSize` device can report.
+/// Ranges aligned to it are aligned to atoms of any device.
+pub const MAX_NON_COHERENT_ATOM_SIZE: u64 = 256;
+
 /// Trait for memory allocation and mapping.
 pub trait Device: Sized {
     /// Memory type that can be used with this device.
@@ -39,4 +44,44 @@ pub trait Device: Sized {
         &self,
         regions: impl IntoIterator<Item = (&'a Self::Memory, Range<u64>)>,
     ) -> Result<(), OutOfMemoryError>;
+
+    /// Get alignment that base of every memory object of memory type `index` satisfies.
+    /// Default implementation reports that memory objects satisfy any alignment
+    /// as memory allocated by Vulkan satisfies alignment of any resource of its type.
+    fn allocation_alignment(&self, _index: u32) -> u64 {
+        u64::max_value()
+    }
+
+    /// Get size and alignment in bytes of ranges of non-coherent memory passed to `flush` and `invalidate`.
+    /// Must be filled from device limit `nonCoherentAtomSize`.
+    /// Default implementation reports single byte atoms.
+    fn non_coherent_atom_size(&self) -> u64 {
+        1
+    }
+
+    /// Allocate memory object that can be exported as handles of `handle_types`.
+    /// Default implementation reports that exportable memory is not supported.
+    ///
+    /// # Parameters
+    /// `size`  - size of the memory object to allocate.
+    /// `index` - memory type index.
+    /// `handle_types` - handle types the memory object can be exported as.
+    unsafe fn allocate_exportable(
+        &self,
+        _index: u32,
+        _size: u64,
+        handle_types: ExternalHandleTypes,
+    ) -> Result<Self::Memory, AllocationError> {
+        Err(AllocationError::ExportUnsupported(handle_types))
+    }
+
+    /// Export memory object allocated by `allocate_exportable` as handle of single `handle_type`.
+    /// Default implementation reports that exporting is not supported.
+    unsafe fn export_memory(
+        &self,
+        _memory: &Self::Memory,
+        _handle_type: ExternalHandleTypes,
+    ) -> Result<ExternalHandle, ExportError> {
+        Err(ExportError::Unsupported)
+    }
 }
@@ -1,3 +1,4 @@
+use external::ExternalHandleTypes;
 use usage::UsageValue;
 
 /// Typical memory error - out of available memory.
@@ -23,6 +24,14 @@ pub enum MappingError {
     #[fail(display = "Memory is not HOST_VISIBLE and can't be mapped")]
     HostInvisible,
 
+    /// Attempt to map memory with `PROTECTED` property.
+    #[fail(display = "Memory is PROTECTED and can't be mapped")]
+    Protected,
+
+    /// Attempt to map memory with `LAZILY_ALLOCATED` property.
+    #[fail(display = "Memory is LAZILY_ALLOCATED and can't be mapped")]
+    LazilyAllocated,
+
     /// Attempt to map memory out of bounds.
     #[fail(display = "Mapping range is out of bound")]
     OutOfBounds,
@@ -54,6 +63,20 @@ pub enum MappingError {
         /// Offset that doesn't satisfy alignment.
         offset: usize,
     },
+
+    /// Attempt to interpret mapped range as slice of elements that don't fit it exactly.
+    #[fail(
+        display = "Range of {} bytes doesn't hold whole number of {}-byte elements",
+        size,
+        element
+    )]
+    InvalidSize {
+        /// Length of the range.
+        size: u64,
+
+        /// Size of the element.
+        element: usize,
+    },
 }
 
 impl From<OutOfMemoryError> for MappingError {
@@ -80,6 +103,22 @@ pub enum AllocationError {
         _1
     )]
     NoSuitableMemory(u32, UsageValue),
+
+    /// Requested alignment is not a power of two.
+    #[fail(display = "Alignment {} is not a power of two", _0)]
+    BadAlignment(u64),
+
+    /// Memory type index is out of memory types of the device.
+    #[fail(display = "Memory type {} doesn't exist, device has {} types", _0, _1)]
+    InvalidMemoryType(u32, u32),
+
+    /// Memory type is not allowed by memory type bits of the resource.
+    #[fail(display = "Memory type {} is not in mask: ({})", _0, _1)]
+    IncompatibleMemoryType(u32, u32),
+
+    /// Device can't allocate memory exportable as requested handle types.
+    #[fail(display = "Memory exportable as {:?} is not supported", _0)]
+    ExportUnsupported(ExternalHandleTypes),
 }
 
 impl From<OutOfMemoryError> for AllocationError {
@@ -88,6 +127,47 @@ impl From<OutOfMemoryError> for AllocationError {
     }
 }
 
+/// Possible cause of memory export failure.
+#[derive(Clone, Copy, Debug, Fail)]
+pub enum ExportError {
+    /// Memory wasn't allocated as exportable with requested handle type.
+    #[fail(
+        display = "Memory is exportable as {:?}, not {:?}",
+        exportable,
+        requested
+    )]
+    NotExportable {
+        /// Handle types memory is exportable as.
+        exportable: ExternalHandleTypes,
+
+        /// Handle type requested.
+        requested: ExternalHandleTypes,
+    },
+
+    /// Block is sub-allocated from memory object shared with other blocks.
+    /// Only dedicated memory can be exported.
+    #[fail(display = "Only dedicated memory can be exported")]
+    NotDedicated,
+
+    /// Device doesn't support exporting memory.
+    #[fail(display = "Exporting memory is not supported")]
+    Unsupported,
+
+    /// Implementation can't create more handles.
+    #[fail(display = "Can't create more handles")]
+    TooManyObjects,
+
+    /// Out of either host or device memory.
+    #[fail(display = "{}", _0)]
+    OutOfMemoryError(OutOfMemoryError),
+}
+
+impl From<OutOfMemoryError> for ExportError {
+    fn from(error: OutOfMemoryError) -> Self {
+        ExportError::OutOfMemoryError(error)
+    }
+}
+
 /// Generic memory error.
 #[derive(Clone, Copy, Debug, Fail)]
 pub enum MemoryError {
@@ -102,6 +182,32 @@ pub enum MemoryError {
     /// Error occurred during allocation.
     #[fail(display = "{}", _0)]
     AllocationError(AllocationError),
+
+    /// Allocator can't satisfy requested alignment.
+    #[fail(
+        display = "Alignment {} exceeds alignment {} supported by allocator",
+        align,
+        supported
+    )]
+    InvalidAlignment {
+        /// Requested alignment.
+        align: u64,
+
+        /// Largest alignment allocator can satisfy.
+        supported: u64,
+    },
+}
+
+impl MemoryError {
+    /// Check if error is caused by exhausted host or device memory.
+    pub fn is_out_of_memory(&self) -> bool {
+        match *self {
+            MemoryError::OutOfMemoryError(_)
+            | MemoryError::AllocationError(AllocationError::OutOfMemoryError(_))
+            | MemoryError::MappingError(MappingError::OutOfMemoryError(_)) => true,
+            _ => false,
+        }
+    }
 }
 
 impl From<OutOfMemoryError> for MemoryError {
@@ -0,0 +1,31 @@
+bitflags! {
+    /// Types of handles memory can be exported as to share it with other APIs or processes.
+    /// See Vulkan docs for detailed info:
+    /// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkExternalMemoryHandleTypeFlagBits.html>
+    #[repr(transparent)]
+    pub struct ExternalHandleTypes: u32 {
+        /// POSIX file descriptor that has meaning only for the same driver.
+        const OPAQUE_FD = 0x00000001;
+
+        /// Windows NT handle that has meaning only for the same driver.
+        const OPAQUE_WIN32 = 0x00000002;
+
+        /// Windows global share handle that has meaning only for the same driver.
+        const OPAQUE_WIN32_KMT = 0x00000004;
+
+        /// Linux dma-buf file descriptor.
+        const DMA_BUF = 0x00000200;
+    }
+}
+
+/// OS handle of exported memory.
+/// Ownership of the handle is transferred to the caller,
+/// which must close it or import it elsewhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExternalHandle {
+    /// POSIX file descriptor.
+    Fd(i32),
+
+    /// Windows handle.
+    Win32(usize),
+}
@@ -1,11 +1,16 @@
-use std::ops::Range;
+#[cfg(feature = "tags")]
+use std::any::Any;
+use std::{cmp::max, collections::HashMap, ops::Range, thread::sleep, time::Duration};
 
 use allocator::*;
 use smallvec::SmallVec;
 
+#[cfg(feature = "tags")]
+use block::Tag;
 use block::Block;
 use device::Device;
 use error::*;
+use external::{ExternalHandle, ExternalHandleTypes};
 use mapping::*;
 use memory::*;
 use usage::{Usage, UsageValue};
@@ -23,11 +28,128 @@ pub struct Config {
     // chunk: Option<ChunkConfig>,
 }
 
+/// Memory block requested from `Heaps`.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockRequest<U> {
+    /// Memory type bits allowed by the resource.
+    pub mask: u32,
+
+    /// Intended usage of the memory.
+    pub usage: U,
+
+    /// Size of the block.
+    pub size: u64,
+
+    /// Alignment of the block offset.
+    pub align: u64,
+}
+
+/// Minimal offset alignments of buffer ranges bound to descriptors.
+/// Must be filled from device limits
+/// `minUniformBufferOffsetAlignment`, `minStorageBufferOffsetAlignment`
+/// and `minTexelBufferOffsetAlignment`.
+/// Default value has maximal alignments allowed by the specification
+/// and is suitable for any device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BufferAlignments {
+    /// Offset alignment of uniform buffers.
+    pub uniform: u64,
+
+    /// Offset alignment of storage buffers.
+    pub storage: u64,
+
+    /// Offset alignment of uniform and storage texel buffers.
+    pub texel: u64,
+}
+
+impl Default for BufferAlignments {
+    fn default() -> Self {
+        BufferAlignments {
+            uniform: 256,
+            storage: 256,
+            texel: 256,
+        }
+    }
+}
+
+/// Role of the buffer memory is allocated for.
+/// Defines offset alignment and memory usage of the allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BufferRole {
+    /// Uniform buffer updated by the host, typically every frame.
+    Uniform,
+
+    /// Storage buffer accessed by the device.
+    Storage,
+
+    /// Uniform or storage texel buffer accessed by the device.
+    Texel,
+
+    /// Vertex buffer.
+    Vertex,
+
+    /// Index buffer. Aligned for 32-bit indices.
+    Index,
+}
+
+impl BufferRole {
+    /// Get offset alignment required for buffers of the role.
+    pub fn alignment(&self, alignments: &BufferAlignments) -> u64 {
+        match *self {
+            BufferRole::Uniform => alignments.uniform,
+            BufferRole::Storage => alignments.storage,
+            BufferRole::Texel => alignments.texel,
+            BufferRole::Vertex => 1,
+            BufferRole::Index => 4,
+        }
+    }
+
+    /// Get memory usage of buffers of the role.
+    /// Uniform buffers are written by the host, other buffers are expected to be
+    /// filled by the device or by uploads.
+    pub fn usage(&self) -> UsageValue {
+        match *self {
+            BufferRole::Uniform => UsageValue::Dynamic,
+            _ => UsageValue::Data,
+        }
+    }
+}
+
+/// Memory utilization of single heap.
+/// Accounts memory allocated by all memory types that share the heap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeapUtilization {
+    /// Total size of the heap.
+    pub size: u64,
+
+    /// Bytes of device memory allocated from the heap.
+    /// Size of lazily allocated memory is accounted in full,
+    /// although device may commit only part of it.
+    pub used: u64,
+
+    /// Bytes of sub-allocated blocks in use that exceed requested sizes.
+    /// This is memory traded for bounded number of size classes.
+    pub wasted: u64,
+}
+
+/// Memory statistics of `Heaps`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Counters of each memory type summed over its allocators.
+    pub types: Vec<AllocatorStats>,
+
+    /// Counters of each heap summed over memory types that share the heap.
+    pub heaps: Vec<AllocatorStats>,
+}
+
 /// Heaps available on particular physical device.
 #[derive(Debug)]
 pub struct Heaps<T> {
     types: Vec<MemoryType<T>>,
     heaps: Vec<MemoryHeap>,
+    suitable: HashMap<(u32, UsageValue, Properties), SmallVec<[u32; 32]>>,
+    buffer_alignments: BufferAlignments,
 }
 
 impl<T: 'static> Heaps<T> {
@@ -60,14 +182,30 @@ impl<T: 'static> Heaps<T> {
                     MemoryType::new(memory_type, heap_index, properties, config)
                 }).collect(),
             heaps,
+            suitable: HashMap::new(),
+            buffer_alignments: BufferAlignments::default(),
         }
     }
 
+    /// Set offset alignments of buffers queried from device limits.
+    pub fn set_buffer_alignments(&mut self, alignments: BufferAlignments) {
+        self.buffer_alignments = alignments;
+    }
+
+    /// Get offset alignments of buffers.
+    pub fn buffer_alignments(&self) -> BufferAlignments {
+        self.buffer_alignments
+    }
+
     /// Allocate memory block
     /// from one of memory types specified by `mask`,
     /// for intended `usage`,
     /// with `size`
     /// and `align` requirements.
+    /// Memory types suitable for `mask` and `usage` are cached on first request.
+    /// Allocations smaller than `MIN_DEDICATED_SIZE` are sub-allocated
+    /// by dynamic allocator regardless of `usage` if memory type has one.
+    /// Arena allocator serves only `Upload` and `Download` usages.
     pub fn allocate<D, U>(
         &mut self,
         device: &D,
@@ -80,32 +218,309 @@ impl<T: 'static> Heaps<T> {
         D: Device<Memory = T>,
         U: Usage,
     {
+        let usage = usage.value();
+        let memory_index = self.select(mask, usage, Properties::empty(), size, align)?;
+        self.allocate_from(device, memory_index, usage, size, align)
+    }
+
+    /// Allocate memory block
+    /// from one of memory types specified by `mask` that have all `properties`,
+    /// for intended `usage`,
+    /// with `size`
+    /// and `align` requirements.
+    /// Among types with required properties the one that fits `usage` best is chosen,
+    /// so e.g. `HOST_CACHED` may be demanded for readback regardless of `usage` preferences.
+    pub fn allocate_with_properties<D, U>(
+        &mut self,
+        device: &D,
+        mask: u32,
+        properties: Properties,
+        usage: U,
+        size: u64,
+        align: u64,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+        U: Usage,
+    {
+        let usage = usage.value();
+        let memory_index = self.select(mask, usage, properties, size, align)?;
+        self.allocate_from(device, memory_index, usage, size, align)
+    }
+
+    /// Allocate dedicated memory block that can be exported as handles of `handle_types`
+    /// from one of memory types specified by `mask`,
+    /// for intended `usage`,
+    /// with `size`
+    /// and `align` requirements.
+    /// Exportable memory is never sub-allocated regardless of `size`,
+    /// as exported handle refers to whole memory object.
+    pub fn allocate_exportable<D, U>(
+        &mut self,
+        device: &D,
+        mask: u32,
+        usage: U,
+        size: u64,
+        align: u64,
+        handle_types: ExternalHandleTypes,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+        U: Usage,
+    {
+        let memory_index = self.select(mask, usage.value(), Properties::empty(), size, align)?;
+
+        let ref mut memory_type = self.types[memory_index as usize];
+        let ref mut memory_heap = self.heaps[memory_type.heap_index];
+
+        let (block, allocated) = memory_type
+            .dedicated
+            .alloc_exportable(device, size, handle_types)?;
+        memory_heap.used += allocated;
+
+        Ok(MemoryBlock {
+            block: BlockFlavor::Dedicated(block),
+            memory_index,
+        })
+    }
+
+    /// Allocate memory block
+    /// from memory type with `memory_type` index chosen by the user,
+    /// for intended `usage`,
+    /// with `size`
+    /// and `align` requirements.
+    /// Unlike `allocate` memory type is used verbatim without matching properties to `usage`,
+    /// which lets engines with hand-tuned memory type assignments override the selection.
+    /// `mask` is memory type bits of the resource requirements
+    /// and must contain the type.
+    pub fn allocate_from_type<D, U>(
+        &mut self,
+        device: &D,
+        memory_type: u32,
+        mask: u32,
+        usage: U,
+        size: u64,
+        align: u64,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+        U: Usage,
+    {
+        debug_assert!(fits_u32(self.types.len()));
+        let count = self.types.len() as u32;
+        if memory_type >= count {
+            return Err(AllocationError::InvalidMemoryType(memory_type, count).into());
+        }
+        // Types beyond 32 bits of the mask are never allowed.
+        if memory_type >= 32 || mask & (1 << memory_type) == 0 {
+            return Err(AllocationError::IncompatibleMemoryType(memory_type, mask).into());
+        }
+        self.allocate_from(device, memory_type, usage.value(), size, align)
+    }
+
+    /// Select memory type specified by `mask` that has `required` properties, fits `usage`
+    /// and resides in heap with enough memory available.
+    fn select(
+        &mut self,
+        mask: u32,
+        usage: UsageValue,
+        required: Properties,
+        size: u64,
+        align: u64,
+    ) -> Result<u32, MemoryError> {
         debug_assert!(fits_u32(self.types.len()));
 
-        let (memory_index, _, _) = {
-            let suitable_types = self
-                .types
-                .iter()
-                .enumerate()
-                .filter(|(index, _)| (mask & (1u32 << index)) != 0)
-                .filter_map(|(index, mt)| {
-                    usage
-                        .memory_fitness(mt.properties)
-                        .map(move |fitness| (index, mt, fitness))
-                }).collect::<SmallVec<[_; 64]>>();
-
-            if suitable_types.is_empty() {
-                return Err(AllocationError::NoSuitableMemory(mask, usage.value()).into());
+        // Memory types are fixed for device, so suitable types are selected once per key.
+        let memory_index = {
+            let ref types = self.types;
+            let ref heaps = self.heaps;
+            let suitable = self
+                .suitable
+                .entry((mask, usage, required))
+                .or_insert_with(|| {
+                    suitable_types(types.iter().map(|mt| mt.properties), mask, required, &usage)
+                });
+
+            if suitable.is_empty() {
+                return Err(AllocationError::NoSuitableMemory(mask, usage).into());
             }
 
-            suitable_types
-                .into_iter()
-                .filter(|(_, mt, _)| self.heaps[mt.heap_index].available() > size + align)
-                .max_by_key(|&(_, _, fitness)| fitness)
+            suitable
+                .iter()
+                .cloned()
+                .find(|&index| heaps[types[index as usize].heap_index].available() > size + align)
                 .ok_or(OutOfMemoryError::HeapsExhausted)?
         };
 
-        self.allocate_from::<D, U>(device, memory_index as u32, usage, size, align)
+        Ok(memory_index)
+    }
+
+    /// Allocate memory block for buffer of the `role`
+    /// from one of memory types specified by `mask`,
+    /// with `size` and `align` requirements.
+    /// Block is aligned to both `align` and offset alignment of the role,
+    /// memory usage is chosen by the role.
+    /// All alignments are powers of two, so the larger one satisfies both.
+    pub fn allocate_buffer<D>(
+        &mut self,
+        device: &D,
+        mask: u32,
+        role: BufferRole,
+        size: u64,
+        align: u64,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        let align = max(align, role.alignment(&self.buffer_alignments));
+        self.allocate(device, mask, role.usage(), size, align)
+    }
+
+    /// Allocate memory block for uniform buffer.
+    /// See `allocate_buffer`.
+    pub fn alloc_uniform<D>(
+        &mut self,
+        device: &D,
+        mask: u32,
+        size: u64,
+        align: u64,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        self.allocate_buffer(device, mask, BufferRole::Uniform, size, align)
+    }
+
+    /// Allocate memory block for storage buffer.
+    /// See `allocate_buffer`.
+    pub fn alloc_storage<D>(
+        &mut self,
+        device: &D,
+        mask: u32,
+        size: u64,
+        align: u64,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        self.allocate_buffer(device, mask, BufferRole::Storage, size, align)
+    }
+
+    /// Allocate memory block for vertex buffer.
+    /// See `allocate_buffer`.
+    pub fn alloc_vertex<D>(
+        &mut self,
+        device: &D,
+        mask: u32,
+        size: u64,
+        align: u64,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        self.allocate_buffer(device, mask, BufferRole::Vertex, size, align)
+    }
+
+    /// Allocate memory block for index buffer.
+    /// See `allocate_buffer`.
+    pub fn alloc_index<D>(
+        &mut self,
+        device: &D,
+        mask: u32,
+        size: u64,
+        align: u64,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        self.allocate_buffer(device, mask, BufferRole::Index, size, align)
+    }
+
+    /// Allocate memory block retrying on out-of-memory errors.
+    /// Up to `attempts` allocations are tried.
+    /// After each failed one `try_free` is called to release some memory,
+    /// e.g. by freeing blocks of completed frames, and thread sleeps for `backoff`.
+    /// Stops early if `try_free` returns `false` because nothing was freed.
+    /// Returns error of the last attempt if all attempts fail.
+    pub fn allocate_with_retry<D, U, F>(
+        &mut self,
+        device: &D,
+        request: BlockRequest<U>,
+        attempts: u32,
+        backoff: Duration,
+        mut try_free: F,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+        U: Usage + Copy,
+        F: FnMut(&mut Self) -> bool,
+    {
+        let BlockRequest {
+            mask,
+            usage,
+            size,
+            align,
+        } = request;
+        let mut attempt = 1;
+        loop {
+            match self.allocate(device, mask, usage, size, align) {
+                Err(error) if error.is_out_of_memory() && attempt < attempts => {
+                    if !try_free(self) {
+                        return Err(error);
+                    }
+                    if backoff != Duration::default() {
+                        sleep(backoff);
+                    }
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Allocate memory block evicting application resources when heaps run out of memory.
+    /// Before the allocation fails `evict` is called with requested `size`
+    /// and index of the memory type allocation failed from,
+    /// or of the most suitable memory type if no heap has enough memory available.
+    /// It may free blocks of lower-priority resources, e.g. streamed textures,
+    /// and must return number of bytes freed.
+    /// Allocation is retried until it succeeds or `evict` frees nothing.
+    pub fn allocate_with_eviction<D, U, F>(
+        &mut self,
+        device: &D,
+        mask: u32,
+        usage: U,
+        size: u64,
+        align: u64,
+        mut evict: F,
+    ) -> Result<MemoryBlock<T>, MemoryError>
+    where
+        D: Device<Memory = T>,
+        U: Usage + Copy,
+        F: FnMut(&mut Self, u64, u32) -> u64,
+    {
+        let usage = usage.value();
+        loop {
+            let selected = self.select(mask, usage, Properties::empty(), size, align);
+            let (memory_type, error) = match selected {
+                Ok(memory_index) => {
+                    match self.allocate_from(device, memory_index, usage, size, align) {
+                        Err(error) if error.is_out_of_memory() => (memory_index, error),
+                        result => return result,
+                    }
+                }
+                Err(error) if error.is_out_of_memory() => {
+                    // No heap has enough memory, so evict from the most suitable type.
+                    // Suitable types are cached by the failed selection.
+                    let memory_index = self.suitable[&(mask, usage, Properties::empty())][0];
+                    (memory_index, error)
+                }
+                Err(error) => return Err(error),
+            };
+            if evict(self, size, memory_type) == 0 {
+                return Err(error);
+            }
+        }
     }
 
     /// Allocate memory block
@@ -159,6 +574,38 @@ impl<T: 'static> Heaps<T> {
         memory_heap.used -= freed;
     }
 
+    /// Get utilization of each heap.
+    /// Memory allocated by all memory types is summed per heap they reside in.
+    pub fn heap_utilization(&self) -> Vec<HeapUtilization> {
+        self.heaps
+            .iter()
+            .enumerate()
+            .map(|(index, heap)| HeapUtilization {
+                size: heap.size,
+                used: heap.used,
+                wasted: self
+                    .types
+                    .iter()
+                    .filter(|memory_type| memory_type.heap_index == index)
+                    .map(|memory_type| memory_type.wasted())
+                    .sum(),
+            }).collect()
+    }
+
+    /// Get memory statistics of each memory type and heap.
+    pub fn stats(&self) -> Stats {
+        let types = self
+            .types
+            .iter()
+            .map(|memory_type| memory_type.stats())
+            .collect::<Vec<_>>();
+        let mut heaps = vec![AllocatorStats::default(); self.heaps.len()];
+        for (memory_type, &stats) in self.types.iter().zip(&types) {
+            heaps[memory_type.heap_index] += stats;
+        }
+        Stats { types, heaps }
+    }
+
     /// Dispose of allocator.
     /// Cleanup allocators before dropping.
     /// Will panic if memory instances are left allocated.
@@ -172,6 +619,34 @@ impl<T: 'static> Heaps<T> {
     }
 }
 
+/// Get indices of memory types specified by `mask` that have `required` properties and fit `usage`.
+/// Memory types are given by their properties in order of indices.
+/// Indices are sorted from the best fitting type to the worst.
+/// Types with equal fitness are sorted from the last to the first.
+pub(crate) fn suitable_types<P, U>(
+    types: P,
+    mask: u32,
+    required: Properties,
+    usage: &U,
+) -> SmallVec<[u32; 32]>
+where
+    P: IntoIterator<Item = Properties>,
+    U: Usage,
+{
+    let mut suitable = types
+        .into_iter()
+        .enumerate()
+        .filter(|&(index, _)| index < 32 && (mask & (1u32 << index)) != 0)
+        .filter(|&(_, properties)| properties.contains(required))
+        .filter_map(|(index, properties)| {
+            usage
+                .memory_fitness(properties)
+                .map(move |fitness| (fitness, index as u32))
+        }).collect::<SmallVec<[_; 32]>>();
+    suitable.sort_by(|a, b| b.cmp(a));
+    suitable.into_iter().map(|(_, index)| index).collect()
+}
+
 /// Memory block allocated from `Heaps`.
 #[derive(Debug)]
 pub struct MemoryBlock<T> {
@@ -184,6 +659,32 @@ impl<T> MemoryBlock<T> {
     pub fn memory_type(&self) -> u32 {
         self.memory_index
     }
+
+    /// Get kind of allocator that served the block.
+    pub fn kind(&self) -> Kind {
+        match self.block {
+            BlockFlavor::Dedicated(_) => Kind::Dedicated,
+            BlockFlavor::Arena(_) => Kind::Arena,
+            BlockFlavor::Dynamic(_) => Kind::Dynamic,
+        }
+    }
+
+    /// Export memory of the block as OS handle of `handle_type`.
+    /// Block must be allocated by `Heaps::allocate_exportable`.
+    pub fn export_handle<D>(
+        &self,
+        device: &D,
+        handle_type: ExternalHandleTypes,
+    ) -> Result<ExternalHandle, ExportError>
+    where
+        T: 'static,
+        D: Device<Memory = T>,
+    {
+        match self.block {
+            BlockFlavor::Dedicated(ref block) => block.export_handle(device, handle_type),
+            _ => Err(ExportError::NotDedicated),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -224,6 +725,27 @@ macro_rules! any_block {
     }};
 }
 
+impl<T> MemoryBlock<T> {
+    /// Attach application-defined tag to the block.
+    /// Previous tag is dropped.
+    #[cfg(feature = "tags")]
+    pub fn set_tag<U: Any + Send + Sync>(&mut self, tag: U) {
+        any_block!(&mut self.block => block.set_tag(tag))
+    }
+
+    /// Get tag attached to the block if it is of type `U`.
+    #[cfg(feature = "tags")]
+    pub fn tag<U: Any>(&self) -> Option<&U> {
+        any_block!(&self.block => block.tag())
+    }
+
+    /// Detach tag from the block.
+    #[cfg(feature = "tags")]
+    pub fn take_tag(&mut self) -> Option<Tag> {
+        any_block!(&mut self.block => block.take_tag())
+    }
+}
+
 impl<T: 'static> Block for MemoryBlock<T> {
     type Memory = T;
 
@@ -345,6 +867,15 @@ impl<T: 'static> MemoryType<T> {
                     .alloc(device, size, align)
                     .map(|(block, allocated)| (BlockFlavor::Dynamic(block), allocated))
             }
+            // Tiny transient allocations go to dynamic allocator if there is no arena.
+            // Long-lived allocations never go to arena as they would pin its chunks.
+            (_, _, Some(ref mut dynamic))
+                if size < MIN_DEDICATED_SIZE && size <= dynamic.max_allocation() =>
+            {
+                dynamic
+                    .alloc(device, size, align)
+                    .map(|(block, allocated)| (BlockFlavor::Dynamic(block), allocated))
+            }
             _ => self
                 .dedicated
                 .alloc(device, size, align)
@@ -364,10 +895,26 @@ impl<T: 'static> MemoryType<T> {
         }
     }
 
+    fn stats(&self) -> AllocatorStats {
+        let mut stats = self.dedicated.stats();
+        if let Some(ref arena) = self.arena {
+            stats += arena.stats();
+        }
+        if let Some(ref dynamic) = self.dynamic {
+            stats += dynamic.stats();
+        }
+        stats
+    }
+
+    fn wasted(&self) -> u64 {
+        self.dynamic.as_ref().map_or(0, |dynamic| dynamic.wasted())
+    }
+
     fn dispose<D>(self, device: &D)
     where
         D: Device<Memory = T>,
     {
+        self.dedicated.dispose(device);
         if let Some(arena) = self.arena {
             arena.dispose(device);
         }
@@ -1,15 +1,113 @@
 use ash::{
     self,
-    version::{DeviceV1_0, FunctionPointers},
+    version::{DeviceV1_0, FunctionPointers, InstanceV1_0},
 };
-use device::Device;
+use device::{Device, MAX_NON_COHERENT_ATOM_SIZE};
 use error::*;
+use external::{ExternalHandle, ExternalHandleTypes};
 use smallvec::SmallVec;
 use std::{
+    ffi::CString,
+    fmt, mem,
     ops::Range,
     ptr::{null, null_mut, NonNull},
 };
 
+/// `VkExportMemoryAllocateInfo`. External memory structures are missing from ash bindings.
+#[repr(C)]
+struct ExportMemoryAllocateInfo {
+    s_type: i32,
+    p_next: *const ash::vk::c_void,
+    handle_types: u32,
+}
+
+/// `VkMemoryGetFdInfoKHR`.
+#[repr(C)]
+struct MemoryGetFdInfo {
+    s_type: i32,
+    p_next: *const ash::vk::c_void,
+    memory: ash::vk::DeviceMemory,
+    handle_type: u32,
+}
+
+const STRUCTURE_TYPE_EXPORT_MEMORY_ALLOCATE_INFO: i32 = 1000072002;
+const STRUCTURE_TYPE_MEMORY_GET_FD_INFO_KHR: i32 = 1000074002;
+
+type GetMemoryFd =
+    unsafe extern "system" fn(ash::vk::Device, *const MemoryGetFdInfo, *mut i32) -> ash::vk::Result;
+
+/// Commands of `VK_KHR_external_memory_fd` extension loaded for a device.
+/// Commands are valid only for the device they were loaded for.
+#[derive(Clone, Copy)]
+pub struct ExternalMemoryFd {
+    get_memory_fd: GetMemoryFd,
+}
+
+impl fmt::Debug for ExternalMemoryFd {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ExternalMemoryFd")
+            .field("get_memory_fd", &(self.get_memory_fd as usize))
+            .finish()
+    }
+}
+
+impl ExternalMemoryFd {
+    /// Load `vkGetMemoryFdKHR` command of `VK_KHR_external_memory_fd` extension enabled on the `device`.
+    /// Returns `None` if command can't be loaded, e.g. when extension is not enabled.
+    ///
+    /// # Safety
+    ///
+    /// `device` must be created from `instance`.
+    pub unsafe fn load<I>(instance: &I, device: ash::vk::Device) -> Option<Self>
+    where
+        I: InstanceV1_0,
+    {
+        let name = CString::new("vkGetMemoryFdKHR").unwrap();
+        let address: usize = mem::transmute(instance.get_device_proc_addr(device, name.as_ptr()));
+        if address == 0 {
+            None
+        } else {
+            Some(ExternalMemoryFd {
+                get_memory_fd: mem::transmute(address),
+            })
+        }
+    }
+
+    /// Handle types that can be exported with loaded commands.
+    fn handle_types(&self) -> ExternalHandleTypes {
+        ExternalHandleTypes::OPAQUE_FD | ExternalHandleTypes::DMA_BUF
+    }
+}
+
+/// Device that allocates memory exportable as file descriptors.
+/// Forwards all other memory commands to the wrapped device.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Copy(bound = ""), Debug(bound = ""))]
+pub struct ExternalMemoryDevice<'a, V: FunctionPointers + 'a> {
+    #[derivative(Debug = "ignore")]
+    device: &'a ash::Device<V>,
+    external: ExternalMemoryFd,
+}
+
+impl<'a, V> ExternalMemoryDevice<'a, V>
+where
+    V: FunctionPointers,
+{
+    /// Wrap the `device` with commands of `VK_KHR_external_memory_fd` loaded for it.
+    ///
+    /// # Safety
+    ///
+    /// `external` must be loaded for the `device`.
+    pub unsafe fn new(device: &'a ash::Device<V>, external: ExternalMemoryFd) -> Self {
+        ExternalMemoryDevice { device, external }
+    }
+
+    /// Get wrapped device.
+    pub fn device(&self) -> &'a ash::Device<V> {
+        self.device
+    }
+}
+
 impl From<ash::vk::Result> for OutOfMemoryError {
     fn from(result: ash::vk::Result) -> OutOfMemoryError {
         match result {
@@ -135,4 +233,123 @@ where
         self.flush_mapped_memory_ranges(&ranges)?;
         Ok(())
     }
+
+    /// Limits of physical device are not available to logical device.
+    /// Largest possible atom is used instead.
+    fn non_coherent_atom_size(&self) -> u64 {
+        MAX_NON_COHERENT_ATOM_SIZE
+    }
+}
+
+impl<'a, V> Device for ExternalMemoryDevice<'a, V>
+where
+    V: FunctionPointers,
+    ash::Device<V>: DeviceV1_0,
+{
+    type Memory = ash::vk::DeviceMemory;
+
+    unsafe fn allocate(
+        &self,
+        index: u32,
+        size: u64,
+    ) -> Result<ash::vk::DeviceMemory, AllocationError> {
+        self.device.allocate(index, size)
+    }
+
+    unsafe fn free(&self, memory: ash::vk::DeviceMemory) {
+        Device::free(self.device, memory)
+    }
+
+    unsafe fn map(
+        &self,
+        memory: &ash::vk::DeviceMemory,
+        range: Range<u64>,
+    ) -> Result<NonNull<u8>, MappingError> {
+        Device::map(self.device, memory, range)
+    }
+
+    unsafe fn unmap(&self, memory: &ash::vk::DeviceMemory) {
+        Device::unmap(self.device, memory)
+    }
+
+    unsafe fn invalidate<'b>(
+        &self,
+        regions: impl IntoIterator<Item = (&'b ash::vk::DeviceMemory, Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        Device::invalidate(self.device, regions)
+    }
+
+    unsafe fn flush<'b>(
+        &self,
+        regions: impl IntoIterator<Item = (&'b ash::vk::DeviceMemory, Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        Device::flush(self.device, regions)
+    }
+
+    fn allocation_alignment(&self, index: u32) -> u64 {
+        self.device.allocation_alignment(index)
+    }
+
+    fn non_coherent_atom_size(&self) -> u64 {
+        self.device.non_coherent_atom_size()
+    }
+
+    unsafe fn allocate_exportable(
+        &self,
+        index: u32,
+        size: u64,
+        handle_types: ExternalHandleTypes,
+    ) -> Result<ash::vk::DeviceMemory, AllocationError> {
+        if handle_types.is_empty() || !self.external.handle_types().contains(handle_types) {
+            return Err(AllocationError::ExportUnsupported(handle_types));
+        }
+
+        let export = ExportMemoryAllocateInfo {
+            s_type: STRUCTURE_TYPE_EXPORT_MEMORY_ALLOCATE_INFO,
+            p_next: null(),
+            handle_types: handle_types.bits(),
+        };
+        Ok(self.device.allocate_memory(
+            &ash::vk::MemoryAllocateInfo {
+                s_type: ash::vk::StructureType::MemoryAllocateInfo,
+                p_next: &export as *const _ as *const _,
+                allocation_size: size,
+                memory_type_index: index,
+            },
+            None,
+        )?)
+    }
+
+    unsafe fn export_memory(
+        &self,
+        memory: &ash::vk::DeviceMemory,
+        handle_type: ExternalHandleTypes,
+    ) -> Result<ExternalHandle, ExportError> {
+        assert_eq!(
+            handle_type.bits().count_ones(),
+            1,
+            "Memory is exported as single handle type"
+        );
+        if !self.external.handle_types().contains(handle_type) {
+            return Err(ExportError::Unsupported);
+        }
+
+        let mut fd = -1;
+        let result = (self.external.get_memory_fd)(
+            self.device.handle(),
+            &MemoryGetFdInfo {
+                s_type: STRUCTURE_TYPE_MEMORY_GET_FD_INFO_KHR,
+                p_next: null(),
+                memory: *memory,
+                handle_type: handle_type.bits(),
+            },
+            &mut fd,
+        );
+        match result {
+            ash::vk::Result::Success => Ok(ExternalHandle::Fd(fd)),
+            ash::vk::Result::ErrorTooManyObjects => Err(ExportError::TooManyObjects),
+            ash::vk::Result::ErrorOutOfHostMemory => Err(OutOfMemoryError::OutOfHostMemory.into()),
+            _ => panic!("unexpected error"),
+        }
+    }
 }
@@ -3,7 +3,7 @@
 use hal::{self, Device as HalDevice};
 use std::{borrow::Borrow, marker::PhantomData, ops::Range, ptr::NonNull};
 
-use device::Device;
+use device::{Device, MAX_NON_COHERENT_ATOM_SIZE};
 use error::*;
 use heaps::*;
 use memory::*;
@@ -127,6 +127,12 @@ where
         self.0.borrow().flush_mapped_memory_ranges(regions);
         Ok(())
     }
+
+    /// Limits of physical device are not available to logical device.
+    /// Largest possible atom is used instead.
+    fn non_coherent_atom_size(&self) -> u64 {
+        MAX_NON_COHERENT_ATOM_SIZE
+    }
 }
 
 /// Fetch data necessary from `Backend::PhysicalDevice`
@@ -25,6 +25,9 @@ extern crate derivative;
 
 #[macro_use]
 extern crate failure;
+
+#[macro_use]
+extern crate log;
 extern crate veclist;
 
 #[cfg(feature = "serde")]
@@ -41,9 +44,11 @@ extern crate rand;
 #[cfg(test)]
 mod test;
 
+#[macro_use]
 mod block;
 mod device;
 mod error;
+mod external;
 mod heaps;
 mod impls;
 mod mapping;
@@ -54,13 +59,24 @@ pub mod allocator;
 pub mod usage;
 
 pub use block::Block;
-pub use device::Device;
-pub use error::{AllocationError, MappingError, MemoryError, OutOfMemoryError};
-pub use heaps::{Config, Heaps, MemoryBlock};
-pub use mapping::{write::Write, Coherent, MappedRange, MaybeCoherent, NonCoherent};
+#[cfg(feature = "tags")]
+pub use block::Tag;
+pub use device::{Device, MAX_NON_COHERENT_ATOM_SIZE};
+pub use error::{AllocationError, ExportError, MappingError, MemoryError, OutOfMemoryError};
+pub use external::{ExternalHandle, ExternalHandleTypes};
+pub use heaps::{
+    BlockRequest, BufferAlignments, BufferRole, Config, HeapUtilization, Heaps, MemoryBlock, Stats,
+};
+pub use mapping::{
+    copy_mapped, write::Write, Coherent, MappedRange, MaybeCoherent, NonCoherent, Pod,
+    TypedBlock, DEFAULT_FLUSH_CHUNK_SIZE,
+};
 pub use memory::{Memory, Properties};
 pub use usage::Usage;
 
+#[cfg(feature = "ash")]
+pub use impls::{ExternalMemoryDevice, ExternalMemoryFd};
+
 #[cfg(feature = "gfx-hal")]
 extern crate gfx_hal as hal;
 
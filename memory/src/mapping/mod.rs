@@ -1,8 +1,15 @@
 mod range;
+mod typed;
 pub(crate) mod write;
 
-use std::{fmt::Debug, ops::Range, ptr::NonNull};
+use std::{
+    fmt::Debug,
+    mem::size_of,
+    ops::Range,
+    ptr::{copy_nonoverlapping, NonNull},
+};
 
+use block::Block;
 use device::Device;
 use error::{MappingError, MemoryError};
 use memory::Memory;
@@ -11,8 +18,12 @@ use util::fits_usize;
 pub(crate) use self::range::{
     mapped_fitting_range, mapped_slice, mapped_slice_mut, mapped_sub_range,
 };
+pub use self::typed::{Pod, TypedBlock};
 use self::write::{Write, WriteFlush};
 
+/// Default size of the segments oversized ranges are split into by chunked flush and invalidate.
+pub const DEFAULT_FLUSH_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
+
 /// Non-coherent marker.
 #[derive(Clone, Copy, Debug)]
 pub struct NonCoherent;
@@ -38,10 +49,67 @@ pub struct MappedRange<'a, T: 'static, C = MaybeCoherent> {
     /// Range of mapped memory.
     range: Range<u64>,
 
+    /// Size of the memory object.
+    size: u64,
+
     /// Coherency marker
     coherent: C,
 }
 
+impl<'a, T: 'static, C> MappedRange<'a, T, C> {
+    /// Get bytes of the whole mapped range.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that device won't write to the memory region until the borrow ends.
+    /// Non-coherent memory must be invalidated before reading.
+    pub unsafe fn as_bytes(&self) -> &[u8] {
+        self.as_slice::<u8>().expect("Bytes are always aligned")
+    }
+
+    /// Get mutable bytes of the whole mapped range.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that device won't access the memory region until the borrow ends.
+    /// Non-coherent memory must be flushed after writing.
+    pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.as_slice_mut::<u8>().expect("Bytes are always aligned")
+    }
+
+    /// Get typed slice over the whole mapped range.
+    /// Returns `MappingError::Unaligned` if mapping pointer is not aligned for `U`
+    /// and `MappingError::InvalidSize` if `U` is zero-sized.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that device won't write to the memory region until the borrow ends.
+    /// `U` must be plain-old-data type with memory layout compatible with data written by the device.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if range length is not multiple of `size_of::<U>()`.
+    pub unsafe fn as_slice<U: Copy>(&self) -> Result<&[U], MappingError> {
+        mapped_slice::<U>(self.ptr, self.range.clone())
+    }
+
+    /// Get mutable typed slice over the whole mapped range.
+    /// Returns `MappingError::Unaligned` if mapping pointer is not aligned for `U`
+    /// and `MappingError::InvalidSize` if `U` is zero-sized.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that device won't access the memory region until the borrow ends.
+    /// `U` must be plain-old-data type.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if range length is not multiple of `size_of::<U>()`.
+    pub unsafe fn as_slice_mut<U: Copy>(&mut self) -> Result<&mut [U], MappingError> {
+        mapped_slice_mut::<U>(self.ptr, self.range.clone())
+    }
+}
+
 impl<'a, T: 'static> MappedRange<'a, T, MaybeCoherent> {
     /// Map range of memory.
     ///
@@ -70,7 +138,7 @@ impl<'a, T: 'static> MappedRange<'a, T, MaybeCoherent> {
 
         let ptr = device.map(memory.raw(), range.clone())?;
         assert!(
-            (ptr.as_ptr() as usize).wrapping_neg() <= (range.end - range.start) as usize,
+            (ptr.as_ptr() as usize).wrapping_neg() >= (range.end - range.start) as usize,
             "Resulting pointer value + range length must fit in usize",
         );
 
@@ -82,6 +150,7 @@ impl<'a, T: 'static> MappedRange<'a, T, MaybeCoherent> {
         MappedRange {
             ptr,
             range,
+            size: memory.size(),
             memory: memory.raw(),
             coherent: MaybeCoherent(memory.host_coherent()),
         }
@@ -120,7 +189,7 @@ impl<'a, T: 'static> MappedRange<'a, T, MaybeCoherent> {
         let (ptr, range) = mapped_sub_range(self.ptr, self.range.clone(), range)
             .ok_or_else(|| MappingError::OutOfBounds)?;
 
-        if self.coherent.0 {
+        if !self.coherent.0 {
             device.invalidate(Some((self.memory, range.clone())))?;
         }
 
@@ -148,7 +217,7 @@ impl<'a, T: 'static> MappedRange<'a, T, MaybeCoherent> {
         let (ptr, range) = mapped_sub_range(self.ptr, self.range.clone(), range)
             .ok_or_else(|| MappingError::OutOfBounds)?;
 
-        if self.coherent.0 {
+        if !self.coherent.0 {
             device.invalidate(Some((self.memory, range.clone())))?;
         }
 
@@ -156,11 +225,272 @@ impl<'a, T: 'static> MappedRange<'a, T, MaybeCoherent> {
 
         Ok(WriteFlush {
             slice,
-            flush: if self.coherent.0 {
+            flush: if !self.coherent.0 {
                 Some((device, self.memory, range))
             } else {
                 None
             },
         })
     }
+
+    /// Copy `data` into the mapping starting at `offset` from the beginning of the mapping.
+    /// Written range is flushed if memory is not coherent.
+    /// Returns `MappingError::OutOfBounds` if `data` doesn't fit the mapping
+    /// and `MappingError::Unaligned` if `offset` is not aligned for `U`.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that device won't access the memory region until this function returns.
+    pub unsafe fn write_slice<D, U>(
+        &mut self,
+        device: &D,
+        offset: u64,
+        data: &[U],
+    ) -> Result<(), MemoryError>
+    where
+        D: Device<Memory = T>,
+        U: Copy,
+    {
+        let end = (data.len() as u64)
+            .checked_mul(size_of::<U>() as u64)
+            .and_then(|size| offset.checked_add(size))
+            .ok_or(MappingError::OutOfBounds)?;
+        let (ptr, range) = mapped_sub_range(self.ptr, self.range.clone(), offset..end)
+            .ok_or(MappingError::OutOfBounds)?;
+
+        mapped_slice_mut::<U>(ptr, range)?.copy_from_slice(data);
+        self.flush(device, offset..end)
+    }
+
+    /// Fetch slice of all whole elements of `U` from `offset` to the end of the mapping.
+    /// Range of the slice is invalidated if memory is not coherent.
+    /// Returns `MappingError::OutOfBounds` if `offset` is beyond the mapping
+    /// and `MappingError::Unaligned` if `offset` is not aligned for `U`.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that device won't write to the memory region until the borrow ends.
+    /// `U` must be plain-old-data type with memory layout compatible with data written by the device.
+    pub unsafe fn read_slice<'b, D, U>(
+        &'b self,
+        device: &D,
+        offset: u64,
+    ) -> Result<&'b [U], MemoryError>
+    where
+        D: Device<Memory = T>,
+        U: Copy,
+    {
+        let len = (self.range.end - self.range.start)
+            .checked_sub(offset)
+            .ok_or(MappingError::OutOfBounds)?;
+        let size = len / size_of::<U>() as u64 * size_of::<U>() as u64;
+        let (ptr, range) = mapped_sub_range(self.ptr, self.range.clone(), offset..offset + size)
+            .ok_or(MappingError::OutOfBounds)?;
+
+        let slice = mapped_slice::<U>(ptr, range)?;
+        self.invalidate(device, offset..offset + size)?;
+        Ok(slice)
+    }
+}
+
+impl<'a, T: 'static> MappedRange<'a, T, MaybeCoherent> {
+    /// Flush sub-range of the mapping making host writes available to the device.
+    /// `range` is relative to the beginning of the mapping.
+    /// Range is extended to `Device::non_coherent_atom_size` boundaries of the memory object
+    /// and clamped to the end of the memory object.
+    /// Does nothing if memory is coherent.
+    ///
+    /// # Safety
+    ///
+    /// Memory object must be created with device specified.
+    pub unsafe fn flush<D>(&self, device: &D, range: Range<u64>) -> Result<(), MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        if self.coherent.0 {
+            return Ok(());
+        }
+        let range = self.atom_range(range, device.non_coherent_atom_size())?;
+        device.flush(Some((self.memory, range)))?;
+        Ok(())
+    }
+
+    /// Invalidate sub-range of the mapping making device writes visible to the host.
+    /// Range is extended the same way as by `flush`.
+    /// Does nothing if memory is coherent.
+    ///
+    /// # Safety
+    ///
+    /// Memory object must be created with device specified.
+    pub unsafe fn invalidate<D>(&self, device: &D, range: Range<u64>) -> Result<(), MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        if self.coherent.0 {
+            return Ok(());
+        }
+        let range = self.atom_range(range, device.non_coherent_atom_size())?;
+        device.invalidate(Some((self.memory, range)))?;
+        Ok(())
+    }
+
+    /// Flush sub-range of the mapping issuing one flush per segment of up to `chunk_size` bytes.
+    /// `range` is relative to the beginning of the mapping.
+    /// Range is extended to `atom_size` boundaries the same way as by `flush`
+    /// and every segment except the last one is multiple of `atom_size`.
+    /// Does nothing if memory is coherent.
+    ///
+    /// # Safety
+    ///
+    /// Memory object must be created with device specified.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `chunk_size` is not a non-zero multiple of `atom_size`.
+    pub unsafe fn flush_chunked<D>(
+        &self,
+        device: &D,
+        range: Range<u64>,
+        chunk_size: u64,
+        atom_size: u64,
+    ) -> Result<(), MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        if self.coherent.0 {
+            return Ok(());
+        }
+        for segment in self.segments(range, chunk_size, atom_size)? {
+            device.flush(Some((self.memory, segment)))?;
+        }
+        Ok(())
+    }
+
+    /// Invalidate sub-range of the mapping issuing one invalidate per segment of up to `chunk_size` bytes.
+    /// Range is split the same way as by `flush_chunked`.
+    /// Does nothing if memory is coherent.
+    ///
+    /// # Safety
+    ///
+    /// Memory object must be created with device specified.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `chunk_size` is not a non-zero multiple of `atom_size`.
+    pub unsafe fn invalidate_chunked<D>(
+        &self,
+        device: &D,
+        range: Range<u64>,
+        chunk_size: u64,
+        atom_size: u64,
+    ) -> Result<(), MemoryError>
+    where
+        D: Device<Memory = T>,
+    {
+        if self.coherent.0 {
+            return Ok(());
+        }
+        for segment in self.segments(range, chunk_size, atom_size)? {
+            device.invalidate(Some((self.memory, segment)))?;
+        }
+        Ok(())
+    }
+
+    fn segments(
+        &self,
+        range: Range<u64>,
+        chunk_size: u64,
+        atom_size: u64,
+    ) -> Result<impl Iterator<Item = Range<u64>>, MappingError> {
+        assert!(
+            chunk_size != 0 && atom_size != 0 && chunk_size % atom_size == 0,
+            "Chunk size must be non-zero multiple of atom size"
+        );
+        let Range { start, end } = self.atom_range(range, atom_size)?;
+        let count = (end - start + chunk_size - 1) / chunk_size;
+        Ok((0..count).map(move |index| {
+            let offset = start + index * chunk_size;
+            offset..(offset + chunk_size).min(end)
+        }))
+    }
+
+    /// Convert range relative to the mapping into range of the memory object
+    /// extended to `atom_size` boundaries of the memory object.
+    /// Range may end at the end of the memory object regardless of `atom_size`.
+    fn atom_range(&self, range: Range<u64>, atom_size: u64) -> Result<Range<u64>, MappingError> {
+        assert_ne!(atom_size, 0, "Atom size can't be 0");
+        let len = self.range.end - self.range.start;
+        if range.start > range.end || range.end > len {
+            return Err(MappingError::OutOfBounds);
+        }
+
+        let start = (self.range.start + range.start) / atom_size * atom_size;
+        let end = self.range.start + range.end;
+        let end = ((end + atom_size - 1) / atom_size * atom_size).min(self.size);
+        Ok(start..end)
+    }
+}
+
+/// Copy data from one host-visible block to another.
+/// Both blocks are mapped for copying and unmapped afterwards.
+/// Source range is invalidated before copying if memory is not coherent.
+/// Destination range is flushed after copying if memory is not coherent.
+///
+/// # Safety
+///
+/// Caller must ensure that device won't access both memory regions until this function returns.
+///
+/// # Panics
+///
+/// This function will panic if ranges have different length.
+pub unsafe fn copy_mapped<D, S, B>(
+    device: &D,
+    src: &mut S,
+    src_range: Range<u64>,
+    dst: &mut B,
+    dst_range: Range<u64>,
+) -> Result<(), MemoryError>
+where
+    D: Device,
+    S: Block<Memory = D::Memory>,
+    B: Block<Memory = D::Memory>,
+{
+    assert_eq!(
+        src_range.end - src_range.start,
+        dst_range.end - dst_range.start,
+        "Source and destination ranges must have equal length"
+    );
+    let result = copy_mapped_ranges(device, src, src_range, dst, dst_range);
+    src.unmap(device);
+    dst.unmap(device);
+    result
+}
+
+unsafe fn copy_mapped_ranges<D, S, B>(
+    device: &D,
+    src: &mut S,
+    src_range: Range<u64>,
+    dst: &mut B,
+    dst_range: Range<u64>,
+) -> Result<(), MemoryError>
+where
+    D: Device,
+    S: Block<Memory = D::Memory>,
+    B: Block<Memory = D::Memory>,
+{
+    let src = src.map(device, src_range)?;
+    let dst = dst.map(device, dst_range)?;
+    let size = src.range.end - src.range.start;
+    assert!(fits_usize(size), "Range length must fit in usize");
+
+    if !src.coherent.0 {
+        device.invalidate(Some((src.memory, src.range.clone())))?;
+    }
+
+    copy_nonoverlapping(src.ptr.as_ptr(), dst.ptr.as_ptr(), size as usize);
+
+    if !dst.coherent.0 {
+        device.flush(Some((dst.memory, dst.range.clone())))?;
+    }
+    Ok(())
 }
@@ -57,7 +57,11 @@ pub(crate) unsafe fn mapped_slice_mut<'a, U>(
     ptr: NonNull<u8>,
     range: Range<u64>,
 ) -> Result<&'a mut [U], MappingError> {
-    let size = (range.end - range.start) as usize;
+    let size = range.end - range.start;
+    if size_of::<U>() == 0 {
+        return Err(MappingError::InvalidSize { size, element: 0 });
+    }
+    let size = size as usize;
     assert_eq!(
         size % size_of::<U>(),
         0,
@@ -71,7 +75,10 @@ pub(crate) unsafe fn mapped_slice_mut<'a, U>(
         });
     }
 
-    Ok(from_raw_parts_mut(ptr.as_ptr() as *mut U, size))
+    Ok(from_raw_parts_mut(
+        ptr.as_ptr() as *mut U,
+        size / size_of::<U>(),
+    ))
 }
 
 /// # Safety
@@ -82,7 +89,11 @@ pub(crate) unsafe fn mapped_slice<'a, U>(
     ptr: NonNull<u8>,
     range: Range<u64>,
 ) -> Result<&'a [U], MappingError> {
-    let size = (range.end - range.start) as usize;
+    let size = range.end - range.start;
+    if size_of::<U>() == 0 {
+        return Err(MappingError::InvalidSize { size, element: 0 });
+    }
+    let size = size as usize;
     assert_eq!(
         size % size_of::<U>(),
         0,
@@ -96,5 +107,8 @@ pub(crate) unsafe fn mapped_slice<'a, U>(
         });
     }
 
-    Ok(from_raw_parts(ptr.as_ptr() as *const U, size))
+    Ok(from_raw_parts(
+        ptr.as_ptr() as *const U,
+        size / size_of::<U>(),
+    ))
 }
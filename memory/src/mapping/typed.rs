@@ -0,0 +1,133 @@
+use std::{
+    mem::size_of,
+    ops::{Deref, DerefMut, Range},
+};
+
+use block::Block;
+use device::Device;
+use error::{MappingError, MemoryError};
+
+use super::{mapped_slice_mut, MappedRange};
+
+/// Plain-old-data type.
+/// Any bit pattern of its size is a valid value and it has no padding bytes,
+/// so it can be freely read from and written to mapped memory.
+///
+/// # Safety
+///
+/// Implementor must ensure that the type satisfies requirements above,
+/// e.g. `#[repr(C)]` struct of `Pod` fields without padding.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl Pod for $ty {})*
+    };
+}
+
+macro_rules! impl_pod_arrays {
+    ($($size:expr),*) => {
+        $(unsafe impl<T: Pod> Pod for [T; $size] {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+impl_pod_arrays!(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 32, 64);
+
+/// Mapped range of the block viewed as slice of `T`.
+/// Slice can't outlive the mapping as both borrow the block.
+/// Block still must be unmapped after the view is dropped.
+#[derive(Debug)]
+pub struct TypedBlock<'a, T: Pod, M: 'static> {
+    mapping: MappedRange<'a, M>,
+    slice: &'a mut [T],
+}
+
+impl<'a, T, M> TypedBlock<'a, T, M>
+where
+    T: Pod,
+    M: 'static,
+{
+    /// Map `range` of the block and view it as slice of `T`.
+    /// Range length must be multiple of `size_of::<T>()`
+    /// and mapping must be aligned for `T`.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that device won't access the memory region until the view is dropped.
+    pub unsafe fn map<B, D>(
+        block: &'a mut B,
+        device: &D,
+        range: Range<u64>,
+    ) -> Result<Self, MappingError>
+    where
+        B: Block<Memory = M>,
+        D: Device<Memory = M>,
+    {
+        let size = range.end.saturating_sub(range.start);
+        let element = size_of::<T>();
+        if element == 0 || size % element as u64 != 0 {
+            return Err(MappingError::InvalidSize { size, element });
+        }
+
+        // Mapping borrows the block for `'a` when returned,
+        // so block is reborrowed through pointer to unmap it if mapping can't be viewed.
+        let block: *mut B = block;
+        let mapping = (*block).map(device, range)?;
+        match mapped_slice_mut::<T>(mapping.ptr(), mapping.range()) {
+            Ok(slice) => Ok(TypedBlock { mapping, slice }),
+            Err(error) => {
+                drop(mapping);
+                (*block).unmap(device);
+                Err(error)
+            }
+        }
+    }
+
+    /// Get range of the memory object viewed.
+    pub fn range(&self) -> Range<u64> {
+        self.mapping.range()
+    }
+
+    /// Make host writes to the viewed range available to the device.
+    /// Does nothing if memory is coherent.
+    pub fn flush<D>(&self, device: &D) -> Result<(), MemoryError>
+    where
+        D: Device<Memory = M>,
+    {
+        let range = self.mapping.range();
+        unsafe { self.mapping.flush(device, 0..range.end - range.start) }
+    }
+
+    /// Make device writes to the viewed range visible to the host.
+    /// Does nothing if memory is coherent.
+    pub fn invalidate<D>(&self, device: &D) -> Result<(), MemoryError>
+    where
+        D: Device<Memory = M>,
+    {
+        let range = self.mapping.range();
+        unsafe { self.mapping.invalidate(device, 0..range.end - range.start) }
+    }
+}
+
+impl<'a, T, M> Deref for TypedBlock<'a, T, M>
+where
+    T: Pod,
+    M: 'static,
+{
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.slice
+    }
+}
+
+impl<'a, T, M> DerefMut for TypedBlock<'a, T, M>
+where
+    T: Pod,
+    M: 'static,
+{
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.slice
+    }
+}
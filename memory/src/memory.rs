@@ -1,3 +1,6 @@
+use device::Device;
+use error::ExportError;
+use external::{ExternalHandle, ExternalHandleTypes};
 use relevant::Relevant;
 
 bitflags! {
@@ -83,6 +86,7 @@ pub struct Memory<T> {
     raw: T,
     size: u64,
     properties: Properties,
+    exportable: ExternalHandleTypes,
     relevant: Relevant,
 }
 
@@ -119,10 +123,55 @@ impl<T> Memory<T> {
             properties,
             raw,
             size,
+            exportable: ExternalHandleTypes::empty(),
             relevant: Relevant,
         }
     }
 
+    /// Create memory from raw object allocated with `Device::allocate_exportable`.
+    pub unsafe fn from_raw_exportable(
+        raw: T,
+        size: u64,
+        properties: Properties,
+        exportable: ExternalHandleTypes,
+    ) -> Self {
+        Memory {
+            exportable,
+            ..Self::from_raw(raw, size, properties)
+        }
+    }
+
+    /// Get handle types this memory can be exported as.
+    pub fn exportable(&self) -> ExternalHandleTypes {
+        self.exportable
+    }
+
+    /// Export this memory as OS handle of `handle_type`.
+    /// Memory must be allocated as exportable with this handle type.
+    /// Returned handle is owned by the caller.
+    pub fn export_handle<D>(
+        &self,
+        device: &D,
+        handle_type: ExternalHandleTypes,
+    ) -> Result<ExternalHandle, ExportError>
+    where
+        T: 'static,
+        D: Device<Memory = T>,
+    {
+        assert_eq!(
+            handle_type.bits().count_ones(),
+            1,
+            "Memory must be exported as single handle type"
+        );
+        if !self.exportable.contains(handle_type) {
+            return Err(ExportError::NotExportable {
+                exportable: self.exportable,
+                requested: handle_type,
+            });
+        }
+        unsafe { device.export_memory(&self.raw, handle_type) }
+    }
+
     /// Check if this memory is host-visible and can be mapped.
     /// `memory.host_visible()` is equivalent to `memory.properties().contains(Properties::HOST_VISIBLE)`
     pub fn host_visible(&self) -> bool {
@@ -1,123 +1,16 @@
 #![allow(dead_code)]
 
-use std::{cell::RefCell, collections::HashSet, ops::Range, ptr::NonNull};
-
 use rand;
 use veclist::VecList;
 
-use allocator::{ArenaConfig, DynamicConfig};
+use allocator::{Allocator, DedicatedAllocator, DedicatedCacheConfig};
 use block::Block;
-use device::Device;
-use error::{AllocationError, MappingError, MemoryError, OutOfMemoryError};
-use heaps::{Config, Heaps, MemoryBlock};
+use error::MemoryError;
+use heaps::{Heaps, MemoryBlock};
 use memory::Properties;
 use usage::*;
 
-struct Inner {
-    freed: HashSet<u64>,
-    next: u64,
-}
-
-struct MockDevice(RefCell<Inner>);
-
-impl MockDevice {
-    fn new() -> Self {
-        MockDevice(RefCell::new(Inner {
-            freed: HashSet::new(),
-            next: 0,
-        }))
-    }
-}
-
-impl Device for MockDevice {
-    type Memory = u64;
-
-    unsafe fn allocate(&self, _index: u32, _size: u64) -> Result<u64, AllocationError> {
-        let mut inner = self.0.borrow_mut();
-        let id = inner.next;
-        inner.next = id + 1;
-        Ok(id)
-    }
-
-    unsafe fn free(&self, memory: u64) {
-        assert!(self.0.borrow_mut().freed.insert(memory), "Double-free");
-    }
-
-    unsafe fn map(&self, _memory: &u64, _range: Range<u64>) -> Result<NonNull<u8>, MappingError> {
-        Ok(NonNull::dangling())
-    }
-
-    unsafe fn unmap(&self, _memory: &u64) {}
-
-    unsafe fn invalidate<'a>(
-        &self,
-        _regions: impl IntoIterator<Item = (&'a u64, Range<u64>)>,
-    ) -> Result<(), OutOfMemoryError> {
-        unimplemented!()
-    }
-    unsafe fn flush<'a>(
-        &self,
-        _regions: impl IntoIterator<Item = (&'a u64, Range<u64>)>,
-    ) -> Result<(), OutOfMemoryError> {
-        unimplemented!()
-    }
-}
-
-fn init() -> Heaps<u64> {
-    let arena_config = ArenaConfig {
-        arena_size: 32 * 1024,
-    };
-    let dynamic_config = DynamicConfig {
-        blocks_per_chunk: 64,
-        block_size_granularity: 256,
-        max_block_size: 32 * 1024,
-    };
-    let small_dynamic_config = DynamicConfig {
-        blocks_per_chunk: 64,
-        block_size_granularity: 32,
-        max_block_size: 1024,
-    };
-
-    unsafe {
-        Heaps::new(
-            vec![
-                (
-                    Properties::DEVICE_LOCAL,
-                    0,
-                    Config {
-                        arena: None,
-                        dynamic: Some(dynamic_config),
-                    },
-                ),
-                (
-                    Properties::HOST_VISIBLE | Properties::HOST_COHERENT | Properties::DEVICE_LOCAL,
-                    1,
-                    Config {
-                        arena: None,
-                        dynamic: Some(small_dynamic_config),
-                    },
-                ),
-                (
-                    Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
-                    2,
-                    Config {
-                        arena: Some(arena_config),
-                        dynamic: Some(dynamic_config),
-                    },
-                ),
-                (
-                    Properties::HOST_VISIBLE | Properties::HOST_COHERENT | Properties::HOST_CACHED,
-                    2,
-                    Config {
-                        arena: Some(arena_config),
-                        dynamic: Some(dynamic_config),
-                    },
-                ),
-            ],
-            vec![16 * 1024 * 1024, 1 * 1024 * 1024, 32 * 1024 * 1024],
-        )
-    }
-}
+use super::{init, MockDevice};
 
 fn random_usage() -> UsageValue {
     match rand::random::<u8>() % 4 {
@@ -226,3 +119,71 @@ fn blocks_test() {
     println!("Dropping Heaps");
     heaps.dispose(device);
 }
+
+#[test]
+fn dedicated_cache_reuse() {
+    let ref device = MockDevice::new();
+    let mut allocator = DedicatedAllocator::with_cache(
+        0,
+        Properties::DEVICE_LOCAL,
+        DedicatedCacheConfig {
+            max_per_size: 2,
+            max_bytes: 4096,
+        },
+    );
+
+    let (block, allocated) = allocator.alloc(device, 1024, 1).unwrap();
+    assert_eq!(allocated, 1024);
+    let memory = *block.memory();
+    assert_eq!(allocator.free(device, block), 0);
+    assert_eq!(allocator.cache_stats().cached, 1024);
+
+    let (block, allocated) = allocator.alloc(device, 1024, 1).unwrap();
+    assert_eq!(allocated, 0);
+    assert_eq!(*block.memory(), memory);
+    assert_eq!(device.0.borrow().next, 1);
+
+    let stats = allocator.cache_stats();
+    assert_eq!((stats.hits, stats.misses, stats.cached), (1, 1, 0));
+
+    // Different size can't reuse cached memory.
+    let (other, allocated) = allocator.alloc(device, 2048, 1).unwrap();
+    assert_eq!(allocated, 2048);
+    assert_ne!(*other.memory(), memory);
+
+    allocator.free(device, block);
+    allocator.free(device, other);
+    allocator.dispose(device);
+    assert_eq!(device.0.borrow().freed.len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Blocks are left allocated during allocator disposal")]
+fn dedicated_dispose_with_allocated_blocks() {
+    let ref device = MockDevice::new();
+    let mut allocator = DedicatedAllocator::new(0, Properties::DEVICE_LOCAL);
+
+    let (_block, _) = allocator.alloc(device, 4096, 1).unwrap();
+    allocator.dispose(device);
+}
+
+#[test]
+fn dedicated_alignment_beyond_memory_objects() {
+    let ref device = MockDevice::new();
+    device.0.borrow_mut().alignment = 256;
+    let mut allocator = DedicatedAllocator::new(0, Properties::DEVICE_LOCAL);
+
+    match allocator.alloc(device, 4096, 1024) {
+        Err(MemoryError::InvalidAlignment {
+            align: 1024,
+            supported: 256,
+        }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert_eq!(device.0.borrow().next, 0);
+
+    let (block, _) = allocator.alloc(device, 4096, 256).unwrap();
+    assert_eq!(block.range().start % 256, 0);
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
@@ -0,0 +1,73 @@
+use allocator::{Allocator, ArenaAllocator, ArenaConfig};
+use block::Block;
+use memory::Properties;
+
+use super::MockDevice;
+
+fn allocator(max_retained: usize) -> ArenaAllocator<u64> {
+    ArenaAllocator::new(
+        0,
+        Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+        ArenaConfig {
+            arena_size: 1024,
+            max_retained,
+        },
+    )
+}
+
+#[test]
+fn drained_arenas_are_reused() {
+    let ref device = MockDevice::new();
+    let mut allocator = allocator(1);
+
+    let (first, allocated) = allocator.alloc(device, 400, 1).unwrap();
+    assert_eq!(allocated, 1024);
+    let (second, allocated) = allocator.alloc(device, 400, 1).unwrap();
+    assert_eq!(allocated, 0);
+    assert_eq!(second.range(), 400..800);
+    let (third, allocated) = allocator.alloc(device, 400, 1).unwrap();
+    assert_eq!(allocated, 1024);
+    let (head, tail) = (*first.memory(), *third.memory());
+    assert_ne!(head, tail);
+
+    // Exhausted arena is retained instead of freed.
+    assert_eq!(allocator.free(device, first), 0);
+    assert_eq!(allocator.free(device, second), 0);
+    assert!(device.0.borrow().freed.is_empty());
+
+    // Freeing the last block of current arena resets it.
+    assert_eq!(allocator.free(device, third), 0);
+    let (block, allocated) = allocator.alloc(device, 400, 1).unwrap();
+    assert_eq!(allocated, 0);
+    assert_eq!((*block.memory(), block.range()), (tail, 0..400));
+
+    // Retained arena serves allocation that doesn't fit current one.
+    let (other, allocated) = allocator.alloc(device, 700, 1).unwrap();
+    assert_eq!(allocated, 0);
+    assert_eq!((*other.memory(), other.range()), (head, 0..700));
+    assert_eq!(device.0.borrow().next, 2);
+
+    allocator.free(device, block);
+    allocator.free(device, other);
+    allocator.dispose(device);
+    let ref freed = device.0.borrow().freed;
+    assert!(freed.contains(&head) && freed.contains(&tail));
+}
+
+#[test]
+fn drained_arenas_freed_beyond_limit() {
+    let ref device = MockDevice::new();
+    let mut allocator = allocator(0);
+
+    let (first, _) = allocator.alloc(device, 600, 1).unwrap();
+    let (second, _) = allocator.alloc(device, 600, 1).unwrap();
+    let memory = *first.memory();
+
+    assert_eq!(allocator.free(device, first), 1024);
+    assert!(device.0.borrow().freed.contains(&memory));
+
+    allocator.free(device, second);
+    assert_eq!(allocator.clear(device), 0);
+    allocator.dispose(device);
+    assert_eq!(device.0.borrow().freed.len(), 2);
+}
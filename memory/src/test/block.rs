@@ -0,0 +1,161 @@
+use allocator::{Allocator, ArenaConfig, DedicatedAllocator, Kind, MIN_DEDICATED_SIZE};
+use block::Block;
+use heaps::{Config, Heaps};
+use memory::Properties;
+use usage::UsageValue;
+
+use super::{init, MockDevice};
+
+#[test]
+fn memory_block_delegation() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+
+    for &(usage, size, kind) in &[
+        (UsageValue::Upload, 64 * 1024, Kind::Dedicated),
+        (UsageValue::Upload, 1024, Kind::Arena),
+        (UsageValue::Dynamic, 1024, Kind::Dynamic),
+    ] {
+        let mut block = heaps.allocate(device, 1 << 2, usage, size, 1).unwrap();
+        assert_eq!(block.kind(), kind);
+        assert_eq!(block.memory_type(), 2);
+        assert_eq!(
+            block.properties(),
+            Properties::HOST_VISIBLE | Properties::HOST_COHERENT
+        );
+
+        let range = block.range();
+        assert!(range.end - range.start >= size);
+        assert!(*block.memory() < device.0.borrow().next);
+
+        {
+            let mapping = block.map(device, 0..size).unwrap();
+            assert_eq!(mapping.range(), range.start..range.start + size);
+        }
+        block.unmap(device);
+
+        heaps.free(device, block);
+    }
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn tiny_allocation_is_sub_allocated() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+
+    // Memory type 1 has no arena allocator for uploads.
+    let block = heaps
+        .allocate(device, 1 << 1, UsageValue::Upload, 256, 1)
+        .unwrap();
+    assert_eq!(block.kind(), Kind::Dynamic);
+    heaps.free(device, block);
+
+    let block = heaps
+        .allocate(device, 1 << 1, UsageValue::Upload, MIN_DEDICATED_SIZE, 1)
+        .unwrap();
+    assert_eq!(block.kind(), Kind::Dedicated);
+    heaps.free(device, block);
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn tiny_long_lived_allocation_avoids_arena() {
+    let ref device = MockDevice::new();
+    let mut heaps = unsafe {
+        Heaps::new(
+            vec![(
+                Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+                0,
+                Config {
+                    arena: Some(ArenaConfig {
+                        arena_size: 32 * 1024,
+                        max_retained: 0,
+                    }),
+                    dynamic: None,
+                },
+            )],
+            vec![16 * 1024 * 1024],
+        )
+    };
+
+    let block = heaps.allocate(device, 1, UsageValue::Upload, 256, 1).unwrap();
+    assert_eq!(block.kind(), Kind::Arena);
+    heaps.free(device, block);
+
+    // Host-visible memory updated every frame may live long and pin arena chunks.
+    let block = heaps.allocate(device, 1, UsageValue::Dynamic, 256, 1).unwrap();
+    assert_eq!(block.kind(), Kind::Dedicated);
+    heaps.free(device, block);
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn block_property_checks() {
+    let ref device = MockDevice::new();
+    let mut allocator =
+        DedicatedAllocator::new(0, Properties::HOST_VISIBLE | Properties::HOST_CACHED);
+
+    let (block, _) = allocator.alloc(device, 1024, 1).unwrap();
+    assert!(block.is_host_visible());
+    assert!(!block.is_host_coherent());
+    assert!(!block.is_device_local());
+    assert!(block.is_cached());
+    allocator.free(device, block);
+    allocator.dispose(device);
+
+    let mut allocator = DedicatedAllocator::new(
+        1,
+        Properties::DEVICE_LOCAL | Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+    );
+    let (block, _) = allocator.alloc(device, 1024, 1).unwrap();
+    assert!(block.is_host_visible());
+    assert!(block.is_host_coherent());
+    assert!(block.is_device_local());
+    assert!(!block.is_cached());
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[cfg(feature = "tags")]
+#[derive(Debug, PartialEq)]
+struct Label(&'static str);
+
+#[cfg(feature = "tags")]
+#[test]
+fn dedicated_block_tag() {
+    let ref device = MockDevice::new();
+    let mut allocator = DedicatedAllocator::new(0, Properties::DEVICE_LOCAL);
+
+    let (mut block, _) = allocator.alloc(device, 1024, 1).unwrap();
+    assert!(block.tag::<Label>().is_none());
+
+    block.set_tag(Label("vertices"));
+    assert_eq!(block.tag::<Label>(), Some(&Label("vertices")));
+    assert!(block.tag::<u32>().is_none());
+
+    block.set_tag(7u32);
+    assert!(block.tag::<Label>().is_none());
+    assert_eq!(block.take_tag().unwrap().downcast_ref::<u32>(), Some(&7));
+    assert!(block.tag::<u32>().is_none());
+
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[cfg(feature = "tags")]
+#[test]
+fn memory_block_tag() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+
+    let mut block = heaps.allocate(device, 1, UsageValue::Data, 1024, 1).unwrap();
+    block.set_tag(Label("scratch"));
+    assert_eq!(block.tag::<Label>(), Some(&Label("scratch")));
+
+    heaps.free(device, block);
+    heaps.dispose(device);
+}
@@ -0,0 +1,53 @@
+use block::Block;
+use heaps::{BufferAlignments, BufferRole};
+use memory::Properties;
+use usage::Dynamic;
+
+use super::{init, MockDevice};
+
+#[test]
+fn uniform_allocation_is_aligned_to_uniform_alignment() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+    heaps.set_buffer_alignments(BufferAlignments {
+        uniform: 512,
+        storage: 64,
+        texel: 16,
+    });
+
+    // Occupy start of the chunk so that alignment is not satisfied by accident.
+    let other = heaps.allocate(device, !0, Dynamic, 32, 1).unwrap();
+    let uniform = heaps.alloc_uniform(device, !0, 64, 1).unwrap();
+    assert_eq!(uniform.range().start % 512, 0);
+    assert!(uniform.properties().contains(Properties::HOST_VISIBLE));
+
+    heaps.free(device, uniform);
+    heaps.free(device, other);
+    heaps.dispose(device);
+}
+
+#[test]
+fn requested_alignment_is_kept() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+
+    let storage = heaps.alloc_storage(device, !0, 100, 1024).unwrap();
+    assert_eq!(storage.range().start % 1024, 0);
+    assert!(storage.properties().contains(Properties::DEVICE_LOCAL));
+
+    let index = heaps.alloc_index(device, !0, 6, 1).unwrap();
+    assert_eq!(index.range().start % 4, 0);
+
+    heaps.free(device, storage);
+    heaps.free(device, index);
+    heaps.dispose(device);
+}
+
+#[test]
+fn default_alignments_fit_any_device() {
+    let alignments = BufferAlignments::default();
+    assert_eq!(BufferRole::Uniform.alignment(&alignments), 256);
+    assert_eq!(BufferRole::Storage.alignment(&alignments), 256);
+    assert_eq!(BufferRole::Texel.alignment(&alignments), 256);
+    assert_eq!(BufferRole::Vertex.alignment(&alignments), 1);
+}
@@ -0,0 +1,52 @@
+use allocator::{Allocator, CappedAllocator, DedicatedAllocator};
+use error::{MemoryError, OutOfMemoryError};
+use memory::Properties;
+
+use super::MockDevice;
+
+#[test]
+fn alloc_up_to_cap() {
+    let ref device = MockDevice::new();
+    let mut allocator =
+        CappedAllocator::new(DedicatedAllocator::new(0, Properties::DEVICE_LOCAL), 1024);
+
+    let (first, _) = allocator.alloc(device, 512, 1).unwrap();
+    let (second, _) = allocator.alloc(device, 512, 1).unwrap();
+    assert_eq!(allocator.remaining(), 0);
+
+    match allocator.alloc(device, 1, 1) {
+        Err(MemoryError::OutOfMemoryError(OutOfMemoryError::OutOfDeviceMemory)) => {}
+        other => panic!("Unexpected result {:?}", other.map(|(_, size)| size)),
+    }
+
+    allocator.free(device, first);
+    assert_eq!(allocator.remaining(), 512);
+    let (third, _) = allocator.alloc(device, 512, 1).unwrap();
+
+    allocator.free(device, second);
+    allocator.free(device, third);
+    assert_eq!(allocator.used(), 0);
+    allocator.into_inner().dispose(device);
+}
+
+#[test]
+fn set_cap() {
+    let ref device = MockDevice::new();
+    let mut allocator =
+        CappedAllocator::new(DedicatedAllocator::new(0, Properties::DEVICE_LOCAL), 256);
+
+    assert!(allocator.alloc(device, 512, 1).is_err());
+
+    allocator.set_cap(1024);
+    let (block, _) = allocator.alloc(device, 512, 1).unwrap();
+    assert_eq!(allocator.remaining(), 512);
+
+    // Lowering cap below used bytes keeps allocated blocks.
+    allocator.set_cap(128);
+    assert_eq!(allocator.remaining(), 0);
+    assert!(allocator.alloc(device, 1, 1).is_err());
+
+    allocator.free(device, block);
+    assert_eq!(allocator.remaining(), 128);
+    allocator.into_inner().dispose(device);
+}
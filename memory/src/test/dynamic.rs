@@ -0,0 +1,316 @@
+use allocator::{Allocator, DynamicAllocator, DynamicConfig, FitStrategy};
+use block::Block;
+use error::{AllocationError, MemoryError};
+use memory::Properties;
+
+use super::MockDevice;
+
+fn dynamic() -> DynamicAllocator<u64> {
+    dynamic_with(FitStrategy::FirstFit)
+}
+
+fn dynamic_with(fit: FitStrategy) -> DynamicAllocator<u64> {
+    DynamicAllocator::new(
+        0,
+        Properties::DEVICE_LOCAL,
+        DynamicConfig {
+            blocks_per_chunk: 64,
+            block_size_granularity: 256,
+            max_block_size: 32 * 1024,
+            fit,
+        },
+    )
+}
+
+#[test]
+fn alignments_share_size_class() {
+    let ref device = MockDevice::new();
+    let mut dynamic = dynamic();
+
+    let (small, _) = dynamic.alloc(device, 200, 16).unwrap();
+    let (large, _) = dynamic.alloc(device, 200, 256).unwrap();
+    assert_eq!(small.range().start % 16, 0);
+    assert_eq!(large.range().start % 256, 0);
+
+    // Both blocks are taken from the same chunk.
+    assert_eq!(small.memory(), large.memory());
+    assert_eq!(small.range(), 0..256);
+    assert_eq!(large.range(), 256..512);
+
+    assert_eq!(dynamic.used(), 512);
+    assert_eq!(dynamic.wasted(), 112);
+
+    dynamic.free(device, small);
+    dynamic.free(device, large);
+    assert_eq!(dynamic.used(), 0);
+    assert_eq!(dynamic.wasted(), 0);
+}
+
+#[test]
+fn over_aligned_request_uses_larger_class() {
+    let ref device = MockDevice::new();
+    let mut dynamic = dynamic();
+
+    let (block, _) = dynamic.alloc(device, 256, 512).unwrap();
+    assert_eq!(block.range().start % 512, 0);
+    assert_eq!(block.range().end - block.range().start, 512);
+    assert_eq!(dynamic.wasted(), 256);
+
+    dynamic.free(device, block);
+    assert_eq!(dynamic.wasted(), 0);
+}
+
+#[test]
+fn invalid_alignment_rejected() {
+    let ref device = MockDevice::new();
+    let mut dynamic = dynamic();
+
+    match dynamic.alloc(device, 256, 0) {
+        Err(MemoryError::AllocationError(AllocationError::BadAlignment(0))) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match dynamic.alloc(device, 256, 64 * 1024) {
+        Err(MemoryError::InvalidAlignment {
+            align: 65536,
+            supported: 32768,
+        }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert_eq!(dynamic.used(), 0);
+}
+
+#[cfg(feature = "guard-pages")]
+fn host_visible_dynamic() -> DynamicAllocator<u64> {
+    DynamicAllocator::new(
+        0,
+        Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+        DynamicConfig {
+            blocks_per_chunk: 64,
+            block_size_granularity: 256,
+            max_block_size: 32 * 1024,
+            fit: FitStrategy::FirstFit,
+        },
+    )
+}
+
+#[test]
+fn thousands_of_small_blocks() {
+    let ref device = MockDevice::new();
+    let mut allocator = dynamic();
+
+    let mut blocks = (0..4096)
+        .map(|_| allocator.alloc(device, 200, 1).unwrap().0)
+        .collect::<Vec<_>>();
+    let block_size = blocks[0].range().end - blocks[0].range().start;
+    assert_eq!(allocator.used(), 4096 * block_size);
+    let objects = device.0.borrow().next;
+
+    // Freed block is taken by the next allocation of the same size.
+    for _ in 0..1000 {
+        let block = blocks.pop().unwrap();
+        let (memory, range) = (*block.memory(), block.range());
+        assert_eq!(allocator.free(device, block), 0);
+
+        let (block, allocated) = allocator.alloc(device, 200, 1).unwrap();
+        assert_eq!(allocated, 0);
+        assert_eq!((*block.memory(), block.range()), (memory, range));
+        blocks.push(block);
+    }
+    assert_eq!(device.0.borrow().next, objects);
+
+    let freed = blocks
+        .into_iter()
+        .map(|block| allocator.free(device, block))
+        .sum::<u64>();
+    assert!(freed > 0);
+    assert_eq!(allocator.used(), 0);
+    assert_eq!(allocator.snapshot().chunks, 0);
+    assert_eq!(device.0.borrow().freed.len() as u64, objects);
+}
+
+#[test]
+#[cfg(feature = "guard-pages")]
+fn guard_intact_within_requested_size() {
+    let ref device = MockDevice::new();
+    let mut dynamic = host_visible_dynamic();
+
+    let (mut block, _) = dynamic.alloc(device, 200, 1).unwrap();
+    let range = block.range();
+    {
+        let mut mapping = block.map(device, range.start..range.start + 200).unwrap();
+        for byte in unsafe { mapping.as_bytes_mut() } {
+            *byte = 0xAB;
+        }
+    }
+    dynamic.free(device, block);
+}
+
+#[test]
+#[cfg(feature = "guard-pages")]
+#[should_panic(
+    expected = "Block 0..256 of 200 bytes from memory type 0 was overrun: guard byte at offset 200"
+)]
+fn write_past_end_trips_guard() {
+    let ref device = MockDevice::new();
+    let mut dynamic = host_visible_dynamic();
+
+    let (mut block, _) = dynamic.alloc(device, 200, 1).unwrap();
+    let range = block.range();
+    {
+        let mut mapping = block.map(device, range.start..range.start + 201).unwrap();
+        unsafe {
+            mapping.as_bytes_mut()[200] = 0;
+        }
+    }
+    dynamic.free(device, block);
+}
+
+#[test]
+#[cfg(feature = "guard-pages")]
+fn biggest_allocation_keeps_guard() {
+    let ref device = MockDevice::new();
+    let mut dynamic = host_visible_dynamic();
+
+    let size = dynamic.max_allocation();
+    assert!(size < dynamic.max_block_size());
+
+    let (mut block, _) = dynamic.alloc(device, size, 1).unwrap();
+    let range = block.range();
+    assert_eq!(range.end - range.start, dynamic.max_block_size());
+    {
+        let mut mapping = block.map(device, range.start..range.start + size).unwrap();
+        for byte in unsafe { mapping.as_bytes_mut() } {
+            *byte = 0xAB;
+        }
+    }
+    dynamic.free(device, block);
+}
+
+/// Allocate 256 bytes with free blocks of 512 and 1024 bytes available.
+/// Returns size of the block allocated and whether it reused freed block of 512 bytes.
+#[cfg(not(feature = "guard-pages"))]
+fn fit_small_request(fit: FitStrategy) -> (u64, bool) {
+    let ref device = MockDevice::new();
+    let mut dynamic = dynamic_with(fit);
+
+    let (first, _) = dynamic.alloc(device, 512, 1).unwrap();
+    let (second, _) = dynamic.alloc(device, 512, 1).unwrap();
+    let (third, _) = dynamic.alloc(device, 1024, 1).unwrap();
+    let (fourth, _) = dynamic.alloc(device, 1024, 1).unwrap();
+    let freed = first.range();
+    dynamic.free(device, first);
+    dynamic.free(device, third);
+
+    let (block, _) = dynamic.alloc(device, 256, 1).unwrap();
+    let range = block.range();
+    let result = (range.end - range.start, range == freed);
+
+    dynamic.free(device, block);
+    dynamic.free(device, second);
+    dynamic.free(device, fourth);
+    assert_eq!(dynamic.used(), 0);
+    result
+}
+
+#[test]
+#[cfg(not(feature = "guard-pages"))]
+fn best_fit_picks_tightest_free_block() {
+    assert_eq!(fit_small_request(FitStrategy::BestFit), (512, true));
+    assert_eq!(fit_small_request(FitStrategy::FirstFit), (256, false));
+    assert_eq!(fit_small_request(FitStrategy::WorstFit), (32 * 1024, false));
+}
+
+/// Allocation trace of a frame that releases most of its large blocks
+/// and then allocates many small ones.
+/// Returns device memory allocated for small blocks and memory wasted by them.
+#[cfg(not(feature = "guard-pages"))]
+fn fragmentation_trace(fit: FitStrategy) -> (u64, u64) {
+    let ref device = MockDevice::new();
+    let mut dynamic = dynamic_with(fit);
+
+    let mut large: Vec<_> = (0..32)
+        .map(|_| dynamic.alloc(device, 512, 1).unwrap().0)
+        .collect();
+    let kept = large.pop().unwrap();
+    for block in large {
+        dynamic.free(device, block);
+    }
+
+    let mut allocated = 0;
+    let small: Vec<_> = (0..16)
+        .map(|_| {
+            let (block, size) = dynamic.alloc(device, 256, 1).unwrap();
+            allocated += size;
+            block
+        })
+        .collect();
+    let wasted = dynamic.wasted();
+
+    for block in small {
+        dynamic.free(device, block);
+    }
+    dynamic.free(device, kept);
+    assert_eq!(dynamic.used(), 0);
+    (allocated, wasted)
+}
+
+#[test]
+#[cfg(not(feature = "guard-pages"))]
+fn fragmentation_by_strategy() {
+    let (first_allocated, first_wasted) = fragmentation_trace(FitStrategy::FirstFit);
+    let (best_allocated, best_wasted) = fragmentation_trace(FitStrategy::BestFit);
+    let (worst_allocated, worst_wasted) = fragmentation_trace(FitStrategy::WorstFit);
+
+    // First-fit allocates new chunk while freed blocks sit idle.
+    assert!(first_allocated > 0);
+    assert_eq!(first_wasted, 0);
+
+    // Best-fit reuses freed blocks wasting least of them.
+    assert_eq!(best_allocated, 0);
+    assert_eq!(best_wasted, 16 * 256);
+
+    assert_eq!(worst_allocated, 0);
+    assert!(worst_wasted > best_wasted);
+}
+
+/// Fill three chunks, free the outer ones in specified order and allocate again.
+/// Returns ranges of the blocks allocated after that.
+fn reuse_after_freeing_chunks(order: [usize; 2]) -> Vec<::std::ops::Range<u64>> {
+    let ref device = MockDevice::new();
+    let mut dynamic = dynamic();
+
+    let mut chunks: Vec<Vec<_>> = (0..3)
+        .map(|_| {
+            (0..64)
+                .map(|_| dynamic.alloc(device, 256, 1).unwrap().0)
+                .collect()
+        }).collect();
+    for &index in &order {
+        for block in chunks[index].drain(..) {
+            dynamic.free(device, block);
+        }
+    }
+
+    // Middle chunk is full, so new chunk is allocated.
+    let (first, _) = dynamic.alloc(device, 256, 1).unwrap();
+    // Both chunks have free blocks now.
+    dynamic.free(device, chunks[1].pop().unwrap());
+    let (second, _) = dynamic.alloc(device, 256, 1).unwrap();
+    let ranges = vec![first.range(), second.range()];
+
+    for block in chunks.into_iter().flatten() {
+        dynamic.free(device, block);
+    }
+    dynamic.free(device, first);
+    dynamic.free(device, second);
+    assert_eq!(dynamic.used(), 0);
+    ranges
+}
+
+#[test]
+fn chunk_reuse_independent_of_free_order() {
+    assert_eq!(
+        reuse_after_freeing_chunks([0, 2]),
+        reuse_after_freeing_chunks([2, 0])
+    );
+}
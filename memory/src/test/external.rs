@@ -0,0 +1,98 @@
+use allocator::{Allocator, DedicatedAllocator, DedicatedCacheConfig, Kind};
+use block::Block;
+use error::ExportError;
+use external::{ExternalHandle, ExternalHandleTypes};
+use memory::Properties;
+use usage::Data;
+
+use super::{init, MockDevice};
+
+#[test]
+fn exportable_memory_is_dedicated() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+
+    // Small enough to be sub-allocated if it wasn't exportable.
+    let block = heaps
+        .allocate_exportable(device, !0, Data, 256, 1, ExternalHandleTypes::OPAQUE_FD)
+        .unwrap();
+    assert_eq!(block.kind(), Kind::Dedicated);
+    assert_eq!(block.range(), 0..256);
+    assert_eq!(
+        device.0.borrow().exportable[block.memory()],
+        ExternalHandleTypes::OPAQUE_FD
+    );
+    assert_eq!(heaps.heap_utilization()[0].used, 256);
+
+    let handle = block
+        .export_handle(device, ExternalHandleTypes::OPAQUE_FD)
+        .unwrap();
+    assert_eq!(handle, ExternalHandle::Fd(*block.memory() as i32));
+
+    heaps.free(device, block);
+    assert_eq!(heaps.heap_utilization()[0].used, 0);
+    heaps.dispose(device);
+}
+
+#[test]
+fn export_requires_requested_handle_type() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+
+    let block = heaps
+        .allocate_exportable(device, !0, Data, 256, 1, ExternalHandleTypes::OPAQUE_FD)
+        .unwrap();
+    match block.export_handle(device, ExternalHandleTypes::OPAQUE_WIN32) {
+        Err(ExportError::NotExportable {
+            exportable,
+            requested,
+        }) => {
+            assert_eq!(exportable, ExternalHandleTypes::OPAQUE_FD);
+            assert_eq!(requested, ExternalHandleTypes::OPAQUE_WIN32);
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    heaps.free(device, block);
+    heaps.dispose(device);
+}
+
+#[test]
+fn sub_allocated_memory_is_not_exportable() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+
+    let block = heaps.allocate(device, !0, Data, 256, 1).unwrap();
+    assert_eq!(block.kind(), Kind::Dynamic);
+    match block.export_handle(device, ExternalHandleTypes::OPAQUE_FD) {
+        Err(ExportError::NotDedicated) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    heaps.free(device, block);
+    heaps.dispose(device);
+}
+
+#[test]
+fn exportable_memory_is_not_cached() {
+    let ref device = MockDevice::new();
+    let mut dedicated = DedicatedAllocator::with_cache(
+        0,
+        Properties::DEVICE_LOCAL,
+        DedicatedCacheConfig {
+            max_per_size: 4,
+            max_bytes: 1024 * 1024,
+        },
+    );
+
+    let (block, allocated) = dedicated
+        .alloc_exportable(device, 64 * 1024, ExternalHandleTypes::DMA_BUF)
+        .unwrap();
+    assert_eq!(allocated, 64 * 1024);
+    let memory = *block.memory();
+
+    assert_eq!(dedicated.free(device, block), 64 * 1024);
+    assert!(device.0.borrow().freed.contains(&memory));
+    assert_eq!(dedicated.cache_stats().cached, 0);
+    dedicated.dispose(device);
+}
@@ -0,0 +1,225 @@
+use std::ops::Range;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use allocator::{Allocator, DynamicAllocator, DynamicBlock, DynamicConfig, FitStrategy};
+use block::Block;
+use memory::Properties;
+
+use super::MockDevice;
+
+/// Number of operations performed by single run.
+/// Bounded to keep the test fast.
+const OPERATIONS: usize = 512;
+
+/// Seeds of the runs. Fixed so failures are reproducible.
+const SEEDS: Range<u8> = 0..4;
+
+struct Live {
+    block: DynamicBlock<u64>,
+    size: u64,
+    stamp: u8,
+}
+
+struct Harness {
+    device: MockDevice,
+    dynamic: DynamicAllocator<u64>,
+    live: Vec<Live>,
+    next_stamp: u8,
+    /// Bytes allocated from device minus bytes returned.
+    device_bytes: u64,
+}
+
+impl Harness {
+    fn new(fit: FitStrategy) -> Self {
+        Harness {
+            device: MockDevice::new(),
+            dynamic: DynamicAllocator::new(
+                0,
+                Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+                DynamicConfig {
+                    blocks_per_chunk: 64,
+                    block_size_granularity: 32,
+                    max_block_size: 4096,
+                    fit,
+                },
+            ),
+            live: Vec::new(),
+            next_stamp: 1,
+            device_bytes: 0,
+        }
+    }
+
+    fn alloc(&mut self, size: u64, align: u64) {
+        let (mut block, allocated) = self
+            .dynamic
+            .alloc(&self.device, size, align)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Failed to allocate {} bytes aligned to {}: {}",
+                    size, align, err
+                )
+            });
+        self.device_bytes += allocated;
+
+        let range = block.range();
+        assert!(
+            range.end - range.start >= size,
+            "Block {:?} is smaller than {}",
+            range,
+            size
+        );
+        assert_eq!(
+            range.start % align,
+            0,
+            "Block {:?} is not aligned to {}",
+            range,
+            align
+        );
+
+        let stamp = self.next_stamp;
+        self.next_stamp = self.next_stamp.wrapping_add(1).max(1);
+        stamp_block(&self.device, &mut block, size, stamp);
+        self.live.push(Live { block, size, stamp });
+    }
+
+    fn free(&mut self, index: usize) {
+        let live = self.live.swap_remove(index);
+        let freed = self.dynamic.free(&self.device, live.block);
+        assert!(freed <= self.device_bytes, "Freed more than was allocated");
+        self.device_bytes -= freed;
+    }
+
+    /// Map block, check that nothing else wrote to it and stamp it anew.
+    fn remap(&mut self, index: usize) {
+        let stamp = self.next_stamp;
+        self.next_stamp = self.next_stamp.wrapping_add(1).max(1);
+
+        let live = &mut self.live[index];
+        let expected = live.stamp;
+        let overwritten = {
+            let mapping = live.block.map(&self.device, 0..live.size).unwrap();
+            let bytes = unsafe { mapping.as_bytes() };
+            bytes.iter().position(|&byte| byte != expected)
+        };
+        if let Some(offset) = overwritten {
+            panic!(
+                "Block {:?} of memory {} was overwritten at offset {}",
+                live.block.range(),
+                live.block.memory(),
+                offset
+            );
+        }
+        live.block.unmap(&self.device);
+        stamp_block(&self.device, &mut live.block, live.size, stamp);
+        live.stamp = stamp;
+    }
+
+    fn check_invariants(&self) {
+        let used: u64 = self
+            .live
+            .iter()
+            .map(|live| live.block.range().end - live.block.range().start)
+            .sum();
+        assert_eq!(self.dynamic.used(), used, "Used accounting drifted");
+
+        let requested: u64 = self.live.iter().map(|live| live.size).sum();
+        assert_eq!(
+            self.dynamic.wasted(),
+            used - requested,
+            "Wasted accounting drifted"
+        );
+
+        let mut ranges = self
+            .live
+            .iter()
+            .map(|live| (*live.block.memory(), live.block.range()))
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|&(memory, ref range)| (memory, range.start));
+        for pair in ranges.windows(2) {
+            let (first, second) = (&pair[0], &pair[1]);
+            assert!(
+                first.0 != second.0 || first.1.end <= second.1.start,
+                "Live blocks {:?} and {:?} of memory {} overlap",
+                first.1,
+                second.1,
+                first.0
+            );
+        }
+    }
+
+    fn finish(mut self) {
+        while !self.live.is_empty() {
+            self.free(0);
+        }
+        assert_eq!(self.dynamic.used(), 0);
+        assert_eq!(self.device_bytes, 0, "Device memory leaked");
+
+        let inner = self.device.0.borrow();
+        assert_eq!(
+            inner.freed.len() as u64,
+            inner.next,
+            "Memory objects leaked"
+        );
+    }
+}
+
+fn stamp_block(device: &MockDevice, block: &mut DynamicBlock<u64>, size: u64, stamp: u8) {
+    {
+        let mut mapping = block.map(device, 0..size).unwrap();
+        for byte in unsafe { mapping.as_bytes_mut() } {
+            *byte = stamp;
+        }
+    }
+    block.unmap(device);
+}
+
+fn run(fit: FitStrategy, seed: u8) {
+    let mut rng = StdRng::from_seed([seed; 32]);
+    let mut harness = Harness::new(fit);
+    // Less than max block size with `guard-pages` feature.
+    let max_size = harness.dynamic.max_allocation();
+
+    for _ in 0..OPERATIONS {
+        match rng.gen_range(0, 4) {
+            0 | 1 => {
+                let size = rng.gen_range(1, max_size + 1);
+                let align = 1 << rng.gen_range(0, 9);
+                harness.alloc(size, align);
+            }
+            2 if !harness.live.is_empty() => {
+                let index = rng.gen_range(0, harness.live.len());
+                harness.free(index);
+            }
+            3 if !harness.live.is_empty() => {
+                let index = rng.gen_range(0, harness.live.len());
+                harness.remap(index);
+            }
+            _ => {}
+        }
+        harness.check_invariants();
+    }
+
+    harness.finish();
+}
+
+#[test]
+fn fuzz_first_fit() {
+    for seed in SEEDS {
+        run(FitStrategy::FirstFit, seed);
+    }
+}
+
+#[test]
+fn fuzz_best_fit() {
+    for seed in SEEDS {
+        run(FitStrategy::BestFit, seed);
+    }
+}
+
+#[test]
+fn fuzz_worst_fit() {
+    for seed in SEEDS {
+        run(FitStrategy::WorstFit, seed);
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::{Arc, Mutex};
+
+use allocator::{Allocator, AllocatorEvent, DedicatedAllocator, InstrumentedAllocator};
+use block::Block;
+use memory::Properties;
+
+use super::MockDevice;
+
+#[test]
+fn alloc_free_events() {
+    let ref device = MockDevice::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let mut allocator = InstrumentedAllocator::new(
+        DedicatedAllocator::new(3, Properties::HOST_VISIBLE | Properties::HOST_COHERENT),
+        3,
+    );
+    let sink = events.clone();
+    allocator.set_callback(Arc::new(move |event: &AllocatorEvent| {
+        sink.lock().unwrap().push(event.clone())
+    }));
+
+    let (mut block, allocated) = allocator.alloc(device, 1024, 16).unwrap();
+    assert_eq!(allocated, 1024);
+    block.map(device, 0..256).unwrap();
+    block.unmap(device);
+    let freed = allocator.free(device, block);
+    assert_eq!(freed, 1024);
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            AllocatorEvent::Alloc {
+                memory_type: 3,
+                size: 1024,
+                align: 16,
+                range: 0..1024,
+                allocated: 1024,
+            },
+            AllocatorEvent::Map {
+                memory_type: 3,
+                range: 0..256,
+            },
+            AllocatorEvent::Unmap { memory_type: 3 },
+            AllocatorEvent::Free {
+                memory_type: 3,
+                range: 0..1024,
+                freed: 1024,
+            },
+        ]
+    );
+
+    allocator.into_inner().dispose(device);
+}
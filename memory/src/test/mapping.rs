@@ -0,0 +1,425 @@
+use std::mem::transmute;
+use std::ptr::{write_bytes, NonNull};
+
+use allocator::{Allocator, ArenaAllocator, ArenaConfig, DedicatedAllocator};
+use block::Block;
+use error::{MappingError, MemoryError};
+use mapping::{copy_mapped, MappedRange, Pod, TypedBlock, DEFAULT_FLUSH_CHUNK_SIZE};
+use memory::{Memory, Properties};
+
+use super::MockDevice;
+
+#[test]
+fn copy_coherent_to_non_coherent() {
+    let ref device = MockDevice::new();
+    let mut coherent =
+        DedicatedAllocator::new(0, Properties::HOST_VISIBLE | Properties::HOST_COHERENT);
+    let mut non_coherent = DedicatedAllocator::new(1, Properties::HOST_VISIBLE);
+
+    let (mut src, _) = coherent.alloc(device, 256, 1).unwrap();
+    let (mut dst, _) = non_coherent.alloc(device, 256, 1).unwrap();
+
+    {
+        let mapping = src.map(device, 0..256).unwrap();
+        unsafe {
+            write_bytes(mapping.ptr().as_ptr(), 0xAB, 256);
+        }
+    }
+    src.unmap(device);
+
+    unsafe {
+        copy_mapped(device, &mut src, 0..64, &mut dst, 128..192).unwrap();
+    }
+
+    assert!(device.0.borrow().invalidated.is_empty());
+    assert_eq!(device.0.borrow().flushed, vec![(*dst.memory(), 128..192)]);
+
+    {
+        let mapping = dst.map(device, 0..256).unwrap();
+        let ptr = mapping.ptr().as_ptr();
+        for offset in 0..256 {
+            let expected = if offset >= 128 && offset < 192 {
+                0xAB
+            } else {
+                0
+            };
+            assert_eq!(unsafe { *ptr.offset(offset) }, expected);
+        }
+    }
+    dst.unmap(device);
+
+    coherent.free(device, src);
+    non_coherent.free(device, dst);
+    coherent.dispose(device);
+    non_coherent.dispose(device);
+}
+
+#[test]
+fn typed_slice_view() {
+    let ref device = MockDevice::new();
+    let mut allocator =
+        DedicatedAllocator::new(0, Properties::HOST_VISIBLE | Properties::HOST_COHERENT);
+    let (mut block, _) = allocator.alloc(device, 64, 4).unwrap();
+
+    {
+        let mut mapping = block.map(device, 0..64).unwrap();
+        unsafe {
+            assert_eq!(mapping.as_bytes().len(), 64);
+            {
+                let floats = mapping.as_slice_mut::<f32>().unwrap();
+                assert_eq!(floats.len(), 16);
+                for (index, value) in floats.iter_mut().enumerate() {
+                    *value = index as f32 * 0.5;
+                }
+            }
+            let floats = mapping.as_slice::<f32>().unwrap();
+            assert_eq!(floats[0], 0.0);
+            assert_eq!(floats[15], 7.5);
+            assert_eq!(floats[1], 0.5);
+        }
+    }
+    block.unmap(device);
+
+    {
+        let mapping = block.map(device, 1..61).unwrap();
+        match unsafe { mapping.as_slice::<f32>() } {
+            Err(MappingError::Unaligned { align, .. }) => assert_eq!(align, 4),
+            other => panic!("Expected unaligned mapping error, got {:?}", other),
+        }
+    }
+    block.unmap(device);
+
+    {
+        let mut mapping = block.map(device, 0..64).unwrap();
+        match unsafe { mapping.as_slice::<()>() } {
+            Err(MappingError::InvalidSize { size, element }) => assert_eq!((size, element), (64, 0)),
+            other => panic!("Expected invalid size error, got {:?}", other),
+        }
+        assert!(unsafe { mapping.as_slice_mut::<()>() }.is_err());
+    }
+    block.unmap(device);
+
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[test]
+fn chunked_flush_of_huge_range() {
+    const SIZE: u64 = 256 * 1024 * 1024;
+    let ref device = MockDevice::new();
+    let memory = unsafe { Memory::from_raw(7, SIZE, Properties::HOST_VISIBLE) };
+    // Flushing never touches mapped memory, so dangling pointer is fine.
+    let mapping = unsafe { MappedRange::from_raw(&memory, NonNull::dangling(), 0..SIZE) };
+
+    unsafe {
+        mapping
+            .flush_chunked(device, 0..SIZE, DEFAULT_FLUSH_CHUNK_SIZE, 256)
+            .unwrap();
+    }
+    {
+        let ref flushed = device.0.borrow().flushed;
+        assert_eq!(flushed.len(), 4);
+        for (index, &(memory, ref range)) in flushed.iter().enumerate() {
+            let start = index as u64 * DEFAULT_FLUSH_CHUNK_SIZE;
+            assert_eq!(memory, 7);
+            assert_eq!(*range, start..start + DEFAULT_FLUSH_CHUNK_SIZE);
+        }
+    }
+
+    // Unaligned range is extended to atom boundaries.
+    unsafe {
+        mapping
+            .invalidate_chunked(device, 100..(3 << 20) + 1, 1 << 20, 256)
+            .unwrap();
+    }
+    assert_eq!(
+        device.0.borrow().invalidated,
+        vec![
+            (7, 0..1 << 20),
+            (7, 1 << 20..2 << 20),
+            (7, 2 << 20..3 << 20),
+            (7, 3 << 20..(3 << 20) + 256),
+        ]
+    );
+
+    memory.into_raw();
+}
+
+#[test]
+fn chunked_flush_at_mapping_end() {
+    let ref device = MockDevice::new();
+    let memory = unsafe { Memory::from_raw(9, 1000, Properties::HOST_VISIBLE) };
+    let whole = unsafe { MappedRange::from_raw(&memory, NonNull::dangling(), 0..1000) };
+    let head = unsafe { MappedRange::from_raw(&memory, NonNull::dangling(), 0..768) };
+
+    unsafe {
+        // Segments stay atom aligned up to the end of the memory object.
+        whole.flush_chunked(device, 700..1000, 128, 64).unwrap();
+        // Mapping ends at atom boundary before the end of the memory object.
+        head.flush_chunked(device, 700..760, 128, 64).unwrap();
+    }
+    assert_eq!(
+        device.0.borrow().flushed,
+        vec![(9, 640..768), (9, 768..896), (9, 896..1000), (9, 640..768)]
+    );
+
+    memory.into_raw();
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vertex {
+    position: [f32; 3],
+    color: [u8; 4],
+}
+
+unsafe impl Pod for Vertex {}
+
+#[test]
+fn typed_block_writes_vertices() {
+    let ref device = MockDevice::new();
+    let mut allocator = DedicatedAllocator::new(0, Properties::HOST_VISIBLE);
+    let (mut block, _) = allocator.alloc(device, 64, 4).unwrap();
+    let vertices = [
+        Vertex {
+            position: [0.0, 1.0, 2.0],
+            color: [255, 0, 0, 255],
+        },
+        Vertex {
+            position: [-1.0, 0.5, 0.25],
+            color: [0, 255, 0, 128],
+        },
+    ];
+
+    {
+        let mut view = unsafe { TypedBlock::<Vertex, _>::map(&mut block, device, 16..48) }.unwrap();
+        assert_eq!(view.len(), 2);
+        view.copy_from_slice(&vertices);
+        view.flush(device).unwrap();
+    }
+    block.unmap(device);
+    assert_eq!(device.0.borrow().flushed, vec![(*block.memory(), 16..48)]);
+
+    {
+        let mapping = block.map(device, 0..64).unwrap();
+        let bytes = unsafe { mapping.as_bytes() };
+        assert!(bytes[..16].iter().all(|&byte| byte == 0));
+        for (index, vertex) in vertices.iter().enumerate() {
+            let ref bytes = bytes[16 + index * 16..32 + index * 16];
+            for (axis, &value) in vertex.position.iter().enumerate() {
+                let mut written = [0u8; 4];
+                written.copy_from_slice(&bytes[axis * 4..axis * 4 + 4]);
+                assert_eq!(unsafe { transmute::<_, f32>(written) }, value);
+            }
+            assert_eq!(bytes[12..16], vertex.color);
+        }
+    }
+    block.unmap(device);
+
+    match unsafe { TypedBlock::<Vertex, _>::map(&mut block, device, 0..24) } {
+        Err(MappingError::InvalidSize { size, element }) => assert_eq!((size, element), (24, 16)),
+        other => panic!("Expected invalid size error, got {:?}", other),
+    }
+
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[test]
+fn typed_block_unmaps_on_misaligned_offset() {
+    let ref device = MockDevice::new();
+    let mut allocator = DedicatedAllocator::new(0, Properties::HOST_VISIBLE);
+    let (mut block, _) = allocator.alloc(device, 64, 4).unwrap();
+    let memory = *block.memory();
+
+    match unsafe { TypedBlock::<Vertex, _>::map(&mut block, device, 2..34) } {
+        Err(MappingError::Unaligned { align: 4, .. }) => {}
+        other => panic!("Expected unaligned error, got {:?}", other),
+    }
+    assert!(!device.0.borrow().mapped.contains(&memory));
+
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[test]
+fn flush_and_invalidate_round_to_atoms() {
+    let ref device = MockDevice::new();
+    device.0.borrow_mut().atom_size = 64;
+    let memory = unsafe { Memory::from_raw(3, 1000, Properties::HOST_VISIBLE) };
+    // Flushing never touches mapped memory, so dangling pointer is fine.
+    let whole = unsafe { MappedRange::from_raw(&memory, NonNull::dangling(), 0..1000) };
+    let tail = unsafe { MappedRange::from_raw(&memory, NonNull::dangling(), 128..640) };
+
+    unsafe {
+        whole.flush(device, 100..200).unwrap();
+        whole.flush(device, 0..1).unwrap();
+        // End of the memory isn't atom aligned, range may end there.
+        whole.flush(device, 950..1000).unwrap();
+        // Range is relative to the mapping, atom boundaries are relative to the memory object.
+        tail.invalidate(device, 2..12).unwrap();
+        tail.invalidate(device, 472..512).unwrap();
+    }
+    assert_eq!(
+        device.0.borrow().flushed,
+        vec![(3, 64..256), (3, 0..64), (3, 896..1000)]
+    );
+    assert_eq!(
+        device.0.borrow().invalidated,
+        vec![(3, 128..192), (3, 576..640)]
+    );
+
+    match unsafe { tail.flush(device, 500..520) } {
+        Err(MemoryError::MappingError(MappingError::OutOfBounds)) => {}
+        other => panic!("Expected out of bounds error, got {:?}", other),
+    }
+    match unsafe { whole.invalidate(device, 990..1001) } {
+        Err(MemoryError::MappingError(MappingError::OutOfBounds)) => {}
+        other => panic!("Expected out of bounds error, got {:?}", other),
+    }
+
+    memory.into_raw();
+}
+
+#[test]
+fn flush_and_invalidate_extend_beyond_unaligned_mapping() {
+    let ref device = MockDevice::new();
+    device.0.borrow_mut().atom_size = 64;
+    let memory = unsafe { Memory::from_raw(3, 1000, Properties::HOST_VISIBLE) };
+    let mapping = unsafe { MappedRange::from_raw(&memory, NonNull::dangling(), 100..600) };
+
+    unsafe {
+        // Atom containing start of the mapping begins before it.
+        mapping.flush(device, 0..10).unwrap();
+        // Atom containing end of the mapping ends after it.
+        mapping.invalidate(device, 450..500).unwrap();
+    }
+    assert_eq!(device.0.borrow().flushed, vec![(3, 64..128)]);
+    assert_eq!(device.0.borrow().invalidated, vec![(3, 512..640)]);
+
+    memory.into_raw();
+}
+
+#[test]
+fn non_coherent_sub_block_flushes_whole_atoms() {
+    let ref device = MockDevice::new();
+    device.0.borrow_mut().atom_size = 64;
+    let mut allocator = ArenaAllocator::new(
+        0,
+        Properties::HOST_VISIBLE,
+        ArenaConfig {
+            arena_size: 1000,
+            max_retained: 0,
+        },
+    );
+    let (head, _) = allocator.alloc(device, 100, 1).unwrap();
+    let (mut block, _) = allocator.alloc(device, 899, 1).unwrap();
+    assert_eq!(block.range(), 100..999);
+    let memory = *block.memory();
+
+    {
+        let mut mapping = block.map(device, 0..899).unwrap();
+        unsafe {
+            mapping.write_slice(device, 10, &[1u8; 20]).unwrap();
+            assert_eq!(
+                mapping.read_slice::<_, u8>(device, 800).unwrap(),
+                &[0u8; 99][..]
+            );
+        }
+    }
+    block.unmap(device);
+    // Sub-block shares memory with neighbours, so whole atoms are flushed and invalidated
+    // and the last one is clamped to the end of the memory object.
+    assert_eq!(device.0.borrow().flushed, vec![(memory, 64..192)]);
+    assert_eq!(device.0.borrow().invalidated, vec![(memory, 896..1000)]);
+
+    allocator.free(device, head);
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[test]
+fn coherent_flush_and_invalidate_are_noops() {
+    let ref device = MockDevice::new();
+    device.0.borrow_mut().atom_size = 64;
+    let memory =
+        unsafe { Memory::from_raw(5, 256, Properties::HOST_VISIBLE | Properties::HOST_COHERENT) };
+    let mapping = unsafe { MappedRange::from_raw(&memory, NonNull::dangling(), 0..256) };
+
+    unsafe {
+        mapping.flush(device, 10..20).unwrap();
+        mapping.invalidate(device, 0..256).unwrap();
+    }
+    assert!(device.0.borrow().flushed.is_empty());
+    assert!(device.0.borrow().invalidated.is_empty());
+
+    memory.into_raw();
+}
+
+#[test]
+fn write_and_read_slices() {
+    let ref device = MockDevice::new();
+    let mut allocator = DedicatedAllocator::new(0, Properties::HOST_VISIBLE);
+    let (mut block, _) = allocator.alloc(device, 64, 4).unwrap();
+    let memory = *block.memory();
+
+    {
+        let mut mapping = block.map(device, 16..62).unwrap();
+        unsafe {
+            mapping.write_slice(device, 8, &[1u32, 2, 3]).unwrap();
+            // Trailing bytes that don't make whole element are not read.
+            let read = mapping.read_slice::<_, u32>(device, 12).unwrap();
+            assert_eq!(read.len(), 8);
+            assert_eq!(read[..3], [2, 3, 0]);
+        }
+    }
+    block.unmap(device);
+    assert_eq!(device.0.borrow().flushed, vec![(memory, 24..36)]);
+    assert_eq!(device.0.borrow().invalidated, vec![(memory, 28..60)]);
+
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
+
+#[test]
+fn write_and_read_slices_check_bounds_and_alignment() {
+    let ref device = MockDevice::new();
+    let mut allocator =
+        DedicatedAllocator::new(0, Properties::HOST_VISIBLE | Properties::HOST_COHERENT);
+    let (mut block, _) = allocator.alloc(device, 64, 4).unwrap();
+
+    {
+        let mut mapping = block.map(device, 0..64).unwrap();
+        unsafe {
+            match mapping.write_slice(device, 56, &[0u32; 3]) {
+                Err(MemoryError::MappingError(MappingError::OutOfBounds)) => {}
+                other => panic!("Expected out of bounds error, got {:?}", other),
+            }
+            match mapping.write_slice(device, !0 - 3, &[0u32; 2]) {
+                Err(MemoryError::MappingError(MappingError::OutOfBounds)) => {}
+                other => panic!("Expected out of bounds error, got {:?}", other),
+            }
+            match mapping.write_slice(device, 2, &[0u32]) {
+                Err(MemoryError::MappingError(MappingError::Unaligned { align, .. })) => {
+                    assert_eq!(align, 4)
+                }
+                other => panic!("Expected unaligned error, got {:?}", other),
+            }
+            match mapping.read_slice::<_, u8>(device, 65) {
+                Err(MemoryError::MappingError(MappingError::OutOfBounds)) => {}
+                other => panic!("Expected out of bounds error, got {:?}", other),
+            }
+            assert!(mapping.as_bytes().iter().all(|&byte| byte == 0));
+
+            // Coherent memory is neither flushed nor invalidated.
+            mapping.write_slice(device, 60, &[7u32]).unwrap();
+            assert_eq!(mapping.read_slice::<_, u32>(device, 60).unwrap(), [7]);
+        }
+    }
+    block.unmap(device);
+    assert!(device.0.borrow().flushed.is_empty());
+    assert!(device.0.borrow().invalidated.is_empty());
+
+    allocator.free(device, block);
+    allocator.dispose(device);
+}
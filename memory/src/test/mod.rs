@@ -1 +1,220 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ops::Range,
+    ptr::NonNull,
+};
+
+use allocator::{ArenaConfig, DynamicConfig, FitStrategy};
+use device::Device;
+use error::{AllocationError, ExportError, MappingError, OutOfMemoryError};
+use external::{ExternalHandle, ExternalHandleTypes};
+use heaps::{Config, Heaps};
+use memory::Properties;
+
 mod allocator;
+mod arena;
+mod block;
+mod buffer;
+mod capped;
+mod dynamic;
+mod external;
+mod fuzz;
+mod instrumented;
+mod mapping;
+mod properties;
+mod recycling;
+mod retry;
+mod selection;
+mod snapshot;
+mod stats;
+mod utilization;
+
+struct Inner {
+    freed: HashSet<u64>,
+    next: u64,
+    /// Host storage backing mapped memory objects.
+    storage: HashMap<u64, Vec<u8>>,
+    /// Memory objects currently mapped.
+    mapped: HashSet<u64>,
+    flushed: Vec<(u64, Range<u64>)>,
+    invalidated: Vec<(u64, Range<u64>)>,
+    /// Memory types device fails to allocate from.
+    exhausted: HashSet<u32>,
+    /// Handle types memory objects were allocated exportable as.
+    exportable: HashMap<u64, ExternalHandleTypes>,
+    /// Alignment of memory objects reported by the device.
+    alignment: u64,
+    /// Non-coherent atom size reported by the device.
+    atom_size: u64,
+}
+
+struct MockDevice(RefCell<Inner>);
+
+impl MockDevice {
+    fn new() -> Self {
+        MockDevice(RefCell::new(Inner {
+            freed: HashSet::new(),
+            next: 0,
+            storage: HashMap::new(),
+            mapped: HashSet::new(),
+            flushed: Vec::new(),
+            invalidated: Vec::new(),
+            exhausted: HashSet::new(),
+            exportable: HashMap::new(),
+            alignment: u64::max_value(),
+            atom_size: 1,
+        }))
+    }
+}
+
+impl Device for MockDevice {
+    type Memory = u64;
+
+    unsafe fn allocate(&self, index: u32, _size: u64) -> Result<u64, AllocationError> {
+        let mut inner = self.0.borrow_mut();
+        if inner.exhausted.contains(&index) {
+            return Err(OutOfMemoryError::OutOfDeviceMemory.into());
+        }
+        let id = inner.next;
+        inner.next = id + 1;
+        Ok(id)
+    }
+
+    unsafe fn free(&self, memory: u64) {
+        let mut inner = self.0.borrow_mut();
+        inner.storage.remove(&memory);
+        assert!(inner.freed.insert(memory), "Double-free");
+    }
+
+    unsafe fn map(&self, memory: &u64, range: Range<u64>) -> Result<NonNull<u8>, MappingError> {
+        let mut inner = self.0.borrow_mut();
+        assert!(inner.mapped.insert(*memory), "Memory is already mapped");
+        let storage = inner.storage.entry(*memory).or_insert_with(Vec::new);
+        if (storage.len() as u64) < range.end {
+            storage.resize(range.end as usize, 0);
+        }
+        Ok(NonNull::new_unchecked(
+            storage.as_mut_ptr().offset(range.start as isize),
+        ))
+    }
+
+    unsafe fn unmap(&self, memory: &u64) {
+        assert!(self.0.borrow_mut().mapped.remove(memory), "Memory is not mapped");
+    }
+
+    unsafe fn invalidate<'a>(
+        &self,
+        regions: impl IntoIterator<Item = (&'a u64, Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        let mut inner = self.0.borrow_mut();
+        inner
+            .invalidated
+            .extend(regions.into_iter().map(|(&memory, range)| (memory, range)));
+        Ok(())
+    }
+    unsafe fn flush<'a>(
+        &self,
+        regions: impl IntoIterator<Item = (&'a u64, Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        let mut inner = self.0.borrow_mut();
+        inner
+            .flushed
+            .extend(regions.into_iter().map(|(&memory, range)| (memory, range)));
+        Ok(())
+    }
+
+    fn allocation_alignment(&self, _index: u32) -> u64 {
+        self.0.borrow().alignment
+    }
+
+    fn non_coherent_atom_size(&self) -> u64 {
+        self.0.borrow().atom_size
+    }
+
+    unsafe fn allocate_exportable(
+        &self,
+        index: u32,
+        size: u64,
+        handle_types: ExternalHandleTypes,
+    ) -> Result<u64, AllocationError> {
+        let memory = self.allocate(index, size)?;
+        self.0
+            .borrow_mut()
+            .exportable
+            .insert(memory, handle_types);
+        Ok(memory)
+    }
+
+    unsafe fn export_memory(
+        &self,
+        memory: &u64,
+        handle_type: ExternalHandleTypes,
+    ) -> Result<ExternalHandle, ExportError> {
+        let inner = self.0.borrow();
+        assert!(
+            inner.exportable[memory].contains(handle_type),
+            "Memory wasn't allocated exportable"
+        );
+        Ok(ExternalHandle::Fd(*memory as i32))
+    }
+}
+
+fn init() -> Heaps<u64> {
+    let arena_config = ArenaConfig {
+        arena_size: 32 * 1024,
+        max_retained: 0,
+    };
+    let dynamic_config = DynamicConfig {
+        blocks_per_chunk: 64,
+        block_size_granularity: 256,
+        max_block_size: 32 * 1024,
+        fit: FitStrategy::FirstFit,
+    };
+    let small_dynamic_config = DynamicConfig {
+        blocks_per_chunk: 64,
+        block_size_granularity: 32,
+        max_block_size: 1024,
+        fit: FitStrategy::FirstFit,
+    };
+
+    unsafe {
+        Heaps::new(
+            vec![
+                (
+                    Properties::DEVICE_LOCAL,
+                    0,
+                    Config {
+                        arena: None,
+                        dynamic: Some(dynamic_config),
+                    },
+                ),
+                (
+                    Properties::HOST_VISIBLE | Properties::HOST_COHERENT | Properties::DEVICE_LOCAL,
+                    1,
+                    Config {
+                        arena: None,
+                        dynamic: Some(small_dynamic_config),
+                    },
+                ),
+                (
+                    Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+                    2,
+                    Config {
+                        arena: Some(arena_config),
+                        dynamic: Some(dynamic_config),
+                    },
+                ),
+                (
+                    Properties::HOST_VISIBLE | Properties::HOST_COHERENT | Properties::HOST_CACHED,
+                    2,
+                    Config {
+                        arena: Some(arena_config),
+                        dynamic: Some(dynamic_config),
+                    },
+                ),
+            ],
+            vec![16 * 1024 * 1024, 1 * 1024 * 1024, 32 * 1024 * 1024],
+        )
+    }
+}
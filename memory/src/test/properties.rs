@@ -0,0 +1,124 @@
+use allocator::{DynamicConfig, FitStrategy};
+use block::Block;
+use error::{AllocationError, MappingError, MemoryError};
+use heaps::{Config, Heaps};
+use memory::Properties;
+use usage::{Data, Protected, Transient};
+
+use super::MockDevice;
+
+fn config() -> Config {
+    Config {
+        arena: None,
+        dynamic: Some(DynamicConfig {
+            blocks_per_chunk: 64,
+            block_size_granularity: 256,
+            max_block_size: 32 * 1024,
+            fit: FitStrategy::FirstFit,
+        }),
+    }
+}
+
+fn protected_heaps() -> Heaps<u64> {
+    unsafe {
+        Heaps::new(
+            vec![
+                (Properties::DEVICE_LOCAL, 0, config()),
+                (Properties::DEVICE_LOCAL | Properties::PROTECTED, 0, config()),
+            ],
+            vec![16 * 1024 * 1024],
+        )
+    }
+}
+
+#[test]
+fn protected_routing() {
+    let mut heaps = protected_heaps();
+    let ref device = MockDevice::new();
+
+    let protected = heaps.allocate(device, 0b11, Protected, 1024, 1).unwrap();
+    assert_eq!(protected.memory_type(), 1);
+
+    let data = heaps.allocate(device, 0b11, Data, 1024, 1).unwrap();
+    assert_eq!(data.memory_type(), 0);
+
+    match heaps.allocate(device, 0b01, Protected, 1024, 1) {
+        Err(MemoryError::AllocationError(AllocationError::NoSuitableMemory(..))) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    heaps.free(device, protected);
+    heaps.free(device, data);
+    heaps.dispose(device);
+}
+
+#[test]
+fn protected_map_fails() {
+    let mut heaps = protected_heaps();
+    let ref device = MockDevice::new();
+
+    for &size in &[1024, 1024 * 1024] {
+        let mut block = heaps.allocate(device, 0b10, Protected, size, 1).unwrap();
+        assert!(block.properties().protected());
+        match block.map(device, 0..size) {
+            Err(MappingError::Protected) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+        heaps.free(device, block);
+    }
+
+    heaps.dispose(device);
+}
+
+fn lazy_heaps() -> Heaps<u64> {
+    unsafe {
+        Heaps::new(
+            vec![
+                (Properties::DEVICE_LOCAL, 0, config()),
+                (
+                    Properties::DEVICE_LOCAL | Properties::LAZILY_ALLOCATED,
+                    0,
+                    config(),
+                ),
+            ],
+            vec![16 * 1024 * 1024],
+        )
+    }
+}
+
+#[test]
+fn transient_routing() {
+    let mut heaps = lazy_heaps();
+    let ref device = MockDevice::new();
+
+    let transient = heaps.allocate(device, 0b11, Transient, 1024, 1).unwrap();
+    assert_eq!(transient.memory_type(), 1);
+    assert!(transient.properties().lazily_allocated());
+
+    let data = heaps.allocate(device, 0b11, Data, 1024, 1).unwrap();
+    assert_eq!(data.memory_type(), 0);
+
+    // Falls back to regular device-local memory.
+    let fallback = heaps.allocate(device, 0b01, Transient, 1024, 1).unwrap();
+    assert_eq!(fallback.memory_type(), 0);
+
+    heaps.free(device, transient);
+    heaps.free(device, data);
+    heaps.free(device, fallback);
+    heaps.dispose(device);
+}
+
+#[test]
+fn lazy_map_fails() {
+    let mut heaps = lazy_heaps();
+    let ref device = MockDevice::new();
+
+    let mut block = heaps.allocate(device, 0b10, Transient, 1024, 1).unwrap();
+    match block.map(device, 0..1024) {
+        Err(MappingError::LazilyAllocated) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+    heaps.free(device, block);
+
+    heaps.dispose(device);
+}
@@ -0,0 +1,98 @@
+use allocator::{Allocator, DedicatedAllocator, RecyclingAllocator};
+use block::Block;
+use error::{AllocationError, MemoryError};
+use memory::Properties;
+
+use super::MockDevice;
+
+const SIZE: u64 = 64 * 1024;
+
+#[test]
+fn free_then_alloc_hits_cache() {
+    let ref device = MockDevice::new();
+    let mut allocator = RecyclingAllocator::new(
+        DedicatedAllocator::new(0, Properties::DEVICE_LOCAL),
+        4 * SIZE,
+    );
+
+    let (block, allocated) = allocator.alloc(device, SIZE, 1).unwrap();
+    assert_eq!(allocated, SIZE);
+    let memory = *block.memory();
+
+    assert_eq!(allocator.free(device, block), 0);
+    assert!(device.0.borrow().freed.is_empty());
+    assert_eq!(allocator.stats().cached, SIZE);
+
+    let (block, allocated) = allocator.alloc(device, SIZE, 1).unwrap();
+    assert_eq!(allocated, 0);
+    assert_eq!(*block.memory(), memory);
+
+    let stats = allocator.stats();
+    assert_eq!((stats.hits, stats.misses, stats.cached), (1, 1, 0));
+    assert_eq!(stats.hit_rate(), 0.5);
+
+    allocator.free(device, block);
+    allocator.into_inner(device).dispose(device);
+    assert!(device.0.borrow().freed.contains(&memory));
+}
+
+#[test]
+fn exceeding_budget_evicts_oldest() {
+    let ref device = MockDevice::new();
+    let mut allocator = RecyclingAllocator::new(
+        DedicatedAllocator::new(0, Properties::DEVICE_LOCAL),
+        2 * SIZE,
+    );
+
+    let blocks = (0..3)
+        .map(|_| allocator.alloc(device, SIZE, 1).unwrap().0)
+        .collect::<Vec<_>>();
+    let memories = blocks
+        .iter()
+        .map(|block| *block.memory())
+        .collect::<Vec<_>>();
+
+    let freed = blocks
+        .into_iter()
+        .map(|block| allocator.free(device, block))
+        .collect::<Vec<_>>();
+    assert_eq!(freed, vec![0, 0, SIZE]);
+
+    {
+        let ref freed = device.0.borrow().freed;
+        assert!(freed.contains(&memories[0]));
+        assert!(!freed.contains(&memories[1]));
+        assert!(!freed.contains(&memories[2]));
+    }
+    let stats = allocator.stats();
+    assert_eq!((stats.evictions, stats.cached), (1, 2 * SIZE));
+
+    // Most recently freed block is reused first.
+    let (block, _) = allocator.alloc(device, SIZE, 1).unwrap();
+    assert_eq!(*block.memory(), memories[2]);
+
+    allocator.free(device, block);
+    assert_eq!(allocator.clear(device), 2 * SIZE);
+    allocator.into_inner(device).dispose(device);
+}
+
+#[test]
+fn zero_alignment_rejected() {
+    let ref device = MockDevice::new();
+    let mut allocator = RecyclingAllocator::new(
+        DedicatedAllocator::new(0, Properties::DEVICE_LOCAL),
+        4 * SIZE,
+    );
+
+    let (block, _) = allocator.alloc(device, SIZE, 1).unwrap();
+    allocator.free(device, block);
+
+    // Cached block must not be probed with zero alignment.
+    match allocator.alloc(device, SIZE, 0) {
+        Err(MemoryError::AllocationError(AllocationError::BadAlignment(0))) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert_eq!(allocator.stats().cached, SIZE);
+
+    allocator.into_inner(device).dispose(device);
+}
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use error::{MemoryError, OutOfMemoryError};
+use heaps::{BlockRequest, Config, Heaps};
+use memory::Properties;
+use usage::Data;
+
+use super::MockDevice;
+
+fn small_heaps() -> Heaps<u64> {
+    unsafe {
+        Heaps::new(
+            vec![(
+                Properties::DEVICE_LOCAL,
+                0,
+                Config {
+                    arena: None,
+                    dynamic: None,
+                },
+            )],
+            vec![1024],
+        )
+    }
+}
+
+fn request() -> BlockRequest<Data> {
+    BlockRequest {
+        mask: 1,
+        usage: Data,
+        size: 600,
+        align: 1,
+    }
+}
+
+#[test]
+fn retry_after_free() {
+    let mut heaps = small_heaps();
+    let ref device = MockDevice::new();
+
+    let mut held = Some(heaps.allocate(device, 1, Data, 600, 1).unwrap());
+    let mut calls = 0;
+    let block = heaps
+        .allocate_with_retry(device, request(), 3, Duration::default(), |heaps| {
+            calls += 1;
+            match held.take() {
+                Some(block) => {
+                    heaps.free(device, block);
+                    true
+                }
+                None => false,
+            }
+        })
+        .unwrap();
+    assert_eq!(calls, 1);
+
+    heaps.free(device, block);
+    heaps.dispose(device);
+}
+
+#[test]
+fn eviction_frees_for_near_budget_allocation() {
+    let mut heaps = small_heaps();
+    let ref device = MockDevice::new();
+
+    let mut cache = vec![
+        heaps.allocate(device, 1, Data, 400, 1).unwrap(),
+        heaps.allocate(device, 1, Data, 400, 1).unwrap(),
+    ];
+    let mut requests = Vec::new();
+    let block = heaps
+        .allocate_with_eviction(device, 1, Data, 600, 1, |heaps, size, memory_type| {
+            requests.push((size, memory_type));
+            match cache.pop() {
+                Some(block) => {
+                    heaps.free(device, block);
+                    400
+                }
+                None => 0,
+            }
+        })
+        .unwrap();
+    // Single eviction is enough.
+    assert_eq!(requests, vec![(600, 0)]);
+
+    heaps.free(device, block);
+    heaps.free(device, cache.pop().unwrap());
+    heaps.dispose(device);
+}
+
+#[test]
+fn eviction_targets_selected_memory_type() {
+    let config = Config {
+        arena: None,
+        dynamic: None,
+    };
+    let mut heaps = unsafe {
+        Heaps::new(
+            vec![
+                (Properties::DEVICE_LOCAL, 0, config),
+                (Properties::DEVICE_LOCAL, 1, config),
+            ],
+            vec![1024, 4096],
+        )
+    };
+    let ref device = MockDevice::new();
+
+    // Most suitable type can't fit the allocation, so the other one is selected.
+    let held = heaps.allocate(device, 0b01, Data, 600, 1).unwrap();
+    device.0.borrow_mut().exhausted.insert(1);
+
+    let mut requests = Vec::new();
+    let block = heaps
+        .allocate_with_eviction(device, 0b11, Data, 600, 1, |_, size, memory_type| {
+            requests.push((size, memory_type));
+            device.0.borrow_mut().exhausted.remove(&memory_type);
+            size
+        })
+        .unwrap();
+    assert_eq!(requests, vec![(600, 1)]);
+    assert_eq!(block.memory_type(), 1);
+
+    heaps.free(device, block);
+    heaps.free(device, held);
+    heaps.dispose(device);
+}
+
+#[test]
+fn eviction_gives_up() {
+    let mut heaps = small_heaps();
+    let ref device = MockDevice::new();
+
+    let held = heaps.allocate(device, 1, Data, 600, 1).unwrap();
+    let result = heaps.allocate_with_eviction(device, 1, Data, 600, 1, |_, _, _| 0);
+    assert!(result.unwrap_err().is_out_of_memory());
+
+    heaps.free(device, held);
+    heaps.dispose(device);
+}
+
+#[test]
+fn retry_gives_up() {
+    let mut heaps = small_heaps();
+    let ref device = MockDevice::new();
+
+    let held = heaps.allocate(device, 1, Data, 600, 1).unwrap();
+    let mut calls = 0;
+    let result = heaps.allocate_with_retry(device, request(), 3, Duration::default(), |_| {
+        calls += 1;
+        true
+    });
+    match result {
+        Err(MemoryError::OutOfMemoryError(OutOfMemoryError::HeapsExhausted)) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert_eq!(calls, 2);
+
+    heaps.free(device, held);
+    heaps.dispose(device);
+}
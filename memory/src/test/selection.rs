@@ -0,0 +1,202 @@
+use error::{AllocationError, MemoryError};
+use heaps::{suitable_types, Config, Heaps};
+use memory::Properties;
+use usage::UsageValue;
+
+use super::MockDevice;
+
+/// Properties and heap indices of memory types.
+fn types() -> Vec<(Properties, u32)> {
+    vec![
+        (Properties::DEVICE_LOCAL, 0),
+        (Properties::HOST_VISIBLE | Properties::HOST_COHERENT, 0),
+        (
+            Properties::DEVICE_LOCAL | Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+            0,
+        ),
+        (Properties::HOST_VISIBLE | Properties::HOST_CACHED, 1),
+    ]
+}
+
+fn heaps() -> Heaps<u64> {
+    let config = Config {
+        arena: None,
+        dynamic: None,
+    };
+    unsafe {
+        Heaps::new(
+            types()
+                .into_iter()
+                .map(|(properties, heap)| (properties, heap, config)),
+            vec![64 * 1024, 16 * 1024],
+        )
+    }
+}
+
+#[test]
+fn cached_selection_matches_uncached() {
+    let usages = [
+        UsageValue::Data,
+        UsageValue::Dynamic,
+        UsageValue::Upload,
+        UsageValue::Download,
+    ];
+    let ref device = MockDevice::new();
+    let mut heaps = heaps();
+
+    for mask in 1..16u32 {
+        for &usage in &usages {
+            let properties = types().into_iter().map(|(properties, _)| properties);
+            let expected = suitable_types(properties, mask, Properties::empty(), &usage)
+                .first()
+                .cloned();
+
+            // First allocation fills the cache, second one uses it.
+            for _ in 0..2 {
+                let memory_type = heaps
+                    .allocate(device, mask, usage, 1024, 1)
+                    .ok()
+                    .map(|block| {
+                        let memory_type = block.memory_type();
+                        heaps.free(device, block);
+                        memory_type
+                    });
+                assert_eq!(memory_type, expected, "Mask {:#b} with {:?}", mask, usage);
+            }
+        }
+    }
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn cached_selection_checks_heap_availability() {
+    let ref device = MockDevice::new();
+    let mut heaps = heaps();
+
+    let first = heaps
+        .allocate(device, 0b1010, UsageValue::Download, 1024, 1)
+        .unwrap();
+    assert_eq!(first.memory_type(), 3);
+
+    // Exhaust heap of the best fitting type. Cached selection must fall back.
+    let filler = heaps
+        .allocate(device, 0b1000, UsageValue::Download, 14 * 1024, 1)
+        .unwrap();
+    let second = heaps
+        .allocate(device, 0b1010, UsageValue::Download, 1024, 1)
+        .unwrap();
+    assert_eq!(second.memory_type(), 1);
+
+    heaps.free(device, filler);
+    let third = heaps
+        .allocate(device, 0b1010, UsageValue::Download, 1024, 1)
+        .unwrap();
+    assert_eq!(third.memory_type(), 3);
+
+    heaps.free(device, first);
+    heaps.free(device, second);
+    heaps.free(device, third);
+    heaps.dispose(device);
+}
+
+#[test]
+fn explicit_memory_type_used_verbatim() {
+    let ref device = MockDevice::new();
+    let mut heaps = heaps();
+
+    // Selection prefers host-cached type for downloads.
+    let block = heaps
+        .allocate_from_type(device, 0, 0b1111, UsageValue::Download, 1024, 1)
+        .unwrap();
+    assert_eq!(block.memory_type(), 0);
+
+    heaps.free(device, block);
+    heaps.dispose(device);
+}
+
+#[test]
+fn invalid_memory_type_rejected() {
+    let ref device = MockDevice::new();
+    let mut heaps = heaps();
+
+    match heaps.allocate_from_type(device, 4, !0, UsageValue::Data, 1024, 1) {
+        Err(MemoryError::AllocationError(AllocationError::InvalidMemoryType(4, 4))) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+    match heaps.allocate_from_type(device, 2, 0b1011, UsageValue::Data, 1024, 1) {
+        Err(MemoryError::AllocationError(AllocationError::IncompatibleMemoryType(2, 0b1011))) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn memory_types_beyond_mask_bits_rejected() {
+    let ref device = MockDevice::new();
+    let config = Config {
+        arena: None,
+        dynamic: None,
+    };
+    let mut heaps: Heaps<u64> = unsafe {
+        Heaps::new(
+            (0..33).map(|_| (Properties::DEVICE_LOCAL, 0, config)),
+            vec![64 * 1024],
+        )
+    };
+
+    match heaps.allocate_from_type(device, 32, !0, UsageValue::Data, 1024, 1) {
+        Err(MemoryError::AllocationError(AllocationError::IncompatibleMemoryType(32, _))) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let properties = (0..33).map(|_| Properties::DEVICE_LOCAL);
+    let suitable = suitable_types(properties, !0, Properties::empty(), &UsageValue::Data);
+    assert_eq!(suitable.len(), 32);
+    assert!(!suitable.contains(&32));
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn required_properties_narrow_selection() {
+    let ref device = MockDevice::new();
+    let mut heaps = heaps();
+
+    let mut select = |properties, usage| {
+        heaps
+            .allocate_with_properties(device, 0b1111, properties, usage, 1024, 1)
+            .map(|block| {
+                let memory_type = block.memory_type();
+                heaps.free(device, block);
+                memory_type
+            })
+    };
+
+    // All host-visible types fit upload, the best one is chosen.
+    assert_eq!(
+        select(Properties::HOST_VISIBLE, UsageValue::Upload).unwrap(),
+        1
+    );
+
+    // Same mask and usage, but only one type is cached.
+    assert_eq!(
+        select(Properties::HOST_CACHED, UsageValue::Upload).unwrap(),
+        3
+    );
+
+    // Device access requires device-local memory, only one of them is host-visible.
+    assert_eq!(
+        select(Properties::HOST_VISIBLE, UsageValue::Data).unwrap(),
+        2
+    );
+    assert_eq!(select(Properties::empty(), UsageValue::Data).unwrap(), 0);
+
+    match select(Properties::PROTECTED, UsageValue::Data) {
+        Err(MemoryError::AllocationError(AllocationError::NoSuitableMemory(0b1111, _))) => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    heaps.dispose(device);
+}
@@ -0,0 +1,74 @@
+use allocator::{
+    Allocator, AllocatorSnapshot, DedicatedAllocator, DedicatedCacheConfig, DynamicAllocator,
+    DynamicConfig, FitStrategy,
+};
+use memory::Properties;
+
+use super::MockDevice;
+
+#[test]
+fn dynamic_histogram_bins_live_blocks() {
+    let ref device = MockDevice::new();
+    let mut dynamic = DynamicAllocator::new(
+        0,
+        Properties::DEVICE_LOCAL,
+        DynamicConfig {
+            blocks_per_chunk: 64,
+            block_size_granularity: 256,
+            max_block_size: 32 * 1024,
+            fit: FitStrategy::FirstFit,
+        },
+    );
+
+    // With `guard-pages` feature requests are padded, so exact fits leave room for the guard.
+    let guard = 32 * 1024 - dynamic.max_allocation();
+    let blocks = [256 - guard, 256 - guard, 200, 1024 - guard, 1000, 600]
+        .iter()
+        .map(|&size| dynamic.alloc(device, size, 1).unwrap().0)
+        .collect::<Vec<_>>();
+
+    let snapshot = dynamic.snapshot();
+    // Chunk of 256 bytes class is a block of 16 KiB class, which is not counted.
+    assert_eq!(snapshot.histogram, vec![0, 0, 0, 0, 0, 0, 0, 0, 3, 1, 2]);
+    assert_eq!(snapshot.histogram[AllocatorSnapshot::bucket(768)], 1);
+    // Chunks of 256, 768, 1024 and 16 KiB classes.
+    assert_eq!(snapshot.chunks, 4);
+    assert_eq!(snapshot.fragmentation, 249.0 / 256.0);
+    assert_eq!(snapshot.largest_free, 63 * 16 * 1024);
+
+    for block in blocks {
+        dynamic.free(device, block);
+    }
+    assert_eq!(dynamic.snapshot(), AllocatorSnapshot::default());
+}
+
+#[test]
+fn dedicated_snapshot_counts_memory_objects() {
+    let ref device = MockDevice::new();
+    let mut dedicated = DedicatedAllocator::with_cache(
+        0,
+        Properties::DEVICE_LOCAL,
+        DedicatedCacheConfig {
+            max_per_size: 4,
+            max_bytes: 4 * 1024 * 1024,
+        },
+    );
+
+    let (small, _) = dedicated.alloc(device, 64 * 1024, 1).unwrap();
+    let (first, _) = dedicated.alloc(device, 1024 * 1024, 1).unwrap();
+    let (second, _) = dedicated.alloc(device, 1024 * 1024, 1).unwrap();
+    dedicated.free(device, first);
+
+    let snapshot = dedicated.snapshot();
+    assert_eq!(snapshot.histogram.len(), 21);
+    assert_eq!(snapshot.histogram[16], 1);
+    assert_eq!(snapshot.histogram[20], 1);
+    assert_eq!(snapshot.histogram.iter().sum::<u64>(), 2);
+    assert_eq!(snapshot.chunks, 3);
+    assert_eq!(snapshot.fragmentation, 0.0);
+    assert_eq!(snapshot.largest_free, 1024 * 1024);
+
+    dedicated.free(device, small);
+    dedicated.free(device, second);
+    dedicated.dispose(device);
+}
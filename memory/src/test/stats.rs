@@ -0,0 +1,62 @@
+use allocator::AllocatorStats;
+use usage::{Data, Upload};
+
+use super::{init, MockDevice};
+
+#[test]
+fn live_and_allocated_memory() {
+    let ref device = MockDevice::new();
+    let mut heaps = init();
+
+    let blocks = vec![
+        // Block of 1 KiB size class from 64 KiB chunk.
+        heaps
+            .allocate_from_type(device, 0, !0, Data, 1000, 1)
+            .unwrap(),
+        heaps
+            .allocate_from_type(device, 0, !0, Data, 1024 * 1024, 1)
+            .unwrap(),
+        // Both blocks are allocated from single 32 KiB arena.
+        heaps
+            .allocate_from_type(device, 2, !0, Upload, 4096, 1)
+            .unwrap(),
+        heaps
+            .allocate_from_type(device, 2, !0, Upload, 1000, 1)
+            .unwrap(),
+    ];
+
+    let stats = heaps.stats();
+    let local = AllocatorStats {
+        allocated: 64 * 1024 + 1024 * 1024,
+        used: 1024 + 1024 * 1024,
+        memory_objects: 2,
+    };
+    let host = AllocatorStats {
+        allocated: 32 * 1024,
+        used: 5096,
+        memory_objects: 1,
+    };
+    assert_eq!(
+        stats.types,
+        vec![
+            local,
+            AllocatorStats::default(),
+            host,
+            AllocatorStats::default()
+        ]
+    );
+    assert_eq!(stats.heaps, vec![local, AllocatorStats::default(), host]);
+    for (stats, utilization) in stats.heaps.iter().zip(heaps.heap_utilization()) {
+        assert_eq!(stats.allocated, utilization.used);
+    }
+
+    for block in blocks {
+        heaps.free(device, block);
+    }
+    for (stats, utilization) in heaps.stats().heaps.iter().zip(heaps.heap_utilization()) {
+        assert_eq!(stats.used, 0);
+        assert_eq!(stats.allocated, utilization.used);
+    }
+
+    heaps.dispose(device);
+}
@@ -0,0 +1,56 @@
+use heaps::{Config, HeapUtilization, Heaps};
+use memory::Properties;
+use usage::{Data, Download};
+
+use super::MockDevice;
+
+#[test]
+fn shared_heap_utilization() {
+    let config = Config {
+        arena: None,
+        dynamic: None,
+    };
+    let mut heaps = unsafe {
+        Heaps::new(
+            vec![
+                (Properties::DEVICE_LOCAL, 0, config),
+                (
+                    Properties::HOST_VISIBLE | Properties::HOST_COHERENT,
+                    0,
+                    config,
+                ),
+                (Properties::DEVICE_LOCAL, 1, config),
+            ],
+            vec![64 * 1024, 32 * 1024],
+        )
+    };
+    let ref device = MockDevice::new();
+
+    let data = heaps.allocate(device, 0b001, Data, 4096, 1).unwrap();
+    let download = heaps.allocate(device, 0b010, Download, 1024, 1).unwrap();
+    assert_eq!(data.memory_type(), 0);
+    assert_eq!(download.memory_type(), 1);
+
+    assert_eq!(
+        heaps.heap_utilization(),
+        vec![
+            HeapUtilization {
+                size: 64 * 1024,
+                used: 5120,
+                wasted: 0,
+            },
+            HeapUtilization {
+                size: 32 * 1024,
+                used: 0,
+                wasted: 0,
+            },
+        ]
+    );
+
+    heaps.free(device, data);
+    assert_eq!(heaps.heap_utilization()[0].used, 1024);
+    heaps.free(device, download);
+    assert_eq!(heaps.heap_utilization()[0].used, 0);
+
+    heaps.dispose(device);
+}
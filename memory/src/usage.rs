@@ -32,7 +32,9 @@ impl Usage for Data {
 
     #[inline]
     fn memory_fitness(&self, properties: Properties) -> Option<u8> {
-        if !properties.contains(Properties::DEVICE_LOCAL) {
+        if !properties.contains(Properties::DEVICE_LOCAL)
+            || properties.contains(Properties::PROTECTED)
+        {
             None
         } else {
             Some(
@@ -137,8 +139,66 @@ impl Usage for Download {
     }
 }
 
-/// Dynamic value that specify memory usage flags.
+/// Device-only access to protected content.
+/// Requires memory with `PROTECTED` property.
+/// Memory of this usage can't be mapped.
+#[derive(Clone, Copy, Debug)]
+pub struct Protected;
+
+impl Usage for Protected {
+    type Fitness = u8;
+
+    #[inline]
+    fn value(self) -> UsageValue {
+        UsageValue::Protected
+    }
+
+    #[inline]
+    fn memory_fitness(&self, properties: Properties) -> Option<u8> {
+        if !properties.contains(Properties::PROTECTED) {
+            None
+        } else {
+            Some(
+                (properties.contains(Properties::DEVICE_LOCAL) as u8) << 1
+                    | (!properties.contains(Properties::LAZILY_ALLOCATED)) as u8,
+            )
+        }
+    }
+}
+
+/// Device-only access to transient attachments.
+/// Prefers memory with `LAZILY_ALLOCATED` property
+/// that is committed only when device touches it.
+/// Falls back to device-local memory.
+/// Memory of this usage can't be mapped.
 #[derive(Clone, Copy, Debug)]
+pub struct Transient;
+
+impl Usage for Transient {
+    type Fitness = u8;
+
+    #[inline]
+    fn value(self) -> UsageValue {
+        UsageValue::Transient
+    }
+
+    #[inline]
+    fn memory_fitness(&self, properties: Properties) -> Option<u8> {
+        if !properties.contains(Properties::DEVICE_LOCAL)
+            || properties.contains(Properties::PROTECTED)
+        {
+            None
+        } else {
+            Some(
+                (properties.contains(Properties::LAZILY_ALLOCATED) as u8) << 1
+                    | (!properties.contains(Properties::HOST_VISIBLE)) as u8,
+            )
+        }
+    }
+}
+
+/// Dynamic value that specify memory usage flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum UsageValue {
     /// Runtime counterpart for `Data`.
     Data,
@@ -148,6 +208,10 @@ pub enum UsageValue {
     Upload,
     /// Runtime counterpart for `Download`.
     Download,
+    /// Runtime counterpart for `Protected`.
+    Protected,
+    /// Runtime counterpart for `Transient`.
+    Transient,
 }
 
 impl Usage for UsageValue {
@@ -165,6 +229,8 @@ impl Usage for UsageValue {
             UsageValue::Dynamic => Dynamic.memory_fitness(properties),
             UsageValue::Upload => Upload.memory_fitness(properties),
             UsageValue::Download => Download.memory_fitness(properties),
+            UsageValue::Protected => Protected.memory_fitness(properties),
+            UsageValue::Transient => Transient.memory_fitness(properties),
         }
     }
 }
@@ -1,5 +1,5 @@
 use command::Families;
-use memory::{Config as MemoryConfig, Heaps, MemoryError, Properties, Usage};
+use memory::{Config as MemoryConfig, Heaps, Properties, Usage};
 use resource::{
     buffer::{self, Buffer},
     image::{self, Image},
@@ -50,7 +50,7 @@ where
         sharing: SharingMode,
         align: u64,
         memory_usage: U,
-    ) -> Result<Buffer<D::Memory, D::Buffer>, MemoryError>
+    ) -> Result<Buffer<D::Memory, D::Buffer>, ResourceError>
     where
         U: Usage,
     {
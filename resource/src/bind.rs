@@ -0,0 +1,66 @@
+//! Helpers to bind resources to memory blocks.
+
+use memory::Block;
+
+use device::Device;
+use error::BindError;
+
+/// Bind buffer to the memory block at the block's offset.
+///
+/// # Panics
+///
+/// This function panics if block offset doesn't satisfy buffer alignment requirement.
+///
+/// # Safety
+///
+/// Block must be allocated from memory type allowed by buffer requirements
+/// and must be large enough to hold the buffer.
+/// No other resource may be bound to the block.
+pub unsafe fn bind_buffer<D, B>(
+    device: &D,
+    buffer: D::UnboundBuffer,
+    block: &B,
+) -> Result<D::Buffer, BindError>
+where
+    D: Device,
+    B: Block<Memory = D::Memory>,
+{
+    let align = device.buffer_requirements(&buffer).align;
+    assert_aligned(block.offset(), align);
+    device.bind_buffer(buffer, block.memory(), block.offset())
+}
+
+/// Bind image to the memory block at the block's offset.
+///
+/// # Panics
+///
+/// This function panics if block offset doesn't satisfy image alignment requirement.
+///
+/// # Safety
+///
+/// Block must be allocated from memory type allowed by image requirements
+/// and must be large enough to hold the image.
+/// No other resource may be bound to the block.
+pub unsafe fn bind_image<D, B>(
+    device: &D,
+    image: D::UnboundImage,
+    block: &B,
+) -> Result<D::Image, BindError>
+where
+    D: Device,
+    B: Block<Memory = D::Memory>,
+{
+    let align = device.image_requirements(&image).align;
+    assert_aligned(block.offset(), align);
+    device.bind_image(image, block.memory(), block.offset())
+}
+
+fn assert_aligned(offset: u64, align: u64) {
+    assert_eq!(
+        offset % align,
+        0,
+        "Block offset {} doesn't satisfy alignment {}",
+        offset,
+        align
+    );
+}
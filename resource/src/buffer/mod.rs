@@ -7,7 +7,7 @@ use memory::MemoryBlock;
 use relevant::Relevant;
 
 use escape::Escape;
-use SharingMode;
+use {MemoryRequirements, SharingMode};
 
 /// Contains information required to create a buffer.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -32,6 +32,36 @@ pub struct CreateInfo {
 pub struct Buffer<M, B> {
     pub(crate) inner: Escape<Inner<M, B>>,
     pub(crate) info: CreateInfo,
+    pub(crate) requirements: MemoryRequirements,
+}
+
+impl<M, B> Buffer<M, B> {
+    /// Get info used to create the buffer.
+    pub fn info(&self) -> &CreateInfo {
+        &self.info
+    }
+
+    /// Get memory requirements of the buffer.
+    /// Blocks the buffer is rebound to must satisfy them.
+    pub fn requirements(&self) -> MemoryRequirements {
+        self.requirements
+    }
+
+    /// Get memory block the buffer is bound to.
+    pub fn block(&self) -> &MemoryBlock<M> {
+        &self.inner.block
+    }
+
+    /// Get mutable memory block the buffer is bound to.
+    /// Block can be mapped through this reference.
+    pub fn block_mut(&mut self) -> &mut MemoryBlock<M> {
+        &mut self.inner.block
+    }
+
+    /// Get raw buffer object.
+    pub fn raw(&self) -> &B {
+        &self.inner.raw
+    }
 }
 
 #[derive(Debug)]
@@ -1,5 +1,6 @@
 use buffer;
 use error;
+use features::DeviceFeatures;
 use image;
 use memory;
 use MemoryRequirements;
@@ -84,4 +85,11 @@ pub trait Device: memory::Device + Sized {
 
     /// Destroy image object.
     unsafe fn destroy_image(&self, image: Self::Image);
+
+    /// Probe optional features supported and enabled on the device.
+    /// Called once at initialization. Result is consulted before using optional code paths.
+    /// Devices that can't tell report no features.
+    fn features(&self) -> DeviceFeatures {
+        DeviceFeatures::default()
+    }
 }
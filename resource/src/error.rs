@@ -44,6 +44,16 @@ pub enum BindError {
     #[fail(display = "Binding to out of bounds memory")]
     OutOfBounds,
 
+    /// Requested binding at offset that doesn't satisfy alignment requirement.
+    #[fail(display = "Binding offset {} doesn't satisfy alignment {}", offset, align)]
+    Unaligned {
+        /// Offset of the binding.
+        offset: u64,
+
+        /// Required alignment.
+        align: u64,
+    },
+
     /// The memory of the host or device is used up.
     #[fail(display = "Out of memory")]
     OutOfMemoryError(memory::OutOfMemoryError),
@@ -0,0 +1,71 @@
+use image::Layout;
+
+/// Optional device functionality.
+/// Queried once when device is initialized, see `Device::features`,
+/// or built from names of enabled extensions with `DeviceFeatures::from_extensions`.
+/// Code paths that depend on a feature consult this structure
+/// and fall back to core functionality when the feature is absent.
+/// Default value has all features absent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeviceFeatures {
+    /// Depth and stencil aspects of an image can be in different layouts.
+    /// See `VK_KHR_separate_depth_stencil_layouts`.
+    /// When absent, layouts of single aspect are replaced with combined depth-stencil layouts
+    /// and depth and stencil aspects of an image can't be tracked separately.
+    pub separate_depth_stencil_layouts: bool,
+
+    /// Debug labels are forwarded to capture tools.
+    /// See `VK_EXT_debug_utils`.
+    /// When absent, label commands are not loaded and labels are dropped.
+    pub debug_utils: bool,
+
+    /// Heap budgets can be queried from the device.
+    /// See `VK_EXT_memory_budget`.
+    /// When absent, only total heap sizes are known.
+    pub memory_budget: bool,
+
+    /// Semaphores with monotonic counter payload.
+    /// See `VK_KHR_timeline_semaphore`.
+    /// When absent, binary semaphore is created per cross-queue dependency.
+    pub timeline_semaphores: bool,
+}
+
+impl DeviceFeatures {
+    /// Get features enabled by instance and device extensions with given names.
+    /// Unknown extensions are ignored.
+    pub fn from_extensions<'a, I>(extensions: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut features = DeviceFeatures::default();
+        for extension in extensions {
+            match extension {
+                "VK_KHR_separate_depth_stencil_layouts" => {
+                    features.separate_depth_stencil_layouts = true
+                }
+                "VK_EXT_debug_utils" => features.debug_utils = true,
+                "VK_EXT_memory_budget" => features.memory_budget = true,
+                "VK_KHR_timeline_semaphore" => features.timeline_semaphores = true,
+                _ => {}
+            }
+        }
+        features
+    }
+
+    /// Get layout to use in place of `layout`.
+    /// Returns `layout` itself if device supports it.
+    pub fn image_layout(&self, layout: Layout) -> Layout {
+        if self.separate_depth_stencil_layouts {
+            return layout;
+        }
+        match layout {
+            Layout::DepthAttachmentOptimal | Layout::StencilAttachmentOptimal => {
+                Layout::DepthStencilAttachmentOptimal
+            }
+            Layout::DepthReadOnlyOptimal | Layout::StencilReadOnlyOptimal => {
+                Layout::DepthStencilReadOnlyOptimal
+            }
+            layout => layout,
+        }
+    }
+}
@@ -2,6 +2,8 @@
 
 pub use self::Format::*;
 
+use super::ImageAspectFlags;
+
 /// Image format.
 #[allow(bad_style, missing_docs)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -268,3 +270,82 @@ pub enum Format {
     // G16_B16R16_2PLANE_422_UNORM_KHR = Format::G16_B16R16_2PLANE_422_UNORM as isize,
     // G16_B16_R16_3PLANE_444_UNORM_KHR = Format::G16_B16_R16_3PLANE_444_UNORM as isize,
 }
+
+impl Format {
+    /// Size of the texel block in bytes.
+    /// Returns `None` for formats that can't be copied as a whole
+    /// such as combined depth-stencil and multi-planar formats.
+    pub fn block_size(&self) -> Option<u32> {
+        match *self as u32 {
+            1 | 9..=15 | 127 => Some(1),
+            2..=8 | 16..=22 | 70..=76 | 124 => Some(2),
+            23..=36 => Some(3),
+            37..=69 | 77..=83 | 98..=100 | 122 | 123 | 125 | 126 => Some(4),
+            84..=90 => Some(6),
+            91..=97 | 101..=103 | 110..=112 => Some(8),
+            104..=106 => Some(12),
+            107..=109 | 113..=115 => Some(16),
+            116..=118 => Some(24),
+            119..=121 => Some(32),
+            131..=134 | 139 | 140 | 147..=150 | 153 | 154 => Some(8),
+            135..=138 | 141..=146 | 151 | 152 | 155 | 156 => Some(16),
+            157..=184 => Some(16),
+            1000054000..=1000054007 => Some(8),
+            _ => None,
+        }
+    }
+
+    /// Width and height of the texel block.
+    /// Uncompressed formats have blocks of single texel.
+    pub fn block_extent(&self) -> (u32, u32) {
+        const ASTC: [(u32, u32); 14] = [
+            (4, 4),
+            (5, 4),
+            (5, 5),
+            (6, 5),
+            (6, 6),
+            (8, 5),
+            (8, 6),
+            (8, 8),
+            (10, 5),
+            (10, 6),
+            (10, 8),
+            (10, 10),
+            (12, 10),
+            (12, 12),
+        ];
+
+        match *self {
+            PVRTC1_2BPP_UNORM_BLOCK_IMG
+            | PVRTC2_2BPP_UNORM_BLOCK_IMG
+            | PVRTC1_2BPP_SRGB_BLOCK_IMG
+            | PVRTC2_2BPP_SRGB_BLOCK_IMG => (8, 4),
+            PVRTC1_4BPP_UNORM_BLOCK_IMG
+            | PVRTC2_4BPP_UNORM_BLOCK_IMG
+            | PVRTC1_4BPP_SRGB_BLOCK_IMG
+            | PVRTC2_4BPP_SRGB_BLOCK_IMG => (4, 4),
+            _ => match *self as u32 {
+                131..=156 => (4, 4),
+                index @ 157..=184 => ASTC[(index as usize - 157) / 2],
+                _ => (1, 1),
+            },
+        }
+    }
+
+    /// Check if format is block-compressed.
+    pub fn is_compressed(&self) -> bool {
+        self.block_extent() != (1, 1)
+    }
+
+    /// Aspects of the image data in this format.
+    pub fn aspects(&self) -> ImageAspectFlags {
+        match *self {
+            D16_UNORM | X8_D24_UNORM_PACK32 | D32_SFLOAT => ImageAspectFlags::DEPTH,
+            S8_UINT => ImageAspectFlags::STENCIL,
+            D16_UNORM_S8_UINT | D24_UNORM_S8_UINT | D32_SFLOAT_S8_UINT => {
+                ImageAspectFlags::DEPTH | ImageAspectFlags::STENCIL
+            }
+            _ => ImageAspectFlags::COLOR,
+        }
+    }
+}
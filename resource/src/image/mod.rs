@@ -1,16 +1,20 @@
 //! Image usage, format, kind, extent, creation-info and wrappers.
 
 pub mod format;
+mod upload;
 mod usage;
 
 pub use self::format::Format;
+pub use self::upload::{mip_extent, stage_texture, BufferImageCopy, StagedTexture, StagingAlignment};
 pub use self::usage::*;
 
+use std::ops::Range;
+
 use memory::MemoryBlock;
 use relevant::Relevant;
 
 use escape::Escape;
-use SharingMode;
+use {MemoryRequirements, SharingMode};
 
 /// Image dimensionality
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -117,6 +121,22 @@ pub enum Layout {
     /// stencil aspect is read-only and/or as read-only image in shader where only stencil aspect is accessed.
     DepthAttachmentStencilReadOnlyOptimal = 1000117001,
 
+    /// Images with this layout can be used as depth attachment
+    /// when depth and stencil aspects are in separate layouts.
+    DepthAttachmentOptimal = 1000241000,
+
+    /// Images with this layout can be used as read-only depth attachment
+    /// and/or as read-only image in shader when depth and stencil aspects are in separate layouts.
+    DepthReadOnlyOptimal = 1000241001,
+
+    /// Images with this layout can be used as stencil attachment
+    /// when depth and stencil aspects are in separate layouts.
+    StencilAttachmentOptimal = 1000241002,
+
+    /// Images with this layout can be used as read-only stencil attachment
+    /// and/or as read-only image in shader when depth and stencil aspects are in separate layouts.
+    StencilReadOnlyOptimal = 1000241003,
+
     /// Image with this layout can be presented to the surface.
     /// Only images from swapchain are presentable.
     /// Note: Images can't be presented in `General` layout.
@@ -142,6 +162,38 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Bitmask specifying which aspects of an image are included in a view or range.
+    /// See Vulkan docs for detailed info:
+    /// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkImageAspectFlagBits.html>
+    #[repr(transparent)]
+    pub struct ImageAspectFlags: u32 {
+        /// Specifies the color aspect.
+        const COLOR = 0x00000001;
+        /// Specifies the depth aspect.
+        const DEPTH = 0x00000002;
+        /// Specifies the stencil aspect.
+        const STENCIL = 0x00000004;
+        /// Specifies the metadata aspect, used for sparse resource operations.
+        const METADATA = 0x00000008;
+    }
+}
+
+/// Range of mip levels and array layers of an image.
+/// See Vulkan docs for detailed info:
+/// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkImageSubresourceRange.html>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubresourceRange {
+    /// Aspects included in the range.
+    pub aspects: ImageAspectFlags,
+
+    /// Mip levels included in the range.
+    pub levels: Range<u32>,
+
+    /// Array layers included in the range.
+    pub layers: Range<u32>,
+}
+
 /// Contains information required to create an image.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CreateInfo {
@@ -186,6 +238,30 @@ pub struct CreateInfo {
 pub struct Image<M, I> {
     pub(super) inner: Escape<Inner<M, I>>,
     pub(super) info: CreateInfo,
+    pub(super) requirements: MemoryRequirements,
+}
+
+impl<M, I> Image<M, I> {
+    /// Get info used to create the image.
+    pub fn info(&self) -> &CreateInfo {
+        &self.info
+    }
+
+    /// Get memory requirements of the image.
+    /// Blocks the image is rebound to must satisfy them.
+    pub fn requirements(&self) -> MemoryRequirements {
+        self.requirements
+    }
+
+    /// Get memory block the image is bound to.
+    pub fn block(&self) -> &MemoryBlock<M> {
+        &self.inner.block
+    }
+
+    /// Get raw image object.
+    pub fn raw(&self) -> &I {
+        &self.inner.raw
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,164 @@
+//! Staging layout for texture uploads.
+
+use std::ops::Range;
+
+use super::{CreateInfo, Extent3D, ImageAspectFlags};
+
+/// Alignment requirements for texture data in staging buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StagingAlignment {
+    /// Required alignment of offset of each mip level.
+    /// Offsets are additionally aligned to the texel block size and 4 as copy commands require.
+    pub offset: u64,
+
+    /// Required alignment of row pitch.
+    /// Pitch is additionally aligned to the texel block size.
+    pub row_pitch: u64,
+}
+
+impl Default for StagingAlignment {
+    fn default() -> Self {
+        StagingAlignment {
+            offset: 1,
+            row_pitch: 1,
+        }
+    }
+}
+
+/// Buffer to image copy region.
+/// See Vulkan docs for detailed info:
+/// <https://www.khronos.org/registry/vulkan/specs/1.1-extensions/man/html/VkBufferImageCopy.html>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BufferImageCopy {
+    /// Offset of the region data in the buffer.
+    pub buffer_offset: u64,
+
+    /// Length of buffer row in texels.
+    /// Always multiple of texel block width.
+    pub buffer_row_length: u32,
+
+    /// Height of the image slice in the buffer in texels.
+    /// Always multiple of texel block height.
+    pub buffer_image_height: u32,
+
+    /// Aspects of the image to copy data to.
+    pub aspects: ImageAspectFlags,
+
+    /// Mip level to copy data to.
+    pub mip_level: u32,
+
+    /// Array layers to copy data to.
+    pub layers: Range<u32>,
+
+    /// Extent of the mip level.
+    /// Not rounded to block size.
+    pub image_extent: Extent3D,
+}
+
+/// Texture data laid out for copying from staging buffer.
+#[derive(Clone, Debug)]
+pub struct StagedTexture {
+    /// Data to be written into staging buffer.
+    pub data: Vec<u8>,
+
+    /// Copy regions to record. One per mip level.
+    pub regions: Vec<BufferImageCopy>,
+}
+
+/// Lay out pixel data of all mip levels for copying from staging buffer to image.
+/// See `rendy_command::upload_texture` that stages the data and records the copy.
+///
+/// `mips` contains tightly packed data for each mip level of the image:
+/// rows of texel blocks for every depth slice of every array layer.
+///
+/// # Panics
+///
+/// This function will panic if image format can't be copied as a whole,
+/// if number of mip levels differs from `info.mips`
+/// or if any mip level has size that doesn't match its extent.
+pub fn stage_texture(
+    info: &CreateInfo,
+    mips: &[&[u8]],
+    alignment: StagingAlignment,
+) -> StagedTexture {
+    let block_size = info
+        .format
+        .block_size()
+        .expect("Format must have texel block size") as u64;
+    let (block_width, block_height) = info.format.block_extent();
+    assert_eq!(
+        mips.len(),
+        info.mips as usize,
+        "Data must be provided for all mip levels"
+    );
+
+    let offset_align = lcm(lcm(alignment.offset, block_size), 4);
+    let row_align = lcm(alignment.row_pitch, block_size);
+
+    let mut data = Vec::new();
+    let mut regions = Vec::with_capacity(mips.len());
+
+    for (level, mip) in mips.iter().enumerate() {
+        let extent = mip_extent(info.extent, level as u32);
+        let blocks_x = (extent.width + block_width - 1) / block_width;
+        let blocks_y = (extent.height + block_height - 1) / block_height;
+        let rows = blocks_y as u64 * extent.depth as u64 * info.array as u64;
+
+        let tight_pitch = blocks_x as u64 * block_size;
+        let pitch = aligned(tight_pitch, row_align);
+        assert_eq!(
+            mip.len() as u64,
+            tight_pitch * rows,
+            "Mip level {} data size doesn't match its extent",
+            level
+        );
+
+        let offset = aligned(data.len() as u64, offset_align);
+        data.resize((offset + pitch * rows) as usize, 0);
+        for row in 0..rows {
+            let src = (row * tight_pitch) as usize;
+            let dst = (offset + row * pitch) as usize;
+            data[dst..dst + tight_pitch as usize]
+                .copy_from_slice(&mip[src..src + tight_pitch as usize]);
+        }
+
+        regions.push(BufferImageCopy {
+            buffer_offset: offset,
+            buffer_row_length: (pitch / block_size) as u32 * block_width,
+            buffer_image_height: blocks_y * block_height,
+            aspects: info.format.aspects(),
+            mip_level: level as u32,
+            layers: 0..info.array,
+            image_extent: extent,
+        });
+    }
+
+    StagedTexture { data, regions }
+}
+
+/// Get extent of the mip level.
+pub fn mip_extent(extent: Extent3D, level: u32) -> Extent3D {
+    Extent3D {
+        width: (extent.width >> level).max(1),
+        height: (extent.height >> level).max(1),
+        depth: (extent.depth >> level).max(1),
+    }
+}
+
+fn aligned(value: u64, align: u64) -> u64 {
+    debug_assert_ne!(align, 0);
+    (value + align - 1) / align * align
+}
+
+fn lcm(left: u64, right: u64) -> u64 {
+    left / gcd(left, right) * right
+}
+
+fn gcd(mut left: u64, mut right: u64) -> u64 {
+    while right != 0 {
+        let rem = left % right;
+        left = right;
+        right = rem;
+    }
+    left
+}
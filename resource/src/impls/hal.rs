@@ -7,6 +7,7 @@ use hal::Device as HalDevice;
 use buffer;
 use device::Device;
 use error;
+use features::DeviceFeatures;
 use image;
 use memory;
 use MemoryRequirements;
@@ -534,4 +535,12 @@ where
     unsafe fn destroy_image(&self, image: Self::Image) {
         self.0.borrow().destroy_image(image);
     }
+
+    fn features(&self) -> DeviceFeatures {
+        // Backends forward debug markers where supported and ignore them otherwise.
+        DeviceFeatures {
+            debug_utils: true,
+            ..DeviceFeatures::default()
+        }
+    }
 }
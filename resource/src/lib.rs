@@ -37,17 +37,24 @@ extern crate gfx_hal as hal;
 #[cfg(feature = "ash")]
 extern crate ash;
 
+mod bind;
 mod device;
 mod error;
 mod escape;
+mod features;
 mod impls;
 mod resources;
 
+#[cfg(test)]
+mod test;
+
 pub mod buffer;
 pub mod image;
 
+pub use bind::{bind_buffer, bind_image};
 pub use device::Device;
-pub use error::{ImageCreationError, ResourceError};
+pub use error::{BindError, ImageCreationError, ResourceError};
+pub use features::DeviceFeatures;
 pub use resources::Resources;
 
 /// Sharing mode.
@@ -1,14 +1,16 @@
 use std::cmp::max;
 use std::default::Default;
 
-use memory::{Block, Heaps, MemoryError, Usage as MemoryUsage};
+use memory::{Block, Heaps, MemoryBlock, MemoryError, Usage as MemoryUsage};
 use relevant::Relevant;
 
+use bind::{bind_buffer, bind_image};
 use buffer;
 use device::Device;
-use error::ResourceError;
+use error::{BindError, ResourceError};
 use escape::{Escape, Terminal};
 use image;
+use MemoryRequirements;
 
 /// Resource manager.
 /// It can be used to create and destroy resources such as buffers and images.
@@ -26,6 +28,7 @@ impl<M: 'static, B: 'static, I: 'static> Resources<M, B, I> {
     }
 
     /// Create a buffer and bind to the memory that support intended usage.
+    /// Memory block is allocated with alignment required by both the buffer and `align`.
     pub fn create_buffer<D, U>(
         &mut self,
         device: &D,
@@ -33,25 +36,29 @@ impl<M: 'static, B: 'static, I: 'static> Resources<M, B, I> {
         info: buffer::CreateInfo,
         align: u64,
         memory_usage: U,
-    ) -> Result<buffer::Buffer<M, B>, MemoryError>
+    ) -> Result<buffer::Buffer<M, B>, ResourceError>
     where
         D: Device<Memory = M, Buffer = B>,
         U: MemoryUsage,
     {
-        let ubuf = device.create_buffer(info)?;
+        let ubuf = device
+            .create_buffer(info)
+            .map_err(MemoryError::from)?;
         let reqs = device.buffer_requirements(&ubuf);
-        let block = heaps.allocate(
-            device,
-            reqs.mask,
-            memory_usage,
-            reqs.size,
-            max(reqs.align, align),
-        )?;
-
-        let buf = unsafe {
-            device
-                .bind_buffer(ubuf, block.memory(), block.range().start)
-                .unwrap()
+        let align = max(reqs.align, align);
+        let block = heaps.allocate(device, reqs.mask, memory_usage, reqs.size, align)?;
+        assert_eq!(
+            block.offset() % align,
+            0,
+            "Allocated block must satisfy alignment requirements"
+        );
+
+        let buf = match unsafe { bind_buffer(device, ubuf, &block) } {
+            Ok(buf) => buf,
+            Err(error) => {
+                heaps.free(device, block);
+                return Err(error.into());
+            }
         };
 
         Ok(buffer::Buffer {
@@ -61,6 +68,7 @@ impl<M: 'static, B: 'static, I: 'static> Resources<M, B, I> {
                 relevant: Relevant,
             }),
             info,
+            requirements: reqs,
         })
     }
 
@@ -79,9 +87,65 @@ impl<M: 'static, B: 'static, I: 'static> Resources<M, B, I> {
     {
         device.destroy_buffer(inner.raw);
         heaps.free(device, inner.block);
+        inner.relevant.dispose();
+    }
+
+    /// Recreate buffer bound to another memory block,
+    /// e.g. after defragmentation moved its content.
+    /// Most backends can't rebind existing resource so new one is created with original create info.
+    /// Content is not copied.
+    ///
+    /// Old raw buffer and block may still be used by frames in flight.
+    /// They are destroyed by `cleanup` that must be called after those frames are complete.
+    /// On failure `block` is freed and `buffer` stays bound to the old block.
+    /// Returns `BindError::OutOfBounds` if `block` is too small for the buffer
+    /// and `BindError::Unaligned` if it doesn't satisfy buffer alignment requirement.
+    ///
+    /// # Safety
+    ///
+    /// `block` must be allocated from `heaps`
+    /// from memory type allowed by buffer requirements.
+    pub unsafe fn rebind_buffer<D>(
+        &mut self,
+        device: &D,
+        heaps: &mut Heaps<M>,
+        buffer: &mut buffer::Buffer<M, B>,
+        block: MemoryBlock<M>,
+    ) -> Result<(), ResourceError>
+    where
+        D: Device<Memory = M, Buffer = B>,
+    {
+        if let Err(error) = check_fits(&block, buffer.requirements) {
+            heaps.free(device, block);
+            return Err(error.into());
+        }
+        let ubuf = match device.create_buffer(buffer.info) {
+            Ok(ubuf) => ubuf,
+            Err(error) => {
+                heaps.free(device, block);
+                return Err(MemoryError::from(error).into());
+            }
+        };
+
+        let buf = match bind_buffer(device, ubuf, &block) {
+            Ok(buf) => buf,
+            Err(error) => {
+                heaps.free(device, block);
+                return Err(error.into());
+            }
+        };
+
+        // Dropped old buffer is sent to the terminal until `cleanup`.
+        buffer.inner = self.buffers.escape(buffer::Inner {
+            raw: buf,
+            block,
+            relevant: Relevant,
+        });
+        Ok(())
     }
 
     /// Create an image and bind to the memory that support intended usage.
+    /// Memory block is allocated with alignment required by both the image and `align`.
     pub fn create_image<D, U>(
         &mut self,
         device: &D,
@@ -96,15 +160,21 @@ impl<M: 'static, B: 'static, I: 'static> Resources<M, B, I> {
     {
         let uimg = device.create_image(info)?;
         let reqs = device.image_requirements(&uimg);
-        let block = heaps.allocate(
-            device,
-            reqs.mask,
-            memory_usage,
-            reqs.size,
-            max(reqs.align, align),
-        )?;
+        let align = max(reqs.align, align);
+        let block = heaps.allocate(device, reqs.mask, memory_usage, reqs.size, align)?;
+        assert_eq!(
+            block.offset() % align,
+            0,
+            "Allocated block must satisfy alignment requirements"
+        );
 
-        let img = unsafe { device.bind_image(uimg, block.memory(), block.range().start)? };
+        let img = match unsafe { bind_image(device, uimg, &block) } {
+            Ok(img) => img,
+            Err(error) => {
+                heaps.free(device, block);
+                return Err(error.into());
+            }
+        };
 
         Ok(image::Image {
             inner: self.images.escape(image::Inner {
@@ -113,6 +183,7 @@ impl<M: 'static, B: 'static, I: 'static> Resources<M, B, I> {
                 relevant: Relevant,
             }),
             info,
+            requirements: reqs,
         })
     }
 
@@ -131,6 +202,54 @@ impl<M: 'static, B: 'static, I: 'static> Resources<M, B, I> {
     {
         device.destroy_image(inner.raw);
         heaps.free(device, inner.block);
+        inner.relevant.dispose();
+    }
+
+    /// Recreate image bound to another memory block.
+    /// See `rebind_buffer` for details.
+    /// Returns `BindError::OutOfBounds` if `block` is too small for the image
+    /// and `BindError::Unaligned` if it doesn't satisfy image alignment requirement.
+    ///
+    /// # Safety
+    ///
+    /// `block` must be allocated from `heaps`
+    /// from memory type allowed by image requirements.
+    pub unsafe fn rebind_image<D>(
+        &mut self,
+        device: &D,
+        heaps: &mut Heaps<M>,
+        image: &mut image::Image<M, I>,
+        block: MemoryBlock<M>,
+    ) -> Result<(), ResourceError>
+    where
+        D: Device<Memory = M, Image = I>,
+    {
+        if let Err(error) = check_fits(&block, image.requirements) {
+            heaps.free(device, block);
+            return Err(error.into());
+        }
+        let uimg = match device.create_image(image.info) {
+            Ok(uimg) => uimg,
+            Err(error) => {
+                heaps.free(device, block);
+                return Err(error.into());
+            }
+        };
+
+        let img = match bind_image(device, uimg, &block) {
+            Ok(img) => img,
+            Err(error) => {
+                heaps.free(device, block);
+                return Err(error.into());
+            }
+        };
+
+        image.inner = self.images.escape(image::Inner {
+            raw: img,
+            block,
+            relevant: Relevant,
+        });
+        Ok(())
     }
 
     /// Recycle dropped resources.
@@ -139,13 +258,29 @@ impl<M: 'static, B: 'static, I: 'static> Resources<M, B, I> {
         D: Device<Memory = M, Buffer = B, Image = I>,
     {
         for buffer in self.buffers.drain() {
-            device.destroy_buffer(buffer.raw);
-            heaps.free(device, buffer.block);
+            Self::destroy_buffer_inner(buffer, device, heaps);
         }
 
         for image in self.images.drain() {
-            device.destroy_image(image.raw);
-            heaps.free(device, image.block);
+            Self::destroy_image_inner(image, device, heaps);
         }
     }
 }
+
+/// Check that resource with `requirements` can be bound to the block.
+fn check_fits<M: 'static>(
+    block: &MemoryBlock<M>,
+    requirements: MemoryRequirements,
+) -> Result<(), BindError> {
+    let range = block.range();
+    if range.end - range.start < requirements.size {
+        Err(BindError::OutOfBounds)
+    } else if range.start % requirements.align != 0 {
+        Err(BindError::Unaligned {
+            offset: range.start,
+            align: requirements.align,
+        })
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use features::DeviceFeatures;
+use image::Layout;
+
+#[test]
+fn features_from_enabled_extensions() {
+    let features = DeviceFeatures::from_extensions(vec![
+        "VK_KHR_swapchain",
+        "VK_EXT_debug_utils",
+        "VK_EXT_memory_budget",
+    ]);
+    assert_eq!(
+        features,
+        DeviceFeatures {
+            debug_utils: true,
+            memory_budget: true,
+            ..DeviceFeatures::default()
+        }
+    );
+}
+
+#[test]
+fn absent_separate_layouts_fall_back_to_combined() {
+    let absent = DeviceFeatures::default();
+    let present = DeviceFeatures::from_extensions(vec!["VK_KHR_separate_depth_stencil_layouts"]);
+    assert_eq!(
+        absent.image_layout(Layout::DepthReadOnlyOptimal),
+        Layout::DepthStencilReadOnlyOptimal
+    );
+    assert_eq!(
+        present.image_layout(Layout::DepthReadOnlyOptimal),
+        Layout::DepthReadOnlyOptimal
+    );
+}
@@ -0,0 +1,184 @@
+use std::{cell::Cell, ops::Range, ptr::NonNull};
+
+use memory::{
+    self,
+    allocator::{DynamicConfig, FitStrategy},
+    AllocationError, Config, Heaps, MappingError, OutOfMemoryError, Properties,
+};
+
+use buffer;
+use device::Device;
+use error::{BindError, ImageCreationError};
+use image;
+use MemoryRequirements;
+
+mod features;
+mod resources;
+mod upload;
+
+const ALIGN: u64 = 256;
+
+#[derive(Debug)]
+struct Bound {
+    memory: u64,
+    offset: u64,
+}
+
+struct MockDevice {
+    next: Cell<u64>,
+    live: Cell<usize>,
+    /// Binding fails with `BindError::OutOfBounds` when set.
+    fail_bind: Cell<bool>,
+}
+
+impl MockDevice {
+    fn new() -> Self {
+        MockDevice {
+            next: Cell::new(0),
+            live: Cell::new(0),
+            fail_bind: Cell::new(false),
+        }
+    }
+
+    fn bind(&self, memory: &u64, offset: u64) -> Result<Bound, BindError> {
+        if self.fail_bind.get() {
+            return Err(BindError::OutOfBounds);
+        }
+        self.live.set(self.live.get() + 1);
+        Ok(Bound {
+            memory: *memory,
+            offset,
+        })
+    }
+
+    fn destroy(&self) {
+        self.live.set(self.live.get() - 1);
+    }
+}
+
+impl memory::Device for MockDevice {
+    type Memory = u64;
+
+    unsafe fn allocate(&self, _index: u32, _size: u64) -> Result<u64, AllocationError> {
+        let id = self.next.get();
+        self.next.set(id + 1);
+        Ok(id)
+    }
+
+    unsafe fn free(&self, _memory: u64) {}
+
+    unsafe fn map(&self, _memory: &u64, _range: Range<u64>) -> Result<NonNull<u8>, MappingError> {
+        unimplemented!()
+    }
+
+    unsafe fn unmap(&self, _memory: &u64) {
+        unimplemented!()
+    }
+
+    unsafe fn invalidate<'a>(
+        &self,
+        _regions: impl IntoIterator<Item = (&'a u64, Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        unimplemented!()
+    }
+
+    unsafe fn flush<'a>(
+        &self,
+        _regions: impl IntoIterator<Item = (&'a u64, Range<u64>)>,
+    ) -> Result<(), OutOfMemoryError> {
+        unimplemented!()
+    }
+}
+
+impl Device for MockDevice {
+    type Sampler = ();
+    type Buffer = Bound;
+    type UnboundBuffer = buffer::CreateInfo;
+    type BufferView = ();
+    type Image = Bound;
+    type UnboundImage = image::CreateInfo;
+    type ImageView = ();
+
+    fn create_buffer(
+        &self,
+        info: buffer::CreateInfo,
+    ) -> Result<buffer::CreateInfo, OutOfMemoryError> {
+        Ok(info)
+    }
+
+    fn buffer_requirements(&self, buffer: &buffer::CreateInfo) -> MemoryRequirements {
+        MemoryRequirements {
+            size: buffer.size,
+            align: ALIGN,
+            mask: 1,
+        }
+    }
+
+    unsafe fn bind_buffer(
+        &self,
+        _buffer: buffer::CreateInfo,
+        memory: &u64,
+        offset: u64,
+    ) -> Result<Bound, BindError> {
+        self.bind(memory, offset)
+    }
+
+    unsafe fn destroy_buffer(&self, _buffer: Bound) {
+        self.destroy()
+    }
+
+    fn create_image(
+        &self,
+        info: image::CreateInfo,
+    ) -> Result<image::CreateInfo, ImageCreationError> {
+        Ok(info)
+    }
+
+    fn image_requirements(&self, image: &image::CreateInfo) -> MemoryRequirements {
+        MemoryRequirements {
+            size: (image.extent.width * image.extent.height * image.extent.depth) as u64,
+            align: ALIGN,
+            mask: 1,
+        }
+    }
+
+    unsafe fn bind_image(
+        &self,
+        _image: image::CreateInfo,
+        memory: &u64,
+        offset: u64,
+    ) -> Result<Bound, BindError> {
+        self.bind(memory, offset)
+    }
+
+    unsafe fn destroy_image(&self, _image: Bound) {
+        self.destroy()
+    }
+}
+
+fn init() -> Heaps<u64> {
+    init_with_granularity(256)
+}
+
+/// Dynamic blocks of sizes that are multiples of `granularity` smaller than `ALIGN`
+/// may be placed at offsets that don't satisfy resource alignment.
+fn init_with_granularity(granularity: u64) -> Heaps<u64> {
+    unsafe {
+        Heaps::new(
+            vec![(
+                Properties::DEVICE_LOCAL,
+                0,
+                Config {
+                    arena: None,
+                    dynamic: Some(DynamicConfig {
+                        blocks_per_chunk: 64,
+                        block_size_granularity: granularity,
+                        max_block_size: 32 * 1024,
+                        fit: FitStrategy::FirstFit,
+                    }),
+                },
+            )],
+            vec![16 * 1024 * 1024],
+        )
+    }
+}
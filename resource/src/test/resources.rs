@@ -0,0 +1,240 @@
+use memory::{usage::Data, Block};
+
+use bind::{bind_buffer, bind_image};
+use buffer::{self, Buffer};
+use device::Device;
+use error::{BindError, ResourceError};
+use image::{self, Image};
+use resources::Resources;
+use SharingMode;
+
+use super::{init, init_with_granularity, Bound, MockDevice, ALIGN};
+
+type MockResources = Resources<u64, Bound, Bound>;
+
+fn buffer_info(size: u64) -> buffer::CreateInfo {
+    buffer::CreateInfo {
+        size,
+        usage: buffer::UsageFlags::STORAGE_BUFFER,
+        sharing: SharingMode::Exclusive,
+    }
+}
+
+fn image_info(width: u32, height: u32) -> image::CreateInfo {
+    image::CreateInfo {
+        kind: image::Kind::D2,
+        format: image::Format::R8_UNORM,
+        extent: image::Extent3D {
+            width,
+            height,
+            depth: 1,
+        },
+        mips: 1,
+        array: 1,
+        samples: image::SampleCountFlags::SAMPLE_COUNT_1,
+        tiling: image::ImageTiling::Optimal,
+        usage: image::UsageFlags::SAMPLED,
+        sharing: SharingMode::Exclusive,
+        flags: image::ImageCreateFlags::empty(),
+    }
+}
+
+fn check_bound(raw: &Bound, block: &impl Block<Memory = u64>) {
+    assert_eq!(raw.memory, *block.memory());
+    assert_eq!(raw.offset, block.range().start);
+    assert_eq!(raw.offset % ALIGN, 0);
+}
+
+#[test]
+fn create_and_destroy() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+    let mut resources = Resources::new();
+
+    let buffers: Vec<Buffer<u64, Bound>> = (1..4)
+        .map(|i| {
+            resources
+                .create_buffer(device, &mut heaps, buffer_info(100 * i), 1, Data)
+                .unwrap()
+        })
+        .collect();
+
+    let image: Image<u64, Bound> = resources
+        .create_image(device, &mut heaps, image_info(30, 30), 1, Data)
+        .unwrap();
+
+    for buffer in &buffers {
+        assert_eq!(buffer.info().usage, buffer::UsageFlags::STORAGE_BUFFER);
+        let range = buffer.block().range();
+        assert!(range.end - range.start >= buffer.info().size);
+        check_bound(buffer.raw(), buffer.block());
+    }
+
+    assert_eq!(image.info().extent.width, 30);
+    check_bound(image.raw(), image.block());
+    assert_eq!(device.live.get(), 4);
+
+    unsafe {
+        for buffer in buffers {
+            MockResources::destroy_buffer(buffer, device, &mut heaps);
+        }
+        MockResources::destroy_image(image, device, &mut heaps);
+    }
+    assert_eq!(device.live.get(), 0);
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn dropped_resources_are_recycled() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+    let mut resources = Resources::new();
+
+    let buffer = resources
+        .create_buffer(device, &mut heaps, buffer_info(1000), 1, Data)
+        .unwrap();
+    let image = resources
+        .create_image(device, &mut heaps, image_info(16, 16), 1, Data)
+        .unwrap();
+    assert_eq!(device.live.get(), 2);
+
+    drop(buffer);
+    drop(image);
+    assert_eq!(device.live.get(), 2);
+
+    unsafe {
+        resources.cleanup(device, &mut heaps);
+    }
+    assert_eq!(device.live.get(), 0);
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn bind_failure_frees_block() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+    let mut resources: MockResources = Resources::new();
+    device.fail_bind.set(true);
+
+    match resources.create_buffer(device, &mut heaps, buffer_info(1000), 1, Data) {
+        Err(ResourceError::BindError(BindError::OutOfBounds)) => {}
+        other => panic!("Unexpected result: {:?}", other.map(|_| ())),
+    }
+    match resources.create_image(device, &mut heaps, image_info(16, 16), 1, Data) {
+        Err(ResourceError::BindError(BindError::OutOfBounds)) => {}
+        other => panic!("Unexpected result: {:?}", other.map(|_| ())),
+    }
+
+    assert_eq!(heaps.stats().types[0].used, 0);
+    assert_eq!(device.live.get(), 0);
+
+    heaps.dispose(device);
+}
+
+#[test]
+fn bind_to_sub_allocated_block() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+
+    let first = heaps.allocate(device, 1, Data, 1000, ALIGN).unwrap();
+    let second = heaps.allocate(device, 1, Data, 1000, ALIGN).unwrap();
+    assert_eq!(first.memory(), second.memory());
+    assert_ne!(second.offset(), 0);
+    assert_eq!(second.offset(), second.range().start);
+
+    unsafe {
+        let buffer = bind_buffer(device, buffer_info(1000), &first).unwrap();
+        check_bound(&buffer, &first);
+        device.destroy_buffer(buffer);
+
+        let image = bind_image(device, image_info(10, 100), &second).unwrap();
+        assert_eq!(image.memory, *second.memory());
+        assert_eq!(image.offset, second.offset());
+        device.destroy_image(image);
+    }
+
+    heaps.free(device, first);
+    heaps.free(device, second);
+    heaps.dispose(device);
+}
+
+#[test]
+fn rebind_buffer_to_relocated_block() {
+    let mut heaps = init();
+    let ref device = MockDevice::new();
+    let mut resources = Resources::new();
+
+    let mut buffer: Buffer<u64, Bound> = resources
+        .create_buffer(device, &mut heaps, buffer_info(300), 1, Data)
+        .unwrap();
+    let old_offset = buffer.block().range().start;
+
+    let block = heaps.allocate(device, 1, Data, 300, ALIGN).unwrap();
+    let new_offset = block.range().start;
+    assert_ne!(old_offset, new_offset);
+
+    unsafe {
+        resources
+            .rebind_buffer(device, &mut heaps, &mut buffer, block)
+            .unwrap();
+    }
+    assert_eq!(*buffer.info(), buffer_info(300));
+    assert_eq!(buffer.block().range().start, new_offset);
+    check_bound(buffer.raw(), buffer.block());
+
+    // Old buffer may be in use until frame is complete.
+    assert_eq!(device.live.get(), 2);
+    unsafe {
+        resources.cleanup(device, &mut heaps);
+    }
+    assert_eq!(device.live.get(), 1);
+
+    unsafe {
+        MockResources::destroy_buffer(buffer, device, &mut heaps);
+    }
+    assert_eq!(device.live.get(), 0);
+}
+
+#[test]
+fn rebind_buffer_to_unfit_block_fails() {
+    let mut heaps = init_with_granularity(64);
+    let ref device = MockDevice::new();
+    let mut resources: MockResources = Resources::new();
+
+    let mut buffer: Buffer<u64, Bound> = resources
+        .create_buffer(device, &mut heaps, buffer_info(300), 1, Data)
+        .unwrap();
+    let old_offset = buffer.block().range().start;
+    assert_eq!(buffer.requirements().size, 300);
+
+    let small = heaps.allocate(device, 1, Data, 100, ALIGN).unwrap();
+    match unsafe { resources.rebind_buffer(device, &mut heaps, &mut buffer, small) } {
+        Err(ResourceError::BindError(BindError::OutOfBounds)) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    // Second block of 320 bytes in the chunk starts at offset not aligned to `ALIGN`.
+    let first = heaps.allocate(device, 1, Data, 320, 1).unwrap();
+    let unaligned = heaps.allocate(device, 1, Data, 320, 1).unwrap();
+    let offset = unaligned.range().start;
+    assert_ne!(offset % ALIGN, 0);
+    match unsafe { resources.rebind_buffer(device, &mut heaps, &mut buffer, unaligned) } {
+        Err(ResourceError::BindError(BindError::Unaligned { offset: o, align })) => {
+            assert_eq!((o, align), (offset, ALIGN));
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    // No new buffer was created and the old one is still bound.
+    assert_eq!(device.live.get(), 1);
+    assert_eq!(buffer.block().range().start, old_offset);
+    check_bound(buffer.raw(), buffer.block());
+
+    heaps.free(device, first);
+    unsafe {
+        MockResources::destroy_buffer(buffer, device, &mut heaps);
+    }
+    heaps.dispose(device);
+}
@@ -0,0 +1,90 @@
+use image::{self, stage_texture, BufferImageCopy, Extent3D, StagingAlignment};
+use SharingMode;
+
+fn bc1_info() -> image::CreateInfo {
+    image::CreateInfo {
+        kind: image::Kind::D2,
+        format: image::Format::BC1_RGBA_UNORM_BLOCK,
+        extent: Extent3D {
+            width: 10,
+            height: 6,
+            depth: 1,
+        },
+        mips: 4,
+        array: 1,
+        samples: image::SampleCountFlags::SAMPLE_COUNT_1,
+        tiling: image::ImageTiling::Optimal,
+        usage: image::UsageFlags::SAMPLED | image::UsageFlags::TRANSFER_DST,
+        sharing: SharingMode::Exclusive,
+        flags: image::ImageCreateFlags::empty(),
+    }
+}
+
+fn region(
+    offset: u64,
+    row_length: u32,
+    image_height: u32,
+    level: u32,
+    width: u32,
+    height: u32,
+) -> BufferImageCopy {
+    BufferImageCopy {
+        buffer_offset: offset,
+        buffer_row_length: row_length,
+        buffer_image_height: image_height,
+        aspects: image::ImageAspectFlags::COLOR,
+        mip_level: level,
+        layers: 0..1,
+        image_extent: Extent3D {
+            width,
+            height,
+            depth: 1,
+        },
+    }
+}
+
+#[test]
+fn bc_compressed_mip_regions() {
+    // 10x6 -> 3x2 blocks, 5x3 -> 2x1 blocks, 2x1 -> 1x1 block, 1x1 -> 1x1 block.
+    let mip0: Vec<u8> = (0..48).collect();
+    let mip1 = vec![0xA1; 16];
+    let mip2 = vec![0xA2; 8];
+    let mip3 = vec![0xA3; 8];
+
+    let staged = stage_texture(
+        &bc1_info(),
+        &[&mip0[..], &mip1[..], &mip2[..], &mip3[..]],
+        StagingAlignment {
+            offset: 16,
+            row_pitch: 16,
+        },
+    );
+
+    assert_eq!(
+        staged.regions,
+        vec![
+            region(0, 16, 8, 0, 10, 6),
+            region(64, 8, 4, 1, 5, 3),
+            region(80, 8, 4, 2, 2, 1),
+            region(96, 8, 4, 3, 1, 1),
+        ]
+    );
+    assert_eq!(staged.data.len(), 112);
+
+    // Rows of the first mip are padded to the row pitch.
+    assert_eq!(&staged.data[0..24], &mip0[0..24]);
+    assert_eq!(&staged.data[24..32], &[0; 8]);
+    assert_eq!(&staged.data[32..56], &mip0[24..48]);
+    assert_eq!(&staged.data[64..80], &mip1[..]);
+    assert_eq!(&staged.data[80..88], &mip2[..]);
+    assert_eq!(&staged.data[96..104], &mip3[..]);
+}
+
+#[test]
+#[should_panic(expected = "doesn't match its extent")]
+fn mip_size_mismatch() {
+    let mip = vec![0; 48];
+    let mut info = bc1_info();
+    info.mips = 2;
+    stage_texture(&info, &[&mip[..], &mip[..]], StagingAlignment::default());
+}